@@ -130,6 +130,10 @@ impl Default for GridBoundary {
 pub struct SpatialGrid {
     pub cells: std::collections::HashMap<(i32, i32), Entity>,
     pub chunk_size: i32,
+    // Positions inserted or removed since the last take_dirty_neighborhoods
+    changed: std::collections::HashSet<(i32, i32)>,
+    // Positions whose state may change next generation, not yet stepped
+    dirty: std::collections::HashSet<(i32, i32)>,
 }
 
 impl SpatialGrid {
@@ -137,6 +141,8 @@ impl SpatialGrid {
         Self {
             cells: std::collections::HashMap::new(),
             chunk_size: 32, // Chunk size for spatial partitioning
+            changed: std::collections::HashSet::new(),
+            dirty: std::collections::HashSet::new(),
         }
     }
     
@@ -144,15 +150,70 @@ impl SpatialGrid {
         Self {
             cells: std::collections::HashMap::new(),
             chunk_size,
+            changed: std::collections::HashSet::new(),
+            dirty: std::collections::HashSet::new(),
         }
     }
     
     pub fn insert(&mut self, position: (i32, i32), entity: Entity) {
-        self.cells.insert(position, entity);
+        if self.cells.insert(position, entity).is_none() {
+            self.changed.insert(position);
+        }
     }
     
     pub fn remove(&mut self, position: &(i32, i32)) -> Option<Entity> {
-        self.cells.remove(position)
+        let entity = self.cells.remove(position);
+        if entity.is_some() {
+            self.changed.insert(*position);
+        }
+        entity
+    }
+    
+    // Count live neighbors by looking up the 8 surrounding positions
+    pub fn count_live_neighbors(&self, position: (i32, i32), boundary: &GridBoundary) -> u8 {
+        GridPosition::get_neighbor_offsets()
+            .iter()
+            .map(|&(dx, dy)| boundary.wrap_position(&GridPosition::new(position.0 + dx, position.1 + dy)))
+            .filter(|neighbor| self.contains(&neighbor.to_tuple()))
+            .count() as u8
+    }
+    
+    // Positions whose neighbor count may have changed since the last call:
+    // every inserted or removed position and its neighbors
+    pub fn take_dirty_neighborhoods(&mut self, boundary: &GridBoundary) -> std::collections::HashSet<(i32, i32)> {
+        let mut dirty = std::collections::HashSet::new();
+        for position in self.changed.drain() {
+            dirty.insert(position);
+            for &(dx, dy) in GridPosition::get_neighbor_offsets() {
+                let neighbor = boundary.wrap_position(&GridPosition::new(position.0 + dx, position.1 + dy));
+                dirty.insert(neighbor.to_tuple());
+            }
+        }
+        dirty
+    }
+    
+    // Add the neighborhoods of positions changed since the last call to the
+    // positions the next generation has to step
+    pub fn mark_dirty_neighborhoods(&mut self, boundary: &GridBoundary) {
+        let dirty = self.take_dirty_neighborhoods(boundary);
+        self.dirty.extend(dirty);
+    }
+    
+    // Treat every live cell as changed, for when something other than the
+    // cells, such as the rule, decides their fate
+    pub fn mark_all_changed(&mut self) {
+        self.changed.extend(self.cells.keys().copied());
+    }
+    
+    pub fn dirty_positions(&self) -> impl Iterator<Item = &(i32, i32)> {
+        self.dirty.iter()
+    }
+    
+    // Take the positions the next generation has to step. A cell whose
+    // neighborhood, itself included, did not change last generation keeps
+    // its state, so these are the only ones that can be born or die.
+    pub fn take_dirty_positions(&mut self) -> std::collections::HashSet<(i32, i32)> {
+        std::mem::take(&mut self.dirty)
     }
     
     pub fn get(&self, position: &(i32, i32)) -> Option<Entity> {
@@ -209,6 +270,7 @@ impl SpatialGrid {
     }
     
     pub fn clear(&mut self) {
+        self.changed.extend(self.cells.keys().copied());
         self.cells.clear();
     }
     
@@ -1,4 +1,4 @@
-use crate::components::{CellState, GridPosition};
+use crate::components::{CellState, GridPosition, NeighborCount, SpatialGrid};
 use crate::resources::{GameConfig, GridState, SimulationState, SimulationTimer};
 use crate::plugins::{GameOfLifePlugin, ConfigPlugin};
 use bevy::prelude::*;
@@ -100,8 +100,8 @@ impl SimulationController {
             timer.force_update();
         }
         
-        // Force one simulation step
-        self.update_once();
+        // Run one fixed step now rather than waiting for the clock
+        self.app.world.run_schedule(FixedUpdate);
         
         if !was_running {
             self.pause();
@@ -115,11 +115,6 @@ impl SimulationController {
             sim_state.reset();
         }
         
-        // Reset timer
-        if let Some(mut timer) = self.app.world.get_resource_mut::<SimulationTimer>() {
-            timer.reset();
-        }
-        
         // Clear grid and regenerate initial pattern
         if let Some(mut grid_state) = self.app.world.get_resource_mut::<GridState>() {
             grid_state.clear();
@@ -135,6 +130,9 @@ impl SimulationController {
         for entity in entities_to_remove {
             self.app.world.despawn(entity);
         }
+        if let Some(mut spatial_grid) = self.app.world.get_resource_mut::<SpatialGrid>() {
+            spatial_grid.clear();
+        }
         
         // Run initialization systems to create initial pattern
         self.app.update();
@@ -177,6 +175,9 @@ impl SimulationController {
         for entity in entities_to_remove {
             self.app.world.despawn(entity);
         }
+        if let Some(mut spatial_grid) = self.app.world.get_resource_mut::<SpatialGrid>() {
+            spatial_grid.clear();
+        }
         
         // Clear grid state
         if let Some(mut grid_state) = self.app.world.get_resource_mut::<GridState>() {
@@ -185,14 +186,18 @@ impl SimulationController {
         
         // Add new cells
         for &(x, y) in cells {
-            self.app.world.spawn((
+            let entity = self.app.world.spawn((
                 CellState::new(true),
                 GridPosition::new(x, y),
-            ));
+                NeighborCount::new(),
+            )).id();
             
             if let Some(mut grid_state) = self.app.world.get_resource_mut::<GridState>() {
                 grid_state.add_cell((x, y));
             }
+            if let Some(mut spatial_grid) = self.app.world.get_resource_mut::<SpatialGrid>() {
+                spatial_grid.insert((x, y), entity);
+            }
         }
     }
     
@@ -264,9 +269,9 @@ impl SimulationController {
         self.target_fps = fps.max(1.0).min(1000.0); // Clamp between 1-1000 FPS
         self.frame_duration = Duration::from_secs_f64(1.0 / self.target_fps);
         
-        // Update simulation timer
+        // Update simulation step rate
         if let Some(mut timer) = self.app.world.get_resource_mut::<SimulationTimer>() {
-            *timer = SimulationTimer::new(self.target_fps as u32);
+            timer.set_steps_per_second(self.target_fps as u32);
         }
     }
     
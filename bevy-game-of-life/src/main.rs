@@ -4,7 +4,40 @@ use bevy_game_of_life::plugins::{GameOfLifePlugin, ConfigPlugin, DebugPlugin};
 fn main() {
     let mut app = App::new();
     
-    // Add minimal Bevy plugins for headless operation
+    // With the capture feature, `--capture <dir>` renders off-screen to PNG
+    // frames instead of opening a window
+    #[cfg(feature = "capture")]
+    let capture = capture_config_from_args();
+    #[cfg(all(feature = "gui", not(feature = "capture")))]
+    let capture: Option<()> = None;
+    
+    // Open a window with the gui feature, otherwise run headless on the
+    // minimal Bevy plugins
+    #[cfg(feature = "capture")]
+    if let Some(config) = capture.clone() {
+        use bevy::app::ScheduleRunnerPlugin;
+        use bevy::window::ExitCondition;
+        use bevy::winit::WinitPlugin;
+        app.add_plugins((
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    close_when_requested: false,
+                })
+                .disable::<WinitPlugin>(),
+            ScheduleRunnerPlugin::run_loop(std::time::Duration::from_millis(1)),
+            bevy_game_of_life::plugins::RenderPlugin,
+            bevy_game_of_life::plugins::FrameCapturePlugin { config },
+        ));
+    }
+    #[cfg(feature = "gui")]
+    if capture.is_none() {
+        app.add_plugins((DefaultPlugins, bevy_game_of_life::plugins::RenderPlugin));
+        #[cfg(feature = "inspector")]
+        app.add_plugins(bevy_game_of_life::plugins::InspectorPlugin);
+    }
+    #[cfg(not(feature = "gui"))]
     app.add_plugins(MinimalPlugins);
     
     // Add our custom plugins
@@ -21,8 +54,31 @@ fn main() {
     app.run();
 }
 
+/// `--capture <dir> [--size <width>x<height>] [--frames <count>]`
+#[cfg(feature = "capture")]
+fn capture_config_from_args() -> Option<bevy_game_of_life::plugins::FrameCaptureConfig> {
+    let args: Vec<String> = std::env::args().collect();
+    let value = |flag: &str| args.iter().position(|arg| arg == flag).and_then(|i| args.get(i + 1));
+    
+    let mut config = bevy_game_of_life::plugins::FrameCaptureConfig {
+        output_dir: value("--capture")?.into(),
+        ..Default::default()
+    };
+    if let Some((width, height)) = value("--size").and_then(|size| size.split_once('x')) {
+        if let (Ok(width), Ok(height)) = (width.parse(), height.parse()) {
+            config.width = width;
+            config.height = height;
+        }
+    }
+    config.max_frames = value("--frames").and_then(|frames| frames.parse().ok());
+    Some(config)
+}
+
 fn startup_system() {
     info!("Bevy Game of Life initialized successfully!");
+    #[cfg(feature = "gui")]
+    info!("Right-drag or arrow keys pan, the wheel zooms, left click toggles a cell");
+    #[cfg(not(feature = "gui"))]
     info!("Note: Running in headless mode");
     info!("Console controls (when implemented):");
     info!("  Q + ENTER - Quit game");
@@ -0,0 +1,285 @@
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::extract_resource::{ExtractResource, ExtractResourcePlugin};
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext};
+use bevy::render::render_resource::{
+    Buffer, BufferDescriptor, BufferUsages, Extent3d, ImageCopyBuffer, ImageDataLayout, Maintain, MapMode,
+    TextureDimension, TextureFormat, TextureUsages,
+};
+use bevy::render::renderer::{RenderContext, RenderDevice};
+use bevy::render::{Render, RenderApp, RenderSet};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+use crate::plugins::render::GridCamera;
+use crate::resources::{GameConfig, SimulationState};
+
+/// Render graph node copying the capture texture into a readable buffer
+const CAPTURE_NODE: &str = "frame_capture";
+
+/// Rows of a texture copied into a buffer must start on this many bytes
+const COPY_BYTES_PER_ROW_ALIGNMENT: u32 = 256;
+
+/// Where and how large captured frames are written
+#[derive(Resource, Debug, Clone)]
+pub struct FrameCaptureConfig {
+    pub output_dir: PathBuf,
+    pub width: u32,
+    pub height: u32,
+    /// Stop the app after this many frames
+    pub max_frames: Option<u64>,
+}
+
+impl Default for FrameCaptureConfig {
+    fn default() -> Self {
+        Self {
+            output_dir: PathBuf::from("frames"),
+            width: 1280,
+            height: 720,
+            max_frames: None,
+        }
+    }
+}
+
+/// Off-screen rendering of every generation to a numbered PNG (requires the
+/// `capture` feature)
+///
+/// The grid camera of `RenderPlugin` is pointed at an image instead of a
+/// window and zoomed to fit the whole grid; after each frame the image is
+/// copied back from the GPU and written as `frame_<generation>.png` whenever
+/// the generation has moved on. With the window plugin disabled this runs
+/// on a server without a display, and the frames can be turned into a video
+/// with e.g. `ffmpeg -i frame_%06d.png`.
+///
+/// Add it after `RenderPlugin`; it starts the simulation.
+pub struct FrameCapturePlugin {
+    pub config: FrameCaptureConfig,
+}
+
+impl Plugin for FrameCapturePlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = mpsc::channel();
+        app.insert_resource(self.config.clone())
+            .insert_resource(CapturedFrames(Mutex::new(receiver)))
+            .add_plugins(ExtractResourcePlugin::<CaptureTarget>::default())
+            .add_systems(Startup, setup_capture_target_system)
+            .add_systems(PostStartup, point_camera_at_capture_system)
+            .add_systems(Update, (update_capture_generation_system, write_frames_system).chain());
+        
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            warn!("Frame capture needs the render plugins; no frames will be written");
+            return;
+        };
+        render_app.insert_resource(FrameSender(Mutex::new(sender)))
+            .add_systems(Render, (
+                prepare_capture_buffer_system.in_set(RenderSet::PrepareResources),
+                read_capture_buffer_system.after(RenderSet::Render).before(RenderSet::Cleanup),
+            ));
+        let mut graph = render_app.world.resource_mut::<RenderGraph>();
+        graph.add_node(CAPTURE_NODE, CaptureNode);
+        graph.add_node_edge(bevy::render::main_graph::node::CAMERA_DRIVER, CAPTURE_NODE);
+    }
+}
+
+/// Image the grid camera renders into, and the generation it shows
+#[derive(Resource, Debug, Clone, ExtractResource)]
+struct CaptureTarget {
+    image: Handle<Image>,
+    width: u32,
+    height: u32,
+    generation: u64,
+}
+
+/// Render world buffer the capture image is copied into each frame
+#[derive(Resource)]
+struct CaptureBuffer {
+    buffer: Buffer,
+    padded_bytes_per_row: u32,
+}
+
+/// RGBA pixels of one rendered frame
+struct CapturedFrame {
+    generation: u64,
+    pixels: Vec<u8>,
+}
+
+#[derive(Resource)]
+struct FrameSender(Mutex<Sender<CapturedFrame>>);
+
+#[derive(Resource)]
+struct CapturedFrames(Mutex<Receiver<CapturedFrame>>);
+
+fn padded_bytes_per_row(width: u32) -> u32 {
+    (width * 4).div_ceil(COPY_BYTES_PER_ROW_ALIGNMENT) * COPY_BYTES_PER_ROW_ALIGNMENT
+}
+
+// System to create the image frames are rendered into
+fn setup_capture_target_system(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut simulation_state: ResMut<SimulationState>,
+    capture_config: Res<FrameCaptureConfig>,
+) {
+    let size = Extent3d {
+        width: capture_config.width,
+        height: capture_config.height,
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image::new_fill(size, TextureDimension::D2, &[0, 0, 0, 255], TextureFormat::Rgba8UnormSrgb);
+    image.texture_descriptor.usage = TextureUsages::COPY_SRC | TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING;
+    
+    if let Err(e) = std::fs::create_dir_all(&capture_config.output_dir) {
+        error!("Failed to create {}: {}", capture_config.output_dir.display(), e);
+    }
+    commands.insert_resource(CaptureTarget {
+        image: images.add(image),
+        width: capture_config.width,
+        height: capture_config.height,
+        generation: simulation_state.get_generation(),
+    });
+    simulation_state.start();
+}
+
+// System to render the grid camera into the capture image, zoomed to fit
+fn point_camera_at_capture_system(
+    mut camera_query: Query<(&mut Camera, &mut OrthographicProjection), With<GridCamera>>,
+    target: Res<CaptureTarget>,
+    config: Res<GameConfig>,
+) {
+    let cell_size = config.rendering.cell_size;
+    let fit = (config.grid.width as f32 * cell_size / target.width as f32)
+        .max(config.grid.height as f32 * cell_size / target.height as f32);
+    for (mut camera, mut projection) in camera_query.iter_mut() {
+        camera.target = RenderTarget::Image(target.image.clone());
+        projection.scale = fit;
+    }
+}
+
+// System to tag the next extracted frame with the generation it shows
+fn update_capture_generation_system(
+    simulation_state: Res<SimulationState>,
+    mut target: ResMut<CaptureTarget>,
+) {
+    if target.generation != simulation_state.get_generation() {
+        target.generation = simulation_state.get_generation();
+    }
+}
+
+// System to write each new generation's frame as a PNG
+fn write_frames_system(
+    frames: Res<CapturedFrames>,
+    target: Res<CaptureTarget>,
+    capture_config: Res<FrameCaptureConfig>,
+    mut last_written: Local<Option<u64>>,
+    mut written: Local<u64>,
+    mut exit: EventWriter<bevy::app::AppExit>,
+) {
+    for frame in frames.0.lock().unwrap().try_iter() {
+        if last_written.is_some_and(|generation| generation >= frame.generation) {
+            continue;
+        }
+        let path = capture_config.output_dir.join(format!("frame_{:06}.png", frame.generation));
+        if let Err(e) = image::save_buffer(&path, &frame.pixels, target.width, target.height, image::ColorType::Rgba8) {
+            error!("Failed to write {}: {}", path.display(), e);
+            continue;
+        }
+        *last_written = Some(frame.generation);
+        *written += 1;
+        
+        if capture_config.max_frames.is_some_and(|max| *written >= max) {
+            info!("Wrote {} frames to {}", *written, capture_config.output_dir.display());
+            exit.send(bevy::app::AppExit);
+            return;
+        }
+    }
+}
+
+// Render world system to create the readback buffer once the target exists
+fn prepare_capture_buffer_system(
+    mut commands: Commands,
+    target: Option<Res<CaptureTarget>>,
+    capture: Option<Res<CaptureBuffer>>,
+    render_device: Res<RenderDevice>,
+) {
+    let (Some(target), None) = (target, capture) else {
+        return;
+    };
+    let padded_bytes_per_row = padded_bytes_per_row(target.width);
+    let buffer = render_device.create_buffer(&BufferDescriptor {
+        label: Some("frame_capture_buffer"),
+        size: padded_bytes_per_row as u64 * target.height as u64,
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    commands.insert_resource(CaptureBuffer { buffer, padded_bytes_per_row });
+}
+
+// Render world system to read the copied frame back and send it to the app
+fn read_capture_buffer_system(
+    target: Option<Res<CaptureTarget>>,
+    capture: Option<Res<CaptureBuffer>>,
+    render_device: Res<RenderDevice>,
+    sender: Res<FrameSender>,
+) {
+    let (Some(target), Some(capture)) = (target, capture) else {
+        return;
+    };
+    let slice = capture.buffer.slice(..);
+    let (mapped_sender, mapped) = mpsc::channel();
+    render_device.map_buffer(&slice, MapMode::Read, move |result| {
+        let _ = mapped_sender.send(result);
+    });
+    render_device.poll(Maintain::Wait);
+    if !matches!(mapped.recv(), Ok(Ok(()))) {
+        return;
+    }
+    
+    // Drop the row padding the copy needed
+    let row_bytes = target.width as usize * 4;
+    let pixels: Vec<u8> = slice.get_mapped_range()
+        .chunks(capture.padded_bytes_per_row as usize)
+        .flat_map(|row| &row[..row_bytes])
+        .copied()
+        .collect();
+    capture.buffer.unmap();
+    
+    let _ = sender.0.lock().unwrap().send(CapturedFrame { generation: target.generation, pixels });
+}
+
+/// Copies the capture image into the readback buffer after the cameras ran
+struct CaptureNode;
+
+impl Node for CaptureNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let (Some(target), Some(capture)) = (world.get_resource::<CaptureTarget>(), world.get_resource::<CaptureBuffer>()) else {
+            return Ok(());
+        };
+        let Some(gpu_image) = world.resource::<RenderAssets<Image>>().get(&target.image) else {
+            return Ok(());
+        };
+        
+        render_context.command_encoder().copy_texture_to_buffer(
+            gpu_image.texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer: &capture.buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(capture.padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            Extent3d {
+                width: target.width,
+                height: target.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        Ok(())
+    }
+}
\ No newline at end of file
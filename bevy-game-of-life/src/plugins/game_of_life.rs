@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 use crate::components::SpatialGrid;
-use crate::resources::{GameConfig, GridState, SimulationState, SimulationTimer};
+use crate::resources::{GameConfig, GridState, LifeRule, SimulationState, SimulationTimer};
 use crate::systems::{add_game_of_life_systems};
 
 /// Main plugin for the Game of Life implementation
@@ -12,11 +12,14 @@ impl Plugin for GameOfLifePlugin {
         app.insert_resource(GameConfig::default())
             .insert_resource(GridState::new())
             .insert_resource(SpatialGrid::new())
-            .insert_resource(SimulationState::new());
+            .insert_resource(SimulationState::new())
+            .insert_resource(LifeRule::conway());
         
-        // Initialize simulation timer from config
+        // Initialize simulation timer from config; the FixedUpdate clock
+        // steps the simulation at its rate
         let config = app.world.get_resource::<GameConfig>().unwrap();
         let timer = SimulationTimer::from_config(config);
+        app.insert_resource(Time::<Fixed>::from_duration(timer.step_duration()));
         app.insert_resource(timer);
         
         // Add all Game of Life systems
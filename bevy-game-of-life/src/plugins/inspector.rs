@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
+use crate::components::{CellState, NeighborCount, SpatialGrid};
+use crate::plugins::render::{cursor_cell, GridCamera, PointerOverUi};
+use crate::resources::{GameConfig, GridState, LifeRule, SimulationState, SimulationTimer};
+use crate::systems::{GameOfLifeSystemSet, InputEvent};
+
+/// egui developer overlay for the windowed renderer (requires the
+/// `inspector` feature)
+///
+/// Shows simulation controls, live statistics, the rule in use and the cell
+/// under the cursor. The overlay replaces the once-a-second statistics log,
+/// which stops while it is installed.
+///
+/// Add it after `RenderPlugin`.
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin);
+        }
+        app.configure_sets(Update, GameOfLifeSystemSet::Debug.run_if(|| false))
+            .add_systems(Update, inspector_ui_system);
+    }
+}
+
+// System to draw the inspector window
+#[allow(clippy::too_many_arguments)]
+fn inspector_ui_system(
+    mut contexts: EguiContexts,
+    mut simulation_state: ResMut<SimulationState>,
+    mut timer: ResMut<SimulationTimer>,
+    mut rule: ResMut<LifeRule>,
+    mut pointer_over_ui: ResMut<PointerOverUi>,
+    mut input_events: EventWriter<InputEvent>,
+    grid_state: Res<GridState>,
+    spatial_grid: Res<SpatialGrid>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<GridCamera>>,
+    cell_query: Query<(&CellState, Option<&NeighborCount>)>,
+) {
+    let hovered = match (window_query.get_single(), camera_query.get_single()) {
+        (Ok(window), Ok((camera, camera_transform))) => {
+            cursor_cell(window, camera, camera_transform, config.rendering.cell_size)
+        }
+        _ => None,
+    };
+    
+    let ctx = contexts.ctx_mut();
+    egui::Window::new("Game of Life").default_width(240.0).show(ctx, |ui| {
+        ui.heading("Simulation");
+        ui.horizontal(|ui| {
+            let label = if simulation_state.is_running() { "Pause" } else { "Run" };
+            if ui.button(label).clicked() {
+                simulation_state.toggle();
+            }
+            if ui.button("Step").clicked() {
+                timer.force_update();
+            }
+            if ui.button("Reset").clicked() {
+                input_events.send(InputEvent { key: 'r' });
+            }
+            if ui.button("Clear").clicked() {
+                input_events.send(InputEvent { key: 'c' });
+            }
+        });
+        
+        let mut steps_per_second = timer.steps_per_second();
+        if ui.add(egui::Slider::new(&mut steps_per_second, 1..=100).text("steps/s")).changed() {
+            timer.set_steps_per_second(steps_per_second);
+        }
+        
+        let mut selected = rule.clone();
+        egui::ComboBox::from_label("Rule")
+            .selected_text(format!("{} ({})", selected.name, selected.notation()))
+            .show_ui(ui, |ui| {
+                for preset in LifeRule::presets() {
+                    let text = format!("{} ({})", preset.name, preset.notation());
+                    ui.selectable_value(&mut selected, preset, text);
+                }
+            });
+        if selected != *rule {
+            *rule = selected;
+        }
+        
+        ui.separator();
+        ui.heading("Statistics");
+        egui::Grid::new("statistics").num_columns(2).show(ui, |ui| {
+            ui.label("Generation");
+            ui.label(simulation_state.get_generation().to_string());
+            ui.end_row();
+            ui.label("Live cells");
+            ui.label(grid_state.cell_count().to_string());
+            ui.end_row();
+            ui.label("Indexed entities");
+            ui.label(spatial_grid.len().to_string());
+            ui.end_row();
+            ui.label("Last step");
+            ui.label(simulation_state.last_step_duration
+                .map_or("-".to_string(), |duration| format!("{:.2} ms", duration.as_secs_f64() * 1000.0)));
+            ui.end_row();
+            ui.label("Frame rate");
+            ui.label(format!("{:.0} fps", 1.0 / time.delta_seconds().max(f32::EPSILON)));
+            ui.end_row();
+        });
+        
+        ui.separator();
+        ui.heading("Cell");
+        match hovered {
+            Some(position) => {
+                ui.label(format!("Position ({}, {})", position.0, position.1));
+                match spatial_grid.get(&position).and_then(|entity| cell_query.get(entity).ok().map(|cell| (entity, cell))) {
+                    Some((entity, (cell_state, neighbor_count))) => {
+                        ui.label(format!("Entity {:?}", entity));
+                        ui.label(format!("Alive: {}, age {}", cell_state.is_alive(), cell_state.age));
+                        if let Some(neighbor_count) = neighbor_count {
+                            ui.label(format!("Neighbors: {}", neighbor_count.count));
+                        }
+                    }
+                    None => {
+                        ui.label("Empty");
+                    }
+                }
+            }
+            None => {
+                ui.label("Hover over the grid to inspect a cell");
+            }
+        }
+    });
+    
+    pointer_over_ui.0 = ctx.is_pointer_over_area() || ctx.wants_pointer_input();
+}
\ No newline at end of file
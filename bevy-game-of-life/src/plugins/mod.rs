@@ -1,4 +1,16 @@
 // Bevy plugins module
 pub mod game_of_life;
+#[cfg(feature = "gui")]
+pub mod render;
+#[cfg(feature = "inspector")]
+pub mod inspector;
+#[cfg(feature = "capture")]
+pub mod capture;
 
-pub use game_of_life::{GameOfLifePlugin, ConfigPlugin, DebugPlugin};
\ No newline at end of file
+pub use game_of_life::{GameOfLifePlugin, ConfigPlugin, DebugPlugin};
+#[cfg(feature = "gui")]
+pub use render::RenderPlugin;
+#[cfg(feature = "inspector")]
+pub use inspector::InspectorPlugin;
+#[cfg(feature = "capture")]
+pub use capture::{FrameCaptureConfig, FrameCapturePlugin};
\ No newline at end of file
@@ -0,0 +1,232 @@
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use crate::components::{CellState, GridBoundary, GridPosition, NeighborCount, SpatialGrid};
+use crate::resources::{GameConfig, GridState};
+use crate::systems::InputEvent;
+
+/// Zoom limits of the camera's orthographic scale
+const MIN_ZOOM: f32 = 0.05;
+const MAX_ZOOM: f32 = 20.0;
+
+/// Screen pixels the camera pans per second with the arrow or WASD keys
+const KEY_PAN_SPEED: f32 = 600.0;
+
+/// Windowed 2D renderer for the Game of Life (requires the `gui` feature)
+///
+/// Every live cell entity gets a sprite as it is spawned, so drawing follows
+/// the same births and deaths as the simulation. The camera pans with the
+/// right mouse button or the arrow/WASD keys and zooms with the mouse wheel;
+/// a left click toggles the cell under the cursor. Space, Enter, +/- and the
+/// other console keys are forwarded as input events.
+///
+/// Add it next to `DefaultPlugins` and `GameOfLifePlugin`.
+pub struct RenderPlugin;
+
+impl Plugin for RenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(Color::BLACK))
+            .init_resource::<PointerOverUi>()
+            .add_systems(Startup, setup_camera_system)
+            .add_systems(Update, (
+                keyboard_input_system,
+                camera_pan_zoom_system,
+                mouse_edit_system,
+                attach_cell_sprites_system,
+            ));
+    }
+}
+
+/// Marker for the camera looking at the grid
+#[derive(Component, Debug)]
+pub struct GridCamera;
+
+/// Set by UI overlays while the pointer is over them, so clicks and drags
+/// meant for the UI neither edit cells nor move the camera
+#[derive(Resource, Debug, Default)]
+pub struct PointerOverUi(pub bool);
+
+/// World position of the center of a cell; rows grow downwards on screen
+pub fn cell_to_world(position: (i32, i32), cell_size: f32) -> Vec2 {
+    Vec2::new(position.0 as f32 * cell_size, -(position.1 as f32) * cell_size)
+}
+
+/// Cell containing a world position
+pub fn world_to_cell(world: Vec2, cell_size: f32) -> (i32, i32) {
+    ((world.x / cell_size).round() as i32, (-world.y / cell_size).round() as i32)
+}
+
+/// Cell under the cursor, if it is inside the window
+pub fn cursor_cell(window: &Window, camera: &Camera, camera_transform: &GlobalTransform, cell_size: f32) -> Option<(i32, i32)> {
+    window.cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor))
+        .map(|world| world_to_cell(world, cell_size))
+}
+
+fn cell_color(color_scheme: &str) -> Color {
+    match color_scheme {
+        "green" => Color::rgb(0.2, 0.9, 0.3),
+        "amber" => Color::rgb(1.0, 0.7, 0.1),
+        _ => Color::WHITE,
+    }
+}
+
+// System to spawn a camera centered on the grid
+fn setup_camera_system(mut commands: Commands, config: Res<GameConfig>) {
+    let cell_size = config.rendering.cell_size;
+    let center = cell_to_world((config.grid.width / 2, config.grid.height / 2), cell_size);
+    commands.spawn((
+        Camera2dBundle {
+            transform: Transform::from_xyz(center.x, center.y, 999.9),
+            ..default()
+        },
+        GridCamera,
+    ));
+}
+
+// System to give newly spawned cells a sprite; despawned cells take theirs
+// with them
+fn attach_cell_sprites_system(
+    mut commands: Commands,
+    new_cells: Query<(Entity, &GridPosition), (With<CellState>, Added<GridPosition>)>,
+    config: Res<GameConfig>,
+) {
+    let cell_size = config.rendering.cell_size;
+    let color = cell_color(&config.rendering.color_scheme);
+    // Leave a gap between cells when grid lines are on
+    let sprite_size = if config.rendering.grid_lines && cell_size > 2.0 { cell_size - 1.0 } else { cell_size };
+    
+    for (entity, position) in new_cells.iter() {
+        let world = cell_to_world(position.to_tuple(), cell_size);
+        commands.entity(entity).insert(SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::splat(sprite_size)),
+                ..default()
+            },
+            transform: Transform::from_xyz(world.x, world.y, 0.0),
+            ..default()
+        });
+    }
+}
+
+// System to pan with the right mouse button or keys and zoom with the wheel
+fn camera_pan_zoom_system(
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<GridCamera>>,
+    mut wheel_events: EventReader<MouseWheel>,
+    mut motion_events: EventReader<MouseMotion>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    pointer_over_ui: Res<PointerOverUi>,
+) {
+    let Ok((mut transform, mut projection)) = camera_query.get_single_mut() else {
+        return;
+    };
+    if pointer_over_ui.0 {
+        wheel_events.clear();
+        motion_events.clear();
+    }
+    
+    for event in wheel_events.read() {
+        let lines = match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / 40.0,
+        };
+        projection.scale = (projection.scale * 0.9f32.powf(lines)).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+    
+    // Drag so the grid follows the cursor
+    let mut pan = Vec2::ZERO;
+    for event in motion_events.read() {
+        if mouse_buttons.pressed(MouseButton::Right) {
+            pan += Vec2::new(-event.delta.x, event.delta.y);
+        }
+    }
+    
+    let mut direction = Vec2::ZERO;
+    if keys.pressed(KeyCode::Left) || keys.pressed(KeyCode::A) {
+        direction.x -= 1.0;
+    }
+    if keys.pressed(KeyCode::Right) || keys.pressed(KeyCode::D) {
+        direction.x += 1.0;
+    }
+    if keys.pressed(KeyCode::Up) || keys.pressed(KeyCode::W) {
+        direction.y += 1.0;
+    }
+    if keys.pressed(KeyCode::Down) || keys.pressed(KeyCode::S) {
+        direction.y -= 1.0;
+    }
+    pan += direction * KEY_PAN_SPEED * time.delta_seconds();
+    
+    // Pans are in screen pixels, so they cover more of the world zoomed out
+    transform.translation += (pan * projection.scale).extend(0.0);
+}
+
+// System to toggle the cell under the cursor on a left click
+fn mouse_edit_system(
+    mut commands: Commands,
+    mouse_buttons: Res<Input<MouseButton>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<GridCamera>>,
+    mut grid_state: ResMut<GridState>,
+    mut spatial_grid: ResMut<SpatialGrid>,
+    config: Res<GameConfig>,
+    pointer_over_ui: Res<PointerOverUi>,
+) {
+    if pointer_over_ui.0 || !mouse_buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let (Ok(window), Ok((camera, camera_transform))) = (window_query.get_single(), camera_query.get_single()) else {
+        return;
+    };
+    let Some(position) = cursor_cell(window, camera, camera_transform, config.rendering.cell_size) else {
+        return;
+    };
+    
+    let boundary = GridBoundary::new(config.grid.width, config.grid.height, config.grid.wrap_edges);
+    if !boundary.is_valid_position(&GridPosition::from_tuple(position)) {
+        return;
+    }
+    
+    // Keep the grid state and the spatial grid in step with the entities, as
+    // the lifecycle systems do
+    if let Some(entity) = spatial_grid.remove(&position) {
+        commands.entity(entity).despawn();
+        grid_state.remove_cell(&position);
+    } else {
+        let entity = commands.spawn((
+            CellState::new(true),
+            GridPosition::from_tuple(position),
+            NeighborCount::new(),
+        )).id();
+        spatial_grid.insert(position, entity);
+        grid_state.add_cell(position);
+    }
+}
+
+// System to forward the console keys from the window as input events
+fn keyboard_input_system(
+    keys: Res<Input<KeyCode>>,
+    mut input_events: EventWriter<InputEvent>,
+) {
+    let bindings = [
+        (KeyCode::Space, ' '),
+        (KeyCode::Return, '\n'),
+        (KeyCode::R, 'r'),
+        (KeyCode::G, 'g'),
+        (KeyCode::B, 'b'),
+        (KeyCode::C, 'c'),
+        (KeyCode::I, 'i'),
+        (KeyCode::Q, 'q'),
+        (KeyCode::Equals, '+'),
+        (KeyCode::NumpadAdd, '+'),
+        (KeyCode::Minus, '-'),
+        (KeyCode::NumpadSubtract, '-'),
+    ];
+    for (key_code, key) in bindings {
+        if keys.just_pressed(key_code) {
+            input_events.send(InputEvent { key });
+        }
+    }
+}
\ No newline at end of file
@@ -161,19 +161,21 @@ impl GameConfig {
     }
 }
 
+/// Step rate of the simulation systems, which run on Bevy's `FixedUpdate`
+/// schedule so generations advance at the same pace whatever the frame rate.
+/// `sync_fixed_timestep_system` applies rate changes to `Time<Fixed>`.
 #[derive(Resource, Debug)]
 pub struct SimulationTimer {
-    timer: Timer,
     steps_per_second: u32,
+    paused: bool,
     force_step: bool,
 }
 
 impl SimulationTimer {
     pub fn new(steps_per_second: u32) -> Self {
-        let duration = std::time::Duration::from_secs_f64(1.0 / steps_per_second as f64);
         Self {
-            timer: Timer::new(duration, TimerMode::Repeating),
-            steps_per_second,
+            steps_per_second: steps_per_second.max(1),
+            paused: false,
             force_step: false,
         }
     }
@@ -182,37 +184,29 @@ impl SimulationTimer {
         Self::new(config.simulation.steps_per_second)
     }
     
-    pub fn tick(&mut self, delta: std::time::Duration) -> bool {
-        let timer_finished = self.timer.tick(delta).just_finished();
-        let should_step = timer_finished || self.force_step;
-        
-        if self.force_step {
-            self.force_step = false; // Reset the flag
-        }
-        
-        should_step
-    }
-    
     pub fn set_steps_per_second(&mut self, steps_per_second: u32) {
-        self.steps_per_second = steps_per_second;
-        let duration = std::time::Duration::from_secs_f64(1.0 / steps_per_second as f64);
-        self.timer.set_duration(duration);
+        self.steps_per_second = steps_per_second.max(1);
     }
     
     pub fn steps_per_second(&self) -> u32 {
         self.steps_per_second
     }
     
+    /// Time between fixed steps at the current rate.
+    pub fn step_duration(&self) -> std::time::Duration {
+        std::time::Duration::from_secs_f64(1.0 / self.steps_per_second as f64)
+    }
+    
     pub fn pause(&mut self) {
-        self.timer.pause();
+        self.paused = true;
     }
     
     pub fn unpause(&mut self) {
-        self.timer.unpause();
+        self.paused = false;
     }
     
     pub fn is_paused(&self) -> bool {
-        self.timer.paused()
+        self.paused
     }
     
     pub fn force_update(&mut self) {
@@ -220,16 +214,13 @@ impl SimulationTimer {
         self.force_step = true;
     }
     
-    pub fn reset(&mut self) {
-        self.timer.reset();
-    }
-    
-    pub fn elapsed(&self) -> std::time::Duration {
-        self.timer.elapsed()
+    pub fn is_step_forced(&self) -> bool {
+        self.force_step
     }
     
-    pub fn remaining(&self) -> std::time::Duration {
-        self.timer.remaining()
+    /// Whether a step was forced since the last call, clearing the flag.
+    pub fn take_forced_step(&mut self) -> bool {
+        std::mem::take(&mut self.force_step)
     }
     
     pub fn speed_up(&mut self) {
@@ -183,6 +183,13 @@ impl GridState {
         }
     }
     
+    // Set the births and deaths of the next generation directly, when they
+    // were worked out from the cells that changed rather than the whole grid
+    pub fn prepare_changes(&mut self, births: HashSet<(i32, i32)>, deaths: HashSet<(i32, i32)>) {
+        self.pending_births = births;
+        self.pending_deaths = deaths;
+    }
+    
     pub fn apply_transition(&mut self) {
         // Apply deaths
         for &pos in &self.pending_deaths {
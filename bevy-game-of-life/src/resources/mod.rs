@@ -1,6 +1,8 @@
 // Bevy resources module
 pub mod config;
 pub mod grid_state;
+pub mod rule;
 
 pub use config::*;
-pub use grid_state::*;
\ No newline at end of file
+pub use grid_state::*;
+pub use rule::*;
\ No newline at end of file
@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Life-like birth/survival rule applied each generation
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LifeRule {
+    pub name: String,
+    /// `birth[n]`: a dead cell with `n` live neighbors is born
+    pub birth: [bool; 9],
+    /// `survival[n]`: a live cell with `n` live neighbors survives
+    pub survival: [bool; 9],
+}
+
+impl LifeRule {
+    pub fn new(name: &str, birth: &[u8], survival: &[u8]) -> Self {
+        let mut rule = Self {
+            name: name.to_string(),
+            birth: [false; 9],
+            survival: [false; 9],
+        };
+        for &n in birth.iter().filter(|&&n| n <= 8) {
+            rule.birth[n as usize] = true;
+        }
+        for &n in survival.iter().filter(|&&n| n <= 8) {
+            rule.survival[n as usize] = true;
+        }
+        rule
+    }
+    
+    /// Conway's Game of Life, B3/S23
+    pub fn conway() -> Self {
+        Self::new("Conway", &[3], &[2, 3])
+    }
+    
+    /// Rules that can be picked by name, Conway's first
+    pub fn presets() -> Vec<Self> {
+        vec![
+            Self::conway(),
+            Self::new("HighLife", &[3, 6], &[2, 3]),
+            Self::new("Seeds", &[2], &[]),
+            Self::new("Day & Night", &[3, 6, 7, 8], &[3, 4, 6, 7, 8]),
+            Self::new("Life without Death", &[3], &[0, 1, 2, 3, 4, 5, 6, 7, 8]),
+        ]
+    }
+    
+    /// Whether a cell is alive next generation
+    pub fn next_state(&self, currently_alive: bool, neighbor_count: u8) -> bool {
+        let n = neighbor_count.min(8) as usize;
+        if currently_alive {
+            self.survival[n]
+        } else {
+            self.birth[n]
+        }
+    }
+    
+    /// Rule in B/S notation, e.g. `B3/S23`
+    pub fn notation(&self) -> String {
+        let digits = |counts: &[bool; 9]| -> String {
+            counts.iter().enumerate().filter(|(_, &on)| on).map(|(n, _)| n.to_string()).collect()
+        };
+        format!("B{}/S{}", digits(&self.birth), digits(&self.survival))
+    }
+}
+
+impl Default for LifeRule {
+    fn default() -> Self {
+        Self::conway()
+    }
+}
\ No newline at end of file
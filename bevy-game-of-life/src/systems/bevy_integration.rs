@@ -1,52 +1,85 @@
 use bevy::prelude::*;
-use crate::components::{CellState, GridPosition, NeighborCount, SpatialGrid};
-use crate::resources::{GameConfig, GridState, SimulationState, SimulationTimer};
+use crate::components::{CellState, GridBoundary, GridPosition, NeighborCount, SpatialGrid};
+use crate::resources::{GameConfig, GridState, LifeRule, SimulationState, SimulationTimer};
 use crate::systems::game_of_life::*;
 use crate::systems::input::{input_system, console_input_system, InputEvent};
 use std::collections::HashSet;
 
-// System to update neighbor counts for all cells
+// Sent when a generation spawns a cell entity
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CellBorn {
+    pub position: (i32, i32),
+    pub entity: Entity,
+}
+
+// Sent when a generation despawns a cell entity
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CellDied {
+    pub position: (i32, i32),
+    pub entity: Entity,
+}
+
+fn grid_boundary(config: &GameConfig) -> GridBoundary {
+    GridBoundary::new(config.grid.width, config.grid.height, config.grid.wrap_edges)
+}
+
+// System to mark the neighbor counts of cells next to births and deaths dirty,
+// and to queue those positions for the next generation
+pub fn mark_dirty_neighbors_system(
+    mut cell_query: Query<&mut NeighborCount, With<CellState>>,
+    mut spatial_grid: ResMut<SpatialGrid>,
+    config: Res<GameConfig>,
+    rule: Res<LifeRule>,
+) {
+    // A new rule can change the fate of any cell, not just those near changes
+    if rule.is_changed() {
+        spatial_grid.mark_all_changed();
+    }
+    spatial_grid.mark_dirty_neighborhoods(&grid_boundary(&config));
+    
+    let spatial_grid = &*spatial_grid;
+    for position in spatial_grid.dirty_positions() {
+        if let Some(entity) = spatial_grid.get(position) {
+            if let Ok(mut neighbor_count) = cell_query.get_mut(entity) {
+                neighbor_count.mark_dirty();
+            }
+        }
+    }
+}
+
+// System to update neighbor counts for dirty cells; only cells spawned or
+// marked dirty since the last run are visited
 pub fn update_neighbor_counts_system(
-    mut cell_query: Query<(&GridPosition, &mut NeighborCount), With<CellState>>,
+    mut cell_query: Query<(&GridPosition, &mut NeighborCount), (With<CellState>, Changed<NeighborCount>)>,
     spatial_grid: Res<SpatialGrid>,
     config: Res<GameConfig>,
 ) {
+    let boundary = grid_boundary(&config);
     for (position, mut neighbor_count) in cell_query.iter_mut() {
         if neighbor_count.is_dirty() {
-            let live_positions: HashSet<(i32, i32)> = spatial_grid
-                .positions()
-                .cloned()
-                .collect();
-            
-            let count = if config.grid.wrap_edges {
-                count_live_neighbors_with_wrapping(
-                    position.to_tuple(),
-                    &live_positions,
-                    config.grid.width,
-                    config.grid.height,
-                )
-            } else {
-                count_live_neighbors(position.to_tuple(), &live_positions, false)
-            };
-            
+            let count = spatial_grid.count_live_neighbors(position.to_tuple(), &boundary);
             neighbor_count.set_count(count);
         }
     }
 }
 
-// System to apply Game of Life rules and determine next generation
+// System to apply Game of Life rules and determine next generation. Only
+// the dirty positions are stepped, so the work is linear in the cells that
+// changed last generation rather than in the live population.
 pub fn apply_game_of_life_system(
-    cell_query: Query<(Entity, &GridPosition, &CellState, &NeighborCount)>,
+    cell_query: Query<(&CellState, &NeighborCount)>,
     mut grid_state: ResMut<GridState>,
-    _spatial_grid: ResMut<SpatialGrid>,
+    mut spatial_grid: ResMut<SpatialGrid>,
     config: Res<GameConfig>,
-    _timer: Res<SimulationTimer>,
+    rule: Res<LifeRule>,
+    mut timer: ResMut<SimulationTimer>,
     simulation_state: Res<SimulationState>,
     mut last_generation: Local<u64>,
 ) {
     // Check if we should apply rules based on generation change
     let current_generation = simulation_state.get_generation();
-    let should_apply = simulation_state.is_running() || current_generation > *last_generation;
+    let forced = timer.take_forced_step();
+    let should_apply = simulation_state.is_running() || forced || current_generation > *last_generation;
     
     if !should_apply {
         return;
@@ -54,36 +87,35 @@ pub fn apply_game_of_life_system(
     
     *last_generation = current_generation;
     
-    // Collect current live cells
-    let current_live_cells: Vec<(i32, i32)> = cell_query
-        .iter()
-        .filter(|(_, _, cell_state, _)| cell_state.is_alive())
-        .map(|(_, position, _, _)| position.to_tuple())
-        .collect();
-    
-    // Apply Game of Life rules
-    let next_generation = if config.grid.wrap_edges {
-        apply_game_of_life_rules(
-            &current_live_cells,
-            true,
-            Some(config.grid.width),
-            Some(config.grid.height),
-        )
-    } else {
-        apply_game_of_life_rules(&current_live_cells, false, None, None)
-    };
+    // Live cells decide from their up to date NeighborCount; dead positions
+    // have no entity and are counted from the spatial grid
+    let boundary = grid_boundary(&config);
+    let mut births = HashSet::new();
+    let mut deaths = HashSet::new();
+    for position in spatial_grid.take_dirty_positions() {
+        let cell = spatial_grid.get(&position).and_then(|entity| cell_query.get(entity).ok());
+        match cell {
+            Some((cell_state, neighbor_count)) => {
+                if cell_state.is_alive() && !rule.next_state(true, neighbor_count.get_count()) {
+                    deaths.insert(position);
+                }
+            }
+            None => {
+                if rule.next_state(false, spatial_grid.count_live_neighbors(position, &boundary)) {
+                    births.insert(position);
+                }
+            }
+        }
+    }
     
-    // Update grid state with new generation
-    let next_live_set: HashSet<(i32, i32)> = next_generation.into_iter().collect();
-    grid_state.prepare_transition(next_live_set);
+    grid_state.prepare_changes(births, deaths);
 }
 
 // System to spawn new cell entities for births
 pub fn spawn_new_cells_system(
     mut commands: Commands,
     grid_state: Res<GridState>,
-    mut spatial_grid: ResMut<SpatialGrid>,
-    _config: Res<GameConfig>,
+    mut born: EventWriter<CellBorn>,
 ) {
     for &position in grid_state.get_pending_births() {
         let entity = commands.spawn((
@@ -92,26 +124,22 @@ pub fn spawn_new_cells_system(
             NeighborCount::new(),
         )).id();
         
-        // Update spatial grid
-        spatial_grid.insert(position, entity);
+        born.send(CellBorn { position, entity });
     }
 }
 
-// System to despawn cell entities for deaths
+// System to despawn cell entities for deaths; the spatial grid is the
+// position to entity index, so each death is a single lookup
 pub fn despawn_dead_cells_system(
     mut commands: Commands,
-    cell_query: Query<(Entity, &GridPosition), With<CellState>>,
     grid_state: Res<GridState>,
     mut spatial_grid: ResMut<SpatialGrid>,
+    mut died: EventWriter<CellDied>,
 ) {
-    for &death_position in grid_state.get_pending_deaths() {
-        // Find entity at this position and despawn it
-        for (entity, position) in cell_query.iter() {
-            if position.to_tuple() == death_position {
-                commands.entity(entity).despawn();
-                spatial_grid.remove(&death_position);
-                break;
-            }
+    for &position in grid_state.get_pending_deaths() {
+        if let Some(entity) = spatial_grid.remove(&position) {
+            commands.entity(entity).despawn();
+            died.send(CellDied { position, entity });
         }
     }
 }
@@ -120,7 +148,6 @@ pub fn despawn_dead_cells_system(
 pub fn finalize_generation_system(
     mut grid_state: ResMut<GridState>,
     mut simulation_state: ResMut<SimulationState>,
-    cell_query: Query<&CellState>,
     _time: Res<Time>,
 ) {
     if grid_state.has_pending_changes() {
@@ -130,57 +157,45 @@ pub fn finalize_generation_system(
         grid_state.apply_transition();
         
         // Update simulation state
-        let live_count = cell_query.iter().filter(|cell| cell.is_alive()).count();
+        let live_count = grid_state.cell_count();
         let step_duration = start_time.elapsed();
         simulation_state.advance_generation(live_count, step_duration);
     }
 }
 
-// System to handle simulation timing
-pub fn simulation_timer_system(
-    mut timer: ResMut<SimulationTimer>,
-    time: Res<Time>,
-    mut cell_query: Query<&mut NeighborCount>,
+// System to apply step rate changes to the FixedUpdate schedule's clock
+pub fn sync_fixed_timestep_system(
+    timer: Res<SimulationTimer>,
+    mut fixed_time: ResMut<Time<Fixed>>,
 ) {
-    if timer.tick(time.delta()) {
-        // Mark all neighbor counts as dirty for recalculation
-        for mut neighbor_count in cell_query.iter_mut() {
-            neighbor_count.mark_dirty();
-        }
+    if timer.is_changed() && fixed_time.timestep() != timer.step_duration() {
+        fixed_time.set_timestep(timer.step_duration());
     }
 }
 
-// System to synchronize grid state with ECS entities
+// System to synchronize grid state with the cells born and died this step;
+// cells that did not change are never visited
 pub fn sync_grid_state_system(
-    cell_query: Query<(&GridPosition, &CellState), Changed<CellState>>,
+    mut born: EventReader<CellBorn>,
+    mut died: EventReader<CellDied>,
     mut grid_state: ResMut<GridState>,
 ) {
-    let mut _any_changes = false;
-    
-    for (position, cell_state) in cell_query.iter() {
-        let pos_tuple = position.to_tuple();
-        
-        if cell_state.is_alive() {
-            if !grid_state.is_alive(&pos_tuple) {
-                grid_state.add_cell(pos_tuple);
-                _any_changes = true;
-            }
-        } else {
-            if grid_state.is_alive(&pos_tuple) {
-                grid_state.remove_cell(&pos_tuple);
-                _any_changes = true;
-            }
-        }
+    for event in died.read() {
+        grid_state.remove_cell(&event.position);
+    }
+    for event in born.read() {
+        grid_state.add_cell(event.position);
     }
 }
 
-// System to update spatial grid when entities move or change
+// System to index the entities of newly born cells; dying cells leave the
+// spatial grid when they are despawned
 pub fn update_spatial_grid_system(
-    entity_query: Query<(Entity, &GridPosition), (With<CellState>, Changed<GridPosition>)>,
+    mut born: EventReader<CellBorn>,
     mut spatial_grid: ResMut<SpatialGrid>,
 ) {
-    for (entity, position) in entity_query.iter() {
-        spatial_grid.insert(position.to_tuple(), entity);
+    for event in born.read() {
+        spatial_grid.insert(event.position, event.entity);
     }
 }
 
@@ -276,6 +291,24 @@ pub fn debug_statistics_system(
     }
 }
 
+// Run condition: the simulation is running or a single step was requested
+pub fn simulation_should_step(
+    simulation_state: Res<SimulationState>,
+    timer: Res<SimulationTimer>,
+) -> bool {
+    simulation_state.is_running() || timer.is_step_forced()
+}
+
+// Run condition: there are live cells to step
+pub fn live_cells_present(grid_state: Res<GridState>) -> bool {
+    grid_state.cell_count() > 0
+}
+
+// Run condition: a generation transition is waiting to be applied
+pub fn pending_changes_exist(grid_state: Res<GridState>) -> bool {
+    grid_state.has_pending_changes()
+}
+
 // System set for organizing Game of Life systems
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
 pub enum GameOfLifeSystemSet {
@@ -286,28 +319,46 @@ pub enum GameOfLifeSystemSet {
     Debug,
 }
 
-// Helper function to add all Game of Life systems to an app
+// Helper function to add all Game of Life systems to an app. Input and debug
+// output run every frame; the generation itself runs on FixedUpdate, once per
+// step at SimulationTimer's rate, and only while the simulation is running
+// with cells to step, so a paused or empty world stays idle.
 pub fn add_game_of_life_systems(app: &mut App) {
     app.add_event::<InputEvent>()
+    .add_event::<CellBorn>()
+    .add_event::<CellDied>()
     .configure_sets(
         Update,
         (
             GameOfLifeSystemSet::Input,
-            GameOfLifeSystemSet::Logic,
-            GameOfLifeSystemSet::Spawn,
-            GameOfLifeSystemSet::Cleanup,
             GameOfLifeSystemSet::Debug,
         ).chain()
     )
+    .configure_sets(
+        FixedUpdate,
+        (
+            GameOfLifeSystemSet::Logic.run_if(simulation_should_step.and_then(live_cells_present)),
+            GameOfLifeSystemSet::Spawn.run_if(pending_changes_exist),
+            GameOfLifeSystemSet::Cleanup.run_if(pending_changes_exist),
+        ).chain()
+    )
     .add_systems(
         Update,
         (
-            (simulation_control_system, console_input_system, input_system).in_set(GameOfLifeSystemSet::Input),
+            (simulation_control_system, console_input_system, input_system, sync_fixed_timestep_system)
+                .chain()
+                .in_set(GameOfLifeSystemSet::Input),
+            debug_statistics_system.in_set(GameOfLifeSystemSet::Debug),
+        ),
+    )
+    .add_systems(
+        FixedUpdate,
+        (
             (
-                simulation_timer_system,
+                mark_dirty_neighbors_system,
                 update_neighbor_counts_system,
                 apply_game_of_life_system,
-            ).in_set(GameOfLifeSystemSet::Logic),
+            ).chain().in_set(GameOfLifeSystemSet::Logic),
             (
                 spawn_new_cells_system,
                 despawn_dead_cells_system,
@@ -318,7 +369,6 @@ pub fn add_game_of_life_systems(app: &mut App) {
                 update_spatial_grid_system,
                 update_cell_ages_system,
             ).in_set(GameOfLifeSystemSet::Cleanup),
-            debug_statistics_system.in_set(GameOfLifeSystemSet::Debug),
         ),
     )
     .add_systems(Startup, initialize_pattern_system);
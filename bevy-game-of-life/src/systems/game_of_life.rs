@@ -1,5 +1,6 @@
 use std::collections::{HashMap, HashSet};
 use crate::components::grid::GridBoundary;
+use crate::resources::LifeRule;
 
 // Core Conway's Game of Life rule implementation
 pub fn should_cell_survive(currently_alive: bool, neighbor_count: u8) -> bool {
@@ -113,6 +114,17 @@ pub fn apply_game_of_life_rules(
     wrap_edges: bool,
     grid_width: Option<i32>,
     grid_height: Option<i32>,
+) -> Vec<(i32, i32)> {
+    apply_life_rule(live_cells, wrap_edges, grid_width, grid_height, &LifeRule::conway())
+}
+
+// Apply any life-like rule to get next generation
+pub fn apply_life_rule(
+    live_cells: &[(i32, i32)],
+    wrap_edges: bool,
+    grid_width: Option<i32>,
+    grid_height: Option<i32>,
+    rule: &LifeRule,
 ) -> Vec<(i32, i32)> {
     let live_set: HashSet<(i32, i32)> = live_cells.iter().cloned().collect();
     let mut next_generation = Vec::new();
@@ -148,7 +160,7 @@ pub fn apply_game_of_life_rules(
             count_live_neighbors(position, &live_set, false)
         };
         
-        if should_cell_survive(currently_alive, neighbor_count) {
+        if rule.next_state(currently_alive, neighbor_count) {
             next_generation.push(position);
         }
     }
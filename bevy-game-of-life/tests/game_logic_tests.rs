@@ -1,5 +1,6 @@
 use bevy_game_of_life::systems::game_of_life::*;
 use bevy_game_of_life::components::cell::*;
+use bevy_game_of_life::resources::LifeRule;
 use std::collections::HashSet;
 
 #[test]
@@ -201,4 +202,22 @@ fn test_boundary_conditions() {
     // (0,0) has neighbors (-1,-1), (-1,0), (0,-1) = 3 neighbors = survives
     // So all should survive!
     assert_eq!(next_gen.len(), 4);
+}
+
+#[test]
+fn test_life_rule_presets() {
+    let conway = LifeRule::conway();
+    assert_eq!(conway.notation(), "B3/S23");
+    for alive in [false, true] {
+        for neighbors in 0..=8 {
+            assert_eq!(conway.next_state(alive, neighbors), should_cell_survive(alive, neighbors));
+        }
+    }
+    
+    // HighLife also births on six neighbors
+    let highlife = LifeRule::presets().into_iter().find(|rule| rule.name == "HighLife").unwrap();
+    assert_eq!(highlife.notation(), "B36/S23");
+    let six = vec![(0, 0), (1, 0), (2, 0), (0, 2), (1, 2), (2, 2)];
+    assert!(apply_life_rule(&six, false, None, None, &highlife).contains(&(1, 1)));
+    assert!(!apply_game_of_life_rules(&six, false, None, None).contains(&(1, 1)));
 }
\ No newline at end of file
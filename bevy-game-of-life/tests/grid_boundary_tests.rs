@@ -1,5 +1,7 @@
 use bevy_game_of_life::systems::game_of_life::*;
 use bevy_game_of_life::components::grid::*;
+use bevy_game_of_life::resources::rule::LifeRule;
+use bevy::ecs::entity::Entity;
 use std::collections::HashSet;
 
 #[test]
@@ -225,4 +227,46 @@ fn test_blinker_at_edge_with_wrapping() {
     let expected_set: HashSet<(i32, i32)> = expected.into_iter().collect();
     
     assert_eq!(next_set, expected_set);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_dirty_positions_step_like_the_whole_grid() {
+    // Stepping only the neighborhoods of last generation's changes, as the
+    // ECS systems do, gives the same generations as stepping every cell
+    let boundary = GridBoundary::new(8, 8, true);
+    let rule = LifeRule::conway();
+    let mut live = vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+    let mut spatial_grid = SpatialGrid::new();
+    let mut next_entity = 0;
+    for &position in &live {
+        spatial_grid.insert(position, Entity::from_raw(next_entity));
+        next_entity += 1;
+    }
+    
+    for generation in 1..=32 {
+        spatial_grid.mark_dirty_neighborhoods(&boundary);
+        let mut births = Vec::new();
+        let mut deaths = Vec::new();
+        for position in spatial_grid.take_dirty_positions() {
+            let alive = spatial_grid.contains(&position);
+            let next = rule.next_state(alive, spatial_grid.count_live_neighbors(position, &boundary));
+            if alive && !next {
+                deaths.push(position);
+            } else if !alive && next {
+                births.push(position);
+            }
+        }
+        for position in &deaths {
+            spatial_grid.remove(position);
+        }
+        for &position in &births {
+            spatial_grid.insert(position, Entity::from_raw(next_entity));
+            next_entity += 1;
+        }
+        
+        live = apply_life_rule(&live, true, Some(8), Some(8), &rule);
+        let expected: HashSet<(i32, i32)> = live.iter().copied().collect();
+        let actual: HashSet<(i32, i32)> = spatial_grid.positions().copied().collect();
+        assert_eq!(actual, expected, "generation {}", generation);
+    }
+}
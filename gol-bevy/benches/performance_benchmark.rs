@@ -1,7 +1,7 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use gol_bevy::components::{Position, CellState};
 use gol_bevy::systems::{neighbor_counting_system, cell_lifecycle_system, cleanup_system};
-use gol_bevy::resources::{GridConfig, SimulationState, Simulations};
+use gol_bevy::resources::{GridConfig, SimulationState, Simulations, SpatialGrid};
 use bevy::prelude::*;
 
 fn setup_test_world(cell_count: usize) -> World {
@@ -11,6 +11,7 @@ fn setup_test_world(cell_count: usize) -> World {
     world.insert_resource(GridConfig::default());
     world.insert_resource(SimulationState::new());
     world.insert_resource(Simulations::new());
+    world.insert_resource(SpatialGrid::new());
     
     // Create a glider pattern and replicate it
     let glider_pattern = vec![
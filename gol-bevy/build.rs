@@ -1,10 +1,39 @@
+use std::process::Command;
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tonic_build::configure()
-        .build_server(true)
-        .build_client(false)
-        .compile_protos(
-            &["../proto/game_of_life.proto"],
-            &["../proto"],
-        )?;
+    // Only the server speaks gRPC; engine-only builds need no protoc
+    if std::env::var_os("CARGO_FEATURE_SERVER").is_some() {
+        tonic_build::configure()
+            .build_server(true)
+            .build_client(true)
+            .compile_protos(
+                &["../proto/game_of_life.proto"],
+                &["../proto"],
+            )?;
+    }
+
+    // Build info reported by GetStatus
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=GOL_GIT_COMMIT={}", commit);
+    println!("cargo:rustc-env=GOL_BUILD_PROFILE={}", std::env::var("PROFILE").unwrap_or_default());
+    println!("cargo:rustc-env=GOL_BEVY_VERSION={}", locked_version("bevy").unwrap_or_default());
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs");
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Version of `package` that Cargo.lock pins.
+fn locked_version(package: &str) -> Option<String> {
+    let lock = std::fs::read_to_string("Cargo.lock").ok()?;
+    let mut lines = lock.lines();
+    lines.find(|line| *line == format!("name = \"{}\"", package))?;
+    let version = lines.next()?.strip_prefix("version = \"")?;
+    Some(version.trim_end_matches('"').to_string())
+}
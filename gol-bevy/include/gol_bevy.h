@@ -0,0 +1,81 @@
+#ifndef GOL_BEVY_H
+#define GOL_BEVY_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Opaque simulation handle.
+typedef struct GolSimulation GolSimulation;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Create an empty `width` by `height` simulation under `rule` (any rule
+// string the server accepts), or Conway's Life when `rule` is null.
+// Returns null on an invalid size or rule.
+//
+// # Safety
+//
+// `rule` must be null or point to a NUL-terminated string.
+GolSimulation *gol_create(int32_t width, int32_t height, const char *rule);
+
+// Release a simulation; null is ignored.
+//
+// # Safety
+//
+// `simulation` must be null or a handle from [`gol_create`] not yet freed.
+void gol_free(GolSimulation *simulation);
+
+// Add the live cells of `pattern` (see [`decode_pattern`]) shifted by the
+// offset. Returns the number of cells newly set alive inside the grid, or
+// -1 on an invalid pattern.
+//
+// # Safety
+//
+// `simulation` must be a live handle and `pattern` a NUL-terminated string.
+int64_t gol_load_pattern(GolSimulation *simulation,
+                         const char *pattern,
+                         int32_t offset_x,
+                         int32_t offset_y);
+
+// Advance `generations` generations, returning the generation reached, or
+// -1 for a null handle.
+//
+// # Safety
+//
+// `simulation` must be null or a live handle.
+int64_t gol_step(GolSimulation *simulation, uint64_t generations);
+
+// Generation of the simulation, or -1 for a null handle.
+//
+// # Safety
+//
+// `simulation` must be null or a live handle.
+int64_t gol_generation(const GolSimulation *simulation);
+
+// Write up to `capacity` live cells as `x, y` pairs into `xy`, which holds
+// `2 * capacity` values, sorted by row then column. Returns the total
+// number of live cells, which may exceed `capacity`; pass a null `xy` to
+// only count them. Returns -1 for a null handle.
+//
+// # Safety
+//
+// `simulation` must be a live handle and `xy` null or valid for writes of
+// `2 * capacity` values.
+int64_t gol_get_cells(const GolSimulation *simulation, int32_t *xy, size_t capacity);
+
+// The last error on this thread, or null if there was none. The string
+// stays valid until the next failing call on this thread.
+const char *gol_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* GOL_BEVY_H */
@@ -1,5 +1,4 @@
 use tonic::{Request, Response, Status};
-use std::collections::HashMap;
 
 // Placeholder for gRPC handlers
 // This will be implemented in a later task
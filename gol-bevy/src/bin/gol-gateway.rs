@@ -0,0 +1,66 @@
+use std::net::SocketAddr;
+use tonic::transport::Server;
+
+use gol_bevy::grpc::proto::game_of_life_service_server::GameOfLifeServiceServer;
+use gol_bevy::grpc::{with_compression, GatewayBackend, GatewayService};
+
+const USAGE: &str = "usage: gol-gateway [--listen <addr>] --backend <label>=<addr> [--backend <label>=<addr> ...]";
+
+/// Default listen address, next to the servers' usual ports.
+const DEFAULT_LISTEN_ADDR: &str = "[::1]:50050";
+
+struct GatewayArgs {
+    listen: SocketAddr,
+    backends: Vec<GatewayBackend>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<GatewayArgs, String> {
+    let mut listen = DEFAULT_LISTEN_ADDR.parse().expect("default listen address is valid");
+    let mut backends: Vec<GatewayBackend> = Vec::new();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or_else(|| format!("{} needs a value", flag));
+        match arg.as_str() {
+            "--listen" => {
+                let addr = value("--listen")?;
+                listen = addr.parse().map_err(|_| format!("invalid listen address: {}", addr))?;
+            }
+            "--backend" => {
+                let backend = GatewayBackend::parse(&value("--backend")?)?;
+                if backends.iter().any(|existing| existing.label == backend.label) {
+                    return Err(format!("backend {} given twice", backend.label));
+                }
+                backends.push(backend);
+            }
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+    if backends.is_empty() {
+        return Err("at least one --backend is required".to_string());
+    }
+    Ok(GatewayArgs { listen, backends })
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{}", error);
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+    
+    let service = GatewayService::new(args.backends);
+    println!("Game of Life gateway listening on {}", args.listen);
+    for backend in service.backends() {
+        println!("  {} -> {}", backend.label, backend.address);
+    }
+    Server::builder()
+        .add_service(with_compression(GameOfLifeServiceServer::new(service)))
+        .serve_with_shutdown(args.listen, async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
@@ -1,11 +1,20 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::resources::Rule;
+
 #[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CellState {
     pub alive: bool,
     pub generation: u64,
     pub neighbor_count: u8,
+    /// Generations decay state: 0 unless the cell is dying under a
+    /// multi-state rule, then 2 up to the rule's state count minus one.
+    #[serde(default)]
+    pub decay: u8,
+    /// Colony color under Immigration or QuadLife; 0 otherwise.
+    #[serde(default)]
+    pub color: u8,
 }
 
 impl CellState {
@@ -14,6 +23,8 @@ impl CellState {
             alive: true,
             generation: 0,
             neighbor_count: 0,
+            decay: 0,
+            color: 0,
         }
     }
     
@@ -22,6 +33,8 @@ impl CellState {
             alive: true,
             generation,
             neighbor_count: 0,
+            decay: 0,
+            color: 0,
         }
     }
     
@@ -30,6 +43,8 @@ impl CellState {
             alive: true,
             generation: 0,
             neighbor_count,
+            decay: 0,
+            color: 0,
         }
     }
     
@@ -38,6 +53,8 @@ impl CellState {
             alive: true,
             generation,
             neighbor_count,
+            decay: 0,
+            color: 0,
         }
     }
     
@@ -54,6 +71,39 @@ impl CellState {
             alive: self.should_survive() || self.should_be_born(),
             generation: self.generation + 1,
             neighbor_count: 0, // Reset for next calculation
+            decay: 0,
+            color: if self.should_survive() { self.color } else { 0 },
+        }
+    }
+}
+
+impl CellState {
+    pub fn is_decaying(&self) -> bool {
+        self.decay >= 2
+    }
+    
+    /// The cell's next state under `rule`. Under a Generations rule a live
+    /// cell that fails to survive starts decaying instead of dying, and a
+    /// decaying cell moves one state on until it runs out of states. A
+    /// surviving cell keeps its color; a newborn cell starts at color 0, as its
+    /// color depends on its parents (see [`Rule::birth_color`]).
+    pub fn next_generation_under(&self, rule: &Rule) -> Self {
+        let (alive, decay) = if self.is_decaying() {
+            (false, if self.decay as u32 + 1 < rule.states() { self.decay + 1 } else { 0 })
+        } else if rule.next_state(self.alive, self.neighbor_count as u32) {
+            (true, 0)
+        } else if self.alive && rule.states() > 2 {
+            (false, 2)
+        } else {
+            (false, 0)
+        };
+        
+        Self {
+            alive,
+            generation: self.generation + 1,
+            neighbor_count: 0,
+            decay,
+            color: if alive && self.alive { self.color } else { 0 },
         }
     }
 }
@@ -71,7 +121,7 @@ mod tests {
     #[test]
     fn test_cell_state_creation() {
         let cell = CellState::new();
-        assert_eq!(cell.alive, true);
+        assert!(cell.alive);
         assert_eq!(cell.generation, 0);
         assert_eq!(cell.neighbor_count, 0);
     }
@@ -79,7 +129,7 @@ mod tests {
     #[test]
     fn test_cell_state_with_generation() {
         let cell = CellState::with_generation(5);
-        assert_eq!(cell.alive, true);
+        assert!(cell.alive);
         assert_eq!(cell.generation, 5);
         assert_eq!(cell.neighbor_count, 0);
     }
@@ -87,7 +137,7 @@ mod tests {
     #[test]
     fn test_cell_state_with_neighbors() {
         let cell = CellState::with_neighbors(3);
-        assert_eq!(cell.alive, true);
+        assert!(cell.alive);
         assert_eq!(cell.generation, 0);
         assert_eq!(cell.neighbor_count, 3);
     }
@@ -144,6 +194,36 @@ mod tests {
         assert_eq!(next.generation, 2);
     }
     
+    #[test]
+    fn test_next_generation_under_generations_rule() {
+        let star_wars: Rule = "B2/S345/C4".parse().unwrap();
+        
+        // A live cell with too few neighbors starts decaying
+        let next = CellState::with_neighbors(1).next_generation_under(&star_wars);
+        assert_eq!((next.alive, next.decay), (false, 2));
+        
+        // Decaying cells ignore their neighbors and run out after the last state
+        let mut dying = CellState::with_neighbors(2);
+        dying.alive = false;
+        dying.decay = 2;
+        let next = dying.next_generation_under(&star_wars);
+        assert_eq!((next.alive, next.decay), (false, 3));
+        let last = CellState { neighbor_count: 2, ..next }.next_generation_under(&star_wars);
+        assert_eq!((last.alive, last.decay), (false, 0));
+        
+        // Under a two-state rule cells die outright
+        let next = CellState::with_neighbors(1).next_generation_under(&Rule::conway());
+        assert_eq!((next.alive, next.decay), (false, 0));
+    }
+    
+    #[test]
+    fn test_survivors_keep_their_color() {
+        let immigration: Rule = "Immigration".parse().unwrap();
+        let cell = CellState { color: 1, ..CellState::with_neighbors(2) };
+        assert_eq!(cell.next_generation_under(&immigration).color, 1);
+        assert_eq!(CellState { neighbor_count: 4, ..cell }.next_generation_under(&immigration).color, 0);
+    }
+    
     #[test]
     fn test_cell_state_serialization() {
         let cell = CellState::with_generation_and_neighbors(5, 3);
@@ -2,7 +2,7 @@
 //! 
 //! This module contains the core ECS components for the Game of Life simulation.
 //! - `Position`: Represents a cell's position in the grid
-//! - `CellState`: Represents the state of a cell (alive, generation, neighbor count)
+//! - `CellState`: Represents the state of a cell (alive, generation, neighbor count, decay)
 
 pub mod position;
 pub mod cell_state;
@@ -0,0 +1,255 @@
+//! C API over the reference engine.
+//!
+//! Lets the C++ implementations step the same engine the server does and
+//! decode patterns with the same parsers, to check their own results
+//! against. Built with the `ffi` feature as a C library:
+//!
+//! ```text
+//! cargo rustc --release --lib --features ffi --crate-type cdylib
+//! ```
+//!
+//! `include/gol_bevy.h` declares the functions below. A simulation is an
+//! opaque handle from [`gol_create`] released with [`gol_free`]. Functions
+//! that can fail return a negative value or null and leave a message for
+//! [`gol_last_error`] on the calling thread. No function may be called
+//! with the same handle from two threads at once.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+use crate::resources::{decode_apgcode, decode_macrocell, decode_rle, scratch_simulation, standard_pattern, Rule, SimulationData};
+
+/// Opaque simulation handle.
+pub struct GolSimulation {
+    simulation: SimulationData,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl Into<String>) {
+    let message = CString::new(message.into().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Cells of a pattern: a standard pattern name, an apgcode, a Macrocell
+/// file or RLE.
+pub fn decode_pattern(pattern: &str) -> Result<Vec<(i32, i32)>, String> {
+    if let Some(cells) = standard_pattern(&pattern.trim().to_ascii_lowercase()) {
+        return Ok(cells);
+    }
+    if pattern.starts_with('x') && pattern.contains('_') {
+        return decode_apgcode(pattern.trim()).map_err(|error| error.to_string());
+    }
+    if pattern.trim_start().starts_with("[M2]") {
+        let macrocell = decode_macrocell(pattern).map_err(|error| error.to_string())?;
+        let Some((width, height)) = macrocell.size() else {
+            return Ok(Vec::new());
+        };
+        let clamp = |size: i64| size.min(i32::MAX as i64) as i32;
+        return Ok(macrocell.cells_within(0, 0, clamp(width), clamp(height)));
+    }
+    decode_rle(pattern).map_err(|error| error.to_string())
+}
+
+/// Read a C string argument, recording an error for null or invalid UTF-8.
+///
+/// # Safety
+///
+/// `text` must be null or point to a NUL-terminated string.
+unsafe fn read_str<'a>(text: *const c_char, name: &str) -> Option<&'a str> {
+    if text.is_null() {
+        set_last_error(format!("{} is null", name));
+        return None;
+    }
+    match unsafe { CStr::from_ptr(text) }.to_str() {
+        Ok(text) => Some(text),
+        Err(_) => {
+            set_last_error(format!("{} is not valid UTF-8", name));
+            None
+        }
+    }
+}
+
+/// Create an empty `width` by `height` simulation under `rule` (any rule
+/// string the server accepts), or Conway's Life when `rule` is null.
+/// Returns null on an invalid size or rule.
+///
+/// # Safety
+///
+/// `rule` must be null or point to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gol_create(width: i32, height: i32, rule: *const c_char) -> *mut GolSimulation {
+    if width <= 0 || height <= 0 {
+        set_last_error("width and height must be positive");
+        return ptr::null_mut();
+    }
+    let rule = if rule.is_null() {
+        Rule::conway()
+    } else {
+        let Some(text) = (unsafe { read_str(rule, "rule") }) else {
+            return ptr::null_mut();
+        };
+        match text.parse::<Rule>() {
+            Ok(rule) => rule,
+            Err(error) => {
+                set_last_error(error.to_string());
+                return ptr::null_mut();
+            }
+        }
+    };
+    let mut simulation = scratch_simulation(width, height);
+    simulation.set_rule(rule);
+    Box::into_raw(Box::new(GolSimulation { simulation }))
+}
+
+/// Release a simulation; null is ignored.
+///
+/// # Safety
+///
+/// `simulation` must be null or a handle from [`gol_create`] not yet freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gol_free(simulation: *mut GolSimulation) {
+    if !simulation.is_null() {
+        drop(unsafe { Box::from_raw(simulation) });
+    }
+}
+
+/// Add the live cells of `pattern` (see [`decode_pattern`]) shifted by the
+/// offset. Returns the number of cells newly set alive inside the grid, or
+/// -1 on an invalid pattern.
+///
+/// # Safety
+///
+/// `simulation` must be a live handle and `pattern` a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gol_load_pattern(simulation: *mut GolSimulation, pattern: *const c_char, offset_x: i32, offset_y: i32) -> i64 {
+    let Some(handle) = (unsafe { simulation.as_mut() }) else {
+        set_last_error("simulation is null");
+        return -1;
+    };
+    let Some(text) = (unsafe { read_str(pattern, "pattern") }) else {
+        return -1;
+    };
+    match decode_pattern(text) {
+        Ok(cells) => handle.simulation.add_pattern(&cells, offset_x, offset_y) as i64,
+        Err(error) => {
+            set_last_error(error);
+            -1
+        }
+    }
+}
+
+/// Advance `generations` generations, returning the generation reached, or
+/// -1 for a null handle.
+///
+/// # Safety
+///
+/// `simulation` must be null or a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gol_step(simulation: *mut GolSimulation, generations: u64) -> i64 {
+    let Some(handle) = (unsafe { simulation.as_mut() }) else {
+        set_last_error("simulation is null");
+        return -1;
+    };
+    for _ in 0..generations {
+        handle.simulation.step();
+    }
+    handle.simulation.generation as i64
+}
+
+/// Generation of the simulation, or -1 for a null handle.
+///
+/// # Safety
+///
+/// `simulation` must be null or a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gol_generation(simulation: *const GolSimulation) -> i64 {
+    match unsafe { simulation.as_ref() } {
+        Some(handle) => handle.simulation.generation as i64,
+        None => {
+            set_last_error("simulation is null");
+            -1
+        }
+    }
+}
+
+/// Write up to `capacity` live cells as `x, y` pairs into `xy`, which holds
+/// `2 * capacity` values, sorted by row then column. Returns the total
+/// number of live cells, which may exceed `capacity`; pass a null `xy` to
+/// only count them. Returns -1 for a null handle.
+///
+/// # Safety
+///
+/// `simulation` must be a live handle and `xy` null or valid for writes of
+/// `2 * capacity` values.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn gol_get_cells(simulation: *const GolSimulation, xy: *mut i32, capacity: usize) -> i64 {
+    let Some(handle) = (unsafe { simulation.as_ref() }) else {
+        set_last_error("simulation is null");
+        return -1;
+    };
+    let mut cells = handle.simulation.get_live_cells();
+    cells.sort_unstable_by_key(|&(x, y)| (y, x));
+    if !xy.is_null() {
+        let out = unsafe { std::slice::from_raw_parts_mut(xy, capacity.saturating_mul(2)) };
+        for (pair, &(x, y)) in out.chunks_exact_mut(2).zip(&cells) {
+            pair[0] = x;
+            pair[1] = y;
+        }
+    }
+    cells.len() as i64
+}
+
+/// The last error on this thread, or null if there was none. The string
+/// stays valid until the next failing call on this thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn gol_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_glider_round_trip() {
+        unsafe {
+            let simulation = gol_create(32, 32, ptr::null());
+            assert!(!simulation.is_null());
+            assert_eq!(gol_load_pattern(simulation, c"bo$2bo$3o!".as_ptr(), 0, 0), 5);
+            assert_eq!(gol_step(simulation, 4), 4);
+            
+            let mut xy = [0i32; 10];
+            assert_eq!(gol_get_cells(simulation, xy.as_mut_ptr(), 5), 5);
+            assert_eq!(xy, [2, 1, 3, 2, 1, 3, 2, 3, 3, 3]);
+            assert_eq!(gol_get_cells(simulation, ptr::null_mut(), 0), 5);
+            gol_free(simulation);
+        }
+    }
+    
+    #[test]
+    fn test_standard_patterns_load_by_name() {
+        unsafe {
+            let simulation = gol_create(16, 16, ptr::null());
+            assert_eq!(gol_load_pattern(simulation, c"R-Pentomino".as_ptr(), 4, 4), 5);
+            assert_eq!(gol_generation(simulation), 0);
+            gol_free(simulation);
+        }
+    }
+    
+    #[test]
+    fn test_errors_are_reported() {
+        unsafe {
+            assert!(gol_create(10, 10, c"B9/S".as_ptr()).is_null());
+            assert!(!gol_last_error().is_null());
+            let simulation = gol_create(10, 10, c"B36/S23".as_ptr());
+            assert_eq!(gol_load_pattern(simulation, c"2o*o!".as_ptr(), 0, 0), -1);
+            let message = CStr::from_ptr(gol_last_error()).to_str().unwrap();
+            assert!(!message.is_empty());
+            gol_free(simulation);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,108 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tonic::codegen::http;
+use tonic::transport::server::TcpConnectInfo;
+use tonic::Code;
+use tower::{Layer, Service};
+
+/// Tower layer logging method, peer, latency and status for every RPC.
+///
+/// Latency is measured until the response headers are sent, so for streaming
+/// RPCs it covers opening the stream rather than its whole lifetime. The status
+/// is read from the headers: errors returned before any message carry it
+/// there, while successful calls only report it in trailers and log as `Ok`.
+#[derive(Clone, Copy, Debug)]
+pub struct AccessLogLayer {
+    enabled: bool,
+}
+
+impl AccessLogLayer {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+}
+
+impl<S> Layer<S> for AccessLogLayer {
+    type Service = AccessLog<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AccessLog { inner, enabled: self.enabled }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AccessLog<S> {
+    inner: S,
+    enabled: bool,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for AccessLog<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<ReqBody>) -> Self::Future {
+        if !self.enabled {
+            return Box::pin(self.inner.call(request));
+        }
+
+        let method = request.uri().path().to_string();
+        let peer = request
+            .extensions()
+            .get::<TcpConnectInfo>()
+            .and_then(|info| info.remote_addr())
+            .map_or_else(|| "unknown".to_string(), |addr| addr.to_string());
+        let started = Instant::now();
+        let future = self.inner.call(request);
+
+        Box::pin(async move {
+            let result = future.await;
+            let status = match &result {
+                Ok(response) => format!("{:?}", response_code(response)),
+                Err(_) => "TransportError".to_string(),
+            };
+            println!(
+                "{} peer={} status={} latency={:.1}ms",
+                method,
+                peer,
+                status,
+                started.elapsed().as_secs_f64() * 1000.0
+            );
+            result
+        })
+    }
+}
+
+/// The gRPC status carried in the response headers, `Ok` when it is left to the trailers.
+fn response_code<B>(response: &http::Response<B>) -> Code {
+    response
+        .headers()
+        .get("grpc-status")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<i32>().ok())
+        .map_or(Code::Ok, Code::from_i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_code_reads_trailers_only_status() {
+        let failed = http::Response::builder().header("grpc-status", "8").body(()).unwrap();
+        assert_eq!(response_code(&failed), Code::ResourceExhausted);
+
+        let streaming = http::Response::builder().body(()).unwrap();
+        assert_eq!(response_code(&streaming), Code::Ok);
+    }
+}
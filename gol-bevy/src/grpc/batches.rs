@@ -0,0 +1,170 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+use crate::grpc::proto::{BatchRunResult, BatchStatus, EndReason, JobState};
+use crate::grpc::ShutdownSignal;
+use crate::resources::{run_seeded, BatchRunOutcome, BatchRunSpec, StopReason};
+
+#[derive(Debug)]
+struct BatchRecord {
+    status: BatchStatus,
+    results: Vec<BatchRunResult>,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Batches submitted through SubmitBatch, oldest first.
+///
+/// Every run of every batch waits for one of a fixed number of permits
+/// before it steps, so however many batches are queued only that many runs
+/// share the stepping pool at once, and batches start in the order they
+/// were submitted. Runs step on scratch grids, never touching simulations.
+/// Cancellation, explicit or by server shutdown, stops queued runs from
+/// starting and running ones within a few hundred generations.
+pub struct BatchRegistry {
+    thread_pool: Arc<rayon::ThreadPool>,
+    shutdown: ShutdownSignal,
+    permits: Arc<Semaphore>,
+    batches: Mutex<Vec<BatchRecord>>,
+}
+
+impl BatchRegistry {
+    pub fn new(thread_pool: Arc<rayon::ThreadPool>, shutdown: ShutdownSignal, concurrency: usize) -> Self {
+        Self {
+            thread_pool,
+            shutdown,
+            permits: Arc::new(Semaphore::new(concurrency.max(1))),
+            batches: Mutex::new(Vec::new()),
+        }
+    }
+    
+    /// Queue `repetitions` runs of `spec`, run `i` seeded with `seed + i`.
+    pub fn submit(self: &Arc<Self>, name: &str, spec: BatchRunSpec, seed: u64, repetitions: u32) -> BatchStatus {
+        let status = BatchStatus {
+            batch_id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            state: JobState::JobPending as i32,
+            runs_total: repetitions as i32,
+            runs_completed: 0,
+            submitted_at_ms: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as i64,
+            message: String::new(),
+        };
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.batches.lock().unwrap().push(BatchRecord {
+            status: status.clone(),
+            results: Vec::new(),
+            cancelled: cancelled.clone(),
+        });
+        
+        let registry = self.clone();
+        let batch_id = status.batch_id.clone();
+        tokio::spawn(async move {
+            registry.run(&batch_id, Arc::new(spec), seed, repetitions, cancelled).await;
+        });
+        
+        status
+    }
+    
+    pub fn list(&self) -> Vec<BatchStatus> {
+        self.batches.lock().unwrap().iter().map(|record| record.status.clone()).collect()
+    }
+    
+    /// A batch's status and the results of its finished runs, by run.
+    pub fn results(&self, batch_id: &str) -> Option<(BatchStatus, Vec<BatchRunResult>)> {
+        let batches = self.batches.lock().unwrap();
+        let record = batches.iter().find(|record| record.status.batch_id == batch_id)?;
+        let mut results = record.results.clone();
+        results.sort_by_key(|result| result.run);
+        Some((record.status.clone(), results))
+    }
+    
+    /// Request cancellation. Queued runs never start and running ones stop
+    /// early; finished runs keep their results.
+    pub fn cancel(&self, batch_id: &str) -> Option<BatchStatus> {
+        let batches = self.batches.lock().unwrap();
+        let record = batches.iter().find(|record| record.status.batch_id == batch_id)?;
+        record.cancelled.store(true, Ordering::SeqCst);
+        Some(record.status.clone())
+    }
+    
+    async fn run(self: Arc<Self>, batch_id: &str, spec: Arc<BatchRunSpec>, seed: u64, repetitions: u32, cancelled: Arc<AtomicBool>) {
+        let mut runs = JoinSet::new();
+        for run in 0..repetitions {
+            let Ok(permit) = self.permits.clone().acquire_owned().await else {
+                break;
+            };
+            if cancelled.load(Ordering::SeqCst) || self.shutdown.is_triggered() {
+                break;
+            }
+            self.update(batch_id, |record| record.status.state = JobState::JobRunning as i32);
+            
+            let registry = self.clone();
+            let batch_id = batch_id.to_string();
+            let spec = spec.clone();
+            let cancelled = cancelled.clone();
+            runs.spawn(async move {
+                let thread_pool = registry.thread_pool.clone();
+                let shutdown = registry.shutdown.clone();
+                let run_seed = seed.wrapping_add(run as u64);
+                let outcome = tokio::task::spawn_blocking(move || {
+                    let _permit = permit;
+                    let stop = || cancelled.load(Ordering::SeqCst) || shutdown.is_triggered();
+                    thread_pool.install(|| run_seeded(&spec, run_seed, &stop))
+                })
+                .await;
+                registry.update(&batch_id, |record| match outcome {
+                    Ok(Some(outcome)) => {
+                        record.results.push(run_result(run, &outcome));
+                        record.status.runs_completed += 1;
+                    }
+                    Ok(None) => {}
+                    Err(_) => {
+                        record.status.state = JobState::JobFailed as i32;
+                        record.status.message = format!("Run {} stopped unexpectedly", run);
+                    }
+                });
+            });
+        }
+        while runs.join_next().await.is_some() {}
+        
+        let stopped = cancelled.load(Ordering::SeqCst) || self.shutdown.is_triggered();
+        self.update(batch_id, |record| {
+            if record.status.state() == JobState::JobFailed {
+                return;
+            }
+            if stopped && (record.status.runs_completed as u32) < repetitions {
+                record.status.state = JobState::JobCancelled as i32;
+                record.status.message = format!("Cancelled after {} of {} runs", record.status.runs_completed, repetitions);
+            } else {
+                record.status.state = JobState::JobCompleted as i32;
+            }
+        });
+    }
+    
+    fn update(&self, batch_id: &str, apply: impl FnOnce(&mut BatchRecord)) {
+        if let Some(record) = self.batches.lock().unwrap().iter_mut().find(|record| record.status.batch_id == batch_id) {
+            apply(record);
+        }
+    }
+}
+
+fn run_result(run: u32, outcome: &BatchRunOutcome) -> BatchRunResult {
+    let (reason, period) = match outcome.stop {
+        Some(StopReason::Extinct) => (EndReason::Extinct, 0),
+        Some(StopReason::Cycle { period }) => (EndReason::Cycle, period as i64),
+        None => (EndReason::None, 0),
+    };
+    BatchRunResult {
+        run: run as i32,
+        seed: outcome.seed,
+        generations: outcome.generations as i64,
+        final_population: outcome.final_population as i64,
+        peak_population: outcome.peak_population as i64,
+        reason: reason as i32,
+        period,
+        elapsed_ms: outcome.elapsed.as_secs_f64() * 1000.0,
+    }
+}
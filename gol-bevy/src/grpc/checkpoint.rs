@@ -0,0 +1,107 @@
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use crate::resources::{HistoryRetention, SimulationData, SimulationSnapshots, SimulationStore};
+
+/// Write-behind checkpointing of published snapshots into the persistent store.
+///
+/// Nothing on the request path touches the store. Every mutation already
+/// publishes a fresh snapshot `Arc`, so a checkpoint pass only has to compare
+/// each current snapshot with the one it last saved to find what changed. It
+/// also drops simulations that were deleted and compacts the history of every
+/// simulation it writes.
+pub struct Checkpointer {
+    store: Arc<SimulationStore>,
+    snapshots: Arc<SimulationSnapshots>,
+    retention: HistoryRetention,
+    saved: Mutex<HashMap<String, Arc<SimulationData>>>,
+}
+
+impl Checkpointer {
+    /// Snapshots already published are treated as saved, since at startup they
+    /// were just restored from the store.
+    pub fn new(store: Arc<SimulationStore>, snapshots: Arc<SimulationSnapshots>, retention: HistoryRetention) -> Self {
+        let saved = snapshots.all().into_iter()
+            .map(|snapshot| (snapshot.id.clone(), snapshot))
+            .collect();
+        Self {
+            store,
+            snapshots,
+            retention,
+            saved: Mutex::new(saved),
+        }
+    }
+
+    /// Persist every simulation changed since the last pass, returning how many
+    /// were written.
+    pub fn checkpoint(&self) -> io::Result<usize> {
+        let mut saved = self.saved.lock().unwrap();
+        let current = self.snapshots.all();
+        let mut written = 0;
+
+        for snapshot in &current {
+            let unchanged = saved.get(&snapshot.id).is_some_and(|last| Arc::ptr_eq(last, snapshot));
+            if unchanged {
+                continue;
+            }
+            self.store.checkpoint(snapshot)?;
+            self.store.compact(&snapshot.id, &self.retention)?;
+            saved.insert(snapshot.id.clone(), snapshot.clone());
+            written += 1;
+        }
+
+        let live: HashSet<&str> = current.iter().map(|snapshot| snapshot.id.as_str()).collect();
+        for id in self.store.ids()? {
+            if !live.contains(id.as_str()) {
+                self.store.remove(&id)?;
+                saved.remove(&id);
+            }
+        }
+
+        Ok(written)
+    }
+
+    /// Final pass at shutdown: checkpoint outstanding changes and flush to disk.
+    pub fn finish(&self) -> io::Result<usize> {
+        let written = self.checkpoint()?;
+        self.store.flush()?;
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::Simulations;
+
+    #[test]
+    fn test_only_changed_simulations_are_written() {
+        let dir = std::env::temp_dir().join(format!("gol-checkpoint-{}", uuid::Uuid::new_v4()));
+        let store = Arc::new(SimulationStore::open(&dir).unwrap());
+        let snapshots = Arc::new(SimulationSnapshots::new());
+        let checkpointer = Checkpointer::new(store.clone(), snapshots.clone(), HistoryRetention::default());
+
+        let mut simulations = Simulations::new();
+        let first = simulations.create_simulation(10, 10, None);
+        let second = simulations.create_simulation(10, 10, None);
+        snapshots.publish(simulations.get_simulation_mut(&first).unwrap());
+        snapshots.publish(simulations.get_simulation_mut(&second).unwrap());
+        assert_eq!(checkpointer.checkpoint().unwrap(), 2);
+        assert_eq!(checkpointer.checkpoint().unwrap(), 0);
+
+        let simulation = simulations.get_simulation_mut(&first).unwrap();
+        simulation.step();
+        snapshots.publish(simulation);
+        assert_eq!(checkpointer.checkpoint().unwrap(), 1);
+
+        snapshots.remove(&second);
+        checkpointer.finish().unwrap();
+        let ids = store.ids().unwrap();
+        assert!(ids.contains(&first) && !ids.contains(&second));
+
+        drop(checkpointer);
+        drop(store);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,113 @@
+//! Simulations stepped in per-simulation ECS worlds.
+//!
+//! With the `ecs` backend the service still keeps every simulation's
+//! settings, replay log and snapshots, but creates, steps, reads and deletes
+//! its cells through a [`SimulationCommandSender`], so a world of the app's
+//! [`SimulationManager`](crate::resources::SimulationManager) does the
+//! stepping. The cells a step reaches are copied back to the simulation, so
+//! every other RPC sees them. Other RPCs change only the simulation; its
+//! world is rebuilt from the simulation's cells before its next step.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tonic::{Code, Status};
+
+use crate::resources::{GridBoundary, SimulationCommandSender, SimulationData};
+
+/// What stepping a simulation in its world needs from it, taken under the
+/// lock so the steps can run without it.
+pub struct EcsWorld {
+    id: String,
+    bounds: (i32, i32),
+    /// Cells to rebuild the world from, when it no longer matches the
+    /// simulation.
+    rebuild: Option<Vec<(i32, i32)>>,
+}
+
+pub struct EcsBackend {
+    sender: SimulationCommandSender,
+    /// Version each simulation was published at when its world last matched it.
+    synced: Mutex<HashMap<String, u64>>,
+}
+
+impl EcsBackend {
+    pub fn new(sender: SimulationCommandSender) -> Self {
+        Self { sender, synced: Mutex::new(HashMap::new()) }
+    }
+
+    /// Whether the ECS systems step `simulation` as the chunked engine would:
+    /// B3/S23 on a clipped grid at full speed, with nothing that has to look
+    /// at each generation. The rest are stepped by the chunked engine.
+    pub fn can_step(simulation: &SimulationData) -> bool {
+        simulation.rule.is_conway()
+            && simulation.rule_changes.is_empty()
+            && simulation.boundary == GridBoundary::Clip
+            && simulation.turmite_rule.is_none()
+            && simulation.breakpoints.is_empty()
+            && simulation.watchpoints.is_empty()
+            && simulation.pacing.is_turbo()
+    }
+
+    /// Give a published simulation a world holding its cells, replacing any
+    /// world it had.
+    pub async fn create(&self, simulation: &SimulationData) -> Result<(), Status> {
+        self.build(&simulation.id, simulation.get_live_cells(), (simulation.width, simulation.height)).await?;
+        self.mark_synced(simulation);
+        Ok(())
+    }
+
+    /// What [`step`](Self::step) needs to step `simulation`: just its id
+    /// while its world matches it, else the cells to rebuild the world from.
+    pub fn world(&self, simulation: &SimulationData) -> EcsWorld {
+        let rebuild = (!self.is_synced(simulation)).then(|| simulation.get_live_cells());
+        EcsWorld { id: simulation.id.clone(), bounds: (simulation.width, simulation.height), rebuild }
+    }
+
+    /// Step `steps` generations in a simulation's world, rebuilding the
+    /// world first if the simulation changed since it last matched, and
+    /// return the cells reached. Copy them back to the simulation and call
+    /// [`mark_synced`](Self::mark_synced) once the result is published.
+    pub async fn step(&self, world: EcsWorld, steps: u32) -> Result<Vec<(i32, i32)>, Status> {
+        if let Some(cells) = world.rebuild {
+            self.build(&world.id, cells, world.bounds).await?;
+        }
+        self.sender.step(&world.id, steps as u64).await.ok_or_else(backend_unavailable)?;
+        let (_, cells) = self.sender.get_cells(&world.id).await.ok_or_else(backend_unavailable)?;
+        Ok(cells)
+    }
+
+    /// The simulation's live cells as its world has them, or `None` when the
+    /// world does not match the published simulation.
+    pub async fn cells(&self, simulation: &SimulationData) -> Option<Vec<(i32, i32)>> {
+        if !self.is_synced(simulation) {
+            return None;
+        }
+        self.sender.get_cells(&simulation.id).await.map(|(_, cells)| cells)
+    }
+
+    pub async fn delete(&self, id: &str) {
+        self.synced.lock().unwrap().remove(id);
+        self.sender.delete(id).await;
+    }
+
+    /// Record that the simulation, as just published, matches its world.
+    pub fn mark_synced(&self, simulation: &SimulationData) {
+        self.synced.lock().unwrap().insert(simulation.id.clone(), simulation.version);
+    }
+
+    async fn build(&self, id: &str, cells: Vec<(i32, i32)>, bounds: (i32, i32)) -> Result<(), Status> {
+        self.sender.delete(id).await;
+        if !self.sender.create(id, cells, Some(bounds)).await {
+            return Err(backend_unavailable());
+        }
+        Ok(())
+    }
+
+    fn is_synced(&self, simulation: &SimulationData) -> bool {
+        self.synced.lock().unwrap().get(&simulation.id) == Some(&simulation.version)
+    }
+}
+
+fn backend_unavailable() -> Status {
+    Status::new(Code::Unavailable, "The ECS backend is not running")
+}
@@ -0,0 +1,149 @@
+//! Statuses carrying google.rpc error details.
+//!
+//! Every error the service returns for a known cause attaches a structured
+//! detail alongside the message, so clients can branch on the cause instead of
+//! matching message text: `BadRequest` field violations for invalid arguments,
+//! `ResourceInfo` for missing simulations, jobs, batches and checkpoints and
+//! for taken simulation ids,
+//! `QuotaFailure` for rate limits and `PreconditionFailure` for edits made
+//! against a stale simulation version.
+
+use tonic::{Code, Status};
+use tonic_types::{ErrorDetails, FieldViolation, StatusExt};
+
+use crate::resources::StaleVersion;
+
+/// `ResourceInfo.resource_type` reported for simulations.
+pub const SIMULATION_RESOURCE: &str = "game_of_life.Simulation";
+
+/// `ResourceInfo.resource_type` reported for background step jobs.
+pub const JOB_RESOURCE: &str = "game_of_life.Job";
+
+/// `ResourceInfo.resource_type` reported for batch runs.
+pub const BATCH_RESOURCE: &str = "game_of_life.Batch";
+
+/// `ResourceInfo.resource_type` reported for checkpoints, named
+/// `<simulation id>/<checkpoint name>`.
+pub const CHECKPOINT_RESOURCE: &str = "game_of_life.Checkpoint";
+
+/// `PreconditionFailure` violation type for a simulation that changed since
+/// the version a call expected.
+pub const STALE_VERSION: &str = "STALE_VERSION";
+
+pub fn simulation_not_found(id: &str) -> Status {
+    not_found(SIMULATION_RESOURCE, id, "Simulation not found")
+}
+
+/// A simulation id asked for that another simulation already has.
+pub fn simulation_exists(id: &str) -> Status {
+    let message = "Simulation already exists";
+    Status::with_error_details(
+        Code::AlreadyExists,
+        message,
+        ErrorDetails::with_resource_info(SIMULATION_RESOURCE, id, "", message),
+    )
+}
+
+pub fn job_not_found(job_id: &str) -> Status {
+    not_found(JOB_RESOURCE, job_id, "Job not found")
+}
+
+pub fn batch_not_found(batch_id: &str) -> Status {
+    not_found(BATCH_RESOURCE, batch_id, "Batch not found")
+}
+
+pub fn checkpoint_not_found(id: &str, name: &str) -> Status {
+    not_found(CHECKPOINT_RESOURCE, &format!("{}/{}", id, name), "Checkpoint not found")
+}
+
+/// A single invalid request field.
+pub fn invalid_field(field: &str, description: &str) -> Status {
+    invalid_fields(description, vec![FieldViolation::new(field, description)])
+}
+
+/// An invalid request, with one violation per offending field.
+pub fn invalid_fields(message: &str, violations: Vec<FieldViolation>) -> Status {
+    Status::with_error_details(Code::InvalidArgument, message, ErrorDetails::with_bad_request(violations))
+}
+
+/// A request refused by a quota; `subject` names what ran out, e.g. `simulation:<id>`.
+pub fn quota_exceeded(subject: &str, description: &str) -> Status {
+    Status::with_error_details(
+        Code::ResourceExhausted,
+        description,
+        ErrorDetails::with_quota_failure_violation(subject, description),
+    )
+}
+
+impl From<StaleVersion> for Status {
+    fn from(stale: StaleVersion) -> Self {
+        let description = format!("Simulation is at version {}, not the expected {}", stale.version, stale.expected);
+        Status::with_error_details(
+            Code::FailedPrecondition,
+            &description,
+            ErrorDetails::with_precondition_failure_violation(STALE_VERSION, format!("simulation:{}", stale.id), &description),
+        )
+    }
+}
+
+fn not_found(resource_type: &str, name: &str, message: &str) -> Status {
+    Status::with_error_details(
+        Code::NotFound,
+        message,
+        ErrorDetails::with_resource_info(resource_type, name, "", message),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_not_found_carries_resource_info() {
+        let status = simulation_not_found("abc");
+        assert_eq!(status.code(), Code::NotFound);
+
+        let details = status.get_error_details();
+        let info = details.resource_info().expect("resource info attached");
+        assert_eq!(info.resource_type, SIMULATION_RESOURCE);
+        assert_eq!(info.resource_name, "abc");
+    }
+
+    #[test]
+    fn test_invalid_fields_carry_violations() {
+        let status = invalid_fields(
+            "Width and height must be positive",
+            vec![
+                FieldViolation::new("width", "must be positive"),
+                FieldViolation::new("height", "must be positive"),
+            ],
+        );
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        let details = status.get_error_details();
+        let fields: Vec<_> = details.bad_request().unwrap().field_violations.iter()
+            .map(|violation| violation.field.as_str())
+            .collect();
+        assert_eq!(fields, vec!["width", "height"]);
+    }
+
+    #[test]
+    fn test_quota_exceeded_carries_subject() {
+        let status = quota_exceeded("client:10.0.0.1", "Rate limit exceeded");
+        assert_eq!(status.code(), Code::ResourceExhausted);
+
+        let details = status.get_error_details();
+        let violation = &details.quota_failure().unwrap().violations[0];
+        assert_eq!(violation.subject, "client:10.0.0.1");
+    }
+
+    #[test]
+    fn test_stale_version_carries_precondition_failure() {
+        let status = Status::from(StaleVersion { id: "abc".to_string(), version: 7, expected: 5 });
+        assert_eq!(status.code(), Code::FailedPrecondition);
+
+        let details = status.get_error_details();
+        let violation = &details.precondition_failure().unwrap().violations[0];
+        assert_eq!((violation.r#type.as_str(), violation.subject.as_str()), (STALE_VERSION, "simulation:abc"));
+    }
+}
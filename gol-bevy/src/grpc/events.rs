@@ -0,0 +1,130 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+use crate::grpc::proto::{Cell, EndReason, SimulationEvent, SimulationEventType};
+use crate::resources::{CellChange, SimulationData, StopReason};
+
+/// Events a watcher may fall behind by before it starts missing some.
+pub const EVENT_BUFFER: usize = 1024;
+
+/// Cloneable server-wide feed of simulation lifecycle events for WatchEvents.
+///
+/// Publishing never blocks: with nobody watching the event is dropped, and a
+/// watcher more than [`EVENT_BUFFER`] events behind skips the oldest ones.
+#[derive(Clone, Debug)]
+pub struct EventBus {
+    sender: broadcast::Sender<SimulationEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        Self { sender }
+    }
+    
+    pub fn publish(&self, event: SimulationEvent) {
+        let _ = self.sender.send(event);
+    }
+    
+    /// Events published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<SimulationEvent> {
+        self.sender.subscribe()
+    }
+}
+
+/// An event of `kind` for `simulation` in its current state; callers fill in
+/// the fields particular to the kind.
+pub fn simulation_event(kind: SimulationEventType, simulation: &SimulationData) -> SimulationEvent {
+    SimulationEvent {
+        r#type: kind as i32,
+        id: simulation.id.clone(),
+        generation: simulation.generation as i64,
+        live_cells: simulation.get_live_cell_count(),
+        timestamp_ms: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as i64,
+        version: simulation.version,
+        ..Default::default()
+    }
+}
+
+/// The STABILIZED event for an unattended run stopping on `reason`.
+pub fn stabilized_event(simulation: &SimulationData, reason: StopReason) -> SimulationEvent {
+    let (reason, period) = match reason {
+        StopReason::Extinct => (EndReason::Extinct, 0),
+        StopReason::Cycle { period } => (EndReason::Cycle, period as i64),
+    };
+    SimulationEvent {
+        reason: reason as i32,
+        period,
+        ..simulation_event(SimulationEventType::Stabilized, simulation)
+    }
+}
+
+/// The CELLS_CHANGED event for the cells `changed` under `simulation`'s
+/// watchpoint `index`, each with its new state.
+pub fn cells_changed_event(simulation: &SimulationData, index: usize, changed: &[CellChange]) -> SimulationEvent {
+    let cells = changed.iter()
+        .map(|&((x, y), state)| Cell {
+            x: x.into(),
+            y: y.into(),
+            alive: state == 1,
+            state: state as i32,
+            color: simulation.cells.color(x, y) as i32,
+            ..Default::default()
+        })
+        .collect();
+    SimulationEvent {
+        watchpoint: index as i32,
+        cells,
+        ..simulation_event(SimulationEventType::CellsChanged, simulation)
+    }
+}
+
+/// The ERROR event for something that went wrong stepping `simulation`,
+/// which carries on regardless.
+pub fn error_event(simulation: &SimulationData, message: String) -> SimulationEvent {
+    SimulationEvent {
+        message,
+        ..simulation_event(SimulationEventType::Error, simulation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::Simulations;
+    
+    #[tokio::test]
+    async fn test_subscribers_see_later_events() {
+        let bus = EventBus::new();
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(10, 10, None);
+        let simulation = simulations.get_simulation(&id).unwrap();
+        
+        // Nobody is watching yet
+        bus.publish(simulation_event(SimulationEventType::Created, simulation));
+        let mut receiver = bus.subscribe();
+        bus.publish(stabilized_event(simulation, StopReason::Cycle { period: 2 }));
+        
+        let event = receiver.recv().await.unwrap();
+        assert_eq!(event.r#type(), SimulationEventType::Stabilized);
+        assert_eq!(event.id, id);
+        assert_eq!(event.reason(), EndReason::Cycle);
+        assert_eq!(event.period, 2);
+        assert!(receiver.try_recv().is_err());
+    }
+    
+    #[test]
+    fn test_error_events_carry_their_message() {
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(10, 10, None);
+        let event = error_event(simulations.get_simulation(&id).unwrap(), "Script error: boom".to_string());
+        assert_eq!(event.r#type(), SimulationEventType::Error);
+        assert_eq!((event.id, event.message), (id, "Script error: boom".to_string()));
+    }
+}
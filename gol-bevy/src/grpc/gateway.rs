@@ -0,0 +1,591 @@
+//! Gateway serving one endpoint in front of several backend servers.
+//!
+//! Each backend is named by a label. New simulations go to the backend named
+//! by the request's `x-gol-backend` metadata, or else to the one with the
+//! fewest simulations routed to it. Every later request for a simulation
+//! goes to the backend holding it. The gateway learns where simulations live
+//! from the responses it forwards, and from ListSimulations on every backend
+//! when it meets an unknown id, so it keeps no state worth persisting.
+//! Bearer tokens are passed on, so backends configured with the clients'
+//! tokens charge their quotas to the client rather than to the gateway.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::RwLock;
+use std::time::Instant;
+use tokio_stream::{Stream, StreamExt, StreamMap};
+use tonic::client::Grpc;
+use tonic::codec::{Codec, EncodeBuf, Encoder, ProstCodec};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::metadata::MetadataMap;
+use tonic::transport::Channel;
+use tonic::{Code, Request, Response, Status, Streaming};
+
+use crate::grpc::errors;
+use crate::grpc::quota::AUTHORIZATION_METADATA_KEY;
+use crate::grpc::service::build_info;
+use crate::grpc::proto::game_of_life_service_client::GameOfLifeServiceClient;
+use crate::grpc::proto::game_of_life_service_server::GameOfLifeService;
+use crate::grpc::proto::*;
+
+/// Request metadata naming the backend a new simulation is created on.
+pub const BACKEND_METADATA_KEY: &str = "x-gol-backend";
+
+/// A backend server the gateway forwards to.
+#[derive(Clone)]
+pub struct GatewayBackend {
+    pub label: String,
+    pub address: String,
+    channel: Channel,
+    client: GameOfLifeServiceClient<Channel>,
+}
+
+impl GatewayBackend {
+    /// A backend at `address`, e.g. `[::1]:50051` or `http://host:50052`.
+    /// Connects on first use, so backends may start after the gateway.
+    pub fn new(label: &str, address: &str) -> Result<Self, String> {
+        let uri = if address.contains("://") { address.to_string() } else { format!("http://{}", address) };
+        let channel = Channel::from_shared(uri)
+            .map_err(|error| format!("invalid address for backend {}: {}", label, error))?
+            .connect_lazy();
+        Ok(Self {
+            label: label.to_string(),
+            address: address.to_string(),
+            client: GameOfLifeServiceClient::new(channel.clone()),
+            channel,
+        })
+    }
+    
+    /// Parse `label=address`, as given to `--backend`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (label, address) = spec.split_once('=')
+            .filter(|(label, address)| !label.is_empty() && !address.is_empty())
+            .ok_or_else(|| format!("expected <label>=<address>, got '{}'", spec))?;
+        Self::new(label, address)
+    }
+    
+    /// UploadPattern with chunks that may end in a client's stream error,
+    /// which fails the request to the backend rather than ending it.
+    async fn upload_pattern<S>(&self, chunks: S) -> Result<Response<LoadPatternResponse>, Status>
+    where
+        S: Stream<Item = Result<PatternChunk, Status>> + Send + 'static,
+    {
+        let mut grpc = Grpc::new(self.channel.clone());
+        grpc.ready().await.map_err(|error| Status::unavailable(format!("Backend {} is not ready: {}", self.label, error)))?;
+        let path = PathAndQuery::from_static("/game_of_life.GameOfLifeService/UploadPattern");
+        grpc.client_streaming(Request::new(chunks), path, ForwardedUploadCodec::default()).await
+    }
+}
+
+/// Encodes forwarded upload chunks. A client's stream error becomes an
+/// encoding error, which resets the backend's request with an error; a
+/// request that just ended would have the backend commit a partial upload.
+#[derive(Default)]
+struct ForwardedUploadCodec(ProstCodec<PatternChunk, LoadPatternResponse>);
+
+impl Codec for ForwardedUploadCodec {
+    type Encode = Result<PatternChunk, Status>;
+    type Decode = LoadPatternResponse;
+    type Encoder = ForwardedUploadEncoder;
+    type Decoder = <ProstCodec<PatternChunk, LoadPatternResponse> as Codec>::Decoder;
+    
+    fn encoder(&mut self) -> Self::Encoder {
+        ForwardedUploadEncoder(self.0.encoder())
+    }
+    
+    fn decoder(&mut self) -> Self::Decoder {
+        self.0.decoder()
+    }
+}
+
+struct ForwardedUploadEncoder(<ProstCodec<PatternChunk, LoadPatternResponse> as Codec>::Encoder);
+
+impl Encoder for ForwardedUploadEncoder {
+    type Item = Result<PatternChunk, Status>;
+    type Error = Status;
+    
+    fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+        self.0.encode(item?, dst)
+    }
+}
+
+pub struct GatewayService {
+    backends: Vec<GatewayBackend>,
+    /// Index into `backends` of every simulation seen, by id.
+    routes: RwLock<HashMap<String, usize>>,
+    /// Index into `backends` of every background job seen, by job id.
+    jobs: RwLock<HashMap<String, usize>>,
+    /// Index into `backends` of every batch seen, by batch id.
+    batches: RwLock<HashMap<String, usize>>,
+    started_at: Instant,
+}
+
+/// Forward a request naming its simulation in `id` to the backend holding it.
+macro_rules! forward {
+    ($self:ident, $request:ident, $method:ident) => {{
+        let (metadata, _, req) = $request.into_parts();
+        let backend = $self.route(&req.id).await?;
+        $self.client(backend).$method(forwarded(&metadata, req)).await
+    }};
+}
+
+/// `message` as a backend request carrying the client's bearer token, if any.
+fn forwarded<T>(metadata: &MetadataMap, message: T) -> Request<T> {
+    let mut request = Request::new(message);
+    if let Some(token) = metadata.get(AUTHORIZATION_METADATA_KEY) {
+        request.metadata_mut().insert(AUTHORIZATION_METADATA_KEY, token.clone());
+    }
+    request
+}
+
+impl GatewayService {
+    pub fn new(backends: Vec<GatewayBackend>) -> Self {
+        Self {
+            backends,
+            routes: RwLock::new(HashMap::new()),
+            jobs: RwLock::new(HashMap::new()),
+            batches: RwLock::new(HashMap::new()),
+            started_at: Instant::now(),
+        }
+    }
+    
+    pub fn backends(&self) -> &[GatewayBackend] {
+        &self.backends
+    }
+    
+    fn client(&self, backend: usize) -> GameOfLifeServiceClient<Channel> {
+        self.backends[backend].client.clone()
+    }
+    
+    fn remember(&self, id: &str, backend: usize) {
+        self.routes.write().unwrap().insert(id.to_string(), backend);
+    }
+    
+    /// The backend a new simulation goes to: the one `x-gol-backend` names,
+    /// else the one with the fewest known simulations. Errors describe an
+    /// unknown label.
+    fn placement(&self, metadata: &MetadataMap) -> Result<usize, String> {
+        if let Some(label) = metadata.get(BACKEND_METADATA_KEY) {
+            let label = label.to_str().unwrap_or_default();
+            return self.backends.iter().position(|backend| backend.label == label).ok_or_else(|| {
+                let labels: Vec<&str> = self.backends.iter().map(|backend| backend.label.as_str()).collect();
+                format!("Unknown backend '{}', expected one of {}", label, labels.join(", "))
+            });
+        }
+        let routes = self.routes.read().unwrap();
+        let mut counts = vec![0; self.backends.len()];
+        for &backend in routes.values() {
+            counts[backend] += 1;
+        }
+        Ok((0..self.backends.len()).min_by_key(|&backend| counts[backend]).unwrap_or(0))
+    }
+    
+    /// The backend holding simulation `id`, asking every backend when the
+    /// id is not known yet.
+    async fn route(&self, id: &str) -> Result<usize, Status> {
+        if let Some(&backend) = self.routes.read().unwrap().get(id) {
+            return Ok(backend);
+        }
+        self.list_all().await;
+        self.routes.read().unwrap().get(id).copied().ok_or_else(|| errors::simulation_not_found(id))
+    }
+    
+    /// Every backend's simulations, learning their routes. Backends that do
+    /// not answer are left out, with their errors returned alongside.
+    async fn list_all(&self) -> (Vec<SimulationSummary>, Vec<Status>) {
+        let mut simulations = Vec::new();
+        let mut failures = Vec::new();
+        for backend in 0..self.backends.len() {
+            match self.client(backend).list_simulations(ListSimulationsRequest {}).await {
+                Ok(response) => {
+                    let listed = response.into_inner().simulations;
+                    let mut routes = self.routes.write().unwrap();
+                    routes.retain(|_, routed| *routed != backend);
+                    routes.extend(listed.iter().map(|simulation| (simulation.id.clone(), backend)));
+                    simulations.extend(listed);
+                }
+                Err(status) => failures.push(status),
+            }
+        }
+        (simulations, failures)
+    }
+    
+    /// The backend running the job or batch `id`, found in `routes` or by
+    /// trying each in turn for ids not seen yet, along with its answer.
+    async fn locate<T, F, Fut>(&self, routes: &RwLock<HashMap<String, usize>>, id: &str, not_found: fn(&str) -> Status, call: F) -> Result<Response<T>, Status>
+    where
+        F: Fn(GameOfLifeServiceClient<Channel>) -> Fut,
+        Fut: Future<Output = Result<Response<T>, Status>>,
+    {
+        let known = routes.read().unwrap().get(id).copied();
+        if let Some(backend) = known {
+            return call(self.client(backend)).await;
+        }
+        for backend in 0..self.backends.len() {
+            match call(self.client(backend)).await {
+                Err(status) if status.code() == Code::NotFound => continue,
+                result => {
+                    routes.write().unwrap().insert(id.to_string(), backend);
+                    return result;
+                }
+            }
+        }
+        Err(not_found(id))
+    }
+}
+
+#[tonic::async_trait]
+impl GameOfLifeService for GatewayService {
+    /// Healthy while every backend answers; counts and rates are summed,
+    /// every backend's automata listed once and its engines under its label.
+    async fn get_status(&self, _request: Request<StatusRequest>) -> Result<Response<StatusResponse>, Status> {
+        let mut response = StatusResponse {
+            status: "healthy".to_string(),
+            version: "1.0.0".to_string(),
+            implementation: "gateway".to_string(),
+            uptime_seconds: self.started_at.elapsed().as_secs() as i64,
+            build: Some(build_info()),
+            ..Default::default()
+        };
+        for backend in 0..self.backends.len() {
+            match self.client(backend).get_status(StatusRequest {}).await {
+                Ok(status) => {
+                    let status = status.into_inner();
+                    response.simulation_count += status.simulation_count;
+                    response.total_chunks += status.total_chunks;
+                    response.generations_per_second += status.generations_per_second;
+                    response.memory_bytes += status.memory_bytes;
+                    response.active_simulation_count += status.active_simulation_count;
+                    response.total_live_cells += status.total_live_cells;
+                    let label = &self.backends[backend].label;
+                    for (engine, version) in status.engine_versions {
+                        response.engine_versions.insert(format!("{}/{}", label, engine), version);
+                    }
+                    response.limits = response.limits.or(status.limits);
+                    for automaton in status.automata {
+                        if !response.automata.contains(&automaton) {
+                            response.automata.push(automaton);
+                        }
+                    }
+                }
+                Err(_) => response.status = "degraded".to_string(),
+            }
+        }
+        Ok(Response::new(response))
+    }
+    
+    async fn get_stats(&self, request: Request<StatsRequest>) -> Result<Response<StatsResponse>, Status> {
+        forward!(self, request, get_stats)
+    }
+    
+    async fn get_census(&self, request: Request<CensusRequest>) -> Result<Response<CensusResponse>, Status> {
+        forward!(self, request, get_census)
+    }
+    
+    async fn run_benchmark(&self, request: Request<BenchmarkRequest>) -> Result<Response<BenchmarkResponse>, Status> {
+        let backend = self.placement(request.metadata())
+            .map_err(|message| errors::invalid_field(BACKEND_METADATA_KEY, &message))?;
+        self.client(backend).run_benchmark(request.into_inner()).await
+    }
+    
+    async fn create_simulation(&self, request: Request<CreateSimulationRequest>) -> Result<Response<SimulationResponse>, Status> {
+        let backend = self.placement(request.metadata())
+            .map_err(|message| errors::invalid_field(BACKEND_METADATA_KEY, &message))?;
+        let (metadata, _, req) = request.into_parts();
+        let response = self.client(backend).create_simulation(forwarded(&metadata, req)).await?;
+        self.remember(&response.get_ref().id, backend);
+        Ok(response)
+    }
+    
+    async fn get_simulation(&self, request: Request<GetSimulationRequest>) -> Result<Response<SimulationResponse>, Status> {
+        forward!(self, request, get_simulation)
+    }
+    
+    async fn get_cells(&self, request: Request<GetCellsRequest>) -> Result<Response<GetCellsResponse>, Status> {
+        forward!(self, request, get_cells)
+    }
+    
+    async fn get_summary(&self, request: Request<GetSummaryRequest>) -> Result<Response<SummaryResponse>, Status> {
+        forward!(self, request, get_summary)
+    }
+    
+    async fn update_simulation(&self, request: Request<UpdateSimulationRequest>) -> Result<Response<SimulationResponse>, Status> {
+        forward!(self, request, update_simulation)
+    }
+    
+    async fn delete_simulation(&self, request: Request<DeleteSimulationRequest>) -> Result<Response<DeleteResponse>, Status> {
+        let id = request.get_ref().id.clone();
+        let response = forward!(self, request, delete_simulation)?;
+        if response.get_ref().success {
+            self.routes.write().unwrap().remove(&id);
+        }
+        Ok(response)
+    }
+    
+    /// Every reachable backend's simulations, oldest first.
+    async fn list_simulations(&self, _request: Request<ListSimulationsRequest>) -> Result<Response<ListSimulationsResponse>, Status> {
+        let (mut simulations, failures) = self.list_all().await;
+        if failures.len() == self.backends.len() && let Some(failure) = failures.into_iter().next() {
+            return Err(failure);
+        }
+        simulations.sort_by(|a, b| a.created_at_ms.cmp(&b.created_at_ms).then_with(|| a.id.cmp(&b.id)));
+        Ok(Response::new(ListSimulationsResponse { simulations }))
+    }
+    
+    /// The copy is made on the same backend as the original.
+    async fn clone_simulation(&self, request: Request<CloneSimulationRequest>) -> Result<Response<SimulationResponse>, Status> {
+        let (metadata, _, req) = request.into_parts();
+        let backend = self.route(&req.id).await?;
+        let response = self.client(backend).clone_simulation(forwarded(&metadata, req)).await?;
+        self.remember(&response.get_ref().id, backend);
+        Ok(response)
+    }
+    
+    async fn rename_simulation(&self, request: Request<RenameSimulationRequest>) -> Result<Response<SimulationResponse>, Status> {
+        forward!(self, request, rename_simulation)
+    }
+    
+    async fn replay_simulation(&self, request: Request<ReplayRequest>) -> Result<Response<ReplayResponse>, Status> {
+        forward!(self, request, replay_simulation)
+    }
+    
+    /// Both sides must be on the same backend.
+    async fn get_diff(&self, request: Request<DiffRequest>) -> Result<Response<DiffResponse>, Status> {
+        let req = request.into_inner();
+        let a = self.route(&req.a.as_ref().map(|target| target.id.clone()).unwrap_or_default()).await?;
+        let b = self.route(&req.b.as_ref().map(|target| target.id.clone()).unwrap_or_default()).await?;
+        if a != b {
+            return Err(Status::new(
+                Code::FailedPrecondition,
+                format!("Simulations on backends {} and {} cannot be compared", self.backends[a].label, self.backends[b].label),
+            ));
+        }
+        self.client(a).get_diff(req).await
+    }
+    
+    async fn get_history(&self, request: Request<HistoryRequest>) -> Result<Response<HistoryResponse>, Status> {
+        forward!(self, request, get_history)
+    }
+    
+    async fn get_statistics(&self, request: Request<StatisticsRequest>) -> Result<Response<StatisticsResponse>, Status> {
+        forward!(self, request, get_statistics)
+    }
+    
+    async fn get_heatmap(&self, request: Request<HeatmapRequest>) -> Result<Response<HeatmapResponse>, Status> {
+        forward!(self, request, get_heatmap)
+    }
+    
+    async fn verify_simulation(&self, request: Request<VerifyRequest>) -> Result<Response<VerifyResponse>, Status> {
+        forward!(self, request, verify_simulation)
+    }
+    
+    async fn step_simulation(&self, request: Request<StepSimulationRequest>) -> Result<Response<StepResponse>, Status> {
+        let (metadata, _, req) = request.into_parts();
+        let backend = self.route(&req.id).await?;
+        let response = self.client(backend).step_simulation(forwarded(&metadata, req)).await?;
+        if !response.get_ref().job_id.is_empty() {
+            self.jobs.write().unwrap().insert(response.get_ref().job_id.clone(), backend);
+        }
+        Ok(response)
+    }
+    
+    async fn load_pattern(&self, request: Request<LoadPatternRequest>) -> Result<Response<LoadPatternResponse>, Status> {
+        forward!(self, request, load_pattern)
+    }
+    
+    /// Routed by the first chunk, which names the simulation. A chunk that
+    /// fails to arrive ends the forwarded upload there.
+    async fn upload_pattern(&self, request: Request<Streaming<PatternChunk>>) -> Result<Response<LoadPatternResponse>, Status> {
+        let mut chunks = request.into_inner();
+        let first = chunks.next().await.transpose()?
+            .ok_or_else(|| errors::invalid_field("id", "Upload contained no chunks"))?;
+        let backend = self.route(&first.id).await?;
+        
+        let (failed, mut failure) = tokio::sync::oneshot::channel();
+        let forwarded = async_stream::stream! {
+            yield Ok(first);
+            let status = loop {
+                match chunks.next().await {
+                    Some(Ok(chunk)) => yield Ok(chunk),
+                    Some(Err(status)) => break status,
+                    None => return,
+                }
+            };
+            let _ = failed.send(status.clone());
+            yield Err(status);
+        };
+        let response = self.backends[backend].upload_pattern(forwarded).await;
+        // The client is told of its own error rather than the backend's
+        failure.try_recv().map_or(response, Err)
+    }
+    
+    async fn clear_region(&self, request: Request<ClearRegionRequest>) -> Result<Response<RegionResponse>, Status> {
+        forward!(self, request, clear_region)
+    }
+    
+    async fn fill_region(&self, request: Request<FillRegionRequest>) -> Result<Response<RegionResponse>, Status> {
+        forward!(self, request, fill_region)
+    }
+    
+    async fn export_pattern(&self, request: Request<ExportPatternRequest>) -> Result<Response<ExportPatternResponse>, Status> {
+        forward!(self, request, export_pattern)
+    }
+    
+    async fn set_rule(&self, request: Request<SetRuleRequest>) -> Result<Response<SetRuleResponse>, Status> {
+        forward!(self, request, set_rule)
+    }
+    
+    async fn set_pacing(&self, request: Request<SetPacingRequest>) -> Result<Response<PacingResponse>, Status> {
+        forward!(self, request, set_pacing)
+    }
+
+    async fn set_webhooks(&self, request: Request<SetWebhooksRequest>) -> Result<Response<WebhooksResponse>, Status> {
+        forward!(self, request, set_webhooks)
+    }
+    
+    async fn set_breakpoints(&self, request: Request<SetBreakpointsRequest>) -> Result<Response<BreakpointsResponse>, Status> {
+        forward!(self, request, set_breakpoints)
+    }
+
+    async fn set_watchpoints(&self, request: Request<SetWatchpointsRequest>) -> Result<Response<WatchpointsResponse>, Status> {
+        forward!(self, request, set_watchpoints)
+    }
+
+    async fn set_annotations(&self, request: Request<SetAnnotationsRequest>) -> Result<Response<AnnotationsResponse>, Status> {
+        forward!(self, request, set_annotations)
+    }
+
+    async fn get_annotations(&self, request: Request<GetAnnotationsRequest>) -> Result<Response<AnnotationsResponse>, Status> {
+        forward!(self, request, get_annotations)
+    }
+    
+    async fn create_checkpoint(&self, request: Request<CreateCheckpointRequest>) -> Result<Response<CheckpointsResponse>, Status> {
+        forward!(self, request, create_checkpoint)
+    }
+    
+    async fn list_checkpoints(&self, request: Request<ListCheckpointsRequest>) -> Result<Response<CheckpointsResponse>, Status> {
+        forward!(self, request, list_checkpoints)
+    }
+    
+    async fn delete_checkpoint(&self, request: Request<DeleteCheckpointRequest>) -> Result<Response<CheckpointsResponse>, Status> {
+        forward!(self, request, delete_checkpoint)
+    }
+    
+    async fn restore_checkpoint(&self, request: Request<RestoreCheckpointRequest>) -> Result<Response<SimulationResponse>, Status> {
+        forward!(self, request, restore_checkpoint)
+    }
+    
+    async fn get_job_status(&self, request: Request<JobStatusRequest>) -> Result<Response<JobStatusResponse>, Status> {
+        let req = request.into_inner();
+        self.locate(&self.jobs, &req.job_id.clone(), errors::job_not_found, |mut client| {
+            let req = req.clone();
+            async move { client.get_job_status(req).await }
+        }).await
+    }
+    
+    async fn cancel_job(&self, request: Request<CancelJobRequest>) -> Result<Response<JobStatusResponse>, Status> {
+        let req = request.into_inner();
+        self.locate(&self.jobs, &req.job_id.clone(), errors::job_not_found, |mut client| {
+            let req = req.clone();
+            async move { client.cancel_job(req).await }
+        }).await
+    }
+    
+    /// Placed like a new simulation: on the backend `x-gol-backend` names,
+    /// else the one with the fewest simulations.
+    async fn submit_batch(&self, request: Request<BatchSpec>) -> Result<Response<BatchStatus>, Status> {
+        let backend = self.placement(request.metadata())
+            .map_err(|message| errors::invalid_field(BACKEND_METADATA_KEY, &message))?;
+        let response = self.client(backend).submit_batch(request.into_inner()).await?;
+        self.batches.write().unwrap().insert(response.get_ref().batch_id.clone(), backend);
+        Ok(response)
+    }
+    
+    /// Every backend's batches, oldest first. Backends that do not answer
+    /// are left out unless none do.
+    async fn list_batches(&self, _request: Request<ListBatchesRequest>) -> Result<Response<ListBatchesResponse>, Status> {
+        let mut batches = Vec::new();
+        let mut failure = None;
+        for backend in 0..self.backends.len() {
+            match self.client(backend).list_batches(ListBatchesRequest {}).await {
+                Ok(response) => {
+                    let listed = response.into_inner().batches;
+                    self.batches.write().unwrap().extend(listed.iter().map(|batch| (batch.batch_id.clone(), backend)));
+                    batches.extend(listed);
+                }
+                Err(status) => failure = Some(status),
+            }
+        }
+        if batches.is_empty() && let Some(failure) = failure {
+            return Err(failure);
+        }
+        batches.sort_by_key(|batch| batch.submitted_at_ms);
+        Ok(Response::new(ListBatchesResponse { batches }))
+    }
+    
+    async fn get_batch_results(&self, request: Request<BatchResultsRequest>) -> Result<Response<BatchResultsResponse>, Status> {
+        let req = request.into_inner();
+        self.locate(&self.batches, &req.batch_id.clone(), errors::batch_not_found, |mut client| {
+            let req = req.clone();
+            async move { client.get_batch_results(req).await }
+        }).await
+    }
+    
+    async fn cancel_batch(&self, request: Request<CancelBatchRequest>) -> Result<Response<BatchStatus>, Status> {
+        let req = request.into_inner();
+        self.locate(&self.batches, &req.batch_id.clone(), errors::batch_not_found, |mut client| {
+            let req = req.clone();
+            async move { client.cancel_batch(req).await }
+        }).await
+    }
+    
+    async fn run_experiment(&self, request: Request<ExperimentRequest>) -> Result<Response<ExperimentResponse>, Status> {
+        forward!(self, request, run_experiment)
+    }
+    
+    type StreamSimulationStream = Pin<Box<dyn Stream<Item = Result<SimulationUpdate, Status>> + Send>>;
+    
+    async fn stream_simulation(&self, request: Request<StreamRequest>) -> Result<Response<Self::StreamSimulationStream>, Status> {
+        let updates = forward!(self, request, stream_simulation)?;
+        Ok(Response::new(Box::pin(updates.into_inner()) as Self::StreamSimulationStream))
+    }
+
+    type WatchEventsStream = Pin<Box<dyn Stream<Item = Result<SimulationEvent, Status>> + Send>>;
+
+    /// Each backend's events merged. Watching everything skips backends that
+    /// do not answer; watching chosen simulations asks just their backends.
+    async fn watch_events(&self, request: Request<WatchEventsRequest>) -> Result<Response<Self::WatchEventsStream>, Status> {
+        let req = request.into_inner();
+        let mut requests: Vec<Option<WatchEventsRequest>> = vec![None; self.backends.len()];
+        if req.ids.is_empty() {
+            requests.fill(Some(WatchEventsRequest { ids: Vec::new() }));
+        }
+        for id in req.ids {
+            let backend = self.route(&id).await?;
+            requests[backend].get_or_insert_with(Default::default).ids.push(id);
+        }
+        
+        let mut merged = StreamMap::new();
+        let mut failure = None;
+        for (backend, request) in requests.into_iter().enumerate() {
+            let Some(request) = request else { continue };
+            let watch_all = request.ids.is_empty();
+            match self.client(backend).watch_events(request).await {
+                Ok(events) => {
+                    merged.insert(backend, events.into_inner());
+                }
+                Err(status) if watch_all => failure = Some(status),
+                Err(status) => return Err(status),
+            }
+        }
+        if merged.is_empty() && let Some(failure) = failure {
+            return Err(failure);
+        }
+        let events = async_stream::stream! {
+            while let Some((_, event)) = merged.next().await {
+                yield event;
+            }
+        };
+        Ok(Response::new(Box::pin(events) as Self::WatchEventsStream))
+    }
+}
@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::grpc::proto::{JobState, JobStatusResponse};
+use crate::grpc::{ShutdownSignal, StepQueue};
+use crate::resources::StopReason;
+
+#[derive(Debug, Clone)]
+struct JobRecord {
+    simulation_id: String,
+    steps_total: u32,
+    steps_completed: u32,
+    state: JobState,
+    generation: u64,
+    live_cells: i64,
+    message: String,
+    /// When the job completed, failed or was cancelled.
+    finished_at: Option<Instant>,
+}
+
+/// How long a finished job's status stays available.
+const FINISHED_JOB_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Most finished jobs kept; the longest finished are dropped first.
+const MAX_FINISHED_JOBS: usize = 1000;
+
+/// Background stepping jobs for StepSimulation requests too large to run inline.
+///
+/// A job feeds its steps through the shared [`StepQueue`] in slices, so it is
+/// paced and coalesced like any other step request and never holds the
+/// simulations lock for the whole run. Progress is recorded after each slice
+/// and cancellation, explicit or by server shutdown, is checked between slices.
+/// Jobs finish early once the simulation reaches its generation limit or its
+/// grid dies out or starts repeating, which pauses it for later jobs too.
+/// Finished jobs are forgotten after [`FINISHED_JOB_TTL`], or sooner once
+/// more than [`MAX_FINISHED_JOBS`] have finished.
+pub struct JobRegistry {
+    step_queue: Arc<StepQueue>,
+    shutdown: ShutdownSignal,
+    slice_steps: u32,
+    jobs: Mutex<HashMap<String, JobRecord>>,
+    cancellations: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl JobRegistry {
+    pub fn new(step_queue: Arc<StepQueue>, shutdown: ShutdownSignal, slice_steps: u32) -> Self {
+        Self {
+            step_queue,
+            shutdown,
+            slice_steps: slice_steps.max(1),
+            jobs: Mutex::new(HashMap::new()),
+            cancellations: Mutex::new(HashMap::new()),
+        }
+    }
+    
+    /// Start stepping a simulation in the background, returning the new job id.
+    pub fn spawn_step_job(self: &Arc<Self>, simulation_id: &str, steps: u32, generation: u64, live_cells: i64) -> String {
+        let job_id = Uuid::new_v4().to_string();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        
+        let mut jobs = self.jobs.lock().unwrap();
+        evict_finished(&mut jobs, Instant::now());
+        jobs.insert(job_id.clone(), JobRecord {
+            simulation_id: simulation_id.to_string(),
+            steps_total: steps,
+            steps_completed: 0,
+            state: JobState::JobPending,
+            generation,
+            live_cells,
+            message: String::new(),
+            finished_at: None,
+        });
+        drop(jobs);
+        self.cancellations.lock().unwrap().insert(job_id.clone(), cancelled.clone());
+        
+        let registry = self.clone();
+        let job = job_id.clone();
+        let simulation_id = simulation_id.to_string();
+        tokio::spawn(async move {
+            registry.run(&job, &simulation_id, steps, &cancelled).await;
+            registry.cancellations.lock().unwrap().remove(&job);
+        });
+        
+        job_id
+    }
+    
+    pub fn status(&self, job_id: &str) -> Option<JobStatusResponse> {
+        self.status_at(job_id, Instant::now())
+    }
+    
+    fn status_at(&self, job_id: &str, now: Instant) -> Option<JobStatusResponse> {
+        let mut jobs = self.jobs.lock().unwrap();
+        evict_finished(&mut jobs, now);
+        jobs.get(job_id).map(|record| to_response(job_id, record))
+    }
+    
+    /// Request cancellation. The job stops before its next slice; steps already
+    /// applied are kept.
+    pub fn cancel(&self, job_id: &str) -> Option<JobStatusResponse> {
+        if let Some(cancelled) = self.cancellations.lock().unwrap().get(job_id) {
+            cancelled.store(true, Ordering::SeqCst);
+        }
+        self.status(job_id)
+    }
+    
+    async fn run(&self, job_id: &str, simulation_id: &str, steps: u32, cancelled: &AtomicBool) {
+        self.update(job_id, |record| record.state = JobState::JobRunning);
+        
+        let mut completed = 0;
+        while completed < steps {
+            if cancelled.load(Ordering::SeqCst) || self.shutdown.is_triggered() {
+                self.update(job_id, |record| {
+                    record.state = JobState::JobCancelled;
+                    record.message = format!("Cancelled after {} of {} steps", completed, steps);
+                });
+                return;
+            }
+            
+            let slice = self.slice_steps.min(steps - completed);
+            match self.step_queue.step_unattended(simulation_id, slice).await {
+                Ok(outcome) => {
+                    completed += outcome.steps;
+                    let breakpoint = outcome.breakpoint.as_ref().map(|condition| format!("a breakpoint fired ({})", condition))
+                        .or_else(|| outcome.watchpoint.map(|region| format!(
+                            "watched cells changed ({}x{} at {}, {})", region.width, region.height, region.x, region.y,
+                        )));
+                    let stopped = match outcome.auto_pause.map(|pause| pause.reason) {
+                        Some(StopReason::Extinct) => Some("the population died out".to_string()),
+                        Some(StopReason::Cycle { period: 1 }) => Some("the grid stopped changing".to_string()),
+                        Some(StopReason::Cycle { period }) => Some(format!("the grid repeats every {} generations", period)),
+                        None if breakpoint.is_some() => breakpoint,
+                        None if outcome.completed => Some("the generation limit was reached".to_string()),
+                        None => None,
+                    }.filter(|_| completed < steps);
+                    self.update(job_id, |record| {
+                        record.steps_completed = completed;
+                        record.generation = outcome.generation;
+                        record.live_cells = outcome.live_cells;
+                        if let Some(cause) = &stopped {
+                            record.state = JobState::JobCompleted;
+                            record.message = format!("Stopped after {} of {} steps: {}", completed, steps, cause);
+                        }
+                    });
+                    if stopped.is_some() {
+                        return;
+                    }
+                }
+                Err(status) => {
+                    self.update(job_id, |record| {
+                        record.state = JobState::JobFailed;
+                        record.message = status.message().to_string();
+                    });
+                    return;
+                }
+            }
+        }
+        
+        self.update(job_id, |record| record.state = JobState::JobCompleted);
+    }
+    
+    fn update(&self, job_id: &str, apply: impl FnOnce(&mut JobRecord)) {
+        if let Some(record) = self.jobs.lock().unwrap().get_mut(job_id) {
+            apply(record);
+            if record.finished_at.is_none() && is_finished(record.state) {
+                record.finished_at = Some(Instant::now());
+            }
+        }
+    }
+}
+
+fn is_finished(state: JobState) -> bool {
+    matches!(state, JobState::JobCompleted | JobState::JobFailed | JobState::JobCancelled)
+}
+
+/// Drop finished jobs past their TTL, then the longest finished of any
+/// beyond [`MAX_FINISHED_JOBS`].
+fn evict_finished(jobs: &mut HashMap<String, JobRecord>, now: Instant) {
+    jobs.retain(|_, record| record.finished_at.is_none_or(|finished| now.duration_since(finished) < FINISHED_JOB_TTL));
+    let mut finished: Vec<_> = jobs.iter()
+        .filter_map(|(job_id, record)| record.finished_at.map(|finished| (finished, job_id.clone())))
+        .collect();
+    if finished.len() > MAX_FINISHED_JOBS {
+        finished.sort();
+        for (_, job_id) in &finished[..finished.len() - MAX_FINISHED_JOBS] {
+            jobs.remove(job_id);
+        }
+    }
+}
+
+fn to_response(job_id: &str, record: &JobRecord) -> JobStatusResponse {
+    JobStatusResponse {
+        job_id: job_id.to_string(),
+        simulation_id: record.simulation_id.clone(),
+        state: record.state as i32,
+        steps_total: record.steps_total as i64,
+        steps_completed: record.steps_completed as i64,
+        generation: record.generation as i64,
+        live_cells: record.live_cells,
+        message: record.message.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::Mutex as AsyncMutex;
+    use crate::grpc::EventBus;
+    use crate::resources::{ReplayLogs, SimulationSnapshots, Simulations};
+    
+    fn registry() -> JobRegistry {
+        let step_queue = StepQueue::new(
+            Arc::new(AsyncMutex::new(Simulations::new())),
+            Arc::new(SimulationSnapshots::new()),
+            Arc::new(ReplayLogs::new()),
+            Arc::new(rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap()),
+            EventBus::new(),
+            None,
+            10,
+        );
+        JobRegistry::new(Arc::new(step_queue), ShutdownSignal::new(), 10)
+    }
+    
+    fn record(state: JobState, finished_at: Option<Instant>) -> JobRecord {
+        JobRecord {
+            simulation_id: "sim".to_string(),
+            steps_total: 10,
+            steps_completed: 0,
+            state,
+            generation: 0,
+            live_cells: 0,
+            message: String::new(),
+            finished_at,
+        }
+    }
+    
+    #[test]
+    fn test_finished_jobs_are_evicted_after_their_ttl() {
+        let registry = registry();
+        let start = Instant::now();
+        {
+            let mut jobs = registry.jobs.lock().unwrap();
+            jobs.insert("running".to_string(), record(JobState::JobRunning, None));
+            jobs.insert("completed".to_string(), record(JobState::JobCompleted, Some(start)));
+            jobs.insert("cancelled".to_string(), record(JobState::JobCancelled, Some(start + Duration::from_secs(60))));
+        }
+        
+        assert!(registry.status_at("completed", start + FINISHED_JOB_TTL - Duration::from_secs(1)).is_some());
+        let later = start + FINISHED_JOB_TTL;
+        assert!(registry.status_at("completed", later).is_none());
+        assert!(registry.status_at("cancelled", later).is_some());
+        
+        // Jobs still running are kept however old
+        assert!(registry.status_at("running", later + FINISHED_JOB_TTL * 10).is_some());
+        assert!(registry.status_at("cancelled", later + FINISHED_JOB_TTL * 10).is_none());
+    }
+    
+    #[test]
+    fn test_longest_finished_jobs_are_evicted_beyond_the_limit() {
+        let registry = registry();
+        let start = Instant::now();
+        {
+            let mut jobs = registry.jobs.lock().unwrap();
+            for index in 0..MAX_FINISHED_JOBS + 2 {
+                let finished = start + Duration::from_millis(index as u64);
+                jobs.insert(format!("job-{}", index), record(JobState::JobFailed, Some(finished)));
+            }
+        }
+        
+        assert!(registry.status_at("job-0", start).is_none());
+        assert!(registry.status_at("job-1", start).is_none());
+        assert!(registry.status_at("job-2", start).is_some());
+        assert_eq!(registry.jobs.lock().unwrap().len(), MAX_FINISHED_JOBS);
+    }
+}
@@ -1,7 +1,37 @@
+pub mod access_log;
+pub mod batches;
+#[cfg(feature = "sled-store")]
+pub mod checkpoint;
+pub mod ecs_backend;
+pub mod errors;
+pub mod events;
+pub mod gateway;
+pub mod jobs;
+pub mod plugin;
+pub mod quota;
+pub mod rate_limit;
+pub mod registry;
 pub mod service;
+pub mod shutdown;
+pub mod step_queue;
+pub mod webhook;
 pub mod proto {
     tonic::include_proto!("game_of_life");
 }
 
+pub use access_log::AccessLogLayer;
+pub use batches::BatchRegistry;
+#[cfg(feature = "sled-store")]
+pub use checkpoint::Checkpointer;
+pub use ecs_backend::EcsBackend;
+pub use events::EventBus;
+pub use gateway::{GatewayBackend, GatewayService};
+pub use jobs::JobRegistry;
+pub use plugin::{with_compression, GrpcServer, GrpcServerPlugin, GrpcServerState, GrpcServerStatus};
+pub use quota::{QuotaExceeded, QuotaTracker};
+pub use rate_limit::{RateLimitExceeded, RateLimitInterceptor, RateLimiter};
+pub use registry::{RegistryEntry, ServerRegistration};
 pub use service::GameOfLifeServiceImpl;
+pub use shutdown::ShutdownSignal;
+pub use step_queue::{StepOutcome, StepQueue};
 pub use proto::*;
\ No newline at end of file
@@ -0,0 +1,620 @@
+//! Bevy plugin owning the gRPC server.
+//!
+//! The plugin owns the tokio runtime and the tonic server task, binds the
+//! listener while the app is being built so an unusable address fails at
+//! startup, mirrors the server's state into the [`GrpcServerStatus`] resource,
+//! rebinds the listener after transient failures, and exits the app once the
+//! server has shut down. Dropping the app waits for the server to drain.
+
+use bevy::prelude::*;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::codec::CompressionEncoding;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::Server;
+
+use crate::grpc::proto::game_of_life_service_server::GameOfLifeServiceServer;
+use crate::grpc::{AccessLogLayer, GameOfLifeServiceImpl, RateLimitInterceptor, ServerRegistration, ShutdownSignal};
+use crate::resources::{process_simulation_commands_system, registered_automata, ScriptHooks, ServerConfig, SimulationBackend, SimulationCommandSender, SimulationManager, SimulationSnapshots, Simulations};
+#[cfg(feature = "sled-store")]
+use crate::grpc::Checkpointer;
+#[cfg(feature = "sled-store")]
+use crate::resources::SimulationStore;
+#[cfg(feature = "sled-store")]
+use std::path::Path;
+
+/// Listener failures closer together than this count toward the restart limit.
+const RESTART_RESET_AFTER: Duration = Duration::from_secs(60);
+
+/// Longest pause between listener restart attempts.
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GrpcServerState {
+    Starting,
+    Listening(SocketAddr),
+    Restarting { attempt: u32, error: String },
+    Failed(String),
+    Stopped,
+}
+
+/// Snapshot of the server state, refreshed at the start of every frame.
+#[derive(Resource, Clone, Debug)]
+pub struct GrpcServerStatus {
+    pub state: GrpcServerState,
+}
+
+/// Handle to the running server.
+#[derive(Resource)]
+pub struct GrpcServer {
+    runtime: Runtime,
+    task: Option<JoinHandle<()>>,
+    pub shutdown: ShutdownSignal,
+    pub simulations: Arc<tokio::sync::Mutex<Simulations>>,
+    /// Must be republished after mutating `simulations` directly, or reads and
+    /// checkpoints will not see the change.
+    pub snapshots: Arc<SimulationSnapshots>,
+    /// Routes commands by simulation id to the per-simulation ECS worlds of
+    /// the app's [`SimulationManager`], which steps them once a frame. The
+    /// service sends through it with the `ecs` backend.
+    pub ecs: SimulationCommandSender,
+    state: Arc<Mutex<GrpcServerState>>,
+    /// Announces the server to clients until it is dropped with the server.
+    registration: Option<ServerRegistration>,
+}
+
+impl GrpcServer {
+    pub fn state(&self) -> GrpcServerState {
+        self.state.lock().unwrap().clone()
+    }
+
+    /// The registry file announcing this server, if it registered.
+    pub fn registry_path(&self) -> Option<&std::path::Path> {
+        self.registration.as_ref().map(ServerRegistration::path)
+    }
+
+    /// Block until the server task has drained and saved.
+    pub fn join(&mut self) {
+        if let Some(task) = self.task.take() {
+            let _ = self.runtime.block_on(task);
+        }
+    }
+}
+
+impl Drop for GrpcServer {
+    /// The app is torn down after its exit event; finish draining before the runtime goes.
+    fn drop(&mut self) {
+        self.shutdown.trigger();
+        self.join();
+    }
+}
+
+pub struct GrpcServerPlugin {
+    config: ServerConfig,
+}
+
+impl GrpcServerPlugin {
+    pub fn new(config: ServerConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Plugin for GrpcServerPlugin {
+    fn build(&self, app: &mut App) {
+        let runtime = Runtime::new().expect("failed to start tokio runtime for gRPC server");
+        let state = Arc::new(Mutex::new(GrpcServerState::Starting));
+
+        let (store, restored, store_error) = match open_store(&self.config) {
+            Ok((store, restored)) => (store, restored, None),
+            Err(error) => (None, Simulations::new(), Some(error)),
+        };
+        let (script, script_error) = match load_script(&self.config) {
+            Ok(script) => (script, None),
+            Err(error) => (None, Some(error)),
+        };
+        let automata = registered_automata();
+        if !automata.is_empty() {
+            println!("Registered automata: {}", automata.join(", "));
+        }
+        let manager = SimulationManager::new();
+        let mut service = GameOfLifeServiceImpl::with_script(&self.config, restored, script);
+        if self.config.backend == SimulationBackend::Ecs {
+            println!("Stepping B3/S23 simulations in per-simulation ECS worlds");
+            service = service.with_ecs_backend(manager.sender());
+        }
+        let service = Arc::new(service);
+        let shutdown = service.shutdown.clone();
+
+        // Bind before the app starts so a bad address is reported immediately
+        let listener = match store_error.or(script_error) {
+            Some(error) => Err(error),
+            None => runtime.block_on(TcpListener::bind(self.config.listen_addr))
+                .map_err(|error| format!("failed to bind {}: {}", self.config.listen_addr, error)),
+        };
+        let mut registration = None;
+        let task = match listener {
+            Ok(listener) => {
+                let addr = listener.local_addr().unwrap_or(self.config.listen_addr);
+                println!("Starting gRPC Game of Life server on {}", addr);
+                *state.lock().unwrap() = GrpcServerState::Listening(addr);
+                registration = self.config.registry_dir.as_deref().and_then(|dir| {
+                    ServerRegistration::register(dir, addr)
+                        .inspect_err(|error| eprintln!("Failed to register in {}: {}", dir.display(), error))
+                        .ok()
+                });
+                let server = run_server(listener, service.clone(), self.config.clone(), state.clone());
+                Some(runtime.spawn(with_checkpoints(server, store, service.clone(), self.config.clone())))
+            }
+            Err(message) => {
+                eprintln!("gRPC server {}", message);
+                *state.lock().unwrap() = GrpcServerState::Failed(message);
+                shutdown.trigger();
+                None
+            }
+        };
+
+        // Translate SIGINT/SIGTERM into a server-wide shutdown
+        let signal_shutdown = shutdown.clone();
+        runtime.spawn(async move {
+            wait_for_termination_signal().await;
+            println!("Shutdown signal received, draining gRPC server");
+            signal_shutdown.trigger();
+        });
+
+        let status = GrpcServerStatus { state: state.lock().unwrap().clone() };
+        app.insert_resource(GrpcServer {
+            runtime,
+            task,
+            shutdown,
+            simulations: service.simulations.clone(),
+            snapshots: service.snapshots.clone(),
+            ecs: manager.sender(),
+            state,
+            registration,
+        })
+        .insert_resource(status)
+        .insert_resource(manager)
+        .add_systems(First, sync_server_status)
+        .add_systems(Update, process_simulation_commands_system)
+        .add_systems(Last, exit_on_shutdown);
+    }
+}
+
+fn sync_server_status(server: Res<GrpcServer>, mut status: ResMut<GrpcServerStatus>) {
+    let state = server.state();
+    if status.state != state {
+        status.state = state;
+    }
+}
+
+fn exit_on_shutdown(server: Res<GrpcServer>, mut exit: EventWriter<AppExit>) {
+    if server.shutdown.is_triggered() {
+        exit.send(match server.state() {
+            GrpcServerState::Failed(_) => AppExit::error(),
+            _ => AppExit::Success,
+        });
+    }
+}
+
+/// Serve until shutdown, rebinding the listener when it fails on its own.
+async fn run_server(
+    listener: TcpListener,
+    service: Arc<GameOfLifeServiceImpl>,
+    config: ServerConfig,
+    state: Arc<Mutex<GrpcServerState>>,
+) {
+    let shutdown = service.shutdown.clone();
+    let mut listener = Some(listener);
+    let mut attempt = 0;
+
+    loop {
+        let started = Instant::now();
+        let error = match listener.take() {
+            Some(listener) => match serve(listener, service.clone(), &config).await {
+                _ if shutdown.is_triggered() => break,
+                Ok(()) => "listener closed unexpectedly".to_string(),
+                Err(error) => error.to_string(),
+            },
+            None => match TcpListener::bind(config.listen_addr).await {
+                Ok(rebound) => {
+                    *state.lock().unwrap() = GrpcServerState::Listening(config.listen_addr);
+                    listener = Some(rebound);
+                    continue;
+                }
+                Err(error) => format!("failed to rebind {}: {}", config.listen_addr, error),
+            },
+        };
+
+        if started.elapsed() >= RESTART_RESET_AFTER {
+            attempt = 0;
+        }
+        attempt += 1;
+
+        if attempt > config.max_listener_restarts {
+            eprintln!("gRPC server giving up after {} restarts: {}", config.max_listener_restarts, error);
+            *state.lock().unwrap() = GrpcServerState::Failed(error);
+            shutdown.trigger();
+            break;
+        }
+
+        eprintln!("gRPC listener failed ({}), restart attempt {}", error, attempt);
+        *state.lock().unwrap() = GrpcServerState::Restarting { attempt, error };
+
+        let backoff = Duration::from_millis(100 * 2u64.pow(attempt.min(6))).min(MAX_RESTART_BACKOFF);
+        tokio::select! {
+            _ = tokio::time::sleep(backoff) => {}
+            _ = shutdown.triggered() => break,
+        }
+    }
+
+    if let Some(path) = &config.persist_path {
+        match service.simulations.lock().await.save_to_file(path) {
+            Ok(()) => println!("Saved simulations to {}", path.display()),
+            Err(error) => eprintln!("Failed to save simulations to {}: {}", path.display(), error),
+        }
+    }
+
+    let mut state = state.lock().unwrap();
+    if !matches!(*state, GrpcServerState::Failed(_)) {
+        *state = GrpcServerState::Stopped;
+    }
+}
+
+/// `server` accepting gzip and zstd requests, and compressing responses and
+/// stream messages for clients that accept either. Clients that ask for
+/// neither get uncompressed messages, so compression is chosen per call.
+pub fn with_compression<T>(server: GameOfLifeServiceServer<T>) -> GameOfLifeServiceServer<T> {
+    server
+        .accept_compressed(CompressionEncoding::Gzip)
+        .accept_compressed(CompressionEncoding::Zstd)
+        .send_compressed(CompressionEncoding::Zstd)
+        .send_compressed(CompressionEncoding::Gzip)
+}
+
+/// Serve one listener until it fails or shutdown drains it.
+async fn serve(
+    listener: TcpListener,
+    service: Arc<GameOfLifeServiceImpl>,
+    config: &ServerConfig,
+) -> Result<(), tonic::transport::Error> {
+    let shutdown = service.shutdown.clone();
+    let listener_shutdown = shutdown.clone();
+    let rate_limit = RateLimitInterceptor::new(service.rate_limiter.clone());
+    let serve = Server::builder()
+        .layer(AccessLogLayer::new(config.access_log))
+        .add_service(InterceptedService::new(with_compression(GameOfLifeServiceServer::from_arc(service)), rate_limit))
+        .serve_with_incoming_shutdown(
+            TcpListenerStream::new(listener),
+            async move { listener_shutdown.triggered().await },
+        );
+    tokio::pin!(serve);
+
+    // Stop accepting new RPCs on shutdown, then give in-flight ones a grace period
+    tokio::select! {
+        result = &mut serve => result,
+        _ = shutdown.triggered() => {
+            match tokio::time::timeout(config.shutdown_grace, &mut serve).await {
+                Ok(result) => result,
+                Err(_) => {
+                    eprintln!("In-flight RPCs still running after {:?}, exiting anyway", config.shutdown_grace);
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+/// Load simulations saved by a previous run, if persistence is configured.
+fn restore_simulations(config: &ServerConfig) -> Simulations {
+    let Some(path) = config.persist_path.as_ref().filter(|path| path.exists()) else {
+        return Simulations::new();
+    };
+
+    match Simulations::load_from_file(path) {
+        Ok(simulations) => {
+            println!("Restored {} simulations from {}", simulations.simulations.len(), path.display());
+            simulations
+        }
+        Err(error) => {
+            eprintln!("Failed to restore simulations from {}: {}", path.display(), error);
+            Simulations::new()
+        }
+    }
+}
+
+/// Load the script named by `script_path`, if any.
+fn load_script(config: &ServerConfig) -> Result<Option<Arc<ScriptHooks>>, String> {
+    let Some(path) = &config.script_path else {
+        return Ok(None);
+    };
+    let script = ScriptHooks::load(path)?;
+    println!("Loaded script {}", path.display());
+    Ok(Some(Arc::new(script)))
+}
+
+#[cfg(feature = "sled-store")]
+type Store = Arc<SimulationStore>;
+
+#[cfg(not(feature = "sled-store"))]
+type Store = std::convert::Infallible;
+
+/// Open the persistent store in `data_dir` and restore from it, or fall back
+/// to the shutdown save file when no data directory is configured.
+#[cfg(feature = "sled-store")]
+fn open_store(config: &ServerConfig) -> Result<(Option<Store>, Simulations), String> {
+    let Some(dir) = &config.data_dir else {
+        return Ok((None, restore_simulations(config)));
+    };
+
+    let open = |dir: &Path| {
+        let store = SimulationStore::open(dir)?;
+        let simulations = store.load()?;
+        Ok::<_, std::io::Error>((store, simulations))
+    };
+    match open(dir) {
+        Ok((store, simulations)) => {
+            println!("Restored {} simulations from store in {}", simulations.simulations.len(), dir.display());
+            Ok((Some(Arc::new(store)), simulations))
+        }
+        Err(error) => Err(format!("failed to open simulation store in {}: {}", dir.display(), error)),
+    }
+}
+
+#[cfg(not(feature = "sled-store"))]
+fn open_store(config: &ServerConfig) -> Result<(Option<Store>, Simulations), String> {
+    match &config.data_dir {
+        Some(dir) => Err(format!("cannot use data directory {}: built without the sled-store feature", dir.display())),
+        None => Ok((None, restore_simulations(config))),
+    }
+}
+
+/// Run the server while checkpointing changed simulations to the store every
+/// `checkpoint_interval`, then take a final checkpoint once in-flight RPCs
+/// have drained.
+#[cfg(feature = "sled-store")]
+fn with_checkpoints(
+    server: impl Future<Output = ()>,
+    store: Option<Store>,
+    service: Arc<GameOfLifeServiceImpl>,
+    config: ServerConfig,
+) -> impl Future<Output = ()> {
+    // Seed the checkpointer now, before any RPC can publish a change it would
+    // otherwise mistake for already saved
+    let checkpointer = store.map(|store| Arc::new(Checkpointer::new(store, service.snapshots.clone(), config.history_retention)));
+
+    async move {
+        let Some(checkpointer) = checkpointer else {
+            return server.await;
+        };
+
+        let periodic = async {
+            let mut ticker = tokio::time::interval(config.checkpoint_interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let pass = checkpointer.clone();
+                if let Ok(Err(error)) = tokio::task::spawn_blocking(move || pass.checkpoint()).await {
+                    eprintln!("Checkpoint failed: {}", error);
+                }
+            }
+        };
+        tokio::select! {
+            _ = server => {}
+            _ = periodic => {}
+        }
+
+        match tokio::task::spawn_blocking(move || checkpointer.finish()).await {
+            Ok(Ok(written)) => println!("Checkpointed {} simulations to store", written),
+            Ok(Err(error)) => eprintln!("Final checkpoint failed: {}", error),
+            Err(error) => eprintln!("Final checkpoint failed: {}", error),
+        }
+    }
+}
+
+#[cfg(not(feature = "sled-store"))]
+fn with_checkpoints<F: Future<Output = ()>>(
+    server: F,
+    _store: Option<Store>,
+    _service: Arc<GameOfLifeServiceImpl>,
+    _config: ServerConfig,
+) -> F {
+    server
+}
+
+async fn wait_for_termination_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate = signal(SignalKind::terminate()).expect("failed to install SIGTERM handler");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local_config() -> ServerConfig {
+        ServerConfig {
+            listen_addr: "127.0.0.1:0".parse().unwrap(),
+            shutdown_grace: Duration::from_millis(200),
+            ..ServerConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_plugin_starts_listening_and_stops_on_shutdown() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(GrpcServerPlugin::new(local_config()));
+        app.update();
+
+        let status = app.world().resource::<GrpcServerStatus>().clone();
+        assert!(matches!(status.state, GrpcServerState::Listening(_)));
+
+        app.world().resource::<GrpcServer>().shutdown.trigger();
+        app.update();
+
+        let mut server = app.world_mut().resource_mut::<GrpcServer>();
+        server.join();
+        assert_eq!(server.state(), GrpcServerState::Stopped);
+    }
+
+    #[test]
+    fn test_server_registers_while_listening() {
+        let dir = std::env::temp_dir().join(format!("gol-plugin-registry-{}", uuid::Uuid::new_v4()));
+        let config = ServerConfig {
+            registry_dir: Some(dir.clone()),
+            ..local_config()
+        };
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(GrpcServerPlugin::new(config));
+
+        let server = app.world().resource::<GrpcServer>();
+        let GrpcServerState::Listening(addr) = server.state() else {
+            panic!("server is not listening");
+        };
+        let path = dir.join(format!("bevy-{}.json", addr.port()));
+        assert_eq!(server.registry_path(), Some(path.as_path()));
+        assert!(path.exists());
+
+        drop(app);
+        assert!(!path.exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_server_answers_compressed_and_plain_clients() {
+        use crate::grpc::proto::game_of_life_service_client::GameOfLifeServiceClient;
+        use crate::grpc::proto::{CreateSimulationRequest, GetSimulationRequest};
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(GrpcServerPlugin::new(local_config()));
+        let server = app.world().resource::<GrpcServer>();
+        let GrpcServerState::Listening(addr) = server.state() else {
+            panic!("server is not listening");
+        };
+
+        server.runtime.block_on(async {
+            for encoding in [None, Some(CompressionEncoding::Gzip), Some(CompressionEncoding::Zstd)] {
+                let mut client = GameOfLifeServiceClient::connect(format!("http://{}", addr)).await.unwrap();
+                if let Some(encoding) = encoding {
+                    client = client.send_compressed(encoding).accept_compressed(encoding);
+                }
+                let created = client.create_simulation(CreateSimulationRequest {
+                    width: 100,
+                    height: 100,
+                    initial_pattern: "glider".to_string(),
+                    ..Default::default()
+                }).await.unwrap().into_inner();
+                let fetched = client.get_simulation(GetSimulationRequest { id: created.id.clone(), ..Default::default() })
+                    .await.unwrap();
+                let expected = encoding.map(|encoding| encoding.to_string());
+                let used = fetched.metadata().get("grpc-encoding").map(|value| value.to_str().unwrap().to_string());
+                assert_eq!(used, expected, "{:?}", encoding);
+                assert_eq!(fetched.into_inner().live_cells, created.live_cells);
+            }
+        });
+    }
+
+    #[cfg(feature = "sled-store")]
+    #[test]
+    fn test_simulations_survive_restart_with_data_dir() {
+        let dir = std::env::temp_dir().join(format!("gol-plugin-store-{}", uuid::Uuid::new_v4()));
+        let config = ServerConfig {
+            data_dir: Some(dir.clone()),
+            ..local_config()
+        };
+
+        let id = {
+            let mut app = App::new();
+            app.add_plugins(MinimalPlugins);
+            app.add_plugins(GrpcServerPlugin::new(config.clone()));
+            let mut server = app.world_mut().resource_mut::<GrpcServer>();
+            let id = server.runtime.block_on(async {
+                let service = server.simulations.clone();
+                let mut simulations = service.lock().await;
+                let id = simulations.create_simulation(16, 16, None);
+                server.snapshots.publish(simulations.get_simulation_mut(&id).unwrap());
+                id
+            });
+            server.shutdown.trigger();
+            server.join();
+            id
+        };
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(GrpcServerPlugin::new(config));
+        let server = app.world().resource::<GrpcServer>();
+        let restored = server.runtime.block_on(async { server.simulations.lock().await.get_simulation(&id).is_some() });
+        assert!(restored);
+
+        drop(app);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(not(feature = "sled-store"))]
+    #[test]
+    fn test_data_dir_without_store_feature_fails_startup() {
+        let config = ServerConfig {
+            data_dir: Some("/tmp/gol-unused".into()),
+            ..local_config()
+        };
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(GrpcServerPlugin::new(config));
+
+        let status = app.world().resource::<GrpcServerStatus>().clone();
+        assert!(matches!(status.state, GrpcServerState::Failed(_)));
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    #[test]
+    fn test_script_without_scripting_feature_fails_startup() {
+        let config = ServerConfig {
+            script_path: Some("/tmp/gol-unused.rhai".into()),
+            ..local_config()
+        };
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(GrpcServerPlugin::new(config));
+
+        let status = app.world().resource::<GrpcServerStatus>().clone();
+        assert!(matches!(status.state, GrpcServerState::Failed(_)));
+    }
+
+    #[test]
+    fn test_bind_failure_is_reported_at_startup() {
+        let occupied = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = ServerConfig {
+            listen_addr: occupied.local_addr().unwrap(),
+            ..local_config()
+        };
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(GrpcServerPlugin::new(config));
+
+        let status = app.world().resource::<GrpcServerStatus>().clone();
+        assert!(matches!(status.state, GrpcServerState::Failed(_)));
+        assert!(app.world().resource::<GrpcServer>().shutdown.is_triggered());
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tonic::{Request, Status};
+
+use crate::grpc::errors;
+use crate::resources::{ClientQuota, SimulationSnapshots};
+
+/// Request metadata carrying a client's token, as `Bearer <token>`.
+pub const AUTHORIZATION_METADATA_KEY: &str = "authorization";
+
+/// Span the steps-per-minute quota counts over.
+const STEP_WINDOW: Duration = Duration::from_secs(60);
+
+/// Characters of a token shown in quota errors.
+const TOKEN_PREFIX_CHARS: usize = 8;
+
+/// Tells apart tokens sharing a prefix without showing the rest.
+fn fingerprint(token: &str) -> u32 {
+    token.bytes().fold(0x811c9dc5u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x01000193))
+}
+
+/// A request refused by a client quota; surfaces as `RESOURCE_EXHAUSTED`
+/// with the client as the `QuotaFailure` subject.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaExceeded {
+    pub subject: String,
+    pub description: String,
+}
+
+impl From<QuotaExceeded> for Status {
+    fn from(exceeded: QuotaExceeded) -> Self {
+        errors::quota_exceeded(&exceeded.subject, &exceeded.description)
+    }
+}
+
+/// A request whose `authorization` header is not a configured token;
+/// surfaces as `UNAUTHENTICATED`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownToken;
+
+impl From<UnknownToken> for Status {
+    fn from(_: UnknownToken) -> Self {
+        Status::unauthenticated("Unknown client token")
+    }
+}
+
+/// Per-client limits on simulations, live cells and steps, so one client
+/// cannot monopolize a shared server.
+///
+/// A simulation belongs to the client that created or cloned it, and its
+/// live cells count against that client whoever adds them; simulations
+/// restored from disk belong to nobody. Steps count against the client that
+/// asks for them, over the last minute.
+pub struct QuotaTracker {
+    quota: ClientQuota,
+    /// Tokens requests may be charged to; `None` ignores tokens.
+    tokens: Option<HashSet<String>>,
+    /// Owning client of every simulation created since startup, by id.
+    owners: Mutex<HashMap<String, String>>,
+    /// Each client's step requests within the last [`STEP_WINDOW`].
+    steps: Mutex<HashMap<String, VecDeque<(Instant, u64)>>>,
+}
+
+impl QuotaTracker {
+    pub fn new(quota: ClientQuota, tokens: Option<Vec<String>>) -> Self {
+        Self {
+            quota,
+            tokens: tokens.map(|tokens| tokens.into_iter().collect()),
+            owners: Mutex::new(HashMap::new()),
+            steps: Mutex::new(HashMap::new()),
+        }
+    }
+    
+    /// Who a request is charged to: `token:<prefix>` for a known bearer
+    /// token, else `client:<ip>`, else `local` for in-process calls. When
+    /// tokens are configured, a request with any other `authorization`
+    /// header is refused as `UNAUTHENTICATED`, so made-up tokens cannot buy
+    /// fresh quotas.
+    pub fn client_key<T>(&self, request: &Request<T>) -> Result<String, UnknownToken> {
+        if let Some(tokens) = &self.tokens
+            && let Some(header) = request.metadata().get(AUTHORIZATION_METADATA_KEY)
+        {
+            let token = header.to_str().ok()
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .map(str::trim)
+                .filter(|token| tokens.contains(*token))
+                .ok_or(UnknownToken)?;
+            // Keyed by the whole token; only the prefix is ever shown
+            let prefix: String = token.chars().take(TOKEN_PREFIX_CHARS).collect();
+            return Ok(format!("token:{}…{:x}", prefix, fingerprint(token)));
+        }
+        Ok(match request.remote_addr() {
+            Some(peer) => format!("client:{}", peer.ip()),
+            None => "local".to_string(),
+        })
+    }
+    
+    /// Refuse a new simulation of `client`'s with `live_cells` cells when
+    /// it would exceed the client's simulation or live cell quota.
+    pub fn check_new_simulation(&self, client: &str, live_cells: u64, snapshots: &SimulationSnapshots) -> Result<(), QuotaExceeded> {
+        if let Some(max) = self.quota.max_simulations {
+            let owned = self.owners.lock().unwrap().values().filter(|owner| *owner == client).count();
+            if owned >= max {
+                return Err(exceeded(client, format!("Simulation quota of {} reached", max)));
+            }
+        }
+        self.check_client_cells(client, live_cells, snapshots)
+    }
+    
+    pub fn record_owner(&self, client: &str, id: &str) {
+        if self.quota.is_limited() {
+            self.owners.lock().unwrap().insert(id.to_string(), client.to_string());
+        }
+    }
+    
+    pub fn forget(&self, id: &str) {
+        self.owners.lock().unwrap().remove(id);
+    }
+    
+    /// Refuse adding `adding` live cells to simulation `id` when its owner
+    /// would exceed their live cell quota. Stepping checks with `0`, so a
+    /// client already over the quota cannot grow further.
+    pub fn check_cells(&self, id: &str, adding: u64, snapshots: &SimulationSnapshots) -> Result<(), QuotaExceeded> {
+        let owner = self.owners.lock().unwrap().get(id).cloned();
+        match owner {
+            Some(owner) => self.check_client_cells(&owner, adding, snapshots),
+            None => Ok(()),
+        }
+    }
+    
+    /// Charge `steps` generations to `client`, refusing them when the client
+    /// has already stepped its quota within the last minute.
+    pub fn take_steps(&self, client: &str, steps: u64) -> Result<(), QuotaExceeded> {
+        self.take_steps_at(client, steps, Instant::now())
+    }
+    
+    fn take_steps_at(&self, client: &str, steps: u64, now: Instant) -> Result<(), QuotaExceeded> {
+        let Some(max) = self.quota.max_steps_per_minute else {
+            return Ok(());
+        };
+        let mut clients = self.steps.lock().unwrap();
+        clients.retain(|_, taken| {
+            while taken.front().is_some_and(|&(at, _)| now.saturating_duration_since(at) >= STEP_WINDOW) {
+                taken.pop_front();
+            }
+            !taken.is_empty()
+        });
+        let taken = clients.entry(client.to_string()).or_default();
+        let used: u64 = taken.iter().map(|&(_, steps)| steps).sum();
+        if used + steps > max {
+            return Err(exceeded(client, format!("Step quota of {} per minute reached ({} used)", max, used)));
+        }
+        taken.push_back((now, steps));
+        Ok(())
+    }
+    
+    fn check_client_cells(&self, client: &str, adding: u64, snapshots: &SimulationSnapshots) -> Result<(), QuotaExceeded> {
+        let Some(max) = self.quota.max_live_cells else {
+            return Ok(());
+        };
+        let owned: Vec<String> = self.owners.lock().unwrap().iter()
+            .filter(|(_, owner)| *owner == client)
+            .map(|(id, _)| id.clone())
+            .collect();
+        let live: u64 = owned.iter()
+            .filter_map(|id| snapshots.get(id))
+            .map(|simulation| simulation.get_live_cell_count() as u64)
+            .sum();
+        if live + adding > max || (adding == 0 && live > max) {
+            return Err(exceeded(client, format!("Live cell quota of {} reached ({} live)", max, live)));
+        }
+        Ok(())
+    }
+}
+
+fn exceeded(client: &str, description: String) -> QuotaExceeded {
+    QuotaExceeded { subject: client.to_string(), description }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::Simulations;
+    
+    fn quota(max_simulations: Option<usize>, max_live_cells: Option<u64>, max_steps_per_minute: Option<u64>) -> QuotaTracker {
+        QuotaTracker::new(ClientQuota { max_simulations, max_live_cells, max_steps_per_minute }, None)
+    }
+    
+    #[test]
+    fn test_clients_are_keyed_by_known_token_then_address() {
+        let with_token = |token: &str| {
+            let mut request = Request::new(());
+            request.metadata_mut().insert(AUTHORIZATION_METADATA_KEY, format!("Bearer {}", token).parse().unwrap());
+            request
+        };
+        let tracker = QuotaTracker::new(ClientQuota::default(), Some(vec!["abcdefghijkl".into(), "abcdefghzzzz".into()]));
+        assert_eq!(tracker.client_key(&Request::new(())).unwrap(), "local");
+        let key = tracker.client_key(&with_token("abcdefghijkl")).unwrap();
+        assert!(key.starts_with("token:abcdefgh…"), "{}", key);
+        assert!(!key.contains("ijkl"));
+        assert_ne!(tracker.client_key(&with_token("abcdefghzzzz")).unwrap(), key);
+        
+        assert_eq!(tracker.client_key(&with_token("made-up")), Err(UnknownToken));
+        
+        // Without configured tokens, any token is ignored
+        let untrusted = QuotaTracker::new(ClientQuota::default(), None);
+        assert_eq!(untrusted.client_key(&with_token("made-up")).unwrap(), "local");
+    }
+    
+    #[test]
+    fn test_steps_are_counted_over_a_minute() {
+        let tracker = quota(None, None, Some(100));
+        let start = Instant::now();
+        assert!(tracker.take_steps_at("a", 60, start).is_ok());
+        assert!(tracker.take_steps_at("a", 50, start).is_err());
+        assert!(tracker.take_steps_at("b", 100, start).is_ok());
+        assert!(tracker.take_steps_at("a", 40, start + Duration::from_secs(30)).is_ok());
+        assert!(tracker.take_steps_at("a", 60, start + Duration::from_secs(61)).is_ok());
+    }
+    
+    #[test]
+    fn test_simulations_and_cells_count_against_owner() {
+        let tracker = quota(Some(1), Some(10), None);
+        let snapshots = SimulationSnapshots::new();
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(20, 20, None);
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        simulation.set_cells(&[(1, 1), (2, 2), (3, 3)]);
+        snapshots.publish(simulation);
+        
+        assert!(tracker.check_new_simulation("a", 0, &snapshots).is_ok());
+        tracker.record_owner("a", &id);
+        let refused = Status::from(tracker.check_new_simulation("a", 0, &snapshots).unwrap_err());
+        assert_eq!(refused.code(), tonic::Code::ResourceExhausted);
+        assert!(tracker.check_new_simulation("b", 0, &snapshots).is_ok());
+        
+        assert!(tracker.check_cells(&id, 7, &snapshots).is_ok());
+        assert!(tracker.check_cells(&id, 8, &snapshots).is_err());
+        assert!(tracker.check_cells("unowned", 1000, &snapshots).is_ok());
+        
+        tracker.forget(&id);
+        assert!(tracker.check_new_simulation("a", 0, &snapshots).is_ok());
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+use crate::grpc::errors;
+use crate::resources::{RateLimit, ServerConfig};
+
+/// Buckets kept before idle (fully refilled) ones are dropped.
+const MAX_TRACKED_BUCKETS: usize = 10_000;
+
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn full(limit: &RateLimit, now: Instant) -> Self {
+        Self {
+            tokens: limit.burst as f64,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, limit: &RateLimit, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.per_second).min(limit.burst as f64);
+        self.last_refill = now;
+    }
+
+    fn try_take(&mut self, limit: &RateLimit, now: Instant) -> bool {
+        self.refill(limit, now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Token buckets for one kind of key, all sharing the same limit.
+struct Buckets<K> {
+    limit: RateLimit,
+    buckets: Mutex<HashMap<K, TokenBucket>>,
+}
+
+impl<K: Hash + Eq + Clone> Buckets<K> {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn try_take(&self, key: &K, now: Instant) -> bool {
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_TRACKED_BUCKETS && !buckets.contains_key(key) {
+            let limit = self.limit;
+            buckets.retain(|_, bucket| {
+                bucket.refill(&limit, now);
+                bucket.tokens < limit.burst as f64
+            });
+        }
+        buckets
+            .entry(key.clone())
+            .or_insert_with(|| TokenBucket::full(&self.limit, now))
+            .try_take(&self.limit, now)
+    }
+}
+
+/// A request refused because its budget is used up; surfaces as `RESOURCE_EXHAUSTED`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitExceeded {
+    /// The exhausted budget, `client:<ip>` or `simulation:<id>`.
+    pub subject: String,
+}
+
+impl From<RateLimitExceeded> for Status {
+    fn from(exceeded: RateLimitExceeded) -> Self {
+        errors::quota_exceeded(&exceeded.subject, &format!("Rate limit exceeded for {}", exceeded.subject))
+    }
+}
+
+/// Per-peer and per-simulation request budgets, so one runaway client or one
+/// hot simulation cannot starve everyone else.
+///
+/// Peers are limited by the [`RateLimitInterceptor`] before a request reaches
+/// the service; simulations are limited inside the service once the request
+/// has been decoded and its simulation id is known.
+pub struct RateLimiter {
+    peers: Option<Buckets<IpAddr>>,
+    simulations: Option<Buckets<String>>,
+}
+
+impl RateLimiter {
+    pub fn new(peer_limit: Option<RateLimit>, simulation_limit: Option<RateLimit>) -> Self {
+        Self {
+            peers: peer_limit.map(Buckets::new),
+            simulations: simulation_limit.map(Buckets::new),
+        }
+    }
+
+    pub fn from_config(config: &ServerConfig) -> Self {
+        Self::new(config.peer_rate_limit, config.simulation_rate_limit)
+    }
+
+    pub fn check_peer(&self, peer: IpAddr) -> Result<(), RateLimitExceeded> {
+        self.check_peer_at(peer, Instant::now())
+    }
+
+    pub fn check_simulation(&self, id: &str) -> Result<(), RateLimitExceeded> {
+        self.check_simulation_at(id, Instant::now())
+    }
+
+    fn check_peer_at(&self, peer: IpAddr, now: Instant) -> Result<(), RateLimitExceeded> {
+        match &self.peers {
+            Some(buckets) if !buckets.try_take(&peer, now) => {
+                Err(RateLimitExceeded { subject: format!("client:{}", peer) })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_simulation_at(&self, id: &str, now: Instant) -> Result<(), RateLimitExceeded> {
+        match &self.simulations {
+            Some(buckets) if !buckets.try_take(&id.to_string(), now) => {
+                Err(RateLimitExceeded { subject: format!("simulation:{}", id) })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Rejects requests from peers that have used up their budget.
+#[derive(Clone)]
+pub struct RateLimitInterceptor {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitInterceptor {
+    pub fn new(limiter: Arc<RateLimiter>) -> Self {
+        Self { limiter }
+    }
+}
+
+impl Interceptor for RateLimitInterceptor {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(peer) = request.remote_addr() {
+            self.limiter.check_peer(peer.ip())?;
+        }
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tonic::Code;
+
+    const LIMIT: RateLimit = RateLimit { per_second: 10.0, burst: 3 };
+
+    #[test]
+    fn test_bucket_allows_burst_then_refills() {
+        let limiter = RateLimiter::new(Some(LIMIT), None);
+        let peer: IpAddr = "127.0.0.1".parse().unwrap();
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.check_peer_at(peer, start).is_ok());
+        }
+        let rejected = Status::from(limiter.check_peer_at(peer, start).unwrap_err());
+        assert_eq!(rejected.code(), Code::ResourceExhausted);
+
+        // One token comes back every 100ms at 10 requests per second
+        assert!(limiter.check_peer_at(peer, start + Duration::from_millis(100)).is_ok());
+        assert!(limiter.check_peer_at(peer, start + Duration::from_millis(100)).is_err());
+    }
+
+    #[test]
+    fn test_keys_have_independent_budgets() {
+        let limiter = RateLimiter::new(None, Some(LIMIT));
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.check_simulation_at("busy", now).is_ok());
+        }
+        assert!(limiter.check_simulation_at("busy", now).is_err());
+        assert!(limiter.check_simulation_at("quiet", now).is_ok());
+    }
+
+    #[test]
+    fn test_interceptor_limits_by_peer_address() {
+        use tonic::transport::server::TcpConnectInfo;
+
+        let limiter = Arc::new(RateLimiter::new(Some(RateLimit { per_second: 0.1, burst: 1 }), None));
+        let mut interceptor = RateLimitInterceptor::new(limiter);
+        let request_from = |addr: &str| {
+            let mut request = Request::new(());
+            request.extensions_mut().insert(TcpConnectInfo {
+                local_addr: None,
+                remote_addr: Some(addr.parse().unwrap()),
+            });
+            request
+        };
+
+        assert!(interceptor.call(request_from("10.0.0.1:4000")).is_ok());
+        // A new connection from the same host shares the budget
+        let rejected = interceptor.call(request_from("10.0.0.1:4001")).unwrap_err();
+        assert_eq!(rejected.code(), Code::ResourceExhausted);
+        assert!(interceptor.call(request_from("10.0.0.2:4000")).is_ok());
+        // Requests without a peer address (in-process calls) are not limited
+        assert!(interceptor.call(Request::new(())).is_ok());
+    }
+
+    #[test]
+    fn test_disabled_limits_always_pass() {
+        let limiter = RateLimiter::new(None, None);
+        let peer: IpAddr = "::1".parse().unwrap();
+        for _ in 0..100 {
+            assert!(limiter.check_peer(peer).is_ok());
+            assert!(limiter.check_simulation("sim").is_ok());
+        }
+    }
+}
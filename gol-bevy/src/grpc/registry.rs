@@ -0,0 +1,99 @@
+//! Registry files announcing running servers.
+//!
+//! While listening, the server keeps a small JSON file in the registry
+//! directory saying which implementation runs where, so clients can list
+//! running backends without being told their ports. The file is removed
+//! again when the server stops; clients probe each entry, so files left by
+//! a crashed server only cost a failed connection.
+
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Implementation name this server registers under, as in GetStatus.
+pub const IMPLEMENTATION: &str = "bevy";
+
+/// Contents of one registry file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistryEntry {
+    pub implementation: String,
+    /// Where clients connect, e.g. `[::1]:50051`.
+    pub address: String,
+    pub pid: u32,
+    pub started_at_ms: u64,
+}
+
+/// A registry file that is removed when this is dropped.
+#[derive(Debug)]
+pub struct ServerRegistration {
+    path: PathBuf,
+}
+
+impl ServerRegistration {
+    /// Announce a server listening on `addr` in `dir`, creating the directory
+    /// if needed. The file is named after the implementation and port, so a
+    /// restarted server replaces its own entry.
+    pub fn register(dir: &Path, addr: SocketAddr) -> io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let entry = RegistryEntry {
+            implementation: IMPLEMENTATION.to_string(),
+            address: advertised_address(addr).to_string(),
+            pid: std::process::id(),
+            started_at_ms: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64,
+        };
+        let path = dir.join(format!("{}-{}.json", IMPLEMENTATION, addr.port()));
+        let json = serde_json::to_string_pretty(&entry).map_err(io::Error::other)?;
+        std::fs::write(&path, json)?;
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ServerRegistration {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// `addr` as a client on this machine reaches it: a wildcard listen address
+/// becomes the loopback address of the same family.
+fn advertised_address(addr: SocketAddr) -> SocketAddr {
+    match addr.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => SocketAddr::new(Ipv4Addr::LOCALHOST.into(), addr.port()),
+        IpAddr::V6(ip) if ip.is_unspecified() => SocketAddr::new(Ipv6Addr::LOCALHOST.into(), addr.port()),
+        _ => addr,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registration_file_lives_until_dropped() {
+        let dir = std::env::temp_dir().join(format!("gol-registry-{}", uuid::Uuid::new_v4()));
+        let registration = ServerRegistration::register(&dir, "0.0.0.0:50061".parse().unwrap()).unwrap();
+        let path = registration.path().to_path_buf();
+        assert_eq!(path, dir.join("bevy-50061.json"));
+
+        let entry: RegistryEntry = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(entry.implementation, "bevy");
+        assert_eq!(entry.address, "127.0.0.1:50061");
+        assert_eq!(entry.pid, std::process::id());
+
+        drop(registration);
+        assert!(!path.exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_wildcard_ipv6_is_advertised_as_loopback() {
+        assert_eq!(advertised_address("[::]:50051".parse().unwrap()), "[::1]:50051".parse().unwrap());
+        assert_eq!(advertised_address("10.0.0.5:50051".parse().unwrap()), "10.0.0.5:50051".parse().unwrap());
+    }
+}
@@ -1,280 +1,2356 @@
-use tonic::{Request, Response, Status, Code};
+use tonic::{Request, Response, Status, Code, Streaming};
+use std::collections::HashSet;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::{Instant, UNIX_EPOCH};
 use tokio::sync::Mutex;
+use tokio::sync::broadcast::error::RecvError;
 use tokio_stream::{Stream, StreamExt};
-use bevy::prelude::*;
+use prost_types::FieldMask;
+use tonic_types::FieldViolation;
 
 use crate::grpc::proto::*;
-use crate::resources::Simulations;
-use crate::components::{Position, CellState};
+use crate::grpc::events::simulation_event;
+use crate::grpc::{errors, webhook};
+use crate::grpc::{BatchRegistry, EcsBackend, EventBus, JobRegistry, QuotaTracker, RateLimiter, ShutdownSignal, StepQueue};
+use crate::resources::{checkpoint_name, common_name, decode_apgcode, pattern_apgcode, decode_rle, detect_periodicity, encode_macrocell, encode_rle, first_generation, is_slug, replay, replay_activity, replay_statistics, random_cells, registered_automata, replay_to_generation, run_benchmark, run_experiment, standard_pattern, take_census, verify_simulation, BatchRunSpec, BreakCondition, CheckStatus, ExperimentOutcome, GridBoundary, GridRegion, MacrocellDecoder, MilestoneWebhook, Perturbation, ReplayLogs, ReplayError, ReplayOp, ReplayRecord, RegionFill, RleDecoder, Rule, RuleError, ScriptHooks, SeededRng, ServerConfig, SimulationCommandSender, SimulationData, SimulationSnapshots, Simulations, Soup, StopReason, TurmiteError, TurmiteRule, CHUNK_SIZE, DEFAULT_SIMULATION_ID, DEFAULT_SIMULATION_SIZE, DEFAULT_VERIFY_GENERATIONS, MAX_ANNOTATIONS, MAX_FILL_CELLS, MAX_REPLAY_STEPS, MAX_VERIFY_GENERATIONS, MAX_WATCH_CELLS, STANDARD_PATTERNS};
 
 pub struct GameOfLifeServiceImpl {
     pub simulations: Arc<Mutex<Simulations>>,
+    /// Read-only views published after every mutation; read RPCs use these
+    /// instead of locking `simulations`.
+    pub snapshots: Arc<SimulationSnapshots>,
+    /// Every state-changing operation, per simulation, for ReplaySimulation.
+    pub replay_logs: Arc<ReplayLogs>,
+    pub thread_pool: Arc<rayon::ThreadPool>,
+    /// Coalesces concurrent step requests into one worker per simulation.
+    pub step_queue: Arc<StepQueue>,
+    pub jobs: Arc<JobRegistry>,
+    pub batches: Arc<BatchRegistry>,
+    /// Lifecycle events for WatchEvents.
+    pub events: EventBus,
+    /// Triggered when the server begins shutting down; open streams end and
+    /// background jobs are cancelled.
+    pub shutdown: ShutdownSignal,
+    /// Per-simulation request budgets; per-peer budgets share it via the interceptor.
+    pub rate_limiter: Arc<RateLimiter>,
+    /// Per-client simulation, live cell and step quotas.
+    pub quotas: Arc<QuotaTracker>,
+    /// Set for the `ecs` backend, which steps simulations in ECS worlds.
+    ecs: Option<Arc<EcsBackend>>,
+    max_inline_steps: u32,
+    /// Cells a SimulationResponse carries before it is truncated.
+    max_response_cells: usize,
+    /// Webhooks every new simulation starts with, from the server config.
+    default_webhooks: Vec<MilestoneWebhook>,
+    /// Configured limits, as GetStatus reports them.
+    limits: ServerLimits,
+    server_start_time: Instant,
+}
+
+impl Default for GameOfLifeServiceImpl {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GameOfLifeServiceImpl {
     pub fn new() -> Self {
+        Self::with_config(&ServerConfig::default())
+    }
+    
+    pub fn with_config(config: &ServerConfig) -> Self {
+        Self::with_simulations(config, Simulations::new())
+    }
+    
+    /// Serve an existing set of simulations, e.g. ones restored from disk.
+    pub fn with_simulations(config: &ServerConfig, simulations: Simulations) -> Self {
+        Self::with_script(config, simulations, None)
+    }
+    
+    /// Serve `simulations`, running `script`'s hooks as they step.
+    pub fn with_script(config: &ServerConfig, mut simulations: Simulations, script: Option<Arc<ScriptHooks>>) -> Self {
+        let server_start_time = simulations.server_start_time;
+        if config.default_simulation && simulations.get_simulation(DEFAULT_SIMULATION_ID).is_none() {
+            let (width, height) = DEFAULT_SIMULATION_SIZE;
+            let id = simulations.create_simulation_with_id(DEFAULT_SIMULATION_ID.to_string(), width, height, Rule::conway());
+            simulations.get_simulation_mut(&id).unwrap().webhooks = config.default_webhooks();
+        }
+        let snapshots = Arc::new(SimulationSnapshots::new());
+        let replay_logs = Arc::new(ReplayLogs::new());
+        for simulation in simulations.simulations.values_mut() {
+            snapshots.publish(simulation);
+            replay_logs.record_restore(simulation);
+        }
+        let simulations = Arc::new(Mutex::new(simulations));
+        let thread_pool = Arc::new(config.build_thread_pool());
+        let events = EventBus::new();
+        let step_queue = Arc::new(StepQueue::new(
+            simulations.clone(),
+            snapshots.clone(),
+            replay_logs.clone(),
+            thread_pool.clone(),
+            events.clone(),
+            script,
+            config.max_batch_steps,
+        ).with_reference_check(config.reference_check));
+        let shutdown = ShutdownSignal::new();
+        let jobs = Arc::new(JobRegistry::new(step_queue.clone(), shutdown.clone(), config.max_batch_steps));
+        let batches = Arc::new(BatchRegistry::new(thread_pool.clone(), shutdown.clone(), config.batch_concurrency));
+        
         Self {
-            simulations: Arc::new(Mutex::new(Simulations::new())),
+            simulations,
+            snapshots,
+            replay_logs,
+            thread_pool,
+            step_queue,
+            jobs,
+            batches,
+            events,
+            shutdown,
+            rate_limiter: Arc::new(RateLimiter::from_config(config)),
+            quotas: Arc::new(QuotaTracker::new(config.client_quota, config.client_tokens.clone())),
+            ecs: None,
+            max_inline_steps: config.max_inline_steps,
+            max_response_cells: config.max_response_cells,
+            default_webhooks: config.default_webhooks(),
+            limits: server_limits(config),
+            server_start_time,
+        }
+    }
+    
+    /// Create, step, read and delete simulations' cells through `sender`, in
+    /// the per-simulation worlds of the app's
+    /// [`SimulationManager`](crate::resources::SimulationManager).
+    pub fn with_ecs_backend(mut self, sender: SimulationCommandSender) -> Self {
+        let ecs = Arc::new(EcsBackend::new(sender));
+        self.step_queue.use_ecs_backend(ecs.clone());
+        self.ecs = Some(ecs);
+        self
+    }
+    
+    /// Apply a streamed pattern upload. RLE and explicit cells are decoded
+    /// and added chunk by chunk under their own short lock, so a multi-megacell
+    /// upload never holds the simulations lock for long; if the stream fails
+    /// partway, the chunks already applied are kept. A macrocell only names its
+    /// root square on its last line, so it is applied once the stream ends,
+    /// expanding just the part that lands on the grid.
+    pub async fn upload_pattern_chunks<S>(&self, mut chunks: S) -> Result<LoadPatternResponse, Status>
+    where
+        S: Stream<Item = Result<PatternChunk, Status>> + Unpin,
+    {
+        let first = chunks.next().await.transpose()?
+            .ok_or_else(|| errors::invalid_field("id", "Upload contained no chunks"))?;
+        self.rate_limiter.check_simulation(&first.id)?;
+        let id = first.id.clone();
+        let name = first.name.clone();
+        let position = grid_position(first.position)
+            .map_err(|message| errors::invalid_field("position", &message))?;
+        let grid = self.snapshots.get(&id)
+            .ok_or_else(|| errors::simulation_not_found(&id))?;
+        
+        let mut decoder = RleDecoder::new();
+        let mut rle_seen = false;
+        let mut macrocell: Option<MacrocellDecoder> = None;
+        let mut cells = Vec::new();
+        let mut cells_added = 0;
+        let mut next = Some(first);
+        while let Some(chunk) = next {
+            rle_seen |= !chunk.rle.is_empty();
+            if !chunk.macrocell.is_empty() {
+                macrocell.get_or_insert_with(MacrocellDecoder::new).feed(&chunk.macrocell)
+                    .map_err(|error| errors::invalid_field("macrocell", &error.to_string()))?;
+            }
+            if rle_seen && macrocell.is_some() {
+                return Err(errors::invalid_field("macrocell", "An upload cannot mix RLE and macrocell text"));
+            }
+            
+            cells.clear();
+            cells.extend(chunk.cells.iter().filter_map(|pos| grid_coordinates(pos.x, pos.y)));
+            decoder.feed(&chunk.rle, &mut cells)
+                .map_err(|error| errors::invalid_field("rle", &error.to_string()))?;
+            cells_added += self.add_uploaded_cells(&id, &cells, position).await?;
+            
+            next = chunks.next().await.transpose()?;
+        }
+        
+        if let Some(macrocell) = macrocell {
+            let pattern = macrocell.finish()
+                .map_err(|error| errors::invalid_field("macrocell", &error.to_string()))?;
+            let (offset_x, offset_y) = position;
+            let cells = pattern.cells_within(
+                offset_x.saturating_neg(),
+                offset_y.saturating_neg(),
+                grid.width.saturating_sub(offset_x),
+                grid.height.saturating_sub(offset_y),
+            );
+            cells_added += self.add_uploaded_cells(&id, &cells, position).await?;
+        }
+        
+        let mut simulations = self.simulations.lock().await;
+        let simulation = simulations.get_simulation_mut(&id)
+            .ok_or_else(|| errors::simulation_not_found(&id))?;
+        self.snapshots.publish(simulation);
+        self.events.publish(SimulationEvent {
+            cells_added: cells_added as i64,
+            ..simulation_event(SimulationEventType::PatternLoaded, simulation)
+        });
+        
+        Ok(LoadPatternResponse {
+            success: cells_added > 0,
+            cells_added,
+            message: if cells_added > 0 {
+                format!("Pattern '{}' uploaded successfully", name)
+            } else {
+                "No cells were added (pattern outside grid or cells already exist)".to_string()
+            },
+            ..Default::default()
+        })
+    }
+    
+    async fn add_uploaded_cells(&self, id: &str, cells: &[(i32, i32)], (offset_x, offset_y): (i32, i32)) -> Result<i32, Status> {
+        if cells.is_empty() {
+            return Ok(0);
+        }
+        self.quotas.check_cells(id, cells.len() as u64, &self.snapshots)?;
+        let mut simulations = self.simulations.lock().await;
+        let simulation = simulations.get_simulation_mut(id)
+            .ok_or_else(|| errors::simulation_not_found(id))?;
+        let cells_added = simulation.add_pattern(cells, offset_x, offset_y);
+        self.replay_logs.record(id, ReplayOp::AddPattern {
+            cells: cells.to_vec(),
+            offset_x,
+            offset_y,
+        });
+        Ok(cells_added)
+    }
+    
+    /// Resolve one side of a diff.
+    async fn diff_target(&self, target: Option<DiffTarget>, field: &str) -> Result<Arc<SimulationData>, Status> {
+        let target = target.ok_or_else(|| errors::invalid_field(field, "Diff target is required"))?;
+        self.rate_limiter.check_simulation(&target.id)?;
+        self.simulation_at(&target.id, target.generation, &format!("{}.generation", field)).await
+    }
+    
+    /// The published snapshot, or with `generation` an earlier state rebuilt
+    /// from the replay log off the async workers. `field` names the
+    /// generation in errors.
+    async fn simulation_at(&self, id: &str, generation: Option<i64>, field: &str) -> Result<Arc<SimulationData>, Status> {
+        let current = self.snapshots.get(id)
+            .ok_or_else(|| errors::simulation_not_found(id))?;
+        let generation = match generation {
+            None => return Ok(current),
+            Some(generation) if generation < 0 => {
+                return Err(errors::invalid_field(field, "Generation must not be negative"));
+            }
+            Some(generation) => generation as u64,
+        };
+        if generation == current.generation {
+            return Ok(current);
+        }
+        
+        let start = self.replay_logs.replay_start(id, generation);
+        let thread_pool = self.thread_pool.clone();
+        let shutdown = self.shutdown.clone();
+        let replay_id = id.to_string();
+        let replayed = tokio::task::spawn_blocking(move || {
+            let stop = || shutdown.is_triggered();
+            thread_pool.install(|| replay_to_generation(&replay_id, &start, generation, &stop))
+        })
+        .await
+        .map_err(|_| Status::new(Code::Internal, "Replay worker stopped unexpectedly"))?;
+        match replayed {
+            // The replay log holds the random cells, not the seed they came from
+            Ok(simulation) => Ok(Arc::new(SimulationData { seed: current.seed, ..simulation })),
+            Err(ReplayError::NotLogged) => Err(errors::invalid_field(
+                field,
+                &format!("Generation {} is not in the replay log of simulation {}", generation, id),
+            )),
+            Err(ReplayError::TooFar) => Err(errors::invalid_field(
+                field,
+                &format!("Generation {} is more than {} generations from a stored state of simulation {}", generation, MAX_REPLAY_STEPS, id),
+            )),
+            Err(ReplayError::Stopped) => Err(Status::new(Code::Unavailable, "Server is shutting down")),
         }
     }
 }
 
+/// Largest width or height CreateSimulation accepts.
+const MAX_GRID_SIZE: i32 = 1000;
+
+/// Initial pattern that fills the grid from a [`SeededRng`].
+const RANDOM_PATTERN: &str = "random";
+
+/// Chance of each cell of a random initial pattern being alive.
+const RANDOM_DENSITY: f64 = 0.5;
+
+/// Most generations one GetStatistics request may cover.
+const MAX_STATISTICS_GENERATIONS: u64 = 10_000;
+
+/// Steps a GetHeatmap window covers when the request leaves it at 0, and
+/// the most it accepts.
+const DEFAULT_HEATMAP_GENERATIONS: i32 = 64;
+const MAX_HEATMAP_GENERATIONS: i32 = 1024;
+
+/// Generations RunBenchmark steps when the request leaves it at 0, and the
+/// most it accepts.
+const DEFAULT_BENCHMARK_GENERATIONS: i32 = 1000;
+const MAX_BENCHMARK_GENERATIONS: i32 = 100_000;
+
+/// Width and height of RunBenchmark's grid when the request leaves them at 0.
+const DEFAULT_BENCHMARK_SIZE: i32 = 500;
+
+/// Most generations each run of a batch may step.
+const MAX_BATCH_GENERATIONS: i64 = 100_000;
+
+/// Most runs one batch may ask for.
+const MAX_BATCH_REPETITIONS: i32 = 1000;
+
+/// Width and height of a batch's grids, and side of its soup, when the
+/// request leaves them at 0.
+const DEFAULT_BATCH_SIZE: i32 = 200;
+const DEFAULT_SOUP_SIZE: i32 = 16;
+
+/// Most variants one experiment may fork, besides its control.
+const MAX_EXPERIMENT_VARIANTS: usize = 8;
+
+/// Most generations each copy of an experiment may step.
+const MAX_EXPERIMENT_GENERATIONS: i64 = 10_000;
+
+/// Longest period GetStats looks for. Detection runs the pattern as if on an
+/// unbounded plane, so patterns about to hit the grid edge may differ.
+const STATS_MAX_PERIOD: u32 = 30;
+
+/// GetStats skips period detection on grids with more live cells than this.
+const STATS_PERIOD_MAX_CELLS: u64 = 5000;
+
+/// One violation per grid dimension failing `invalid`.
+fn dimension_violations(req: &CreateSimulationRequest, invalid: impl Fn(i32) -> bool, description: &str) -> Vec<FieldViolation> {
+    [("width", req.width), ("height", req.height)].into_iter()
+        .filter(|(_, size)| invalid(*size))
+        .map(|(field, _)| FieldViolation::new(field, description))
+        .collect()
+}
+
+/// SimulationResponse fields a read mask may name.
+const SIMULATION_FIELDS: [&str; 17] = ["id", "generation", "live_cells", "grid", "cells", "rule", "turmite", "turmites", "config", "completed", "auto_pause", "name", "seed", "version", "breakpoints", "watchpoints", "rule_changes"];
+
+/// Most webhooks one simulation may have.
+const MAX_WEBHOOKS: usize = 8;
+
+/// One violation per field of `webhooks` that SetWebhooks cannot accept.
+fn webhook_violations(webhooks: &[Webhook]) -> Vec<FieldViolation> {
+    let mut violations = Vec::new();
+    if webhooks.len() > MAX_WEBHOOKS {
+        violations.push(FieldViolation::new("webhooks", format!("At most {} webhooks are allowed", MAX_WEBHOOKS)));
+    }
+    for (index, webhook) in webhooks.iter().enumerate() {
+        if let Err(message) = webhook::validate_url(&webhook.url) {
+            violations.push(FieldViolation::new(format!("webhooks[{}].url", index), message));
+        }
+        if webhook.every_generations < 0 {
+            violations.push(FieldViolation::new(format!("webhooks[{}].every_generations", index), "Must not be negative"));
+        }
+        let notifies = webhook.on_completion || webhook.on_extinction || webhook.on_stabilization || webhook.every_generations > 0;
+        if !notifies {
+            violations.push(FieldViolation::new(format!("webhooks[{}]", index), "Choose at least one milestone or every_generations"));
+        }
+    }
+    violations
+}
+
+fn milestone_webhook(webhook: &Webhook) -> MilestoneWebhook {
+    MilestoneWebhook {
+        url: webhook.url.clone(),
+        on_completion: webhook.on_completion,
+        on_extinction: webhook.on_extinction,
+        on_stabilization: webhook.on_stabilization,
+        every_generations: webhook.every_generations as u64,
+    }
+}
+
+fn webhook_message(webhook: &MilestoneWebhook) -> Webhook {
+    Webhook {
+        url: webhook.url.clone(),
+        on_completion: webhook.on_completion,
+        on_extinction: webhook.on_extinction,
+        on_stabilization: webhook.on_stabilization,
+        every_generations: webhook.every_generations as i64,
+    }
+}
+
+/// Most breakpoints one simulation may have.
+const MAX_BREAKPOINTS: usize = 16;
+
+/// The conditions of `breakpoints`, or one violation per breakpoint that
+/// SetBreakpoints cannot accept. Pattern breakpoints need a B3/S23
+/// simulation, whose objects census can identify.
+fn break_conditions(breakpoints: &[Breakpoint], conway: bool) -> Result<Vec<BreakCondition>, Vec<FieldViolation>> {
+    let mut violations = Vec::new();
+    if breakpoints.len() > MAX_BREAKPOINTS {
+        violations.push(FieldViolation::new("breakpoints", format!("At most {} breakpoints are allowed", MAX_BREAKPOINTS)));
+    }
+    let mut conditions = Vec::new();
+    for (index, breakpoint) in breakpoints.iter().enumerate() {
+        let condition = match &breakpoint.condition {
+            None => Err("Choose a condition".to_string()),
+            Some(breakpoint::Condition::PopulationAbove(threshold) | breakpoint::Condition::PopulationBelow(threshold) | breakpoint::Condition::Generation(threshold))
+                if *threshold < 0 => Err("Must not be negative".to_string()),
+            Some(breakpoint::Condition::PopulationAbove(threshold)) => Ok(BreakCondition::PopulationAbove(*threshold as u64)),
+            Some(breakpoint::Condition::PopulationBelow(threshold)) => Ok(BreakCondition::PopulationBelow(*threshold as u64)),
+            Some(breakpoint::Condition::Generation(generation)) => Ok(BreakCondition::Generation(*generation as u64)),
+            Some(breakpoint::Condition::Pattern(_)) if !conway => Err("Pattern breakpoints only support B3/S23".to_string()),
+            Some(breakpoint::Condition::Pattern(pattern)) => pattern_apgcode(pattern).map(BreakCondition::Pattern),
+        };
+        match condition {
+            Ok(condition) => conditions.push(condition),
+            Err(message) => violations.push(FieldViolation::new(format!("breakpoints[{}]", index), message)),
+        }
+    }
+    if violations.is_empty() { Ok(conditions) } else { Err(violations) }
+}
+
+fn breakpoint_message(condition: &BreakCondition) -> Breakpoint {
+    let condition = match condition {
+        BreakCondition::PopulationAbove(threshold) => breakpoint::Condition::PopulationAbove(*threshold as i64),
+        BreakCondition::PopulationBelow(threshold) => breakpoint::Condition::PopulationBelow(*threshold as i64),
+        BreakCondition::Pattern(apgcode) => breakpoint::Condition::Pattern(apgcode.clone()),
+        BreakCondition::Generation(generation) => breakpoint::Condition::Generation(*generation as i64),
+    };
+    Breakpoint { condition: Some(condition) }
+}
+
+fn breakpoint_messages(simulation: &SimulationData) -> Vec<Breakpoint> {
+    simulation.breakpoints.iter().map(|breakpoint| breakpoint_message(&breakpoint.condition)).collect()
+}
+
+/// Most watchpoints one simulation may have.
+const MAX_WATCHPOINTS: usize = 16;
+
+/// The regions of `watchpoints` with whether each pauses, or one violation
+/// per watchpoint SetWatchpoints cannot accept. Regions must overlap a
+/// `width` by `height` grid and cover at most [`MAX_WATCH_CELLS`] of it.
+fn watch_regions(watchpoints: &[Watchpoint], width: i32, height: i32) -> Result<Vec<(GridRegion, bool)>, Vec<FieldViolation>> {
+    let mut violations = Vec::new();
+    if watchpoints.len() > MAX_WATCHPOINTS {
+        violations.push(FieldViolation::new("watchpoints", format!("At most {} watchpoints are allowed", MAX_WATCHPOINTS)));
+    }
+    let mut regions = Vec::new();
+    for (index, watchpoint) in watchpoints.iter().enumerate() {
+        let region = grid_region(watchpoint.region).and_then(|region| match region.clipped_area(width, height) {
+            0 => Err("Region lies outside the grid".to_string()),
+            area if area > MAX_WATCH_CELLS => Err(format!("Region covers {} cells; at most {} may be watched", area, MAX_WATCH_CELLS)),
+            _ => Ok(region),
+        });
+        match region {
+            Ok(region) => regions.push((region, watchpoint.pause)),
+            Err(message) => violations.push(FieldViolation::new(format!("watchpoints[{}].region", index), message)),
+        }
+    }
+    if violations.is_empty() { Ok(regions) } else { Err(violations) }
+}
+
+fn region_message(region: GridRegion) -> Region {
+    Region {
+        x: region.x.into(),
+        y: region.y.into(),
+        width: region.width.into(),
+        height: region.height.into(),
+    }
+}
+
+fn watchpoint_messages(simulation: &SimulationData) -> Vec<Watchpoint> {
+    simulation.watchpoints.iter()
+        .map(|watchpoint| Watchpoint { region: Some(region_message(watchpoint.region)), pause: watchpoint.pause })
+        .collect()
+}
+
+/// The annotations SetAnnotations was asked for, or one violation per
+/// annotation it cannot accept.
+fn requested_annotations(annotations: &[Annotation]) -> Result<Vec<crate::resources::Annotation>, Vec<FieldViolation>> {
+    let mut violations = Vec::new();
+    if annotations.len() > MAX_ANNOTATIONS {
+        violations.push(FieldViolation::new("annotations", format!("At most {} annotations are allowed", MAX_ANNOTATIONS)));
+    }
+    let mut accepted = Vec::new();
+    for (index, annotation) in annotations.iter().enumerate() {
+        let annotation = grid_coordinates(annotation.x, annotation.y)
+            .ok_or_else(|| format!("Cell ({}, {}) is beyond grid coordinates", annotation.x, annotation.y))
+            .and_then(|(x, y)| crate::resources::Annotation::new(x, y, &annotation.text));
+        match annotation {
+            Ok(annotation) => accepted.push(annotation),
+            Err(message) => violations.push(FieldViolation::new(format!("annotations[{}]", index), message)),
+        }
+    }
+    if violations.is_empty() { Ok(accepted) } else { Err(violations) }
+}
+
+fn annotation_message(annotation: &crate::resources::Annotation) -> Annotation {
+    Annotation {
+        x: annotation.x.into(),
+        y: annotation.y.into(),
+        text: annotation.text.clone(),
+    }
+}
+
+fn checkpoint_message(checkpoint: &crate::resources::Checkpoint) -> Checkpoint {
+    Checkpoint {
+        name: checkpoint.name.clone(),
+        generation: checkpoint.generation() as i64,
+        live_cells: checkpoint.state.get_live_cell_count(),
+        rule: checkpoint.state.rule.to_string(),
+        created_at_ms: checkpoint.created_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as i64,
+    }
+}
+
+fn checkpoints_response(simulation: &SimulationData) -> CheckpointsResponse {
+    CheckpointsResponse {
+        id: simulation.id.clone(),
+        checkpoints: simulation.checkpoints.iter().map(checkpoint_message).collect(),
+        version: simulation.version,
+    }
+}
+
+/// Longest simulation name, in characters.
+const MAX_NAME_LENGTH: usize = 64;
+
+/// Check a requested simulation name; empty means unnamed.
+fn requested_name(name: &str) -> Result<Option<String>, String> {
+    let name = name.trim();
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!("Name must be at most {} characters", MAX_NAME_LENGTH));
+    }
+    Ok((!name.is_empty()).then(|| name.to_string()))
+}
+
+/// Cells of a batch pattern: a standard pattern name, an apgcode or RLE.
+fn batch_pattern(pattern: &str) -> Result<Vec<(i32, i32)>, String> {
+    if let Some(cells) = standard_pattern(&pattern.to_ascii_lowercase()) {
+        return Ok(cells);
+    }
+    if pattern.starts_with('x') && pattern.contains('_') {
+        return decode_apgcode(pattern).map_err(|error| error.to_string());
+    }
+    let cells = decode_rle(pattern).map_err(|error| error.to_string())?;
+    if cells.is_empty() {
+        return Err("Expected a standard pattern name, an apgcode or RLE with live cells".to_string());
+    }
+    Ok(cells)
+}
+
+/// The runs a SubmitBatch request asks for, or one violation per field it
+/// cannot accept.
+fn batch_run_spec(spec: &BatchSpec) -> Result<BatchRunSpec, Vec<FieldViolation>> {
+    let default_to = |value: i32, default: i32| if value == 0 { default } else { value };
+    let (width, height) = (default_to(spec.width, DEFAULT_BATCH_SIZE), default_to(spec.height, DEFAULT_BATCH_SIZE));
+    let soup_size = default_to(spec.soup_size, DEFAULT_SOUP_SIZE);
+    
+    let mut violations = Vec::new();
+    if let Err(message) = requested_name(&spec.name) {
+        violations.push(FieldViolation::new("name", message));
+    }
+    let pattern = if spec.pattern.trim().is_empty() {
+        if spec.soup_density == 0.0 {
+            violations.push(FieldViolation::new("pattern", "Give a pattern, a soup density or both"));
+        }
+        Vec::new()
+    } else {
+        batch_pattern(spec.pattern.trim()).unwrap_or_else(|message| {
+            violations.push(FieldViolation::new("pattern", message));
+            Vec::new()
+        })
+    };
+    let rule = requested_rule(&spec.rule).map_err(|error| violations.push(FieldViolation::new("rule", error.to_string())));
+    for (field, size) in [("width", width), ("height", height), ("soup_size", soup_size)] {
+        if !(1..=MAX_GRID_SIZE).contains(&size) {
+            violations.push(FieldViolation::new(field, format!("Must be between 1 and {}", MAX_GRID_SIZE)));
+        }
+    }
+    if !(1..=MAX_BATCH_GENERATIONS).contains(&spec.generations) {
+        violations.push(FieldViolation::new("generations", format!("Must be between 1 and {}", MAX_BATCH_GENERATIONS)));
+    }
+    if !(0..=MAX_BATCH_REPETITIONS).contains(&spec.repetitions) {
+        violations.push(FieldViolation::new("repetitions", format!("Must be between 1 and {}", MAX_BATCH_REPETITIONS)));
+    }
+    if !(0.0..=1.0).contains(&spec.soup_density) {
+        violations.push(FieldViolation::new("soup_density", "Must be between 0 and 1"));
+    }
+    
+    match rule {
+        Ok(rule) if violations.is_empty() => Ok(BatchRunSpec {
+            pattern,
+            rule,
+            width,
+            height,
+            generations: spec.generations as u64,
+            soup_size,
+            soup_density: spec.soup_density,
+        }),
+        _ => Err(violations),
+    }
+}
+
+/// The perturbations a RunExperiment request asks for on `source`, or one
+/// violation per field it cannot accept.
+fn experiment_perturbations(req: &ExperimentRequest, source: &SimulationData) -> Result<Vec<Perturbation>, Vec<FieldViolation>> {
+    let mut violations = Vec::new();
+    if !(1..=MAX_EXPERIMENT_VARIANTS).contains(&req.variants.len()) {
+        violations.push(FieldViolation::new("variants", format!("Give between 1 and {} variants", MAX_EXPERIMENT_VARIANTS)));
+    }
+    if !(1..=MAX_EXPERIMENT_GENERATIONS).contains(&req.generations) {
+        violations.push(FieldViolation::new("generations", format!("Must be between 1 and {}", MAX_EXPERIMENT_GENERATIONS)));
+    }
+    let mut perturbations = Vec::new();
+    for (index, variant) in req.variants.iter().enumerate() {
+        let field = |name: &str| format!("variants[{}].{}", index, name);
+        let mut perturbation = Perturbation::default();
+        if let Err(message) = requested_name(&variant.name) {
+            violations.push(FieldViolation::new(field("name"), message));
+        }
+        if !variant.rule.trim().is_empty() {
+            if source.turmite_rule.is_some() {
+                violations.push(FieldViolation::new(field("rule"), "Turmite simulations have no cell rule"));
+            }
+            match requested_rule(&variant.rule) {
+                Ok(rule) => perturbation.rule = Some(rule),
+                Err(error) => violations.push(FieldViolation::new(field("rule"), error.to_string())),
+            }
+        }
+        if !(0.0..=1.0).contains(&variant.soup_density) {
+            violations.push(FieldViolation::new(field("soup_density"), "Must be between 0 and 1"));
+        } else if variant.soup_density > 0.0 {
+            let size = if variant.soup_size == 0 { DEFAULT_SOUP_SIZE } else { variant.soup_size };
+            if !(1..=MAX_GRID_SIZE).contains(&size) {
+                violations.push(FieldViolation::new(field("soup_size"), format!("Must be between 1 and {}", MAX_GRID_SIZE)));
+            }
+            perturbation.soup = Some(Soup { seed: variant.seed, size, density: variant.soup_density });
+        }
+        if !variant.pattern.trim().is_empty() {
+            match batch_pattern(variant.pattern.trim()) {
+                Ok(cells) => perturbation.pattern = cells.into_iter()
+                    .map(|(x, y)| (x.saturating_add(variant.x), y.saturating_add(variant.y)))
+                    .collect(),
+                Err(message) => violations.push(FieldViolation::new(field("pattern"), message)),
+            }
+        }
+        perturbations.push(perturbation);
+    }
+    if violations.is_empty() { Ok(perturbations) } else { Err(violations) }
+}
+
+fn experiment_result(variant: i32, name: &str, rule: &str, outcome: &ExperimentOutcome, control: &ExperimentOutcome) -> ExperimentResult {
+    let (reason, period) = match outcome.stop {
+        Some(StopReason::Extinct) => (EndReason::Extinct, 0),
+        Some(StopReason::Cycle { period }) => (EndReason::Cycle, period as i64),
+        None => (EndReason::None, 0),
+    };
+    ExperimentResult {
+        variant,
+        name: name.to_string(),
+        rule: rule.to_string(),
+        generations: outcome.generations as i64,
+        initial_population: outcome.initial_population as i64,
+        final_population: outcome.final_population as i64,
+        peak_population: outcome.peak_population as i64,
+        mean_population: outcome.mean_population,
+        reason: reason as i32,
+        period,
+        population_difference: outcome.final_population as i64 - control.final_population as i64,
+        elapsed_ms: outcome.elapsed.as_secs_f64() * 1000.0,
+    }
+}
+
+/// UpdateSimulationRequest fields an update mask may name.
+const UPDATE_FIELDS: [&str; 5] = ["generation", "cells", "cells.alive", "rule", "boundary_mode"];
+
+fn mask_has(mask: &FieldMask, path: &str) -> bool {
+    mask.paths.iter().any(|candidate| candidate == path)
+}
+
+/// An empty mask or `*` selects every field.
+fn is_full_mask(mask: &FieldMask) -> bool {
+    mask.paths.is_empty() || mask_has(mask, "*")
+}
+
+/// The first path in `mask` that is not one of `allowed`.
+fn unknown_path<'a>(mask: &'a FieldMask, allowed: &[&str]) -> Option<&'a str> {
+    mask.paths.iter()
+        .map(String::as_str)
+        .find(|path| !allowed.contains(path))
+}
+
+/// Grid coordinates of a cell or position sent as 64-bit; `None` past the
+/// 32-bit range grids use, where no grid reaches.
+fn grid_coordinates(x: i64, y: i64) -> Option<(i32, i32)> {
+    Some((i32::try_from(x).ok()?, i32::try_from(y).ok()?))
+}
+
+/// Where a pattern is placed; required, and within grid coordinate range.
+fn grid_position(position: Option<Position>) -> Result<(i32, i32), String> {
+    let position = position.ok_or("Position is required")?;
+    grid_coordinates(position.x, position.y)
+        .ok_or_else(|| format!("Position ({}, {}) is beyond grid coordinates", position.x, position.y))
+}
+
+/// Limits from the server config, with 0 for unlimited.
+fn server_limits(config: &ServerConfig) -> ServerLimits {
+    let quota = config.client_quota;
+    ServerLimits {
+        max_response_cells: config.max_response_cells.min(i32::MAX as usize) as i32,
+        max_inline_steps: config.max_inline_steps.min(i32::MAX as u32) as i32,
+        max_batch_steps: config.max_batch_steps.min(i32::MAX as u32) as i32,
+        max_fill_cells: MAX_FILL_CELLS as i64,
+        max_simulations_per_client: quota.max_simulations.map_or(0, |limit| limit.min(i32::MAX as usize) as i32),
+        max_live_cells_per_client: quota.max_live_cells.map_or(0, |limit| limit.min(i64::MAX as u64) as i64),
+        max_steps_per_minute_per_client: quota.max_steps_per_minute.map_or(0, |limit| limit.min(i64::MAX as u64) as i64),
+        peer_requests_per_second: config.peer_rate_limit.map_or(0.0, |limit| limit.per_second),
+        simulation_requests_per_second: config.simulation_rate_limit.map_or(0.0, |limit| limit.per_second),
+    }
+}
+
+/// The crate version, commit, profile and optional features this server
+/// was built with.
+pub fn build_info() -> BuildInfo {
+    let features = [
+        ("sled-store", cfg!(feature = "sled-store")),
+        ("ffi", cfg!(feature = "ffi")),
+        ("scripting", cfg!(feature = "scripting")),
+    ];
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_commit: env!("GOL_GIT_COMMIT").to_string(),
+        profile: env!("GOL_BUILD_PROFILE").to_string(),
+        features: features.into_iter().filter(|(_, enabled)| *enabled).map(|(name, _)| name.to_string()).collect(),
+    }
+}
+
+/// The rectangle a region request edits; required, non-empty, and within
+/// grid coordinate range.
+fn grid_region(region: Option<Region>) -> Result<GridRegion, String> {
+    let region = region.ok_or("Region is required")?;
+    if region.width <= 0 || region.height <= 0 {
+        return Err(format!("Region is {}x{}; width and height must be positive", region.width, region.height));
+    }
+    let (x, y) = grid_coordinates(region.x, region.y)
+        .ok_or_else(|| format!("Region corner ({}, {}) is beyond grid coordinates", region.x, region.y))?;
+    let (width, height) = grid_coordinates(region.width, region.height)
+        .ok_or_else(|| format!("Region size {}x{} is beyond grid coordinates", region.width, region.height))?;
+    Ok(GridRegion { x, y, width, height })
+}
+
+/// Cells of an update request in the form the replay log records them.
+fn replay_cells(cells: &[Cell]) -> Vec<(i32, i32, bool)> {
+    cells.iter()
+        .filter_map(|cell| grid_coordinates(cell.x, cell.y).map(|(x, y)| (x, y, cell.alive)))
+        .collect()
+}
+
+/// Whether an update clears the grid first, and the cells it then sets
+/// alive or dead.
+type CellEdits = (bool, Vec<(i32, i32, bool)>);
+
+/// Fold an update's cell operations into one clear and the cells set after
+/// it; a clear drops the cells listed before it.
+fn cell_operations(operations: &[CellOperation]) -> Result<CellEdits, String> {
+    let mut clear = false;
+    let mut cells = Vec::new();
+    for operation in operations {
+        let alive = match CellOperationType::try_from(operation.r#type) {
+            Ok(CellOperationType::SetAlive) => true,
+            Ok(CellOperationType::SetDead) => false,
+            Ok(CellOperationType::ClearAll) if operation.cells.is_empty() => {
+                clear = true;
+                cells.clear();
+                continue;
+            }
+            Ok(CellOperationType::ClearAll) => return Err("CLEAR_ALL takes no cells".to_string()),
+            _ => return Err(format!("Unknown cell operation {}", operation.r#type)),
+        };
+        cells.extend(operation.cells.iter().filter_map(|pos| grid_coordinates(pos.x, pos.y)).map(|(x, y)| (x, y, alive)));
+    }
+    Ok((clear, cells))
+}
+
+/// Cells an update gives a state above 1; states the rule does not have
+/// are skipped when applied.
+fn replay_states(cells: &[Cell]) -> Vec<(i32, i32, u8)> {
+    cells.iter()
+        .filter(|cell| cell.state > 1 && cell.state <= u8::MAX as i32)
+        .filter_map(|cell| grid_coordinates(cell.x, cell.y).map(|(x, y)| (x, y, cell.state as u8)))
+        .collect()
+}
+
+/// Colors of the live cells in an update; out-of-range colors are skipped
+/// when applied.
+fn replay_colors(cells: &[Cell]) -> Vec<(i32, i32, u8)> {
+    cells.iter()
+        .filter(|cell| cell.alive && cell.color > 0 && cell.color <= u8::MAX as i32)
+        .filter_map(|cell| grid_coordinates(cell.x, cell.y).map(|(x, y)| (x, y, cell.color as u8)))
+        .collect()
+}
+
+/// Build only the response fields named in `mask`, skipping the cell list
+/// entirely unless it was asked for.
+fn masked_simulation_response(simulation: &SimulationData, mask: &FieldMask, max_cells: usize) -> SimulationResponse {
+    let wants = |field| mask_has(mask, field);
+    let (cells, truncated) = if wants("cells") { capped_cell_messages(simulation, max_cells) } else { (Vec::new(), false) };
+    SimulationResponse {
+        id: if wants("id") { simulation.id.clone() } else { String::new() },
+        generation: if wants("generation") { simulation.generation as i64 } else { 0 },
+        live_cells: if wants("live_cells") { simulation.get_live_cell_count() } else { 0 },
+        grid: wants("grid").then_some(grid_info(simulation)),
+        cells,
+        rule: if wants("rule") { simulation.rule.to_string() } else { String::new() },
+        turmite: if wants("turmite") { turmite_rule_notation(simulation) } else { String::new() },
+        turmites: if wants("turmites") { turmite_messages(simulation) } else { Vec::new() },
+        config: wants("config").then(|| simulation_config(simulation)),
+        completed: wants("completed") && simulation.is_completed(),
+        auto_pause: if wants("auto_pause") { auto_pause_message(simulation) } else { None },
+        name: if wants("name") { simulation.name.clone().unwrap_or_default() } else { String::new() },
+        truncated,
+        seed: simulation.seed.filter(|_| wants("seed")),
+        version: if wants("version") { simulation.version } else { 0 },
+        breakpoints: if wants("breakpoints") { breakpoint_messages(simulation) } else { Vec::new() },
+        watchpoints: if wants("watchpoints") { watchpoint_messages(simulation) } else { Vec::new() },
+        rule_changes: if wants("rule_changes") { rule_change_messages(simulation) } else { Vec::new() },
+    }
+}
+
+fn replay_entry_message((sequence, record): (usize, &ReplayRecord)) -> ReplayEntry {
+    ReplayEntry {
+        sequence: sequence as i32,
+        operation: record.op.name().to_string(),
+        detail: record.op.describe(),
+        recorded_at_ms: record.recorded_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as i64,
+    }
+}
+
+/// `simulation` in full, except that only its first `max_cells` cells are
+/// sent when it has more.
+fn simulation_response(simulation: &SimulationData, max_cells: usize) -> SimulationResponse {
+    let (cells, truncated) = capped_cell_messages(simulation, max_cells);
+    SimulationResponse {
+        id: simulation.id.clone(),
+        generation: simulation.generation as i64,
+        live_cells: simulation.get_live_cell_count(),
+        grid: Some(grid_info(simulation)),
+        cells,
+        rule: simulation.rule.to_string(),
+        turmite: turmite_rule_notation(simulation),
+        turmites: turmite_messages(simulation),
+        config: Some(simulation_config(simulation)),
+        completed: simulation.is_completed(),
+        auto_pause: auto_pause_message(simulation),
+        name: simulation.name.clone().unwrap_or_default(),
+        truncated,
+        seed: simulation.seed,
+        version: simulation.version,
+        breakpoints: breakpoint_messages(simulation),
+        watchpoints: watchpoint_messages(simulation),
+        rule_changes: rule_change_messages(simulation),
+    }
+}
+
+/// `history` is the simulation's replay log usage, as entries and bytes.
+fn simulation_summary(simulation: &SimulationData, history: (usize, u64)) -> SimulationSummary {
+    let (history_entries, history_memory_bytes) = history;
+    let stats = simulation.cells.stats();
+    SimulationSummary {
+        id: simulation.id.clone(),
+        name: simulation.name.clone().unwrap_or_default(),
+        generation: simulation.generation as i64,
+        live_cells: simulation.get_live_cell_count(),
+        grid: Some(grid_info(simulation)),
+        rule: simulation.turmite_rule.as_ref().map_or_else(|| simulation.rule.to_string(), ToString::to_string),
+        created_at_ms: simulation.created_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as i64,
+        chunk_count: stats.chunk_count as i64,
+        cell_memory_bytes: stats.memory_bytes as i64,
+        history_entries: history_entries as i64,
+        history_memory_bytes: history_memory_bytes as i64,
+        memory_bytes: (stats.memory_bytes + history_memory_bytes) as i64,
+        version: simulation.version,
+    }
+}
+
+fn auto_pause_message(simulation: &SimulationData) -> Option<AutoPause> {
+    let pause = simulation.auto_pause.filter(|_| simulation.is_auto_paused())?;
+    let (reason, period) = end_reason(pause.reason);
+    Some(AutoPause { reason: reason as i32, generation: pause.generation as i64, period: period as i64 })
+}
+
+fn end_reason(reason: StopReason) -> (EndReason, u64) {
+    match reason {
+        StopReason::Extinct => (EndReason::Extinct, 0),
+        StopReason::Cycle { period } => (EndReason::Cycle, period),
+    }
+}
+
+fn simulation_config(simulation: &SimulationData) -> SimulationConfig {
+    SimulationConfig {
+        max_generations: simulation.max_generations.unwrap_or(0) as i64,
+        auto_step_ms: 0,
+        completion_webhook: simulation.completion_webhook.clone().unwrap_or_default(),
+    }
+}
+
+/// Check a create request's generation limit and webhook, naming the
+/// offending field on failure.
+fn validate_config(config: &SimulationConfig) -> Result<(), (&'static str, String)> {
+    if config.max_generations < 0 {
+        return Err(("config.max_generations", "Max generations must not be negative".to_string()));
+    }
+    if config.completion_webhook.is_empty() {
+        return Ok(());
+    }
+    if config.max_generations == 0 {
+        return Err(("config.completion_webhook", "A completion webhook needs max_generations".to_string()));
+    }
+    webhook::validate_url(&config.completion_webhook)
+        .map(|_| ())
+        .map_err(|message| ("config.completion_webhook", message))
+}
+
+/// Store a validated config's generation limit and webhook on a new simulation.
+fn apply_config(simulation: &mut SimulationData, config: &SimulationConfig) {
+    simulation.max_generations = (config.max_generations > 0).then_some(config.max_generations as u64);
+    simulation.completion_webhook = (!config.completion_webhook.is_empty()).then(|| config.completion_webhook.clone());
+}
+
+fn grid_info(simulation: &SimulationData) -> GridInfo {
+    GridInfo {
+        width: simulation.width,
+        height: simulation.height,
+        boundary_mode: match simulation.boundary {
+            GridBoundary::Clip => BoundaryMode::Clip,
+            GridBoundary::Wrap => BoundaryMode::Wrap,
+            GridBoundary::Mirror => BoundaryMode::Mirror,
+            GridBoundary::KleinBottle => BoundaryMode::KleinBottle,
+        } as i32,
+    }
+}
+
+fn grid_boundary(mode: i32) -> Result<GridBoundary, String> {
+    match BoundaryMode::try_from(mode) {
+        Ok(BoundaryMode::Clip) => Ok(GridBoundary::Clip),
+        Ok(BoundaryMode::Wrap) => Ok(GridBoundary::Wrap),
+        Ok(BoundaryMode::Mirror) => Ok(GridBoundary::Mirror),
+        Ok(BoundaryMode::KleinBottle) => Ok(GridBoundary::KleinBottle),
+        Err(_) => Err(format!("Unknown boundary mode {}", mode)),
+    }
+}
+
+/// Parse a requested cell rule; empty means B3/S23.
+fn requested_rule(rule: &str) -> Result<Rule, RuleError> {
+    if rule.is_empty() {
+        return Ok(Rule::conway());
+    }
+    rule.parse()
+}
+
+/// Most rule changes one simulation may have scheduled.
+const MAX_RULE_CHANGES: usize = 16;
+
+fn rule_change_messages(simulation: &SimulationData) -> Vec<RuleChange> {
+    simulation.rule_changes.iter()
+        .map(|(&generation, rule)| RuleChange { generation, rule: rule.to_string() })
+        .collect()
+}
+
+fn turmite_rule_notation(simulation: &SimulationData) -> String {
+    simulation.turmite_rule.as_ref().map(ToString::to_string).unwrap_or_default()
+}
+
+fn turmite_messages(simulation: &SimulationData) -> Vec<Turmite> {
+    use crate::resources::Heading as Facing;
+    
+    simulation.turmites.iter().map(|turmite| Turmite {
+        x: turmite.x,
+        y: turmite.y,
+        heading: match turmite.heading {
+            Facing::North => Heading::North,
+            Facing::East => Heading::East,
+            Facing::South => Heading::South,
+            Facing::West => Heading::West,
+        } as i32,
+        state: turmite.state as i32,
+    }).collect()
+}
+
+/// Check requested starting turmites against the grid and rule, defaulting
+/// to one turmite at the centre facing north.
+fn starting_turmites(req: &CreateSimulationRequest, rule: &TurmiteRule) -> Result<Vec<crate::resources::Turmite>, String> {
+    use crate::resources::Heading as Facing;
+    
+    if req.turmites.is_empty() {
+        return Ok(vec![crate::resources::Turmite { x: req.width / 2, y: req.height / 2, ..Default::default() }]);
+    }
+    req.turmites.iter().map(|turmite| {
+        if turmite.x < 0 || turmite.x >= req.width || turmite.y < 0 || turmite.y >= req.height {
+            return Err(format!("Turmite at ({}, {}) is outside the grid", turmite.x, turmite.y));
+        }
+        if turmite.state < 0 || turmite.state as usize >= rule.states() {
+            return Err(format!("Turmite state {} is out of range for {} states", turmite.state, rule.states()));
+        }
+        let heading = match Heading::try_from(turmite.heading) {
+            Ok(Heading::North) => Facing::North,
+            Ok(Heading::East) => Facing::East,
+            Ok(Heading::South) => Facing::South,
+            Ok(Heading::West) => Facing::West,
+            Err(_) => return Err(format!("Unknown heading {}", turmite.heading)),
+        };
+        Ok(crate::resources::Turmite { x: turmite.x, y: turmite.y, heading, state: turmite.state as u8 })
+    }).collect()
+}
+
+/// Convert every live cell of a simulation into its wire representation,
+/// followed by any cells decaying under a Generations rule.
+fn live_cell_messages(simulation: &SimulationData) -> Vec<Cell> {
+    simulation.cells.iter_live().map(|position| cell_message(simulation, position, 1))
+        .chain(simulation.cells.iter_decaying().map(|(position, state)| cell_message(simulation, position, state)))
+        .collect()
+}
+
+fn cell_message(simulation: &SimulationData, (x, y): (i32, i32), state: u8) -> Cell {
+    Cell {
+        x: x.into(),
+        y: y.into(),
+        alive: state == 1,
+        neighbors: simulation.rule.neighbor_count(&simulation.cells, x, y) as i32,
+        state: state as i32,
+        color: simulation.cells.color(x, y) as i32,
+    }
+}
+
+/// Live and decaying cells, which GetCells pages through.
+fn cell_total(simulation: &SimulationData) -> usize {
+    simulation.cells.population() as usize + simulation.cells.decaying_count()
+}
+
+/// Every cell when there are at most `max_cells`, else the first
+/// `max_cells` in row-major order, as the first GetCells page; the flag is
+/// set when cells were left out.
+fn capped_cell_messages(simulation: &SimulationData, max_cells: usize) -> (Vec<Cell>, bool) {
+    if cell_total(simulation) <= max_cells {
+        return (live_cell_messages(simulation), false);
+    }
+    (cell_page(simulation, None, max_cells).0, true)
+}
+
+/// Up to `limit` cells following `after` in row-major order, or from the
+/// first cell without one, and the position of the last cell when more
+/// follow. Only the page's cells are sorted and built.
+fn cell_page(simulation: &SimulationData, after: Option<(i32, i32)>, limit: usize) -> (Vec<Cell>, Option<(i32, i32)>) {
+    let row_major = |&((x, y), _): &((i32, i32), u8)| (y, x);
+    let mut cells: Vec<((i32, i32), u8)> = simulation.cells.iter_live().map(|position| (position, 1))
+        .chain(simulation.cells.iter_decaying())
+        .filter(|cell| after.is_none_or(|(x, y)| row_major(cell) > (y, x)))
+        .collect();
+    let more = cells.len() > limit;
+    if more {
+        cells.select_nth_unstable_by_key(limit, row_major);
+        cells.truncate(limit);
+    }
+    cells.sort_unstable_by_key(row_major);
+    let last = cells.last().map(|&(position, _)| position).filter(|_| more);
+    (cells.into_iter().map(|(position, state)| cell_message(simulation, position, state)).collect(), last)
+}
+
+/// GetCells page tokens: the generation every page is read at and the
+/// last cell already sent.
+fn page_token(generation: u64, (x, y): (i32, i32)) -> String {
+    format!("{}:{}:{}", generation, x, y)
+}
+
+fn parse_page_token(token: &str) -> Option<(u64, (i32, i32))> {
+    let mut parts = token.splitn(3, ':');
+    let generation = parts.next()?.parse().ok()?;
+    let x = parts.next()?.parse().ok()?;
+    let y = parts.next()?.parse().ok()?;
+    Some((generation, (x, y)))
+}
+
 #[tonic::async_trait]
 impl game_of_life_service_server::GameOfLifeService for GameOfLifeServiceImpl {
     async fn get_status(&self, _request: Request<StatusRequest>) -> Result<Response<StatusResponse>, Status> {
-        let simulations = self.simulations.lock().await;
+        let snapshots = self.snapshots.all();
+        let now = Instant::now();
         let response = StatusResponse {
             status: "healthy".to_string(),
             version: "1.0.0".to_string(),
             implementation: "bevy".to_string(),
-            uptime_seconds: simulations.uptime_seconds(),
+            uptime_seconds: self.server_start_time.elapsed().as_secs() as i64,
+            simulation_count: snapshots.len() as i32,
+            total_chunks: snapshots.iter().map(|simulation| simulation.cells.chunk_count()).sum::<usize>() as i64,
+            generations_per_second: snapshots.iter().map(|simulation| simulation.pacing.achieved_gps(now)).sum(),
+            memory_bytes: snapshots.iter()
+                .map(|simulation| simulation.cells.stats().memory_bytes + self.replay_logs.usage(&simulation.id).1)
+                .sum::<u64>() as i64,
+            automata: registered_automata(),
+            active_simulation_count: snapshots.iter().filter(|simulation| simulation.pacing.achieved_gps(now) > 0.0).count() as i32,
+            total_live_cells: snapshots.iter().map(|simulation| simulation.get_live_cell_count()).sum(),
+            engine_versions: [("bevy".to_string(), env!("GOL_BEVY_VERSION").to_string())].into(),
+            limits: Some(self.limits),
+            build: Some(build_info()),
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn get_stats(&self, request: Request<StatsRequest>) -> Result<Response<StatsResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let region = req.region.map(|region| grid_region(Some(region)))
+            .transpose()
+            .map_err(|message| errors::invalid_field("region", &message))?;
+        let simulation = self.snapshots.get(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        
+        let stats = simulation.cells.stats();
+        // A region singles out one object, run as if nothing else were there
+        let cells = match region {
+            Some(region) => Some(simulation.get_live_cells().into_iter()
+                .filter(|&(x, y)| region.contains(x, y))
+                .collect::<Vec<_>>()),
+            None => (stats.live_cells <= STATS_PERIOD_MAX_CELLS).then(|| simulation.get_live_cells()),
+        };
+        // Detection follows live cells only, so it cannot see Generations decay
+        // or turmites
+        let periodicity = if let Some(cells) = cells.filter(|cells| cells.len() as u64 <= STATS_PERIOD_MAX_CELLS)
+            && simulation.rule.states() == 2
+            && simulation.turmite_rule.is_none()
+        {
+            let rule = simulation.rule.clone();
+            tokio::task::spawn_blocking(move || detect_periodicity(&cells, &rule, STATS_MAX_PERIOD))
+                .await
+                .map_err(|_| Status::new(Code::Internal, "Period detection worker stopped unexpectedly"))?
+        } else {
+            None
+        };
+        
+        let response = StatsResponse {
+            id: req.id,
+            generation: simulation.generation as i64,
+            live_cells: stats.live_cells as i64,
+            chunk_count: stats.chunk_count as i64,
+            chunk_size: CHUNK_SIZE,
+            memory_bytes: stats.memory_bytes as i64,
+            period: periodicity.map_or(0, |p| p.period as i32),
+            displacement_x: periodicity.map_or(0, |p| p.dx),
+            displacement_y: periodicity.map_or(0, |p| p.dy),
+            speed: periodicity.map(|p| p.speed()).unwrap_or_default(),
+            direction: periodicity.map(|p| p.direction()).unwrap_or_default(),
+        };
+        
+        Ok(Response::new(response))
+    }
+
+    async fn get_census(&self, request: Request<CensusRequest>) -> Result<Response<CensusResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let simulation = self.snapshots.get(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        if simulation.turmite_rule.is_some() {
+            return Err(Status::new(Code::FailedPrecondition, "Census does not support turmite simulations"));
+        }
+        // Object codes and names are those of Conway's Life
+        if !simulation.rule.is_conway() {
+            return Err(Status::new(Code::FailedPrecondition, format!("Census only supports B3/S23, not {}", simulation.rule)));
+        }
+
+        // Identifying objects runs each one for several generations
+        let cells = simulation.get_live_cells();
+        let census = tokio::task::spawn_blocking(move || take_census(&cells))
+            .await
+            .map_err(|_| Status::new(Code::Internal, "Census worker stopped unexpectedly"))?;
+
+        let response = CensusResponse {
+            id: req.id,
+            generation: simulation.generation as i64,
+            objects: census.objects.into_iter().map(|entry| CensusObject {
+                name: common_name(&entry.apgcode).unwrap_or_default().to_string(),
+                apgcode: entry.apgcode,
+                count: entry.count as i32,
+                cells: entry.cells as i64,
+            }).collect(),
+            unidentified_objects: census.unidentified_objects as i32,
+            unidentified_cells: census.unidentified_cells as i64,
+        };
+
+        Ok(Response::new(response))
+    }
+
+    async fn run_benchmark(&self, request: Request<BenchmarkRequest>) -> Result<Response<BenchmarkResponse>, Status> {
+        let client = self.quotas.client_key(&request)?;
+        let req = request.into_inner();
+        let name = if req.pattern.is_empty() { STANDARD_PATTERNS[0].0.to_string() } else { req.pattern.to_ascii_lowercase() };
+        let default_to = |value: i32, default: i32| if value == 0 { default } else { value };
+        let generations = default_to(req.generations, DEFAULT_BENCHMARK_GENERATIONS);
+        let (width, height) = (default_to(req.width, DEFAULT_BENCHMARK_SIZE), default_to(req.height, DEFAULT_BENCHMARK_SIZE));
+        
+        let mut violations = Vec::new();
+        let cells = standard_pattern(&name);
+        if cells.is_none() {
+            let names: Vec<&str> = STANDARD_PATTERNS.iter().map(|(pattern, _)| *pattern).collect();
+            violations.push(FieldViolation::new("pattern", format!("Expected one of {}", names.join(", "))));
+        }
+        if !(1..=MAX_BENCHMARK_GENERATIONS).contains(&generations) {
+            violations.push(FieldViolation::new("generations", format!("Must be between 1 and {}", MAX_BENCHMARK_GENERATIONS)));
+        }
+        for (field, size) in [("width", width), ("height", height)] {
+            if !(1..=MAX_GRID_SIZE).contains(&size) {
+                violations.push(FieldViolation::new(field, format!("Must be between 1 and {}", MAX_GRID_SIZE)));
+            }
+        }
+        let Some(cells) = cells.filter(|_| violations.is_empty()) else {
+            return Err(errors::invalid_fields("Invalid benchmark", violations));
+        };
+        self.quotas.take_steps(&client, generations as u64)?;
+        
+        let thread_pool = self.thread_pool.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            thread_pool.install(|| run_benchmark(&cells, width, height, generations as u64))
+        })
+        .await
+        .map_err(|_| Status::new(Code::Internal, "Benchmark worker stopped unexpectedly"))?;
+        
+        let seconds = result.elapsed.as_secs_f64().max(f64::EPSILON);
+        Ok(Response::new(BenchmarkResponse {
+            pattern: name,
+            implementation: "bevy".to_string(),
+            generations: result.generations as i64,
+            elapsed_ms: result.elapsed.as_secs_f64() * 1000.0,
+            generations_per_second: result.generations as f64 / seconds,
+            cell_updates_per_second: result.cell_updates as f64 / seconds,
+            final_population: result.final_population as i64,
+            peak_population: result.peak_population as i64,
+            peak_memory_bytes: result.peak_memory_bytes as i64,
+        }))
+    }
+
+    async fn create_simulation(&self, request: Request<CreateSimulationRequest>) -> Result<Response<SimulationResponse>, Status> {
+        let client = self.quotas.client_key(&request)?;
+        let req = request.into_inner();
+        let mut simulations = self.simulations.lock().await;
+        
+        if req.width <= 0 || req.height <= 0 {
+            return Err(errors::invalid_fields(
+                "Width and height must be positive",
+                dimension_violations(&req, |size| size <= 0, "must be positive"),
+            ));
+        }
+        
+        if req.width > MAX_GRID_SIZE || req.height > MAX_GRID_SIZE {
+            return Err(errors::invalid_fields(
+                "Grid size too large (max 1000x1000)",
+                dimension_violations(&req, |size| size > MAX_GRID_SIZE, "must be at most 1000"),
+            ));
+        }
+        
+        let id = match req.id.as_str() {
+            "" => simulations.unused_id(),
+            id if !is_slug(id) => {
+                return Err(errors::invalid_field("id", "Ids are lowercase letters, digits and hyphens, at most 64 long"));
+            }
+            id if simulations.get_simulation(id).is_some() => return Err(errors::simulation_exists(id)),
+            id => id.to_string(),
+        };
+        
+        let boundary = grid_boundary(req.boundary_mode)
+            .map_err(|message| errors::invalid_field("boundary_mode", &message))?;
+        let config = req.config.clone().unwrap_or_default();
+        validate_config(&config).map_err(|(field, message)| errors::invalid_field(field, &message))?;
+        
+        let soup = (req.initial_pattern == RANDOM_PATTERN).then(|| {
+            let mut rng = req.seed.map_or_else(SeededRng::from_entropy, SeededRng::new);
+            (rng.seed(), random_cells(&mut rng, req.width, req.height, RANDOM_DENSITY))
+        });
+        let starting_cells = soup.as_ref().map_or(0, |(_, cells)| cells.len() as u64);
+        self.quotas.check_new_simulation(&client, starting_cells, &self.snapshots)?;
+        
+        if !req.turmite.is_empty() {
+            if !req.rule.is_empty() {
+                return Err(errors::invalid_field("turmite", "Turmites cannot be combined with a cell rule"));
+            }
+            if soup.is_some() {
+                return Err(errors::invalid_field("initial_pattern", "Turmites start on an empty grid"));
+            }
+            let rule: TurmiteRule = req.turmite.parse()
+                .map_err(|error: TurmiteError| errors::invalid_field("turmite", &error.to_string()))?;
+            let turmites = starting_turmites(&req, &rule)
+                .map_err(|message| errors::invalid_field("turmites", &message))?;
+            let id = simulations.create_turmite_simulation_with_id(id, req.width, req.height, rule.clone(), turmites.clone());
+            
+            let simulation = simulations.get_simulation_mut(&id).unwrap();
+            simulation.boundary = boundary;
+            apply_config(simulation, &config);
+            simulation.webhooks = self.default_webhooks.clone();
+            self.snapshots.publish(simulation);
+            self.replay_logs.record(&id, ReplayOp::CreateTurmite { width: req.width, height: req.height, rule, boundary, turmites });
+            self.quotas.record_owner(&client, &id);
+            self.events.publish(simulation_event(SimulationEventType::Created, simulation));
+            
+            return Ok(Response::new(simulation_response(simulation, self.max_response_cells)));
+        }
+        if !req.turmites.is_empty() {
+            return Err(errors::invalid_field("turmites", "Turmites need a turmite rule"));
+        }
+        
+        let rule = requested_rule(&req.rule)
+            .map_err(|error| errors::invalid_field("rule", &error.to_string()))?;
+        
+        let id = simulations.create_simulation_with_id(id, req.width, req.height, rule.clone());
+        
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        simulation.boundary = boundary;
+        apply_config(simulation, &config);
+        simulation.webhooks = self.default_webhooks.clone();
+        if let Some((seed, cells)) = &soup {
+            simulation.set_cells(cells);
+            simulation.seed = Some(*seed);
+        }
+        self.snapshots.publish(simulation);
+        self.replay_logs.record(&id, ReplayOp::Create { width: req.width, height: req.height, rule, boundary });
+        if let Some((_, cells)) = soup {
+            self.replay_logs.record(&id, ReplayOp::AddPattern { cells, offset_x: 0, offset_y: 0 });
+        }
+        self.quotas.record_owner(&client, &id);
+        self.events.publish(simulation_event(SimulationEventType::Created, simulation));
+        if let Some(ecs) = &self.ecs {
+            ecs.create(simulation).await?;
+        }
+        
+        Ok(Response::new(simulation_response(simulation, self.max_response_cells)))
+    }
+
+    async fn get_simulation(&self, request: Request<GetSimulationRequest>) -> Result<Response<SimulationResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let read_mask = req.read_mask.as_ref().filter(|mask| !is_full_mask(mask));
+        if let Some(path) = read_mask.and_then(|mask| unknown_path(mask, &SIMULATION_FIELDS)) {
+            return Err(errors::invalid_field("read_mask", &format!("Unknown field path '{}'", path)));
+        }
+        
+        let mut simulation = self.simulation_at(&req.id, req.generation, "generation").await?;
+        if let Some(ecs) = &self.ecs && req.generation.is_none() && let Some(cells) = ecs.cells(&simulation).await {
+            let mut current = (*simulation).clone();
+            current.set_cells(&cells);
+            simulation = Arc::new(current);
+        }
+        
+        let response = match read_mask {
+            Some(mask) => masked_simulation_response(&simulation, mask, self.max_response_cells),
+            None => simulation_response(&simulation, self.max_response_cells),
+        };
+        Ok(Response::new(response))
+    }
+    
+    async fn get_cells(&self, request: Request<GetCellsRequest>) -> Result<Response<GetCellsResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        if req.page_size < 0 {
+            return Err(errors::invalid_field("page_size", "Page size must not be negative"));
+        }
+        let page_size = match req.page_size as usize {
+            0 => self.max_response_cells,
+            size => size.min(self.max_response_cells),
+        };
+        let (generation, after) = match req.page_token.as_str() {
+            "" => (None, None),
+            token => {
+                let (generation, after) = parse_page_token(token)
+                    .ok_or_else(|| errors::invalid_field("page_token", "Not a page token from GetCells"))?;
+                (Some(generation as i64), Some(after))
+            }
+        };
+        
+        let simulation = self.simulation_at(&req.id, generation, "page_token").await?;
+        let (cells, last) = cell_page(&simulation, after, page_size);
+        Ok(Response::new(GetCellsResponse {
+            cells,
+            next_page_token: last.map(|last| page_token(simulation.generation, last)).unwrap_or_default(),
+            generation: simulation.generation as i64,
+            total_cells: cell_total(&simulation) as i64,
+        }))
+    }
+
+    async fn get_summary(&self, request: Request<GetSummaryRequest>) -> Result<Response<SummaryResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let simulation = self.snapshots.get(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        
+        let bounds = simulation.cells.bounding_box().map(|((x0, y0), (x1, y1))| Region {
+            x: x0 as i64,
+            y: y0 as i64,
+            width: x1 as i64 - x0 as i64 + 1,
+            height: y1 as i64 - y0 as i64 + 1,
+        });
+        Ok(Response::new(SummaryResponse {
+            id: req.id,
+            generation: simulation.generation as i64,
+            live_cells: simulation.get_live_cell_count(),
+            bounds,
+            version: simulation.version,
+        }))
+    }
+
+    async fn update_simulation(&self, request: Request<UpdateSimulationRequest>) -> Result<Response<SimulationResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let update_mask = req.update_mask.as_ref();
+        if let Some(mask) = update_mask {
+            if let Some(path) = unknown_path(mask, &UPDATE_FIELDS) {
+                return Err(errors::invalid_field("update_mask", &format!("Unknown field path '{}'", path)));
+            }
+            if mask_has(mask, "cells") && mask_has(mask, "cells.alive") {
+                return Err(errors::invalid_field("update_mask", "'cells' and 'cells.alive' cannot be combined"));
+            }
+            if mask_has(mask, "generation") && req.generation < 0 {
+                return Err(errors::invalid_field("generation", "Generation must not be negative"));
+            }
+        }
+        let rule = match update_mask {
+            Some(mask) => mask_has(mask, "rule").then(|| requested_rule(&req.rule)).transpose(),
+            None if req.rule.is_empty() => Ok(None),
+            None => requested_rule(&req.rule).map(Some),
+        }.map_err(|error| errors::invalid_field("rule", &error.to_string()))?;
+        let boundary = grid_boundary(req.boundary_mode)
+            .map_err(|message| errors::invalid_field("boundary_mode", &message))?;
+        let boundary = match update_mask {
+            Some(mask) => mask_has(mask, "boundary_mode").then_some(boundary),
+            None => (boundary != GridBoundary::Clip).then_some(boundary),
+        };
+        
+        let edits_cells = match update_mask {
+            Some(mask) => mask_has(mask, "cells") || mask_has(mask, "cells.alive"),
+            None => !req.cells.is_empty(),
+        };
+        if edits_cells && !req.operations.is_empty() {
+            return Err(errors::invalid_field("operations", "Operations cannot be combined with cells"));
+        }
+        if req.expected_generation.is_some_and(|generation| generation < 0) {
+            return Err(errors::invalid_field("expected_generation", "Generation must not be negative"));
+        }
+        let (clear, cells, colors, states) = if !req.operations.is_empty() {
+            let (clear, cells) = cell_operations(&req.operations)
+                .map_err(|message| errors::invalid_field("operations", &message))?;
+            (clear, cells, Vec::new(), Vec::new())
+        } else if edits_cells {
+            let clear = update_mask.is_some_and(|mask| mask_has(mask, "cells"));
+            (clear, replay_cells(&req.cells), replay_colors(&req.cells), replay_states(&req.cells))
+        } else {
+            (false, Vec::new(), Vec::new(), Vec::new())
+        };
+        let generation = match update_mask {
+            Some(mask) => mask_has(mask, "generation").then_some(req.generation as u64),
+            None => (req.generation > 0).then_some(req.generation as u64),
+        };
+        
+        let alive = cells.iter().filter(|&&(_, _, alive)| alive).count();
+        self.quotas.check_cells(&req.id, alive as u64, &self.snapshots)?;
+        let mut simulations = self.simulations.lock().await;
+        
+        let simulation = simulations.get_simulation_mut(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        simulation.check_version(req.expected_version)?;
+        if let Some(expected) = req.expected_generation && simulation.generation != expected as u64 {
+            return Err(Status::new(
+                Code::Aborted,
+                format!("Simulation is at generation {}, not the expected {}", simulation.generation, expected),
+            ));
+        }
+        if rule.is_some() && simulation.turmite_rule.is_some() {
+            return Err(errors::invalid_field("rule", "Turmite simulations have no cell rule"));
+        }
+        
+        let op = ReplayOp::Update { rule, boundary, generation, clear, cells, colors, states };
+        
+        // Applied through the logged operation so a replay does exactly the same thing
+        op.apply(simulation);
+        self.replay_logs.record(&req.id, op);
+        
+        self.snapshots.publish(simulation);
+        
+        Ok(Response::new(simulation_response(simulation, self.max_response_cells)))
+    }
+
+    async fn delete_simulation(&self, request: Request<DeleteSimulationRequest>) -> Result<Response<DeleteResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let mut simulations = self.simulations.lock().await;
+        if let Some(simulation) = simulations.get_simulation(&req.id) {
+            simulation.check_version(req.expected_version)?;
+        }
+        
+        let last = self.snapshots.get(&req.id);
+        let success = simulations.delete_simulation(&req.id);
+        self.snapshots.remove(&req.id);
+        self.replay_logs.remove(&req.id);
+        self.quotas.forget(&req.id);
+        if let Some(ecs) = &self.ecs {
+            ecs.delete(&req.id).await;
+        }
+        if success && let Some(last) = last {
+            self.events.publish(simulation_event(SimulationEventType::Deleted, &last));
+        }
+        let response = DeleteResponse {
+            success,
+            message: if success {
+                "Simulation deleted successfully".to_string()
+            } else {
+                "Simulation not found".to_string()
+            },
+        };
+        
+        Ok(Response::new(response))
+    }
+
+    async fn list_simulations(&self, _request: Request<ListSimulationsRequest>) -> Result<Response<ListSimulationsResponse>, Status> {
+        let mut snapshots = self.snapshots.all();
+        snapshots.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        
+        Ok(Response::new(ListSimulationsResponse {
+            simulations: snapshots.iter()
+                .map(|simulation| simulation_summary(simulation, self.replay_logs.usage(&simulation.id)))
+                .collect(),
+        }))
+    }
+
+    async fn clone_simulation(&self, request: Request<CloneSimulationRequest>) -> Result<Response<SimulationResponse>, Status> {
+        let client = self.quotas.client_key(&request)?;
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let name = requested_name(&req.name)
+            .map_err(|message| errors::invalid_field("name", &message))?;
+        let source = self.snapshots.get(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        self.quotas.check_new_simulation(&client, source.get_live_cell_count() as u64, &self.snapshots)?;
+        let mut simulations = self.simulations.lock().await;
+        
+        let id = simulations.clone_simulation(&req.id, name)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        simulation.webhooks = self.default_webhooks.clone();
+        self.snapshots.publish(simulation);
+        // The copy's history starts from the state it was cloned in
+        self.replay_logs.record_restore(simulation);
+        self.quotas.record_owner(&client, &id);
+        self.events.publish(simulation_event(SimulationEventType::Created, simulation));
+        
+        Ok(Response::new(simulation_response(simulation, self.max_response_cells)))
+    }
+
+    async fn rename_simulation(&self, request: Request<RenameSimulationRequest>) -> Result<Response<SimulationResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let name = requested_name(&req.name)
+            .map_err(|message| errors::invalid_field("name", &message))?;
+        let mut simulations = self.simulations.lock().await;
+        
+        let simulation = simulations.get_simulation_mut(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        simulation.check_version(req.expected_version)?;
+        simulation.name = name;
+        self.snapshots.publish(simulation);
+        
+        Ok(Response::new(simulation_response(simulation, self.max_response_cells)))
+    }
+
+    async fn replay_simulation(&self, request: Request<ReplayRequest>) -> Result<Response<ReplayResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        if req.up_to < 0 {
+            return Err(errors::invalid_field("up_to", "Entry count must not be negative"));
+        }
+        
+        // Take the log and the live state under the same lock so they agree
+        let (entries, current) = {
+            let _simulations = self.simulations.lock().await;
+            let current = self.snapshots.get(&req.id)
+                .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+            (self.replay_logs.entries(&req.id).unwrap_or_default(), current)
+        };
+        
+        let total_entries = entries.len();
+        let replayed_entries = match req.up_to as usize {
+            0 => total_entries,
+            up_to => up_to.min(total_entries),
+        };
+        
+        // Replaying can step many generations, so keep it off the async workers
+        let id = req.id.clone();
+        let thread_pool = self.thread_pool.clone();
+        let (entries, replayed) = tokio::task::spawn_blocking(move || {
+            let replayed = thread_pool.install(|| replay(&id, &entries[..replayed_entries]));
+            (entries, replayed)
+        })
+        .await
+        .map_err(|_| Status::new(Code::Internal, "Replay worker stopped unexpectedly"))?;
+        let replayed = replayed.ok_or_else(|| Status::new(Code::FailedPrecondition, "Replay log has no starting entry"))?;
+        
+        let matches_current = replayed_entries == total_entries
+            && replayed.generation == current.generation
+            && replayed.cells == current.cells;
+        
+        let response = ReplayResponse {
+            simulation: Some(simulation_response(&replayed, self.max_response_cells)),
+            entries_replayed: replayed_entries as i32,
+            total_entries: total_entries as i32,
+            matches_current,
+            entries: if req.include_entries {
+                entries[..replayed_entries].iter().enumerate().map(replay_entry_message).collect()
+            } else {
+                Vec::new()
+            },
         };
+        
         Ok(Response::new(response))
     }
 
-    async fn create_simulation(&self, request: Request<CreateSimulationRequest>) -> Result<Response<SimulationResponse>, Status> {
+    async fn get_diff(&self, request: Request<DiffRequest>) -> Result<Response<DiffResponse>, Status> {
         let req = request.into_inner();
-        let mut simulations = self.simulations.lock().await;
+        let a = self.diff_target(req.a, "a").await?;
+        let b = self.diff_target(req.b, "b").await?;
         
-        if req.width <= 0 || req.height <= 0 {
-            return Err(Status::new(Code::InvalidArgument, "Width and height must be positive"));
+        let only_in_a = a.cells.difference(&b.cells);
+        let common_cells = a.get_live_cell_count() - only_in_a.len() as i64;
+        let positions = |cells: Vec<(i32, i32)>| cells.into_iter().map(|(x, y)| Position { x: x.into(), y: y.into() }).collect();
+        Ok(Response::new(DiffResponse {
+            only_in_b: positions(b.cells.difference(&a.cells)),
+            only_in_a: positions(only_in_a),
+            common_cells,
+            generation_a: a.generation as i64,
+            generation_b: b.generation as i64,
+        }))
+    }
+
+    async fn get_history(&self, request: Request<HistoryRequest>) -> Result<Response<HistoryResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let current = self.snapshots.get(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        
+        // An update that set the generation back can leave the log starting
+        // after the current generation
+        let mut first = first_generation(&self.replay_logs.entries(&req.id).unwrap_or_default())
+            .unwrap_or(current.generation)
+            .min(current.generation);
+        // Once the earliest keyframes are dropped, generations from the start
+        // of the log are only rebuilt as far as a capped replay reaches
+        if let Some(keyframe) = self.replay_logs.earliest_keyframe(&req.id)
+            && keyframe > first + MAX_REPLAY_STEPS
+        {
+            first = keyframe.min(current.generation);
         }
+        Ok(Response::new(HistoryResponse {
+            id: req.id,
+            first_generation: first as i64,
+            head_generation: current.generation as i64,
+        }))
+    }
+
+    async fn get_statistics(&self, request: Request<StatisticsRequest>) -> Result<Response<StatisticsResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let current = self.snapshots.get(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        let entries = self.replay_logs.entries(&req.id).unwrap_or_default();
+        let first = first_generation(&entries).unwrap_or(current.generation).min(current.generation);
         
-        if req.width > 1000 || req.height > 1000 {
-            return Err(Status::new(Code::InvalidArgument, "Grid size too large (max 1000x1000)"));
+        let from = req.from_generation.unwrap_or(first as i64);
+        let to = req.to_generation.unwrap_or(current.generation as i64);
+        let mut violations = Vec::new();
+        if from < first as i64 {
+            violations.push(FieldViolation::new("from_generation", format!("Generations before {} are not in the replay log", first)));
+        }
+        if to > current.generation as i64 {
+            violations.push(FieldViolation::new("to_generation", format!("Simulation is only at generation {}", current.generation)));
+        }
+        if to < from {
+            violations.push(FieldViolation::new("to_generation", "Must not be before from_generation"));
+        } else if (to - from) as u64 > MAX_STATISTICS_GENERATIONS {
+            violations.push(FieldViolation::new("to_generation", format!("At most {} generations per request", MAX_STATISTICS_GENERATIONS)));
+        }
+        if !violations.is_empty() {
+            return Err(errors::invalid_fields("Invalid generation range", violations));
         }
         
-        let id = simulations.create_simulation(req.width, req.height, 
-            if req.initial_pattern.is_empty() { None } else { Some(req.initial_pattern) });
+        let id = req.id.clone();
+        let thread_pool = self.thread_pool.clone();
+        let samples = tokio::task::spawn_blocking(move || {
+            thread_pool.install(|| replay_statistics(&id, &entries, from as u64, to as u64))
+        })
+        .await
+        .map_err(|_| Status::new(Code::Internal, "Replay worker stopped unexpectedly"))?
+        .ok_or_else(|| Status::new(Code::FailedPrecondition, "Replay log has no starting entry"))?;
         
-        let simulation = simulations.get_simulation(&id).unwrap();
-        let response = SimulationResponse {
-            id: id.clone(),
-            generation: simulation.generation as i64,
-            live_cells: simulation.get_live_cell_count(),
-            grid: Some(GridInfo {
-                width: simulation.width,
-                height: simulation.height,
-            }),
-            cells: simulation.get_live_cells().into_iter().map(|(x, y)| {
-                let cell_state = simulation.cells.get(&(x, y)).unwrap();
-                Cell {
-                    x,
-                    y,
-                    alive: cell_state.alive,
-                    neighbors: cell_state.neighbor_count as i32,
-                }
+        Ok(Response::new(StatisticsResponse {
+            id: req.id,
+            generations: samples.into_iter().map(|sample| GenerationStatistics {
+                generation: sample.generation as i64,
+                population: sample.population as i64,
+                births: sample.births as i64,
+                deaths: sample.deaths as i64,
             }).collect(),
+        }))
+    }
+
+    async fn get_heatmap(&self, request: Request<HeatmapRequest>) -> Result<Response<HeatmapResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let generations = match req.generations {
+            0 => DEFAULT_HEATMAP_GENERATIONS,
+            generations if (1..=MAX_HEATMAP_GENERATIONS).contains(&generations) => generations,
+            _ => return Err(errors::invalid_field("generations", &format!("Generations must be between 1 and {}", MAX_HEATMAP_GENERATIONS))),
         };
+        let current = self.snapshots.get(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        let entries = self.replay_logs.entries(&req.id).unwrap_or_default();
+        let first = first_generation(&entries).unwrap_or(current.generation).min(current.generation);
+        // A window reaching back past the log covers what the log still holds
+        let to = current.generation;
+        let from = to.saturating_sub(generations as u64).max(first);
         
-        Ok(Response::new(response))
+        let id = req.id.clone();
+        let thread_pool = self.thread_pool.clone();
+        let activity = tokio::task::spawn_blocking(move || {
+            thread_pool.install(|| replay_activity(&id, &entries, from, to))
+        })
+        .await
+        .map_err(|_| Status::new(Code::Internal, "Replay worker stopped unexpectedly"))?
+        .ok_or_else(|| Status::new(Code::FailedPrecondition, "Replay log has no starting entry"))?;
+        
+        let mut cells: Vec<HeatCell> = activity.into_iter()
+            .map(|((x, y), count)| HeatCell { x: x as i64, y: y as i64, count: count as i32 })
+            .collect();
+        cells.sort_unstable_by_key(|cell| (cell.y, cell.x));
+        let max_count = cells.iter().map(|cell| cell.count).max().unwrap_or(0);
+        let truncated = cells.len() > self.max_response_cells;
+        cells.truncate(self.max_response_cells);
+        
+        Ok(Response::new(HeatmapResponse {
+            id: req.id,
+            from_generation: from as i64,
+            to_generation: to as i64,
+            max_count,
+            cells,
+            truncated,
+        }))
     }
 
-    async fn get_simulation(&self, request: Request<GetSimulationRequest>) -> Result<Response<SimulationResponse>, Status> {
+    async fn verify_simulation(&self, request: Request<VerifyRequest>) -> Result<Response<VerifyResponse>, Status> {
         let req = request.into_inner();
-        let simulations = self.simulations.lock().await;
+        self.rate_limiter.check_simulation(&req.id)?;
+        let generations = match req.generations {
+            0 => DEFAULT_VERIFY_GENERATIONS,
+            generations if generations < 0 || generations as u64 > MAX_VERIFY_GENERATIONS => {
+                return Err(errors::invalid_field("generations", &format!("Must be between 0 and {}", MAX_VERIFY_GENERATIONS)));
+            }
+            generations => generations as u64,
+        };
         
-        let simulation = simulations.get_simulation(&req.id)
-            .ok_or_else(|| Status::new(Code::NotFound, "Simulation not found"))?;
+        // Take the log and the live state under the same lock so they agree
+        let (entries, current) = {
+            let _simulations = self.simulations.lock().await;
+            let current = self.snapshots.get(&req.id)
+                .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+            (self.replay_logs.entries(&req.id).unwrap_or_default(), current)
+        };
         
-        let response = SimulationResponse {
-            id: req.id.clone(),
-            generation: simulation.generation as i64,
-            live_cells: simulation.get_live_cell_count(),
-            grid: Some(GridInfo {
-                width: simulation.width,
-                height: simulation.height,
-            }),
-            cells: simulation.get_live_cells().into_iter().map(|(x, y)| {
-                let cell_state = simulation.cells.get(&(x, y)).unwrap();
-                Cell {
-                    x,
-                    y,
-                    alive: cell_state.alive,
-                    neighbors: cell_state.neighbor_count as i32,
-                }
+        let thread_pool = self.thread_pool.clone();
+        let checks = req.checks.clone();
+        let simulation = current.clone();
+        let results = tokio::task::spawn_blocking(move || {
+            thread_pool.install(|| verify_simulation(&simulation, &entries, generations, &checks))
+        })
+        .await
+        .map_err(|_| Status::new(Code::Internal, "Verification worker stopped unexpectedly"))?
+        .map_err(|message| errors::invalid_field("checks", &message))?;
+        
+        Ok(Response::new(VerifyResponse {
+            id: req.id,
+            generation: current.generation as i64,
+            passed: results.iter().all(|check| check.status != CheckStatus::Failed),
+            results: results.into_iter().map(|check| InvariantResult {
+                name: check.name.to_string(),
+                status: match check.status {
+                    CheckStatus::Passed => InvariantStatus::Passed,
+                    CheckStatus::Failed => InvariantStatus::Failed,
+                    CheckStatus::Skipped => InvariantStatus::Skipped,
+                } as i32,
+                detail: check.detail,
             }).collect(),
+        }))
+    }
+
+    async fn step_simulation(&self, request: Request<StepSimulationRequest>) -> Result<Response<StepResponse>, Status> {
+        let client = self.quotas.client_key(&request)?;
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        
+        let steps = if req.steps <= 0 { 1 } else { req.steps as u32 };
+        let simulation = self.snapshots.get(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        self.quotas.check_cells(&req.id, 0, &self.snapshots)?;
+        self.quotas.take_steps(&client, steps as u64)?;
+        
+        if steps > self.max_inline_steps {
+            // A job runs its steps in slices, so only its start is checked
+            simulation.check_version(req.expected_version)?;
+            let live_cells = simulation.get_live_cell_count();
+            let job_id = self.jobs.spawn_step_job(&req.id, steps, simulation.generation, live_cells);
+            
+            return Ok(Response::new(StepResponse {
+                generation: simulation.generation as i64,
+                live_cells,
+                changed_cells: 0,
+                job_id,
+                completed: simulation.is_completed(),
+                version: simulation.version,
+                breakpoint: None,
+                watchpoint: None,
+            }));
+        }
+        
+        let outcome = self.step_queue.step(&req.id, steps, req.expected_version).await?;
+        
+        let response = StepResponse {
+            generation: outcome.generation as i64,
+            live_cells: outcome.live_cells,
+            changed_cells: outcome.changed_cells,
+            job_id: String::new(),
+            completed: outcome.completed,
+            version: outcome.version,
+            breakpoint: outcome.breakpoint.as_ref().map(breakpoint_message),
+            watchpoint: outcome.watchpoint.map(|region| Watchpoint { region: Some(region_message(region)), pause: true }),
         };
         
         Ok(Response::new(response))
     }
 
-    async fn update_simulation(&self, request: Request<UpdateSimulationRequest>) -> Result<Response<SimulationResponse>, Status> {
+    async fn load_pattern(&self, request: Request<LoadPatternRequest>) -> Result<Response<LoadPatternResponse>, Status> {
         let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let overwrite = OverwritePolicy::try_from(req.overwrite)
+            .map_err(|_| errors::invalid_field("overwrite", "Unknown overwrite policy"))?;
         let mut simulations = self.simulations.lock().await;
         
         let simulation = simulations.get_simulation_mut(&req.id)
-            .ok_or_else(|| Status::new(Code::NotFound, "Simulation not found"))?;
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        simulation.check_version(req.expected_version)?;
+        
+        let (offset_x, offset_y) = grid_position(req.position)
+            .map_err(|message| errors::invalid_field("position", &message))?;
+        // Cells past the coordinate range are off the grid before placement
+        let (name, pattern_cells, unplaceable): (_, Vec<_>, _) = if req.apgcode.is_empty() {
+            let pattern = req.pattern.ok_or_else(|| errors::invalid_field("pattern", "Pattern is required"))?;
+            let total = pattern.cells.len();
+            let cells: Vec<_> = pattern.cells.into_iter().filter_map(|pos| grid_coordinates(pos.x, pos.y)).collect();
+            let unplaceable = total - cells.len();
+            (pattern.name, cells, unplaceable)
+        } else {
+            let cells = decode_apgcode(&req.apgcode)
+                .map_err(|error| errors::invalid_field("apgcode", &error.to_string()))?;
+            (req.apgcode, cells, 0)
+        };
+        self.quotas.check_cells(&req.id, pattern_cells.len() as u64, &self.snapshots)?;
         
-        if req.generation > 0 {
-            simulation.generation = req.generation as u64;
+        let placement = simulation.place_pattern(&pattern_cells, offset_x, offset_y);
+        let out_of_bounds = placement.out_of_bounds + unplaceable;
+        let overlapping = placement.overlapping(simulation);
+        let bounds = placement.bounds();
+        if overwrite == OverwritePolicy::Error && overlapping > 0 {
+            return Err(Status::new(
+                Code::FailedPrecondition,
+                format!("Pattern '{}' would land on {} live cells", name, overlapping),
+            ));
         }
         
-        if !req.cells.is_empty() {
-            simulation.cells.clear();
-            for cell in req.cells {
-                if cell.x >= 0 && cell.x < simulation.width && cell.y >= 0 && cell.y < simulation.height {
-                    simulation.cells.insert((cell.x, cell.y), CellState {
-                        alive: cell.alive,
-                        generation: simulation.generation,
-                        neighbor_count: cell.neighbors as u8,
-                    });
-                }
-            }
+        let mut cells_replaced = 0;
+        if let (OverwritePolicy::Replace, Some(region)) = (overwrite, bounds) {
+            cells_replaced = simulation.clear_region(region);
+            self.replay_logs.record(&req.id, ReplayOp::ClearRegion { region });
         }
+        let cells_added = simulation.add_pattern(&pattern_cells, offset_x, offset_y);
+        self.snapshots.publish(simulation);
+        self.replay_logs.record(&req.id, ReplayOp::AddPattern {
+            cells: pattern_cells,
+            offset_x,
+            offset_y,
+        });
+        self.events.publish(SimulationEvent {
+            cells_added: cells_added as i64,
+            ..simulation_event(SimulationEventType::PatternLoaded, simulation)
+        });
         
-        let response = SimulationResponse {
-            id: req.id.clone(),
-            generation: simulation.generation as i64,
-            live_cells: simulation.get_live_cell_count(),
-            grid: Some(GridInfo {
-                width: simulation.width,
-                height: simulation.height,
-            }),
-            cells: simulation.get_live_cells().into_iter().map(|(x, y)| {
-                let cell_state = simulation.cells.get(&(x, y)).unwrap();
-                Cell {
-                    x,
-                    y,
-                    alive: cell_state.alive,
-                    neighbors: cell_state.neighbor_count as i32,
+        let message = match bounds {
+            None => format!("No cells were placed ({} outside the grid)", out_of_bounds),
+            Some(_) => {
+                let mut message = format!("Pattern '{}' loaded: {} cells added", name, cells_added);
+                if out_of_bounds > 0 {
+                    message += &format!(", {} outside the grid dropped", out_of_bounds);
                 }
-            }).collect(),
+                if overwrite == OverwritePolicy::Skip && overlapping > 0 {
+                    message += &format!(", {} already alive", overlapping);
+                }
+                if cells_replaced > 0 {
+                    message += &format!(", {} live cells replaced", cells_replaced);
+                }
+                message
+            }
+        };
+        let response = LoadPatternResponse {
+            success: bounds.is_some(),
+            cells_added,
+            message,
+            cells_out_of_bounds: out_of_bounds as i32,
+            cells_overlapping: overlapping as i32,
+            cells_replaced: cells_replaced as i32,
+            bounds: bounds.map(|region| Region {
+                x: region.x as i64,
+                y: region.y as i64,
+                width: region.width as i64,
+                height: region.height as i64,
+            }),
+            version: simulation.version,
         };
         
         Ok(Response::new(response))
     }
 
-    async fn delete_simulation(&self, request: Request<DeleteSimulationRequest>) -> Result<Response<DeleteResponse>, Status> {
+    async fn upload_pattern(&self, request: Request<Streaming<PatternChunk>>) -> Result<Response<LoadPatternResponse>, Status> {
+        let response = self.upload_pattern_chunks(request.into_inner()).await?;
+        Ok(Response::new(response))
+    }
+
+    async fn clear_region(&self, request: Request<ClearRegionRequest>) -> Result<Response<RegionResponse>, Status> {
         let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let region = grid_region(req.region).map_err(|message| errors::invalid_field("region", &message))?;
         let mut simulations = self.simulations.lock().await;
         
-        let success = simulations.delete_simulation(&req.id);
-        let response = DeleteResponse {
-            success,
-            message: if success {
-                "Simulation deleted successfully".to_string()
-            } else {
-                "Simulation not found".to_string()
-            },
-        };
+        let simulation = simulations.get_simulation_mut(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        simulation.check_version(req.expected_version)?;
+        let cells_before = simulation.clear_region(region);
+        self.replay_logs.record(&req.id, ReplayOp::ClearRegion { region });
+        self.snapshots.publish(simulation);
         
-        Ok(Response::new(response))
+        Ok(Response::new(RegionResponse {
+            id: req.id,
+            generation: simulation.generation as i64,
+            cells_before: cells_before as i64,
+            cells_after: 0,
+            live_cells: simulation.get_live_cell_count(),
+            seed: None,
+            version: simulation.version,
+        }))
     }
 
-    async fn step_simulation(&self, request: Request<StepSimulationRequest>) -> Result<Response<StepResponse>, Status> {
+    async fn fill_region(&self, request: Request<FillRegionRequest>) -> Result<Response<RegionResponse>, Status> {
         let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let region = grid_region(req.region).map_err(|message| errors::invalid_field("region", &message))?;
+        if !req.solid && !(0.0..=1.0).contains(&req.density) {
+            return Err(errors::invalid_field("density", "Density must be between 0 and 1"));
+        }
+        let fill = if req.solid {
+            RegionFill::Solid
+        } else {
+            let seed = req.seed.unwrap_or_else(|| SeededRng::from_entropy().next_u64());
+            RegionFill::Random { seed, density: req.density }
+        };
         let mut simulations = self.simulations.lock().await;
         
         let simulation = simulations.get_simulation_mut(&req.id)
-            .ok_or_else(|| Status::new(Code::NotFound, "Simulation not found"))?;
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        simulation.check_version(req.expected_version)?;
+        let area = region.clipped_area(simulation.width, simulation.height);
+        if area > MAX_FILL_CELLS {
+            return Err(errors::invalid_field("region", &format!(
+                "Region covers {} cells of the grid; at most {} can be filled at once", area, MAX_FILL_CELLS,
+            )));
+        }
+        let expected = if req.solid { area } else { (area as f64 * req.density).ceil() as u64 };
+        self.quotas.check_cells(&req.id, expected, &self.snapshots)?;
         
-        let steps = if req.steps <= 0 { 1 } else { req.steps };
-        let initial_cells = simulation.get_live_cell_count();
+        let (cells_before, cells_after) = simulation.fill_region(region, fill);
+        self.replay_logs.record(&req.id, ReplayOp::FillRegion { region, fill });
+        self.snapshots.publish(simulation);
         
-        // Apply Game of Life rules for the specified number of steps
-        for _ in 0..steps {
-            simulation.generation += 1;
-            
-            // Calculate neighbors for all cells
-            let mut neighbor_counts: std::collections::HashMap<(i32, i32), u8> = std::collections::HashMap::new();
-            
-            for ((x, y), cell) in &simulation.cells {
-                if cell.alive {
-                    let neighbors = [
-                        (x - 1, y - 1), (*x, y - 1), (x + 1, y - 1),
-                        (x - 1, *y),                  (x + 1, *y),
-                        (x - 1, y + 1), (*x, y + 1), (x + 1, y + 1),
-                    ];
-                    
-                    for (nx, ny) in neighbors {
-                        if nx >= 0 && nx < simulation.width && ny >= 0 && ny < simulation.height {
-                            *neighbor_counts.entry((nx, ny)).or_insert(0) += 1;
-                        }
-                    }
-                }
-            }
-            
-            // Apply Game of Life rules
-            let mut new_cells = std::collections::HashMap::new();
-            
-            // Check all positions that might have cells
-            for ((x, y), neighbor_count) in neighbor_counts {
-                let currently_alive = simulation.cells.get(&(x, y)).map(|c| c.alive).unwrap_or(false);
-                
-                let will_be_alive = if currently_alive {
-                    neighbor_count == 2 || neighbor_count == 3
-                } else {
-                    neighbor_count == 3
-                };
-                
-                if will_be_alive {
-                    new_cells.insert((x, y), CellState {
-                        alive: true,
-                        generation: simulation.generation,
-                        neighbor_count,
-                    });
-                }
-            }
-            
-            simulation.cells = new_cells;
+        Ok(Response::new(RegionResponse {
+            id: req.id,
+            generation: simulation.generation as i64,
+            cells_before: cells_before as i64,
+            cells_after: cells_after as i64,
+            live_cells: simulation.get_live_cell_count(),
+            seed: match fill {
+                RegionFill::Random { seed, .. } => Some(seed),
+                RegionFill::Solid => None,
+            },
+            version: simulation.version,
+        }))
+    }
+
+    async fn export_pattern(&self, request: Request<ExportPatternRequest>) -> Result<Response<ExportPatternResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let format = PatternFormat::try_from(req.format)
+            .map_err(|_| errors::invalid_field("format", "Unknown pattern format"))?;
+        
+        let simulation = self.snapshots.get(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        let cells = simulation.get_live_cells();
+        let content = match format {
+            PatternFormat::PatternRle => encode_rle(&cells, &simulation.rule),
+            PatternFormat::PatternMacrocell => encode_macrocell(&cells, &simulation.rule),
+        };
+        
+        Ok(Response::new(ExportPatternResponse {
+            id: req.id,
+            format: format as i32,
+            content,
+            generation: simulation.generation as i64,
+            live_cells: cells.len() as i64,
+        }))
+    }
+
+    async fn set_rule(&self, request: Request<SetRuleRequest>) -> Result<Response<SetRuleResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        // An empty rule cancels a scheduled change rather than scheduling B3/S23
+        let rule = if req.rule.is_empty() && req.at_generation.is_some() {
+            None
+        } else {
+            Some(requested_rule(&req.rule).map_err(|error| errors::invalid_field("rule", &error.to_string()))?)
+        };
+        
+        let mut simulations = self.simulations.lock().await;
+        let simulation = simulations.get_simulation_mut(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        simulation.check_version(req.expected_version)?;
+        if simulation.turmite_rule.is_some() {
+            return Err(errors::invalid_field("rule", "Turmite simulations have no cell rule"));
+        }
+        let schedules = req.at_generation.filter(|&generation| generation > simulation.generation);
+        if rule.is_some()
+            && let Some(generation) = schedules
+            && !simulation.rule_changes.contains_key(&generation)
+            && simulation.rule_changes.len() >= MAX_RULE_CHANGES
+        {
+            return Err(errors::invalid_field("at_generation", &format!("At most {} rule changes may be scheduled", MAX_RULE_CHANGES)));
         }
         
-        let final_cells = simulation.get_live_cell_count();
-        let changed_cells = (initial_cells as i64 - final_cells as i64).abs();
+        let op = ReplayOp::SetRule { rule, generation: req.at_generation };
+        op.apply(simulation);
+        self.replay_logs.record(&req.id, op);
+        self.snapshots.publish(simulation);
         
-        let response = StepResponse {
+        Ok(Response::new(SetRuleResponse {
+            id: req.id,
             generation: simulation.generation as i64,
-            live_cells: final_cells,
-            changed_cells,
+            rule: simulation.rule.to_string(),
+            rule_changes: rule_change_messages(simulation),
+            version: simulation.version,
+        }))
+    }
+
+    async fn set_pacing(&self, request: Request<SetPacingRequest>) -> Result<Response<PacingResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        
+        let valid_target = req.target_generations_per_second.is_finite() && req.target_generations_per_second > 0.0;
+        if !req.turbo && !valid_target {
+            return Err(errors::invalid_field(
+                "target_generations_per_second",
+                "Target generations per second must be positive unless turbo is set",
+            ));
+        }
+        
+        let mut simulations = self.simulations.lock().await;
+        let simulation = simulations.get_simulation_mut(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        simulation.check_version(req.expected_version)?;
+        
+        simulation.pacing.set_target(if req.turbo { None } else { Some(req.target_generations_per_second) });
+        self.snapshots.publish(simulation);
+        
+        let response = PacingResponse {
+            id: req.id,
+            target_generations_per_second: simulation.pacing.target_gps().unwrap_or(0.0),
+            turbo: simulation.pacing.is_turbo(),
+            achieved_generations_per_second: simulation.pacing.achieved_gps(Instant::now()),
+            version: simulation.version,
         };
         
         Ok(Response::new(response))
     }
 
-    async fn load_pattern(&self, request: Request<LoadPatternRequest>) -> Result<Response<LoadPatternResponse>, Status> {
+    async fn set_webhooks(&self, request: Request<SetWebhooksRequest>) -> Result<Response<WebhooksResponse>, Status> {
         let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let violations = webhook_violations(&req.webhooks);
+        if !violations.is_empty() {
+            return Err(errors::invalid_fields("Invalid webhooks", violations));
+        }
+        
         let mut simulations = self.simulations.lock().await;
+        let simulation = simulations.get_simulation_mut(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        simulation.check_version(req.expected_version)?;
+        simulation.webhooks = req.webhooks.iter().map(milestone_webhook).collect();
+        self.snapshots.publish(simulation);
+        
+        Ok(Response::new(WebhooksResponse {
+            id: req.id,
+            webhooks: simulation.webhooks.iter().map(webhook_message).collect(),
+            version: simulation.version,
+        }))
+    }
+
+    async fn set_breakpoints(&self, request: Request<SetBreakpointsRequest>) -> Result<Response<BreakpointsResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
         
+        let mut simulations = self.simulations.lock().await;
         let simulation = simulations.get_simulation_mut(&req.id)
-            .ok_or_else(|| Status::new(Code::NotFound, "Simulation not found"))?;
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        simulation.check_version(req.expected_version)?;
+        let conway = simulation.turmite_rule.is_none() && simulation.rule.is_conway();
+        let conditions = break_conditions(&req.breakpoints, conway)
+            .map_err(|violations| errors::invalid_fields("Invalid breakpoints", violations))?;
+        simulation.breakpoints = conditions.into_iter()
+            .map(|condition| crate::resources::Breakpoint::new(condition, &simulation.cells))
+            .collect();
+        self.snapshots.publish(simulation);
         
-        let pattern = req.pattern.ok_or_else(|| Status::new(Code::InvalidArgument, "Pattern is required"))?;
-        let position = req.position.ok_or_else(|| Status::new(Code::InvalidArgument, "Position is required"))?;
+        Ok(Response::new(BreakpointsResponse {
+            id: req.id,
+            breakpoints: breakpoint_messages(simulation),
+            version: simulation.version,
+        }))
+    }
+
+    async fn set_watchpoints(&self, request: Request<SetWatchpointsRequest>) -> Result<Response<WatchpointsResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
         
-        let pattern_cells: Vec<(i32, i32)> = pattern.cells.into_iter()
-            .map(|pos| (pos.x, pos.y))
+        let mut simulations = self.simulations.lock().await;
+        let simulation = simulations.get_simulation_mut(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        simulation.check_version(req.expected_version)?;
+        let regions = watch_regions(&req.watchpoints, simulation.width, simulation.height)
+            .map_err(|violations| errors::invalid_fields("Invalid watchpoints", violations))?;
+        simulation.watchpoints = regions.into_iter()
+            .map(|(region, pause)| crate::resources::Watchpoint::new(region, pause, simulation.width, simulation.height, &simulation.cells))
             .collect();
+        self.snapshots.publish(simulation);
         
-        let cells_added = simulation.add_pattern(&pattern_cells, position.x, position.y);
+        Ok(Response::new(WatchpointsResponse {
+            id: req.id,
+            watchpoints: watchpoint_messages(simulation),
+            version: simulation.version,
+        }))
+    }
+
+    async fn set_annotations(&self, request: Request<SetAnnotationsRequest>) -> Result<Response<AnnotationsResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let annotations = requested_annotations(&req.annotations)
+            .map_err(|violations| errors::invalid_fields("Invalid annotations", violations))?;
         
-        let response = LoadPatternResponse {
-            success: cells_added > 0,
-            cells_added,
-            message: if cells_added > 0 {
-                format!("Pattern '{}' loaded successfully", pattern.name)
-            } else {
-                "No cells were added (pattern outside grid or cells already exist)".to_string()
-            },
-        };
+        let mut simulations = self.simulations.lock().await;
+        let simulation = simulations.get_simulation_mut(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        simulation.check_version(req.expected_version)?;
+        simulation.annotations = annotations;
+        self.snapshots.publish(simulation);
         
-        Ok(Response::new(response))
+        Ok(Response::new(AnnotationsResponse {
+            id: req.id,
+            annotations: simulation.annotations.iter().map(annotation_message).collect(),
+            version: simulation.version,
+        }))
+    }
+
+    async fn get_annotations(&self, request: Request<GetAnnotationsRequest>) -> Result<Response<AnnotationsResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let region = req.region.map(|region| grid_region(Some(region)))
+            .transpose()
+            .map_err(|message| errors::invalid_field("region", &message))?;
+        let simulation = self.snapshots.get(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        
+        Ok(Response::new(AnnotationsResponse {
+            id: req.id,
+            annotations: simulation.annotations.iter()
+                .filter(|annotation| region.is_none_or(|region| annotation.is_in(&region)))
+                .map(annotation_message)
+                .collect(),
+            version: simulation.version,
+        }))
+    }
+
+    async fn create_checkpoint(&self, request: Request<CreateCheckpointRequest>) -> Result<Response<CheckpointsResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let name = checkpoint_name(&req.name)
+            .map_err(|message| errors::invalid_field("name", &message))?;
+        
+        let mut simulations = self.simulations.lock().await;
+        let simulation = simulations.get_simulation_mut(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        simulation.check_version(req.expected_version)?;
+        simulation.create_checkpoint(name)
+            .map_err(|message| Status::new(Code::FailedPrecondition, message))?;
+        self.snapshots.publish(simulation);
+        
+        Ok(Response::new(checkpoints_response(simulation)))
+    }
+
+    async fn list_checkpoints(&self, request: Request<ListCheckpointsRequest>) -> Result<Response<CheckpointsResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let simulation = self.snapshots.get(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        
+        Ok(Response::new(checkpoints_response(&simulation)))
+    }
+
+    async fn delete_checkpoint(&self, request: Request<DeleteCheckpointRequest>) -> Result<Response<CheckpointsResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let mut simulations = self.simulations.lock().await;
+        let simulation = simulations.get_simulation_mut(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        simulation.check_version(req.expected_version)?;
+        if !simulation.delete_checkpoint(req.name.trim()) {
+            return Err(errors::checkpoint_not_found(&req.id, req.name.trim()));
+        }
+        self.snapshots.publish(simulation);
+        
+        Ok(Response::new(checkpoints_response(simulation)))
+    }
+
+    async fn restore_checkpoint(&self, request: Request<RestoreCheckpointRequest>) -> Result<Response<SimulationResponse>, Status> {
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let mut simulations = self.simulations.lock().await;
+        let simulation = simulations.get_simulation_mut(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        simulation.check_version(req.expected_version)?;
+        if !simulation.restore_checkpoint(req.name.trim()) {
+            return Err(errors::checkpoint_not_found(&req.id, req.name.trim()));
+        }
+        // Logged with the whole restored state, so replays carry on past the rollback
+        self.replay_logs.record_restore(simulation);
+        self.snapshots.publish(simulation);
+        
+        Ok(Response::new(simulation_response(simulation, self.max_response_cells)))
+    }
+
+    async fn get_job_status(&self, request: Request<JobStatusRequest>) -> Result<Response<JobStatusResponse>, Status> {
+        let req = request.into_inner();
+        let status = self.jobs.status(&req.job_id)
+            .ok_or_else(|| errors::job_not_found(&req.job_id))?;
+        
+        Ok(Response::new(status))
+    }
+
+    async fn cancel_job(&self, request: Request<CancelJobRequest>) -> Result<Response<JobStatusResponse>, Status> {
+        let req = request.into_inner();
+        let status = self.jobs.cancel(&req.job_id)
+            .ok_or_else(|| errors::job_not_found(&req.job_id))?;
+        
+        Ok(Response::new(status))
+    }
+
+    async fn submit_batch(&self, request: Request<BatchSpec>) -> Result<Response<BatchStatus>, Status> {
+        let client = self.quotas.client_key(&request)?;
+        let req = request.into_inner();
+        let spec = batch_run_spec(&req)
+            .map_err(|violations| errors::invalid_fields("Invalid batch", violations))?;
+        let name = requested_name(&req.name).unwrap_or_default().unwrap_or_default();
+        let repetitions = if req.repetitions == 0 { 1 } else { req.repetitions as u32 };
+        // Every repetition steps the whole run
+        self.quotas.take_steps(&client, repetitions as u64 * spec.generations)?;
+        
+        Ok(Response::new(self.batches.submit(&name, spec, req.seed, repetitions)))
+    }
+
+    async fn list_batches(&self, _request: Request<ListBatchesRequest>) -> Result<Response<ListBatchesResponse>, Status> {
+        Ok(Response::new(ListBatchesResponse { batches: self.batches.list() }))
+    }
+
+    async fn get_batch_results(&self, request: Request<BatchResultsRequest>) -> Result<Response<BatchResultsResponse>, Status> {
+        let req = request.into_inner();
+        let (status, results) = self.batches.results(&req.batch_id)
+            .ok_or_else(|| errors::batch_not_found(&req.batch_id))?;
+        
+        Ok(Response::new(BatchResultsResponse { status: Some(status), results }))
+    }
+
+    async fn cancel_batch(&self, request: Request<CancelBatchRequest>) -> Result<Response<BatchStatus>, Status> {
+        let req = request.into_inner();
+        let status = self.batches.cancel(&req.batch_id)
+            .ok_or_else(|| errors::batch_not_found(&req.batch_id))?;
+        
+        Ok(Response::new(status))
+    }
+
+    async fn run_experiment(&self, request: Request<ExperimentRequest>) -> Result<Response<ExperimentResponse>, Status> {
+        let client = self.quotas.client_key(&request)?;
+        let req = request.into_inner();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let source = self.snapshots.get(&req.id)
+            .ok_or_else(|| errors::simulation_not_found(&req.id))?;
+        let perturbations = experiment_perturbations(&req, &source)
+            .map_err(|violations| errors::invalid_fields("Invalid experiment", violations))?;
+        // The control and every variant each step the whole run
+        self.quotas.check_cells(&req.id, 0, &self.snapshots)?;
+        self.quotas.take_steps(&client, (perturbations.len() as u64 + 1) * req.generations as u64)?;
+        
+        let thread_pool = self.thread_pool.clone();
+        let shutdown = self.shutdown.clone();
+        let forked = source.clone();
+        let generations = req.generations as u64;
+        let source_rule = source.turmite_rule.as_ref().map_or_else(|| source.rule.to_string(), ToString::to_string);
+        let rules: Vec<String> = perturbations.iter()
+            .map(|perturbation| perturbation.rule.as_ref().map_or_else(|| source_rule.clone(), ToString::to_string))
+            .collect();
+        let outcomes = tokio::task::spawn_blocking(move || {
+            let stop = || shutdown.is_triggered();
+            thread_pool.install(|| run_experiment(&forked, &perturbations, generations, &stop))
+        })
+        .await
+        .map_err(|_| Status::new(Code::Internal, "Experiment worker stopped unexpectedly"))?
+        .ok_or_else(|| Status::new(Code::Unavailable, "Server is shutting down"))?;
+        
+        let (control, variants) = outcomes.split_first().expect("the control always runs");
+        Ok(Response::new(ExperimentResponse {
+            id: req.id,
+            generation: source.generation as i64,
+            control: Some(experiment_result(-1, "control", &source_rule, control, control)),
+            results: variants.iter().zip(&req.variants).zip(&rules).enumerate()
+                .map(|(index, ((outcome, variant), rule))| experiment_result(index as i32, variant.name.trim(), rule, outcome, control))
+                .collect(),
+        }))
     }
 
     type StreamSimulationStream = Pin<Box<dyn Stream<Item = Result<SimulationUpdate, Status>> + Send>>;
 
     async fn stream_simulation(&self, request: Request<StreamRequest>) -> Result<Response<Self::StreamSimulationStream>, Status> {
+        let client = self.quotas.client_key(&request)?;
         let req = request.into_inner();
-        let simulations = self.simulations.clone();
+        self.rate_limiter.check_simulation(&req.id)?;
+        let snapshots = self.snapshots.clone();
+        let step_queue = self.step_queue.clone();
+        let quotas = self.quotas.clone();
+        let shutdown = self.shutdown.clone();
         
         // Verify simulation exists
-        {
-            let sim_guard = simulations.lock().await;
-            if sim_guard.get_simulation(&req.id).is_none() {
-                return Err(Status::new(Code::NotFound, "Simulation not found"));
-            }
+        if snapshots.get(&req.id).is_none() {
+            return Err(errors::simulation_not_found(&req.id));
         }
         
         let stream = async_stream::stream! {
@@ -285,82 +2361,120 @@ impl game_of_life_service_server::GameOfLifeService for GameOfLifeServiceImpl {
             );
             
             loop {
-                interval.tick().await;
-                
-                let mut sim_guard = simulations.lock().await;
-                let simulation = match sim_guard.get_simulation_mut(&req.id) {
-                    Some(sim) => sim,
-                    None => {
-                        yield Err(Status::new(Code::NotFound, "Simulation not found"));
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = shutdown.triggered() => {
+                        yield Err(Status::new(Code::Unavailable, "Server is shutting down"));
                         break;
                     }
-                };
+                }
                 
+                let mut paused = None;
+                let mut breakpoint = None;
+                let mut watchpoint = None;
                 if req.auto_step {
-                    // Step the simulation
-                    simulation.generation += 1;
-                    
-                    // Apply Game of Life rules (simplified for streaming)
-                    let mut neighbor_counts: std::collections::HashMap<(i32, i32), u8> = std::collections::HashMap::new();
-                    
-                    for ((x, y), cell) in &simulation.cells {
-                        if cell.alive {
-                            let neighbors = [
-                                (x - 1, y - 1), (*x, y - 1), (x + 1, y - 1),
-                                (x - 1, *y),                  (x + 1, *y),
-                                (x - 1, y + 1), (*x, y + 1), (x + 1, y + 1),
-                            ];
-                            
-                            for (nx, ny) in neighbors {
-                                if nx >= 0 && nx < simulation.width && ny >= 0 && ny < simulation.height {
-                                    *neighbor_counts.entry((nx, ny)).or_insert(0) += 1;
-                                }
-                            }
-                        }
+                    // Each automatic step counts against the watching client's quotas
+                    if let Err(exceeded) = quotas.check_cells(&req.id, 0, &snapshots).and_then(|_| quotas.take_steps(&client, 1)) {
+                        yield Err(exceeded.into());
+                        break;
                     }
-                    
-                    let mut new_cells = std::collections::HashMap::new();
-                    
-                    for ((x, y), neighbor_count) in neighbor_counts {
-                        let currently_alive = simulation.cells.get(&(x, y)).map(|c| c.alive).unwrap_or(false);
-                        
-                        let will_be_alive = if currently_alive {
-                            neighbor_count == 2 || neighbor_count == 3
-                        } else {
-                            neighbor_count == 3
-                        };
-                        
-                        if will_be_alive {
-                            new_cells.insert((x, y), CellState {
-                                alive: true,
-                                generation: simulation.generation,
-                                neighbor_count,
-                            });
-                        }
+                    // Queue behind any concurrent steppers; a missing simulation is reported below
+                    if let Ok(outcome) = step_queue.step_unattended(&req.id, 1).await {
+                        paused = outcome.auto_pause;
+                        breakpoint = outcome.breakpoint;
+                        watchpoint = outcome.watchpoint;
                     }
-                    
-                    simulation.cells = new_cells;
                 }
                 
-                let live_cells = simulation.get_live_cell_count();
-                let changed_cells: Vec<Cell> = simulation.get_live_cells().into_iter().map(|(x, y)| {
-                    let cell_state = simulation.cells.get(&(x, y)).unwrap();
-                    Cell {
-                        x,
-                        y,
-                        alive: cell_state.alive,
-                        neighbors: cell_state.neighbor_count as i32,
+                // Read from the published snapshot so observers never wait on the stepper
+                let simulation = match snapshots.get(&req.id) {
+                    Some(sim) => sim,
+                    None => {
+                        yield Err(errors::simulation_not_found(&req.id));
+                        break;
                     }
-                }).collect();
+                };
+                
+                let live_cells = simulation.get_live_cell_count();
+                let changed_cells: Vec<Cell> = live_cell_messages(&simulation);
+                // Turmites keep drawing on an empty grid
+                let reason = if live_cells == 0 && simulation.turmites.is_empty() {
+                    EndReason::Extinct
+                } else if let Some(pause) = paused {
+                    end_reason(pause.reason).0
+                } else if breakpoint.is_some() {
+                    EndReason::Breakpoint
+                } else if watchpoint.is_some() {
+                    EndReason::Watchpoint
+                } else if simulation.is_completed() {
+                    EndReason::MaxGenerations
+                } else {
+                    EndReason::None
+                };
+                let ended = reason != EndReason::None;
                 
                 yield Ok(SimulationUpdate {
                     generation: simulation.generation as i64,
                     live_cells,
                     changed_cells,
-                    simulation_ended: live_cells == 0,
+                    simulation_ended: ended,
+                    turmites: turmite_messages(&simulation),
+                    reason: reason as i32,
+                    version: simulation.version,
                 });
                 
-                if live_cells == 0 {
+                if ended {
+                    break;
+                }
+            }
+        };
+        
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    type WatchEventsStream = Pin<Box<dyn Stream<Item = Result<SimulationEvent, Status>> + Send>>;
+
+    /// Events for the requested simulations, or for every simulation, until
+    /// the client hangs up or the server shuts down. A watcher that falls
+    /// behind skips the events it missed. Watching chosen simulations ends
+    /// once the last of them is deleted.
+    async fn watch_events(&self, request: Request<WatchEventsRequest>) -> Result<Response<Self::WatchEventsStream>, Status> {
+        let req = request.into_inner();
+        // Subscribe first so nothing that happens after the checks is missed
+        let mut events = self.events.subscribe();
+        for id in &req.ids {
+            self.rate_limiter.check_simulation(id)?;
+            if self.snapshots.get(id).is_none() {
+                return Err(errors::simulation_not_found(id));
+            }
+        }
+        let watch_all = req.ids.is_empty();
+        let mut watched: HashSet<String> = req.ids.into_iter().collect();
+        let shutdown = self.shutdown.clone();
+        
+        let stream = async_stream::stream! {
+            loop {
+                let event = tokio::select! {
+                    event = events.recv() => event,
+                    _ = shutdown.triggered() => {
+                        yield Err(Status::new(Code::Unavailable, "Server is shutting down"));
+                        break;
+                    }
+                };
+                let event = match event {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                };
+                if !watch_all && !watched.contains(&event.id) {
+                    continue;
+                }
+                if !watch_all && event.r#type() == SimulationEventType::Deleted {
+                    watched.remove(&event.id);
+                }
+                yield Ok(event);
+                
+                if !watch_all && watched.is_empty() {
                     break;
                 }
             }
@@ -0,0 +1,59 @@
+use tokio::sync::watch;
+
+/// Cloneable server-wide shutdown flag.
+///
+/// Triggering it stops the listener from accepting new RPCs, ends open
+/// simulation streams, and cancels background jobs. Every clone observes the
+/// same flag.
+#[derive(Clone, Debug)]
+pub struct ShutdownSignal {
+    sender: watch::Sender<bool>,
+}
+
+impl Default for ShutdownSignal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShutdownSignal {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(false);
+        Self { sender }
+    }
+    
+    pub fn trigger(&self) {
+        self.sender.send_replace(true);
+    }
+    
+    pub fn is_triggered(&self) -> bool {
+        *self.sender.borrow()
+    }
+    
+    /// Resolve once shutdown has been triggered (immediately if it already was).
+    pub async fn triggered(&self) {
+        let mut receiver = self.sender.subscribe();
+        // The sender lives in `self`, so the channel cannot close while we wait
+        let _ = receiver.wait_for(|triggered| *triggered).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn test_trigger_wakes_waiters() {
+        let signal = ShutdownSignal::new();
+        let waiter = signal.clone();
+        let handle = tokio::spawn(async move { waiter.triggered().await });
+        
+        assert!(!signal.is_triggered());
+        signal.trigger();
+        handle.await.unwrap();
+        assert!(signal.is_triggered());
+        
+        // Late waiters return immediately
+        signal.triggered().await;
+    }
+}
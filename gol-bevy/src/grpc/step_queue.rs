@@ -0,0 +1,449 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use tokio::sync::{oneshot, Mutex};
+use tonic::{Code, Status};
+
+use crate::grpc::events::{cells_changed_event, error_event, simulation_event, stabilized_event};
+use crate::grpc::proto::{EndReason, SimulationEvent, SimulationEventType};
+use crate::grpc::{errors, webhook, EcsBackend, EventBus};
+use crate::resources::{AutoPause, BreakCondition, Divergence, GridRegion, Milestone, ReferenceGrid, ReplayLogs, ReplayOp, ScriptHooks, SimulationData, SimulationSnapshots, Simulations, StopReason};
+
+/// Result of one caller's share of a coalesced step batch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StepOutcome {
+    pub generation: u64,
+    pub live_cells: i64,
+    pub changed_cells: i64,
+    /// Generations actually applied, fewer than requested if the run stopped early.
+    pub steps: u32,
+    /// The simulation is at its generation limit, so later steps are skipped.
+    pub completed: bool,
+    /// Set for unattended steps once the grid has died out or started repeating.
+    pub auto_pause: Option<AutoPause>,
+    /// Set when a breakpoint fired, stopping the steps at its generation.
+    pub breakpoint: Option<BreakCondition>,
+    /// Set when cells under a pausing watchpoint changed, stopping the steps there.
+    pub watchpoint: Option<GridRegion>,
+    pub version: u64,
+}
+
+struct PendingStep {
+    steps: u32,
+    unattended: bool,
+    /// Refuse the steps unless the simulation is at this version when they run.
+    expected_version: Option<u64>,
+    reply: oneshot::Sender<Result<StepOutcome, Status>>,
+}
+
+#[derive(Default)]
+struct SimulationQueue {
+    pending: VecDeque<PendingStep>,
+    running: bool,
+}
+
+/// Per-simulation step queues.
+///
+/// Concurrent step requests for the same simulation are appended to that
+/// simulation's queue and drained by a single worker task, which applies them
+/// in arrival order and replies to each caller with the state after its own
+/// steps. Workers take at most `max_batch_steps` steps per lock acquisition and
+/// yield between batches, so one busy simulation cannot starve the others; a
+/// single larger request is stepped in slices of that size, releasing the
+/// lock between them.
+pub struct StepQueue {
+    simulations: Arc<Mutex<Simulations>>,
+    snapshots: Arc<SimulationSnapshots>,
+    replay_logs: Arc<ReplayLogs>,
+    thread_pool: Arc<rayon::ThreadPool>,
+    events: EventBus,
+    /// Hooks run after every generation stepped and on every auto-pause.
+    script: Option<Arc<ScriptHooks>>,
+    /// Step a dense reference grid beside every simulation and compare.
+    reference_check: bool,
+    /// First divergence from the reference stepper found per simulation;
+    /// those simulations are not compared again.
+    divergences: std::sync::Mutex<HashMap<String, Divergence>>,
+    queues: std::sync::Mutex<HashMap<String, SimulationQueue>>,
+    max_batch_steps: u32,
+    /// Steps the simulations it can in their ECS worlds, once the service
+    /// has one.
+    ecs: OnceLock<Arc<EcsBackend>>,
+}
+
+impl StepQueue {
+    pub fn new(
+        simulations: Arc<Mutex<Simulations>>,
+        snapshots: Arc<SimulationSnapshots>,
+        replay_logs: Arc<ReplayLogs>,
+        thread_pool: Arc<rayon::ThreadPool>,
+        events: EventBus,
+        script: Option<Arc<ScriptHooks>>,
+        max_batch_steps: u32,
+    ) -> Self {
+        Self {
+            simulations,
+            snapshots,
+            replay_logs,
+            thread_pool,
+            events,
+            script,
+            reference_check: false,
+            divergences: std::sync::Mutex::new(HashMap::new()),
+            queues: std::sync::Mutex::new(HashMap::new()),
+            max_batch_steps: max_batch_steps.max(1),
+            ecs: OnceLock::new(),
+        }
+    }
+    
+    /// Step the simulations `ecs` can step in their ECS worlds from now on,
+    /// as [`EcsBackend::can_step`] allows. Only the first backend given is used.
+    pub fn use_ecs_backend(&self, ecs: Arc<EcsBackend>) {
+        let _ = self.ecs.set(ecs);
+    }
+    
+    /// Check every generation stepped against [`ReferenceGrid`], reporting
+    /// the first cell each simulation gets wrong to event watchers. Slow; meant for debugging engines.
+    pub fn with_reference_check(mut self, enabled: bool) -> Self {
+        self.reference_check = enabled;
+        self
+    }
+    
+    /// Whether a script or the reference check has to see every generation
+    /// stepped.
+    pub fn checks_each_generation(&self) -> bool {
+        self.script.is_some() || self.reference_check
+    }
+    
+    /// Where a simulation first diverged from the reference stepper, if it has.
+    pub fn divergence(&self, id: &str) -> Option<Divergence> {
+        self.divergences.lock().unwrap().get(id).copied()
+    }
+    
+    /// Queue `steps` generations for a simulation and wait for them to be
+    /// applied. With `expected_version` they fail, untaken, if the simulation
+    /// has changed by the time they run.
+    pub async fn step(self: &Arc<Self>, id: &str, steps: u32, expected_version: Option<u64>) -> Result<StepOutcome, Status> {
+        self.enqueue(id, steps, false, expected_version).await
+    }
+    
+    /// Queue steps on behalf of a server-side runner nobody is watching. They
+    /// stop early, and the simulation is paused, once the grid dies out or
+    /// repeats itself; a paused simulation is not stepped until it changes.
+    pub async fn step_unattended(self: &Arc<Self>, id: &str, steps: u32) -> Result<StepOutcome, Status> {
+        self.enqueue(id, steps, true, None).await
+    }
+    
+    async fn enqueue(self: &Arc<Self>, id: &str, steps: u32, unattended: bool, expected_version: Option<u64>) -> Result<StepOutcome, Status> {
+        let (reply, outcome) = oneshot::channel();
+        
+        let start_worker = {
+            let mut queues = self.queues.lock().unwrap();
+            let queue = queues.entry(id.to_string()).or_default();
+            queue.pending.push_back(PendingStep { steps, unattended, expected_version, reply });
+            !std::mem::replace(&mut queue.running, true)
+        };
+        
+        if start_worker {
+            // Detached so a caller hanging up cannot strand the rest of the queue
+            let worker = self.clone();
+            let id = id.to_string();
+            tokio::spawn(async move { worker.drain(id).await });
+        }
+        
+        match outcome.await {
+            Ok(result) => result,
+            Err(_) => Err(Status::new(Code::Internal, "Step worker stopped unexpectedly")),
+        }
+    }
+    
+    /// Number of step requests waiting for a simulation, excluding the batch in progress.
+    pub fn pending(&self, id: &str) -> usize {
+        self.queues.lock().unwrap().get(id).map_or(0, |queue| queue.pending.len())
+    }
+    
+    async fn drain(&self, id: String) {
+        while let Some(batch) = self.next_batch(&id) {
+            let mut results = Vec::with_capacity(batch.len());
+            for request in &batch {
+                results.push(self.apply(&id, request).await);
+            }
+            
+            for (request, result) in batch.into_iter().zip(results) {
+                let _ = request.reply.send(result);
+            }
+            
+            // Let other simulations' workers take the lock before the next batch
+            tokio::task::yield_now().await;
+        }
+    }
+    
+    /// Apply one request's steps. Turbo simulations step in bursts of up to
+    /// `max_batch_steps` under the lock, yielding it between bursts, or
+    /// without it in their ECS world when there is one; paced
+    /// simulations release the lock while waiting for
+    /// their governor between generations. Steps past the simulation's
+    /// generation limit are skipped, and those after a breakpoint fires or
+    /// a pausing watchpoint sees its cells change. Webhooks hear of the milestones reached
+    /// and watchers of the steps taken and of the run stopping.
+    async fn apply(&self, id: &str, request: &PendingStep) -> Result<StepOutcome, Status> {
+        let (steps, unattended) = (request.steps, request.unattended);
+        let not_found = || errors::simulation_not_found(id);
+        
+        let mut simulations = self.simulations.lock().await;
+        let simulation = simulations.get_simulation(id).ok_or_else(not_found)?;
+        simulation.check_version(request.expected_version)?;
+        let initial_cells = simulation.get_live_cell_count();
+        let initial_generation = simulation.generation;
+        let was_completed = simulation.is_completed();
+        
+        let allowed = if unattended && simulation.is_auto_paused() { 0 } else { simulation.steps_allowed(steps) };
+        let mut remaining = allowed;
+        let mut stabilized = false;
+        let mut breakpoint = None;
+        let mut watchpoint = None;
+        while remaining > 0 {
+            let simulation = simulations.get_simulation_mut(id).ok_or_else(not_found)?;
+            
+            let delay = simulation.pacing.delay_until_next_step(Instant::now());
+            if !delay.is_zero() {
+                drop(simulations);
+                tokio::time::sleep(delay).await;
+                simulations = self.simulations.lock().await;
+                continue;
+            }
+            
+            let turbo = simulation.pacing.is_turbo();
+            let burst = if turbo { remaining.min(self.max_batch_steps) } else { 1 };
+            if !unattended && !self.checks_each_generation() && let Some(ecs) = self.ecs.get() && EcsBackend::can_step(simulation) {
+                let (world, version) = (ecs.world(simulation), simulation.version);
+                drop(simulations);
+                let cells = ecs.step(world, burst).await?;
+                simulations = self.simulations.lock().await;
+                let simulation = simulations.get_simulation_mut(id).ok_or_else(not_found)?;
+                if simulation.version != version {
+                    // Changed while its world stepped, so step it again from where it is now
+                    continue;
+                }
+                simulation.generation += burst as u64;
+                simulation.set_cells(&cells);
+                self.snapshots.publish(simulation);
+                ecs.mark_synced(simulation);
+                self.replay_logs.record(id, ReplayOp::Step { steps: burst as u64 });
+                self.replay_logs.record_keyframe(simulation);
+                remaining -= burst;
+                continue;
+            }
+            let script = self.script.as_deref();
+            let (taken, paused, hit, watched, ops) = self.thread_pool.install(|| {
+                // Script edits are logged between the steps around them, so a
+                // replay, which runs no scripts, still matches
+                let mut ops = Vec::new();
+                let mut unlogged = 0;
+                let mut taken = 0;
+                let mut paused = false;
+                let mut hit = None;
+                let mut watched = None;
+                let mut reference = self.reference_for(simulation);
+                while taken < burst {
+                    let population = simulation.cells.population();
+                    let pause = if unattended {
+                        simulation.step_watched()
+                    } else {
+                        simulation.step();
+                        None
+                    };
+                    if !simulation.breakpoints.is_empty() {
+                        hit = simulation.check_breakpoints(population)
+                            .map(|index| simulation.breakpoints[index].condition.clone());
+                    }
+                    taken += 1;
+                    unlogged += 1;
+                    if let Some(grid) = &mut reference {
+                        grid.step();
+                        if let Some(divergence) = grid.first_divergence(&simulation.cells) {
+                            self.report_divergence(simulation, divergence);
+                            reference = None;
+                        } else if *grid.rule() != simulation.rule {
+                            // A scheduled rule change took over at this generation
+                            reference = self.reference_for(simulation);
+                        }
+                    }
+                    let edits = script.map_or_else(Vec::new, |script| run_script(script, simulation, pause, &self.events));
+                    if !edits.is_empty() {
+                        if reference.is_some() {
+                            reference = self.reference_for(simulation);
+                        }
+                        ops.push(ReplayOp::Step { steps: std::mem::take(&mut unlogged) });
+                        ops.push(ReplayOp::Update {
+                            rule: None,
+                            boundary: None,
+                            generation: None,
+                            clear: false,
+                            cells: edits,
+                            colors: Vec::new(),
+                            states: Vec::new(),
+                        });
+                    }
+                    if !simulation.watchpoints.is_empty() {
+                        for (index, changed) in simulation.check_watchpoints() {
+                            self.events.publish(cells_changed_event(simulation, index, &changed));
+                            let watchpoint = &simulation.watchpoints[index];
+                            if watchpoint.pause && watched.is_none() {
+                                watched = Some(watchpoint.region);
+                            }
+                        }
+                    }
+                    paused = pause.is_some();
+                    if paused || hit.is_some() || watched.is_some() {
+                        break;
+                    }
+                }
+                if unlogged > 0 {
+                    ops.push(ReplayOp::Step { steps: unlogged });
+                }
+                // Readers see the burst's last generation, not each one on the way
+                self.snapshots.publish(simulation);
+                (taken, paused, hit, watched, ops)
+            });
+            for op in ops {
+                self.replay_logs.record(id, op);
+            }
+            self.replay_logs.record_keyframe(simulation);
+            remaining -= taken;
+            stabilized = paused;
+            breakpoint = hit;
+            watchpoint = watched;
+            if stabilized || breakpoint.is_some() || watchpoint.is_some() {
+                break;
+            }
+            if turbo && remaining > 0 {
+                // Let other simulations and edits take the lock between bursts
+                drop(simulations);
+                tokio::task::yield_now().await;
+                simulations = self.simulations.lock().await;
+            }
+        }
+        
+        let simulation = simulations.get_simulation(id).ok_or_else(not_found)?;
+        let live_cells = simulation.get_live_cell_count();
+        let completed = simulation.is_completed();
+        if completed && !was_completed && let Some(url) = &simulation.completion_webhook {
+            let summary = serde_json::json!({
+                "id": simulation.id,
+                "generation": simulation.generation,
+                "live_cells": live_cells,
+                "reason": "max_generations",
+            });
+            webhook::spawn_delivery(url.clone(), summary.to_string());
+        }
+        let taken = allowed - remaining;
+        let mut milestones = Vec::new();
+        if completed && !was_completed {
+            milestones.push(Milestone::Completed);
+        }
+        if initial_cells > 0 && live_cells == 0 && simulation.turmites.is_empty() {
+            milestones.push(Milestone::Extinct);
+        }
+        if stabilized && let Some(AutoPause { reason: StopReason::Cycle { period }, .. }) = simulation.auto_pause {
+            milestones.push(Milestone::Stabilized { period });
+        }
+        webhook::notify_milestones(simulation, &milestones, initial_generation);
+        if taken > 0 {
+            self.events.publish(SimulationEvent {
+                steps: taken as i64,
+                ..simulation_event(SimulationEventType::Stepped, simulation)
+            });
+        }
+        if completed && !was_completed {
+            self.events.publish(SimulationEvent {
+                reason: EndReason::MaxGenerations as i32,
+                ..simulation_event(SimulationEventType::Paused, simulation)
+            });
+        }
+        if stabilized && let Some(pause) = simulation.auto_pause {
+            self.events.publish(stabilized_event(simulation, pause.reason));
+        }
+        if breakpoint.is_some() {
+            self.events.publish(SimulationEvent {
+                reason: EndReason::Breakpoint as i32,
+                ..simulation_event(SimulationEventType::Paused, simulation)
+            });
+        } else if watchpoint.is_some() {
+            self.events.publish(SimulationEvent {
+                reason: EndReason::Watchpoint as i32,
+                ..simulation_event(SimulationEventType::Paused, simulation)
+            });
+        }
+        Ok(StepOutcome {
+            generation: simulation.generation,
+            live_cells,
+            changed_cells: (initial_cells - live_cells).abs(),
+            steps: taken,
+            completed,
+            auto_pause: if unattended && simulation.is_auto_paused() { simulation.auto_pause } else { None },
+            breakpoint,
+            watchpoint,
+            version: simulation.version,
+        })
+    }
+    
+    /// A reference grid to step beside `simulation`, when checking is on and
+    /// the simulation has not diverged already and is one the reference
+    /// stepper covers.
+    fn reference_for(&self, simulation: &SimulationData) -> Option<ReferenceGrid> {
+        if !self.reference_check || self.divergences.lock().unwrap().contains_key(&simulation.id) {
+            return None;
+        }
+        ReferenceGrid::from_simulation(simulation).ok()
+    }
+    
+    fn report_divergence(&self, simulation: &SimulationData, divergence: Divergence) {
+        let message = format!(
+            "Reference check: diverged at generation {}: cell ({}, {}) is in state {} but the reference stepper has {}; {} cells differ",
+            divergence.generation, divergence.x, divergence.y, divergence.actual, divergence.expected, divergence.differing,
+        );
+        self.events.publish(error_event(simulation, message));
+        self.divergences.lock().unwrap().insert(simulation.id.clone(), divergence);
+    }
+    
+    /// Pop the next batch of requests, or retire the worker when the queue is empty.
+    fn next_batch(&self, id: &str) -> Option<Vec<PendingStep>> {
+        let mut queues = self.queues.lock().unwrap();
+        let queue = queues.get_mut(id)?;
+        
+        let mut batch = Vec::new();
+        let mut total_steps: u32 = 0;
+        while let Some(next) = queue.pending.front() {
+            if !batch.is_empty() && total_steps.saturating_add(next.steps) > self.max_batch_steps {
+                break;
+            }
+            total_steps = total_steps.saturating_add(next.steps);
+            batch.push(queue.pending.pop_front().unwrap());
+        }
+        
+        if batch.is_empty() {
+            queues.remove(id);
+            return None;
+        }
+        Some(batch)
+    }
+}
+
+/// Run the script's hooks on a simulation that just stepped, and paused if
+/// `pause` is set, returning the cells they changed. Failures are reported
+/// to event watchers and keep the changes made before them.
+fn run_script(script: &ScriptHooks, simulation: &mut SimulationData, pause: Option<AutoPause>, events: &EventBus) -> Vec<(i32, i32, bool)> {
+    let mut runs = vec![script.on_generation(simulation)];
+    if let Some(pause) = pause {
+        runs.push(script.on_stabilize(simulation, pause.reason));
+    }
+    let mut edits = Vec::new();
+    for run in runs {
+        if let Some(error) = run.error {
+            events.publish(error_event(simulation, format!("Script error: {}", error)));
+        }
+        edits.extend(run.edits);
+    }
+    edits
+}
@@ -0,0 +1,133 @@
+//! Webhooks: a JSON summary POSTed to a client-supplied URL when a simulation
+//! reaches its generation limit, dies out, stabilizes or passes a number of
+//! generations.
+//!
+//! Only plain `http://` URLs are supported. Deliveries are fire-and-forget: a
+//! failed or slow endpoint is logged and never holds up stepping.
+
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::header::{CONTENT_TYPE, HOST};
+use hyper::{Request, Uri};
+use hyper_util::rt::TokioIo;
+use std::time::Duration;
+use tokio::net::TcpStream;
+
+use crate::resources::{Milestone, SimulationData};
+
+/// How long a delivery may take, connection included.
+pub const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Check that `url` is an `http://` URL with a host.
+pub fn validate_url(url: &str) -> Result<Uri, String> {
+    let uri: Uri = url.parse().map_err(|error| format!("Invalid webhook URL '{}': {}", url, error))?;
+    if uri.scheme_str() != Some("http") {
+        return Err(format!("Webhook URL '{}' must start with http://", url));
+    }
+    if uri.host().is_none() {
+        return Err(format!("Webhook URL '{}' has no host", url));
+    }
+    Ok(uri)
+}
+
+/// POST `body` as JSON to `url`, returning the response status code.
+pub async fn post_json(url: &str, body: String) -> Result<u16, String> {
+    let uri = validate_url(url)?;
+    let delivery = async {
+        let host = uri.host().unwrap_or_default();
+        let stream = TcpStream::connect((host, uri.port_u16().unwrap_or(80))).await
+            .map_err(|error| format!("connecting to {}: {}", host, error))?;
+        let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream)).await
+            .map_err(|error| error.to_string())?;
+        tokio::spawn(async move {
+            let _ = connection.await;
+        });
+
+        let authority = uri.authority().map(|authority| authority.as_str()).unwrap_or(host);
+        let request = Request::post(uri.path_and_query().map_or("/", |path| path.as_str()))
+            .header(HOST, authority)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Full::new(Bytes::from(body)))
+            .map_err(|error| error.to_string())?;
+        let response = sender.send_request(request).await.map_err(|error| error.to_string())?;
+        Ok(response.status().as_u16())
+    };
+    tokio::time::timeout(WEBHOOK_TIMEOUT, delivery).await
+        .unwrap_or_else(|_| Err(format!("no response within {:?}", WEBHOOK_TIMEOUT)))
+}
+
+/// Deliver `body` to `url` in the background, logging failures.
+pub fn spawn_delivery(url: String, body: String) {
+    tokio::spawn(async move {
+        match post_json(&url, body).await {
+            Ok(status) if (200..300).contains(&status) => {}
+            Ok(status) => eprintln!("Webhook {} answered {}", url, status),
+            Err(error) => eprintln!("Webhook {} failed: {}", url, error),
+        }
+    });
+}
+
+/// Tell `simulation`'s webhooks of the milestones reached by stepping it
+/// from generation `from_generation`, each webhook hearing of those it asks for.
+pub fn notify_milestones(simulation: &SimulationData, milestones: &[Milestone], from_generation: u64) {
+    for webhook in &simulation.webhooks {
+        let interval = webhook.passes_interval(from_generation, simulation.generation).then_some(Milestone::Generation);
+        for &milestone in milestones.iter().chain(&interval) {
+            if webhook.wants(milestone) {
+                spawn_delivery(webhook.url.clone(), milestone_summary(simulation, milestone));
+            }
+        }
+    }
+}
+
+fn milestone_summary(simulation: &SimulationData, milestone: Milestone) -> String {
+    let mut summary = serde_json::json!({
+        "id": simulation.id,
+        "name": simulation.name,
+        "generation": simulation.generation,
+        "live_cells": simulation.get_live_cell_count(),
+        "milestone": milestone.name(),
+    });
+    if let Milestone::Stabilized { period } = milestone {
+        summary["period"] = period.into();
+    }
+    summary.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_validates_urls() {
+        assert!(validate_url("http://localhost:8080/done").is_ok());
+        assert!(validate_url("https://example.com/done").is_err());
+        assert!(validate_url("/done").is_err());
+        assert!(validate_url("not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_posts_json_body() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook?run=1", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            let mut buffer = [0; 1024];
+            while !String::from_utf8_lossy(&received).ends_with("{\"done\":true}") {
+                let read = socket.read(&mut buffer).await.unwrap();
+                received.extend_from_slice(&buffer[..read]);
+            }
+            socket.write_all(b"HTTP/1.1 204 No Content\r\ncontent-length: 0\r\n\r\n").await.unwrap();
+            String::from_utf8(received).unwrap()
+        });
+
+        let status = post_json(&url, "{\"done\":true}".to_string()).await.unwrap();
+        assert_eq!(status, 204);
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /hook?run=1 HTTP/1.1\r\n"));
+        assert!(request.to_lowercase().contains("content-type: application/json"));
+    }
+}
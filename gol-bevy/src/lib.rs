@@ -3,14 +3,26 @@
 //! This library provides a Game of Life implementation using Bevy ECS.
 //! It includes components, systems, and resources for simulating Conway's Game of Life.
 
+#[cfg(feature = "server")]
 pub mod components;
+#[cfg(feature = "server")]
 pub mod systems;
 pub mod resources;
+#[cfg(feature = "server")]
 pub mod api;
+#[cfg(feature = "server")]
 pub mod grpc;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
+#[cfg(feature = "server")]
 pub use components::*;
+#[cfg(feature = "server")]
 pub use systems::*;
 pub use resources::*;
+#[cfg(feature = "server")]
 pub use api::*;
-pub use grpc::*;
\ No newline at end of file
+#[cfg(feature = "server")]
+pub use grpc::GameOfLifeServiceImpl;
\ No newline at end of file
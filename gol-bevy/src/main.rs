@@ -1,44 +1,38 @@
 use bevy::prelude::*;
-use tokio::runtime::Runtime;
 
-mod components;
-mod systems;
-mod resources;
-mod api;
-mod grpc;
+use gol_bevy::components::CellState;
+use gol_bevy::systems::*;
+use gol_bevy::resources::{GridConfig, ServerConfig, SimulationState, Simulations, SpatialGrid};
+use gol_bevy::grpc::GrpcServerPlugin;
 
-use components::*;
-use systems::*;
-use resources::{GridConfig, SimulationState, Simulations};
-use api::*;
-use grpc::GameOfLifeServiceImpl;
-
-fn main() {
-    // Create async runtime for gRPC server
-    let rt = Runtime::new().unwrap();
-    
-    // Start gRPC server in background
-    rt.spawn(async {
-        start_grpc_server().await.unwrap();
-    });
+fn main() -> AppExit {
+    let server_config = match ServerConfig::from_env().with_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}", error);
+            eprintln!("usage: gol-bevy [--listen <addr>] [--data-dir <dir>] [--registry-dir <dir>] [--script <file>] [--reference-check] [--default-simulation] [--webhook <url>] [--webhook-every <generations>] [--batch-concurrency <runs>] [--quota-simulations <n>] [--quota-live-cells <n>] [--quota-steps-per-minute <n>]");
+            return AppExit::error();
+        }
+    };
     
-    // Start Bevy app
     App::new()
         .add_plugins(MinimalPlugins)
+        .add_plugins(GrpcServerPlugin::new(server_config.clone()))
+        .insert_resource(server_config)
         .init_resource::<GridConfig>()
         .init_resource::<SimulationState>()
         .init_resource::<Simulations>()
+        .init_resource::<SpatialGrid>()
         .add_systems(Startup, setup_game)
         .add_systems(Update, (
             neighbor_counting_system,
             cell_lifecycle_system,
             cleanup_system,
-        ).chain())
-        .run();
+        ).chain().run_if(simulation_running.and_then(any_with_component::<CellState>)))
+        .run()
 }
 
 fn setup_game(
-    mut commands: Commands,
     mut grid_config: ResMut<GridConfig>,
     mut simulation_state: ResMut<SimulationState>,
 ) {
@@ -48,20 +42,3 @@ fn setup_game(
     
     info!("Game of Life Bevy server initialized");
 }
-
-async fn start_grpc_server() -> Result<(), Box<dyn std::error::Error>> {
-    use tonic::transport::Server;
-    use grpc::proto::game_of_life_service_server::GameOfLifeServiceServer;
-    
-    let addr = "[::1]:50051".parse()?;
-    let service = GameOfLifeServiceImpl::new();
-    
-    println!("Starting gRPC Game of Life server on {}", addr);
-    
-    Server::builder()
-        .add_service(GameOfLifeServiceServer::new(service))
-        .serve(addr)
-        .await?;
-    
-    Ok(())
-}
@@ -0,0 +1,65 @@
+//! Text labels pinned to cells of a simulation.
+//!
+//! Annotations flag structures worth coming back to in a big run. They are
+//! part of the simulation, saved and cloned with it, but take no part in
+//! stepping, so replays and history leave them out.
+
+use serde::{Deserialize, Serialize};
+
+use crate::resources::GridRegion;
+
+/// Most annotations one simulation may have.
+pub const MAX_ANNOTATIONS: usize = 256;
+
+/// Longest annotation text, in characters.
+pub const MAX_ANNOTATION_LENGTH: usize = 80;
+
+/// A label at cell `(x, y)`, which may lie outside the grid.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub x: i32,
+    pub y: i32,
+    pub text: String,
+}
+
+impl Annotation {
+    /// An annotation with `text` trimmed, or why the text cannot be one:
+    /// empty, too long or holding control characters.
+    pub fn new(x: i32, y: i32, text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err("Text is required".to_string());
+        }
+        if text.chars().count() > MAX_ANNOTATION_LENGTH {
+            return Err(format!("Text must be at most {} characters", MAX_ANNOTATION_LENGTH));
+        }
+        if text.chars().any(char::is_control) {
+            return Err("Text must not contain control characters".to_string());
+        }
+        Ok(Self { x, y, text: text.to_string() })
+    }
+
+    pub fn is_in(&self, region: &GridRegion) -> bool {
+        region.contains(self.x, self.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotation_text_is_checked() {
+        assert_eq!(Annotation::new(1, 2, "  glider gun ").unwrap().text, "glider gun");
+        assert!(Annotation::new(0, 0, "   ").is_err());
+        assert!(Annotation::new(0, 0, &"x".repeat(MAX_ANNOTATION_LENGTH + 1)).is_err());
+        assert!(Annotation::new(0, 0, "two\nlines").is_err());
+    }
+
+    #[test]
+    fn test_annotation_region_excludes_far_edges() {
+        let region = GridRegion { x: 10, y: 10, width: 5, height: 5 };
+        assert!(Annotation::new(10, 14, "in").unwrap().is_in(&region));
+        assert!(!Annotation::new(15, 10, "out").unwrap().is_in(&region));
+    }
+}
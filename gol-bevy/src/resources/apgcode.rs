@@ -0,0 +1,369 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::resources::{ChunkedGrid, Rule};
+
+/// Error raised while decoding an apgcode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApgcodeError(pub String);
+
+impl fmt::Display for ApgcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ApgcodeError {}
+
+/// Column values of a five-row strip, bit 0 being the top row.
+const WECHSLER_DIGITS: &[u8; 32] = b"0123456789abcdefghijklmnopqrstuv";
+
+/// Digits counting the zero columns after a `y`, for runs of 4 to 39.
+const ZERO_RUN_DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// Encode cells in extended Wechsler format, relative to their bounding box.
+///
+/// The pattern is cut into strips five rows tall, separated by `z`. Each
+/// column of a strip becomes one digit `0`-`v` whose bits are its live
+/// cells, top row first. Runs of empty columns shrink to `w` (2), `x` (3)
+/// or `y` plus a digit (4 to 39), and empty columns ending a strip are dropped.
+pub fn wechsler(cells: &[(i32, i32)]) -> String {
+    let cells = normalize(cells);
+    let live: HashSet<(i32, i32)> = cells.iter().copied().collect();
+    let width = cells.iter().map(|(x, _)| x + 1).max().unwrap_or(0);
+    let height = cells.iter().map(|(_, y)| y + 1).max().unwrap_or(0);
+
+    let mut code = String::new();
+    for strip in 0..(height + 4) / 5 {
+        if strip > 0 {
+            code.push('z');
+        }
+        let mut zeros = 0;
+        for x in 0..width {
+            let value = (0..5)
+                .filter(|row| live.contains(&(x, strip * 5 + row)))
+                .fold(0, |value, row| value | 1 << row);
+            if value == 0 {
+                zeros += 1;
+                continue;
+            }
+            push_zeros(&mut code, zeros);
+            zeros = 0;
+            code.push(WECHSLER_DIGITS[value] as char);
+        }
+    }
+    code
+}
+
+fn push_zeros(code: &mut String, mut zeros: usize) {
+    while zeros > 0 {
+        let run = match zeros {
+            1 => {
+                code.push('0');
+                1
+            }
+            2 => {
+                code.push('w');
+                2
+            }
+            3 => {
+                code.push('x');
+                3
+            }
+            _ => {
+                let run = zeros.min(39);
+                code.push('y');
+                code.push(ZERO_RUN_DIGITS[run - 4] as char);
+                run
+            }
+        };
+        zeros -= run;
+    }
+}
+
+/// The apgsearch ordering: shorter codes first, then lexicographic.
+fn canonical(codes: impl IntoIterator<Item = String>) -> Option<String> {
+    codes.into_iter().min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+}
+
+/// Canonical Wechsler code over every rotation and reflection of each phase.
+fn canonical_wechsler(phases: &[Vec<(i32, i32)>]) -> String {
+    let codes = phases.iter()
+        .flat_map(|phase| orientations(phase))
+        .map(|cells| wechsler(&cells));
+    canonical(codes).unwrap_or_default()
+}
+
+/// The eight rotations and reflections of a pattern.
+fn orientations(cells: &[(i32, i32)]) -> Vec<Vec<(i32, i32)>> {
+    // Bit 0 mirrors x, bit 1 mirrors y, bit 2 swaps the axes
+    (0..8).map(|orientation| {
+        cells.iter().map(|&(x, y)| {
+            let (x, y) = if orientation & 4 != 0 { (y, x) } else { (x, y) };
+            (if orientation & 1 != 0 { -x } else { x }, if orientation & 2 != 0 { -y } else { y })
+        }).collect()
+    }).collect()
+}
+
+/// Shift cells so their bounding box starts at the origin, sorted.
+fn normalize(cells: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    let min_x = cells.iter().map(|(x, _)| *x).min().unwrap_or(0);
+    let min_y = cells.iter().map(|(_, y)| *y).min().unwrap_or(0);
+    let mut cells: Vec<_> = cells.iter().map(|(x, y)| (x - min_x, y - min_y)).collect();
+    cells.sort_unstable();
+    cells.dedup();
+    cells
+}
+
+/// Canonical apgcode of a still life: `xs<population>_<wechsler>`.
+pub fn still_life_apgcode(cells: &[(i32, i32)]) -> String {
+    format!("xs{}_{}", normalize(cells).len(), canonical_wechsler(&[cells.to_vec()]))
+}
+
+/// Canonical apgcode of an oscillator given each of its phases: `xp<period>_<wechsler>`.
+pub fn oscillator_apgcode(phases: &[Vec<(i32, i32)>]) -> String {
+    format!("xp{}_{}", phases.len(), canonical_wechsler(phases))
+}
+
+/// Canonical apgcode of a spaceship given each of its phases: `xq<period>_<wechsler>`.
+pub fn spaceship_apgcode(phases: &[Vec<(i32, i32)>]) -> String {
+    format!("xq{}_{}", phases.len(), canonical_wechsler(phases))
+}
+
+/// How a pattern repeats: its period and how far it moves each period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Periodicity {
+    pub period: u32,
+    /// Cells moved per period; positive `dy` is south.
+    pub dx: i32,
+    pub dy: i32,
+}
+
+impl Periodicity {
+    pub fn is_moving(&self) -> bool {
+        self.dx != 0 || self.dy != 0
+    }
+
+    /// Speed in the usual notation, e.g. `c/4 diagonal`, `c/2 orthogonal` or
+    /// `(2,1)c/6 oblique`. Empty for still lifes and oscillators.
+    pub fn speed(&self) -> String {
+        let (major, minor) = (self.dx.abs().max(self.dy.abs()) as u32, self.dx.abs().min(self.dy.abs()) as u32);
+        if major == 0 {
+            return String::new();
+        }
+        if minor != 0 && minor != major {
+            return format!("({},{})c/{} oblique", major, minor, self.period);
+        }
+        let divisor = gcd(major, self.period);
+        let (cells, period) = (major / divisor, self.period / divisor);
+        let mut speed = if cells == 1 { "c".to_string() } else { format!("{}c", cells) };
+        if period != 1 {
+            speed.push_str(&format!("/{}", period));
+        }
+        speed.push_str(if minor == 0 { " orthogonal" } else { " diagonal" });
+        speed
+    }
+
+    /// Compass heading, e.g. `north` or `south-east`. Empty unless moving.
+    pub fn direction(&self) -> String {
+        let vertical = match self.dy.signum() {
+            -1 => "north",
+            1 => "south",
+            _ => "",
+        };
+        let horizontal = match self.dx.signum() {
+            -1 => "west",
+            1 => "east",
+            _ => "",
+        };
+        match (vertical, horizontal) {
+            ("", heading) | (heading, "") => heading.to_string(),
+            (vertical, horizontal) => format!("{}-{}", vertical, horizontal),
+        }
+    }
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// Every phase of a pattern, in order, starting from the one it was given in.
+type Phases = Vec<Vec<(i32, i32)>>;
+
+/// Run a pattern under `rule` on an unbounded grid until it repeats, up to
+/// `max_period` generations, returning how it repeats and each phase seen on the way.
+fn evolve(cells: &[(i32, i32)], rule: &Rule, max_period: u32) -> Option<(Periodicity, Phases)> {
+    if cells.is_empty() {
+        return None;
+    }
+    let start = normalize(cells);
+    let (start_x, start_y) = bounds_origin(cells);
+    let mut grid = ChunkedGrid::new();
+    for &(x, y) in cells {
+        grid.set(x, y, true);
+    }
+
+    let mut phases = vec![cells.to_vec()];
+    for period in 1..=max_period {
+        grid = grid.step_rule(rule, false);
+        let live = grid.live_cells();
+        if normalize(&live) == start {
+            let (x, y) = bounds_origin(&live);
+            return Some((Periodicity { period, dx: x - start_x, dy: y - start_y }, phases));
+        }
+        if live.is_empty() {
+            return None;
+        }
+        phases.push(live);
+    }
+    None
+}
+
+/// Period and displacement of a pattern run in isolation under `rule`, or
+/// `None` if it dies or does not repeat within `max_period` generations.
+pub fn detect_periodicity(cells: &[(i32, i32)], rule: &Rule, max_period: u32) -> Option<Periodicity> {
+    evolve(cells, rule, max_period).map(|(periodicity, _)| periodicity)
+}
+
+/// Run an isolated object on an unbounded grid for up to `max_period`
+/// generations and return its canonical apgcode once it repeats: `xs` for
+/// still lifes, `xp` for oscillators and `xq` for spaceships. Returns `None`
+/// for an empty pattern or one that does not repeat in time.
+pub fn classify(cells: &[(i32, i32)], max_period: u32) -> Option<String> {
+    let (periodicity, phases) = evolve(cells, &Rule::conway(), max_period)?;
+    Some(match (periodicity.period, periodicity.is_moving()) {
+        (1, false) => still_life_apgcode(cells),
+        (_, false) => oscillator_apgcode(&phases),
+        (_, true) => spaceship_apgcode(&phases),
+    })
+}
+
+fn bounds_origin(cells: &[(i32, i32)]) -> (i32, i32) {
+    (
+        cells.iter().map(|(x, _)| *x).min().unwrap_or(0),
+        cells.iter().map(|(_, y)| *y).min().unwrap_or(0),
+    )
+}
+
+/// Decode an `xs`, `xp` or `xq` apgcode into cells relative to the top-left
+/// corner of its bounding box. For oscillators and spaceships this is the
+/// phase the code was built from.
+pub fn decode_apgcode(code: &str) -> Result<Vec<(i32, i32)>, ApgcodeError> {
+    let code = code.trim();
+    let (prefix, body) = code.split_once('_')
+        .ok_or_else(|| ApgcodeError(format!("'{}' is not an apgcode", code)))?;
+    let valid_prefix = ["xs", "xp", "xq"].iter().any(|kind| {
+        prefix.strip_prefix(kind).is_some_and(|number| !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()))
+    });
+    if !valid_prefix {
+        return Err(ApgcodeError(format!("Unsupported apgcode prefix '{}'", prefix)));
+    }
+
+    let mut cells = Vec::new();
+    let (mut x, mut strip) = (0i32, 0i32);
+    let mut chars = body.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            'w' => x += 2,
+            'x' => x += 3,
+            'y' => {
+                let run = chars.next()
+                    .and_then(|digit| ZERO_RUN_DIGITS.iter().position(|d| *d as char == digit))
+                    .ok_or_else(|| ApgcodeError("'y' must be followed by a digit or letter".to_string()))?;
+                x += 4 + run as i32;
+            }
+            'z' => {
+                strip += 1;
+                x = 0;
+            }
+            ch => {
+                let value = WECHSLER_DIGITS.iter().position(|d| *d as char == ch)
+                    .ok_or_else(|| ApgcodeError(format!("Unexpected character '{}' in apgcode", ch)))?;
+                cells.extend((0..5).filter(|row| value & (1 << row) != 0).map(|row| (x, strip * 5 + row)));
+                x += 1;
+            }
+        }
+    }
+
+    if cells.is_empty() {
+        return Err(ApgcodeError(format!("Apgcode '{}' has no live cells", code)));
+    }
+    Ok(normalize(&cells))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::decode_rle;
+
+    fn classify_rle(rle: &str) -> Option<String> {
+        classify(&decode_rle(rle).unwrap(), 30)
+    }
+
+    #[test]
+    fn test_classifies_common_objects() {
+        assert_eq!(classify_rle("2o$2o!").as_deref(), Some("xs4_33"));
+        assert_eq!(classify_rle("b2o$o2bo$b2o!").as_deref(), Some("xs6_696"));
+        assert_eq!(classify_rle("2o$obo$bo!").as_deref(), Some("xs5_253"));
+        assert_eq!(classify_rle("b2o$o2bo$bobo$2bo!").as_deref(), Some("xs7_2596"));
+        assert_eq!(classify_rle("3o!").as_deref(), Some("xp2_7"));
+        assert_eq!(classify_rle("2o$2o$2b2o$2b2o!").as_deref(), Some("xp2_318c"));
+        assert_eq!(classify_rle("bo$2bo$3o!").as_deref(), Some("xq4_153"));
+        assert_eq!(classify_rle("bo2bo$o$o3bo$4o!").as_deref(), Some("xq4_6frc"));
+    }
+
+    #[test]
+    fn test_unstable_patterns_are_not_classified() {
+        // An R-pentomino runs for over a thousand generations
+        assert_eq!(classify_rle("b2o$2o$bo!"), None);
+        // A lone cell dies
+        assert_eq!(classify_rle("o!"), None);
+    }
+
+    #[test]
+    fn test_detects_speed_and_direction() {
+        let glider = detect_periodicity(&decode_rle("bo$2bo$3o!").unwrap(), &Rule::conway(), 30).unwrap();
+        assert_eq!(glider, Periodicity { period: 4, dx: 1, dy: 1 });
+        assert_eq!((glider.speed().as_str(), glider.direction().as_str()), ("c/4 diagonal", "south-east"));
+
+        // This LWSS phase travels west
+        let lwss = detect_periodicity(&decode_rle("bo2bo$o$o3bo$4o!").unwrap(), &Rule::conway(), 30).unwrap();
+        assert_eq!((lwss.period, lwss.dx, lwss.dy), (4, -2, 0));
+        assert_eq!((lwss.speed().as_str(), lwss.direction().as_str()), ("c/2 orthogonal", "west"));
+
+        let blinker = detect_periodicity(&decode_rle("3o!").unwrap(), &Rule::conway(), 30).unwrap();
+        assert_eq!((blinker.period, blinker.is_moving()), (2, false));
+        assert_eq!((blinker.speed(), blinker.direction()), (String::new(), String::new()));
+
+        let oblique = Periodicity { period: 6, dx: -1, dy: -2 };
+        assert_eq!((oblique.speed().as_str(), oblique.direction().as_str()), ("(2,1)c/6 oblique", "north-west"));
+        let fast = Periodicity { period: 5, dx: 0, dy: 2 };
+        assert_eq!(fast.speed(), "2c/5 orthogonal");
+    }
+
+    #[test]
+    fn test_decode_round_trips() {
+        for code in ["xs4_33", "xs6_696", "xp2_7", "xq4_153", "xq4_6frc", "xp2_318c"] {
+            let cells = decode_apgcode(code).unwrap();
+            assert_eq!(classify(&cells, 30).as_deref(), Some(code));
+        }
+    }
+
+    #[test]
+    fn test_zero_runs_and_strips() {
+        // Two cells 45 columns apart on different strips
+        let cells = vec![(0, 0), (45, 6)];
+        let code = wechsler(&cells);
+        assert_eq!(code, "1zyzy22");
+        assert_eq!(decode_apgcode(&format!("xs2_{}", code)).unwrap(), cells);
+    }
+
+    #[test]
+    fn test_rejects_invalid_codes() {
+        assert!(decode_apgcode("33").is_err());
+        assert!(decode_apgcode("yl4_33").is_err());
+        assert!(decode_apgcode("xs4_3!").is_err());
+        assert!(decode_apgcode("xs0_").is_err());
+        assert!(decode_apgcode("xs4_y").is_err());
+    }
+}
@@ -0,0 +1,187 @@
+//! Custom automata registered at startup.
+//!
+//! Rules that neither the built-in notations nor a rule table express well
+//! can live in their own crate: implement [`Automaton`] and pass it to
+//! [`register_automaton`] before the server starts. From then on it is a
+//! rule like any other, selected over gRPC by giving its name as the `rule`
+//! of CreateSimulation or UpdateSimulation; GetStatus lists every name
+//! registered.
+//!
+//! ```text
+//! register_automaton(Arc::new(BriansBrain))?;
+//! App::new().add_plugins(GrpcServerPlugin::new(config)).run();
+//! ```
+//!
+//! An automaton sees a cell's state followed by its neighbors' in the order
+//! rule tables use, clockwise from north, and is stepped the same way: each
+//! distinct neighborhood is looked up once per generation. Saved simulations
+//! record only its name, so a server restoring them must register it too.
+
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use crate::resources::{neighbor_offsets, Neighborhood, Rule, RuleError, MAX_RULE_STATES};
+
+/// A cellular automaton stepped cell by cell from its neighborhood.
+pub trait Automaton: Send + Sync + 'static {
+    /// Name the automaton is selected by, case-insensitively. It must not
+    /// already be a rule.
+    fn name(&self) -> &str;
+
+    /// Cell states including dead (0) and alive (1).
+    fn states(&self) -> u32 {
+        2
+    }
+
+    fn neighborhood(&self) -> Neighborhood {
+        Neighborhood::Moore
+    }
+
+    /// Next state of a cell given its state followed by its neighbors'. An
+    /// empty neighborhood must stay empty.
+    fn next_state(&self, cells: &[u8]) -> u8;
+}
+
+/// A registered automaton as a rule holds it; two are equal if they are the
+/// same registration.
+#[derive(Clone)]
+pub struct AutomatonHandle(Arc<dyn Automaton>);
+
+impl AutomatonHandle {
+    pub fn automaton(&self) -> &dyn Automaton {
+        self.0.as_ref()
+    }
+}
+
+impl fmt::Debug for AutomatonHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AutomatonHandle").field(&self.0.name()).finish()
+    }
+}
+
+impl PartialEq for AutomatonHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for AutomatonHandle {}
+
+static AUTOMATA: RwLock<Vec<AutomatonHandle>> = RwLock::new(Vec::new());
+
+/// Make an automaton selectable as a rule. Fails if its name is taken by a
+/// rule or another automaton, or it has an unusable number of states or
+/// brings an empty neighborhood to life.
+pub fn register_automaton(automaton: Arc<dyn Automaton>) -> Result<(), RuleError> {
+    let name = automaton.name().trim();
+    if name.is_empty() || name.starts_with('@') {
+        return Err(RuleError(format!("Invalid automaton name: {:?}", automaton.name())));
+    }
+    if name.parse::<Rule>().is_ok() {
+        return Err(RuleError(format!("Automaton name is already a rule: {}", name)));
+    }
+    if !(2..=MAX_RULE_STATES).contains(&automaton.states()) {
+        return Err(RuleError(format!("Automaton {} must have 2 to {} states", name, MAX_RULE_STATES)));
+    }
+    let empty = vec![0; neighbor_offsets(automaton.neighborhood()).len() + 1];
+    if automaton.next_state(&empty) != 0 {
+        return Err(RuleError(format!("Automaton {} brings an empty neighborhood to life", name)));
+    }
+    AUTOMATA.write().unwrap().push(AutomatonHandle(automaton));
+    Ok(())
+}
+
+/// The registered automaton named `name`, ignoring case.
+pub fn find_automaton(name: &str) -> Option<AutomatonHandle> {
+    let name = name.trim();
+    AUTOMATA.read().unwrap().iter()
+        .find(|handle| handle.0.name().trim().eq_ignore_ascii_case(name))
+        .cloned()
+}
+
+/// Names of every registered automaton, in registration order.
+pub fn registered_automata() -> Vec<String> {
+    AUTOMATA.read().unwrap().iter().map(|handle| handle.0.name().trim().to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::ChunkedGrid;
+
+    /// Brian's Brain: dead cells with two firing neighbors fire, firing cells
+    /// start refractory and refractory cells die.
+    struct BriansBrain;
+
+    impl Automaton for BriansBrain {
+        fn name(&self) -> &str {
+            "Brian's Brain Test"
+        }
+
+        fn states(&self) -> u32 {
+            3
+        }
+
+        fn next_state(&self, cells: &[u8]) -> u8 {
+            match cells[0] {
+                0 if cells[1..].iter().filter(|&&state| state == 1).count() == 2 => 1,
+                1 => 2,
+                _ => 0,
+            }
+        }
+    }
+
+    struct Spontaneous;
+
+    impl Automaton for Spontaneous {
+        fn name(&self) -> &str {
+            "Spontaneous Test"
+        }
+
+        fn next_state(&self, _cells: &[u8]) -> u8 {
+            1
+        }
+    }
+
+    #[test]
+    fn test_registered_automata_are_rules() {
+        register_automaton(Arc::new(BriansBrain)).unwrap();
+        assert!(registered_automata().contains(&"Brian's Brain Test".to_string()));
+        assert!(register_automaton(Arc::new(BriansBrain)).is_err());
+
+        let rule: Rule = "brian's brain test".parse().unwrap();
+        assert_eq!(rule.to_string(), "Brian's Brain Test");
+        assert_eq!(rule.definition().parse::<Rule>().unwrap(), rule);
+        assert_eq!(rule.states(), 3);
+
+        // Two firing cells side by side light the cells above and below them
+        let mut cells = ChunkedGrid::new();
+        cells.set(3, 3, true);
+        cells.set(4, 3, true);
+        let cells = cells.step_rule(&rule, false);
+        let mut live = cells.live_cells();
+        live.sort_unstable();
+        assert_eq!(live, vec![(3, 2), (3, 4), (4, 2), (4, 4)]);
+        let mut decaying: Vec<_> = cells.iter_decaying().collect();
+        decaying.sort_unstable();
+        assert_eq!(decaying, vec![((3, 3), 2), ((4, 3), 2)]);
+    }
+
+    #[test]
+    fn test_unusable_automata_are_refused() {
+        assert!(register_automaton(Arc::new(Spontaneous)).is_err());
+
+        struct Shadowing;
+        impl Automaton for Shadowing {
+            fn name(&self) -> &str {
+                "B3/S23"
+            }
+
+            fn next_state(&self, _cells: &[u8]) -> u8 {
+                0
+            }
+        }
+        assert!(register_automaton(Arc::new(Shadowing)).is_err());
+        assert!(find_automaton("B3/S23").is_none());
+    }
+}
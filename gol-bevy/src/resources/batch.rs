@@ -0,0 +1,122 @@
+//! Seeded batch runs.
+//!
+//! A batch steps the same pattern many times on scratch grids, each run
+//! with its own seed. A [`SeededRng`] started from the seed fills a square
+//! at the grid's center with a random soup before the pattern is placed
+//! over it, so runs differ only by seed and a batch can be repeated exactly.
+//! Runs stop early once their grid dies out or repeats, as unattended runs
+//! do.
+
+use std::time::{Duration, Instant};
+
+use crate::resources::{random_cells, scratch_simulation, Rule, SeededRng, StopReason};
+
+/// Generations a run steps between checks for cancellation.
+const CANCEL_CHECK_GENERATIONS: u64 = 256;
+
+/// What each run of a batch steps; only the seed differs between runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchRunSpec {
+    /// Cells placed with their bounding box centered on the grid, over the
+    /// soup.
+    pub pattern: Vec<(i32, i32)>,
+    pub rule: Rule,
+    pub width: i32,
+    pub height: i32,
+    pub generations: u64,
+    /// Side of the centered square filled with soup.
+    pub soup_size: i32,
+    /// Chance of each soup cell being alive; `0.0` adds no soup.
+    pub soup_density: f64,
+}
+
+/// How one run ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatchRunOutcome {
+    pub seed: u64,
+    /// Generations stepped, fewer than asked when the run stopped early.
+    pub generations: u64,
+    pub final_population: u64,
+    pub peak_population: u64,
+    /// Set when the grid died out or started repeating.
+    pub stop: Option<StopReason>,
+    pub elapsed: Duration,
+}
+
+/// Step one run of `spec` seeded with `seed`, or `None` if `cancelled`
+/// reported true before it finished.
+pub fn run_seeded(spec: &BatchRunSpec, seed: u64, cancelled: &dyn Fn() -> bool) -> Option<BatchRunOutcome> {
+    let started = Instant::now();
+    let mut simulation = scratch_simulation(spec.width, spec.height);
+    simulation.rule = spec.rule.clone();
+    let soup = random_cells(&mut SeededRng::new(seed), spec.soup_size, spec.soup_size, spec.soup_density);
+    let mut cells = centered(&soup, spec.width, spec.height);
+    cells.extend(centered(&spec.pattern, spec.width, spec.height));
+    simulation.set_cells(&cells);
+    
+    let mut peak_population = simulation.cells.population();
+    let mut stop = None;
+    while simulation.generation < spec.generations {
+        if simulation.generation.is_multiple_of(CANCEL_CHECK_GENERATIONS) && cancelled() {
+            return None;
+        }
+        let pause = simulation.step_watched();
+        peak_population = peak_population.max(simulation.cells.population());
+        if let Some(pause) = pause {
+            stop = Some(pause.reason);
+            break;
+        }
+    }
+    
+    Some(BatchRunOutcome {
+        seed,
+        generations: simulation.generation,
+        final_population: simulation.cells.population(),
+        peak_population,
+        stop,
+        elapsed: started.elapsed(),
+    })
+}
+
+/// `cells` moved so their bounding box is centered on a `width` by `height` grid.
+fn centered(cells: &[(i32, i32)], width: i32, height: i32) -> Vec<(i32, i32)> {
+    let pattern_width = cells.iter().map(|&(x, _)| x).max().unwrap_or(0) + 1;
+    let pattern_height = cells.iter().map(|&(_, y)| y).max().unwrap_or(0) + 1;
+    let (dx, dy) = ((width - pattern_width) / 2, (height - pattern_height) / 2);
+    cells.iter().map(|&(x, y)| (x + dx, y + dy)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::standard_pattern;
+    
+    fn spec(pattern: Vec<(i32, i32)>, density: f64) -> BatchRunSpec {
+        BatchRunSpec {
+            pattern,
+            rule: Rule::conway(),
+            width: 64,
+            height: 64,
+            generations: 500,
+            soup_size: 16,
+            soup_density: density,
+        }
+    }
+    
+    #[test]
+    fn test_runs_stop_when_stable() {
+        let outcome = run_seeded(&spec(standard_pattern("diehard").unwrap(), 0.0), 1, &|| false).unwrap();
+        assert_eq!(outcome.stop, Some(StopReason::Extinct));
+        assert_eq!(outcome.generations, 130);
+        assert_eq!(outcome.final_population, 0);
+        
+        let block = run_seeded(&spec(vec![(0, 0), (1, 0), (0, 1), (1, 1)], 0.0), 1, &|| false).unwrap();
+        assert_eq!(block.stop, Some(StopReason::Cycle { period: 1 }));
+        assert_eq!(block.peak_population, 4);
+    }
+    
+    #[test]
+    fn test_cancelled_runs_return_nothing() {
+        assert!(run_seeded(&spec(Vec::new(), 0.4), 3, &|| true).is_none());
+    }
+}
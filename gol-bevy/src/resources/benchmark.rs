@@ -0,0 +1,93 @@
+use std::time::{Duration, Instant};
+
+use crate::resources::{decode_rle, SimulationData, Simulations};
+
+/// Patterns RunBenchmark can step, as RLE, so every backend runs the same
+/// workload. The first is the default.
+pub const STANDARD_PATTERNS: [(&str, &str); 4] = [
+    ("glider-gun", "24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!"),
+    ("acorn", "bo$3bo$2o2b3o!"),
+    ("r-pentomino", "b2o$2o$bo!"),
+    ("diehard", "6bo$2o$bo3b3o!"),
+];
+
+/// Cells of a standard pattern, by name ignoring case.
+pub fn standard_pattern(name: &str) -> Option<Vec<(i32, i32)>> {
+    let (_, rle) = STANDARD_PATTERNS.iter().find(|(pattern, _)| pattern.eq_ignore_ascii_case(name))?;
+    decode_rle(rle).ok()
+}
+
+/// Timing and storage figures from stepping a pattern in-process.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchmarkResult {
+    pub generations: u64,
+    /// Time spent stepping, without building the grid.
+    pub elapsed: Duration,
+    /// Live cells at the start of each step, summed.
+    pub cell_updates: u64,
+    pub final_population: u64,
+    pub peak_population: u64,
+    pub peak_memory_bytes: u64,
+}
+
+/// Step `cells`, centered on a Conway grid of `width` by `height`, for
+/// `generations` generations.
+pub fn run_benchmark(cells: &[(i32, i32)], width: i32, height: i32, generations: u64) -> BenchmarkResult {
+    let mut simulation = scratch_simulation(width, height);
+    let pattern_width = cells.iter().map(|&(x, _)| x).max().unwrap_or(0) + 1;
+    let pattern_height = cells.iter().map(|&(_, y)| y).max().unwrap_or(0) + 1;
+    let (dx, dy) = ((width - pattern_width) / 2, (height - pattern_height) / 2);
+    let placed: Vec<(i32, i32)> = cells.iter().map(|&(x, y)| (x + dx, y + dy)).collect();
+    simulation.set_cells(&placed);
+    
+    let mut cell_updates = 0;
+    let mut peak_population = simulation.cells.population();
+    let mut peak_memory_bytes = simulation.cells.stats().memory_bytes;
+    let started = Instant::now();
+    for _ in 0..generations {
+        cell_updates += simulation.cells.population();
+        simulation.step();
+        let stats = simulation.cells.stats();
+        peak_population = peak_population.max(stats.live_cells);
+        peak_memory_bytes = peak_memory_bytes.max(stats.memory_bytes);
+    }
+    
+    BenchmarkResult {
+        generations,
+        elapsed: started.elapsed(),
+        cell_updates,
+        final_population: simulation.cells.population(),
+        peak_population,
+        peak_memory_bytes,
+    }
+}
+
+/// A simulation that belongs to no server state.
+pub fn scratch_simulation(width: i32, height: i32) -> SimulationData {
+    let mut simulations = Simulations::new();
+    let id = simulations.create_simulation(width, height, None);
+    simulations.simulations.remove(&id).expect("simulation was just created")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_standard_patterns_decode() {
+        for (name, _) in STANDARD_PATTERNS {
+            assert!(standard_pattern(name).is_some_and(|cells| !cells.is_empty()), "{}", name);
+        }
+        assert_eq!(standard_pattern("Glider-Gun").map(|cells| cells.len()), Some(36));
+        assert!(standard_pattern("gun").is_none());
+    }
+    
+    #[test]
+    fn test_diehard_dies_out() {
+        let result = run_benchmark(&standard_pattern("diehard").unwrap(), 100, 100, 130);
+        assert_eq!(result.generations, 130);
+        assert_eq!(result.final_population, 0);
+        assert!(result.peak_population > 7);
+        assert!(result.cell_updates >= 7 * 129);
+    }
+}
@@ -0,0 +1,247 @@
+//! What happens at the edges of a bounded simulation.
+//!
+//! Cells are stored on an unbounded [`ChunkedGrid`] and a step is computed as
+//! if the grid went on forever, then clipped back to the simulation's
+//! rectangle. A clipped grid treats everything past its edges as dead. The
+//! other boundaries copy the cells within reach of an edge to just past it
+//! before each step, so the step sees the grid's surroundings as they are on
+//! the surface it models:
+//!
+//! - a wrapped grid is a torus, with each edge joined to the opposite one;
+//! - a mirrored grid reflects at every edge, as if the grid were repeated
+//!   flipped beyond it;
+//! - a Klein bottle joins left and right edges like a torus, but joins top
+//!   and bottom with a left-right flip.
+
+use serde::{Deserialize, Serialize};
+
+use crate::resources::ChunkedGrid;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GridBoundary {
+    /// Cells past the edges are dead and never come alive.
+    #[default]
+    Clip,
+    /// Opposite edges are joined, as on a torus.
+    Wrap,
+    /// Every edge reflects the cells next to it.
+    Mirror,
+    /// Left and right edges are joined; top and bottom are joined flipped
+    /// left to right.
+    KleinBottle,
+}
+
+impl GridBoundary {
+    /// Copy the cells that a step with a `margin`-cell neighborhood would see
+    /// across the edges of the `width` x `height` grid to just outside it.
+    /// The copies must be clipped away again after the step.
+    pub fn extend(self, cells: &mut ChunkedGrid, width: i32, height: i32, margin: i32) {
+        if self == GridBoundary::Clip || (cells.population() == 0 && cells.decaying_count() == 0) {
+            return;
+        }
+        let near_edge = |(x, y): (i32, i32)| x < margin || x >= width - margin || y < margin || y >= height - margin;
+
+        let mut copies = Vec::new();
+        let sources = cells.iter_live().chain(cells.iter_decaying().map(|(cell, _)| cell));
+        for (x, y) in sources.filter(|&cell| near_edge(cell)) {
+            let (state, color) = (cells.state(x, y), cells.color(x, y));
+            for image in self.images(x, y, width, height, margin) {
+                copies.push((image, state, color));
+            }
+        }
+        for ((x, y), state, color) in copies {
+            cells.set_state(x, y, state);
+            cells.set_color(x, y, color);
+        }
+    }
+
+    /// Bring a position that left the grid back onto it, as an agent walking
+    /// across the edge would arrive. Returns the position and whether the
+    /// crossing flipped left and right, and up and down; `None` if a clipped
+    /// grid lost it.
+    pub fn fold(self, x: i32, y: i32, width: i32, height: i32) -> Option<(i32, i32, bool, bool)> {
+        match self {
+            GridBoundary::Clip => {
+                (x >= 0 && x < width && y >= 0 && y < height).then_some((x, y, false, false))
+            }
+            GridBoundary::Wrap => Some((x.rem_euclid(width), y.rem_euclid(height), false, false)),
+            GridBoundary::Mirror => {
+                let (x, flip_x) = reflected(x, width);
+                let (y, flip_y) = reflected(y, height);
+                Some((x, y, flip_x, flip_y))
+            }
+            GridBoundary::KleinBottle => {
+                let flip_x = y.div_euclid(height) % 2 != 0;
+                let x = if flip_x { width - 1 - x } else { x };
+                Some((x.rem_euclid(width), y.rem_euclid(height), flip_x, false))
+            }
+        }
+    }
+
+    /// Positions outside the grid but within `margin` cells of it where the
+    /// cell at `(x, y)` appears.
+    fn images(self, x: i32, y: i32, width: i32, height: i32, margin: i32) -> Vec<(i32, i32)> {
+        let images: Vec<(i32, i32)> = match self {
+            GridBoundary::Clip => Vec::new(),
+            GridBoundary::Wrap => grid_product(&wrapped(x, width, margin), &wrapped(y, height, margin)),
+            GridBoundary::Mirror => grid_product(&mirrored(x, width, margin), &mirrored(y, height, margin)),
+            GridBoundary::KleinBottle => {
+                // Each lap across the top or bottom edge flips the row
+                let laps = margin / height + 1;
+                (-laps..=laps)
+                    .map(|lap| (lap, y + lap * height))
+                    .filter(|&(_, image_y)| in_band(image_y, height, margin))
+                    .flat_map(|(lap, image_y)| {
+                        let column = if lap % 2 != 0 { width - 1 - x } else { x };
+                        wrapped(column, width, margin).into_iter().map(move |image_x| (image_x, image_y))
+                    })
+                    .collect()
+            }
+        };
+        images.into_iter().filter(|&image| image != (x, y)).collect()
+    }
+}
+
+fn in_band(position: i32, size: i32, margin: i32) -> bool {
+    position >= -margin && position < size + margin
+}
+
+fn grid_product(xs: &[i32], ys: &[i32]) -> Vec<(i32, i32)> {
+    xs.iter().flat_map(|&x| ys.iter().map(move |&y| (x, y))).collect()
+}
+
+/// Every `position + k * size` in `[-margin, size + margin)`.
+fn wrapped(position: i32, size: i32, margin: i32) -> Vec<i32> {
+    let laps = margin / size + 1;
+    (-laps..=laps)
+        .map(|lap| position + lap * size)
+        .filter(|&image| in_band(image, size, margin))
+        .collect()
+}
+
+/// Every reflection of `position` in `[-margin, size + margin)` when each
+/// edge mirrors the line of cells beside it: `-1` reflects `0`, `size`
+/// reflects `size - 1`, and so on.
+fn mirrored(position: i32, size: i32, margin: i32) -> Vec<i32> {
+    let laps = margin / (2 * size) + 1;
+    let mut images: Vec<i32> = (-laps..=laps)
+        .flat_map(|lap| [position + 2 * lap * size, -1 - position + 2 * lap * size])
+        .filter(|&image| in_band(image, size, margin))
+        .collect();
+    images.sort_unstable();
+    images.dedup();
+    images
+}
+
+/// Where a mirrored line of `size` cells puts `position`, and whether it
+/// arrives reflected.
+fn reflected(position: i32, size: i32) -> (i32, bool) {
+    let offset = position.rem_euclid(2 * size);
+    if offset < size {
+        (offset, false)
+    } else {
+        (2 * size - 1 - offset, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::Rule;
+
+    fn step(cells: &ChunkedGrid, boundary: GridBoundary, size: i32) -> ChunkedGrid {
+        let mut cells = cells.clone();
+        boundary.extend(&mut cells, size, size, 1);
+        let mut next = cells.step_rule(&Rule::conway(), false);
+        next.retain_rect(size, size);
+        next
+    }
+
+    fn grid(cells: &[(i32, i32)]) -> ChunkedGrid {
+        let mut grid = ChunkedGrid::new();
+        for &(x, y) in cells {
+            grid.set(x, y, true);
+        }
+        grid
+    }
+
+    fn sorted_cells(grid: &ChunkedGrid) -> Vec<(i32, i32)> {
+        let mut cells = grid.live_cells();
+        cells.sort_unstable();
+        cells
+    }
+
+    #[test]
+    fn test_wrap_joins_opposite_edges() {
+        // A blinker lying across the left and right edges
+        let mut cells = ChunkedGrid::new();
+        for (x, y) in [(9, 5), (0, 5), (1, 5)] {
+            cells.set(x, y, true);
+        }
+
+        let wrapped = step(&cells, GridBoundary::Wrap, 10);
+        let mut live = wrapped.live_cells();
+        live.sort_unstable();
+        assert_eq!(live, vec![(0, 4), (0, 5), (0, 6)]);
+
+        // Clipped, the two halves die out alone
+        assert_eq!(step(&cells, GridBoundary::Clip, 10).population(), 0);
+    }
+
+    #[test]
+    fn test_wrap_carries_glider_around_corner() {
+        let mut cells = ChunkedGrid::new();
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            cells.set(x, y, true);
+        }
+        // A glider moves one cell diagonally every 4 generations, so after 4 * 8
+        // it has crossed the whole 8x8 torus and is back where it started
+        let start = cells.clone();
+        for _ in 0..32 {
+            cells = step(&cells, GridBoundary::Wrap, 8);
+        }
+        assert_eq!(cells, start);
+    }
+
+    #[test]
+    fn test_images_cover_small_grids() {
+        assert_eq!(wrapped(0, 3, 1), vec![0, 3]);
+        assert_eq!(wrapped(2, 3, 1), vec![-1, 2]);
+        assert_eq!(wrapped(0, 2, 3), vec![-2, 0, 2, 4]);
+        assert_eq!(mirrored(0, 3, 1), vec![-1, 0]);
+        assert_eq!(mirrored(2, 3, 1), vec![2, 3]);
+        assert_eq!(mirrored(0, 2, 3), vec![-1, 0, 3, 4]);
+    }
+
+    #[test]
+    fn test_mirror_reflects_at_edges() {
+        // A blinker standing on the left edge sees its own reflection beside it
+        let cells = grid(&[(0, 4), (0, 5), (0, 6)]);
+        assert_eq!(sorted_cells(&step(&cells, GridBoundary::Mirror, 10)), vec![(0, 4), (0, 6), (1, 5)]);
+    }
+
+    #[test]
+    fn test_klein_bottle_flips_across_top_and_bottom() {
+        // On the centre column the flip changes nothing, so a blinker across
+        // the top and bottom edges turns as it would on a torus
+        let centred = grid(&[(4, 8), (4, 0), (4, 1)]);
+        assert_eq!(sorted_cells(&step(&centred, GridBoundary::KleinBottle, 9)), vec![(3, 0), (4, 0), (5, 0)]);
+
+        // Off centre its far end reappears on the mirrored column, too far away
+        let off_centre = grid(&[(2, 8), (2, 0), (2, 1)]);
+        assert_eq!(step(&off_centre, GridBoundary::KleinBottle, 9).population(), 0);
+
+        // Left and right are joined as usual
+        let across = grid(&[(8, 4), (0, 4), (1, 4)]);
+        assert_eq!(sorted_cells(&step(&across, GridBoundary::KleinBottle, 9)), vec![(0, 3), (0, 4), (0, 5)]);
+    }
+
+    #[test]
+    fn test_fold_brings_positions_back() {
+        assert_eq!(GridBoundary::Clip.fold(10, 3, 10, 10), None);
+        assert_eq!(GridBoundary::Wrap.fold(10, -1, 10, 10), Some((0, 9, false, false)));
+        assert_eq!(GridBoundary::Mirror.fold(10, -1, 10, 10), Some((9, 0, true, true)));
+        assert_eq!(GridBoundary::KleinBottle.fold(2, -1, 10, 10), Some((7, 9, true, false)));
+        assert_eq!(GridBoundary::KleinBottle.fold(-1, 3, 10, 10), Some((9, 3, false, false)));
+    }
+}
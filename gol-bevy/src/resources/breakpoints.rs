@@ -0,0 +1,163 @@
+//! Conditions that stop a simulation being stepped when they come true.
+//!
+//! Breakpoints are checked after every generation stepped, by callers and
+//! unattended runs alike, and the steps stop at the first generation one
+//! holds. Each fires as its condition changes rather than while it holds: a
+//! population threshold when it is crossed, a pattern when more of it
+//! appear, a generation when it is reached. Stepping on from a breakpoint
+//! therefore carries on until the next time it fires.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::resources::census::shape;
+use crate::resources::{apgcode_for_name, classify, clusters, decode_apgcode, decode_rle, ChunkedGrid, CENSUS_MAX_OBJECT_CELLS, CENSUS_MAX_PERIOD};
+
+/// Cluster shapes a pattern breakpoint remembers the identity of before it
+/// starts over, so settled ash is only identified once.
+const MAX_REMEMBERED_SHAPES: usize = 4096;
+
+/// What makes a breakpoint fire.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BreakCondition {
+    /// The population rises to this many live cells or more from fewer.
+    PopulationAbove(u64),
+    /// The population falls to this many live cells or fewer from more.
+    PopulationBelow(u64),
+    /// More objects with this apgcode are on the grid than a generation ago.
+    Pattern(String),
+    /// The simulation reaches this generation.
+    Generation(u64),
+}
+
+impl fmt::Display for BreakCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BreakCondition::PopulationAbove(threshold) => write!(f, "population above {}", threshold),
+            BreakCondition::PopulationBelow(threshold) => write!(f, "population below {}", threshold),
+            BreakCondition::Pattern(apgcode) => write!(f, "pattern {}", apgcode),
+            BreakCondition::Generation(generation) => write!(f, "generation {}", generation),
+        }
+    }
+}
+
+/// A condition and what it last saw of the simulation.
+#[derive(Debug, Clone)]
+pub struct Breakpoint {
+    pub condition: BreakCondition,
+    /// Objects matching a pattern condition at the last generation checked.
+    matches: usize,
+    /// Whether each cluster shape seen is the pattern.
+    shapes: HashMap<Vec<(i32, i32)>, bool>,
+}
+
+impl Breakpoint {
+    /// A breakpoint on a simulation whose cells are `cells`, which do not
+    /// count as having just reached the condition.
+    pub fn new(condition: BreakCondition, cells: &ChunkedGrid) -> Self {
+        let mut shapes = HashMap::new();
+        let matches = match &condition {
+            BreakCondition::Pattern(apgcode) => count_matches(&mut shapes, apgcode, cells),
+            _ => 0,
+        };
+        Self { condition, matches, shapes }
+    }
+
+    /// Whether the step that took a simulation from `previous_population`
+    /// live cells to `cells` at `generation` fired this breakpoint.
+    pub fn fired(&mut self, generation: u64, previous_population: u64, cells: &ChunkedGrid) -> bool {
+        let population = cells.population();
+        match &self.condition {
+            BreakCondition::PopulationAbove(threshold) => previous_population < *threshold && population >= *threshold,
+            BreakCondition::PopulationBelow(threshold) => previous_population > *threshold && population <= *threshold,
+            BreakCondition::Generation(target) => generation == *target,
+            BreakCondition::Pattern(apgcode) => {
+                let previous = std::mem::replace(&mut self.matches, count_matches(&mut self.shapes, apgcode, cells));
+                self.matches > previous
+            }
+        }
+    }
+}
+
+/// Clusters of live cells identified as `apgcode`, in any phase and
+/// orientation, remembering in `shapes` which cluster shapes are.
+fn count_matches(shapes: &mut HashMap<Vec<(i32, i32)>, bool>, apgcode: &str, cells: &ChunkedGrid) -> usize {
+    if shapes.len() > MAX_REMEMBERED_SHAPES {
+        shapes.clear();
+    }
+    clusters(&cells.live_cells()).into_iter()
+        .filter(|cluster| cluster.len() <= CENSUS_MAX_OBJECT_CELLS)
+        .filter(|cluster| *shapes.entry(shape(cluster))
+            .or_insert_with(|| classify(cluster, CENSUS_MAX_PERIOD).as_deref() == Some(apgcode)))
+        .count()
+}
+
+/// The canonical apgcode of a pattern given by common name (`glider`),
+/// apgcode (`xq4_153`) or RLE (`bo$2bo$3o!`), for a pattern breakpoint.
+pub fn pattern_apgcode(pattern: &str) -> Result<String, String> {
+    let pattern = pattern.trim();
+    if let Some(apgcode) = apgcode_for_name(pattern) {
+        return Ok(apgcode.to_string());
+    }
+    let cells = decode_apgcode(pattern)
+        .or_else(|_| decode_rle(pattern))
+        .map_err(|_| format!("'{}' is not a pattern name, apgcode or RLE", pattern))?;
+    classify(&cells, CENSUS_MAX_PERIOD)
+        .ok_or_else(|| format!("'{}' is not a still life, oscillator or spaceship", pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rle: &str, dx: i32, dy: i32) -> ChunkedGrid {
+        let mut cells = ChunkedGrid::new();
+        for (x, y) in decode_rle(rle).unwrap() {
+            cells.set(x + dx, y + dy, true);
+        }
+        cells
+    }
+
+    #[test]
+    fn test_population_breakpoints_fire_on_crossing() {
+        let cells = grid("3o!", 0, 0);
+        let mut above = Breakpoint::new(BreakCondition::PopulationAbove(3), &cells);
+        assert!(above.fired(1, 2, &cells));
+        assert!(!above.fired(2, 3, &cells));
+
+        let mut below = Breakpoint::new(BreakCondition::PopulationBelow(3), &cells);
+        assert!(below.fired(1, 4, &cells));
+        assert!(!below.fired(2, 3, &cells));
+    }
+
+    #[test]
+    fn test_generation_breakpoint_fires_once_reached() {
+        let cells = ChunkedGrid::new();
+        let mut breakpoint = Breakpoint::new(BreakCondition::Generation(10), &cells);
+        assert!(!breakpoint.fired(9, 0, &cells));
+        assert!(breakpoint.fired(10, 0, &cells));
+    }
+
+    #[test]
+    fn test_pattern_breakpoint_fires_when_more_appear() {
+        let one = grid("bo$2bo$3o!", 0, 0);
+        let mut breakpoint = Breakpoint::new(BreakCondition::Pattern("xq4_153".to_string()), &one);
+        assert!(!breakpoint.fired(1, 5, &one));
+
+        let mut two = grid("bo$2bo$3o!", 0, 0);
+        // Another glider, rotated and well apart
+        for (x, y) in decode_rle("3o$2bo$bo!").unwrap() {
+            two.set(x + 20, y + 20, true);
+        }
+        assert!(breakpoint.fired(2, 5, &two));
+        assert!(!breakpoint.fired(3, 10, &two));
+    }
+
+    #[test]
+    fn test_pattern_apgcode_accepts_names_codes_and_rle() {
+        assert_eq!(pattern_apgcode("glider").unwrap(), "xq4_153");
+        assert_eq!(pattern_apgcode("xs4_33").unwrap(), "xs4_33");
+        assert_eq!(pattern_apgcode("b2o$o2bo$b2o!").unwrap(), "xs6_696");
+        assert!(pattern_apgcode("not a pattern").is_err());
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::resources::classify;
+
+/// Longest period an object is run for while identifying it.
+pub const CENSUS_MAX_PERIOD: u32 = 30;
+
+/// Objects larger than this are left unidentified rather than simulated.
+pub const CENSUS_MAX_OBJECT_CELLS: usize = 1000;
+
+/// Everyday names for the commonest objects, keyed by apgcode.
+const COMMON_NAMES: [(&str, &str); 12] = [
+    ("xs4_33", "block"),
+    ("xs6_696", "beehive"),
+    ("xs7_2596", "loaf"),
+    ("xs5_253", "boat"),
+    ("xs6_356", "ship"),
+    ("xs4_252", "tub"),
+    ("xs8_6996", "pond"),
+    ("xp2_7", "blinker"),
+    ("xp2_7e", "toad"),
+    ("xp2_318c", "beacon"),
+    ("xq4_153", "glider"),
+    ("xq4_6frc", "lightweight spaceship"),
+];
+
+/// Everyday name of an object, if it has one.
+pub fn common_name(apgcode: &str) -> Option<&'static str> {
+    COMMON_NAMES.iter().find(|(code, _)| *code == apgcode).map(|(_, name)| *name)
+}
+
+/// Apgcode of the object with this everyday name, ignoring case.
+pub fn apgcode_for_name(name: &str) -> Option<&'static str> {
+    COMMON_NAMES.iter().find(|(_, common)| common.eq_ignore_ascii_case(name)).map(|(code, _)| *code)
+}
+
+/// How many of one kind of object were found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CensusEntry {
+    pub apgcode: String,
+    pub count: u32,
+    /// Live cells across every instance.
+    pub cells: u64,
+}
+
+/// Objects on a grid, grouped by apgcode, most common first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Census {
+    pub objects: Vec<CensusEntry>,
+    /// Clusters that are not periodic within [`CENSUS_MAX_PERIOD`] generations,
+    /// or too large to try.
+    pub unidentified_objects: u32,
+    pub unidentified_cells: u64,
+}
+
+/// Split live cells into clusters, joining cells up to two apart so that
+/// objects such as spaceships, whose cells are not all adjacent in every
+/// phase, stay whole.
+pub fn clusters(cells: &[(i32, i32)]) -> Vec<Vec<(i32, i32)>> {
+    let mut unvisited: HashSet<(i32, i32)> = cells.iter().copied().collect();
+    let mut clusters = Vec::new();
+
+    let mut starts = cells.to_vec();
+    starts.sort_unstable();
+    for start in starts {
+        if !unvisited.remove(&start) {
+            continue;
+        }
+        let mut cluster = vec![start];
+        let mut queue = VecDeque::from([start]);
+        while let Some((x, y)) = queue.pop_front() {
+            for dy in -2..=2 {
+                for dx in -2..=2 {
+                    let neighbor = (x + dx, y + dy);
+                    if unvisited.remove(&neighbor) {
+                        cluster.push(neighbor);
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+        clusters.push(cluster);
+    }
+    clusters
+}
+
+/// Identify every cluster of live cells by running it in isolation.
+///
+/// Identical clusters are only simulated once per census, so grids settled
+/// into ash of a handful of common objects are cheap to survey.
+pub fn take_census(cells: &[(i32, i32)]) -> Census {
+    let mut identified: HashMap<Vec<(i32, i32)>, Option<String>> = HashMap::new();
+    let mut counts: HashMap<String, (u32, u64)> = HashMap::new();
+    let mut census = Census::default();
+
+    for cluster in clusters(cells) {
+        let apgcode = if cluster.len() > CENSUS_MAX_OBJECT_CELLS {
+            None
+        } else {
+            identified.entry(shape(&cluster))
+                .or_insert_with(|| classify(&cluster, CENSUS_MAX_PERIOD))
+                .clone()
+        };
+
+        match apgcode {
+            Some(apgcode) => {
+                let entry = counts.entry(apgcode).or_default();
+                entry.0 += 1;
+                entry.1 += cluster.len() as u64;
+            }
+            None => {
+                census.unidentified_objects += 1;
+                census.unidentified_cells += cluster.len() as u64;
+            }
+        }
+    }
+
+    census.objects = counts.into_iter()
+        .map(|(apgcode, (count, cells))| CensusEntry { apgcode, count, cells })
+        .collect();
+    census.objects.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.apgcode.cmp(&b.apgcode)));
+    census
+}
+
+/// A cluster's cells relative to its bounding box, for spotting repeats.
+pub(crate) fn shape(cluster: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    let min_x = cluster.iter().map(|(x, _)| *x).min().unwrap_or(0);
+    let min_y = cluster.iter().map(|(_, y)| *y).min().unwrap_or(0);
+    let mut shape: Vec<_> = cluster.iter().map(|(x, y)| (x - min_x, y - min_y)).collect();
+    shape.sort_unstable();
+    shape
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::decode_rle;
+
+    fn place(rle: &str, dx: i32, dy: i32) -> Vec<(i32, i32)> {
+        decode_rle(rle).unwrap().into_iter().map(|(x, y)| (x + dx, y + dy)).collect()
+    }
+
+    #[test]
+    fn test_common_names_match_classification() {
+        let objects = [
+            ("2o$2o!", "block"),
+            ("b2o$o2bo$b2o!", "beehive"),
+            ("b2o$o2bo$bobo$2bo!", "loaf"),
+            ("2o$obo$bo!", "boat"),
+            ("2o$obo$b2o!", "ship"),
+            ("bo$obo$bo!", "tub"),
+            ("b2o$o2bo$o2bo$b2o!", "pond"),
+            ("3o!", "blinker"),
+            ("b3o$3o!", "toad"),
+            ("2o$2o$2b2o$2b2o!", "beacon"),
+            ("bo$2bo$3o!", "glider"),
+            ("bo2bo$o$o3bo$4o!", "lightweight spaceship"),
+        ];
+        for (rle, name) in objects {
+            let apgcode = classify(&place(rle, 0, 0), CENSUS_MAX_PERIOD).unwrap();
+            assert_eq!(common_name(&apgcode), Some(name), "{} classified as {}", name, apgcode);
+        }
+    }
+
+    #[test]
+    fn test_census_counts_objects() {
+        let mut cells = place("2o$2o!", 0, 0);
+        cells.extend(place("2o$2o!", 10, 0));
+        cells.extend(place("3o!", 0, 10));
+        cells.extend(place("bo2bo$o$o3bo$4o!", 20, 20));
+        // An R-pentomino is still evolving
+        cells.extend(place("b2o$2o$bo!", 40, 0));
+
+        let census = take_census(&cells);
+        assert_eq!(census.objects, vec![
+            CensusEntry { apgcode: "xs4_33".to_string(), count: 2, cells: 8 },
+            CensusEntry { apgcode: "xp2_7".to_string(), count: 1, cells: 3 },
+            CensusEntry { apgcode: "xq4_6frc".to_string(), count: 1, cells: 9 },
+        ]);
+        assert_eq!((census.unidentified_objects, census.unidentified_cells), (1, 5));
+    }
+
+    #[test]
+    fn test_clusters_join_cells_two_apart() {
+        let cells = vec![(0, 0), (2, 2), (10, 10), (13, 10)];
+        let mut sizes: Vec<_> = clusters(&cells).iter().map(Vec::len).collect();
+        sizes.sort_unstable();
+        assert_eq!(sizes, vec![1, 1, 2]);
+    }
+}
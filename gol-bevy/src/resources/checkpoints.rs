@@ -0,0 +1,144 @@
+//! Named checkpoints of a simulation.
+//!
+//! A checkpoint copies everything stepping depends on (generation, cells,
+//! rule, scheduled rule changes, boundary and turmites) so a simulation can
+//! be rolled back to it after edits or runs that went wrong, however long
+//! ago. Unlike the replay log they are saved with the simulation. Names,
+//! annotations, breakpoints and other settings are not part of one and are
+//! left alone by a rollback.
+
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::resources::{SimulationData, StagnationWatch};
+
+/// Most checkpoints one simulation may have.
+pub const MAX_CHECKPOINTS: usize = 8;
+
+/// Longest checkpoint name, in characters.
+pub const MAX_CHECKPOINT_NAME_LENGTH: usize = 64;
+
+/// A simulation's state as it was when the checkpoint was made.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    pub name: String,
+    pub created_at: SystemTime,
+    /// Shared, so publishing the simulation does not copy every checkpoint's
+    /// cells; its own checkpoints are always empty.
+    pub state: Arc<SimulationData>,
+}
+
+impl Checkpoint {
+    pub fn generation(&self) -> u64 {
+        self.state.generation
+    }
+}
+
+/// `name` trimmed, or why it cannot name a checkpoint: empty, too long or
+/// holding control characters.
+pub fn checkpoint_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Name is required".to_string());
+    }
+    if name.chars().count() > MAX_CHECKPOINT_NAME_LENGTH {
+        return Err(format!("Name must be at most {} characters", MAX_CHECKPOINT_NAME_LENGTH));
+    }
+    if name.chars().any(char::is_control) {
+        return Err("Name must not contain control characters".to_string());
+    }
+    Ok(name.to_string())
+}
+
+impl SimulationData {
+    pub fn checkpoint(&self, name: &str) -> Option<&Checkpoint> {
+        self.checkpoints.iter().find(|checkpoint| checkpoint.name == name)
+    }
+
+    /// Checkpoint the current state as `name`, replacing any checkpoint
+    /// already called that. Fails when the simulation already has
+    /// [`MAX_CHECKPOINTS`] others.
+    pub fn create_checkpoint(&mut self, name: String) -> Result<&Checkpoint, String> {
+        self.checkpoints.retain(|checkpoint| checkpoint.name != name);
+        if self.checkpoints.len() >= MAX_CHECKPOINTS {
+            return Err(format!("At most {} checkpoints are allowed; delete one first", MAX_CHECKPOINTS));
+        }
+        let state = SimulationData { checkpoints: Vec::new(), ..self.clone() };
+        self.checkpoints.push(Checkpoint { name, created_at: SystemTime::now(), state: Arc::new(state) });
+        Ok(self.checkpoints.last().unwrap())
+    }
+
+    /// Whether there was a checkpoint called `name` to delete.
+    pub fn delete_checkpoint(&mut self, name: &str) -> bool {
+        let count = self.checkpoints.len();
+        self.checkpoints.retain(|checkpoint| checkpoint.name != name);
+        self.checkpoints.len() < count
+    }
+
+    /// Roll back to the checkpoint called `name`, keeping it for later
+    /// rollbacks. Earlier states are no guide to repeats after a rollback,
+    /// so any auto-pause is cleared and the stagnation watch starts over.
+    /// Returns whether there was such a checkpoint.
+    pub fn restore_checkpoint(&mut self, name: &str) -> bool {
+        let Some(state) = self.checkpoint(name).map(|checkpoint| Arc::clone(&checkpoint.state)) else {
+            return false;
+        };
+        self.generation = state.generation;
+        self.cells = state.cells.clone();
+        self.rule = state.rule.clone();
+        self.rule_changes = state.rule_changes.clone();
+        self.boundary = state.boundary;
+        self.turmites = state.turmites.clone();
+        self.auto_pause = None;
+        self.stagnation = StagnationWatch::default();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::scratch_simulation;
+
+    #[test]
+    fn test_restore_rolls_back_stepping_state() {
+        let mut simulation = scratch_simulation(32, 32);
+        simulation.set_cells(&[(10, 10), (11, 10), (12, 10)]);
+        simulation.create_checkpoint("blinker".to_string()).unwrap();
+        simulation.set_cells(&[(1, 1)]);
+        simulation.set_rule("B36/S23".parse().unwrap());
+        simulation.step();
+        simulation.name = Some("kept".to_string());
+
+        assert!(simulation.restore_checkpoint("blinker"));
+        assert_eq!(simulation.generation, 0);
+        assert_eq!(simulation.cells.population(), 3);
+        assert_eq!(simulation.rule.to_string(), "B3/S23");
+        assert_eq!(simulation.name.as_deref(), Some("kept"));
+        assert!(simulation.checkpoint("blinker").is_some());
+        assert!(!simulation.restore_checkpoint("missing"));
+    }
+
+    #[test]
+    fn test_checkpoints_are_limited_and_replaced_by_name() {
+        let mut simulation = scratch_simulation(8, 8);
+        for index in 0..MAX_CHECKPOINTS {
+            simulation.create_checkpoint(format!("c{}", index)).unwrap();
+        }
+        assert!(simulation.create_checkpoint("another".to_string()).is_err());
+        simulation.step();
+        assert_eq!(simulation.create_checkpoint("c0".to_string()).unwrap().generation(), 1);
+        assert_eq!(simulation.checkpoints.len(), MAX_CHECKPOINTS);
+        assert!(simulation.delete_checkpoint("c0"));
+        assert!(!simulation.delete_checkpoint("c0"));
+        assert!(simulation.checkpoints.iter().all(|checkpoint| checkpoint.state.checkpoints.is_empty()));
+    }
+
+    #[test]
+    fn test_checkpoint_names_are_checked() {
+        assert_eq!(checkpoint_name("  before edit ").unwrap(), "before edit");
+        assert!(checkpoint_name("  ").is_err());
+        assert!(checkpoint_name(&"x".repeat(MAX_CHECKPOINT_NAME_LENGTH + 1)).is_err());
+        assert!(checkpoint_name("a\tb").is_err());
+    }
+}
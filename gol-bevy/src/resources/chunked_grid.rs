@@ -0,0 +1,791 @@
+//! Chunked sparse cell storage.
+//!
+//! Cells are stored in 64x64 bitmap chunks keyed by chunk coordinate. Each chunk
+//! row is a single `u64`, so empty space costs nothing, neighboring cells share
+//! cache lines, and a generation step computes a whole row of neighbor counts
+//! with bitwise adders instead of hashing every cell. Chunk coordinates are
+//! unbounded, so the grid itself is effectively infinite; bounded simulations
+//! clip with [`ChunkedGrid::retain_rect`].
+//!
+//! Cells decaying under a Generations rule are rare next to live ones and are
+//! kept beside the bitmaps in a map from coordinate to state. Likewise the
+//! colors of live cells under Immigration or QuadLife are kept in a map that
+//! leaves out color 0.
+
+#[cfg(feature = "server")]
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::resources::{neighbor_offsets, step_transitions, Neighborhood, Rule};
+
+/// Width and height of a chunk in cells.
+pub const CHUNK_SIZE: i32 = 64;
+
+const CHUNK_SHIFT: i32 = 6;
+const CHUNK_MASK: i32 = CHUNK_SIZE - 1;
+const LAST_ROW: usize = CHUNK_SIZE as usize - 1;
+
+/// A 64x64 block of cells. Bit `x` of `rows[y]` is the cell at local `(x, y)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Chunk {
+    rows: [u64; CHUNK_SIZE as usize],
+}
+
+impl Chunk {
+    pub const EMPTY: Chunk = Chunk { rows: [0; CHUNK_SIZE as usize] };
+
+    pub fn get(&self, local_x: usize, local_y: usize) -> bool {
+        self.rows[local_y] >> local_x & 1 == 1
+    }
+
+    /// Set a cell, returning true if its state changed.
+    pub fn set(&mut self, local_x: usize, local_y: usize, alive: bool) -> bool {
+        let before = self.rows[local_y];
+        if alive {
+            self.rows[local_y] |= 1 << local_x;
+        } else {
+            self.rows[local_y] &= !(1 << local_x);
+        }
+        before != self.rows[local_y]
+    }
+
+    pub fn population(&self) -> u64 {
+        self.rows.iter().map(|row| row.count_ones() as u64).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.iter().all(|row| *row == 0)
+    }
+
+    pub fn rows(&self) -> &[u64; CHUNK_SIZE as usize] {
+        &self.rows
+    }
+}
+
+/// Summary of how a grid is laid out in memory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChunkStats {
+    pub chunk_count: usize,
+    pub live_cells: u64,
+    pub memory_bytes: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChunkedGrid {
+    chunks: HashMap<(i32, i32), Chunk>,
+    population: u64,
+    /// Generations decay state (2 and up) of every dying cell.
+    decaying: HashMap<(i32, i32), u8>,
+    /// Color of every live cell whose color is not 0.
+    colors: HashMap<(i32, i32), u8>,
+}
+
+/// Split a world coordinate into its chunk key and local offset.
+fn split(x: i32, y: i32) -> ((i32, i32), (usize, usize)) {
+    (
+        (x >> CHUNK_SHIFT, y >> CHUNK_SHIFT),
+        ((x & CHUNK_MASK) as usize, (y & CHUNK_MASK) as usize),
+    )
+}
+
+impl ChunkedGrid {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_alive(&self, x: i32, y: i32) -> bool {
+        let (key, (lx, ly)) = split(x, y);
+        self.chunks.get(&key).is_some_and(|chunk| chunk.get(lx, ly))
+    }
+
+    /// Set a cell, returning true if its state changed. Chunks are allocated on
+    /// first birth and released once their last cell dies. Any decay state the
+    /// cell had is dropped, as is its color if it dies.
+    pub fn set(&mut self, x: i32, y: i32, alive: bool) -> bool {
+        let (key, (lx, ly)) = split(x, y);
+        let was_decaying = !self.decaying.is_empty() && self.decaying.remove(&(x, y)).is_some();
+
+        if !alive {
+            if !self.colors.is_empty() {
+                self.colors.remove(&(x, y));
+            }
+            let Some(chunk) = self.chunks.get_mut(&key) else {
+                return was_decaying;
+            };
+            let changed = chunk.set(lx, ly, false);
+            if changed {
+                self.population -= 1;
+                if chunk.is_empty() {
+                    self.chunks.remove(&key);
+                }
+            }
+            return changed || was_decaying;
+        }
+
+        let changed = self.chunks.entry(key).or_insert(Chunk::EMPTY).set(lx, ly, true);
+        if changed {
+            self.population += 1;
+        }
+        changed
+    }
+
+    /// 0 for dead, 1 for alive, or the decay state of a dying cell.
+    pub fn state(&self, x: i32, y: i32) -> u8 {
+        if self.is_alive(x, y) {
+            return 1;
+        }
+        self.decaying.get(&(x, y)).copied().unwrap_or(0)
+    }
+
+    /// Set a cell to dead (0), alive (1) or a decay state.
+    pub fn set_state(&mut self, x: i32, y: i32, state: u8) {
+        self.set(x, y, state == 1);
+        if state >= 2 {
+            self.decaying.insert((x, y), state);
+        }
+    }
+
+    /// Every dying cell and its decay state.
+    pub fn iter_decaying(&self) -> impl Iterator<Item = ((i32, i32), u8)> + '_ {
+        self.decaying.iter().map(|(&cell, &state)| (cell, state))
+    }
+
+    pub fn decaying_count(&self) -> usize {
+        self.decaying.len()
+    }
+
+    /// Color of a live cell under a multi-color rule; 0 for dead cells.
+    pub fn color(&self, x: i32, y: i32) -> u8 {
+        self.colors.get(&(x, y)).copied().unwrap_or(0)
+    }
+
+    /// Color a live cell; dead cells are left alone.
+    pub fn set_color(&mut self, x: i32, y: i32, color: u8) {
+        if color == 0 {
+            self.colors.remove(&(x, y));
+        } else if self.is_alive(x, y) {
+            self.colors.insert((x, y), color);
+        }
+    }
+
+    /// Every live cell with a color other than 0, and its color.
+    pub fn iter_colored(&self) -> impl Iterator<Item = ((i32, i32), u8)> + '_ {
+        self.colors.iter().map(|(&cell, &color)| (cell, color))
+    }
+
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.population = 0;
+        self.decaying.clear();
+        self.colors.clear();
+    }
+
+    pub fn population(&self) -> u64 {
+        self.population
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn stats(&self) -> ChunkStats {
+        let per_chunk = std::mem::size_of::<Chunk>() + std::mem::size_of::<(i32, i32)>();
+        let per_decaying = std::mem::size_of::<((i32, i32), u8)>();
+        ChunkStats {
+            chunk_count: self.chunks.len(),
+            live_cells: self.population,
+            memory_bytes: (self.chunks.capacity() * per_chunk
+                + (self.decaying.capacity() + self.colors.capacity()) * per_decaying) as u64,
+        }
+    }
+
+    /// Iterate over the coordinates of every live cell, chunk by chunk.
+    pub fn iter_live(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.chunks.iter().flat_map(|(&(cx, cy), chunk)| {
+            chunk.rows.iter().enumerate().flat_map(move |(ly, &row)| {
+                let mut bits = row;
+                std::iter::from_fn(move || {
+                    if bits == 0 {
+                        return None;
+                    }
+                    let lx = bits.trailing_zeros() as i32;
+                    bits &= bits - 1;
+                    Some(((cx << CHUNK_SHIFT) + lx, (cy << CHUNK_SHIFT) + ly as i32))
+                })
+            })
+        })
+    }
+
+    pub fn live_cells(&self) -> Vec<(i32, i32)> {
+        self.iter_live().collect()
+    }
+
+    /// Cells alive here but not in `other`, sorted.
+    pub fn difference(&self, other: &ChunkedGrid) -> Vec<(i32, i32)> {
+        let mut cells: Vec<_> = self.iter_live().filter(|&(x, y)| !other.is_alive(x, y)).collect();
+        cells.sort_unstable();
+        cells
+    }
+
+    /// The top-left and bottom-right live cells' corners, inclusive, found
+    /// from each chunk's occupied rows and columns without visiting cells.
+    pub fn bounding_box(&self) -> Option<((i32, i32), (i32, i32))> {
+        let mut bounds: Option<((i32, i32), (i32, i32))> = None;
+        for (&(cx, cy), chunk) in &self.chunks {
+            let columns = chunk.rows.iter().fold(0, |columns, row| columns | row);
+            let (Some(first), Some(last)) = (chunk.rows.iter().position(|row| *row != 0), chunk.rows.iter().rposition(|row| *row != 0)) else {
+                continue;
+            };
+            let (base_x, base_y) = (cx << CHUNK_SHIFT, cy << CHUNK_SHIFT);
+            let min = (base_x + columns.trailing_zeros() as i32, base_y + first as i32);
+            let max = (base_x + LAST_ROW as i32 - columns.leading_zeros() as i32, base_y + last as i32);
+            bounds = Some(match bounds {
+                None => (min, max),
+                Some((low, high)) => ((low.0.min(min.0), low.1.min(min.1)), (high.0.max(max.0), high.1.max(max.1))),
+            });
+        }
+        bounds
+    }
+
+    pub fn neighbor_count(&self, x: i32, y: i32) -> u8 {
+        let mut count = 0;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if (dx != 0 || dy != 0) && self.is_alive(x + dx, y + dy) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// Kill every cell in `[x0, x1) x [y0, y1)`, with `x0` and `y0` not
+    /// negative, returning how many live cells died. Decay states and colors
+    /// there are dropped.
+    pub fn clear_rect(&mut self, (x0, y0): (i32, i32), (x1, y1): (i32, i32)) -> u64 {
+        let mut killed = 0;
+        self.chunks.retain(|&(cx, cy), chunk| {
+            let base_x = cx << CHUNK_SHIFT;
+            let columns = span_mask(base_x, x1) & !span_mask(base_x, x0);
+            let base_y = cy << CHUNK_SHIFT;
+            if columns == 0 || base_y >= y1 || base_y + CHUNK_SIZE <= y0 {
+                return true;
+            }
+            let mut kept = false;
+            for (ly, row) in chunk.rows.iter_mut().enumerate() {
+                let y = base_y + ly as i32;
+                if y >= y0 && y < y1 {
+                    killed += (*row & columns).count_ones() as u64;
+                    *row &= !columns;
+                }
+                kept |= *row != 0;
+            }
+            kept
+        });
+        self.population -= killed;
+        let outside = |&(x, y): &(i32, i32), _: &mut u8| x < x0 || x >= x1 || y < y0 || y >= y1;
+        self.decaying.retain(outside);
+        self.colors.retain(outside);
+        killed
+    }
+
+    /// Bring every cell in `[x0, x1) x [y0, y1)` to life, with `x0` and `y0`
+    /// not negative, returning how many were born. Decaying cells there
+    /// come back to life.
+    pub fn fill_rect(&mut self, (x0, y0): (i32, i32), (x1, y1): (i32, i32)) -> u64 {
+        if x0 >= x1 || y0 >= y1 {
+            return 0;
+        }
+        let mut born = 0;
+        for cy in (y0 >> CHUNK_SHIFT)..=((y1 - 1) >> CHUNK_SHIFT) {
+            for cx in (x0 >> CHUNK_SHIFT)..=((x1 - 1) >> CHUNK_SHIFT) {
+                let base_x = cx << CHUNK_SHIFT;
+                let columns = span_mask(base_x, x1) & !span_mask(base_x, x0);
+                let base_y = cy << CHUNK_SHIFT;
+                let chunk = self.chunks.entry((cx, cy)).or_insert(Chunk::EMPTY);
+                for (ly, row) in chunk.rows.iter_mut().enumerate() {
+                    let y = base_y + ly as i32;
+                    if y >= y0 && y < y1 {
+                        born += (columns & !*row).count_ones() as u64;
+                        *row |= columns;
+                    }
+                }
+            }
+        }
+        self.population += born;
+        self.decaying.retain(|&(x, y), _| x < x0 || x >= x1 || y < y0 || y >= y1);
+        born
+    }
+
+    /// Kill every cell outside `[0, width) x [0, height)`.
+    pub fn retain_rect(&mut self, width: i32, height: i32) {
+        let mut population = 0;
+        self.chunks.retain(|&(cx, cy), chunk| {
+            let columns = span_mask(cx << CHUNK_SHIFT, width);
+            let (base_y, mut kept) = (cy << CHUNK_SHIFT, false);
+            for (ly, row) in chunk.rows.iter_mut().enumerate() {
+                let y = base_y + ly as i32;
+                *row &= if y >= 0 && y < height { columns } else { 0 };
+                kept |= *row != 0;
+                population += row.count_ones() as u64;
+            }
+            kept
+        });
+        self.population = population;
+        let inside = |&(x, y): &(i32, i32), _: &mut u8| x >= 0 && x < width && y >= 0 && y < height;
+        self.decaying.retain(inside);
+        self.colors.retain(inside);
+    }
+
+    /// Compute the next generation under Conway's B3/S23 rules.
+    ///
+    /// Only chunks holding live cells, plus neighbors those cells touch across a
+    /// chunk edge, are visited. With `parallel` set the chunks are processed on
+    /// the current rayon pool; builds without the server, which have no
+    /// rayon, always step on one thread.
+    pub fn step(&self, parallel: bool) -> ChunkedGrid {
+        self.step_rule(&Rule::conway(), parallel)
+    }
+
+    /// Compute the next generation under `rule`.
+    ///
+    /// Radius-1 rules step whole chunk rows at a time like [`ChunkedGrid::step`].
+    /// Larger neighborhoods fall back to tallying each live cell's contribution
+    /// to its neighbors, and rule tables and registered automata are looked
+    /// up cell by cell; all run on one thread whatever `parallel` says.
+    pub fn step_rule(&self, rule: &Rule, parallel: bool) -> ChunkedGrid {
+        if let Some(table) = rule.table() {
+            return table.step(self);
+        }
+        if let Some(automaton) = rule.automaton() {
+            return step_transitions(self, neighbor_offsets(automaton.neighborhood()), |cells| automaton.next_state(cells));
+        }
+        let mut next = self.step_live(rule, parallel);
+        if rule.states() > 2 {
+            self.decay_into(&mut next, rule.states());
+        }
+        if rule.colors() > 1 {
+            self.color_into(&mut next, rule);
+        }
+        next
+    }
+
+    /// Color the cells of a multi-color step: survivors keep their color and
+    /// each newborn cell takes the color its parents decide on.
+    fn color_into(&self, next: &mut ChunkedGrid, rule: &Rule) {
+        let offsets = rule.offsets();
+        let mut parents = vec![0; rule.colors() as usize];
+        let colors = next.iter_live().filter_map(|(x, y)| {
+            let color = if self.is_alive(x, y) {
+                self.color(x, y)
+            } else {
+                parents.fill(0);
+                for (dx, dy) in &offsets {
+                    if self.is_alive(x + dx, y + dy) {
+                        parents[self.color(x + dx, y + dy) as usize] += 1;
+                    }
+                }
+                rule.birth_color(&parents)
+            };
+            (color != 0).then_some(((x, y), color))
+        }).collect();
+        next.colors = colors;
+    }
+
+    /// Apply Generations decay on top of a two-state step: cells that were
+    /// dying cannot be born and move one state on, and live cells that did
+    /// not survive start dying.
+    fn decay_into(&self, next: &mut ChunkedGrid, states: u32) {
+        for (&(x, y), &state) in &self.decaying {
+            next.set(x, y, false);
+            if (state as u32) + 1 < states {
+                next.decaying.insert((x, y), state + 1);
+            }
+        }
+        for (x, y) in self.iter_live() {
+            if !next.is_alive(x, y) {
+                next.decaying.insert((x, y), 2);
+            }
+        }
+    }
+
+    /// Which cells are alive next generation, as if `rule` had two states.
+    fn step_live(&self, rule: &Rule, parallel: bool) -> ChunkedGrid {
+        let Some(masks) = rule.small_masks() else {
+            return self.step_counting(rule);
+        };
+        let neighborhood = rule.neighborhood();
+        let candidates = self.candidate_chunks();
+
+        let step = |key| self.step_chunk(key, masks, neighborhood).map(|chunk| (key, chunk));
+        let stepped: Vec<((i32, i32), Chunk)> = match parallel {
+            #[cfg(feature = "server")]
+            true => candidates.into_par_iter().filter_map(step).collect(),
+            _ => candidates.into_iter().filter_map(step).collect(),
+        };
+
+        let population = stepped.iter().map(|(_, chunk)| chunk.population()).sum();
+        ChunkedGrid {
+            chunks: stepped.into_iter().collect(),
+            population,
+            decaying: HashMap::new(),
+            colors: HashMap::new(),
+        }
+    }
+
+    fn step_counting(&self, rule: &Rule) -> ChunkedGrid {
+        let offsets = rule.offsets();
+        let mut counts: HashMap<(i32, i32), u32> = HashMap::new();
+        for (x, y) in self.iter_live() {
+            counts.entry((x, y)).or_default();
+            for (dx, dy) in &offsets {
+                *counts.entry((x + dx, y + dy)).or_default() += 1;
+            }
+        }
+
+        let mut next = ChunkedGrid::new();
+        for ((x, y), count) in counts {
+            let alive = self.is_alive(x, y);
+            let count = count + (alive && rule.include_center()) as u32;
+            if rule.next_state(alive, count) {
+                next.set(x, y, true);
+            }
+        }
+        next
+    }
+
+    /// Every chunk that may hold a live cell in the next generation.
+    fn candidate_chunks(&self) -> Vec<(i32, i32)> {
+        let mut candidates = HashSet::with_capacity(self.chunks.len() * 2);
+
+        for (&(cx, cy), chunk) in &self.chunks {
+            candidates.insert((cx, cy));
+
+            let top = chunk.rows[0];
+            let bottom = chunk.rows[LAST_ROW];
+            let left = chunk.rows.iter().any(|row| row & 1 != 0);
+            let right = chunk.rows.iter().any(|row| row >> 63 != 0);
+
+            let spills = [
+                (0, -1, top != 0),
+                (0, 1, bottom != 0),
+                (-1, 0, left),
+                (1, 0, right),
+                (-1, -1, top & 1 != 0),
+                (1, -1, top >> 63 != 0),
+                (-1, 1, bottom & 1 != 0),
+                (1, 1, bottom >> 63 != 0),
+            ];
+            for (dx, dy, spills_over) in spills {
+                if spills_over {
+                    candidates.insert((cx + dx, cy + dy));
+                }
+            }
+        }
+
+        candidates.into_iter().collect()
+    }
+
+    fn step_chunk(&self, (cx, cy): (i32, i32), (birth, survival): (u16, u16), neighborhood: Neighborhood) -> Option<Chunk> {
+        let chunk_at = |dx: i32, dy: i32| self.chunks.get(&(cx + dx, cy + dy)).unwrap_or(&Chunk::EMPTY);
+        let (north_west, north, north_east) = (chunk_at(-1, -1), chunk_at(0, -1), chunk_at(1, -1));
+        let (west, center, east) = (chunk_at(-1, 0), chunk_at(0, 0), chunk_at(1, 0));
+        let (south_west, south, south_east) = (chunk_at(-1, 1), chunk_at(0, 1), chunk_at(1, 1));
+
+        // Row `y` of the 3-chunk-wide strip, as (west word, center word, east word)
+        let row = |y: i32| -> (u64, u64, u64) {
+            if y < 0 {
+                (north_west.rows[LAST_ROW], north.rows[LAST_ROW], north_east.rows[LAST_ROW])
+            } else if y > LAST_ROW as i32 {
+                (south_west.rows[0], south.rows[0], south_east.rows[0])
+            } else {
+                let y = y as usize;
+                (west.rows[y], center.rows[y], east.rows[y])
+            }
+        };
+
+        let mut next = Chunk::EMPTY;
+        for y in 0..CHUNK_SIZE {
+            let (above_w, above, above_e) = row(y - 1);
+            let (here_w, here, here_e) = row(y);
+            let (below_w, below, below_e) = row(y + 1);
+
+            let planes = match neighborhood {
+                Neighborhood::Moore => count_planes(&[
+                    shift_west(above, above_w), above, shift_east(above, above_e),
+                    shift_west(here, here_w), shift_east(here, here_e),
+                    shift_west(below, below_w), below, shift_east(below, below_e),
+                ]),
+                Neighborhood::VonNeumann => {
+                    count_planes(&[above, shift_west(here, here_w), shift_east(here, here_e), below])
+                }
+                // Axial coordinates: the north-east and south-west corners are not neighbors
+                Neighborhood::Hexagonal => count_planes(&[
+                    shift_west(above, above_w), above,
+                    shift_west(here, here_w), shift_east(here, here_e),
+                    below, shift_east(below, below_e),
+                ]),
+            };
+
+            next.rows[y as usize] = apply_rule(here, &planes, birth, survival);
+        }
+
+        (!next.is_empty()).then_some(next)
+    }
+}
+
+/// Align each cell's west neighbor with the cell, pulling bit 63 of the chunk to the west.
+fn shift_west(row: u64, west: u64) -> u64 {
+    (row << 1) | (west >> 63)
+}
+
+/// Align each cell's east neighbor with the cell, pulling bit 0 of the chunk to the east.
+fn shift_east(row: u64, east: u64) -> u64 {
+    (row >> 1) | (east << 63)
+}
+
+/// Bit-sliced sum of the neighbor words: bit `x` of `planes[i]` is bit `i` of
+/// the live-neighbor count of column `x`.
+fn count_planes(neighbors: &[u64]) -> [u64; 4] {
+    let mut planes = [0u64; 4];
+    for &neighbor in neighbors {
+        let mut carry = neighbor;
+        for plane in planes.iter_mut() {
+            if carry == 0 {
+                break;
+            }
+            let overflow = *plane & carry;
+            *plane ^= carry;
+            carry = overflow;
+        }
+    }
+    planes
+}
+
+/// Mask of columns whose count in `planes` equals `count`.
+fn count_equals(planes: &[u64; 4], count: u8) -> u64 {
+    planes.iter().enumerate().fold(u64::MAX, |mask, (bit, plane)| {
+        mask & if count >> bit & 1 == 1 { *plane } else { !*plane }
+    })
+}
+
+/// Next state of each column given its neighbor counts: bit `n` of `birth`
+/// and `survival` is set when a dead or live cell with `n` neighbors lives on.
+fn apply_rule(alive: u64, planes: &[u64; 4], birth: u16, survival: u16) -> u64 {
+    (0..=8u8).fold(0, |next, count| {
+        let mask = match (birth >> count & 1 == 1, survival >> count & 1 == 1) {
+            (false, false) => return next,
+            (true, true) => u64::MAX,
+            (true, false) => !alive,
+            (false, true) => alive,
+        };
+        next | (count_equals(planes, count) & mask)
+    })
+}
+
+/// Bits of a chunk row whose world column, starting at `base`, lies in `[0, limit)`.
+fn span_mask(base: i32, limit: i32) -> u64 {
+    let lo = (-base).clamp(0, CHUNK_SIZE);
+    let hi = (limit - base).clamp(0, CHUNK_SIZE);
+    if lo >= hi {
+        return 0;
+    }
+    let width = (hi - lo) as u32;
+    let bits = if width == 64 { u64::MAX } else { (1u64 << width) - 1 };
+    bits << lo
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid_from(cells: &[(i32, i32)]) -> ChunkedGrid {
+        let mut grid = ChunkedGrid::new();
+        for (x, y) in cells {
+            grid.set(*x, *y, true);
+        }
+        grid
+    }
+
+    fn sorted(mut cells: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn test_difference_lists_cells_missing_from_other() {
+        let a = grid_from(&[(0, 0), (1, 1), (200, -5)]);
+        let b = grid_from(&[(1, 1), (2, 2)]);
+        assert_eq!(a.difference(&b), vec![(0, 0), (200, -5)]);
+        assert_eq!(b.difference(&a), vec![(2, 2)]);
+        assert!(a.difference(&a).is_empty());
+    }
+
+    #[test]
+    fn test_bounding_box_spans_chunks() {
+        assert_eq!(ChunkedGrid::new().bounding_box(), None);
+        let mut grid = grid_from(&[(3, 70), (200, -5), (-1, 63)]);
+        assert_eq!(grid.bounding_box(), Some(((-1, -5), (200, 70))));
+        grid.set(200, -5, false);
+        assert_eq!(grid.bounding_box(), Some(((-1, 63), (3, 70))));
+    }
+
+    #[test]
+    fn test_set_and_clear_cells_across_chunks() {
+        let mut grid = ChunkedGrid::new();
+        assert!(grid.set(0, 0, true));
+        assert!(!grid.set(0, 0, true));
+        assert!(grid.set(-1, -1, true));
+        assert!(grid.set(130, 5, true));
+
+        assert_eq!(grid.population(), 3);
+        assert_eq!(grid.chunk_count(), 3);
+        assert!(grid.is_alive(-1, -1));
+        assert!(!grid.is_alive(1, 1));
+
+        assert!(grid.set(-1, -1, false));
+        assert_eq!(grid.chunk_count(), 2);
+        assert_eq!(sorted(grid.live_cells()), vec![(0, 0), (130, 5)]);
+    }
+
+    #[test]
+    fn test_blinker_across_chunk_corner() {
+        // Vertical blinker straddling the corner where four chunks meet
+        let grid = grid_from(&[(-1, -1), (-1, 0), (-1, 1)]);
+
+        let next = grid.step(false);
+        assert_eq!(sorted(next.live_cells()), vec![(-2, 0), (-1, 0), (0, 0)]);
+        assert_eq!(next.neighbor_count(-1, 0), 2);
+
+        let back = next.step(false);
+        assert_eq!(sorted(back.live_cells()), vec![(-1, -1), (-1, 0), (-1, 1)]);
+    }
+
+    #[test]
+    fn test_glider_crosses_chunk_boundary() {
+        let mut grid = grid_from(&[(61, 60), (62, 61), (60, 62), (61, 62), (62, 62)]);
+
+        // A glider moves one cell diagonally every four generations
+        for _ in 0..16 {
+            grid = grid.step(false);
+        }
+
+        assert_eq!(
+            sorted(grid.live_cells()),
+            sorted(vec![(65, 64), (66, 65), (64, 66), (65, 66), (66, 66)])
+        );
+        assert_eq!(grid.chunk_count(), 1);
+    }
+
+    #[test]
+    fn test_parallel_step_matches_sequential() {
+        let cells: Vec<(i32, i32)> = (0..200)
+            .flat_map(|i| [(i * 7 % 300, i * 13 % 300), (i * 7 % 300 + 1, i * 13 % 300), (i * 7 % 300 + 2, i * 13 % 300)])
+            .collect();
+        let grid = grid_from(&cells);
+
+        assert_eq!(grid.step(true), grid.step(false));
+    }
+
+    #[test]
+    fn test_step_rule_matches_cell_by_cell_counts() {
+        // A soup straddling chunk edges, including negative coordinates
+        let cells: Vec<(i32, i32)> = (0..400)
+            .map(|i| ((i * 37 % 41) - 20 + 64 * (i % 2), (i * 53 % 43) - 20))
+            .collect();
+        let grid = grid_from(&cells);
+
+        for rule in ["B3/S23", "B36/S23", "B2/S013V", "B2/S34H", "R2,C0,M1,S3..6,B4..5,NM", "R3,C0,M0,S2..5,B3..4,NN", "R2,C0,M0,S2..7,B4..5,NH"] {
+            let rule: Rule = rule.parse().unwrap();
+            let range = rule.range() as i32;
+            let mut expected = Vec::new();
+            for y in -20 - range..=23 + range {
+                for x in -20 - range..=84 + range {
+                    if rule.next_state(grid.is_alive(x, y), rule.neighbor_count(&grid, x, y)) {
+                        expected.push((x, y));
+                    }
+                }
+            }
+            assert_eq!(sorted(grid.step_rule(&rule, false).live_cells()), sorted(expected), "rule {}", rule);
+        }
+    }
+
+    #[test]
+    fn test_generations_cells_decay_before_dying() {
+        let brians_brain: Rule = "B2/S/C3".parse().unwrap();
+        let grid = grid_from(&[(0, 0), (1, 0)]);
+
+        let first = grid.step_rule(&brians_brain, false);
+        assert_eq!(sorted(first.live_cells()), vec![(0, -1), (0, 1), (1, -1), (1, 1)]);
+        assert_eq!((first.state(0, 0), first.state(1, 0), first.decaying_count()), (2, 2, 2));
+
+        // The dying pair blocks births between the new cells, then is gone
+        let second = first.step_rule(&brians_brain, false);
+        assert_eq!(sorted(second.live_cells()), vec![(-1, 0), (0, -2), (0, 2), (1, -2), (1, 2), (2, 0)]);
+        assert_eq!(second.state(0, 0), 0);
+        assert_eq!(second.state(0, 1), 2);
+        assert_eq!(second.decaying_count(), 4);
+    }
+
+    #[test]
+    fn test_colors_pass_to_survivors_and_newborns() {
+        let immigration: Rule = "Immigration".parse().unwrap();
+        // A blinker with two cells of color 1 is born into color 1 sideways
+        let mut grid = grid_from(&[(0, 1), (1, 1), (2, 1)]);
+        grid.set_color(0, 1, 1);
+        grid.set_color(1, 1, 1);
+
+        let next = grid.step_rule(&immigration, false);
+        assert_eq!(sorted(next.live_cells()), vec![(1, 0), (1, 1), (1, 2)]);
+        assert_eq!((next.color(1, 0), next.color(1, 1), next.color(1, 2)), (1, 1, 1));
+
+        // Under QuadLife three different parents give the fourth color
+        let quad_life: Rule = "QuadLife".parse().unwrap();
+        let mut grid = grid_from(&[(0, 0), (2, 0), (1, 2)]);
+        grid.set_color(2, 0, 1);
+        grid.set_color(1, 2, 2);
+        assert_eq!(grid.step_rule(&quad_life, false).color(1, 1), 3);
+
+        // Dead cells have no color
+        let mut grid = grid_from(&[(5, 5)]);
+        grid.set_color(5, 5, 1);
+        grid.set(5, 5, false);
+        grid.set_color(6, 6, 1);
+        assert_eq!(grid.iter_colored().count(), 0);
+    }
+
+    #[test]
+    fn test_set_state_and_clip_decaying_cells() {
+        let mut grid = ChunkedGrid::new();
+        grid.set_state(3, 3, 4);
+        grid.set_state(20, 3, 2);
+        assert_eq!((grid.state(3, 3), grid.population()), (4, 0));
+
+        grid.retain_rect(10, 10);
+        assert_eq!(grid.iter_decaying().collect::<Vec<_>>(), vec![((3, 3), 4)]);
+
+        // Setting a dying cell alive or dead drops its decay state
+        assert!(grid.set(3, 3, true));
+        assert_eq!((grid.state(3, 3), grid.decaying_count()), (1, 0));
+    }
+
+    #[test]
+    fn test_retain_rect_clips_cells() {
+        let mut grid = grid_from(&[(-1, 0), (0, 0), (9, 9), (10, 9), (200, 200)]);
+        grid.retain_rect(10, 10);
+
+        assert_eq!(sorted(grid.live_cells()), vec![(0, 0), (9, 9)]);
+        assert_eq!(grid.population(), 2);
+        assert_eq!(grid.chunk_count(), 1);
+    }
+
+    #[test]
+    fn test_stats_report_chunks() {
+        let grid = grid_from(&[(0, 0), (100, 100)]);
+        let stats = grid.stats();
+
+        assert_eq!(stats.chunk_count, 2);
+        assert_eq!(stats.live_cells, 2);
+        assert!(stats.memory_bytes >= 2 * std::mem::size_of::<Chunk>() as u64);
+    }
+}
@@ -0,0 +1,164 @@
+//! A/B experiments on a simulation.
+//!
+//! An experiment forks a simulation at its current generation into a control
+//! copy and one copy per variant, perturbs each variant in its own way and
+//! steps them all side by side. The simulation itself is left as it was, so
+//! an experiment can be run again from the same state with other variants.
+//! Copies stop early once their grid dies out or repeats, as batch runs do.
+
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+
+use crate::resources::{random_cells, Rule, SeededRng, SimulationData, StagnationWatch, StopReason};
+
+/// Generations a copy steps between checks for cancellation.
+const CANCEL_CHECK_GENERATIONS: u64 = 256;
+
+/// Random cells added over a square at the grid's center.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Soup {
+    pub seed: u64,
+    pub size: i32,
+    /// Chance of each cell in the square being made alive.
+    pub density: f64,
+}
+
+/// How one variant differs from the control. Every part is optional; a
+/// variant with none steps as the control does.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Perturbation {
+    /// Rule the variant switches to, dropping any scheduled rule changes.
+    pub rule: Option<Rule>,
+    pub soup: Option<Soup>,
+    /// Cells made alive, already placed on the grid.
+    pub pattern: Vec<(i32, i32)>,
+}
+
+/// How one copy of an experiment ran.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExperimentOutcome {
+    /// Generations stepped, fewer than asked when the copy stopped early.
+    pub generations: u64,
+    /// Live cells once perturbed, before the first step.
+    pub initial_population: u64,
+    pub final_population: u64,
+    pub peak_population: u64,
+    /// Live cells averaged over every generation stepped.
+    pub mean_population: f64,
+    /// Set when the grid died out or started repeating.
+    pub stop: Option<StopReason>,
+    pub elapsed: Duration,
+}
+
+/// Step a control copy of `source` and one copy per perturbation for
+/// `generations` generations in parallel, returning the control's outcome
+/// first; `None` if `cancelled` reported true before they finished.
+pub fn run_experiment(source: &SimulationData, perturbations: &[Perturbation], generations: u64, cancelled: &(dyn Fn() -> bool + Sync)) -> Option<Vec<ExperimentOutcome>> {
+    let control = Perturbation::default();
+    std::iter::once(&control).chain(perturbations)
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|perturbation| run_variant(source, perturbation, generations, cancelled))
+        .collect()
+}
+
+fn run_variant(source: &SimulationData, perturbation: &Perturbation, generations: u64, cancelled: &dyn Fn() -> bool) -> Option<ExperimentOutcome> {
+    let started = Instant::now();
+    let mut simulation = perturbed(source, perturbation);
+    let initial_population = simulation.cells.population();
+    
+    let (mut peak_population, mut population_sum) = (initial_population, 0u64);
+    let mut stop = None;
+    let mut stepped = 0;
+    while stepped < generations {
+        if stepped.is_multiple_of(CANCEL_CHECK_GENERATIONS) && cancelled() {
+            return None;
+        }
+        let pause = simulation.step_watched();
+        stepped += 1;
+        let population = simulation.cells.population();
+        peak_population = peak_population.max(population);
+        population_sum = population_sum.saturating_add(population);
+        if let Some(pause) = pause {
+            stop = Some(pause.reason);
+            break;
+        }
+    }
+    
+    Some(ExperimentOutcome {
+        generations: stepped,
+        initial_population,
+        final_population: simulation.cells.population(),
+        peak_population,
+        mean_population: if stepped == 0 { initial_population as f64 } else { population_sum as f64 / stepped as f64 },
+        stop,
+        elapsed: started.elapsed(),
+    })
+}
+
+/// A copy of `source` with `perturbation` applied, watched for stagnation
+/// afresh.
+fn perturbed(source: &SimulationData, perturbation: &Perturbation) -> SimulationData {
+    let mut simulation = source.clone();
+    simulation.auto_pause = None;
+    simulation.stagnation = StagnationWatch::default();
+    if let Some(rule) = &perturbation.rule {
+        simulation.rule_changes.clear();
+        simulation.set_rule(rule.clone());
+    }
+    if let Some(soup) = &perturbation.soup {
+        let (dx, dy) = ((simulation.width - soup.size) / 2, (simulation.height - soup.size) / 2);
+        for (x, y) in random_cells(&mut SeededRng::new(soup.seed), soup.size, soup.size, soup.density) {
+            if simulation.in_bounds(x + dx, y + dy) {
+                simulation.cells.set(x + dx, y + dy, true);
+            }
+        }
+    }
+    for &(x, y) in &perturbation.pattern {
+        if simulation.in_bounds(x, y) {
+            simulation.cells.set(x, y, true);
+        }
+    }
+    simulation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::scratch_simulation;
+    
+    fn blinker() -> SimulationData {
+        let mut simulation = scratch_simulation(32, 32);
+        simulation.set_cells(&[(10, 10), (11, 10), (12, 10)]);
+        simulation
+    }
+    
+    #[test]
+    fn test_control_runs_unperturbed() {
+        let source = blinker();
+        let outcomes = run_experiment(&source, &[], 10, &|| false).unwrap();
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].initial_population, 3);
+        assert_eq!(outcomes[0].stop, Some(StopReason::Cycle { period: 2 }));
+        assert_eq!(source.generation, 0);
+    }
+    
+    #[test]
+    fn test_variants_are_perturbed_independently() {
+        let source = blinker();
+        let dies = Perturbation { rule: Some("B3/S4".parse::<Rule>().unwrap()), ..Perturbation::default() };
+        let grows = Perturbation { pattern: vec![(20, 20), (21, 20), (20, 21), (21, 21)], ..Perturbation::default() };
+        let outcomes = run_experiment(&source, &[dies, grows], 10, &|| false).unwrap();
+        assert_eq!(outcomes[1].stop, Some(StopReason::Extinct));
+        assert_eq!(outcomes[1].final_population, 0);
+        assert_eq!(outcomes[2].initial_population, 7);
+        assert_eq!(outcomes[2].final_population, 7);
+        assert_eq!(source.cells.population(), 3);
+    }
+    
+    #[test]
+    fn test_cancelled_experiments_return_nothing() {
+        assert!(run_experiment(&blinker(), &[Perturbation::default()], 10, &|| true).is_none());
+    }
+}
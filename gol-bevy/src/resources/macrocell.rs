@@ -0,0 +1,412 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::resources::Rule;
+
+/// Error raised while decoding Golly macrocell text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MacrocellError(pub String);
+
+impl fmt::Display for MacrocellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for MacrocellError {}
+
+/// Largest node level accepted; keeps every coordinate inside an `i64`.
+const MAX_LEVEL: u32 = 62;
+
+/// Live-cell bounds `(min_x, min_y, max_x, max_y)`, inclusive.
+type Bounds = (i64, i64, i64, i64);
+
+#[derive(Debug, Clone)]
+enum Node {
+    /// Level 3: an 8x8 block, one bit per cell, bit `x` of row `y`.
+    Leaf([u8; 8]),
+    /// Level 1: a 2x2 block of cell states (multi-state files).
+    Quad([bool; 4]),
+    /// Level 2 or 4 and up; children are nw, ne, sw, se node numbers, 0 = empty.
+    Inner { level: u32, children: [usize; 4] },
+}
+
+/// A decoded macrocell pattern: a quadtree whose nodes may be shared.
+///
+/// Golly's macrocell format names every distinct square once and builds larger
+/// squares out of references to smaller ones, so a pattern spanning billions
+/// of cells can fit in a few kilobytes. Expanding all of it is rarely what a
+/// caller wants, so cells are only produced for a requested window.
+#[derive(Debug, Clone, Default)]
+pub struct Macrocell {
+    /// Node `n` (1-based in the file) lives at index `n - 1`.
+    nodes: Vec<Node>,
+    /// Live-cell bounds of each node relative to its own top-left corner.
+    bounds: Vec<Option<Bounds>>,
+}
+
+impl Macrocell {
+    /// Bounds of the live cells, relative to the root square's top-left corner.
+    fn root_bounds(&self) -> Option<Bounds> {
+        self.bounds.last().copied().flatten()
+    }
+
+    /// Width and height of the live cells' bounding box.
+    pub fn size(&self) -> Option<(i64, i64)> {
+        self.root_bounds().map(|(min_x, min_y, max_x, max_y)| (max_x - min_x + 1, max_y - min_y + 1))
+    }
+
+    /// Live cells relative to the top-left corner of the bounding box, like a
+    /// decoded RLE pattern, keeping only those with `x0 <= x < x1` and
+    /// `y0 <= y < y1`. Squares outside the window are never expanded.
+    pub fn cells_within(&self, x0: i32, y0: i32, x1: i32, y1: i32) -> Vec<(i32, i32)> {
+        let mut cells = Vec::new();
+        if let Some((min_x, min_y, _, _)) = self.root_bounds() {
+            let window = (x0 as i64 + min_x, y0 as i64 + min_y, x1 as i64 + min_x, y1 as i64 + min_y);
+            self.expand(self.nodes.len(), 0, 0, window, &mut |x, y| {
+                cells.push(((x - min_x) as i32, (y - min_y) as i32));
+            });
+        }
+        cells
+    }
+
+    fn expand(&self, number: usize, ox: i64, oy: i64, window: Bounds, emit: &mut impl FnMut(i64, i64)) {
+        let Some((min_x, min_y, max_x, max_y)) = number.checked_sub(1).and_then(|index| self.bounds[index]) else {
+            return;
+        };
+        let (x0, y0, x1, y1) = window;
+        if ox + max_x < x0 || ox + min_x >= x1 || oy + max_y < y0 || oy + min_y >= y1 {
+            return;
+        }
+        let mut visit = |x: i64, y: i64| {
+            if x >= x0 && x < x1 && y >= y0 && y < y1 {
+                emit(x, y);
+            }
+        };
+
+        match &self.nodes[number - 1] {
+            Node::Leaf(rows) => {
+                for (y, row) in rows.iter().enumerate() {
+                    for x in (0..8).filter(|x| row & (1 << x) != 0) {
+                        visit(ox + x, oy + y as i64);
+                    }
+                }
+            }
+            Node::Quad(states) => {
+                for (quadrant, _) in states.iter().enumerate().filter(|(_, alive)| **alive) {
+                    visit(ox + (quadrant & 1) as i64, oy + (quadrant >> 1) as i64);
+                }
+            }
+            Node::Inner { level, children } => {
+                let half = 1i64 << (level - 1);
+                for (quadrant, child) in children.iter().enumerate() {
+                    let cx = ox + if quadrant & 1 == 1 { half } else { 0 };
+                    let cy = oy + if quadrant >= 2 { half } else { 0 };
+                    self.expand(*child, cx, cy, window, emit);
+                }
+            }
+        }
+    }
+
+    fn level_of(&self, number: usize) -> Option<u32> {
+        match &self.nodes[number - 1] {
+            Node::Leaf(_) => Some(3),
+            Node::Quad(_) => Some(1),
+            Node::Inner { level, .. } => Some(*level),
+        }
+    }
+
+    fn push(&mut self, node: Node) {
+        let bounds = match &node {
+            Node::Leaf(rows) => rows.iter().enumerate()
+                .flat_map(|(y, row)| (0..8).filter(move |x| row & (1 << x) != 0).map(move |x| (x, y as i64)))
+                .fold(None, |bounds, (x, y)| Some(union(bounds, (x, y, x, y)))),
+            Node::Quad(states) => states.iter().enumerate()
+                .filter(|(_, alive)| **alive)
+                .map(|(quadrant, _)| ((quadrant & 1) as i64, (quadrant >> 1) as i64))
+                .fold(None, |bounds, (x, y)| Some(union(bounds, (x, y, x, y)))),
+            Node::Inner { level, children } => {
+                let half = 1i64 << (level - 1);
+                children.iter().enumerate()
+                    .filter_map(|(quadrant, child)| {
+                        let (min_x, min_y, max_x, max_y) = self.bounds[child.checked_sub(1)?]?;
+                        let dx = if quadrant & 1 == 1 { half } else { 0 };
+                        let dy = if quadrant >= 2 { half } else { 0 };
+                        Some((min_x + dx, min_y + dy, max_x + dx, max_y + dy))
+                    })
+                    .fold(None, |bounds, child| Some(union(bounds, child)))
+            }
+        };
+        self.nodes.push(node);
+        self.bounds.push(bounds);
+    }
+}
+
+fn union(bounds: Option<Bounds>, (min_x, min_y, max_x, max_y): Bounds) -> Bounds {
+    match bounds {
+        Some((a, b, c, d)) => (a.min(min_x), b.min(min_y), c.max(max_x), d.max(max_y)),
+        None => (min_x, min_y, max_x, max_y),
+    }
+}
+
+/// Incremental decoder for Golly macrocell (`.mc`) text.
+///
+/// Like [`RleDecoder`](crate::resources::RleDecoder), text can be fed in
+/// arbitrary blocks. The `[M2]` header and `#` comment lines are skipped. Each
+/// remaining line defines the next node: `.`/`*`/`$` lines are 8x8 leaves and
+/// `level nw ne sw se` lines build a square from earlier nodes. The last node
+/// is the whole pattern.
+#[derive(Debug, Default)]
+pub struct MacrocellDecoder {
+    pattern: Macrocell,
+    partial_line: String,
+}
+
+impl MacrocellDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode every complete line in `block`, holding back a trailing partial line.
+    pub fn feed(&mut self, block: &str) -> Result<(), MacrocellError> {
+        self.partial_line.push_str(block);
+        while let Some(end) = self.partial_line.find('\n') {
+            let line: String = self.partial_line.drain(..=end).collect();
+            self.decode_line(&line)?;
+        }
+        Ok(())
+    }
+
+    /// Decode any final unterminated line and return the pattern.
+    pub fn finish(mut self) -> Result<Macrocell, MacrocellError> {
+        let line = std::mem::take(&mut self.partial_line);
+        self.decode_line(&line)?;
+        Ok(self.pattern)
+    }
+
+    fn decode_line(&mut self, line: &str) -> Result<(), MacrocellError> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+            return Ok(());
+        }
+
+        let node = if line.starts_with(|ch: char| ch.is_ascii_digit()) {
+            self.decode_node(line)?
+        } else {
+            decode_leaf(line)?
+        };
+        self.pattern.push(node);
+        Ok(())
+    }
+
+    fn decode_node(&self, line: &str) -> Result<Node, MacrocellError> {
+        let invalid = || MacrocellError(format!("Invalid node line '{}'", line));
+        let numbers = line.split_whitespace()
+            .map(|field| field.parse::<usize>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let [level, nw, ne, sw, se] = numbers[..] else {
+            return Err(invalid());
+        };
+        let level = u32::try_from(level).map_err(|_| invalid())?;
+
+        if level == 1 {
+            return Ok(Node::Quad([nw != 0, ne != 0, sw != 0, se != 0]));
+        }
+        if level == 3 || level > MAX_LEVEL {
+            return Err(MacrocellError(format!("Unsupported node level {}", level)));
+        }
+
+        let children = [nw, ne, sw, se];
+        for &child in &children {
+            if child > self.pattern.nodes.len() {
+                return Err(MacrocellError(format!("Node {} refers to undefined node {}", self.pattern.nodes.len() + 1, child)));
+            }
+            if child != 0 && self.pattern.level_of(child) != Some(level - 1) {
+                return Err(MacrocellError(format!("Node {} has a child of the wrong level", self.pattern.nodes.len() + 1)));
+            }
+        }
+        Ok(Node::Inner { level, children })
+    }
+}
+
+fn decode_leaf(line: &str) -> Result<Node, MacrocellError> {
+    let mut rows = [0u8; 8];
+    let (mut x, mut y) = (0, 0);
+    for ch in line.chars() {
+        match ch {
+            '.' => x += 1,
+            '*' => {
+                if x >= 8 || y >= 8 {
+                    return Err(MacrocellError(format!("Leaf '{}' is larger than 8x8", line)));
+                }
+                rows[y] |= 1 << x;
+                x += 1;
+            }
+            '$' => {
+                y += 1;
+                x = 0;
+            }
+            other => return Err(MacrocellError(format!("Unexpected character '{}' in macrocell leaf", other))),
+        }
+    }
+    Ok(Node::Leaf(rows))
+}
+
+/// Decode a complete macrocell document.
+pub fn decode_macrocell(text: &str) -> Result<Macrocell, MacrocellError> {
+    let mut decoder = MacrocellDecoder::new();
+    decoder.feed(text)?;
+    decoder.finish()
+}
+
+/// Encode live cells as a macrocell document under `rule`, positioned
+/// relative to their bounding box. Identical squares are written once and shared.
+pub fn encode_macrocell(cells: &[(i32, i32)], rule: &Rule) -> String {
+    let mut text = format!("[M2] (gol-bevy)\n#R {}\n", rule);
+    let Some(min_x) = cells.iter().map(|(x, _)| *x as i64).min() else {
+        return text;
+    };
+    let min_y = cells.iter().map(|(_, y)| *y as i64).min().unwrap();
+
+    let mut blocks: HashMap<(i64, i64), [u8; 8]> = HashMap::new();
+    for &(x, y) in cells {
+        let (x, y) = (x as i64 - min_x, y as i64 - min_y);
+        blocks.entry((x / 8, y / 8)).or_default()[(y % 8) as usize] |= 1 << (x % 8);
+    }
+    let extent = blocks.keys().map(|(bx, by)| (*bx).max(*by) + 1).max().unwrap();
+    let mut level = 3;
+    while (1i64 << (level - 3)) < extent {
+        level += 1;
+    }
+
+    let mut encoder = MacrocellEncoder::default();
+    let keys: Vec<_> = blocks.keys().copied().collect();
+    encoder.node(level, 0, 0, keys, &blocks);
+    for line in encoder.lines {
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text
+}
+
+#[derive(Default)]
+struct MacrocellEncoder {
+    lines: Vec<String>,
+    leaves: HashMap<[u8; 8], usize>,
+    inner: HashMap<(u32, [usize; 4]), usize>,
+}
+
+impl MacrocellEncoder {
+    /// Number of the node covering the square of `level` whose top-left block
+    /// is `(bx, by)`; `keys` are the non-empty blocks inside it.
+    fn node(&mut self, level: u32, bx: i64, by: i64, keys: Vec<(i64, i64)>, blocks: &HashMap<(i64, i64), [u8; 8]>) -> usize {
+        if keys.is_empty() {
+            return 0;
+        }
+        if level == 3 {
+            let rows = blocks[&(bx, by)];
+            return self.intern_leaf(rows);
+        }
+
+        let half = 1i64 << (level - 4);
+        let mut quadrants: [Vec<(i64, i64)>; 4] = Default::default();
+        for (x, y) in keys {
+            let quadrant = usize::from(x >= bx + half) + 2 * usize::from(y >= by + half);
+            quadrants[quadrant].push((x, y));
+        }
+
+        let mut children = [0; 4];
+        for (quadrant, keys) in quadrants.into_iter().enumerate() {
+            let cx = bx + if quadrant & 1 == 1 { half } else { 0 };
+            let cy = by + if quadrant >= 2 { half } else { 0 };
+            children[quadrant] = self.node(level - 1, cx, cy, keys, blocks);
+        }
+
+        if let Some(number) = self.inner.get(&(level, children)) {
+            return *number;
+        }
+        let [nw, ne, sw, se] = children;
+        self.lines.push(format!("{} {} {} {} {}", level, nw, ne, sw, se));
+        self.inner.insert((level, children), self.lines.len());
+        self.lines.len()
+    }
+
+    fn intern_leaf(&mut self, rows: [u8; 8]) -> usize {
+        if let Some(number) = self.leaves.get(&rows) {
+            return *number;
+        }
+        let last_row = rows.iter().rposition(|row| *row != 0).unwrap_or(0);
+        let mut line = String::new();
+        for row in &rows[..=last_row] {
+            let width = 8 - row.leading_zeros() as usize;
+            line.extend((0..width).map(|x| if row & (1 << x) != 0 { '*' } else { '.' }));
+            line.push('$');
+        }
+        self.lines.push(line);
+        self.leaves.insert(rows, self.lines.len());
+        self.lines.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut cells: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+        cells.sort_unstable();
+        cells
+    }
+
+    #[test]
+    fn test_decodes_glider() {
+        // A glider leaf in the north-east quadrant of a 16x16 root
+        let text = "[M2] (golly 4.2)\n#R B3/S23\n$$.*$..*$***$\n4 0 1 0 0\n";
+        let pattern = decode_macrocell(text).unwrap();
+        assert_eq!(pattern.size(), Some((3, 3)));
+        let cells = sorted(pattern.cells_within(0, 0, 100, 100));
+        assert_eq!(cells, vec![(0, 2), (1, 0), (1, 2), (2, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn test_round_trip_shares_repeated_squares() {
+        // Two identical blinkers far apart, both aligned to 8x8 leaves, share one leaf
+        let mut cells = vec![(0, 1), (1, 1), (2, 1)];
+        cells.extend([(504, 305), (505, 305), (506, 305)]);
+        let text = encode_macrocell(&cells, &Rule::conway());
+        assert_eq!(text.lines().filter(|line| line.ends_with('$')).count(), 1);
+
+        let pattern = decode_macrocell(&text).unwrap();
+        assert_eq!(pattern.size(), Some((507, 305)));
+        assert_eq!(sorted(pattern.cells_within(0, 0, 1000, 1000)), vec![(0, 0), (1, 0), (2, 0), (504, 304), (505, 304), (506, 304)]);
+    }
+
+    #[test]
+    fn test_window_limits_expansion() {
+        let text = encode_macrocell(&[(0, 0), (100, 100), (200, 200)], &Rule::conway());
+        let pattern = decode_macrocell(&text).unwrap();
+        assert_eq!(pattern.cells_within(50, 50, 150, 150), vec![(100, 100)]);
+    }
+
+    #[test]
+    fn test_blocks_may_split_anywhere() {
+        let text = encode_macrocell(&[(3, 4), (9, 2), (17, 30), (40, 1)], &Rule::conway());
+        let expected = sorted(decode_macrocell(&text).unwrap().cells_within(0, 0, 64, 64));
+
+        for split in 0..text.len() {
+            let mut decoder = MacrocellDecoder::new();
+            decoder.feed(&text[..split]).unwrap();
+            decoder.feed(&text[split..]).unwrap();
+            let cells = sorted(decoder.finish().unwrap().cells_within(0, 0, 64, 64));
+            assert_eq!(cells, expected, "split at {}", split);
+        }
+    }
+
+    #[test]
+    fn test_rejects_invalid_nodes() {
+        assert!(decode_macrocell("4 1 0 0 0\n").is_err());
+        assert!(decode_macrocell("**$\n5 1 0 0 0\n").is_err());
+        assert!(decode_macrocell("*********$\n").is_err());
+        assert!(decode_macrocell("4 0 0 0\n").is_err());
+        assert!(decode_macrocell("").unwrap().size().is_none());
+    }
+}
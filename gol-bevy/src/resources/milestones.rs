@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+/// Something worth telling a webhook about, reached while stepping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Milestone {
+    /// The simulation reached its generation limit.
+    Completed,
+    /// No live cells are left, nor turmites to draw new ones.
+    Extinct,
+    /// An unattended run found the grid repeating every `period` generations.
+    Stabilized { period: u64 },
+    /// The generation passed a multiple of a webhook's `every_generations`.
+    Generation,
+}
+
+impl Milestone {
+    /// Name used in webhook payloads.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Milestone::Completed => "completed",
+            Milestone::Extinct => "extinct",
+            Milestone::Stabilized { .. } => "stabilized",
+            Milestone::Generation => "generation",
+        }
+    }
+}
+
+/// An `http://` URL POSTed a JSON summary when a simulation reaches the
+/// milestones it asks for.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MilestoneWebhook {
+    pub url: String,
+    pub on_completion: bool,
+    pub on_extinction: bool,
+    pub on_stabilization: bool,
+    /// Also notify each time the generation passes a multiple of this; `0`
+    /// never does.
+    pub every_generations: u64,
+}
+
+impl MilestoneWebhook {
+    /// A webhook told of every milestone, and of every `every_generations`
+    /// generations when that is not `0`.
+    pub fn all_milestones(url: &str, every_generations: u64) -> Self {
+        Self {
+            url: url.to_string(),
+            on_completion: true,
+            on_extinction: true,
+            on_stabilization: true,
+            every_generations,
+        }
+    }
+    
+    pub fn wants(&self, milestone: Milestone) -> bool {
+        match milestone {
+            Milestone::Completed => self.on_completion,
+            Milestone::Extinct => self.on_extinction,
+            Milestone::Stabilized { .. } => self.on_stabilization,
+            Milestone::Generation => self.every_generations > 0,
+        }
+    }
+    
+    /// Whether stepping from generation `from` to `to` passed a multiple of
+    /// `every_generations`.
+    pub fn passes_interval(&self, from: u64, to: u64) -> bool {
+        self.every_generations > 0 && from / self.every_generations != to / self.every_generations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_interval_counts_multiples_passed() {
+        let webhook = MilestoneWebhook { every_generations: 100, ..Default::default() };
+        assert!(webhook.passes_interval(99, 100));
+        assert!(webhook.passes_interval(50, 350));
+        assert!(!webhook.passes_interval(100, 199));
+        assert!(!MilestoneWebhook::default().passes_interval(0, 1000));
+    }
+    
+    #[test]
+    fn test_webhook_wants_chosen_milestones() {
+        let webhook = MilestoneWebhook { on_extinction: true, ..Default::default() };
+        assert!(webhook.wants(Milestone::Extinct));
+        assert!(!webhook.wants(Milestone::Completed));
+        assert!(!webhook.wants(Milestone::Generation));
+        assert!(MilestoneWebhook::all_milestones("http://hooks/run", 0).wants(Milestone::Stabilized { period: 2 }));
+    }
+}
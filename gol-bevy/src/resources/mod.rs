@@ -1,7 +1,127 @@
+// The engine: rules, grids and pattern formats, built for every target
+pub mod apgcode;
+pub mod automaton;
+pub mod boundary;
+pub mod census;
+pub mod chunked_grid;
+pub mod macrocell;
+pub mod rle;
+pub mod rule;
+pub mod rule_table;
+pub mod stagnation;
+pub mod turmite;
+
+// Simulations and what serves them, left out of builds without the server
+#[cfg(feature = "server")]
+pub mod annotations;
+#[cfg(feature = "server")]
+pub mod batch;
+#[cfg(feature = "server")]
+pub mod breakpoints;
+#[cfg(feature = "server")]
+pub mod benchmark;
+#[cfg(feature = "server")]
+pub mod checkpoints;
+#[cfg(feature = "server")]
+pub mod experiment;
+#[cfg(feature = "server")]
 pub mod grid_config;
+#[cfg(feature = "server")]
+pub mod milestones;
+#[cfg(feature = "server")]
+pub mod pacing;
+#[cfg(feature = "server")]
+pub mod persistence;
+#[cfg(feature = "server")]
+pub mod reference;
+#[cfg(feature = "server")]
+pub mod region;
+#[cfg(feature = "server")]
+pub mod replay_log;
+#[cfg(feature = "server")]
+pub mod rng;
+#[cfg(feature = "server")]
+pub mod scripting;
+#[cfg(feature = "server")]
 pub mod simulation_state;
+#[cfg(feature = "server")]
+pub mod simulation_manager;
+#[cfg(feature = "server")]
 pub mod simulations;
+#[cfg(feature = "server")]
+pub mod server_config;
+#[cfg(feature = "server")]
+pub mod slug;
+#[cfg(feature = "server")]
+pub mod snapshots;
+#[cfg(feature = "server")]
+pub mod spatial_grid;
+#[cfg(feature = "server")]
+pub mod verification;
+#[cfg(feature = "server")]
+pub mod watchpoints;
+#[cfg(feature = "sled-store")]
+pub mod store;
+
+pub use apgcode::*;
+pub use automaton::*;
+pub use boundary::*;
+pub use census::*;
+pub use chunked_grid::*;
+pub use macrocell::*;
+pub use rle::*;
+pub use rule::*;
+pub use rule_table::*;
+pub use stagnation::*;
+pub use turmite::*;
 
+#[cfg(feature = "server")]
+pub use annotations::*;
+#[cfg(feature = "server")]
+pub use batch::*;
+#[cfg(feature = "server")]
+pub use breakpoints::*;
+#[cfg(feature = "server")]
+pub use benchmark::*;
+#[cfg(feature = "server")]
+pub use checkpoints::*;
+#[cfg(feature = "server")]
+pub use experiment::*;
+#[cfg(feature = "server")]
 pub use grid_config::*;
+#[cfg(feature = "server")]
+pub use milestones::*;
+#[cfg(feature = "server")]
+pub use pacing::*;
+#[cfg(feature = "server")]
+pub use persistence::*;
+#[cfg(feature = "server")]
+pub use reference::*;
+#[cfg(feature = "server")]
+pub use region::*;
+#[cfg(feature = "server")]
+pub use replay_log::*;
+#[cfg(feature = "server")]
+pub use rng::*;
+#[cfg(feature = "server")]
+pub use scripting::*;
+#[cfg(feature = "server")]
 pub use simulation_state::*;
-pub use simulations::*;
\ No newline at end of file
+#[cfg(feature = "server")]
+pub use simulation_manager::*;
+#[cfg(feature = "server")]
+pub use simulations::*;
+#[cfg(feature = "server")]
+pub use server_config::*;
+#[cfg(feature = "server")]
+pub use slug::*;
+#[cfg(feature = "server")]
+pub use snapshots::*;
+#[cfg(feature = "server")]
+pub use spatial_grid::*;
+#[cfg(feature = "server")]
+pub use verification::*;
+#[cfg(feature = "server")]
+pub use watchpoints::*;
+#[cfg(feature = "sled-store")]
+pub use store::*;
@@ -0,0 +1,144 @@
+use std::time::{Duration, Instant};
+
+/// Length of the window achieved throughput is averaged over.
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(1);
+
+/// Per-simulation generation-rate governor.
+///
+/// In turbo mode (the default) generations run as fast as the server can step
+/// them. With a target rate set, the stepper waits between generations so the
+/// simulation never exceeds that many generations per second. Either way the
+/// achieved rate is measured so it can be reported back to clients.
+#[derive(Debug, Clone)]
+pub struct Pacing {
+    target_gps: Option<f64>,
+    last_step: Option<Instant>,
+    window_start: Instant,
+    window_steps: u64,
+    last_rate: f64,
+}
+
+impl Default for Pacing {
+    fn default() -> Self {
+        Self {
+            target_gps: None,
+            last_step: None,
+            window_start: Instant::now(),
+            window_steps: 0,
+            last_rate: 0.0,
+        }
+    }
+}
+
+impl Pacing {
+    pub fn turbo() -> Self {
+        Self::default()
+    }
+    
+    pub fn is_turbo(&self) -> bool {
+        self.target_gps.is_none()
+    }
+    
+    pub fn target_gps(&self) -> Option<f64> {
+        self.target_gps
+    }
+    
+    /// Limit stepping to `gps` generations per second, or remove the limit with `None`.
+    pub fn set_target(&mut self, gps: Option<f64>) {
+        self.target_gps = gps.filter(|gps| gps.is_finite() && *gps > 0.0);
+    }
+    
+    /// How long the stepper must wait before the next generation is allowed.
+    pub fn delay_until_next_step(&self, now: Instant) -> Duration {
+        match (self.target_gps, self.last_step) {
+            (Some(gps), Some(last_step)) => {
+                let next_allowed = last_step + Duration::from_secs_f64(1.0 / gps);
+                next_allowed.saturating_duration_since(now)
+            }
+            _ => Duration::ZERO,
+        }
+    }
+    
+    pub fn record_step(&mut self, now: Instant) {
+        self.last_step = Some(now);
+        
+        let elapsed = now.saturating_duration_since(self.window_start);
+        if elapsed >= THROUGHPUT_WINDOW {
+            self.last_rate = self.window_steps as f64 / elapsed.as_secs_f64();
+            self.window_start = now;
+            self.window_steps = 0;
+        }
+        self.window_steps += 1;
+    }
+    
+    /// Generations per second achieved over the most recent window. Falls to
+    /// zero once the simulation has been idle for more than a window.
+    pub fn achieved_gps(&self, now: Instant) -> f64 {
+        let idle = match self.last_step {
+            Some(last_step) => now.saturating_duration_since(last_step),
+            None => return 0.0,
+        };
+        if idle > THROUGHPUT_WINDOW {
+            return 0.0;
+        }
+        
+        let elapsed = now.saturating_duration_since(self.window_start);
+        if self.last_rate == 0.0 && !elapsed.is_zero() {
+            // First window still filling up
+            return self.window_steps as f64 / elapsed.as_secs_f64().max(THROUGHPUT_WINDOW.as_secs_f64() / 10.0);
+        }
+        self.last_rate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_turbo_never_delays() {
+        let mut pacing = Pacing::turbo();
+        let now = Instant::now();
+        pacing.record_step(now);
+        assert!(pacing.is_turbo());
+        assert_eq!(pacing.delay_until_next_step(now), Duration::ZERO);
+    }
+    
+    #[test]
+    fn test_target_rate_spaces_steps() {
+        let mut pacing = Pacing::turbo();
+        pacing.set_target(Some(10.0));
+        let now = Instant::now();
+        
+        // First step is immediate, the next must wait a tenth of a second
+        assert_eq!(pacing.delay_until_next_step(now), Duration::ZERO);
+        pacing.record_step(now);
+        assert_eq!(pacing.delay_until_next_step(now), Duration::from_millis(100));
+        assert_eq!(pacing.delay_until_next_step(now + Duration::from_millis(40)), Duration::from_millis(60));
+        assert_eq!(pacing.delay_until_next_step(now + Duration::from_millis(150)), Duration::ZERO);
+    }
+    
+    #[test]
+    fn test_invalid_target_means_turbo() {
+        let mut pacing = Pacing::turbo();
+        pacing.set_target(Some(0.0));
+        assert!(pacing.is_turbo());
+        pacing.set_target(Some(f64::NAN));
+        assert!(pacing.is_turbo());
+    }
+    
+    #[test]
+    fn test_achieved_rate_over_window() {
+        let mut pacing = Pacing::turbo();
+        let start = Instant::now();
+        pacing.window_start = start;
+        
+        for i in 0..=20 {
+            pacing.record_step(start + Duration::from_millis(i * 50));
+        }
+        
+        let now = start + Duration::from_millis(1000);
+        assert!((pacing.achieved_gps(now) - 20.0).abs() < 1e-9);
+        assert_eq!(pacing.achieved_gps(now + Duration::from_secs(5)), 0.0);
+    }
+}
@@ -0,0 +1,287 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::resources::{Annotation, Checkpoint, ChunkedGrid, GridBoundary, MilestoneWebhook, Pacing, Rule, SimulationData, Simulations, StagnationWatch, Turmite};
+
+/// On-disk form of a simulation. Pacing, breakpoints, watchpoints and
+/// timestamps are not preserved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedSimulation {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub generation: u64,
+    pub width: i32,
+    pub height: i32,
+    pub cells: Vec<(i32, i32)>,
+    /// Rule notation, or a rule table's whole `.rule` file; saves from before
+    /// rules were configurable hold B3/S23.
+    #[serde(default = "default_rule")]
+    pub rule: String,
+    /// Rules scheduled by SetRule and the generations they take over at,
+    /// in the same notation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rule_changes: Vec<(u64, String)>,
+    /// Saves from before boundaries were configurable are clipped.
+    #[serde(default)]
+    pub boundary: GridBoundary,
+    /// Cells dying under a Generations rule, with their decay state.
+    #[serde(default)]
+    pub decaying: Vec<(i32, i32, u8)>,
+    /// Live cells of a color other than 0 under Immigration or QuadLife.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub colors: Vec<(i32, i32, u8)>,
+    /// Turmite rule notation, for simulations run by turmites.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turmite_rule: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub turmites: Vec<Turmite>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_generations: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completion_webhook: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<MilestoneWebhook>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checkpoints: Vec<PersistedCheckpoint>,
+    /// Saves from before simulations were versioned start at 0.
+    #[serde(default)]
+    pub version: u64,
+}
+
+/// On-disk form of a checkpoint, with the state it rolls back to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedCheckpoint {
+    pub name: String,
+    /// Milliseconds since the Unix epoch.
+    pub created_at_ms: u64,
+    pub state: PersistedSimulation,
+}
+
+fn default_rule() -> String {
+    Rule::conway().to_string()
+}
+
+impl From<&SimulationData> for PersistedSimulation {
+    fn from(simulation: &SimulationData) -> Self {
+        let mut cells = simulation.get_live_cells();
+        cells.sort_unstable();
+        Self {
+            id: simulation.id.clone(),
+            name: simulation.name.clone(),
+            generation: simulation.generation,
+            width: simulation.width,
+            height: simulation.height,
+            cells,
+            rule: simulation.rule.definition(),
+            rule_changes: simulation.rule_changes.iter().map(|(&generation, rule)| (generation, rule.definition())).collect(),
+            boundary: simulation.boundary,
+            decaying: simulation.get_decaying_cells(),
+            colors: simulation.get_colored_cells(),
+            turmite_rule: simulation.turmite_rule.as_ref().map(ToString::to_string),
+            turmites: simulation.turmites.clone(),
+            max_generations: simulation.max_generations,
+            completion_webhook: simulation.completion_webhook.clone(),
+            webhooks: simulation.webhooks.clone(),
+            seed: simulation.seed,
+            annotations: simulation.annotations.clone(),
+            checkpoints: simulation.checkpoints.iter().map(|checkpoint| PersistedCheckpoint {
+                name: checkpoint.name.clone(),
+                created_at_ms: checkpoint.created_at.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_millis() as u64),
+                state: checkpoint.state.as_ref().into(),
+            }).collect(),
+            version: simulation.version,
+        }
+    }
+}
+
+impl From<PersistedSimulation> for SimulationData {
+    fn from(persisted: PersistedSimulation) -> Self {
+        let mut simulation = SimulationData {
+            id: persisted.id,
+            name: persisted.name,
+            generation: persisted.generation,
+            width: persisted.width,
+            height: persisted.height,
+            cells: ChunkedGrid::new(),
+            rule: persisted.rule.parse().unwrap_or_default(),
+            rule_changes: persisted.rule_changes.into_iter()
+                .filter_map(|(generation, rule)| Some((generation, rule.parse().ok()?)))
+                .collect(),
+            boundary: persisted.boundary,
+            turmite_rule: persisted.turmite_rule.and_then(|rule| rule.parse().ok()),
+            turmites: Vec::new(),
+            is_running: false,
+            created_at: SystemTime::now(),
+            pacing: Pacing::turbo(),
+            max_generations: persisted.max_generations,
+            completion_webhook: persisted.completion_webhook,
+            webhooks: persisted.webhooks,
+            auto_pause: None,
+            stagnation: StagnationWatch::default(),
+            breakpoints: Vec::new(),
+            watchpoints: Vec::new(),
+            annotations: persisted.annotations,
+            checkpoints: persisted.checkpoints.into_iter().map(|checkpoint| Checkpoint {
+                name: checkpoint.name,
+                created_at: UNIX_EPOCH + Duration::from_millis(checkpoint.created_at_ms),
+                state: Arc::new(checkpoint.state.into()),
+            }).collect(),
+            seed: persisted.seed,
+            version: persisted.version,
+        };
+        simulation.set_cells(&persisted.cells);
+        simulation.set_decaying(&persisted.decaying);
+        simulation.set_colors(&persisted.colors);
+        simulation.set_turmites(persisted.turmites);
+        simulation
+    }
+}
+
+impl Simulations {
+    /// Write every simulation to `path` as JSON. The file is written to a
+    /// sibling temp file first and renamed into place so a crash mid-write
+    /// never leaves a truncated save.
+    pub fn save_to_file(&self, path: &Path) -> io::Result<()> {
+        let mut persisted: Vec<PersistedSimulation> = self.simulations.values().map(PersistedSimulation::from).collect();
+        persisted.sort_by(|a, b| a.id.cmp(&b.id));
+        
+        let json = serde_json::to_vec_pretty(&persisted)?;
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, json)?;
+        fs::rename(&temp_path, path)
+    }
+    
+    /// Load simulations previously written by [`Simulations::save_to_file`].
+    pub fn load_from_file(path: &Path) -> io::Result<Self> {
+        let json = fs::read(path)?;
+        let persisted: Vec<PersistedSimulation> = serde_json::from_slice(&json)?;
+        
+        let mut simulations = Simulations::new();
+        for simulation in persisted {
+            simulations.simulations.insert(simulation.id.clone(), simulation.into());
+        }
+        Ok(simulations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = std::env::temp_dir().join(format!("gol-persist-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("simulations.json");
+        
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(30, 20, None);
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        simulation.rule = "B36/S23".parse().unwrap();
+        simulation.boundary = GridBoundary::Wrap;
+        simulation.max_generations = Some(50);
+        simulation.set_cells(&[(1, 1), (2, 1), (3, 1)]);
+        simulation.create_checkpoint("start".to_string()).unwrap();
+        simulation.step();
+        simulation.annotations = vec![Annotation::new(2, 1, "blinker").unwrap()];
+        simulation.schedule_rule(Some(Rule::conway()), Some(40));
+        simulation.version = 4;
+        
+        simulations.save_to_file(&path).unwrap();
+        let restored = Simulations::load_from_file(&path).unwrap();
+        
+        let original = simulations.get_simulation(&id).unwrap();
+        let loaded = restored.get_simulation(&id).unwrap();
+        assert_eq!(loaded.generation, 1);
+        assert_eq!((loaded.width, loaded.height), (30, 20));
+        assert_eq!(loaded.cells, original.cells);
+        assert_eq!(loaded.rule, original.rule);
+        assert_eq!(loaded.boundary, GridBoundary::Wrap);
+        assert_eq!(loaded.max_generations, Some(50));
+        assert_eq!(loaded.annotations, original.annotations);
+        assert_eq!(loaded.rule_changes, original.rule_changes);
+        assert_eq!(loaded.version, 4);
+        let checkpoint = loaded.checkpoint("start").unwrap();
+        assert_eq!(checkpoint.generation(), 0);
+        assert_eq!(checkpoint.state.cells, original.checkpoint("start").unwrap().state.cells);
+        
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    
+    #[test]
+    fn test_cell_colors_survive_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("gol-persist-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("simulations.json");
+        
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation_with_rule(20, 20, None, "QuadLife".parse().unwrap());
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        simulation.set_cells(&[(5, 5), (6, 5), (7, 5)]);
+        simulation.set_colors(&[(5, 5, 3), (6, 5, 2)]);
+        
+        simulations.save_to_file(&path).unwrap();
+        let restored = Simulations::load_from_file(&path).unwrap();
+        
+        let loaded = restored.get_simulation(&id).unwrap();
+        assert_eq!(loaded.rule.to_string(), "QuadLife");
+        assert_eq!(loaded.get_colored_cells(), vec![(5, 5, 3), (6, 5, 2)]);
+        
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    
+    #[test]
+    fn test_turmites_survive_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("gol-persist-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("simulations.json");
+        
+        let mut simulations = Simulations::new();
+        let rule = "{{{1,2,1},{0,1,0}},{{0,8,0},{1,1,1}}}".parse().unwrap();
+        let id = simulations.create_turmite_simulation(20, 20, rule, vec![Turmite { x: 10, y: 10, ..Default::default() }]);
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        for _ in 0..5 {
+            simulation.step();
+        }
+        
+        simulations.save_to_file(&path).unwrap();
+        let restored = Simulations::load_from_file(&path).unwrap();
+        
+        let original = simulations.get_simulation(&id).unwrap();
+        let loaded = restored.get_simulation(&id).unwrap();
+        assert_eq!(loaded.turmite_rule, original.turmite_rule);
+        assert_eq!(loaded.turmites, original.turmites);
+        assert_eq!(loaded.cells, original.cells);
+        
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    
+    #[test]
+    fn test_decaying_cells_survive_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("gol-persist-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("simulations.json");
+        
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation_with_rule(20, 20, None, "B2/S/C3".parse().unwrap());
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        simulation.set_cells(&[(5, 5), (6, 5)]);
+        simulation.step();
+        assert_eq!(simulation.get_decaying_cells(), vec![(5, 5, 2), (6, 5, 2)]);
+        
+        simulations.save_to_file(&path).unwrap();
+        let restored = Simulations::load_from_file(&path).unwrap();
+        assert_eq!(restored.get_simulation(&id).unwrap().cells, simulations.get_simulation(&id).unwrap().cells);
+        
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,208 @@
+//! Dense reference stepper for differential testing.
+//!
+//! A deliberately plain implementation of the stepping rules: the whole grid
+//! is one array of cell states, and each generation visits every cell and
+//! counts its neighbors one by one, finding cells past the edges with
+//! [`GridBoundary::fold`]. It is far slower than [`ChunkedGrid`] but has no
+//! chunks, bit slicing, copied edges or threads to get wrong, so the engine,
+//! and any faster engine added later, can be checked against it generation
+//! by generation. The server does so for every step when started with
+//! `--reference-check`.
+//!
+//! Covers every counting rule (life-like, larger than life, von Neumann and
+//! hexagonal neighborhoods, Generations) on every boundary. Rule tables,
+//! registered automata, multi-color rules and turmites are not covered.
+
+use crate::resources::{ChunkedGrid, GridBoundary, Rule, SimulationData};
+
+/// Largest grid, in cells, the reference stepper will hold.
+pub const MAX_REFERENCE_CELLS: u64 = 1 << 24;
+
+/// A simulation's grid held cell by cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceGrid {
+    width: i32,
+    height: i32,
+    rule: Rule,
+    boundary: GridBoundary,
+    generation: u64,
+    /// Row-major states: 0 dead, 1 alive, 2 and up decaying.
+    states: Vec<u8>,
+}
+
+/// The first cell, in row-major order, where the engine and the reference
+/// stepper disagree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divergence {
+    pub generation: u64,
+    pub x: i32,
+    pub y: i32,
+    /// State the reference stepper computed.
+    pub expected: u8,
+    /// State the engine computed.
+    pub actual: u8,
+    /// Cells of the grid in disagreement, including this one.
+    pub differing: usize,
+}
+
+impl ReferenceGrid {
+    /// Copy a simulation's current grid, or say why it cannot be covered.
+    pub fn from_simulation(simulation: &SimulationData) -> Result<Self, String> {
+        let rule = &simulation.rule;
+        if simulation.turmite_rule.is_some() {
+            return Err("turmites are not covered".to_string());
+        }
+        if rule.table().is_some() || rule.automaton().is_some() || rule.colors() > 1 {
+            return Err(format!("rule {} is not covered", rule));
+        }
+        let area = simulation.width as u64 * simulation.height as u64;
+        if area > MAX_REFERENCE_CELLS {
+            return Err(format!("{} cells is more than the {} covered", area, MAX_REFERENCE_CELLS));
+        }
+
+        let mut grid = Self {
+            width: simulation.width,
+            height: simulation.height,
+            rule: rule.clone(),
+            boundary: simulation.boundary,
+            generation: simulation.generation,
+            states: vec![0; area as usize],
+        };
+        for (x, y) in simulation.cells.iter_live() {
+            grid.set_state(x, y, 1);
+        }
+        for ((x, y), state) in simulation.cells.iter_decaying() {
+            grid.set_state(x, y, state);
+        }
+        Ok(grid)
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// State of a cell, dead outside the grid.
+    pub fn state(&self, x: i32, y: i32) -> u8 {
+        self.index(x, y).map_or(0, |index| self.states[index])
+    }
+
+    fn set_state(&mut self, x: i32, y: i32, state: u8) {
+        if let Some(index) = self.index(x, y) {
+            self.states[index] = state;
+        }
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        let inside = x >= 0 && x < self.width && y >= 0 && y < self.height;
+        inside.then(|| y as usize * self.width as usize + x as usize)
+    }
+
+    /// Whether the cell a neighbor offset lands on is alive, following the
+    /// boundary past the edges.
+    fn neighbor_alive(&self, x: i32, y: i32) -> bool {
+        self.boundary.fold(x, y, self.width, self.height)
+            .is_some_and(|(x, y, _, _)| self.state(x, y) == 1)
+    }
+
+    /// Advance one generation.
+    pub fn step(&mut self) {
+        let offsets = self.rule.offsets();
+        let states = self.rule.states() as u8;
+        let mut next = vec![0; self.states.len()];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let state = self.state(x, y);
+                let neighbors = offsets.iter().filter(|(dx, dy)| self.neighbor_alive(x + dx, y + dy)).count() as u32;
+                let count = neighbors + (self.rule.include_center() && state == 1) as u32;
+                next[y as usize * self.width as usize + x as usize] = match state {
+                    0 => self.rule.next_state(false, count) as u8,
+                    1 if self.rule.next_state(true, count) => 1,
+                    // A dying cell decays one state further until it is dead
+                    1 if states > 2 => 2,
+                    1 => 0,
+                    decaying if decaying + 1 < states => decaying + 1,
+                    _ => 0,
+                };
+            }
+        }
+        self.states = next;
+        self.generation += 1;
+    }
+
+    /// Compare the engine's `cells` at the same generation with this grid.
+    pub fn first_divergence(&self, cells: &ChunkedGrid) -> Option<Divergence> {
+        let mut first = None;
+        let mut differing = 0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (expected, actual) = (self.state(x, y), cells.state(x, y));
+                if expected != actual {
+                    differing += 1;
+                    first.get_or_insert((x, y, expected, actual));
+                }
+            }
+        }
+        first.map(|(x, y, expected, actual)| Divergence { generation: self.generation, x, y, expected, actual, differing })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::{scratch_simulation, SeededRng};
+
+    fn random_soup(simulation: &mut SimulationData, seed: u64) {
+        let mut rng = SeededRng::new(seed);
+        let cells: Vec<(i32, i32)> = (0..simulation.height)
+            .flat_map(|y| (0..simulation.width).map(move |x| (x, y)))
+            .filter(|_| rng.chance(0.4))
+            .collect();
+        simulation.set_cells(&cells);
+    }
+
+    #[test]
+    fn test_engine_matches_reference() {
+        let rules = ["B3/S23", "B36/S23", "B2/S", "B3/S23V", "B2/S34H", "R2,C0,M1,S2..5,B3..4,NM", "B2/S345/C4", "R2,C5,M0,S2..3,B3,NM"];
+        let boundaries = [GridBoundary::Clip, GridBoundary::Wrap, GridBoundary::Mirror, GridBoundary::KleinBottle];
+        for (seed, rule) in rules.iter().enumerate() {
+            for boundary in boundaries {
+                let mut simulation = scratch_simulation(23, 17);
+                simulation.set_rule(rule.parse().unwrap());
+                simulation.boundary = boundary;
+                random_soup(&mut simulation, seed as u64);
+
+                let mut reference = ReferenceGrid::from_simulation(&simulation).unwrap();
+                for _ in 0..12 {
+                    simulation.step();
+                    reference.step();
+                    assert_eq!(reference.first_divergence(&simulation.cells), None, "{} on {:?}", rule, boundary);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_first_divergence_is_reported() {
+        let mut simulation = scratch_simulation(10, 10);
+        simulation.set_cells(&[(4, 3), (4, 4), (4, 5)]);
+        let mut reference = ReferenceGrid::from_simulation(&simulation).unwrap();
+        simulation.step();
+        reference.step();
+
+        simulation.cells.set(7, 1, true);
+        simulation.cells.set(4, 4, false);
+        let divergence = reference.first_divergence(&simulation.cells).unwrap();
+        assert_eq!(divergence, Divergence { generation: 1, x: 7, y: 1, expected: 0, actual: 1, differing: 2 });
+
+        simulation.set_rule("Immigration".parse().unwrap());
+        assert!(ReferenceGrid::from_simulation(&simulation).is_err());
+        let mut huge = scratch_simulation(1 << 12, 1 << 12);
+        assert!(ReferenceGrid::from_simulation(&huge).is_ok());
+        huge.width = 1 << 13;
+        assert!(ReferenceGrid::from_simulation(&huge).is_err());
+    }
+}
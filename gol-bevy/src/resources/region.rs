@@ -0,0 +1,130 @@
+//! Editing whole rectangles of a grid at once.
+//!
+//! ClearRegion and FillRegion change a rectangle in one call instead of one
+//! UpdateSimulation cell at a time. Clearing and solid fills work a chunk
+//! row at a time; random fills draw every cell from a [`SeededRng`], so the
+//! same seed fills the same rectangle the same way.
+
+use crate::resources::{random_cells, SeededRng, SimulationData};
+
+/// Most cells one fill may cover, after clipping to the grid.
+pub const MAX_FILL_CELLS: u64 = 1 << 26;
+
+/// A rectangle of cells with its top-left corner at `(x, y)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridRegion {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl GridRegion {
+    /// The part of the region inside a `width` by `height` grid, as its
+    /// corners `(x0, y0)` inclusive and `(x1, y1)` exclusive; `None` if the
+    /// two do not overlap.
+    pub fn clip(&self, width: i32, height: i32) -> Option<((i32, i32), (i32, i32))> {
+        let (x0, y0) = (self.x.max(0), self.y.max(0));
+        let x1 = self.x.saturating_add(self.width).min(width);
+        let y1 = self.y.saturating_add(self.height).min(height);
+        (x0 < x1 && y0 < y1).then_some(((x0, y0), (x1, y1)))
+    }
+
+    /// Whether cell `(x, y)` lies in the region.
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        (self.x..self.x.saturating_add(self.width)).contains(&x)
+            && (self.y..self.y.saturating_add(self.height)).contains(&y)
+    }
+
+    /// Cells of the region inside a `width` by `height` grid.
+    pub fn clipped_area(&self, width: i32, height: i32) -> u64 {
+        self.clip(width, height).map_or(0, |((x0, y0), (x1, y1))| (x1 - x0) as u64 * (y1 - y0) as u64)
+    }
+}
+
+/// What a fill leaves in its region.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RegionFill {
+    /// Every cell alive.
+    Solid,
+    /// Each cell alive with chance `density`, drawn from `seed`.
+    Random { seed: u64, density: f64 },
+}
+
+// Densities are checked to lie in 0..=1 before a fill is made, so they are
+// never NaN and equality is total
+impl Eq for RegionFill {}
+
+impl SimulationData {
+    /// Kill every cell of the region inside the grid, returning how many
+    /// live cells died. Decay states and colors there are dropped.
+    pub fn clear_region(&mut self, region: GridRegion) -> u64 {
+        match region.clip(self.width, self.height) {
+            Some((from, to)) => self.cells.clear_rect(from, to),
+            None => 0,
+        }
+    }
+
+    /// Replace the region's cells inside the grid by `fill`, returning how
+    /// many live cells the region held before and after.
+    pub fn fill_region(&mut self, region: GridRegion, fill: RegionFill) -> (u64, u64) {
+        let Some((from, to)) = region.clip(self.width, self.height) else {
+            return (0, 0);
+        };
+        match fill {
+            RegionFill::Solid => {
+                let area = region.clipped_area(self.width, self.height);
+                let born = self.cells.fill_rect(from, to);
+                (area - born, area)
+            }
+            RegionFill::Random { seed, density } => {
+                let before = self.cells.clear_rect(from, to);
+                let cells = random_cells(&mut SeededRng::new(seed), to.0 - from.0, to.1 - from.1, density);
+                for &(x, y) in &cells {
+                    self.cells.set(from.0 + x, from.1 + y, true);
+                }
+                (before, cells.len() as u64)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::scratch_simulation;
+
+    #[test]
+    fn test_clear_and_fill_stay_inside_the_region() {
+        let mut simulation = scratch_simulation(200, 100);
+        simulation.set_cells(&[(0, 0), (63, 10), (64, 10), (130, 50), (199, 99)]);
+
+        let region = GridRegion { x: 60, y: 5, width: 80, height: 50 };
+        assert_eq!(simulation.clear_region(region), 3);
+        assert_eq!(simulation.get_live_cells().len(), 2);
+        assert!(simulation.cells.is_alive(199, 99));
+
+        // Past the right and bottom edges only the part on the grid fills
+        let corner = GridRegion { x: 190, y: 95, width: 100, height: 100 };
+        assert_eq!(simulation.fill_region(corner, RegionFill::Solid), (1, 50));
+        assert_eq!(simulation.cells.population(), 51);
+        assert_eq!(simulation.clear_region(GridRegion { x: -10, y: -10, width: 5, height: 5 }), 0);
+        assert_eq!(corner.clipped_area(200, 100), 50);
+    }
+
+    #[test]
+    fn test_random_fill_is_repeatable() {
+        let mut first = scratch_simulation(100, 100);
+        first.set_cells(&[(5, 5), (50, 50)]);
+        let mut second = first.clone();
+
+        let region = GridRegion { x: 10, y: 10, width: 64, height: 64 };
+        let fill = RegionFill::Random { seed: 9, density: 0.3 };
+        let (before, after) = first.fill_region(region, fill);
+        second.fill_region(region, fill);
+        assert_eq!(first.cells, second.cells);
+        assert_eq!((before, first.cells.population()), (1, after + 1));
+        assert!(first.cells.is_alive(5, 5));
+        assert!((64 * 64 / 5..64 * 64 * 2 / 5).contains(&(after as usize)), "{}", after);
+    }
+}
@@ -0,0 +1,869 @@
+use std::collections::{HashMap, VecDeque};
+use std::mem::size_of;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::resources::{ChunkedGrid, GridBoundary, GridRegion, Pacing, RegionFill, Rule, SimulationData, StagnationWatch, Turmite, TurmiteRule};
+
+/// Generations a simulation steps between keyframes.
+pub const KEYFRAME_INTERVAL: u64 = 1000;
+
+/// Keyframes kept per simulation; the oldest are dropped first.
+pub const MAX_KEYFRAMES: usize = 64;
+
+/// Most generations rebuilding one past generation may step.
+pub const MAX_REPLAY_STEPS: u64 = 10_000;
+
+/// One state-changing operation applied to a simulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayOp {
+    /// An empty grid was created.
+    Create { width: i32, height: i32, rule: Rule, boundary: GridBoundary },
+    /// An empty grid run by turmites was created.
+    CreateTurmite { width: i32, height: i32, rule: TurmiteRule, boundary: GridBoundary, turmites: Vec<Turmite> },
+    /// The simulation was restored in this state, from disk or by rolling
+    /// back to a checkpoint. A replay can start here.
+    Restore {
+        width: i32,
+        height: i32,
+        rule: Rule,
+        rule_changes: Vec<(u64, Rule)>,
+        boundary: GridBoundary,
+        generation: u64,
+        cells: Vec<(i32, i32)>,
+        decaying: Vec<(i32, i32, u8)>,
+        colors: Vec<(i32, i32, u8)>,
+        turmite_rule: Option<TurmiteRule>,
+        turmites: Vec<Turmite>,
+    },
+    /// Pattern cells were added at an offset (LoadPattern, or one upload chunk).
+    AddPattern { cells: Vec<(i32, i32)>, offset_x: i32, offset_y: i32 },
+    /// An UpdateSimulation call: optionally switch the rule and boundary,
+    /// optionally set the generation, optionally clear the grid, then set each
+    /// listed cell alive or dead, color the listed live cells and give the
+    /// listed cells a state above 1.
+    Update {
+        rule: Option<Rule>,
+        boundary: Option<GridBoundary>,
+        generation: Option<u64>,
+        clear: bool,
+        cells: Vec<(i32, i32, bool)>,
+        colors: Vec<(i32, i32, u8)>,
+        states: Vec<(i32, i32, u8)>,
+    },
+    /// A SetRule call: switch to `rule` now, or once `generation` is
+    /// reached; without a rule, cancel the switch due at `generation`.
+    SetRule { rule: Option<Rule>, generation: Option<u64> },
+    /// Every cell of a rectangle was killed (ClearRegion).
+    ClearRegion { region: GridRegion },
+    /// A rectangle's cells were replaced by a solid or seeded random fill
+    /// (FillRegion).
+    FillRegion { region: GridRegion, fill: RegionFill },
+    /// Generations were stepped; consecutive steps are merged into one entry.
+    Step { steps: u64 },
+}
+
+impl ReplayOp {
+    /// Short operation name used in audit listings.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Create { .. } | Self::CreateTurmite { .. } => "create",
+            Self::Restore { .. } => "restore",
+            Self::AddPattern { .. } => "add_pattern",
+            Self::Update { .. } => "update",
+            Self::SetRule { .. } => "set_rule",
+            Self::ClearRegion { .. } => "clear_region",
+            Self::FillRegion { .. } => "fill_region",
+            Self::Step { .. } => "step",
+        }
+    }
+
+    /// Human-readable summary of the operation's arguments.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Create { width, height, rule, .. } => format!("{}x{} grid, rule {}", width, height, rule),
+            Self::CreateTurmite { width, height, rule, turmites, .. } => {
+                format!("{}x{} grid, {} turmites with rule {}", width, height, turmites.len(), rule)
+            }
+            Self::Restore { width, height, rule, generation, cells, .. } => {
+                format!("{}x{} grid, rule {}, at generation {} with {} cells", width, height, rule, generation, cells.len())
+            }
+            Self::AddPattern { cells, offset_x, offset_y } => {
+                format!("{} cells at ({}, {})", cells.len(), offset_x, offset_y)
+            }
+            Self::Update { rule, boundary, generation, clear, cells, .. } => {
+                let mut parts = Vec::new();
+                if let Some(rule) = rule {
+                    parts.push(format!("rule {}", rule));
+                }
+                if let Some(boundary) = boundary {
+                    parts.push(format!("{:?} boundary", boundary).to_lowercase());
+                }
+                if let Some(generation) = generation {
+                    parts.push(format!("generation {}", generation));
+                }
+                if *clear {
+                    parts.push("clear".to_string());
+                }
+                parts.push(format!("{} cells", cells.len()));
+                parts.join(", ")
+            }
+            Self::SetRule { rule, generation } => match (rule, generation) {
+                (Some(rule), Some(generation)) => format!("rule {} at generation {}", rule, generation),
+                (Some(rule), None) => format!("rule {}", rule),
+                (None, Some(generation)) => format!("cancel rule change at generation {}", generation),
+                (None, None) => String::new(),
+            },
+            Self::ClearRegion { region } => {
+                format!("{}x{} at ({}, {})", region.width, region.height, region.x, region.y)
+            }
+            Self::FillRegion { region, fill } => {
+                let fill = match fill {
+                    RegionFill::Solid => "solid".to_string(),
+                    RegionFill::Random { seed, density } => format!("density {} from seed {}", density, seed),
+                };
+                format!("{}x{} at ({}, {}), {}", region.width, region.height, region.x, region.y, fill)
+            }
+            Self::Step { steps } => format!("{} generations", steps),
+        }
+    }
+
+    /// Approximate bytes the operation takes up in a log.
+    pub fn memory_bytes(&self) -> u64 {
+        let cells = |count: usize, size: usize| (count * size) as u64;
+        let payload = match self {
+            Self::Create { .. } | Self::SetRule { .. } | Self::ClearRegion { .. } | Self::FillRegion { .. } | Self::Step { .. } => 0,
+            Self::CreateTurmite { turmites, .. } => cells(turmites.len(), size_of::<Turmite>()),
+            Self::Restore { cells: live, rule_changes, decaying, colors, turmites, .. } => {
+                cells(live.len(), size_of::<(i32, i32)>())
+                    + cells(rule_changes.len(), size_of::<(u64, Rule)>())
+                    + cells(decaying.len() + colors.len(), size_of::<(i32, i32, u8)>())
+                    + cells(turmites.len(), size_of::<Turmite>())
+            }
+            Self::AddPattern { cells: added, .. } => cells(added.len(), size_of::<(i32, i32)>()),
+            Self::Update { cells: changed, colors, states, .. } => {
+                cells(changed.len(), size_of::<(i32, i32, bool)>()) + cells(colors.len() + states.len(), size_of::<(i32, i32, u8)>())
+            }
+        };
+        size_of::<ReplayRecord>() as u64 + payload
+    }
+
+    /// Apply the operation to `simulation`. `Create` and `CreateTurmite`
+    /// only start a replay and are applied by [`replay`]; a later `Restore`
+    /// replaces the simulation's state with its own.
+    pub fn apply(&self, simulation: &mut SimulationData) {
+        match self {
+            Self::Create { .. } | Self::CreateTurmite { .. } => {}
+            Self::Restore { rule, rule_changes, boundary, generation, cells, decaying, colors, turmites, .. } => {
+                simulation.rule = rule.clone();
+                simulation.rule_changes = rule_changes.iter().cloned().collect();
+                simulation.boundary = *boundary;
+                simulation.generation = *generation;
+                simulation.set_cells(cells);
+                simulation.set_decaying(decaying);
+                simulation.set_colors(colors);
+                simulation.set_turmites(turmites.clone());
+                simulation.stagnation = StagnationWatch::default();
+            }
+            Self::AddPattern { cells, offset_x, offset_y } => {
+                simulation.add_pattern(cells, *offset_x, *offset_y);
+            }
+            Self::Update { rule, boundary, generation, clear, cells, colors, states } => {
+                if let Some(rule) = rule {
+                    simulation.set_rule(rule.clone());
+                }
+                if let Some(boundary) = boundary {
+                    simulation.boundary = *boundary;
+                }
+                if let Some(generation) = generation {
+                    simulation.generation = *generation;
+                }
+                if *clear {
+                    simulation.cells.clear();
+                }
+                for &(x, y, alive) in cells {
+                    if simulation.in_bounds(x, y) {
+                        simulation.cells.set(x, y, alive);
+                    }
+                }
+                simulation.set_colors(colors);
+                simulation.set_decaying(states);
+            }
+            Self::SetRule { rule, generation } => {
+                simulation.schedule_rule(rule.clone(), *generation);
+            }
+            Self::ClearRegion { region } => {
+                simulation.clear_region(*region);
+            }
+            Self::FillRegion { region, fill } => {
+                simulation.fill_region(*region, *fill);
+            }
+            Self::Step { steps } => {
+                for _ in 0..*steps {
+                    simulation.step();
+                }
+            }
+        }
+    }
+}
+
+/// A logged operation and when it was recorded.
+#[derive(Debug, Clone)]
+pub struct ReplayRecord {
+    pub op: ReplayOp,
+    pub recorded_at: SystemTime,
+}
+
+/// A simulation's state part way through its log, so past generations are
+/// rebuilt from nearby rather than from the start.
+#[derive(Debug, Clone)]
+struct Keyframe {
+    /// Log entries applied to reach `simulation`.
+    next_entry: usize,
+    /// Highest generation a step before the keyframe reached. A replay to an
+    /// earlier generation could stop before the keyframe, so only later
+    /// generations are rebuilt from it.
+    high_water: u64,
+    simulation: Arc<SimulationData>,
+}
+
+#[derive(Default)]
+struct ReplayLog {
+    records: Vec<ReplayRecord>,
+    keyframes: VecDeque<Keyframe>,
+    /// Highest generation stepped to since the log began.
+    high_water: u64,
+}
+
+/// Where rebuilding a past generation starts: a keyframe and the entries
+/// logged after it, or a whole log.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayStart {
+    pub keyframe: Option<Arc<SimulationData>>,
+    pub entries: Vec<ReplayRecord>,
+}
+
+/// Why [`replay_to_generation`] did not rebuild a generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The log does not start a replay, or never reaches the generation.
+    NotLogged,
+    /// Reaching the generation would step more than [`MAX_REPLAY_STEPS`].
+    TooFar,
+    /// The caller asked the replay to stop.
+    Stopped,
+}
+
+/// Append-only log of every state-changing operation, per simulation.
+///
+/// Callers record an operation while still holding the simulations lock they
+/// applied it under, so each log is in the same order the operations took
+/// effect. Every [`KEYFRAME_INTERVAL`] generations stepped, the state reached
+/// is kept as a keyframe to replay from. Logs live only in memory: a
+/// simulation restored from disk starts its log with a `Restore` entry
+/// holding the restored state.
+#[derive(Default)]
+pub struct ReplayLogs {
+    logs: Mutex<HashMap<String, ReplayLog>>,
+}
+
+impl ReplayLogs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Log a restored simulation's current state: the start of its history
+    /// when restored from disk or cloned, or a rollback to a checkpoint.
+    pub fn record_restore(&self, simulation: &SimulationData) {
+        let mut cells = simulation.get_live_cells();
+        cells.sort_unstable();
+        self.record(&simulation.id, ReplayOp::Restore {
+            width: simulation.width,
+            height: simulation.height,
+            rule: simulation.rule.clone(),
+            rule_changes: simulation.rule_changes.iter().map(|(&generation, rule)| (generation, rule.clone())).collect(),
+            boundary: simulation.boundary,
+            generation: simulation.generation,
+            cells,
+            decaying: simulation.get_decaying_cells(),
+            colors: simulation.get_colored_cells(),
+            turmite_rule: simulation.turmite_rule.clone(),
+            turmites: simulation.turmites.clone(),
+        });
+    }
+
+    pub fn record(&self, id: &str, op: ReplayOp) {
+        let mut logs = self.logs.lock().unwrap();
+        let log = logs.entry(id.to_string()).or_default();
+
+        // Steps are not merged into an entry a keyframe already includes
+        let keyframed = log.keyframes.back().map_or(0, |keyframe| keyframe.next_entry);
+        if let ReplayOp::Step { steps } = op
+            && log.records.len() > keyframed
+            && let Some(ReplayRecord { op: ReplayOp::Step { steps: logged }, .. }) = log.records.last_mut()
+        {
+            *logged += steps;
+            return;
+        }
+
+        log.records.push(ReplayRecord { op, recorded_at: SystemTime::now() });
+    }
+
+    /// Note that `simulation` stepped to its current generation, keeping its
+    /// state as a keyframe once it is [`KEYFRAME_INTERVAL`] generations past
+    /// the last one. Call after recording the steps, under the same lock.
+    pub fn record_keyframe(&self, simulation: &SimulationData) {
+        let mut logs = self.logs.lock().unwrap();
+        let Some(log) = logs.get_mut(&simulation.id) else {
+            return;
+        };
+        log.high_water = log.high_water.max(simulation.generation);
+        let last = log.keyframes.back().map_or(0, |keyframe| keyframe.simulation.generation);
+        if simulation.generation < last.saturating_add(KEYFRAME_INTERVAL) {
+            return;
+        }
+        let Some(first) = log.records.first() else {
+            return;
+        };
+        let keyframe = Keyframe {
+            next_entry: log.records.len(),
+            high_water: log.high_water,
+            simulation: Arc::new(replayed_state(simulation, first.recorded_at)),
+        };
+        log.keyframes.push_back(keyframe);
+        if log.keyframes.len() > MAX_KEYFRAMES {
+            log.keyframes.pop_front();
+        }
+    }
+
+    /// Copy of a simulation's log, or `None` if nothing was ever recorded for it.
+    pub fn entries(&self, id: &str) -> Option<Vec<ReplayRecord>> {
+        self.logs.lock().unwrap().get(id).map(|log| log.records.clone())
+    }
+
+    /// Where rebuilding `generation` of a simulation starts: the latest
+    /// keyframe a replay from the start would pass on its way there, else
+    /// the start of the log.
+    pub fn replay_start(&self, id: &str, generation: u64) -> ReplayStart {
+        let logs = self.logs.lock().unwrap();
+        let Some(log) = logs.get(id) else {
+            return ReplayStart::default();
+        };
+        match log.keyframes.iter().rev().find(|keyframe| keyframe.high_water <= generation) {
+            Some(keyframe) => ReplayStart {
+                keyframe: Some(keyframe.simulation.clone()),
+                entries: log.records[keyframe.next_entry..].to_vec(),
+            },
+            None => ReplayStart { keyframe: None, entries: log.records.clone() },
+        }
+    }
+
+    /// Earliest generation past which keyframes cover the history, once
+    /// keyframes near the start of the log have been dropped.
+    pub fn earliest_keyframe(&self, id: &str) -> Option<u64> {
+        let logs = self.logs.lock().unwrap();
+        logs.get(id)?.keyframes.front().map(|keyframe| keyframe.high_water)
+    }
+
+    /// How many entries a simulation's log holds and roughly how many bytes
+    /// they and its keyframes take, or `(0, 0)` without a log.
+    pub fn usage(&self, id: &str) -> (usize, u64) {
+        self.logs.lock().unwrap().get(id).map_or((0, 0), |log| {
+            let entries: u64 = log.records.iter().map(|entry| entry.op.memory_bytes()).sum();
+            let keyframes: u64 = log.keyframes.iter().map(|keyframe| keyframe.simulation.cells.stats().memory_bytes).sum();
+            (log.records.len(), entries + keyframes)
+        })
+    }
+
+    pub fn remove(&self, id: &str) -> bool {
+        self.logs.lock().unwrap().remove(id).is_some()
+    }
+}
+
+/// Rebuild a simulation by applying `entries` in order. Returns `None` unless
+/// the first entry is a `Create`, `CreateTurmite` or `Restore`.
+pub fn replay(id: &str, entries: &[ReplayRecord]) -> Option<SimulationData> {
+    let (first, rest) = entries.split_first()?;
+    let mut simulation = start_replay(id, first)?;
+    for entry in rest {
+        entry.op.apply(&mut simulation);
+    }
+    Some(simulation)
+}
+
+/// Rebuild a simulation as it was at `generation` from `start`: every entry
+/// is applied up to the step that would take it past that generation, which
+/// is only run as far as `generation`. Stepping more than
+/// [`MAX_REPLAY_STEPS`] generations in all is refused, and `stop` is checked
+/// before every generation.
+pub fn replay_to_generation(id: &str, start: &ReplayStart, generation: u64, stop: &dyn Fn() -> bool) -> Result<SimulationData, ReplayError> {
+    let (mut simulation, rest) = match &start.keyframe {
+        Some(keyframe) => (SimulationData::clone(keyframe), start.entries.as_slice()),
+        None => {
+            let (first, rest) = start.entries.split_first().ok_or(ReplayError::NotLogged)?;
+            (start_replay(id, first).ok_or(ReplayError::NotLogged)?, rest)
+        }
+    };
+    let mut budget = MAX_REPLAY_STEPS;
+    for entry in rest {
+        let ReplayOp::Step { steps } = entry.op else {
+            entry.op.apply(&mut simulation);
+            continue;
+        };
+        let crosses = simulation.generation <= generation && simulation.generation + steps > generation;
+        let steps = if crosses { generation - simulation.generation } else { steps };
+        budget = budget.checked_sub(steps).ok_or(ReplayError::TooFar)?;
+        for _ in 0..steps {
+            if stop() {
+                return Err(ReplayError::Stopped);
+            }
+            simulation.step();
+        }
+        if crosses {
+            break;
+        }
+    }
+    if simulation.generation == generation { Ok(simulation) } else { Err(ReplayError::NotLogged) }
+}
+
+/// Population, births and deaths of one generation in a replayed series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenerationSample {
+    pub generation: u64,
+    pub population: u64,
+    /// Cells that came alive in the step to this generation.
+    pub births: u64,
+    /// Cells that died in the step to this generation.
+    pub deaths: u64,
+}
+
+/// Replay `entries`, sampling every step that ends after `from` and no later
+/// than `to`. Edits between steps are applied but not sampled. Returns `None`
+/// if the log does not start a replay.
+pub fn replay_statistics(id: &str, entries: &[ReplayRecord], from: u64, to: u64) -> Option<Vec<GenerationSample>> {
+    let (first, rest) = entries.split_first()?;
+    let mut simulation = start_replay(id, first)?;
+    let mut samples = Vec::new();
+    for entry in rest {
+        if simulation.generation >= to {
+            break;
+        }
+        let ReplayOp::Step { steps } = entry.op else {
+            entry.op.apply(&mut simulation);
+            continue;
+        };
+        for _ in 0..steps {
+            if simulation.generation >= to {
+                break;
+            }
+            if simulation.generation < from {
+                simulation.step();
+                continue;
+            }
+            let before = simulation.cells.clone();
+            simulation.step();
+            samples.push(GenerationSample {
+                generation: simulation.generation,
+                population: simulation.cells.population(),
+                births: simulation.cells.difference(&before).len() as u64,
+                deaths: before.difference(&simulation.cells).len() as u64,
+            });
+        }
+    }
+    Some(samples)
+}
+
+/// Replay `entries`, counting for each cell how many of the steps ending
+/// after `from` and no later than `to` left it alive. Cells never alive in
+/// that window are left out. Returns `None` if the log does not start a
+/// replay.
+pub fn replay_activity(id: &str, entries: &[ReplayRecord], from: u64, to: u64) -> Option<HashMap<(i32, i32), u32>> {
+    let (first, rest) = entries.split_first()?;
+    let mut simulation = start_replay(id, first)?;
+    let mut activity = HashMap::new();
+    for entry in rest {
+        if simulation.generation >= to {
+            break;
+        }
+        let ReplayOp::Step { steps } = entry.op else {
+            entry.op.apply(&mut simulation);
+            continue;
+        };
+        for _ in 0..steps {
+            if simulation.generation >= to {
+                break;
+            }
+            simulation.step();
+            if simulation.generation > from {
+                for cell in simulation.cells.iter_live() {
+                    *activity.entry(cell).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+    Some(activity)
+}
+
+/// Earliest generation [`replay_to_generation`] can rebuild: where the
+/// log's first step starts, as edits before it are all applied. `None` if
+/// the log does not start a replay.
+pub fn first_generation(entries: &[ReplayRecord]) -> Option<u64> {
+    let (first, rest) = entries.split_first()?;
+    let mut generation = match &first.op {
+        ReplayOp::Create { .. } | ReplayOp::CreateTurmite { .. } => 0,
+        ReplayOp::Restore { generation, .. } => *generation,
+        _ => return None,
+    };
+    for entry in rest {
+        match entry.op {
+            ReplayOp::Step { .. } => break,
+            ReplayOp::Update { generation: Some(set), .. } | ReplayOp::Restore { generation: set, .. } => generation = set,
+            _ => {}
+        }
+    }
+    Some(generation)
+}
+
+/// `simulation` as a replay of its log would rebuild it: the cells, rules and
+/// turmites, without the settings no entry records.
+fn replayed_state(simulation: &SimulationData, created_at: SystemTime) -> SimulationData {
+    SimulationData {
+        id: simulation.id.clone(),
+        name: None,
+        generation: simulation.generation,
+        width: simulation.width,
+        height: simulation.height,
+        cells: simulation.cells.clone(),
+        rule: simulation.rule.clone(),
+        rule_changes: simulation.rule_changes.clone(),
+        boundary: simulation.boundary,
+        turmite_rule: simulation.turmite_rule.clone(),
+        turmites: simulation.turmites.clone(),
+        is_running: false,
+        created_at,
+        pacing: Pacing::turbo(),
+        max_generations: None,
+        completion_webhook: None,
+        webhooks: Vec::new(),
+        auto_pause: None,
+        stagnation: StagnationWatch::default(),
+        breakpoints: Vec::new(),
+        watchpoints: Vec::new(),
+        annotations: Vec::new(),
+        checkpoints: Vec::new(),
+        seed: None,
+        version: 0,
+    }
+}
+
+/// The simulation a replay starts from, if `first` is an entry that starts one.
+fn start_replay(id: &str, first: &ReplayRecord) -> Option<SimulationData> {
+    let conway = Rule::conway();
+    let (width, height, rule, rule_changes, boundary, generation, cells, decaying, colors, turmite_rule, turmites) = match &first.op {
+        ReplayOp::Create { width, height, rule, boundary } => {
+            (*width, *height, rule, &[][..], *boundary, 0, &[][..], &[][..], &[][..], None, &[][..])
+        }
+        ReplayOp::CreateTurmite { width, height, rule, boundary, turmites } => {
+            (*width, *height, &conway, &[][..], *boundary, 0, &[][..], &[][..], &[][..], Some(rule), turmites.as_slice())
+        }
+        ReplayOp::Restore { width, height, rule, rule_changes, boundary, generation, cells, decaying, colors, turmite_rule, turmites } => (
+            *width,
+            *height,
+            rule,
+            rule_changes.as_slice(),
+            *boundary,
+            *generation,
+            cells.as_slice(),
+            decaying.as_slice(),
+            colors.as_slice(),
+            turmite_rule.as_ref(),
+            turmites.as_slice(),
+        ),
+        _ => return None,
+    };
+
+    let mut simulation = SimulationData {
+        id: id.to_string(),
+        name: None,
+        generation,
+        width,
+        height,
+        cells: ChunkedGrid::new(),
+        rule: rule.clone(),
+        rule_changes: rule_changes.iter().cloned().collect(),
+        boundary,
+        turmite_rule: turmite_rule.cloned(),
+        turmites: Vec::new(),
+        is_running: false,
+        created_at: first.recorded_at,
+        pacing: Pacing::turbo(),
+        max_generations: None,
+        completion_webhook: None,
+        webhooks: Vec::new(),
+        auto_pause: None,
+        stagnation: StagnationWatch::default(),
+        breakpoints: Vec::new(),
+        watchpoints: Vec::new(),
+        annotations: Vec::new(),
+        checkpoints: Vec::new(),
+        seed: None,
+        version: 0,
+    };
+    simulation.set_cells(cells);
+    simulation.set_decaying(decaying);
+    simulation.set_colors(colors);
+    simulation.set_turmites(turmites.to_vec());
+    Some(simulation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::Simulations;
+
+    #[test]
+    fn test_replay_reproduces_live_state() {
+        let logs = ReplayLogs::new();
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(20, 20, None);
+        logs.record(&id, ReplayOp::Create { width: 20, height: 20, rule: Rule::conway(), boundary: GridBoundary::Clip });
+
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        simulation.add_pattern(&glider, 3, 3);
+        logs.record(&id, ReplayOp::AddPattern { cells: glider.to_vec(), offset_x: 3, offset_y: 3 });
+        for _ in 0..4 {
+            simulation.step();
+            logs.record(&id, ReplayOp::Step { steps: 1 });
+        }
+        simulation.cells.set(15, 15, true);
+        logs.record(&id, ReplayOp::Update {
+            rule: None,
+            boundary: None,
+            generation: None,
+            clear: false,
+            cells: vec![(15, 15, true)],
+            colors: Vec::new(),
+            states: Vec::new(),
+        });
+
+        let entries = logs.entries(&id).unwrap();
+        assert_eq!(entries.len(), 4, "consecutive steps are merged");
+        assert_eq!(entries[2].op, ReplayOp::Step { steps: 4 });
+
+        let replayed = replay(&id, &entries).unwrap();
+        assert_eq!(replayed.generation, simulation.generation);
+        assert_eq!(replayed.cells, simulation.cells);
+
+        let partial = replay(&id, &entries[..2]).unwrap();
+        assert_eq!(partial.generation, 0);
+        assert_eq!(partial.get_live_cell_count(), 5);
+    }
+
+    #[test]
+    fn test_replay_applies_rollbacks() {
+        let logs = ReplayLogs::new();
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(20, 20, None);
+        logs.record(&id, ReplayOp::Create { width: 20, height: 20, rule: Rule::conway(), boundary: GridBoundary::Clip });
+
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        simulation.add_pattern(&[(4, 3), (4, 4), (4, 5)], 0, 0);
+        logs.record(&id, ReplayOp::AddPattern { cells: vec![(4, 3), (4, 4), (4, 5)], offset_x: 0, offset_y: 0 });
+        simulation.step();
+        logs.record(&id, ReplayOp::Step { steps: 1 });
+        simulation.create_checkpoint("horizontal".to_string()).unwrap();
+        simulation.add_pattern(&[(10, 10)], 0, 0);
+        logs.record(&id, ReplayOp::AddPattern { cells: vec![(10, 10)], offset_x: 0, offset_y: 0 });
+        simulation.step();
+        logs.record(&id, ReplayOp::Step { steps: 1 });
+        simulation.restore_checkpoint("horizontal");
+        logs.record_restore(simulation);
+        simulation.step();
+        logs.record(&id, ReplayOp::Step { steps: 1 });
+
+        let replayed = replay(&id, &logs.entries(&id).unwrap()).unwrap();
+        assert_eq!(replayed.generation, 2);
+        assert_eq!(replayed.cells, simulation.cells);
+        assert!(!replayed.cells.is_alive(10, 10));
+    }
+
+    #[test]
+    fn test_replay_to_generation_stops_mid_step() {
+        let logs = ReplayLogs::new();
+        logs.record("blinker", ReplayOp::Create { width: 10, height: 10, rule: Rule::conway(), boundary: GridBoundary::Clip });
+        logs.record("blinker", ReplayOp::AddPattern { cells: vec![(4, 3), (4, 4), (4, 5)], offset_x: 0, offset_y: 0 });
+        logs.record("blinker", ReplayOp::Step { steps: 5 });
+        logs.record("blinker", ReplayOp::AddPattern { cells: vec![(0, 0)], offset_x: 0, offset_y: 0 });
+        assert_eq!(first_generation(&logs.entries("blinker").unwrap()), Some(0));
+
+        let vertical = replay_to_generation("blinker", &logs.replay_start("blinker", 2), 2, &|| false).unwrap();
+        assert_eq!(vertical.generation, 2);
+        assert!(vertical.cells.is_alive(4, 3));
+
+        let horizontal = replay_to_generation("blinker", &logs.replay_start("blinker", 3), 3, &|| false).unwrap();
+        assert!(horizontal.cells.is_alive(3, 4));
+
+        // Edits made at the last generation are part of its state
+        let last = replay_to_generation("blinker", &logs.replay_start("blinker", 5), 5, &|| false).unwrap();
+        assert!(last.cells.is_alive(0, 0));
+        assert_eq!(replay_to_generation("blinker", &logs.replay_start("blinker", 6), 6, &|| false).unwrap_err(), ReplayError::NotLogged);
+        assert_eq!(replay_to_generation("blinker", &logs.replay_start("blinker", 4), 4, &|| true).unwrap_err(), ReplayError::Stopped);
+    }
+
+    #[test]
+    fn test_replay_to_generation_starts_from_the_latest_keyframe() {
+        let logs = ReplayLogs::new();
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(20, 20, None);
+        logs.record(&id, ReplayOp::Create { width: 20, height: 20, rule: Rule::conway(), boundary: GridBoundary::Clip });
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        simulation.add_pattern(&[(4, 3), (4, 4), (4, 5)], 0, 0);
+        logs.record(&id, ReplayOp::AddPattern { cells: vec![(4, 3), (4, 4), (4, 5)], offset_x: 0, offset_y: 0 });
+        let steps = MAX_REPLAY_STEPS + 2 * KEYFRAME_INTERVAL;
+        for _ in 0..steps / KEYFRAME_INTERVAL {
+            for _ in 0..KEYFRAME_INTERVAL {
+                simulation.step();
+            }
+            logs.record(&id, ReplayOp::Step { steps: KEYFRAME_INTERVAL });
+            logs.record_keyframe(simulation);
+        }
+
+        // Steps past a keyframe are logged apart from the steps it includes
+        assert_eq!(logs.entries(&id).unwrap().len() as u64, 2 + steps / KEYFRAME_INTERVAL);
+        let start = logs.replay_start(&id, steps - 1);
+        assert_eq!(start.keyframe.as_ref().unwrap().generation, steps - KEYFRAME_INTERVAL);
+        let replayed = replay_to_generation(&id, &start, steps - 1, &|| false).unwrap();
+        assert!(replayed.cells.is_alive(3, 4));
+        assert!(replay_to_generation(&id, &logs.replay_start(&id, 1), 1, &|| false).unwrap().cells.is_alive(3, 4));
+
+        // Generations before the first keyframe replay from the start, as far as the cap
+        assert!(logs.replay_start(&id, KEYFRAME_INTERVAL - 1).keyframe.is_none());
+        let start = ReplayStart { keyframe: None, entries: logs.entries(&id).unwrap() };
+        assert_eq!(replay_to_generation(&id, &start, MAX_REPLAY_STEPS + 1, &|| false).unwrap_err(), ReplayError::TooFar);
+    }
+
+    #[test]
+    fn test_replay_switches_scheduled_rule_mid_step() {
+        let logs = ReplayLogs::new();
+        logs.record("seed", ReplayOp::Create { width: 10, height: 10, rule: Rule::conway(), boundary: GridBoundary::Clip });
+        logs.record("seed", ReplayOp::SetRule { rule: Some("B3/S012345678".parse().unwrap()), generation: Some(2) });
+        logs.record("seed", ReplayOp::Step { steps: 2 });
+        logs.record("seed", ReplayOp::AddPattern { cells: vec![(5, 5)], offset_x: 0, offset_y: 0 });
+        logs.record("seed", ReplayOp::Step { steps: 3 });
+        let entries = logs.entries("seed").unwrap();
+
+        // The lone cell added after the switch survives under the new rule
+        let replayed = replay("seed", &entries).unwrap();
+        assert_eq!(replayed.rule.to_string(), "B3/S012345678");
+        assert!(replayed.cells.is_alive(5, 5));
+        assert!(replay_to_generation("seed", &logs.replay_start("seed", 1), 1, &|| false).unwrap().rule.is_conway());
+        assert!(!replay_to_generation("seed", &logs.replay_start("seed", 2), 2, &|| false).unwrap().rule.is_conway());
+    }
+
+    #[test]
+    fn test_replay_statistics_counts_births_and_deaths() {
+        let logs = ReplayLogs::new();
+        logs.record("blinker", ReplayOp::Create { width: 10, height: 10, rule: Rule::conway(), boundary: GridBoundary::Clip });
+        logs.record("blinker", ReplayOp::AddPattern { cells: vec![(4, 3), (4, 4), (4, 5)], offset_x: 0, offset_y: 0 });
+        logs.record("blinker", ReplayOp::Step { steps: 2 });
+        logs.record("blinker", ReplayOp::AddPattern { cells: vec![(0, 0)], offset_x: 0, offset_y: 0 });
+        logs.record("blinker", ReplayOp::Step { steps: 3 });
+        let entries = logs.entries("blinker").unwrap();
+
+        let samples = replay_statistics("blinker", &entries, 1, 4).unwrap();
+        let generations: Vec<u64> = samples.iter().map(|sample| sample.generation).collect();
+        assert_eq!(generations, vec![2, 3, 4]);
+        assert_eq!((samples[0].population, samples[0].births, samples[0].deaths), (3, 2, 2));
+        // The lone cell added at generation 2 dies in the next step
+        assert_eq!((samples[1].population, samples[1].births, samples[1].deaths), (3, 2, 3));
+    }
+
+    #[test]
+    fn test_replay_activity_counts_generations_alive() {
+        let logs = ReplayLogs::new();
+        logs.record("blinker", ReplayOp::Create { width: 10, height: 10, rule: Rule::conway(), boundary: GridBoundary::Clip });
+        logs.record("blinker", ReplayOp::AddPattern { cells: vec![(4, 3), (4, 4), (4, 5), (0, 9), (1, 9), (0, 8), (1, 8)], offset_x: 0, offset_y: 0 });
+        logs.record("blinker", ReplayOp::Step { steps: 6 });
+        let entries = logs.entries("blinker").unwrap();
+
+        let activity = replay_activity("blinker", &entries, 2, 6).unwrap();
+        // The blinker's center and the block never go out; its ends take turns
+        assert_eq!(activity[&(4, 4)], 4);
+        assert_eq!(activity[&(0, 9)], 4);
+        assert_eq!((activity[&(3, 4)], activity[&(4, 3)]), (2, 2));
+        assert_eq!(activity.len(), 9);
+        assert!(replay_activity("blinker", &entries, 6, 6).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_replay_starts_from_restored_state() {
+        let logs = ReplayLogs::new();
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(10, 10, None);
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        simulation.set_cells(&[(4, 3), (4, 4), (4, 5)]);
+        simulation.generation = 7;
+
+        logs.record_restore(simulation);
+        simulation.step();
+        logs.record(&id, ReplayOp::Step { steps: 1 });
+
+        let entries = logs.entries(&id).unwrap();
+        assert_eq!(first_generation(&entries), Some(7));
+        let replayed = replay(&id, &entries).unwrap();
+        assert_eq!(replayed.generation, 8);
+        assert_eq!(replayed.cells, simulation.cells);
+    }
+
+    #[test]
+    fn test_replay_moves_turmites() {
+        let logs = ReplayLogs::new();
+        let mut simulations = Simulations::new();
+        let turmites = vec![Turmite { x: 5, y: 5, ..Default::default() }];
+        let id = simulations.create_turmite_simulation(10, 10, TurmiteRule::langtons_ant(), turmites.clone());
+        logs.record(&id, ReplayOp::CreateTurmite {
+            width: 10,
+            height: 10,
+            rule: TurmiteRule::langtons_ant(),
+            boundary: GridBoundary::Clip,
+            turmites,
+        });
+
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        for _ in 0..12 {
+            simulation.step();
+        }
+        logs.record(&id, ReplayOp::Step { steps: 12 });
+
+        let replayed = replay(&id, &logs.entries(&id).unwrap()).unwrap();
+        assert_eq!(replayed.cells, simulation.cells);
+        assert_eq!(replayed.turmites, simulation.turmites);
+    }
+
+    #[test]
+    fn test_usage_counts_entries_and_payloads() {
+        let logs = ReplayLogs::new();
+        assert_eq!(logs.usage("blinker"), (0, 0));
+        logs.record("blinker", ReplayOp::Create { width: 10, height: 10, rule: Rule::conway(), boundary: GridBoundary::Clip });
+        logs.record("blinker", ReplayOp::Step { steps: 1 });
+        logs.record("blinker", ReplayOp::Step { steps: 2 });
+        let (entries, bare) = logs.usage("blinker");
+        assert_eq!(entries, 2);
+
+        logs.record("blinker", ReplayOp::AddPattern { cells: vec![(4, 3), (4, 4), (4, 5)], offset_x: 0, offset_y: 0 });
+        let (entries, with_pattern) = logs.usage("blinker");
+        assert_eq!(entries, 3);
+        assert_eq!(with_pattern - bare, (size_of::<ReplayRecord>() + 3 * size_of::<(i32, i32)>()) as u64);
+    }
+
+    #[test]
+    fn test_replay_requires_a_starting_entry() {
+        let logs = ReplayLogs::new();
+        logs.record("abc", ReplayOp::Step { steps: 1 });
+        assert!(replay("abc", &logs.entries("abc").unwrap()).is_none());
+        assert!(first_generation(&logs.entries("abc").unwrap()).is_none());
+        assert!(logs.remove("abc"));
+        assert!(logs.entries("abc").is_none());
+    }
+}
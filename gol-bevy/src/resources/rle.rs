@@ -0,0 +1,235 @@
+use std::fmt;
+
+use crate::resources::Rule;
+
+/// Error raised while decoding RLE pattern text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RleError(pub String);
+
+impl fmt::Display for RleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for RleError {}
+
+/// Incremental decoder for run-length encoded (Golly/LifeWiki RLE) patterns.
+///
+/// Text can be fed in arbitrary blocks: a block may end mid-line, mid-run or
+/// in the middle of the header, so large patterns can be decoded as they
+/// arrive without ever holding the whole document. `#` comment lines and the
+/// `x = .., y = ..` header line are skipped; `b`/`.` are dead cells, any other
+/// letter is a live cell, `$` ends a row and `!` ends the pattern.
+#[derive(Debug, Default)]
+pub struct RleDecoder {
+    x: i32,
+    y: i32,
+    run: Option<u32>,
+    at_line_start: bool,
+    skipping_line: bool,
+    finished: bool,
+    in_body: bool,
+}
+
+impl RleDecoder {
+    pub fn new() -> Self {
+        Self {
+            at_line_start: true,
+            ..Self::default()
+        }
+    }
+
+    /// Whether the terminating `!` has been seen; anything after it is ignored.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Decode the next block, appending live cells relative to the pattern's
+    /// top-left corner.
+    pub fn feed(&mut self, block: &str, cells: &mut Vec<(i32, i32)>) -> Result<(), RleError> {
+        for ch in block.chars() {
+            if self.finished {
+                break;
+            }
+
+            if ch == '\n' || ch == '\r' {
+                self.at_line_start = true;
+                self.skipping_line = false;
+                continue;
+            }
+            if self.skipping_line {
+                continue;
+            }
+            if self.at_line_start {
+                self.at_line_start = false;
+                // Comment and header lines only appear before the cell data
+                if ch == '#' || (ch == 'x' && !self.in_body) {
+                    self.skipping_line = true;
+                    continue;
+                }
+            }
+
+            match ch {
+                ' ' | '\t' => {}
+                '0'..='9' => {
+                    let digit = ch as u32 - '0' as u32;
+                    let run = self.run.unwrap_or(0).checked_mul(10).and_then(|run| run.checked_add(digit));
+                    self.run = Some(run.ok_or_else(|| RleError("Run length too large".to_string()))?);
+                }
+                '!' => {
+                    self.in_body = true;
+                    self.finished = true;
+                }
+                '$' => {
+                    self.in_body = true;
+                    let rows = self.take_run()?;
+                    self.y = self.y.checked_add(rows).ok_or_else(|| RleError("Pattern too tall".to_string()))?;
+                    self.x = 0;
+                }
+                'b' | '.' => {
+                    self.in_body = true;
+                    let run = self.take_run()?;
+                    self.x = self.x.checked_add(run).ok_or_else(|| RleError("Pattern too wide".to_string()))?;
+                }
+                ch if ch.is_ascii_alphabetic() => {
+                    self.in_body = true;
+                    let run = self.take_run()?;
+                    let end = self.x.checked_add(run).ok_or_else(|| RleError("Pattern too wide".to_string()))?;
+                    cells.extend((self.x..end).map(|x| (x, self.y)));
+                    self.x = end;
+                }
+                other => return Err(RleError(format!("Unexpected character '{}' in RLE", other))),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn take_run(&mut self) -> Result<i32, RleError> {
+        let run = self.run.take().unwrap_or(1);
+        i32::try_from(run).map_err(|_| RleError("Run length too large".to_string()))
+    }
+}
+
+/// Decode a complete RLE document.
+pub fn decode_rle(text: &str) -> Result<Vec<(i32, i32)>, RleError> {
+    let mut cells = Vec::new();
+    RleDecoder::new().feed(text, &mut cells)?;
+    Ok(cells)
+}
+
+/// Longest line written by [`encode_rle`], as Golly does.
+const RLE_LINE_WIDTH: usize = 70;
+
+/// Encode live cells as an RLE document under `rule`, positioned relative to
+/// their bounding box.
+pub fn encode_rle(cells: &[(i32, i32)], rule: &Rule) -> String {
+    let mut cells = cells.to_vec();
+    cells.sort_unstable_by_key(|&(x, y)| (y, x));
+    cells.dedup();
+    let (Some(min_x), Some(max_x)) = (cells.iter().map(|(x, _)| *x).min(), cells.iter().map(|(x, _)| *x).max()) else {
+        return format!("x = 0, y = 0, rule = {}\n!\n", rule);
+    };
+    let (min_y, max_y) = (cells[0].1, cells[cells.len() - 1].1);
+
+    let mut items = Vec::new();
+    let (mut x, mut y) = (min_x as i64, min_y as i64);
+    let mut live_run = 0;
+    for &(cell_x, cell_y) in &cells {
+        let (cell_x, cell_y) = (cell_x as i64, cell_y as i64);
+        if cell_y != y || cell_x != x {
+            push_run(&mut items, live_run, 'o');
+            live_run = 0;
+        }
+        if cell_y != y {
+            push_run(&mut items, cell_y - y, '$');
+            (x, y) = (min_x as i64, cell_y);
+        }
+        push_run(&mut items, cell_x - x, 'b');
+        live_run += 1;
+        x = cell_x + 1;
+    }
+    push_run(&mut items, live_run, 'o');
+    items.push("!".to_string());
+
+    let mut text = format!("x = {}, y = {}, rule = {}\n", max_x as i64 - min_x as i64 + 1, max_y as i64 - min_y as i64 + 1, rule);
+    let mut line_len = 0;
+    for item in items {
+        if line_len + item.len() > RLE_LINE_WIDTH {
+            text.push('\n');
+            line_len = 0;
+        }
+        line_len += item.len();
+        text.push_str(&item);
+    }
+    text.push('\n');
+    text
+}
+
+fn push_run(items: &mut Vec<String>, run: i64, tag: char) {
+    if run > 0 {
+        items.push(if run == 1 { tag.to_string() } else { format!("{}{}", run, tag) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER: &str = "#N Glider\n#C A small spaceship\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!\n";
+
+    #[test]
+    fn test_decodes_glider() {
+        let cells = decode_rle(GLIDER).unwrap();
+        assert_eq!(cells, vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn test_blocks_may_split_anywhere() {
+        let expected = decode_rle(GLIDER).unwrap();
+
+        for split in 0..GLIDER.len() {
+            let mut decoder = RleDecoder::new();
+            let mut cells = Vec::new();
+            decoder.feed(&GLIDER[..split], &mut cells).unwrap();
+            decoder.feed(&GLIDER[split..], &mut cells).unwrap();
+            assert_eq!(cells, expected, "split at {}", split);
+            assert!(decoder.is_finished());
+        }
+    }
+
+    #[test]
+    fn test_multi_digit_runs_and_blank_rows() {
+        let cells = decode_rle("12o2$o!").unwrap();
+        assert_eq!(cells.len(), 13);
+        assert_eq!(cells[11], (11, 0));
+        assert_eq!(cells[12], (0, 2));
+    }
+
+    #[test]
+    fn test_text_after_terminator_is_ignored() {
+        let cells = decode_rle("o!\nthis is a trailing comment").unwrap();
+        assert_eq!(cells, vec![(0, 0)]);
+    }
+
+    #[test]
+    fn test_encode_round_trips() {
+        let glider = decode_rle(GLIDER).unwrap();
+        let shifted: Vec<_> = glider.iter().map(|(x, y)| (x + 40, y + 7)).collect();
+        let text = encode_rle(&shifted, &Rule::conway());
+        assert_eq!(text, "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+        assert_eq!(decode_rle(&text).unwrap(), glider);
+        
+        let sparse = vec![(0, 0), (200, 0), (0, 100)];
+        let text = encode_rle(&sparse, &Rule::conway());
+        assert!(text.lines().all(|line| line.len() <= RLE_LINE_WIDTH));
+        assert_eq!(decode_rle(&text).unwrap(), sparse);
+    }
+
+    #[test]
+    fn test_rejects_invalid_characters() {
+        assert!(decode_rle("2o*o!").is_err());
+        assert!(decode_rle("99999999999o!").is_err());
+    }
+}
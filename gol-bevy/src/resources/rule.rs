@@ -0,0 +1,561 @@
+//! Outer-totalistic rules: life-like `B3/S23`, Generations and Larger than Life.
+//!
+//! A rule says which live-neighbor counts give birth to a dead cell and which
+//! keep a live cell alive. Neighbors are counted over a Moore (square) or von
+//! Neumann (diamond) neighborhood of some radius. Rules are written either in
+//! B/S notation, with a `V` suffix for the radius-1 von Neumann neighborhood
+//! or `H` for the hexagonal one, or in Golly's Larger than Life notation such
+//! as `R5,C0,M1,S34..58,B34..45,NM`.
+//!
+//! Hexagonal rules use axial coordinates on the square grid, as Golly does:
+//! the hex at `(x, y)` touches `(x ± 1, y)`, `(x, y ± 1)`, `(x - 1, y - 1)`
+//! and `(x + 1, y + 1)`, so only the north-east and south-west corners of the
+//! Moore neighborhood are left out.
+//!
+//! Generations rules (`B2/S/C3`, or a `C` above 2 in Larger than Life) give
+//! cells more than two states. A live cell that fails to survive does not die
+//! at once but decays through states 2, 3, ... up to `C - 1` before it is
+//! dead again. Decaying cells neither count as neighbors nor can be born into.
+//!
+//! `Immigration` and `QuadLife` play B3/S23 with two or four colors of live
+//! cell. Survivors keep their color and a newborn cell takes the color most of
+//! its parents share; under QuadLife a cell born of three different colors
+//! takes the fourth.
+//!
+//! Anything else can be given as a Golly `.rule` file holding an `@TABLE`
+//! transition table; see [`RuleTable`]. Automata registered by other crates
+//! are selected by name; see [`register_automaton`](crate::resources::register_automaton).
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::resources::{find_automaton, Automaton, AutomatonHandle, ChunkedGrid, RuleTable};
+
+/// Largest neighborhood radius a rule may use.
+pub const MAX_RULE_RANGE: u32 = 10;
+
+/// Most states a Generations rule may have, so every state fits in a byte.
+pub const MAX_RULE_STATES: u32 = 255;
+
+/// Named multi-color variants of B3/S23 and their color counts.
+const COLOR_RULES: [(&str, u32); 2] = [("Immigration", 2), ("QuadLife", 4)];
+
+/// Error raised while parsing a rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleError(pub String);
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// Every cell within the radius in both directions.
+    Moore,
+    /// Cells whose x and y distances sum to at most the radius.
+    VonNeumann,
+    /// Hexes within the radius in axial coordinates.
+    Hexagonal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    range: u32,
+    neighborhood: Neighborhood,
+    /// Whether a live cell counts itself among its neighbors.
+    include_center: bool,
+    /// Cell states including dead and alive; above 2 for Generations rules.
+    states: u32,
+    /// Colors a live cell can have; 1 unless a multi-color variant.
+    colors: u32,
+    /// Transition table that replaces the birth and survival counts.
+    table: Option<Arc<RuleTable>>,
+    /// Registered automaton that replaces the birth and survival counts.
+    automaton: Option<AutomatonHandle>,
+    /// Indexed by neighbor count.
+    birth: Vec<bool>,
+    survival: Vec<bool>,
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::conway()
+    }
+}
+
+impl Rule {
+    /// Conway's Game of Life, B3/S23.
+    pub fn conway() -> Self {
+        Self::life_like(Neighborhood::Moore, &[3], &[2, 3])
+    }
+
+    fn life_like(neighborhood: Neighborhood, birth: &[u32], survival: &[u32]) -> Self {
+        let mut rule = Self::empty(1, neighborhood, false);
+        for &count in birth {
+            rule.birth[count as usize] = true;
+        }
+        for &count in survival {
+            rule.survival[count as usize] = true;
+        }
+        rule
+    }
+
+    fn empty(range: u32, neighborhood: Neighborhood, include_center: bool) -> Self {
+        let max = neighborhood_size(range, neighborhood) + include_center as u32;
+        Self {
+            range,
+            neighborhood,
+            include_center,
+            states: 2,
+            colors: 1,
+            table: None,
+            automaton: None,
+            birth: vec![false; max as usize + 1],
+            survival: vec![false; max as usize + 1],
+        }
+    }
+
+    /// A rule run by a transition table, counting neighbors as it does.
+    pub fn from_table(table: RuleTable) -> Self {
+        let mut rule = Self::empty(1, table.neighborhood(), false);
+        rule.states = table.states();
+        rule.table = Some(Arc::new(table));
+        rule
+    }
+
+    pub fn table(&self) -> Option<&RuleTable> {
+        self.table.as_deref()
+    }
+
+    /// A rule run by a registered automaton.
+    pub fn from_automaton(handle: AutomatonHandle) -> Self {
+        let automaton = handle.automaton();
+        let mut rule = Self::empty(1, automaton.neighborhood(), false);
+        rule.states = automaton.states();
+        rule.automaton = Some(handle);
+        rule
+    }
+
+    pub fn automaton(&self) -> Option<&dyn Automaton> {
+        self.automaton.as_ref().map(AutomatonHandle::automaton)
+    }
+
+    /// Text that parses back into this rule: its notation, or for a table
+    /// rule the whole `.rule` file.
+    pub fn definition(&self) -> String {
+        match &self.table {
+            Some(table) => table.source().to_string(),
+            None => self.to_string(),
+        }
+    }
+
+    pub fn range(&self) -> u32 {
+        self.range
+    }
+
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    pub fn include_center(&self) -> bool {
+        self.include_center
+    }
+
+    pub fn states(&self) -> u32 {
+        self.states
+    }
+
+    pub fn colors(&self) -> u32 {
+        self.colors
+    }
+
+    pub fn is_conway(&self) -> bool {
+        *self == Self::conway()
+    }
+
+    /// Largest neighbor count a cell can have under this rule.
+    pub fn max_count(&self) -> u32 {
+        self.birth.len() as u32 - 1
+    }
+
+    /// Whether a cell with `count` live neighbors is alive next generation.
+    pub fn next_state(&self, alive: bool, count: u32) -> bool {
+        let counts = if alive { &self.survival } else { &self.birth };
+        counts.get(count as usize).copied().unwrap_or(false)
+    }
+
+    /// Color of a cell born with `parents[c]` live neighbors of color `c`:
+    /// the commonest color, or on a tie the first color no parent has, if
+    /// any, else the lowest tied color.
+    pub fn birth_color(&self, parents: &[u32]) -> u8 {
+        let most = parents.iter().copied().max().unwrap_or(0);
+        let tied = parents.iter().filter(|&&count| count == most).count();
+        let color = if tied > 1 {
+            (0..self.colors as usize).find(|&color| parents.get(color).is_none_or(|&count| count == 0))
+        } else {
+            None
+        };
+        color.or_else(|| parents.iter().position(|&count| count == most)).unwrap_or(0) as u8
+    }
+
+    /// Offsets of every neighbor of the origin, excluding the origin itself.
+    pub fn offsets(&self) -> Vec<(i32, i32)> {
+        let range = self.range as i32;
+        let mut offsets = Vec::new();
+        for dy in -range..=range {
+            for dx in -range..=range {
+                let inside = match self.neighborhood {
+                    Neighborhood::Moore => true,
+                    Neighborhood::VonNeumann => dx.abs() + dy.abs() <= range,
+                    Neighborhood::Hexagonal => (dx - dy).abs() <= range,
+                };
+                if inside && (dx, dy) != (0, 0) {
+                    offsets.push((dx, dy));
+                }
+            }
+        }
+        offsets
+    }
+
+    /// Live neighbors of `(x, y)` as this rule counts them.
+    pub fn neighbor_count(&self, grid: &ChunkedGrid, x: i32, y: i32) -> u32 {
+        let neighbors = self.offsets().into_iter()
+            .filter(|(dx, dy)| grid.is_alive(x + dx, y + dy))
+            .count() as u32;
+        neighbors + (self.include_center && grid.is_alive(x, y)) as u32
+    }
+
+    /// Birth and survival counts as bitmasks, for radius-1 rules that fit the
+    /// bit-sliced stepper; `None` for anything larger.
+    pub(crate) fn small_masks(&self) -> Option<(u16, u16)> {
+        if self.range != 1 || self.include_center || self.table.is_some() {
+            return None;
+        }
+        let mask = |counts: &[bool]| counts.iter().enumerate()
+            .filter(|(_, on)| **on)
+            .fold(0u16, |mask, (count, _)| mask | 1 << count);
+        Some((mask(&self.birth), mask(&self.survival)))
+    }
+
+    fn parse_life_like(text: &str) -> Result<Self, RuleError> {
+        let (body, neighborhood) = if let Some(body) = text.strip_suffix('V') {
+            (body.strip_suffix('/').unwrap_or(body), Neighborhood::VonNeumann)
+        } else if let Some(body) = text.strip_suffix('H') {
+            (body.strip_suffix('/').unwrap_or(body), Neighborhood::Hexagonal)
+        } else {
+            (text, Neighborhood::Moore)
+        };
+        let max = neighborhood_size(1, neighborhood);
+
+        let (mut birth, mut survival, mut states) = (None, None, None);
+        for part in body.split('/') {
+            let (slot, digits) = match part.split_at_checked(1) {
+                Some(("B", digits)) => (&mut birth, digits),
+                Some(("S", digits)) => (&mut survival, digits),
+                Some(("C", count)) if states.is_none() => {
+                    states = Some(parse_states(count, text)?);
+                    continue;
+                }
+                _ => return Err(RuleError(format!("Expected B and S counts in rule '{}'", text))),
+            };
+            if slot.is_some() {
+                return Err(RuleError(format!("Rule '{}' lists the same counts twice", text)));
+            }
+            let counts = digits.chars()
+                .map(|digit| digit.to_digit(10).filter(|count| *count <= max)
+                    .ok_or_else(|| RuleError(format!("Invalid neighbor count '{}' in rule '{}'", digit, text))))
+                .collect::<Result<Vec<_>, _>>()?;
+            *slot = Some(counts);
+        }
+
+        let (Some(birth), Some(survival)) = (birth, survival) else {
+            return Err(RuleError(format!("Expected B and S counts in rule '{}'", text)));
+        };
+        let mut rule = Self::life_like(neighborhood, &birth, &survival);
+        rule.states = states.unwrap_or(2);
+        Ok(rule)
+    }
+
+    fn parse_larger_than_life(text: &str) -> Result<Self, RuleError> {
+        let field = |prefix: char| -> Result<&str, RuleError> {
+            let mut values = text.split(',').filter_map(|part| part.strip_prefix(prefix));
+            match (values.next(), values.next()) {
+                (Some(value), None) => Ok(value),
+                _ => Err(RuleError(format!("Rule '{}' needs exactly one {} field", text, prefix))),
+            }
+        };
+        let number = |prefix: char| -> Result<u32, RuleError> {
+            field(prefix)?.parse()
+                .map_err(|_| RuleError(format!("Invalid {} value in rule '{}'", prefix, text)))
+        };
+        if let Some(part) = text.split(',').find(|part| !part.starts_with(['R', 'C', 'M', 'S', 'B', 'N'])) {
+            return Err(RuleError(format!("Unexpected field '{}' in rule '{}'", part, text)));
+        }
+
+        let range = number('R')?;
+        if !(1..=MAX_RULE_RANGE).contains(&range) {
+            return Err(RuleError(format!("Range must be between 1 and {}", MAX_RULE_RANGE)));
+        }
+        let states = match field('C')? {
+            "0" => 2,
+            count => parse_states(count, text)?,
+        };
+        let include_center = match number('M')? {
+            0 => false,
+            1 => true,
+            _ => return Err(RuleError("M must be 0 or 1".to_string())),
+        };
+        let neighborhood = match field('N')? {
+            "M" => Neighborhood::Moore,
+            "N" => Neighborhood::VonNeumann,
+            "H" => Neighborhood::Hexagonal,
+            other => return Err(RuleError(format!("Unknown neighborhood '{}'; expected NM, NN or NH", other))),
+        };
+
+        let mut rule = Self::empty(range, neighborhood, include_center);
+        rule.states = states;
+        let max = rule.max_count();
+        for (prefix, counts) in [('B', &mut rule.birth), ('S', &mut rule.survival)] {
+            let value = field(prefix)?;
+            if value.is_empty() {
+                continue;
+            }
+            let (low, high) = value.split_once("..").unwrap_or((value, value));
+            let bound = |bound: &str| bound.parse::<u32>().ok().filter(|count| *count <= max)
+                .ok_or_else(|| RuleError(format!("Invalid {} range '{}'; counts go up to {}", prefix, value, max)));
+            let (low, high) = (bound(low)?, bound(high)?);
+            for count in low..=high {
+                counts[count as usize] = true;
+            }
+        }
+        Ok(rule)
+    }
+}
+
+/// The state count of a `C` field: 2 for an ordinary rule, more for Generations.
+fn parse_states(count: &str, text: &str) -> Result<u32, RuleError> {
+    count.parse().ok().filter(|states| (2..=MAX_RULE_STATES).contains(states))
+        .ok_or_else(|| RuleError(format!("Invalid state count '{}' in rule '{}'; expected 2 to {}", count, text, MAX_RULE_STATES)))
+}
+
+/// Cells in a neighborhood of the given radius, not counting the center.
+fn neighborhood_size(range: u32, neighborhood: Neighborhood) -> u32 {
+    match neighborhood {
+        Neighborhood::Moore => (2 * range + 1).pow(2) - 1,
+        Neighborhood::VonNeumann => 2 * range * (range + 1),
+        Neighborhood::Hexagonal => 3 * range * (range + 1),
+    }
+}
+
+impl FromStr for Rule {
+    type Err = RuleError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if text.trim_start().starts_with("@RULE") {
+            return RuleTable::parse(text).map(Self::from_table);
+        }
+        if let Some(handle) = find_automaton(text) {
+            return Ok(Self::from_automaton(handle));
+        }
+        let text = text.trim().to_ascii_uppercase();
+        if let Some(&(_, colors)) = COLOR_RULES.iter().find(|(name, _)| name.to_ascii_uppercase() == text) {
+            return Ok(Self { colors, ..Self::conway() });
+        }
+        let rule = if text.starts_with('R') {
+            Self::parse_larger_than_life(&text)?
+        } else {
+            Self::parse_life_like(&text)?
+        };
+        // Birth from nothing would fill the infinite plane in one step
+        if rule.birth[0] {
+            return Err(RuleError("Rules with birth on 0 neighbors are not supported".to_string()));
+        }
+        Ok(rule)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(table) = &self.table {
+            return write!(f, "{}", table);
+        }
+        if let Some(automaton) = self.automaton() {
+            return f.write_str(automaton.name().trim());
+        }
+        let counts = |counts: &[bool]| counts.iter().enumerate()
+            .filter(|(_, on)| **on)
+            .map(|(count, _)| count as u32)
+            .collect::<Vec<_>>();
+        let (birth, survival) = (counts(&self.birth), counts(&self.survival));
+
+        if let Some((name, _)) = COLOR_RULES.iter().find(|(_, colors)| *colors == self.colors) {
+            return f.write_str(name);
+        }
+
+        if self.range == 1 && !self.include_center {
+            let digits = |counts: &[u32]| counts.iter().map(u32::to_string).collect::<String>();
+            write!(f, "B{}/S{}", digits(&birth), digits(&survival))?;
+            if self.states > 2 {
+                write!(f, "/C{}", self.states)?;
+            }
+            match self.neighborhood {
+                Neighborhood::Moore => {}
+                Neighborhood::VonNeumann => f.write_str("V")?,
+                Neighborhood::Hexagonal => f.write_str("H")?,
+            }
+            return Ok(());
+        }
+
+        // Larger than Life notation only holds one contiguous range each
+        let span = |counts: &[u32]| match (counts.first(), counts.last()) {
+            (Some(low), Some(high)) if low == high => low.to_string(),
+            (Some(low), Some(high)) => format!("{}..{}", low, high),
+            _ => String::new(),
+        };
+        write!(
+            f,
+            "R{},C{},M{},S{},B{},N{}",
+            self.range,
+            if self.states > 2 { self.states } else { 0 },
+            self.include_center as u8,
+            span(&survival),
+            span(&birth),
+            match self.neighborhood {
+                Neighborhood::Moore => "M",
+                Neighborhood::VonNeumann => "N",
+                Neighborhood::Hexagonal => "H",
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_life_like_rules() {
+        let rule: Rule = "b3/s23".parse().unwrap();
+        assert!(rule.is_conway());
+        assert_eq!(rule.to_string(), "B3/S23");
+
+        let highlife: Rule = "S23/B36".parse().unwrap();
+        assert_eq!(highlife.to_string(), "B36/S23");
+        assert!(highlife.next_state(false, 6));
+
+        let von_neumann: Rule = "B1/S1V".parse().unwrap();
+        assert_eq!(von_neumann.neighborhood(), Neighborhood::VonNeumann);
+        assert_eq!(von_neumann.offsets().len(), 4);
+        assert_eq!(von_neumann.to_string(), "B1/S1V");
+    }
+
+    #[test]
+    fn test_parses_larger_than_life_rules() {
+        let bosco: Rule = "R5,C0,M1,S34..58,B34..45,NM".parse().unwrap();
+        assert_eq!((bosco.range(), bosco.max_count()), (5, 121));
+        assert!(bosco.next_state(true, 34) && bosco.next_state(true, 58) && !bosco.next_state(true, 59));
+        assert!(bosco.next_state(false, 45) && !bosco.next_state(false, 33));
+        assert_eq!(bosco.to_string(), "R5,C0,M1,S34..58,B34..45,NM");
+
+        let diamond: Rule = "R2,C0,M0,S2..3,B3,NN".parse().unwrap();
+        assert_eq!(diamond.offsets().len(), 12);
+        assert_eq!(diamond.to_string(), "R2,C0,M0,S2..3,B3,NN");
+
+        // A radius-1 rule written in LtL notation is just a life-like rule
+        let life: Rule = "R1,C0,M0,S2..3,B3,NM".parse().unwrap();
+        assert!(life.is_conway());
+    }
+
+    #[test]
+    fn test_parses_hexagonal_rules() {
+        let hex: Rule = "B2/S34H".parse().unwrap();
+        assert_eq!(hex.neighborhood(), Neighborhood::Hexagonal);
+        assert_eq!(hex.max_count(), 6);
+        let mut offsets = hex.offsets();
+        offsets.sort_unstable();
+        assert_eq!(offsets, vec![(-1, -1), (-1, 0), (0, -1), (0, 1), (1, 0), (1, 1)]);
+        assert_eq!(hex.to_string(), "B2/S34H");
+        assert_eq!("B2/S/C3H".parse::<Rule>().unwrap().to_string(), "B2/S/C3H");
+
+        let larger: Rule = "R2,C0,M0,S2..7,B4..5,NH".parse().unwrap();
+        assert_eq!((larger.offsets().len(), larger.to_string().as_str()), (18, "R2,C0,M0,S2..7,B4..5,NH"));
+        assert!("B7/S23H".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn test_parses_generations_rules() {
+        let brians_brain: Rule = "B2/S/C3".parse().unwrap();
+        assert_eq!(brians_brain.states(), 3);
+        assert_eq!(brians_brain.to_string(), "B2/S/C3");
+
+        let star_wars: Rule = "s345/b2/c4".parse().unwrap();
+        assert_eq!((star_wars.states(), star_wars.to_string().as_str()), (4, "B2/S345/C4"));
+
+        let larger: Rule = "R2,C5,M0,S2..3,B3,NM".parse().unwrap();
+        assert_eq!((larger.states(), larger.to_string().as_str()), (5, "R2,C5,M0,S2..3,B3,NM"));
+
+        // C2 is an ordinary two-state rule
+        assert!("B3/S23/C2".parse::<Rule>().unwrap().is_conway());
+    }
+
+    #[test]
+    fn test_parses_color_rules() {
+        let immigration: Rule = "immigration".parse().unwrap();
+        assert_eq!((immigration.colors(), immigration.to_string().as_str()), (2, "Immigration"));
+        assert!(!immigration.is_conway());
+        assert!(immigration.next_state(false, 3) && immigration.next_state(true, 2));
+
+        let quad_life: Rule = "QuadLife".parse().unwrap();
+        assert_eq!((quad_life.colors(), quad_life.to_string().as_str()), (4, "QuadLife"));
+        assert_eq!(Rule::conway().colors(), 1);
+    }
+
+    #[test]
+    fn test_birth_color_follows_majority() {
+        let immigration: Rule = "Immigration".parse().unwrap();
+        assert_eq!(immigration.birth_color(&[1, 2]), 1);
+        assert_eq!(immigration.birth_color(&[3, 0]), 0);
+
+        // Three different parents give the fourth color
+        let quad_life: Rule = "QuadLife".parse().unwrap();
+        assert_eq!(quad_life.birth_color(&[1, 0, 1, 1]), 1);
+        assert_eq!(quad_life.birth_color(&[0, 2, 0, 1]), 1);
+    }
+
+    #[test]
+    fn test_parses_rule_tables() {
+        let source = "@RULE Seeds2\n@TABLE\nn_states:2\nneighborhood:vonNeumann\nsymmetries:permute\n0,1,1,0,0,1\n1,0,0,0,0,1\n";
+        let seeds: Rule = source.parse().unwrap();
+        assert_eq!((seeds.to_string().as_str(), seeds.states()), ("Seeds2", 2));
+        assert_eq!(seeds.neighborhood(), Neighborhood::VonNeumann);
+        assert_eq!(seeds.definition(), source);
+        assert_eq!(seeds.definition().parse::<Rule>().unwrap(), seeds);
+        assert!(seeds.small_masks().is_none());
+        assert_eq!(Rule::conway().definition(), "B3/S23");
+    }
+
+    #[test]
+    fn test_rejects_invalid_rules() {
+        for rule in ["", "B3", "B9/S23", "B3/S23/B3", "B0/S8", "R0,C0,M0,S2..3,B3,NM", "R11,C0,M0,S2..3,B3,NM",
+                     "B2/S/C1", "B2/S/C256", "B2/S/C3/C4", "R2,C1,M0,S2..3,B3,NM", "R2,C0,M0,S2..30,B3,NM", "R2,C0,M0,S2..3,B3,NX", "R2,C0,M0,B3,NM"] {
+            assert!(rule.parse::<Rule>().is_err(), "{:?} should be rejected", rule);
+        }
+    }
+
+    #[test]
+    fn test_counts_neighbors_in_neighborhood() {
+        let mut grid = ChunkedGrid::new();
+        for (x, y) in [(0, 0), (2, 0), (1, 1), (0, 2)] {
+            grid.set(x, y, true);
+        }
+        let moore: Rule = "R2,C0,M1,S2..3,B3,NM".parse().unwrap();
+        assert_eq!(moore.neighbor_count(&grid, 0, 0), 4);
+        let von_neumann: Rule = "R2,C0,M0,S2..3,B3,NN".parse().unwrap();
+        assert_eq!(von_neumann.neighbor_count(&grid, 0, 0), 3);
+    }
+}
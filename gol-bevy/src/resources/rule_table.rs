@@ -0,0 +1,523 @@
+//! Golly rule tables: arbitrary cellular automata from `@TABLE` files.
+//!
+//! A `.rule` file names the rule in an `@RULE` line and describes it in an
+//! `@TABLE` section:
+//!
+//! ```text
+//! @RULE WireWorld
+//! @TABLE
+//! n_states:4
+//! neighborhood:Moore
+//! symmetries:permute
+//! var a={0,1,2,3}
+//! ...
+//! 1,a,b,c,d,e,f,g,h,2
+//! ```
+//!
+//! Each transition lists the cell's current state, its neighbors' states
+//! clockwise from north in Golly's order (`N,NE,E,SE,S,SW,W,NW` for Moore,
+//! `N,E,S,W` for von Neumann and `N,E,SE,S,W,NW` for hexagonal) and the
+//! cell's next state. Entries may be states, `{..}` sets of states or
+//! variables; a variable used twice in one transition stands for the same
+//! state both times. The first transition that matches under any of the
+//! table's symmetries wins, and cells no transition matches keep their state.
+//! Other sections such as `@COLORS` are ignored.
+//!
+//! Tables are interpreted: each distinct neighborhood is looked up once per
+//! generation and the answer reused for every cell that has it.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::resources::{ChunkedGrid, Neighborhood, RuleError, MAX_RULE_STATES};
+
+/// States a transition entry accepts, as a 256-bit set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct StateSet([u64; 4]);
+
+impl StateSet {
+    fn insert(&mut self, state: u8) {
+        self.0[state as usize / 64] |= 1 << (state % 64);
+    }
+
+    fn contains(&self, state: u8) -> bool {
+        self.0[state as usize / 64] & (1 << (state % 64)) != 0
+    }
+
+    fn union(&mut self, other: &StateSet) {
+        for (word, other) in self.0.iter_mut().zip(other.0) {
+            *word |= other;
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    states: StateSet,
+    /// Binding slot of a variable, shared by every use of it in a transition.
+    slot: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Output {
+    State(u8),
+    Slot(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Transition {
+    /// The cell followed by its neighbors.
+    inputs: Vec<Entry>,
+    output: Output,
+    slots: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Symmetry {
+    /// Neighbor orders a transition is also tried in.
+    Orders(Vec<Vec<usize>>),
+    /// Any order of the neighbors matches.
+    Permute,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleTable {
+    name: String,
+    states: u32,
+    neighborhood: Neighborhood,
+    symmetry: Symmetry,
+    transitions: Vec<Transition>,
+    /// The file the table was read from, so it can be saved and read again.
+    source: String,
+}
+
+impl RuleTable {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn states(&self) -> u32 {
+        self.states
+    }
+
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Neighbor offsets in Golly's order, clockwise from north.
+    pub fn offsets(&self) -> &'static [(i32, i32)] {
+        neighbor_offsets(self.neighborhood)
+    }
+
+    /// Next state of a cell given its state followed by its neighbors'.
+    pub fn next_state(&self, cells: &[u8]) -> u8 {
+        let (center, neighbors) = (cells[0], &cells[1..]);
+        for transition in &self.transitions {
+            let mut bindings = vec![None; transition.slots];
+            if !bind(&transition.inputs[0], center, &mut bindings) {
+                continue;
+            }
+            let matched = match &self.symmetry {
+                Symmetry::Orders(orders) => orders.iter().find_map(|order| {
+                    let mut bindings = bindings.clone();
+                    let all = transition.inputs[1..].iter().zip(order)
+                        .all(|(entry, &neighbor)| bind(entry, neighbors[neighbor], &mut bindings));
+                    all.then_some(bindings)
+                }),
+                Symmetry::Permute => permute(&transition.inputs[1..], neighbors, 0, &mut bindings).then_some(bindings),
+            };
+            if let Some(bindings) = matched {
+                return match transition.output {
+                    Output::State(state) => state,
+                    Output::Slot(slot) => bindings[slot].unwrap_or(center),
+                };
+            }
+        }
+        center
+    }
+
+    /// Compute the next generation of `grid`.
+    pub fn step(&self, grid: &ChunkedGrid) -> ChunkedGrid {
+        step_transitions(grid, self.offsets(), |cells| self.next_state(cells))
+    }
+
+    /// Read a rule from the text of a `.rule` file.
+    pub fn parse(text: &str) -> Result<Self, RuleError> {
+        let mut name = None;
+        let (mut in_table, mut seen_table) = (false, false);
+        let mut states = None;
+        let mut neighborhood = Neighborhood::Moore;
+        let mut symmetry_name = "none".to_string();
+        let mut variables: HashMap<String, StateSet> = HashMap::new();
+        let mut lines = Vec::new();
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('@') {
+                in_table = section == "TABLE";
+                seen_table |= in_table;
+                if let Some(rule_name) = section.strip_prefix("RULE") {
+                    name = Some(rule_name.trim().to_string());
+                }
+                continue;
+            }
+            if !in_table {
+                continue;
+            }
+
+            if let Some(count) = line.strip_prefix("n_states:") {
+                let count = count.trim().parse::<u32>().ok()
+                    .filter(|count| (2..=MAX_RULE_STATES).contains(count))
+                    .ok_or_else(|| RuleError(format!("n_states must be from 2 to {}", MAX_RULE_STATES)))?;
+                states = Some(count);
+            } else if let Some(kind) = line.strip_prefix("neighborhood:") {
+                neighborhood = match kind.trim() {
+                    "Moore" => Neighborhood::Moore,
+                    "vonNeumann" => Neighborhood::VonNeumann,
+                    "hexagonal" => Neighborhood::Hexagonal,
+                    other => return Err(RuleError(format!("Unsupported neighborhood '{}'", other))),
+                };
+            } else if let Some(symmetries) = line.strip_prefix("symmetries:") {
+                symmetry_name = symmetries.trim().to_string();
+            } else if let Some(definition) = line.strip_prefix("var ") {
+                let states = states.ok_or_else(|| RuleError("n_states must come before variables".to_string()))?;
+                let (variable, values) = definition.split_once('=')
+                    .ok_or_else(|| RuleError(format!("Expected 'var name={{...}}', found '{}'", line)))?;
+                let set = parse_set(values.trim(), states, &variables)?;
+                variables.insert(variable.trim().to_string(), set);
+            } else {
+                lines.push(line);
+            }
+        }
+
+        let name = name.filter(|name| !name.is_empty())
+            .ok_or_else(|| RuleError("Rule file has no @RULE name".to_string()))?;
+        if !seen_table {
+            return Err(RuleError(format!("Rule '{}' has no @TABLE section", name)));
+        }
+        let states = states.ok_or_else(|| RuleError(format!("Rule '{}' does not give n_states", name)))?;
+        let neighbors = neighbor_offsets(neighborhood).len();
+        let symmetry = parse_symmetry(&symmetry_name, neighbors)?;
+        let transitions = lines.into_iter()
+            .map(|line| parse_transition(line, states, neighbors, &variables))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let table = Self { name, states, neighborhood, symmetry, transitions, source: text.to_string() };
+        // Like birth on 0, empty space coming alive would fill the plane
+        if table.next_state(&vec![0; neighbors + 1]) != 0 {
+            return Err(RuleError(format!("Rule '{}' brings empty space to life, which is not supported", table.name)));
+        }
+        Ok(table)
+    }
+}
+
+impl fmt::Display for RuleTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// Compute the next generation of `grid` cell by cell: `next_state` gets each
+/// cell's state followed by its neighbors' at `offsets`, and is asked once per
+/// distinct neighborhood.
+pub fn step_transitions(grid: &ChunkedGrid, offsets: &[(i32, i32)], next_state: impl Fn(&[u8]) -> u8) -> ChunkedGrid {
+    let occupied: Vec<(i32, i32)> = grid.iter_live().chain(grid.iter_decaying().map(|(cell, _)| cell)).collect();
+    let mut candidates: HashSet<(i32, i32)> = HashSet::with_capacity(occupied.len() * (offsets.len() + 1));
+    for &(x, y) in &occupied {
+        candidates.insert((x, y));
+        candidates.extend(offsets.iter().map(|(dx, dy)| (x + dx, y + dy)));
+    }
+
+    let mut known: HashMap<Vec<u8>, u8> = HashMap::new();
+    let mut cells = Vec::with_capacity(offsets.len() + 1);
+    let mut next = ChunkedGrid::new();
+    for (x, y) in candidates {
+        cells.clear();
+        cells.push(grid.state(x, y));
+        cells.extend(offsets.iter().map(|(dx, dy)| grid.state(x + dx, y + dy)));
+        let state = match known.get(&cells) {
+            Some(&state) => state,
+            None => *known.entry(cells.clone()).or_insert_with(|| next_state(&cells)),
+        };
+        if state != 0 {
+            next.set_state(x, y, state);
+        }
+    }
+    next
+}
+
+/// Neighbor offsets in Golly's order, clockwise from north.
+pub fn neighbor_offsets(neighborhood: Neighborhood) -> &'static [(i32, i32)] {
+    match neighborhood {
+        Neighborhood::Moore => &[(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)],
+        Neighborhood::VonNeumann => &[(0, -1), (1, 0), (0, 1), (-1, 0)],
+        Neighborhood::Hexagonal => &[(0, -1), (1, 0), (1, 1), (0, 1), (-1, 0), (-1, -1)],
+    }
+}
+
+/// Match `state` against an entry, binding its variable on first use.
+fn bind(entry: &Entry, state: u8, bindings: &mut [Option<u8>]) -> bool {
+    if !entry.states.contains(state) {
+        return false;
+    }
+    match entry.slot {
+        Some(slot) => match bindings[slot] {
+            Some(bound) => bound == state,
+            None => {
+                bindings[slot] = Some(state);
+                true
+            }
+        },
+        None => true,
+    }
+}
+
+/// Match entries to neighbors in any order, trying each unused neighbor for
+/// each entry in turn.
+fn permute(entries: &[Entry], neighbors: &[u8], used: u32, bindings: &mut [Option<u8>]) -> bool {
+    let Some((entry, rest)) = entries.split_first() else {
+        return true;
+    };
+    for (index, &state) in neighbors.iter().enumerate() {
+        if used & (1 << index) != 0 {
+            continue;
+        }
+        let mut attempt = bindings.to_vec();
+        if bind(entry, state, &mut attempt) && permute(rest, neighbors, used | (1 << index), &mut attempt) {
+            bindings.copy_from_slice(&attempt);
+            return true;
+        }
+    }
+    false
+}
+
+/// Neighbor orders for Golly's `rotateN`, `rotateNreflect`,
+/// `reflect_horizontal` and `none` symmetries. Neighbors are listed clockwise,
+/// so a rotation shifts the order and a reflection reverses it about north.
+fn parse_symmetry(name: &str, neighbors: usize) -> Result<Symmetry, RuleError> {
+    let rotate = |order: &[usize], by: usize| order.iter().map(|index| (index + by) % neighbors).collect::<Vec<_>>();
+    let identity: Vec<usize> = (0..neighbors).collect();
+    let mirror: Vec<usize> = (0..neighbors).map(|index| (neighbors - index) % neighbors).collect();
+
+    let orders = match name {
+        "none" => vec![identity],
+        "permute" => return Ok(Symmetry::Permute),
+        "reflect_horizontal" => vec![identity, mirror],
+        _ => {
+            let (count, reflect) = match name.strip_prefix("rotate") {
+                Some(rest) => match rest.strip_suffix("reflect") {
+                    Some(count) => (count, true),
+                    None => (rest, false),
+                },
+                None => return Err(RuleError(format!("Unknown symmetries '{}'", name))),
+            };
+            let rotations = count.parse::<usize>().ok()
+                .filter(|&rotations| rotations >= 2 && neighbors.is_multiple_of(rotations))
+                .ok_or_else(|| RuleError(format!("Symmetries '{}' do not fit a {}-cell neighborhood", name, neighbors)))?;
+            let step = neighbors / rotations;
+            let mut orders: Vec<Vec<usize>> = (0..rotations).map(|turn| rotate(&identity, turn * step)).collect();
+            if reflect {
+                orders.extend((0..rotations).map(|turn| rotate(&mirror, turn * step)));
+            }
+            orders
+        }
+    };
+    Ok(Symmetry::Orders(orders))
+}
+
+/// A state, a `{..}` set of states and variables, or a variable name.
+fn parse_set(text: &str, states: u32, variables: &HashMap<String, StateSet>) -> Result<StateSet, RuleError> {
+    let mut set = StateSet::default();
+    let items = match text.strip_prefix('{').and_then(|inner| inner.strip_suffix('}')) {
+        Some(inner) => inner.split(',').map(str::trim).collect(),
+        None => vec![text],
+    };
+    for item in items {
+        if let Some(variable) = variables.get(item) {
+            set.union(variable);
+        } else {
+            set.insert(parse_state(item, states)?);
+        }
+    }
+    Ok(set)
+}
+
+fn parse_state(text: &str, states: u32) -> Result<u8, RuleError> {
+    text.parse::<u32>().ok()
+        .filter(|state| *state < states)
+        .map(|state| state as u8)
+        .ok_or_else(|| RuleError(format!("'{}' is not a state or variable", text)))
+}
+
+/// Split a transition into entries at top-level commas, or into single
+/// digits when written without commas.
+fn split_entries(line: &str) -> Vec<String> {
+    if !line.contains(',') {
+        return line.chars().filter(|c| !c.is_whitespace()).map(String::from).collect();
+    }
+    let (mut entries, mut current, mut depth) = (Vec::new(), String::new(), 0);
+    for c in line.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                entries.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    entries.push(current.trim().to_string());
+    entries
+}
+
+fn parse_transition(line: &str, states: u32, neighbors: usize, variables: &HashMap<String, StateSet>) -> Result<Transition, RuleError> {
+    let entries = split_entries(line);
+    if entries.len() != neighbors + 2 {
+        return Err(RuleError(format!("Transition '{}' should have {} entries", line, neighbors + 2)));
+    }
+
+    let mut slots: HashMap<&str, usize> = HashMap::new();
+    let inputs = entries[..=neighbors].iter().map(|entry| {
+        let slot = variables.contains_key(entry.as_str()).then(|| {
+            let next = slots.len();
+            *slots.entry(entry.as_str()).or_insert(next)
+        });
+        Ok(Entry { states: parse_set(entry, states, variables)?, slot })
+    }).collect::<Result<Vec<_>, RuleError>>()?;
+
+    let output = &entries[neighbors + 1];
+    let output = match slots.get(output.as_str()) {
+        Some(&slot) => Output::Slot(slot),
+        None => Output::State(parse_state(output, states)?),
+    };
+    Ok(Transition { inputs, output, slots: slots.len() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIREWORLD: &str = "@RULE WireWorld
+A simple circuit simulator.
+@TABLE
+n_states:4
+neighborhood:Moore
+symmetries:permute
+var a={0,1,2,3}
+var b={a}
+var c={a}
+var d={a}
+var e={a}
+var f={a}
+var g={a}
+var h={a}
+var p={0,2,3}
+var q={p}
+var r={p}
+var s={p}
+var t={p}
+var u={p}
+var v={p}
+# electron head becomes tail, tail becomes wire
+1,a,b,c,d,e,f,g,h,2
+2,a,b,c,d,e,f,g,h,3
+# wire with one or two heads becomes a head
+3,1,p,q,r,s,t,u,v,1
+3,1,1,p,q,r,s,t,u,1
+@COLORS
+1 255 255 0
+";
+
+    fn wireworld() -> RuleTable {
+        RuleTable::parse(WIREWORLD).unwrap()
+    }
+
+    #[test]
+    fn test_parses_rule_file() {
+        let table = wireworld();
+        assert_eq!((table.name(), table.states(), table.neighborhood()), ("WireWorld", 4, Neighborhood::Moore));
+        assert_eq!(table.transitions.len(), 4);
+        assert_eq!(table.source(), WIREWORLD);
+    }
+
+    #[test]
+    fn test_transitions_match_under_permutation() {
+        let table = wireworld();
+        // A wire cell with one head anywhere around it fires
+        assert_eq!(table.next_state(&[3, 0, 0, 0, 0, 0, 1, 0, 0]), 1);
+        assert_eq!(table.next_state(&[3, 0, 1, 0, 0, 0, 1, 0, 0]), 1);
+        // Three heads are too many
+        assert_eq!(table.next_state(&[3, 1, 1, 1, 0, 0, 0, 0, 0]), 3);
+        assert_eq!(table.next_state(&[1, 3, 3, 3, 3, 3, 3, 3, 3]), 2);
+        assert_eq!(table.next_state(&[0, 1, 1, 1, 0, 0, 0, 0, 0]), 0);
+    }
+
+    #[test]
+    fn test_wireworld_electron_travels_along_wire() {
+        let table = wireworld();
+        let mut grid = ChunkedGrid::new();
+        grid.set_state(0, 0, 2);
+        grid.set_state(1, 0, 1);
+        for x in 2..6 {
+            grid.set_state(x, 0, 3);
+        }
+
+        let next = table.step(&grid);
+        let states: Vec<u8> = (0..6).map(|x| next.state(x, 0)).collect();
+        assert_eq!(states, vec![3, 2, 1, 3, 3, 3]);
+        assert_eq!(next.state(0, 1), 0);
+    }
+
+    #[test]
+    fn test_bound_variables_and_rotations() {
+        let table = RuleTable::parse("@RULE Echo
+@TABLE
+n_states:3
+neighborhood:vonNeumann
+symmetries:rotate4
+var a={1,2}
+# A dead cell copies a state it sees to the north and south
+0,a,0,a,0,a
+").unwrap();
+        assert_eq!(table.next_state(&[0, 2, 0, 2, 0]), 2);
+        assert_eq!(table.next_state(&[0, 0, 1, 0, 1]), 1);
+        assert_eq!(table.next_state(&[0, 1, 0, 2, 0]), 0);
+    }
+
+    #[test]
+    fn test_compact_transitions_and_reflection() {
+        let table = RuleTable::parse("@RULE Corner\n@TABLE\nn_states:2\nneighborhood:Moore\nsymmetries:reflect_horizontal\n0110000001 # N and NE live make a birth\n").unwrap();
+        assert_eq!(table.next_state(&[0, 1, 1, 0, 0, 0, 0, 0, 0]), 1);
+        // Mirrored onto north and north-west
+        assert_eq!(table.next_state(&[0, 1, 0, 0, 0, 0, 0, 0, 1]), 1);
+        assert_eq!(table.next_state(&[0, 0, 0, 0, 0, 1, 1, 0, 0]), 0);
+    }
+
+    #[test]
+    fn test_rejects_invalid_tables() {
+        let table = |body: &str| RuleTable::parse(&format!("@RULE Bad\n@TABLE\n{}", body));
+        for body in [
+            "neighborhood:Moore\n0,0,0,0,0,0,0,0,0,1\n",
+            "n_states:2\nneighborhood:Moore\n0,0,0,0,0,0,0,0,0,1\n",
+            "n_states:2\nneighborhood:Moore\n0,1,0,0,0,0,0,0,1\n",
+            "n_states:2\nneighborhood:Moore\n0,1,0,0,0,0,0,0,0,2\n",
+            "n_states:2\nneighborhood:vonNeumann\nsymmetries:rotate8\n0,1,0,0,0,1\n",
+            "n_states:2\nneighborhood:oneDimensional\n",
+            "n_states:2\nneighborhood:Moore\n0,x,0,0,0,0,0,0,0,1\n",
+        ] {
+            assert!(table(body).is_err(), "{:?} should be rejected", body);
+        }
+        assert!(RuleTable::parse("@TABLE\nn_states:2\n").is_err());
+        assert!(RuleTable::parse("@RULE NoTable\n").is_err());
+    }
+}
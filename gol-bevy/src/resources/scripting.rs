@@ -0,0 +1,278 @@
+//! Rhai scripts hooked into stepping.
+//!
+//! A script given with `--script` may define either hook:
+//!
+//! ```text
+//! fn on_generation(grid) { ... }
+//! fn on_stabilize(grid, reason, period) { ... }
+//! ```
+//!
+//! `on_generation` runs after every generation a simulation steps, and
+//! `on_stabilize` once an unattended run finds the grid dead (`reason` is
+//! `"extinct"`, `period` 0) or repeating (`"cycle"`). The `grid` they get
+//! reads and changes the simulation, so a script can seed, perturb or
+//! measure it without rebuilding the server:
+//!
+//! - `grid.generation`, `grid.width`, `grid.height`, `grid.population`
+//! - `grid.is_alive(x, y)` and `grid.set(x, y, alive)`; cells outside the
+//!   grid are dead and setting them does nothing
+//! - `grid.live_cells()`, an array of `[x, y]` pairs
+//! - `grid.random()` in `0.0..1.0` and `grid.chance(p)`, drawn from the
+//!   simulation's seed and generation so a rerun draws the same numbers
+//!
+//! `print` writes to the server log. Each call may run at most
+//! [`MAX_SCRIPT_OPERATIONS`] operations, so a runaway loop fails the hook
+//! instead of stalling the simulation. Requires the `scripting` feature.
+
+/// Operations one hook call may run before it is aborted.
+pub const MAX_SCRIPT_OPERATIONS: u64 = 10_000_000;
+
+/// What a hook call did: the cells it set alive or dead, in the order it set
+/// them, and why it failed if it did. Cells set before a failure stay set.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ScriptRun {
+    pub edits: Vec<(i32, i32, bool)>,
+    pub error: Option<String>,
+}
+
+#[cfg(feature = "scripting")]
+pub use enabled::ScriptHooks;
+#[cfg(not(feature = "scripting"))]
+pub use disabled::ScriptHooks;
+
+#[cfg(feature = "scripting")]
+mod enabled {
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    
+    use rhai::{Array, CallFnOptions, Dynamic, Engine, Scope, AST, FLOAT, INT};
+    
+    use super::{ScriptRun, MAX_SCRIPT_OPERATIONS};
+    use crate::resources::{ChunkedGrid, SeededRng, SimulationData, StopReason};
+    
+    /// Decorrelates the random numbers of consecutive generations.
+    const GENERATION_MIX: u64 = 0x9e3779b97f4a7c15;
+    
+    /// A compiled script and the hooks it defines.
+    pub struct ScriptHooks {
+        engine: Engine,
+        ast: AST,
+        on_generation: bool,
+        on_stabilize: bool,
+    }
+    
+    /// The simulation state a hook sees, moved in for the call and back out after it.
+    struct GridAccess {
+        cells: ChunkedGrid,
+        width: i32,
+        height: i32,
+        generation: u64,
+        rng: SeededRng,
+        edits: Vec<(i32, i32, bool)>,
+    }
+    
+    impl GridAccess {
+        fn in_bounds(&self, x: INT, y: INT) -> bool {
+            x >= 0 && x < self.width as INT && y >= 0 && y < self.height as INT
+        }
+    }
+    
+    /// The `grid` argument of a hook.
+    #[derive(Clone)]
+    struct ScriptGrid(Arc<Mutex<GridAccess>>);
+    
+    impl ScriptGrid {
+        fn with<T>(&self, f: impl FnOnce(&mut GridAccess) -> T) -> T {
+            f(&mut self.0.lock().unwrap())
+        }
+    }
+    
+    fn new_engine() -> Engine {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_SCRIPT_OPERATIONS);
+        engine.on_print(|text| println!("[script] {}", text));
+        engine.on_debug(|text, _, position| println!("[script] {} {}", position, text));
+        
+        engine.register_type_with_name::<ScriptGrid>("Grid")
+            .register_get("generation", |grid: &mut ScriptGrid| grid.with(|access| access.generation as INT))
+            .register_get("width", |grid: &mut ScriptGrid| grid.with(|access| access.width as INT))
+            .register_get("height", |grid: &mut ScriptGrid| grid.with(|access| access.height as INT))
+            .register_get("population", |grid: &mut ScriptGrid| grid.with(|access| access.cells.population() as INT))
+            .register_fn("is_alive", |grid: &mut ScriptGrid, x: INT, y: INT| {
+                grid.with(|access| access.in_bounds(x, y) && access.cells.is_alive(x as i32, y as i32))
+            })
+            .register_fn("set", |grid: &mut ScriptGrid, x: INT, y: INT, alive: bool| {
+                grid.with(|access| {
+                    if access.in_bounds(x, y) && access.cells.set(x as i32, y as i32, alive) {
+                        access.edits.push((x as i32, y as i32, alive));
+                    }
+                })
+            })
+            .register_fn("live_cells", |grid: &mut ScriptGrid| {
+                grid.with(|access| {
+                    access.cells.iter_live()
+                        .map(|(x, y)| Dynamic::from_array(vec![Dynamic::from(x as INT), Dynamic::from(y as INT)]))
+                        .collect::<Array>()
+                })
+            })
+            .register_fn("random", |grid: &mut ScriptGrid| {
+                grid.with(|access| (access.rng.next_u64() >> 11) as FLOAT / (1u64 << 53) as FLOAT)
+            })
+            .register_fn("chance", |grid: &mut ScriptGrid, probability: FLOAT| {
+                grid.with(|access| access.rng.chance(probability))
+            });
+        engine
+    }
+    
+    impl ScriptHooks {
+        pub fn load(path: &Path) -> Result<Self, String> {
+            let source = std::fs::read_to_string(path)
+                .map_err(|error| format!("failed to read script {}: {}", path.display(), error))?;
+            Self::compile(&source).map_err(|error| format!("script {}: {}", path.display(), error))
+        }
+        
+        /// Compile a script and run its top-level statements once.
+        pub fn compile(source: &str) -> Result<Self, String> {
+            let engine = new_engine();
+            let ast = engine.compile(source).map_err(|error| error.to_string())?;
+            engine.run_ast(&ast).map_err(|error| error.to_string())?;
+            
+            let defines = |name: &str, params: usize| ast.iter_functions().any(|function| function.name == name && function.params.len() == params);
+            let on_generation = defines("on_generation", 1);
+            let on_stabilize = defines("on_stabilize", 3);
+            Ok(Self { engine, ast, on_generation, on_stabilize })
+        }
+        
+        /// Run `on_generation` on a simulation that just stepped.
+        pub fn on_generation(&self, simulation: &mut SimulationData) -> ScriptRun {
+            if !self.on_generation {
+                return ScriptRun::default();
+            }
+            self.call(simulation, "on_generation", |grid| vec![Dynamic::from(grid)])
+        }
+        
+        /// Run `on_stabilize` on a simulation an unattended run just paused.
+        pub fn on_stabilize(&self, simulation: &mut SimulationData, reason: StopReason) -> ScriptRun {
+            if !self.on_stabilize {
+                return ScriptRun::default();
+            }
+            let (reason, period) = match reason {
+                StopReason::Extinct => ("extinct", 0),
+                StopReason::Cycle { period } => ("cycle", period as INT),
+            };
+            self.call(simulation, "on_stabilize", |grid| {
+                vec![Dynamic::from(grid), Dynamic::from(reason.to_string()), Dynamic::from(period)]
+            })
+        }
+        
+        fn call(
+            &self,
+            simulation: &mut SimulationData,
+            name: &str,
+            args: impl FnOnce(ScriptGrid) -> Vec<Dynamic>,
+        ) -> ScriptRun {
+            let seed = simulation.seed.unwrap_or_default() ^ simulation.generation.wrapping_mul(GENERATION_MIX);
+            let grid = ScriptGrid(Arc::new(Mutex::new(GridAccess {
+                cells: std::mem::take(&mut simulation.cells),
+                width: simulation.width,
+                height: simulation.height,
+                generation: simulation.generation,
+                rng: SeededRng::new(seed),
+                edits: Vec::new(),
+            })));
+            
+            let options = CallFnOptions::new().eval_ast(false);
+            let result = self.engine.call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &self.ast, name, args(grid.clone()));
+            
+            // The grid goes back even if the hook failed partway
+            let (cells, edits) = grid.with(|access| (std::mem::take(&mut access.cells), std::mem::take(&mut access.edits)));
+            simulation.cells = cells;
+            ScriptRun { edits, error: result.err().map(|error| format!("{} failed: {}", name, error)) }
+        }
+    }
+}
+
+#[cfg(not(feature = "scripting"))]
+mod disabled {
+    use std::path::Path;
+    
+    use super::ScriptRun;
+    use crate::resources::{SimulationData, StopReason};
+    
+    /// Stands in for the hooks when built without the `scripting` feature;
+    /// loading a script fails, so none is ever run.
+    pub struct ScriptHooks(std::convert::Infallible);
+    
+    impl ScriptHooks {
+        pub fn load(path: &Path) -> Result<Self, String> {
+            Err(format!("cannot run script {}: built without the scripting feature", path.display()))
+        }
+        
+        pub fn on_generation(&self, _simulation: &mut SimulationData) -> ScriptRun {
+            match self.0 {}
+        }
+        
+        pub fn on_stabilize(&self, _simulation: &mut SimulationData, _reason: StopReason) -> ScriptRun {
+            match self.0 {}
+        }
+    }
+}
+
+#[cfg(all(test, feature = "scripting"))]
+mod tests {
+    use super::*;
+    use crate::resources::{scratch_simulation, StopReason};
+    
+    #[test]
+    fn test_on_generation_reads_and_changes_the_grid() {
+        let hooks = ScriptHooks::compile(r#"
+            fn on_generation(grid) {
+                if grid.generation == 2 && grid.population == 3 {
+                    grid.set(0, 0, true);
+                    grid.set(-1, 0, true);
+                    grid.set(9, 9, grid.is_alive(2, 1));
+                }
+            }
+        "#).unwrap();
+        let mut simulation = scratch_simulation(10, 10);
+        simulation.set_cells(&[(1, 1), (2, 1), (3, 1)]);
+        
+        simulation.step();
+        assert_eq!(hooks.on_generation(&mut simulation), ScriptRun::default());
+        simulation.step();
+        assert_eq!(hooks.on_generation(&mut simulation).edits, vec![(0, 0, true), (9, 9, true)]);
+        assert_eq!(simulation.get_live_cell_count(), 5);
+        assert_eq!(hooks.on_stabilize(&mut simulation, StopReason::Extinct), ScriptRun::default());
+    }
+    
+    #[test]
+    fn test_on_stabilize_can_reseed() {
+        let hooks = ScriptHooks::compile(r#"
+            fn on_stabilize(grid, reason, period) {
+                if reason == "extinct" {
+                    for x in 0..grid.width {
+                        if grid.chance(1.0) { grid.set(x, 0, true); }
+                    }
+                }
+            }
+        "#).unwrap();
+        let mut simulation = scratch_simulation(8, 8);
+        let run = hooks.on_stabilize(&mut simulation, StopReason::Extinct);
+        assert_eq!((run.edits.len(), run.error), (8, None));
+        assert_eq!(simulation.get_live_cell_count(), 8);
+        assert!(hooks.on_stabilize(&mut simulation, StopReason::Cycle { period: 2 }).edits.is_empty());
+    }
+    
+    #[test]
+    fn test_failed_hooks_keep_their_edits() {
+        assert!(ScriptHooks::compile("fn on_generation(grid) {").is_err());
+        
+        let hooks = ScriptHooks::compile("fn on_generation(grid) { grid.set(1, 1, true); loop {} }").unwrap();
+        let mut simulation = scratch_simulation(4, 4);
+        simulation.set_cells(&[(0, 0)]);
+        let run = hooks.on_generation(&mut simulation);
+        assert!(run.error.is_some());
+        assert_eq!(run.edits, vec![(1, 1, true)]);
+        assert_eq!(simulation.get_live_cell_count(), 2);
+    }
+}
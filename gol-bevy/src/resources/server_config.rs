@@ -0,0 +1,515 @@
+use bevy::prelude::*;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::resources::MilestoneWebhook;
+
+/// Environment variable overriding the gRPC listen address.
+pub const LISTEN_ADDR_ENV_VAR: &str = "GOL_LISTEN_ADDR";
+
+/// Environment variable used to pin the stepping thread pool size.
+pub const THREADS_ENV_VAR: &str = "GOL_THREADS";
+
+/// Environment variable overriding the largest step count run inline.
+pub const MAX_INLINE_STEPS_ENV_VAR: &str = "GOL_MAX_INLINE_STEPS";
+
+/// Environment variable overriding the most cells put in one SimulationResponse.
+pub const MAX_RESPONSE_CELLS_ENV_VAR: &str = "GOL_MAX_RESPONSE_CELLS";
+
+/// Environment variable naming the file simulations are saved to on shutdown.
+pub const PERSIST_PATH_ENV_VAR: &str = "GOL_PERSIST_PATH";
+
+/// Environment variable enabling the per-request access log (`1`/`true`).
+pub const ACCESS_LOG_ENV_VAR: &str = "GOL_ACCESS_LOG";
+
+/// Environment variable overriding the per-peer rate limit (`rate[/burst]`, or `off`).
+pub const PEER_RATE_LIMIT_ENV_VAR: &str = "GOL_PEER_RATE_LIMIT";
+
+/// Environment variable overriding the per-simulation rate limit (`rate[/burst]`, or `off`).
+pub const SIMULATION_RATE_LIMIT_ENV_VAR: &str = "GOL_SIMULATION_RATE_LIMIT";
+
+/// Environment variable naming the directory of the persistent simulation store.
+pub const DATA_DIR_ENV_VAR: &str = "GOL_DATA_DIR";
+
+/// Environment variable naming a Rhai script whose hooks run as simulations step.
+pub const SCRIPT_ENV_VAR: &str = "GOL_SCRIPT";
+
+/// Environment variable enabling the reference stepper check of every step (`1`/`true`).
+pub const REFERENCE_CHECK_ENV_VAR: &str = "GOL_REFERENCE_CHECK";
+
+/// Environment variable overriding the seconds between store checkpoints.
+pub const CHECKPOINT_SECS_ENV_VAR: &str = "GOL_CHECKPOINT_SECS";
+
+/// Environment variable naming the directory running servers register in
+/// for clients to discover (`off` to not register).
+pub const REGISTRY_DIR_ENV_VAR: &str = "GOL_REGISTRY_DIR";
+
+/// Environment variable naming an `http://` URL every new simulation
+/// notifies of its milestones.
+pub const WEBHOOK_URL_ENV_VAR: &str = "GOL_WEBHOOK_URL";
+
+/// Environment variable setting how many generations apart the server-wide
+/// webhook is also notified (`0` for never).
+pub const WEBHOOK_EVERY_ENV_VAR: &str = "GOL_WEBHOOK_EVERY";
+
+/// Environment variable overriding how many batch runs step at once.
+pub const BATCH_CONCURRENCY_ENV_VAR: &str = "GOL_BATCH_CONCURRENCY";
+
+/// Environment variable capping the simulations each client may own.
+pub const QUOTA_SIMULATIONS_ENV_VAR: &str = "GOL_QUOTA_SIMULATIONS";
+
+/// Environment variable capping the live cells across each client's simulations.
+pub const QUOTA_LIVE_CELLS_ENV_VAR: &str = "GOL_QUOTA_LIVE_CELLS";
+
+/// Environment variable capping the generations each client may step per minute.
+pub const QUOTA_STEPS_PER_MINUTE_ENV_VAR: &str = "GOL_QUOTA_STEPS_PER_MINUTE";
+
+/// Environment variable listing the bearer tokens clients may use, comma separated.
+pub const CLIENT_TOKENS_ENV_VAR: &str = "GOL_CLIENT_TOKENS";
+
+/// Environment variable choosing what steps simulations (`chunked` or `ecs`).
+pub const BACKEND_ENV_VAR: &str = "GOL_BACKEND";
+
+/// Environment variable creating the `default` simulation at startup (`1`/`true`).
+pub const DEFAULT_SIMULATION_ENV_VAR: &str = "GOL_DEFAULT_SIMULATION";
+
+/// Id of the simulation created at startup, the one the console client
+/// opens first.
+pub const DEFAULT_SIMULATION_ID: &str = "default";
+
+/// Width and height of the simulation created at startup, the size the
+/// console client creates.
+pub const DEFAULT_SIMULATION_SIZE: (i32, i32) = (100, 50);
+
+/// Default time between write-behind checkpoints to the persistent store.
+pub const DEFAULT_CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Default time in-flight RPCs get to finish after a shutdown signal.
+pub const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+/// Default number of times a failed listener is rebound before the server gives up.
+pub const DEFAULT_MAX_LISTENER_RESTARTS: u32 = 5;
+
+/// Default cap on generations stepped per lock acquisition when draining a step queue.
+pub const DEFAULT_MAX_BATCH_STEPS: u32 = 1000;
+
+/// Default largest StepSimulation request answered inline; larger ones become jobs.
+pub const DEFAULT_MAX_INLINE_STEPS: u32 = 10_000;
+
+/// Default cap on cells per SimulationResponse or GetCells page, keeping
+/// dense grids well inside gRPC's 4 MiB message limit.
+pub const DEFAULT_MAX_RESPONSE_CELLS: usize = 100_000;
+
+/// Default number of batch runs stepped at once; later runs queue.
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 2;
+
+/// Default request budget for each client address.
+pub const DEFAULT_PEER_RATE_LIMIT: RateLimit = RateLimit { per_second: 200.0, burst: 400 };
+
+/// Default request budget for each simulation, across all clients.
+pub const DEFAULT_SIMULATION_RATE_LIMIT: RateLimit = RateLimit { per_second: 100.0, burst: 200 };
+
+/// How much checkpoint history the persistent store keeps per simulation.
+///
+/// The newest `keep_recent` checkpoints are always kept. Older ones are thinned
+/// to the newest checkpoint in each span of `thin_every_generations`
+/// generations, or dropped entirely when that is `0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HistoryRetention {
+    pub keep_recent: usize,
+    pub thin_every_generations: u64,
+}
+
+impl Default for HistoryRetention {
+    fn default() -> Self {
+        Self {
+            keep_recent: 20,
+            thin_every_generations: 1000,
+        }
+    }
+}
+
+/// Limits on what each client, told apart by a known bearer token or else
+/// by address, may use of a shared server; `None` leaves a resource unlimited.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ClientQuota {
+    pub max_simulations: Option<usize>,
+    /// Live cells across every simulation the client owns.
+    pub max_live_cells: Option<u64>,
+    pub max_steps_per_minute: Option<u64>,
+}
+
+impl ClientQuota {
+    pub fn is_limited(&self) -> bool {
+        *self != Self::default()
+    }
+}
+
+/// What steps simulations and answers for their cells.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SimulationBackend {
+    /// The chunked bitmap engine, for every rule and boundary.
+    #[default]
+    Chunked,
+    /// A Bevy ECS world per simulation, from the app's
+    /// [`SimulationManager`](crate::resources::SimulationManager). It steps
+    /// B3/S23 on clipped grids; other simulations keep the chunked engine.
+    Ecs,
+}
+
+impl FromStr for SimulationBackend {
+    type Err = String;
+    
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "chunked" => Ok(Self::Chunked),
+            "ecs" => Ok(Self::Ecs),
+            other => Err(format!("unknown backend '{}', expected chunked or ecs", other)),
+        }
+    }
+}
+
+/// Token-bucket parameters: a sustained request rate plus the burst allowed on top of it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    pub per_second: f64,
+    pub burst: u32,
+}
+
+impl FromStr for RateLimit {
+    type Err = String;
+    
+    /// Parse `rate` or `rate/burst`; the burst defaults to one second's worth of requests.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (rate, burst) = match value.split_once('/') {
+            Some((rate, burst)) => (rate, Some(burst)),
+            None => (value, None),
+        };
+        let per_second: f64 = rate.trim().parse().map_err(|_| format!("invalid rate: {}", rate))?;
+        if !per_second.is_finite() || per_second <= 0.0 {
+            return Err(format!("rate must be positive: {}", rate));
+        }
+        let burst = match burst {
+            Some(burst) => burst.trim().parse().map_err(|_| format!("invalid burst: {}", burst))?,
+            None => per_second.ceil() as u32,
+        };
+        Ok(Self { per_second, burst: burst.max(1) })
+    }
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct ServerConfig {
+    /// Address the gRPC server listens on.
+    pub listen_addr: SocketAddr,
+    /// Worker threads used for parallel generation stepping. `0` uses all cores.
+    pub threads: usize,
+    /// Generations a step queue applies before releasing the simulations lock.
+    pub max_batch_steps: u32,
+    /// StepSimulation requests above this many steps run as background jobs.
+    pub max_inline_steps: u32,
+    /// Most cells a SimulationResponse carries before it is truncated, and
+    /// the largest GetCells page.
+    pub max_response_cells: usize,
+    /// Save simulations here on shutdown and restore them on startup.
+    pub persist_path: Option<PathBuf>,
+    /// How long shutdown waits for in-flight RPCs before exiting anyway.
+    pub shutdown_grace: Duration,
+    /// Consecutive listener failures tolerated before the server shuts down.
+    pub max_listener_restarts: u32,
+    /// Log method, peer, latency and status for every RPC.
+    pub access_log: bool,
+    /// Requests each client address may make; `None` disables the limit.
+    pub peer_rate_limit: Option<RateLimit>,
+    /// Requests that may target any one simulation; `None` disables the limit.
+    pub simulation_rate_limit: Option<RateLimit>,
+    /// Directory of the persistent simulation store (requires the `sled-store` feature).
+    pub data_dir: Option<PathBuf>,
+    /// How often changed simulations are checkpointed to the store.
+    pub checkpoint_interval: Duration,
+    /// Checkpoint history kept per simulation after compaction.
+    pub history_retention: HistoryRetention,
+    /// Directory a registry file announcing this server is kept in while it
+    /// listens; `None` does not register.
+    pub registry_dir: Option<PathBuf>,
+    /// `http://` URL new simulations notify on completion, extinction and
+    /// stabilization, for long unattended runs.
+    pub webhook_url: Option<String>,
+    /// Generations between extra notifications of `webhook_url`; `0` sends none.
+    pub webhook_every: u64,
+    /// Batch runs stepped at once, across all batches.
+    pub batch_concurrency: usize,
+    /// What each client may use; unlimited by default.
+    pub client_quota: ClientQuota,
+    /// Bearer tokens quotas are charged to. Requests with any other token are
+    /// refused; with `None` tokens are ignored and clients are told apart by
+    /// address.
+    pub client_tokens: Option<Vec<String>>,
+    /// Rhai script with `on_generation`/`on_stabilize` hooks (requires the
+    /// `scripting` feature).
+    pub script_path: Option<PathBuf>,
+    /// Compare every generation stepped with the dense reference stepper and
+    /// log where each simulation first diverges. Slow; for debugging engines.
+    pub reference_check: bool,
+    /// Create a [`DEFAULT_SIMULATION_ID`] simulation at startup unless one
+    /// was restored.
+    pub default_simulation: bool,
+    /// What steps simulations.
+    pub backend: SimulationBackend,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            listen_addr: "[::1]:50051".parse().unwrap(),
+            threads: 0,
+            max_batch_steps: DEFAULT_MAX_BATCH_STEPS,
+            max_inline_steps: DEFAULT_MAX_INLINE_STEPS,
+            max_response_cells: DEFAULT_MAX_RESPONSE_CELLS,
+            persist_path: None,
+            shutdown_grace: DEFAULT_SHUTDOWN_GRACE,
+            max_listener_restarts: DEFAULT_MAX_LISTENER_RESTARTS,
+            access_log: false,
+            peer_rate_limit: Some(DEFAULT_PEER_RATE_LIMIT),
+            simulation_rate_limit: Some(DEFAULT_SIMULATION_RATE_LIMIT),
+            data_dir: None,
+            checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL,
+            history_retention: HistoryRetention::default(),
+            registry_dir: None,
+            webhook_url: None,
+            webhook_every: 0,
+            batch_concurrency: DEFAULT_BATCH_CONCURRENCY,
+            client_quota: ClientQuota::default(),
+            client_tokens: None,
+            script_path: None,
+            reference_check: false,
+            default_simulation: false,
+            backend: SimulationBackend::Chunked,
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn new(threads: usize) -> Self {
+        Self {
+            threads,
+            ..Self::default()
+        }
+    }
+    
+    /// Build a config from the process environment, falling back to defaults
+    /// for unset or unparsable values.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            listen_addr: env_value(LISTEN_ADDR_ENV_VAR).unwrap_or(defaults.listen_addr),
+            threads: env_value(THREADS_ENV_VAR).unwrap_or(defaults.threads),
+            max_inline_steps: env_value(MAX_INLINE_STEPS_ENV_VAR).unwrap_or(defaults.max_inline_steps),
+            max_response_cells: env_value(MAX_RESPONSE_CELLS_ENV_VAR)
+                .filter(|&cells| cells > 0)
+                .unwrap_or(defaults.max_response_cells),
+            persist_path: env_value(PERSIST_PATH_ENV_VAR),
+            access_log: env_flag(ACCESS_LOG_ENV_VAR).unwrap_or(defaults.access_log),
+            peer_rate_limit: env_rate_limit(PEER_RATE_LIMIT_ENV_VAR).unwrap_or(defaults.peer_rate_limit),
+            simulation_rate_limit: env_rate_limit(SIMULATION_RATE_LIMIT_ENV_VAR).unwrap_or(defaults.simulation_rate_limit),
+            data_dir: env_value(DATA_DIR_ENV_VAR),
+            checkpoint_interval: env_value(CHECKPOINT_SECS_ENV_VAR)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.checkpoint_interval),
+            registry_dir: env_registry_dir(),
+            webhook_url: env_value(WEBHOOK_URL_ENV_VAR),
+            webhook_every: env_value(WEBHOOK_EVERY_ENV_VAR).unwrap_or(defaults.webhook_every),
+            batch_concurrency: env_value(BATCH_CONCURRENCY_ENV_VAR).unwrap_or(defaults.batch_concurrency),
+            client_quota: ClientQuota {
+                max_simulations: env_value(QUOTA_SIMULATIONS_ENV_VAR),
+                max_live_cells: env_value(QUOTA_LIVE_CELLS_ENV_VAR),
+                max_steps_per_minute: env_value(QUOTA_STEPS_PER_MINUTE_ENV_VAR),
+            },
+            client_tokens: std::env::var(CLIENT_TOKENS_ENV_VAR).ok().map(|tokens| parse_tokens(&tokens)),
+            script_path: env_value(SCRIPT_ENV_VAR),
+            reference_check: env_flag(REFERENCE_CHECK_ENV_VAR).unwrap_or(defaults.reference_check),
+            default_simulation: env_flag(DEFAULT_SIMULATION_ENV_VAR).unwrap_or(defaults.default_simulation),
+            backend: env_value(BACKEND_ENV_VAR).unwrap_or(defaults.backend),
+            ..defaults
+        }
+    }
+    
+    /// Apply command-line flags on top of this config. Flags take precedence
+    /// over the environment.
+    pub fn with_args<I>(mut self, args: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = || inline_value.clone().or_else(|| args.next())
+                .ok_or_else(|| format!("{} requires a value", flag));
+            
+            match flag.as_str() {
+                "--data-dir" => self.data_dir = Some(PathBuf::from(value()?)),
+                "--registry-dir" => self.registry_dir = Some(PathBuf::from(value()?)),
+                "--script" => self.script_path = Some(PathBuf::from(value()?)),
+                "--reference-check" => self.reference_check = true,
+                "--default-simulation" => self.default_simulation = true,
+                "--backend" => self.backend = value()?.parse()?,
+                "--listen" => self.listen_addr = value()?.parse().map_err(|_| "invalid --listen address".to_string())?,
+                "--webhook" => {
+                    let url = value()?;
+                    if !url.starts_with("http://") {
+                        return Err(format!("--webhook must be an http:// URL: {}", url));
+                    }
+                    self.webhook_url = Some(url);
+                }
+                "--webhook-every" => self.webhook_every = value()?.parse().map_err(|_| "invalid --webhook-every".to_string())?,
+                "--batch-concurrency" => {
+                    self.batch_concurrency = value()?.parse().ok().filter(|&runs| runs > 0)
+                        .ok_or_else(|| "--batch-concurrency must be a positive number".to_string())?;
+                }
+                "--quota-simulations" => self.client_quota.max_simulations = Some(value()?.parse().map_err(|_| "invalid --quota-simulations".to_string())?),
+                "--quota-live-cells" => self.client_quota.max_live_cells = Some(value()?.parse().map_err(|_| "invalid --quota-live-cells".to_string())?),
+                "--quota-steps-per-minute" => self.client_quota.max_steps_per_minute = Some(value()?.parse().map_err(|_| "invalid --quota-steps-per-minute".to_string())?),
+                "--client-tokens" => self.client_tokens = Some(parse_tokens(&value()?)),
+                other => return Err(format!("unknown argument: {}", other)),
+            }
+        }
+        Ok(self)
+    }
+    
+    /// Webhooks every new simulation starts with.
+    pub fn default_webhooks(&self) -> Vec<MilestoneWebhook> {
+        self.webhook_url.iter()
+            .map(|url| MilestoneWebhook::all_milestones(url, self.webhook_every))
+            .collect()
+    }
+    
+    /// Build the rayon pool used for stepping simulations.
+    pub fn build_thread_pool(&self) -> rayon::ThreadPool {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(self.threads)
+            .thread_name(|index| format!("gol-step-{}", index))
+            .build()
+            .expect("failed to build stepping thread pool")
+    }
+}
+
+fn env_value<T: FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|value| value.trim().parse().ok())
+}
+
+fn parse_tokens(tokens: &str) -> Vec<String> {
+    tokens.split(',').map(str::trim).filter(|token| !token.is_empty()).map(String::from).collect()
+}
+
+/// Accepts `1`/`0` as well as `true`/`false`.
+fn env_flag(name: &str) -> Option<bool> {
+    match std::env::var(name).ok()?.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// The registry directory from the environment, else `gol-servers` in the
+/// system temporary directory, so servers are discoverable by default.
+fn env_registry_dir() -> Option<PathBuf> {
+    match std::env::var(REGISTRY_DIR_ENV_VAR) {
+        Ok(value) if matches!(value.trim(), "off" | "0" | "") => None,
+        Ok(value) => Some(PathBuf::from(value.trim())),
+        Err(_) => Some(default_registry_dir()),
+    }
+}
+
+/// Where servers register when `GOL_REGISTRY_DIR` is unset. Clients look
+/// here by default too.
+pub fn default_registry_dir() -> PathBuf {
+    std::env::temp_dir().join("gol-servers")
+}
+
+/// `Some(None)` when the limit is explicitly switched off, `None` when unset or unparsable.
+fn env_rate_limit(name: &str) -> Option<Option<RateLimit>> {
+    let value = std::env::var(name).ok()?;
+    match value.trim() {
+        "off" | "0" => Some(None),
+        value => value.parse().ok().map(Some),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_thread_pool_respects_thread_count() {
+        let pool = ServerConfig::new(2).build_thread_pool();
+        assert_eq!(pool.current_num_threads(), 2);
+    }
+    
+    #[test]
+    fn test_default_uses_all_cores() {
+        let config = ServerConfig::default();
+        assert_eq!(config.threads, 0);
+        assert!(config.build_thread_pool().current_num_threads() >= 1);
+    }
+    
+    #[test]
+    fn test_args_override_data_dir() {
+        let args = ["--data-dir", "/var/lib/gol", "--listen=127.0.0.1:6000"].map(String::from);
+        let config = ServerConfig::default().with_args(args).unwrap();
+        assert_eq!(config.data_dir, Some(PathBuf::from("/var/lib/gol")));
+        assert_eq!(config.listen_addr, "127.0.0.1:6000".parse().unwrap());
+        assert!(!config.reference_check);
+        assert!(ServerConfig::default().with_args(["--reference-check".to_string()]).unwrap().reference_check);
+        assert!(ServerConfig::default().with_args(["--default-simulation".to_string()]).unwrap().default_simulation);
+        assert_eq!(ServerConfig::default().with_args(["--backend=ecs".to_string()]).unwrap().backend, SimulationBackend::Ecs);
+        assert!(ServerConfig::default().with_args(["--backend=flecs".to_string()]).is_err());
+        let tokens = ServerConfig::default().with_args(["--client-tokens=alice, bob,".to_string()]).unwrap().client_tokens;
+        assert_eq!(tokens, Some(vec!["alice".to_string(), "bob".to_string()]));
+        
+        assert!(ServerConfig::default().with_args(["--data-dir".to_string()]).is_err());
+        assert!(ServerConfig::default().with_args(["--bogus".to_string()]).is_err());
+    }
+    
+    #[test]
+    fn test_webhook_args_set_default_webhooks() {
+        assert!(ServerConfig::default().default_webhooks().is_empty());
+        let args = ["--webhook", "http://hooks:8080/gol", "--webhook-every=5000"].map(String::from);
+        let config = ServerConfig::default().with_args(args).unwrap();
+        assert_eq!(config.default_webhooks(), vec![MilestoneWebhook::all_milestones("http://hooks:8080/gol", 5000)]);
+        
+        assert!(ServerConfig::default().with_args(["--webhook", "https://hooks/gol"].map(String::from)).is_err());
+    }
+    
+    #[test]
+    fn test_batch_concurrency_arg() {
+        assert_eq!(ServerConfig::default().batch_concurrency, DEFAULT_BATCH_CONCURRENCY);
+        let config = ServerConfig::default().with_args(["--batch-concurrency", "4"].map(String::from)).unwrap();
+        assert_eq!(config.batch_concurrency, 4);
+        assert!(ServerConfig::default().with_args(["--batch-concurrency=0".to_string()]).is_err());
+    }
+    
+    #[test]
+    fn test_quota_args() {
+        assert!(!ServerConfig::default().client_quota.is_limited());
+        let args = ["--quota-simulations", "5", "--quota-steps-per-minute=100000"].map(String::from);
+        let config = ServerConfig::default().with_args(args).unwrap();
+        assert_eq!(config.client_quota, ClientQuota {
+            max_simulations: Some(5),
+            max_live_cells: None,
+            max_steps_per_minute: Some(100_000),
+        });
+        assert!(ServerConfig::default().with_args(["--quota-live-cells", "lots"].map(String::from)).is_err());
+    }
+    
+    #[test]
+    fn test_rate_limit_parsing() {
+        assert_eq!("50".parse(), Ok(RateLimit { per_second: 50.0, burst: 50 }));
+        assert_eq!("2.5/10".parse(), Ok(RateLimit { per_second: 2.5, burst: 10 }));
+        assert!("0".parse::<RateLimit>().is_err());
+        assert!("fast".parse::<RateLimit>().is_err());
+    }
+}
@@ -0,0 +1,340 @@
+//! One ECS world per simulation.
+//!
+//! Every simulation the ECS backend serves lives in its own [`World`] with its
+//! own cell entities, [`SimulationState`] and [`SpatialGrid`], stepped by its
+//! own schedule of the Game of Life systems. Worlds never share entities, so
+//! they step independently and in parallel.
+//!
+//! Other threads, such as gRPC handlers, drive the worlds through a
+//! [`SimulationCommandSender`]: commands are queued by simulation id and the
+//! app applies them once a frame, stepping every world that was asked to step
+//! at the same time.
+
+use bevy::ecs::schedule::ExecutorKind;
+use bevy::prelude::*;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+use tokio::sync::oneshot;
+
+use crate::components::{CellState, Position};
+use crate::resources::{SimulationState, SpatialGrid};
+use crate::systems::{cell_lifecycle_system, cleanup_system, neighbor_counting_system};
+
+/// A simulation's generation and live cells.
+pub type WorldCells = (u64, Vec<(i32, i32)>);
+
+/// A request for one simulation's world, answered on its `reply` channel.
+#[derive(Debug)]
+pub enum SimulationCommand {
+    /// Start a world with `cells` alive, bounded by `bounds` if set; replies
+    /// false if `id` is taken.
+    Create { id: String, cells: Vec<(i32, i32)>, bounds: Option<(i32, i32)>, reply: oneshot::Sender<bool> },
+    /// Replies with the generation reached, or `None` for an unknown id.
+    Step { id: String, generations: u64, reply: oneshot::Sender<Option<u64>> },
+    /// Replies with the live cells and generation, or `None` for an unknown id.
+    GetCells { id: String, reply: oneshot::Sender<Option<WorldCells>> },
+    /// Replies whether the world existed.
+    Delete { id: String, reply: oneshot::Sender<bool> },
+}
+
+/// Queues [`SimulationCommand`]s for the app to route to their worlds.
+#[derive(Debug, Clone)]
+pub struct SimulationCommandSender {
+    sender: Sender<SimulationCommand>,
+}
+
+impl SimulationCommandSender {
+    pub async fn create(&self, id: &str, cells: Vec<(i32, i32)>, bounds: Option<(i32, i32)>) -> bool {
+        let (reply, answer) = oneshot::channel();
+        self.send(SimulationCommand::Create { id: id.to_string(), cells, bounds, reply });
+        answer.await.unwrap_or(false)
+    }
+    
+    pub async fn step(&self, id: &str, generations: u64) -> Option<u64> {
+        let (reply, answer) = oneshot::channel();
+        self.send(SimulationCommand::Step { id: id.to_string(), generations, reply });
+        answer.await.ok().flatten()
+    }
+    
+    pub async fn get_cells(&self, id: &str) -> Option<WorldCells> {
+        let (reply, answer) = oneshot::channel();
+        self.send(SimulationCommand::GetCells { id: id.to_string(), reply });
+        answer.await.ok().flatten()
+    }
+    
+    pub async fn delete(&self, id: &str) -> bool {
+        let (reply, answer) = oneshot::channel();
+        self.send(SimulationCommand::Delete { id: id.to_string(), reply });
+        answer.await.unwrap_or(false)
+    }
+    
+    /// Once the app is gone the reply channel closes, which the callers above
+    /// read as a missing simulation.
+    fn send(&self, command: SimulationCommand) {
+        let _ = self.sender.send(command);
+    }
+}
+
+/// A simulation's world and the schedule that steps it one generation.
+pub struct SimulationWorld {
+    world: World,
+    schedule: Schedule,
+    /// Width and height of the grid; cells stepped outside it die, as on a
+    /// clipped [`ChunkedGrid`](crate::resources::ChunkedGrid). `None` is an
+    /// unbounded plane.
+    bounds: Option<(i32, i32)>,
+}
+
+impl SimulationWorld {
+    pub fn new(cells: &[(i32, i32)]) -> Self {
+        let mut world = World::new();
+        let mut simulation_state = SimulationState::new();
+        simulation_state.start();
+        world.insert_resource(simulation_state);
+        world.insert_resource(SpatialGrid::new());
+        world.spawn_batch(cells.iter().map(|&(x, y)| (Position::new(x, y), CellState::new())));
+        
+        let mut schedule = Schedule::default();
+        // Worlds step in parallel with each other, so each runs its own
+        // systems in turn
+        schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        schedule.add_systems((
+            neighbor_counting_system,
+            cell_lifecycle_system,
+            cleanup_system,
+        ).chain());
+        
+        Self { world, schedule, bounds: None }
+    }
+    
+    /// A world for a `width` x `height` grid, with the cells outside it dropped.
+    pub fn bounded(cells: &[(i32, i32)], width: i32, height: i32) -> Self {
+        let inside: Vec<_> = cells.iter().copied().filter(|&(x, y)| x >= 0 && x < width && y >= 0 && y < height).collect();
+        Self { bounds: Some((width, height)), ..Self::new(&inside) }
+    }
+    
+    pub fn step(&mut self, generations: u64) -> u64 {
+        for _ in 0..generations {
+            self.schedule.run(&mut self.world);
+            self.clip();
+            self.world.clear_trackers();
+        }
+        self.generation()
+    }
+    
+    /// Kill the cells stepped outside the bounds.
+    fn clip(&mut self) {
+        let Some((width, height)) = self.bounds else {
+            return;
+        };
+        let mut query = self.world.query::<(Entity, &Position)>();
+        let outside: Vec<(Entity, Position)> = query.iter(&self.world)
+            .filter(|(_, position)| position.x < 0 || position.x >= width || position.y < 0 || position.y >= height)
+            .map(|(entity, position)| (entity, *position))
+            .collect();
+        for (entity, position) in outside {
+            self.world.resource_mut::<SpatialGrid>().remove(&position);
+            self.world.despawn(entity);
+        }
+    }
+    
+    pub fn generation(&self) -> u64 {
+        self.world.resource::<SimulationState>().generation
+    }
+    
+    pub fn live_cells(&mut self) -> Vec<(i32, i32)> {
+        let mut query = self.world.query::<(&Position, &CellState)>();
+        query.iter(&self.world)
+            .filter(|(_, cell_state)| cell_state.alive)
+            .map(|(position, _)| (position.x, position.y))
+            .collect()
+    }
+}
+
+/// The ECS worlds of every simulation, by id.
+#[derive(Resource)]
+pub struct SimulationManager {
+    worlds: HashMap<String, SimulationWorld>,
+    sender: Sender<SimulationCommand>,
+    receiver: Mutex<Receiver<SimulationCommand>>,
+}
+
+impl Default for SimulationManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulationManager {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            worlds: HashMap::new(),
+            sender,
+            receiver: Mutex::new(receiver),
+        }
+    }
+    
+    /// A handle for queueing commands from other threads.
+    pub fn sender(&self) -> SimulationCommandSender {
+        SimulationCommandSender { sender: self.sender.clone() }
+    }
+    
+    /// Add a world for `id`, bounded to a `(width, height)` grid if `bounds`
+    /// is set, returning false if there already is one.
+    pub fn create(&mut self, id: &str, cells: &[(i32, i32)], bounds: Option<(i32, i32)>) -> bool {
+        if self.worlds.contains_key(id) {
+            return false;
+        }
+        let world = match bounds {
+            Some((width, height)) => SimulationWorld::bounded(cells, width, height),
+            None => SimulationWorld::new(cells),
+        };
+        self.worlds.insert(id.to_string(), world);
+        true
+    }
+    
+    pub fn remove(&mut self, id: &str) -> bool {
+        self.worlds.remove(id).is_some()
+    }
+    
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut SimulationWorld> {
+        self.worlds.get_mut(id)
+    }
+    
+    pub fn len(&self) -> usize {
+        self.worlds.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.worlds.is_empty()
+    }
+    
+    /// Step each listed world by its number of generations, all at once,
+    /// returning the generation each reached. Unknown ids are left out.
+    pub fn step_many(&mut self, steps: &HashMap<String, u64>) -> HashMap<String, u64> {
+        self.worlds.par_iter_mut()
+            .filter_map(|(id, world)| steps.get(id).map(|&generations| (id.clone(), world.step(generations))))
+            .collect()
+    }
+    
+    /// Apply every queued command. Creates, reads and deletes are applied in
+    /// the order they were sent, then every step asked for is run together,
+    /// several steps of one simulation adding up.
+    pub fn process_commands(&mut self) {
+        let commands: Vec<SimulationCommand> = self.receiver.lock().unwrap().try_iter().collect();
+        if commands.is_empty() {
+            return;
+        }
+        
+        let mut steps: HashMap<String, u64> = HashMap::new();
+        let mut step_replies = Vec::new();
+        for command in commands {
+            match command {
+                SimulationCommand::Create { id, cells, bounds, reply } => {
+                    let _ = reply.send(self.create(&id, &cells, bounds));
+                }
+                SimulationCommand::Step { id, generations, reply } => {
+                    *steps.entry(id.clone()).or_insert(0) += generations;
+                    step_replies.push((id, reply));
+                }
+                SimulationCommand::GetCells { id, reply } => {
+                    let cells = self.worlds.get_mut(&id).map(|world| (world.generation(), world.live_cells()));
+                    let _ = reply.send(cells);
+                }
+                SimulationCommand::Delete { id, reply } => {
+                    let _ = reply.send(self.remove(&id));
+                }
+            }
+        }
+        
+        let reached = self.step_many(&steps);
+        for (id, reply) in step_replies {
+            let _ = reply.send(reached.get(&id).copied());
+        }
+    }
+}
+
+/// Route queued commands to their simulations' worlds once a frame.
+pub fn process_simulation_commands_system(mut manager: ResMut<SimulationManager>) {
+    manager.process_commands();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::{Simulations, CHUNK_SIZE};
+    
+    fn sorted(mut cells: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+        cells.sort_unstable();
+        cells
+    }
+    
+    #[test]
+    fn test_worlds_step_independently() {
+        let blinker = [(1, 0), (1, 1), (1, 2)];
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let mut manager = SimulationManager::new();
+        assert!(manager.create("blinker", &blinker, None));
+        assert!(manager.create("glider", &glider, None));
+        assert!(!manager.create("glider", &[], None));
+        
+        let steps = HashMap::from([("blinker".to_string(), 1), ("glider".to_string(), 4)]);
+        let reached = manager.step_many(&steps);
+        assert_eq!(reached, HashMap::from([("blinker".to_string(), 1), ("glider".to_string(), 4)]));
+        assert_eq!(sorted(manager.get_mut("blinker").unwrap().live_cells()), vec![(0, 1), (1, 1), (2, 1)]);
+        
+        // Matches the chunked grid the gRPC service steps
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(CHUNK_SIZE * 4, CHUNK_SIZE * 4, None);
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        simulation.set_cells(&glider);
+        for _ in 0..4 {
+            simulation.step();
+        }
+        assert_eq!(sorted(manager.get_mut("glider").unwrap().live_cells()), sorted(simulation.get_live_cells()));
+    }
+    
+    #[test]
+    fn test_commands_are_routed_by_id() {
+        let mut manager = SimulationManager::new();
+        let sender = manager.sender();
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let requests = runtime.spawn(async move {
+            assert!(sender.create("a", vec![(1, 0), (1, 1), (1, 2)], None).await);
+            let (first, second) = tokio::join!(sender.step("a", 1), sender.step("a", 1));
+            assert_eq!(first.max(second), Some(2));
+            assert_eq!(sender.step("missing", 1).await, None);
+            let (generation, cells) = sender.get_cells("a").await.unwrap();
+            assert_eq!((generation, cells.len()), (2, 3));
+            assert!(sender.delete("a").await);
+        });
+        while !requests.is_finished() {
+            runtime.block_on(tokio::task::yield_now());
+            manager.process_commands();
+        }
+        runtime.block_on(requests).unwrap();
+        assert!(manager.is_empty());
+    }
+    
+    #[test]
+    fn test_bounded_worlds_clip_like_the_chunked_grid() {
+        // A glider walking off the bottom right corner of a clipped grid
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let mut manager = SimulationManager::new();
+        assert!(manager.create("glider", &glider, Some((8, 8))));
+        
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(8, 8, None);
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        simulation.set_cells(&glider);
+        for generation in 1..=32 {
+            simulation.step();
+            manager.step_many(&HashMap::from([("glider".to_string(), 1)]));
+            let world = manager.get_mut("glider").unwrap();
+            assert_eq!(sorted(world.live_cells()), sorted(simulation.get_live_cells()), "generation {}", generation);
+        }
+    }
+}
@@ -1,13 +1,21 @@
 use bevy::prelude::*;
-use uuid::Uuid;
-use std::collections::HashMap;
-use std::time::SystemTime;
-use crate::components::{Position, CellState};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Instant, SystemTime};
+use crate::resources::{random_slug, step_turmites, Annotation, AutoPause, Breakpoint, CellChange, Checkpoint, ChunkedGrid, GridBoundary, GridRegion, MilestoneWebhook, Pacing, Rule, SeededRng, StagnationWatch, Turmite, TurmiteRule, Watchpoint};
+
+/// Random slugs tried before longer numbers are drawn, which are all but
+/// certain to be free.
+const SHORT_ID_ATTEMPTS: usize = 16;
+
+/// Live-cell count at which stepping switches from a single thread to the rayon pool.
+/// Below this the cost of splitting and merging outweighs the parallel speedup.
+pub const PARALLEL_STEP_THRESHOLD: u64 = 4096;
 
 #[derive(Resource)]
 pub struct Simulations {
     pub simulations: HashMap<String, SimulationData>,
-    pub server_start_time: SystemTime,
+    /// Monotonic, so uptime is unaffected by changes to the wall clock.
+    pub server_start_time: Instant,
 }
 
 impl Default for Simulations {
@@ -16,41 +24,152 @@ impl Default for Simulations {
     }
 }
 
+/// The cells of a pattern that land on the grid at some offset, in
+/// row-major order without repeats, and how many fell off its edges.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PatternPlacement {
+    pub cells: Vec<(i32, i32)>,
+    pub out_of_bounds: usize,
+}
+
+impl PatternPlacement {
+    /// The smallest rectangle holding every placed cell; `None` when none landed.
+    pub fn bounds(&self) -> Option<GridRegion> {
+        let min_x = self.cells.iter().map(|&(x, _)| x).min()?;
+        let max_x = self.cells.iter().map(|&(x, _)| x).max()?;
+        let (min_y, max_y) = (self.cells.first()?.1, self.cells.last()?.1);
+        Some(GridRegion { x: min_x, y: min_y, width: max_x - min_x + 1, height: max_y - min_y + 1 })
+    }
+    
+    /// Placed cells that are already alive in `simulation`.
+    pub fn overlapping(&self, simulation: &SimulationData) -> usize {
+        self.cells.iter().filter(|&&(x, y)| simulation.cells.is_alive(x, y)).count()
+    }
+}
+
+/// A change refused because the simulation has moved past the version its
+/// caller last saw.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleVersion {
+    pub id: String,
+    pub version: u64,
+    pub expected: u64,
+}
+
 #[derive(Debug, Clone)]
 pub struct SimulationData {
     pub id: String,
+    /// Label shown in simulation lists; ids stay the only way to address it.
+    pub name: Option<String>,
     pub generation: u64,
     pub width: i32,
     pub height: i32,
-    pub cells: HashMap<(i32, i32), CellState>,
+    pub cells: ChunkedGrid,
+    pub rule: Rule,
+    /// Rules to switch to on reaching each generation, set by SetRule.
+    pub rule_changes: BTreeMap<u64, Rule>,
+    pub boundary: GridBoundary,
+    /// Set for turmite simulations, whose cells are only changed by their
+    /// turmites; `rule` is then unused.
+    pub turmite_rule: Option<TurmiteRule>,
+    pub turmites: Vec<Turmite>,
     pub is_running: bool,
     pub created_at: SystemTime,
+    pub pacing: Pacing,
+    /// Generation at which stepping stops; `None` runs forever.
+    pub max_generations: Option<u64>,
+    /// `http://` URL told when the simulation reaches `max_generations`.
+    pub completion_webhook: Option<String>,
+    /// Webhooks told of the milestones they ask for, set by SetWebhooks.
+    pub webhooks: Vec<MilestoneWebhook>,
+    /// Set when an unattended run found the grid dead or repeating.
+    pub auto_pause: Option<AutoPause>,
+    pub stagnation: StagnationWatch,
+    /// Conditions that stop stepping, set by SetBreakpoints.
+    pub breakpoints: Vec<Breakpoint>,
+    /// Rectangles watched for changed cells, set by SetWatchpoints.
+    pub watchpoints: Vec<Watchpoint>,
+    /// Labels pinned to cells, set by SetAnnotations.
+    pub annotations: Vec<Annotation>,
+    /// States to roll back to, set by CreateCheckpoint.
+    pub checkpoints: Vec<Checkpoint>,
+    /// Seed its random starting cells were drawn from; `None` when it
+    /// started with none.
+    pub seed: Option<u64>,
+    /// Counts published changes, so clients can tell whether anyone else
+    /// changed the simulation since they last looked.
+    pub version: u64,
 }
 
 impl Simulations {
     pub fn new() -> Self {
         Self {
             simulations: HashMap::new(),
-            server_start_time: SystemTime::now(),
+            server_start_time: Instant::now(),
         }
     }
     
     pub fn create_simulation(&mut self, width: i32, height: i32, initial_pattern: Option<String>) -> String {
-        let id = Uuid::new_v4().to_string();
+        self.create_simulation_with_rule(width, height, initial_pattern, Rule::conway())
+    }
+    
+    pub fn create_simulation_with_rule(&mut self, width: i32, height: i32, _initial_pattern: Option<String>, rule: Rule) -> String {
+        let id = self.unused_id();
+        self.create_simulation_with_id(id, width, height, rule)
+    }
+    
+    /// Create an empty simulation named `id`, replacing any simulation
+    /// that already has it.
+    pub fn create_simulation_with_id(&mut self, id: String, width: i32, height: i32, rule: Rule) -> String {
         let simulation = SimulationData {
             id: id.clone(),
+            name: None,
             generation: 0,
             width,
             height,
-            cells: HashMap::new(),
+            cells: ChunkedGrid::new(),
+            rule,
+            rule_changes: BTreeMap::new(),
+            boundary: GridBoundary::Clip,
+            turmite_rule: None,
+            turmites: Vec::new(),
             is_running: false,
             created_at: SystemTime::now(),
+            pacing: Pacing::turbo(),
+            max_generations: None,
+            completion_webhook: None,
+            webhooks: Vec::new(),
+            auto_pause: None,
+            stagnation: StagnationWatch::default(),
+            breakpoints: Vec::new(),
+            watchpoints: Vec::new(),
+            annotations: Vec::new(),
+            checkpoints: Vec::new(),
+            seed: None,
+            version: 0,
         };
         
         self.simulations.insert(id.clone(), simulation);
         id
     }
     
+    /// Create a simulation run by turmites instead of a cell rule. Turmites
+    /// outside the grid are dropped.
+    pub fn create_turmite_simulation(&mut self, width: i32, height: i32, rule: TurmiteRule, turmites: Vec<Turmite>) -> String {
+        let id = self.unused_id();
+        self.create_turmite_simulation_with_id(id, width, height, rule, turmites)
+    }
+    
+    /// Create a turmite simulation named `id`, replacing any simulation that
+    /// already has it.
+    pub fn create_turmite_simulation_with_id(&mut self, id: String, width: i32, height: i32, rule: TurmiteRule, turmites: Vec<Turmite>) -> String {
+        let id = self.create_simulation_with_id(id, width, height, Rule::conway());
+        let simulation = self.simulations.get_mut(&id).unwrap();
+        simulation.turmite_rule = Some(rule);
+        simulation.set_turmites(turmites);
+        id
+    }
+    
     pub fn get_simulation(&self, id: &str) -> Option<&SimulationData> {
         self.simulations.get(id)
     }
@@ -59,55 +178,456 @@ impl Simulations {
         self.simulations.get_mut(id)
     }
     
+    /// Copy a simulation's current state into a new simulation, returning its
+    /// id. The copy starts out stopped, with no auto-pause recorded and no
+    /// webhooks, so it never notifies the source's owner. Its breakpoints,
+    /// watchpoints, annotations and checkpoints are copied with the cells
+    /// they were set against.
+    pub fn clone_simulation(&mut self, id: &str, name: Option<String>) -> Option<String> {
+        let source = self.simulations.get(id)?;
+        let clone_id = self.unused_id();
+        let clone = SimulationData {
+            id: clone_id.clone(),
+            name,
+            is_running: false,
+            created_at: SystemTime::now(),
+            auto_pause: None,
+            stagnation: StagnationWatch::default(),
+            completion_webhook: None,
+            webhooks: Vec::new(),
+            version: 0,
+            ..source.clone()
+        };
+        
+        self.simulations.insert(clone_id.clone(), clone);
+        Some(clone_id)
+    }
+    
+    /// A human-friendly id such as `brisk-glider-42` that no simulation has.
+    pub fn unused_id(&self) -> String {
+        let mut rng = SeededRng::from_entropy();
+        let mut attempts = 0;
+        loop {
+            let mut id = random_slug(&mut rng);
+            if attempts >= SHORT_ID_ATTEMPTS {
+                id = format!("{}-{}", id, rng.next_u64());
+            }
+            if !self.simulations.contains_key(&id) {
+                return id;
+            }
+            attempts += 1;
+        }
+    }
+    
     pub fn delete_simulation(&mut self, id: &str) -> bool {
         self.simulations.remove(id).is_some()
     }
     
+    pub fn total_chunks(&self) -> usize {
+        self.simulations.values().map(|simulation| simulation.cells.chunk_count()).sum()
+    }
+    
     pub fn uptime_seconds(&self) -> i64 {
-        SystemTime::now()
-            .duration_since(self.server_start_time)
-            .unwrap_or_default()
-            .as_secs() as i64
+        self.server_start_time.elapsed().as_secs() as i64
     }
 }
 
 impl SimulationData {
+    pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && x < self.width && y >= 0 && y < self.height
+    }
+    
     pub fn set_cells(&mut self, cells: &[(i32, i32)]) {
         self.cells.clear();
         for (x, y) in cells {
-            if *x >= 0 && *x < self.width && *y >= 0 && *y < self.height {
-                self.cells.insert((*x, *y), CellState::new());
+            if self.in_bounds(*x, *y) {
+                self.cells.set(*x, *y, true);
             }
         }
     }
     
+    /// Give cells a state above 1: a decay state under a Generations rule, or
+    /// any other state of a rule table. States outside the rule's range and
+    /// cells outside the grid are skipped.
+    pub fn set_decaying(&mut self, cells: &[(i32, i32, u8)]) {
+        for &(x, y, state) in cells {
+            if self.in_bounds(x, y) && state >= 2 && (state as u32) < self.rule.states() {
+                self.cells.set_state(x, y, state);
+            }
+        }
+    }
+    
+    /// Color live cells under a multi-color rule. Colors the rule does not
+    /// have, dead cells and cells outside the grid are skipped.
+    pub fn set_colors(&mut self, cells: &[(i32, i32, u8)]) {
+        for &(x, y, color) in cells {
+            if self.in_bounds(x, y) && (color as u32) < self.rule.colors() {
+                self.cells.set_color(x, y, color);
+            }
+        }
+    }
+    
+    /// Switch to another cell rule. Decay states and colors the new rule does
+    /// not have are dropped: such cells die or lose their color.
+    pub fn set_rule(&mut self, rule: Rule) {
+        let dropped: Vec<_> = self.cells.iter_decaying()
+            .filter(|&(_, state)| state as u32 >= rule.states())
+            .map(|(cell, _)| cell)
+            .collect();
+        for (x, y) in dropped {
+            self.cells.set(x, y, false);
+        }
+        let faded: Vec<_> = self.cells.iter_colored()
+            .filter(|&(_, color)| color as u32 >= rule.colors())
+            .map(|(cell, _)| cell)
+            .collect();
+        for (x, y) in faded {
+            self.cells.set_color(x, y, 0);
+        }
+        self.rule = rule;
+    }
+    
+    /// Switch to `rule` now, or on reaching `generation` if that is still
+    /// ahead, replacing any switch already due then; without a rule, cancel
+    /// the switch due at `generation`.
+    pub fn schedule_rule(&mut self, rule: Option<Rule>, generation: Option<u64>) {
+        match (rule, generation) {
+            (Some(rule), Some(generation)) if generation > self.generation => {
+                self.rule_changes.insert(generation, rule);
+            }
+            (Some(rule), _) => self.set_rule(rule),
+            (None, Some(generation)) => {
+                self.rule_changes.remove(&generation);
+            }
+            (None, None) => {}
+        }
+    }
+    
+    /// Switch to the rules due by the current generation, the latest last.
+    /// A changed rule makes earlier states no guide to repeats, so the
+    /// stagnation watch starts over.
+    fn apply_rule_changes(&mut self) {
+        while let Some(change) = self.rule_changes.first_entry() {
+            if *change.key() > self.generation {
+                break;
+            }
+            let rule = change.remove();
+            self.set_rule(rule);
+            self.stagnation = StagnationWatch::default();
+        }
+    }
+    
+    /// Live cells with a color other than 0, and their colors, sorted.
+    pub fn get_colored_cells(&self) -> Vec<(i32, i32, u8)> {
+        let mut cells: Vec<_> = self.cells.iter_colored().map(|((x, y), color)| (x, y, color)).collect();
+        cells.sort_unstable();
+        cells
+    }
+    
+    pub fn set_turmites(&mut self, turmites: Vec<Turmite>) {
+        self.turmites = turmites.into_iter().filter(|turmite| self.in_bounds(turmite.x, turmite.y)).collect();
+    }
+    
     pub fn get_live_cells(&self) -> Vec<(i32, i32)> {
-        self.cells
-            .iter()
-            .filter(|(_, cell)| cell.alive)
-            .map(|((x, y), _)| (*x, *y))
-            .collect()
+        self.cells.live_cells()
+    }
+    
+    /// Cells decaying under a Generations rule and their states, sorted.
+    pub fn get_decaying_cells(&self) -> Vec<(i32, i32, u8)> {
+        let mut cells: Vec<_> = self.cells.iter_decaying().map(|((x, y), state)| (x, y, state)).collect();
+        cells.sort_unstable();
+        cells
     }
     
     pub fn get_live_cell_count(&self) -> i64 {
-        self.cells.values().filter(|cell| cell.alive).count() as i64
+        self.cells.population() as i64
     }
     
     pub fn add_pattern(&mut self, pattern: &[(i32, i32)], offset_x: i32, offset_y: i32) -> i32 {
         let mut cells_added = 0;
         
         for (x, y) in pattern {
-            let new_x = x + offset_x;
-            let new_y = y + offset_y;
+            // Cells pushed past the coordinate range are off the grid too
+            let (Some(new_x), Some(new_y)) = (x.checked_add(offset_x), y.checked_add(offset_y)) else {
+                continue;
+            };
             
-            if new_x >= 0 && new_x < self.width && new_y >= 0 && new_y < self.height {
-                if !self.cells.contains_key(&(new_x, new_y)) {
-                    self.cells.insert((new_x, new_y), CellState::new());
-                    cells_added += 1;
-                }
+            if self.in_bounds(new_x, new_y) && self.cells.set(new_x, new_y, true) {
+                cells_added += 1;
             }
         }
         
         cells_added
     }
-}
\ No newline at end of file
+    
+    /// Where `pattern` would land at the offset, without changing the grid.
+    pub fn place_pattern(&self, pattern: &[(i32, i32)], offset_x: i32, offset_y: i32) -> PatternPlacement {
+        let mut placement = PatternPlacement::default();
+        for &(x, y) in pattern {
+            match (x.checked_add(offset_x), y.checked_add(offset_y)) {
+                (Some(x), Some(y)) if self.in_bounds(x, y) => placement.cells.push((x, y)),
+                _ => placement.out_of_bounds += 1,
+            }
+        }
+        placement.cells.sort_unstable_by_key(|&(x, y)| (y, x));
+        placement.cells.dedup();
+        placement
+    }
+    
+    /// Refuse a change unless the simulation is still at the `expected`
+    /// version; `None` accepts any.
+    pub fn check_version(&self, expected: Option<u64>) -> Result<(), StaleVersion> {
+        match expected {
+            Some(expected) if expected != self.version => {
+                Err(StaleVersion { id: self.id.clone(), version: self.version, expected })
+            }
+            _ => Ok(()),
+        }
+    }
+    
+    /// Whether the simulation has reached its generation limit.
+    pub fn is_completed(&self) -> bool {
+        self.max_generations.is_some_and(|max| self.generation >= max)
+    }
+    
+    /// How many of `steps` generations can run before the limit.
+    pub fn steps_allowed(&self, steps: u32) -> u32 {
+        match self.max_generations {
+            Some(max) => max.saturating_sub(self.generation).min(steps as u64) as u32,
+            None => steps,
+        }
+    }
+    
+    /// Whether an unattended run paused the simulation and it has not changed
+    /// since.
+    pub fn is_auto_paused(&self) -> bool {
+        self.auto_pause.is_some_and(|pause| pause.holds_for(&self.cells, &self.turmites))
+    }
+    
+    /// Check every breakpoint against the generation just stepped to from
+    /// one of `previous_population` live cells, returning the index of the
+    /// first that fired.
+    pub fn check_breakpoints(&mut self, previous_population: u64) -> Option<usize> {
+        let (generation, cells) = (self.generation, &self.cells);
+        // Every breakpoint looks, so pattern counts stay current
+        self.breakpoints.iter_mut()
+            .map(|breakpoint| breakpoint.fired(generation, previous_population, cells))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .position(|fired| fired)
+    }
+    
+    /// Check every watchpoint for cells changed since it last looked,
+    /// returning the index and changed cells of those with any.
+    pub fn check_watchpoints(&mut self) -> Vec<(usize, Vec<CellChange>)> {
+        let cells = &self.cells;
+        self.watchpoints.iter_mut()
+            .map(|watchpoint| watchpoint.changes(cells))
+            .enumerate()
+            .filter(|(_, changed)| !changed.is_empty())
+            .collect()
+    }
+    
+    /// Step like [`step`](Self::step), watching for the population dying out
+    /// or the grid repeating an earlier state. Returns the pause once it
+    /// does, also recording it in `auto_pause`.
+    pub fn step_watched(&mut self) -> Option<AutoPause> {
+        self.stagnation.resume(self.generation, &self.cells, &self.turmites);
+        self.step();
+        let pause = self.stagnation.observe(self.generation, &self.cells, &self.turmites);
+        if pause.is_some() {
+            self.auto_pause = pause;
+        }
+        pause
+    }
+    
+    /// Advance the simulation by one generation under its rule, or move
+    /// each of its turmites once.
+    ///
+    /// Large populations step their chunks in parallel; callers control the
+    /// thread count by running this inside `ThreadPool::install`. Cells born
+    /// outside the grid bounds are discarded, or under a wrapped boundary
+    /// reached from the opposite edge.
+    pub fn step(&mut self) {
+        self.generation += 1;
+        
+        if let Some(rule) = &self.turmite_rule {
+            step_turmites(rule, &mut self.turmites, &mut self.cells, self.boundary, self.width, self.height);
+            self.pacing.record_step(Instant::now());
+            return;
+        }
+        
+        self.boundary.extend(&mut self.cells, self.width, self.height, self.rule.range() as i32);
+        let parallel = self.cells.population() >= PARALLEL_STEP_THRESHOLD;
+        self.cells = self.cells.step_rule(&self.rule, parallel);
+        self.cells.retain_rect(self.width, self.height);
+        self.apply_rule_changes();
+        self.pacing.record_step(Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn live_set(simulation: &SimulationData) -> std::collections::HashSet<(i32, i32)> {
+        simulation.get_live_cells().into_iter().collect()
+    }
+    
+    #[test]
+    fn test_step_blinker_oscillates() {
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(10, 10, None);
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        simulation.set_cells(&[(4, 3), (4, 4), (4, 5)]);
+        
+        simulation.step();
+        assert_eq!(simulation.generation, 1);
+        assert_eq!(live_set(simulation), [(3, 4), (4, 4), (5, 4)].into_iter().collect());
+        
+        simulation.step();
+        assert_eq!(live_set(simulation), [(4, 3), (4, 4), (4, 5)].into_iter().collect());
+    }
+    
+    #[test]
+    fn test_step_clips_to_grid_bounds() {
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(5, 5, None);
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        
+        // Blinker on the top edge would grow into y = -1
+        simulation.set_cells(&[(1, 0), (2, 0), (3, 0)]);
+        simulation.step();
+        
+        assert_eq!(live_set(simulation), [(2, 0), (2, 1)].into_iter().collect());
+    }
+    
+    #[test]
+    fn test_parallel_step_matches_sequential() {
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(400, 400, None);
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        
+        // Tile gliders and blinkers until we are well above the parallel threshold
+        let mut cells = Vec::new();
+        for gx in 0..40 {
+            for gy in 0..40 {
+                let (ox, oy) = (gx * 10, gy * 10);
+                cells.extend([(ox + 1, oy), (ox + 2, oy + 1), (ox, oy + 2), (ox + 1, oy + 2), (ox + 2, oy + 2)]);
+                cells.extend([(ox + 6, oy + 5), (ox + 6, oy + 6), (ox + 6, oy + 7)]);
+            }
+        }
+        simulation.set_cells(&cells);
+        assert!(simulation.cells.population() >= PARALLEL_STEP_THRESHOLD);
+        
+        let mut expected = simulation.cells.clone();
+        for _ in 0..4 {
+            expected = expected.step(false);
+            expected.retain_rect(400, 400);
+            simulation.step();
+        }
+        
+        assert_eq!(simulation.cells, expected);
+    }
+    
+    #[test]
+    fn test_steps_stop_at_max_generations() {
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(10, 10, None);
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        assert_eq!(simulation.steps_allowed(100), 100);
+        
+        simulation.max_generations = Some(5);
+        simulation.generation = 3;
+        assert_eq!(simulation.steps_allowed(100), 2);
+        assert_eq!(simulation.steps_allowed(1), 1);
+        assert!(!simulation.is_completed());
+        
+        simulation.generation = 7;
+        assert_eq!(simulation.steps_allowed(100), 0);
+        assert!(simulation.is_completed());
+    }
+    
+    #[test]
+    fn test_clone_copies_state_independently() {
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(10, 10, None);
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        simulation.set_cells(&[(4, 3), (4, 4), (4, 5)]);
+        simulation.step();
+        simulation.completion_webhook = Some("http://owner.example/done".to_string());
+        simulation.webhooks = vec![MilestoneWebhook { url: "http://owner.example/hook".to_string(), ..Default::default() }];
+        
+        let clone_id = simulations.clone_simulation(&id, Some("copy".to_string())).unwrap();
+        assert_ne!(clone_id, id);
+        let clone = simulations.get_simulation_mut(&clone_id).unwrap();
+        assert_eq!(clone.name.as_deref(), Some("copy"));
+        assert_eq!(clone.generation, 1);
+        assert!(clone.webhooks.is_empty());
+        assert_eq!(clone.completion_webhook, None);
+        clone.step();
+        
+        let original = simulations.get_simulation(&id).unwrap();
+        assert_eq!(original.generation, 1);
+        assert_eq!(live_set(original), [(3, 4), (4, 4), (5, 4)].into_iter().collect());
+        assert!(simulations.clone_simulation("missing", None).is_none());
+    }
+    
+    #[test]
+    fn test_scheduled_rule_applies_from_its_generation() {
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(10, 10, None);
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        let without_death: Rule = "B3/S012345678".parse().unwrap();
+        simulation.schedule_rule(Some(without_death.clone()), Some(2));
+        
+        // A lone cell dies under B3/S23 until the switch
+        simulation.set_cells(&[(5, 5)]);
+        simulation.step();
+        assert!(live_set(simulation).is_empty());
+        assert!(simulation.rule.is_conway());
+        simulation.step();
+        assert_eq!(simulation.rule, without_death);
+        assert!(simulation.rule_changes.is_empty());
+        simulation.set_cells(&[(5, 5)]);
+        simulation.step();
+        assert_eq!(live_set(simulation), [(5, 5)].into_iter().collect());
+        
+        // No rule cancels; a generation already reached switches now
+        simulation.schedule_rule(Some(Rule::conway()), Some(10));
+        simulation.schedule_rule(None, Some(10));
+        assert!(simulation.rule_changes.is_empty());
+        simulation.schedule_rule(Some(Rule::conway()), Some(3));
+        assert!(simulation.rule.is_conway());
+    }
+    
+    #[test]
+    fn test_turmite_simulation_ignores_cell_rule() {
+        let mut simulations = Simulations::new();
+        let ant = Turmite { x: 5, y: 5, ..Default::default() };
+        let id = simulations.create_turmite_simulation(10, 10, TurmiteRule::langtons_ant(), vec![ant, Turmite { x: 20, ..ant }]);
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        assert_eq!(simulation.turmites.len(), 1);
+        
+        // A lone cell would die under B3/S23 but turmites leave it be
+        simulation.set_cells(&[(0, 0)]);
+        simulation.step();
+        assert_eq!(live_set(simulation), [(0, 0), (5, 5)].into_iter().collect());
+        assert_eq!((simulation.turmites[0].x, simulation.turmites[0].y), (6, 5));
+    }
+    
+    #[test]
+    fn test_place_pattern_reports_cells_off_the_grid() {
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(10, 10, None);
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        simulation.set_cells(&[(8, 8)]);
+        
+        let placement = simulation.place_pattern(&[(0, 0), (1, 1), (1, 1), (3, 0), (i32::MAX, 0)], 7, 7);
+        assert_eq!(placement.cells, vec![(7, 7), (8, 8)]);
+        assert_eq!(placement.out_of_bounds, 2);
+        assert_eq!(placement.overlapping(simulation), 1);
+        assert_eq!(placement.bounds(), Some(GridRegion { x: 7, y: 7, width: 2, height: 2 }));
+        assert_eq!(simulation.place_pattern(&[(0, 0)], 20, 0).bounds(), None);
+    }
+}
@@ -0,0 +1,63 @@
+//! Human-friendly simulation ids.
+//!
+//! New simulations are named like `brisk-glider-42`: an adjective, a Life
+//! object and a number, drawn until no simulation has the name, so users
+//! can read and type ids instead of copying UUIDs. Ids chosen by clients
+//! must be slugs of the same alphabet.
+
+use crate::resources::SeededRng;
+
+const ADJECTIVES: [&str; 24] = [
+    "amber", "bold", "brisk", "calm", "clever", "crisp", "dapper", "eager",
+    "fancy", "gentle", "hardy", "jolly", "keen", "lively", "lucky", "mellow",
+    "nimble", "proud", "quiet", "rapid", "steady", "sunny", "swift", "vivid",
+];
+
+const OBJECTS: [&str; 24] = [
+    "acorn", "beacon", "beehive", "blinker", "block", "boat", "clock", "diehard",
+    "eater", "glider", "gun", "hive", "honeyfarm", "loaf", "pond", "pulsar",
+    "puffer", "rake", "ship", "snake", "spaceship", "tub", "toad", "wick",
+];
+
+/// Numbers drawn are below this.
+const SUFFIXES: u64 = 100;
+
+/// Longest id a client may choose.
+pub const MAX_SLUG_LEN: usize = 64;
+
+/// An `adjective-object-number` slug drawn from `rng`.
+pub fn random_slug(rng: &mut SeededRng) -> String {
+    let adjective = ADJECTIVES[(rng.next_u64() % ADJECTIVES.len() as u64) as usize];
+    let object = OBJECTS[(rng.next_u64() % OBJECTS.len() as u64) as usize];
+    format!("{}-{}-{}", adjective, object, rng.next_u64() % SUFFIXES)
+}
+
+/// Whether `id` may name a simulation: lowercase ASCII letters, digits and
+/// hyphens, at most [`MAX_SLUG_LEN`] long, neither starting nor ending with
+/// a hyphen.
+pub fn is_slug(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_SLUG_LEN
+        && !id.starts_with('-')
+        && !id.ends_with('-')
+        && id.bytes().all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_slugs_are_slugs() {
+        let mut rng = SeededRng::new(3);
+        let slugs: Vec<String> = (0..50).map(|_| random_slug(&mut rng)).collect();
+        assert!(slugs.iter().all(|slug| is_slug(slug) && slug.split('-').count() == 3), "{:?}", slugs);
+        assert_eq!(slugs[0], random_slug(&mut SeededRng::new(3)));
+
+        assert!(is_slug("default"));
+        assert!(is_slug("brisk-glider-42"));
+        for id in ["", "-default", "default-", "Default", "a b", "sim_1", &"a".repeat(MAX_SLUG_LEN + 1)] {
+            assert!(!is_slug(id), "{:?}", id);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use arc_swap::ArcSwap;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use crate::resources::SimulationData;
+
+/// Immutable, lock-free views of every simulation for read-only RPCs.
+///
+/// Writers publish a fresh `Arc<SimulationData>` after each mutation, so
+/// readers load the latest snapshot without waiting on the simulations mutex
+/// that stepping holds. The outer `RwLock` only guards the id → slot map and is
+/// write-locked just when a simulation is created or deleted.
+#[derive(Default)]
+pub struct SimulationSnapshots {
+    slots: RwLock<HashMap<String, Arc<ArcSwap<SimulationData>>>>,
+}
+
+impl SimulationSnapshots {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Publish the current state of a simulation, replacing any previous
+    /// snapshot. Every publish is a change, so it bumps the version first.
+    pub fn publish(&self, simulation: &mut SimulationData) {
+        simulation.version += 1;
+        let snapshot = Arc::new(simulation.clone());
+        
+        if let Some(slot) = self.slots.read().unwrap().get(&simulation.id) {
+            slot.store(snapshot);
+            return;
+        }
+        
+        self.slots
+            .write()
+            .unwrap()
+            .insert(simulation.id.clone(), Arc::new(ArcSwap::new(snapshot)));
+    }
+    
+    /// Latest published snapshot of a simulation.
+    pub fn get(&self, id: &str) -> Option<Arc<SimulationData>> {
+        self.slots.read().unwrap().get(id).map(|slot| slot.load_full())
+    }
+    
+    pub fn remove(&self, id: &str) -> bool {
+        self.slots.write().unwrap().remove(id).is_some()
+    }
+    
+    pub fn len(&self) -> usize {
+        self.slots.read().unwrap().len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    /// Latest snapshot of every simulation.
+    pub fn all(&self) -> Vec<Arc<SimulationData>> {
+        self.slots.read().unwrap().values().map(|slot| slot.load_full()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::Simulations;
+    
+    #[test]
+    fn test_snapshot_is_isolated_from_later_mutation() {
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(10, 10, None);
+        let snapshots = SimulationSnapshots::new();
+        
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        simulation.set_cells(&[(1, 0), (1, 1), (1, 2)]);
+        snapshots.publish(simulation);
+        
+        let before = snapshots.get(&id).unwrap();
+        simulation.step();
+        assert_eq!(before.generation, 0);
+        
+        snapshots.publish(simulation);
+        let after = snapshots.get(&id).unwrap();
+        assert_eq!(after.generation, 1);
+        assert_eq!(before.generation, 0);
+    }
+    
+    #[test]
+    fn test_remove_snapshot() {
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(10, 10, None);
+        let snapshots = SimulationSnapshots::new();
+        snapshots.publish(simulations.get_simulation_mut(&id).unwrap());
+        
+        assert_eq!(snapshots.len(), 1);
+        assert!(snapshots.remove(&id));
+        assert!(snapshots.get(&id).is_none());
+        assert!(!snapshots.remove(&id));
+    }
+}
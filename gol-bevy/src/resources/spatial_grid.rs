@@ -0,0 +1,110 @@
+//! Live cell index for the ECS systems.
+//!
+//! The grid maps every live cell's position to its entity and keeps each
+//! position's live neighbor count up to date as cells are inserted and
+//! removed, so a generation costs work in the cells that changed rather than
+//! in every live cell. Positions whose count may have changed are collected
+//! until [`SpatialGrid::take_dirty`] hands them to the neighbor system.
+
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::components::Position;
+
+#[derive(Resource, Debug, Default)]
+pub struct SpatialGrid {
+    cells: HashMap<Position, Entity>,
+    /// Live neighbors of every position next to a live cell; positions with
+    /// none are left out.
+    neighbor_counts: HashMap<Position, u8>,
+    dirty: HashSet<Position>,
+}
+
+impl SpatialGrid {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Record a live cell. Inserting a position that is already live only
+    /// updates its entity.
+    pub fn insert(&mut self, position: Position, entity: Entity) {
+        if self.cells.insert(position, entity).is_some() {
+            return;
+        }
+        for neighbor in position.neighbors() {
+            *self.neighbor_counts.entry(neighbor).or_insert(0) += 1;
+            self.dirty.insert(neighbor);
+        }
+        self.dirty.insert(position);
+    }
+    
+    pub fn remove(&mut self, position: &Position) -> Option<Entity> {
+        let entity = self.cells.remove(position)?;
+        for neighbor in position.neighbors() {
+            if let Some(count) = self.neighbor_counts.get_mut(&neighbor) {
+                *count -= 1;
+                if *count == 0 {
+                    self.neighbor_counts.remove(&neighbor);
+                }
+            }
+            self.dirty.insert(neighbor);
+        }
+        self.dirty.insert(*position);
+        Some(entity)
+    }
+    
+    pub fn get(&self, position: &Position) -> Option<Entity> {
+        self.cells.get(position).copied()
+    }
+    
+    pub fn contains(&self, position: &Position) -> bool {
+        self.cells.contains_key(position)
+    }
+    
+    pub fn neighbor_count(&self, position: &Position) -> u8 {
+        self.neighbor_counts.get(position).copied().unwrap_or(0)
+    }
+    
+    /// Positions inserted, removed or next to one since the last call.
+    pub fn take_dirty(&mut self) -> Vec<Position> {
+        self.dirty.drain().collect()
+    }
+    
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+    
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.neighbor_counts.clear();
+        self.dirty.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_counts_follow_inserts_and_removes() {
+        let mut grid = SpatialGrid::new();
+        let a = Entity::from_raw(1);
+        grid.insert(Position::new(0, 0), a);
+        grid.insert(Position::new(1, 0), Entity::from_raw(2));
+        grid.insert(Position::new(0, 0), a);
+        assert_eq!(grid.neighbor_count(&Position::new(0, 1)), 2);
+        assert_eq!(grid.neighbor_count(&Position::new(0, 0)), 1);
+        assert_eq!(grid.take_dirty().len(), 12);
+        assert!(grid.take_dirty().is_empty());
+        
+        assert_eq!(grid.remove(&Position::new(1, 0)), Some(Entity::from_raw(2)));
+        assert_eq!(grid.neighbor_count(&Position::new(0, 1)), 1);
+        assert_eq!(grid.neighbor_count(&Position::new(2, 0)), 0);
+        assert_eq!(grid.take_dirty().len(), 9);
+        assert_eq!(grid.remove(&Position::new(1, 0)), None);
+    }
+}
@@ -0,0 +1,178 @@
+//! Spotting simulations that have stopped going anywhere.
+//!
+//! Unattended runs (background jobs and auto-stepping streams) would
+//! otherwise keep stepping a dead or repeating grid until their step budget
+//! runs out. After each generation they feed a fingerprint of the grid to a
+//! [`StagnationWatch`], which reports once the population is gone or the grid
+//! has returned to a state it held in the last [`MAX_CYCLE_PERIOD`]
+//! generations.
+//!
+//! Fingerprints are 64-bit hashes, so a repeat is detected by hash rather than
+//! by comparing whole grids. Collisions are possible but vanishingly rare.
+
+use std::collections::VecDeque;
+
+use crate::resources::{ChunkedGrid, Turmite};
+
+/// Longest cycle a watch can detect; longer ones run on.
+pub const MAX_CYCLE_PERIOD: usize = 64;
+
+/// Why an unattended run stopped stepping a simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// No live cells are left, nor turmites to draw new ones.
+    Extinct,
+    /// The grid repeats the state it held `period` generations earlier.
+    Cycle { period: u64 },
+}
+
+/// Record of an automatic pause: why and at which generation it happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoPause {
+    pub reason: StopReason,
+    pub generation: u64,
+    fingerprint: u64,
+}
+
+impl AutoPause {
+    /// Whether the simulation is still in the state it was paused in, so
+    /// stepping it further would be wasted.
+    pub fn holds_for(&self, cells: &ChunkedGrid, turmites: &[Turmite]) -> bool {
+        self.fingerprint == fingerprint(cells, turmites)
+    }
+}
+
+/// Fingerprints of the last generations of a simulation.
+#[derive(Debug, Clone, Default)]
+pub struct StagnationWatch {
+    recent: VecDeque<(u64, u64)>,
+}
+
+impl StagnationWatch {
+    /// Start watching from the state at `generation`. History is kept only if
+    /// this is the state last observed, since an edit in between breaks any
+    /// cycle.
+    pub fn resume(&mut self, generation: u64, cells: &ChunkedGrid, turmites: &[Turmite]) {
+        let current = (generation, fingerprint(cells, turmites));
+        if self.recent.back() != Some(&current) {
+            self.recent.clear();
+            self.recent.push_back(current);
+        }
+    }
+
+    /// Record the state reached at `generation` and report whether the
+    /// simulation has stagnated.
+    pub fn observe(&mut self, generation: u64, cells: &ChunkedGrid, turmites: &[Turmite]) -> Option<AutoPause> {
+        let current = fingerprint(cells, turmites);
+        let pause = |reason| AutoPause { reason, generation, fingerprint: current };
+
+        let extinct = cells.population() == 0 && cells.decaying_count() == 0 && turmites.is_empty();
+        let earlier = self.recent.iter().rev().find(|&&(_, seen)| seen == current).map(|&(seen_at, _)| seen_at);
+
+        if self.recent.len() > MAX_CYCLE_PERIOD {
+            self.recent.pop_front();
+        }
+        self.recent.push_back((generation, current));
+
+        if extinct {
+            Some(pause(StopReason::Extinct))
+        } else {
+            earlier.map(|seen_at| pause(StopReason::Cycle { period: generation - seen_at }))
+        }
+    }
+}
+
+/// Order-independent hash of every cell's position, state and color, and of
+/// the turmites in order.
+fn fingerprint(cells: &ChunkedGrid, turmites: &[Turmite]) -> u64 {
+    let live = cells.iter_live().map(|(x, y)| mix(position(x, y) ^ ((cells.color(x, y) as u64) << 48)));
+    let decaying = cells.iter_decaying().map(|((x, y), state)| mix(position(x, y) ^ ((state as u64) << 56)));
+    let grid = live.chain(decaying).fold(0u64, u64::wrapping_add);
+    turmites.iter().fold(grid, |hash, turmite| {
+        let walker = position(turmite.x, turmite.y) ^ ((turmite.heading as u64) << 40) ^ ((turmite.state as u64) << 48);
+        mix(hash ^ mix(walker))
+    })
+}
+
+fn position(x: i32, y: i32) -> u64 {
+    ((x as u32 as u64) << 32) | y as u32 as u64
+}
+
+/// splitmix64 finalizer.
+fn mix(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+    value ^ (value >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::Rule;
+
+    fn grid(cells: &[(i32, i32)]) -> ChunkedGrid {
+        let mut grid = ChunkedGrid::new();
+        for &(x, y) in cells {
+            grid.set(x, y, true);
+        }
+        grid
+    }
+
+    fn run(mut cells: ChunkedGrid, generations: u64) -> Option<AutoPause> {
+        let mut watch = StagnationWatch::default();
+        watch.resume(0, &cells, &[]);
+        for generation in 1..=generations {
+            cells = cells.step_rule(&Rule::conway(), false);
+            if let Some(pause) = watch.observe(generation, &cells, &[]) {
+                return Some(pause);
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn test_detects_still_life_and_oscillator() {
+        let block = run(grid(&[(0, 0), (1, 0), (0, 1), (1, 1)]), 10).unwrap();
+        assert_eq!((block.reason, block.generation), (StopReason::Cycle { period: 1 }, 1));
+
+        let blinker = run(grid(&[(0, 0), (1, 0), (2, 0)]), 10).unwrap();
+        assert_eq!((blinker.reason, blinker.generation), (StopReason::Cycle { period: 2 }, 2));
+    }
+
+    #[test]
+    fn test_detects_extinction() {
+        let pause = run(grid(&[(0, 0), (5, 5)]), 10).unwrap();
+        assert_eq!((pause.reason, pause.generation), (StopReason::Extinct, 1));
+    }
+
+    #[test]
+    fn test_moving_glider_is_not_a_cycle() {
+        assert_eq!(run(grid(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]), 40), None);
+    }
+
+    #[test]
+    fn test_edits_reset_history() {
+        let blinker = grid(&[(0, 0), (1, 0), (2, 0)]);
+        let mut watch = StagnationWatch::default();
+        watch.resume(0, &blinker, &[]);
+        let turned = blinker.step_rule(&Rule::conway(), false);
+        assert_eq!(watch.observe(1, &turned, &[]), None);
+
+        // Someone restored the horizontal phase by hand: not a cycle yet
+        watch.resume(1, &blinker, &[]);
+        assert_eq!(watch.observe(2, &turned, &[]), None);
+        assert!(watch.observe(3, &blinker, &[]).is_some());
+    }
+
+    #[test]
+    fn test_fingerprint_sees_states_and_turmites() {
+        let cells = grid(&[(3, 3)]);
+        let mut decaying = cells.clone();
+        decaying.set_state(4, 4, 2);
+        assert_ne!(fingerprint(&cells, &[]), fingerprint(&decaying, &[]));
+
+        let ant = Turmite { x: 1, y: 1, ..Default::default() };
+        let moved = Turmite { x: 2, ..ant };
+        assert_ne!(fingerprint(&cells, &[ant]), fingerprint(&cells, &[moved]));
+    }
+}
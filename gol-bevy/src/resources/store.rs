@@ -0,0 +1,190 @@
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+
+use crate::resources::{HistoryRetention, PersistedSimulation, SimulationData, Simulations};
+
+/// Persistent simulation store backed by sled.
+///
+/// The `latest` tree holds each simulation's most recent checkpoint and is
+/// what startup restores from. Every checkpoint is also appended to the
+/// `history` tree, keyed by simulation id and generation, so earlier states
+/// stay available until [`SimulationStore::compact`] thins them out.
+pub struct SimulationStore {
+    db: sled::Db,
+    latest: sled::Tree,
+    history: sled::Tree,
+}
+
+impl SimulationStore {
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        let db = sled::open(dir)?;
+        let latest = db.open_tree("latest")?;
+        let history = db.open_tree("history")?;
+        Ok(Self { db, latest, history })
+    }
+
+    /// Record the current state of a simulation as its latest checkpoint and
+    /// in its history.
+    pub fn checkpoint(&self, simulation: &SimulationData) -> io::Result<()> {
+        let value = serde_json::to_vec(&PersistedSimulation::from(simulation))?;
+        self.history.insert(history_key(&simulation.id, simulation.generation), value.as_slice())?;
+        self.latest.insert(simulation.id.as_bytes(), value)?;
+        Ok(())
+    }
+
+    /// Forget a simulation and its whole history.
+    pub fn remove(&self, id: &str) -> io::Result<()> {
+        self.latest.remove(id.as_bytes())?;
+        for key in self.history.scan_prefix(history_prefix(id)).keys() {
+            self.history.remove(key?)?;
+        }
+        Ok(())
+    }
+
+    /// Ids of every simulation with a checkpoint.
+    pub fn ids(&self) -> io::Result<HashSet<String>> {
+        self.latest.iter().keys()
+            .map(|key| Ok(String::from_utf8_lossy(&key?).into_owned()))
+            .collect()
+    }
+
+    /// Restore every simulation from its latest checkpoint.
+    pub fn load(&self) -> io::Result<Simulations> {
+        let mut simulations = Simulations::new();
+        for entry in self.latest.iter() {
+            let (_, value) = entry?;
+            let persisted: PersistedSimulation = serde_json::from_slice(&value)?;
+            simulations.simulations.insert(persisted.id.clone(), persisted.into());
+        }
+        Ok(simulations)
+    }
+
+    /// Generations with a stored checkpoint, oldest first.
+    pub fn history(&self, id: &str) -> io::Result<Vec<u64>> {
+        let prefix = history_prefix(id);
+        self.history.scan_prefix(&prefix).keys()
+            .map(|key| Ok(generation_from_key(&key?, prefix.len())))
+            .collect()
+    }
+
+    /// The state of a simulation as checkpointed at `generation`.
+    pub fn load_generation(&self, id: &str, generation: u64) -> io::Result<Option<SimulationData>> {
+        match self.history.get(history_key(id, generation))? {
+            Some(value) => {
+                let persisted: PersistedSimulation = serde_json::from_slice(&value)?;
+                Ok(Some(persisted.into()))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Thin a simulation's history according to `retention`, returning the
+    /// number of checkpoints removed.
+    pub fn compact(&self, id: &str, retention: &HistoryRetention) -> io::Result<usize> {
+        let generations = self.history(id)?;
+        let mut kept_spans = HashSet::new();
+        let mut removed = 0;
+
+        for (age, generation) in generations.into_iter().rev().enumerate() {
+            if age < retention.keep_recent {
+                continue;
+            }
+            let keep = retention.thin_every_generations > 0
+                && kept_spans.insert(generation / retention.thin_every_generations);
+            if !keep {
+                self.history.remove(history_key(id, generation))?;
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Block until every write so far is durable on disk.
+    pub fn flush(&self) -> io::Result<()> {
+        self.db.flush()?;
+        Ok(())
+    }
+}
+
+fn history_prefix(id: &str) -> Vec<u8> {
+    let mut prefix = id.as_bytes().to_vec();
+    prefix.push(0);
+    prefix
+}
+
+/// Big-endian generations keep a simulation's history sorted oldest first.
+fn history_key(id: &str, generation: u64) -> Vec<u8> {
+    let mut key = history_prefix(id);
+    key.extend_from_slice(&generation.to_be_bytes());
+    key
+}
+
+fn generation_from_key(key: &[u8], prefix_len: usize) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&key[prefix_len..prefix_len + 8]);
+    u64::from_be_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store() -> (SimulationStore, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("gol-store-{}", uuid::Uuid::new_v4()));
+        (SimulationStore::open(&dir).unwrap(), dir)
+    }
+
+    #[test]
+    fn test_checkpoints_restore_latest_state() {
+        let (store, dir) = temp_store();
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(20, 20, None);
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        simulation.set_cells(&[(1, 2), (2, 2), (3, 2)]);
+
+        store.checkpoint(simulation).unwrap();
+        simulation.step();
+        store.checkpoint(simulation).unwrap();
+
+        let restored = store.load().unwrap();
+        let loaded = restored.get_simulation(&id).unwrap();
+        assert_eq!(loaded.generation, 1);
+        assert_eq!(loaded.cells, simulation.cells);
+
+        assert_eq!(store.history(&id).unwrap(), vec![0, 1]);
+        let first = store.load_generation(&id, 0).unwrap().unwrap();
+        assert!(first.cells.is_alive(1, 2));
+
+        store.remove(&id).unwrap();
+        assert!(store.ids().unwrap().is_empty());
+        assert!(store.history(&id).unwrap().is_empty());
+
+        drop(store);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_compaction_keeps_recent_and_thins_older() {
+        let (store, dir) = temp_store();
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(10, 10, None);
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+
+        for generation in (0..100).step_by(10) {
+            simulation.generation = generation;
+            store.checkpoint(simulation).unwrap();
+        }
+
+        let retention = HistoryRetention { keep_recent: 3, thin_every_generations: 25 };
+        let removed = store.compact(&id, &retention).unwrap();
+
+        // 70, 80, 90 are recent; older ones keep the newest per 25-generation span
+        assert_eq!(store.history(&id).unwrap(), vec![20, 40, 60, 70, 80, 90]);
+        assert_eq!(removed, 4);
+
+        drop(store);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,364 @@
+//! Turmites: agents that walk the grid, reading and flipping cells as they go.
+//!
+//! Each turmite has a position, a heading and an internal state. Every
+//! generation it looks up its state and the cell under it in the rule's
+//! transition table, turns, writes the cell, changes state and moves one cell
+//! forward. Langton's ant is the one-state turmite `RL`: turn right on a dead
+//! cell and left on a live one, flipping the cell either way.
+//!
+//! Rules are written either as a turn per cell value (`RL`, `LR`, `RN`, with
+//! `N` for no turn and `U` for a U-turn) or in Golly's notation, one
+//! `{write, turn, next state}` triple per state and cell value, with turns
+//! coded 1 (none), 2 (right), 4 (U-turn) and 8 (left), so Langton's ant is
+//! also `{{{1,2,0},{0,8,0}}}`. Only two-valued cells are supported.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::resources::{ChunkedGrid, GridBoundary};
+
+/// Most internal states a turmite rule may have.
+pub const MAX_TURMITE_STATES: usize = 256;
+
+/// Error raised while parsing a turmite rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurmiteError(pub String);
+
+impl fmt::Display for TurmiteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TurmiteError {}
+
+/// Compass direction a turmite faces; north is towards smaller y.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Heading {
+    #[default]
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Heading {
+    const CLOCKWISE: [Heading; 4] = [Heading::North, Heading::East, Heading::South, Heading::West];
+
+    pub fn turn(self, turn: Turn) -> Self {
+        let quarters = match turn {
+            Turn::None => 0,
+            Turn::Right => 1,
+            Turn::UTurn => 2,
+            Turn::Left => 3,
+        };
+        Self::CLOCKWISE[(self as usize + quarters) % 4]
+    }
+
+    /// The heading seen in a mirror that flips left and right (`flip_x`)
+    /// and/or up and down (`flip_y`).
+    pub fn mirror(self, flip_x: bool, flip_y: bool) -> Self {
+        match self {
+            Heading::East | Heading::West if flip_x => self.turn(Turn::UTurn),
+            Heading::North | Heading::South if flip_y => self.turn(Turn::UTurn),
+            _ => self,
+        }
+    }
+
+    /// Offset of the cell one step ahead.
+    pub fn delta(self) -> (i32, i32) {
+        match self {
+            Heading::North => (0, -1),
+            Heading::East => (1, 0),
+            Heading::South => (0, 1),
+            Heading::West => (-1, 0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Turn {
+    None,
+    Right,
+    UTurn,
+    Left,
+}
+
+impl Turn {
+    fn from_letter(letter: char) -> Option<Self> {
+        match letter.to_ascii_uppercase() {
+            'N' => Some(Turn::None),
+            'R' => Some(Turn::Right),
+            'U' => Some(Turn::UTurn),
+            'L' => Some(Turn::Left),
+            _ => None,
+        }
+    }
+
+    fn letter(self) -> char {
+        match self {
+            Turn::None => 'N',
+            Turn::Right => 'R',
+            Turn::UTurn => 'U',
+            Turn::Left => 'L',
+        }
+    }
+
+    fn from_code(code: u32) -> Option<Self> {
+        match code {
+            1 => Some(Turn::None),
+            2 => Some(Turn::Right),
+            4 => Some(Turn::UTurn),
+            8 => Some(Turn::Left),
+            _ => None,
+        }
+    }
+
+    fn code(self) -> u32 {
+        match self {
+            Turn::None => 1,
+            Turn::Right => 2,
+            Turn::UTurn => 4,
+            Turn::Left => 8,
+        }
+    }
+}
+
+/// What a turmite does in one state on one cell value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    /// Whether the cell is left alive.
+    pub write: bool,
+    pub turn: Turn,
+    pub next_state: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurmiteRule {
+    /// Indexed by state, then by cell value (dead, alive).
+    table: Vec<[Transition; 2]>,
+}
+
+impl TurmiteRule {
+    /// Langton's ant, `RL`.
+    pub fn langtons_ant() -> Self {
+        "RL".parse().unwrap()
+    }
+
+    pub fn states(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn transition(&self, state: u8, alive: bool) -> Transition {
+        self.table[state as usize][alive as usize]
+    }
+
+    /// Whether the rule is a one-state ant that flips every cell it visits,
+    /// and so can be written as a turn per cell value.
+    fn is_ant(&self) -> bool {
+        self.table.len() == 1 && self.table[0][0].write && !self.table[0][1].write
+    }
+
+    fn parse_ant(text: &str) -> Result<Self, TurmiteError> {
+        let turns: Vec<Turn> = text.chars()
+            .map(|letter| Turn::from_letter(letter)
+                .ok_or_else(|| TurmiteError(format!("Unknown turn '{}', expected L, R, N or U", letter))))
+            .collect::<Result<_, _>>()?;
+        let [on_dead, on_alive] = turns[..] else {
+            return Err(TurmiteError(format!("Ant rule '{}' must give one turn for each of the 2 cell values", text)));
+        };
+        Ok(Self {
+            table: vec![[
+                Transition { write: true, turn: on_dead, next_state: 0 },
+                Transition { write: false, turn: on_alive, next_state: 0 },
+            ]],
+        })
+    }
+
+    fn parse_golly(text: &str) -> Result<Self, TurmiteError> {
+        let numbers: Vec<u32> = text.split(|c: char| !c.is_ascii_digit())
+            .filter(|number| !number.is_empty())
+            .map(|number| number.parse().map_err(|_| TurmiteError(format!("Invalid number '{}'", number))))
+            .collect::<Result<_, _>>()?;
+        let depth = text.chars().take_while(|&c| c == '{').count();
+        if depth != 3 || numbers.is_empty() || !numbers.len().is_multiple_of(6) {
+            return Err(TurmiteError(
+                "Turmite table must list {write, turn, next state} for both cell values of every state".to_string(),
+            ));
+        }
+
+        let states = numbers.len() / 6;
+        if states > MAX_TURMITE_STATES {
+            return Err(TurmiteError(format!("Turmite has {} states, at most {} are supported", states, MAX_TURMITE_STATES)));
+        }
+        let transition = |triple: &[u32]| -> Result<Transition, TurmiteError> {
+            let write = match triple[0] {
+                0 => false,
+                1 => true,
+                color => return Err(TurmiteError(format!("Cell value {} is not supported, only 0 and 1", color))),
+            };
+            let turn = Turn::from_code(triple[1])
+                .ok_or_else(|| TurmiteError(format!("Unknown turn code {}, expected 1, 2, 4 or 8", triple[1])))?;
+            if triple[2] as usize >= states {
+                return Err(TurmiteError(format!("Next state {} is out of range for {} states", triple[2], states)));
+            }
+            Ok(Transition { write, turn, next_state: triple[2] as u8 })
+        };
+
+        let table = numbers.chunks(6)
+            .map(|state| Ok([transition(&state[..3])?, transition(&state[3..])?]))
+            .collect::<Result<_, TurmiteError>>()?;
+        Ok(Self { table })
+    }
+}
+
+impl FromStr for TurmiteRule {
+    type Err = TurmiteError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+        if text.is_empty() {
+            Err(TurmiteError("Turmite rule is empty".to_string()))
+        } else if text.starts_with('{') {
+            Self::parse_golly(&text)
+        } else {
+            Self::parse_ant(&text)
+        }
+    }
+}
+
+impl fmt::Display for TurmiteRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ant() {
+            return write!(f, "{}{}", self.table[0][0].turn.letter(), self.table[0][1].turn.letter());
+        }
+        let states: Vec<String> = self.table.iter()
+            .map(|transitions| {
+                let triples: Vec<String> = transitions.iter()
+                    .map(|t| format!("{{{},{},{}}}", t.write as u8, t.turn.code(), t.next_state))
+                    .collect();
+                format!("{{{}}}", triples.join(","))
+            })
+            .collect();
+        write!(f, "{{{}}}", states.join(","))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Turmite {
+    pub x: i32,
+    pub y: i32,
+    pub heading: Heading,
+    pub state: u8,
+}
+
+/// Advance every turmite one move, in order, so later turmites see the cells
+/// earlier ones wrote. Turmites that walk off the `width` x `height` grid
+/// come back on where `boundary` joins the edge they crossed, turned as it
+/// reflects them, or are removed from a clipped grid.
+pub fn step_turmites(
+    rule: &TurmiteRule,
+    turmites: &mut Vec<Turmite>,
+    cells: &mut ChunkedGrid,
+    boundary: GridBoundary,
+    width: i32,
+    height: i32,
+) {
+    turmites.retain_mut(|turmite| {
+        let transition = rule.transition(turmite.state, cells.is_alive(turmite.x, turmite.y));
+        turmite.heading = turmite.heading.turn(transition.turn);
+        cells.set(turmite.x, turmite.y, transition.write);
+        turmite.state = transition.next_state;
+        let (dx, dy) = turmite.heading.delta();
+        let Some((x, y, flip_x, flip_y)) = boundary.fold(turmite.x + dx, turmite.y + dy, width, height) else {
+            return false;
+        };
+        turmite.x = x;
+        turmite.y = y;
+        turmite.heading = turmite.heading.mirror(flip_x, flip_y);
+        true
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_and_formats_rules() {
+        let ant: TurmiteRule = "rl".parse().unwrap();
+        assert_eq!(ant, TurmiteRule::langtons_ant());
+        assert_eq!(ant.to_string(), "RL");
+        assert_eq!(ant.transition(0, false), Transition { write: true, turn: Turn::Right, next_state: 0 });
+
+        // Langton's ant in Golly's notation is shown in the short form
+        assert_eq!("{{{1, 2, 0}, {0, 8, 0}}}".parse::<TurmiteRule>().unwrap().to_string(), "RL");
+
+        let spiral = "{{{1,2,1},{0,1,0}},{{0,8,0},{1,1,1}}}";
+        let rule: TurmiteRule = spiral.parse().unwrap();
+        assert_eq!(rule.states(), 2);
+        assert_eq!(rule.transition(1, false), Transition { write: false, turn: Turn::Left, next_state: 0 });
+        assert_eq!(rule.to_string(), spiral);
+    }
+
+    #[test]
+    fn test_rejects_invalid_rules() {
+        for text in ["", "RLR", "RX", "{{{1,2,0}}}", "{{{1,2,1},{0,8,0}}}", "{{{2,2,0},{0,8,0}}}", "{{{1,3,0},{0,8,0}}}", "{1,2,0,0,8,0}"] {
+            assert!(text.parse::<TurmiteRule>().is_err(), "{} should be rejected", text);
+        }
+    }
+
+    #[test]
+    fn test_langtons_ant_first_moves() {
+        let rule = TurmiteRule::langtons_ant();
+        let mut cells = ChunkedGrid::new();
+        let mut turmites = vec![Turmite { x: 5, y: 5, ..Default::default() }];
+
+        // On empty cells the ant turns right each move, tracing a square
+        for _ in 0..4 {
+            step_turmites(&rule, &mut turmites, &mut cells, GridBoundary::Clip, 10, 10);
+        }
+        assert_eq!(turmites[0], Turmite { x: 5, y: 5, heading: Heading::North, state: 0 });
+        assert_eq!(cells.population(), 4);
+
+        // Back on a live cell it turns left and clears it
+        step_turmites(&rule, &mut turmites, &mut cells, GridBoundary::Clip, 10, 10);
+        assert_eq!(turmites[0], Turmite { x: 4, y: 5, heading: Heading::West, state: 0 });
+        assert!(!cells.is_alive(5, 5));
+    }
+
+    #[test]
+    fn test_turmites_leaving_the_grid_are_removed() {
+        let rule = TurmiteRule::langtons_ant();
+        let mut cells = ChunkedGrid::new();
+        let mut turmites = vec![
+            Turmite { x: 9, y: 0, heading: Heading::North, state: 0 },
+            Turmite { x: 2, y: 2, heading: Heading::North, state: 0 },
+        ];
+        step_turmites(&rule, &mut turmites, &mut cells, GridBoundary::Clip, 10, 10);
+        assert_eq!(turmites, vec![Turmite { x: 3, y: 2, heading: Heading::East, state: 0 }]);
+        assert!(cells.is_alive(9, 0));
+    }
+
+    #[test]
+    fn test_turmites_cross_joined_edges() {
+        let rule = TurmiteRule::langtons_ant();
+        let start = Turmite { x: 9, y: 0, heading: Heading::North, state: 0 };
+        let moved = |boundary| {
+            let mut turmites = vec![start];
+            step_turmites(&rule, &mut turmites, &mut ChunkedGrid::new(), boundary, 10, 10);
+            turmites
+        };
+        // Turning right onto the east edge
+        assert_eq!(moved(GridBoundary::Wrap), vec![Turmite { x: 0, y: 0, heading: Heading::East, state: 0 }]);
+        assert_eq!(moved(GridBoundary::Mirror), vec![Turmite { x: 9, y: 0, heading: Heading::West, state: 0 }]);
+        assert_eq!(moved(GridBoundary::KleinBottle), vec![Turmite { x: 0, y: 0, heading: Heading::East, state: 0 }]);
+
+        // Walking off the top of a Klein bottle arrives flipped at the bottom
+        let mut turmites = vec![Turmite { x: 2, y: 0, heading: Heading::West, state: 0 }];
+        step_turmites(&rule, &mut turmites, &mut ChunkedGrid::new(), GridBoundary::KleinBottle, 10, 10);
+        assert_eq!(turmites, vec![Turmite { x: 7, y: 9, heading: Heading::North, state: 0 }]);
+    }
+}
@@ -0,0 +1,212 @@
+//! Invariant checks over a simulation.
+//!
+//! Each check works on a private copy of a simulation and tests a property
+//! every correct engine keeps, so a claim that a backend steps correctly can
+//! be checked by a machine rather than by eye:
+//!
+//! - `conservation`: every generation the population changes by exactly its
+//!   births minus its deaths, the grid's population count matches the cells
+//!   it holds, and no cell lies outside the grid.
+//! - `still_life`: a grid one step leaves unchanged stays unchanged however
+//!   many more steps it takes. Skipped for grids that are not still lifes.
+//! - `determinism`: stepping on many threads and on one give the same grid.
+//!   Skipped for turmite simulations, which always step on one.
+//! - `history`: replaying the recorded operation log from its start rebuilds
+//!   the current generation and grid exactly.
+
+use crate::resources::{replay, ReplayRecord, SimulationData};
+
+/// Every check, in the order [`verify_simulation`] runs them.
+pub const INVARIANTS: [&str; 4] = ["conservation", "still_life", "determinism", "history"];
+
+/// Generations the stepping checks cover unless asked for another number.
+pub const DEFAULT_VERIFY_GENERATIONS: u64 = 16;
+
+/// Most generations the stepping checks may be asked to cover.
+pub const MAX_VERIFY_GENERATIONS: u64 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    /// The invariant does not apply to the simulation.
+    Skipped,
+}
+
+/// Outcome of one check: what was checked, or the first violation found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvariantCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl InvariantCheck {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self { name, status, detail: detail.into() }
+    }
+}
+
+/// Run the named checks, or every check when `checks` is empty, on
+/// `simulation` with its replay log `history`. Fails on an unknown name.
+pub fn verify_simulation(simulation: &SimulationData, history: &[ReplayRecord], generations: u64, checks: &[String]) -> Result<Vec<InvariantCheck>, String> {
+    if let Some(unknown) = checks.iter().find(|check| !INVARIANTS.contains(&check.as_str())) {
+        return Err(format!("Unknown check '{}', expected one of {}", unknown, INVARIANTS.join(", ")));
+    }
+    let wanted = |name: &str| checks.is_empty() || checks.iter().any(|check| check == name);
+    let mut results = Vec::new();
+    if wanted("conservation") {
+        results.push(check_conservation(simulation, generations));
+    }
+    if wanted("still_life") {
+        results.push(check_still_life(simulation, generations));
+    }
+    if wanted("determinism") {
+        results.push(check_determinism(simulation, generations));
+    }
+    if wanted("history") {
+        results.push(check_history(simulation, history));
+    }
+    Ok(results)
+}
+
+pub fn check_conservation(simulation: &SimulationData, generations: u64) -> InvariantCheck {
+    let fail = |detail: String| InvariantCheck::new("conservation", CheckStatus::Failed, detail);
+    let grid_violation = |copy: &SimulationData| {
+        let counted = copy.cells.iter_live().count() as u64;
+        if counted != copy.cells.population() {
+            return Some(format!("generation {}: population {} but {} live cells", copy.generation, copy.cells.population(), counted));
+        }
+        copy.cells.iter_live().find(|&(x, y)| !copy.in_bounds(x, y))
+            .map(|(x, y)| format!("generation {}: live cell ({}, {}) outside the grid", copy.generation, x, y))
+    };
+
+    let mut copy = simulation.clone();
+    if let Some(violation) = grid_violation(&copy) {
+        return fail(violation);
+    }
+    for _ in 0..generations {
+        let before = copy.cells.clone();
+        copy.step();
+        let births = copy.cells.difference(&before).len() as u64;
+        let deaths = before.difference(&copy.cells).len() as u64;
+        if before.population() + births != copy.cells.population() + deaths {
+            return fail(format!(
+                "generation {}: population went from {} to {} with {} births and {} deaths",
+                copy.generation, before.population(), copy.cells.population(), births, deaths,
+            ));
+        }
+        if let Some(violation) = grid_violation(&copy) {
+            return fail(violation);
+        }
+    }
+    InvariantCheck::new("conservation", CheckStatus::Passed, format!("population balanced over {} generations", generations))
+}
+
+pub fn check_still_life(simulation: &SimulationData, generations: u64) -> InvariantCheck {
+    let mut copy = simulation.clone();
+    copy.step();
+    if copy.cells != simulation.cells {
+        return InvariantCheck::new("still_life", CheckStatus::Skipped, "grid changes in one step");
+    }
+    for _ in 1..generations {
+        copy.step();
+        if copy.cells != simulation.cells {
+            return InvariantCheck::new(
+                "still_life",
+                CheckStatus::Failed,
+                format!("still life changed at generation {}", copy.generation),
+            );
+        }
+    }
+    InvariantCheck::new("still_life", CheckStatus::Passed, format!("unchanged for {} generations", generations.max(1)))
+}
+
+pub fn check_determinism(simulation: &SimulationData, generations: u64) -> InvariantCheck {
+    if simulation.turmite_rule.is_some() {
+        return InvariantCheck::new("determinism", CheckStatus::Skipped, "turmites always step on one thread");
+    }
+    let mut cells = simulation.cells.clone();
+    for generation in simulation.generation + 1..=simulation.generation + generations {
+        simulation.boundary.extend(&mut cells, simulation.width, simulation.height, simulation.rule.range() as i32);
+        let mut parallel = cells.step_rule(&simulation.rule, true);
+        let serial = cells.step_rule(&simulation.rule, false);
+        if parallel != serial {
+            let differing = parallel.difference(&serial).len() + serial.difference(&parallel).len();
+            return InvariantCheck::new(
+                "determinism",
+                CheckStatus::Failed,
+                format!("generation {}: parallel and serial steps differ in {} cells", generation, differing),
+            );
+        }
+        parallel.retain_rect(simulation.width, simulation.height);
+        cells = parallel;
+    }
+    InvariantCheck::new("determinism", CheckStatus::Passed, format!("parallel and serial steps agree over {} generations", generations))
+}
+
+pub fn check_history(simulation: &SimulationData, history: &[ReplayRecord]) -> InvariantCheck {
+    let Some(replayed) = replay(&simulation.id, history) else {
+        return InvariantCheck::new("history", CheckStatus::Skipped, "no recorded history to replay");
+    };
+    if replayed.generation != simulation.generation {
+        return InvariantCheck::new(
+            "history",
+            CheckStatus::Failed,
+            format!("replay reached generation {}, not {}", replayed.generation, simulation.generation),
+        );
+    }
+    if replayed.cells != simulation.cells {
+        let differing = replayed.cells.difference(&simulation.cells).len() + simulation.cells.difference(&replayed.cells).len();
+        return InvariantCheck::new(
+            "history",
+            CheckStatus::Failed,
+            format!("replay differs from the grid in {} live cells", differing),
+        );
+    }
+    InvariantCheck::new("history", CheckStatus::Passed, format!("{} operations replay to generation {}", history.len(), simulation.generation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::{scratch_simulation, ReplayLogs, ReplayOp, Simulations};
+
+    #[test]
+    fn test_invariants_hold_for_the_engine() {
+        let mut simulation = scratch_simulation(32, 32);
+        simulation.set_cells(&[(11, 10), (12, 10), (10, 11), (11, 11), (11, 12)]);
+        let results = verify_simulation(&simulation, &[], 20, &[]).unwrap();
+        let statuses: Vec<_> = results.iter().map(|check| (check.name, check.status)).collect();
+        assert_eq!(statuses, vec![
+            ("conservation", CheckStatus::Passed),
+            ("still_life", CheckStatus::Skipped),
+            ("determinism", CheckStatus::Passed),
+            ("history", CheckStatus::Skipped),
+        ]);
+
+        simulation.set_cells(&[(1, 1), (2, 1), (1, 2), (2, 2)]);
+        assert_eq!(check_still_life(&simulation, 10).status, CheckStatus::Passed);
+        assert!(verify_simulation(&simulation, &[], 1, &["bogus".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_history_must_replay_to_the_grid() {
+        let mut simulations = Simulations::new();
+        let id = simulations.create_simulation(16, 16, None);
+        let logs = ReplayLogs::new();
+        logs.record_restore(simulations.get_simulation(&id).unwrap());
+
+        let simulation = simulations.get_simulation_mut(&id).unwrap();
+        simulation.add_pattern(&[(1, 0), (1, 1), (1, 2)], 4, 4);
+        logs.record(&id, ReplayOp::AddPattern { cells: vec![(1, 0), (1, 1), (1, 2)], offset_x: 4, offset_y: 4 });
+        simulation.step();
+        logs.record(&id, ReplayOp::Step { steps: 1 });
+        let history = logs.entries(&id).unwrap();
+        assert_eq!(check_history(simulation, &history).status, CheckStatus::Passed);
+
+        // An edit the log never heard of
+        simulation.cells.set(0, 0, true);
+        assert_eq!(check_history(simulation, &history).status, CheckStatus::Failed);
+    }
+}
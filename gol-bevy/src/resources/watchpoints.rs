@@ -0,0 +1,89 @@
+//! Rectangles of cells watched for changes as a simulation is stepped.
+//!
+//! A watchpoint keeps the state of every cell it covers and, after each
+//! generation stepped, reports those that differ, whether the step or an
+//! edit in between changed them. A pausing watchpoint also stops the steps
+//! there, as a breakpoint does.
+
+use crate::resources::{ChunkedGrid, GridRegion};
+
+/// Most cells one watchpoint may cover, after clipping to the grid.
+pub const MAX_WATCH_CELLS: u64 = 1 << 16;
+
+/// A changed cell's position and new state.
+pub type CellChange = ((i32, i32), u8);
+
+/// A watched rectangle and the states its cells had when last checked.
+#[derive(Debug, Clone)]
+pub struct Watchpoint {
+    /// The rectangle as set, before clipping.
+    pub region: GridRegion,
+    /// Stop stepping when the cells change.
+    pub pause: bool,
+    /// Corners of the region inside the grid, `(x1, y1)` exclusive.
+    from: (i32, i32),
+    to: (i32, i32),
+    /// State of each covered cell in row-major order.
+    states: Vec<u8>,
+}
+
+impl Watchpoint {
+    /// A watchpoint on the part of `region` inside a `width` by `height`
+    /// grid whose cells are `cells`, as they are now.
+    pub fn new(region: GridRegion, pause: bool, width: i32, height: i32, cells: &ChunkedGrid) -> Self {
+        let (from, to) = region.clip(width, height).unwrap_or(((0, 0), (0, 0)));
+        let mut watchpoint = Self { region, pause, from, to, states: Vec::new() };
+        watchpoint.states = watchpoint.read(cells);
+        watchpoint
+    }
+
+    fn read(&self, cells: &ChunkedGrid) -> Vec<u8> {
+        (self.from.1..self.to.1)
+            .flat_map(|y| (self.from.0..self.to.0).map(move |x| cells.state(x, y)))
+            .collect()
+    }
+
+    /// Covered cells whose state differs from the last check, with their
+    /// new state, remembering the states for the next.
+    pub fn changes(&mut self, cells: &ChunkedGrid) -> Vec<CellChange> {
+        let states = self.read(cells);
+        let width = (self.to.0 - self.from.0).max(0) as usize;
+        let changed = states.iter().zip(&self.states).enumerate()
+            .filter(|(_, (now, before))| now != before)
+            .map(|(index, (&state, _))| ((self.from.0 + (index % width) as i32, self.from.1 + (index / width) as i32), state))
+            .collect();
+        self.states = states;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watchpoint_reports_changed_cells_once() {
+        let mut cells = ChunkedGrid::new();
+        let region = GridRegion { x: 2, y: 2, width: 3, height: 3 };
+        let mut watchpoint = Watchpoint::new(region, false, 10, 10, &cells);
+        assert!(watchpoint.changes(&cells).is_empty());
+
+        cells.set(3, 4, true);
+        cells.set(0, 0, true); // Outside the region
+        assert_eq!(watchpoint.changes(&cells), vec![((3, 4), 1)]);
+        assert!(watchpoint.changes(&cells).is_empty());
+
+        cells.set(3, 4, false);
+        assert_eq!(watchpoint.changes(&cells), vec![((3, 4), 0)]);
+    }
+
+    #[test]
+    fn test_watchpoint_is_clipped_to_the_grid() {
+        let mut cells = ChunkedGrid::new();
+        let region = GridRegion { x: 8, y: 8, width: 5, height: 5 };
+        let mut watchpoint = Watchpoint::new(region, true, 10, 10, &cells);
+        cells.set(9, 9, true);
+        cells.set(11, 11, true);
+        assert_eq!(watchpoint.changes(&cells), vec![((9, 9), 1)]);
+    }
+}
@@ -1,11 +1,12 @@
 use bevy::prelude::*;
 use crate::components::{Position, CellState};
-use crate::resources::SimulationState;
+use crate::resources::{SimulationState, SpatialGrid};
 
 pub fn lifecycle_system(
     mut commands: Commands,
     mut query: Query<(Entity, &Position, &mut CellState)>,
     mut simulation_state: ResMut<SimulationState>,
+    mut spatial_grid: ResMut<SpatialGrid>,
 ) {
     let mut entities_to_despawn = Vec::new();
     let mut entities_to_birth = Vec::new();
@@ -15,11 +16,12 @@ pub fn lifecycle_system(
         if cell_state.alive {
             // Living cell logic
             if cell_state.should_survive() {
-                // Cell survives to next generation
+                // Cell survives to next generation, keeping its count until
+                // its neighborhood changes
                 cell_state.generation += 1;
-                cell_state.neighbor_count = 0; // Reset for next cycle
             } else {
                 // Cell dies
+                spatial_grid.remove(position);
                 entities_to_despawn.push(entity);
             }
         } else {
@@ -43,10 +45,13 @@ pub fn lifecycle_system(
     for (position, entity) in entities_to_birth {
         // Update the existing ghost cell to be alive
         if let Some(mut entity_commands) = commands.get_entity(entity) {
+            spatial_grid.insert(position, entity);
             entity_commands.insert(CellState {
                 alive: true,
                 generation: simulation_state.generation + 1,
                 neighbor_count: 0,
+                decay: 0,
+                color: 0,
             });
         }
     }
@@ -57,8 +62,9 @@ pub fn lifecycle_system(
 
 pub fn cell_lifecycle_system(
     mut commands: Commands,
-    mut query: Query<(Entity, &Position, &mut CellState)>,
+    query: Query<(Entity, &Position, &mut CellState)>,
     mut simulation_state: ResMut<SimulationState>,
+    mut spatial_grid: ResMut<SpatialGrid>,
 ) {
     let mut cells_to_process = Vec::new();
     
@@ -78,12 +84,16 @@ pub fn cell_lifecycle_system(
                         entity_commands.insert(CellState {
                             alive: true,
                             generation: cell_state.generation + 1,
-                            neighbor_count: 0, // Reset for next cycle
+                            // Kept until its neighborhood changes
+                            neighbor_count: cell_state.neighbor_count,
+                            decay: 0,
+                            color: cell_state.color,
                         });
                     }
                 },
                 _ => {
                     // Cell dies (underpopulation or overpopulation)
+                    spatial_grid.remove(&position);
                     commands.entity(entity).despawn();
                 }
             }
@@ -91,10 +101,13 @@ pub fn cell_lifecycle_system(
             // Dead cell rules
             if cell_state.neighbor_count == 3 {
                 // Cell is born
+                spatial_grid.insert(position, entity);
                 commands.entity(entity).insert(CellState {
                     alive: true,
                     generation: simulation_state.generation + 1,
                     neighbor_count: 0,
+                    decay: 0,
+                    color: 0,
                 });
             } else {
                 // Dead cell remains dead, remove ghost cell
@@ -1,14 +1,17 @@
 //! Game of Life ECS Systems
 //! 
 //! This module contains the core ECS systems for the Game of Life simulation.
-//! - `neighbor_system`: Calculate neighbor counts for all cells
+//! - `neighbor_system`: Keep neighbor counts current from the `SpatialGrid`
 //! - `lifecycle_system`: Apply Conway's Game of Life rules
 //!
 //! The systems use sparse representation for efficiency - only live cells and
-//! their neighbors are processed.
+//! their neighbors are processed, and neighbor counts are only recomputed
+//! around cells that were born or died.
 
 use bevy::prelude::*;
 
+use crate::resources::SimulationState;
+
 pub mod neighbor_system;
 pub mod lifecycle_system;
 
@@ -21,6 +24,12 @@ mod scheduler_integration_test;
 pub use neighbor_system::{neighbor_calculation_system, neighbor_counting_system};
 pub use lifecycle_system::{lifecycle_system, cell_lifecycle_system, cleanup_system};
 
+/// Run condition for the Game of Life systems: the simulation is running.
+/// Combine with `any_with_component::<CellState>` so an empty world idles too.
+pub fn simulation_running(simulation_state: Res<SimulationState>) -> bool {
+    simulation_state.is_running
+}
+
 // System sets for organizing execution order
 #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum GameOfLifeSystemSet {
@@ -0,0 +1,127 @@
+//! WebAssembly exports of the reference engine.
+//!
+//! Lets a web viewer step patterns in the browser with the same rules and
+//! parsers the server uses. Built without the server, which leaves out
+//! bevy, tokio, tonic and rayon:
+//!
+//! ```text
+//! cargo rustc --release --lib --no-default-features --features wasm --target wasm32-unknown-unknown --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/gol_bevy.wasm
+//! ```
+//!
+//! From JavaScript a simulation is made with `create(width, height, rule)`
+//! and driven with its `step`, `getCells` and `loadRLE` methods. Cells past
+//! the edges are dead, as with the server's default boundary.
+
+use wasm_bindgen::prelude::*;
+
+use crate::resources::{decode_rle, ChunkedGrid, Rule};
+
+/// A bounded grid stepped under one rule.
+#[wasm_bindgen]
+pub struct Simulation {
+    width: i32,
+    height: i32,
+    rule: Rule,
+    generation: u64,
+    cells: ChunkedGrid,
+}
+
+/// An empty `width` x `height` simulation under `rule`, or Conway's Life
+/// when none is given.
+pub fn new_simulation(width: i32, height: i32, rule: Option<&str>) -> Result<Simulation, String> {
+    if width <= 0 || height <= 0 {
+        return Err(format!("Grid must be at least 1x1, not {}x{}", width, height));
+    }
+    let rule = match rule {
+        Some(rule) => rule.parse::<Rule>().map_err(|error| error.to_string())?,
+        None => Rule::conway(),
+    };
+    Ok(Simulation { width, height, rule, generation: 0, cells: ChunkedGrid::new() })
+}
+
+#[wasm_bindgen]
+pub fn create(width: i32, height: i32, rule: Option<String>) -> Result<Simulation, JsError> {
+    new_simulation(width, height, rule.as_deref()).map_err(|error| JsError::new(&error))
+}
+
+impl Simulation {
+    /// Set the cells of the RLE pattern `rle` alive with its top-left corner
+    /// at (`x`, `y`), dropping any that fall outside the grid. Returns how
+    /// many were set.
+    pub fn place_rle(&mut self, rle: &str, x: i32, y: i32) -> Result<u32, String> {
+        let mut placed = 0;
+        for (cell_x, cell_y) in decode_rle(rle).map_err(|error| error.to_string())? {
+            let (cell_x, cell_y) = (cell_x.saturating_add(x), cell_y.saturating_add(y));
+            if cell_x >= 0 && cell_x < self.width && cell_y >= 0 && cell_y < self.height {
+                self.cells.set(cell_x, cell_y, true);
+                placed += 1;
+            }
+        }
+        Ok(placed)
+    }
+
+    pub fn live_cells(&self) -> Vec<(i32, i32)> {
+        self.cells.live_cells()
+    }
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    /// Advance `generations` generations, returning the generation reached.
+    pub fn step(&mut self, generations: u32) -> u64 {
+        for _ in 0..generations {
+            self.cells = self.cells.step_rule(&self.rule, false);
+            self.cells.retain_rect(self.width, self.height);
+            self.generation += 1;
+        }
+        self.generation
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn population(&self) -> u64 {
+        self.cells.population()
+    }
+
+    /// Live cells as a flat `[x0, y0, x1, y1, ...]` array.
+    #[wasm_bindgen(js_name = getCells)]
+    pub fn get_cells(&self) -> Vec<i32> {
+        self.cells.iter_live().flat_map(|(x, y)| [x, y]).collect()
+    }
+
+    #[wasm_bindgen(js_name = loadRLE)]
+    pub fn load_rle(&mut self, rle: &str, x: i32, y: i32) -> Result<u32, JsError> {
+        self.place_rle(rle, x, y).map_err(|error| JsError::new(&error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blinker_oscillates() {
+        let mut simulation = new_simulation(16, 16, None).unwrap();
+        assert_eq!(simulation.place_rle("3o!", 5, 5).unwrap(), 3);
+        assert_eq!(simulation.step(1), 1);
+        let mut cells = simulation.live_cells();
+        cells.sort();
+        assert_eq!(cells, vec![(6, 4), (6, 5), (6, 6)]);
+        simulation.step(1);
+        assert_eq!(simulation.get_cells().len(), 6);
+    }
+
+    #[test]
+    fn test_cells_outside_the_grid_are_dropped() {
+        let mut simulation = new_simulation(4, 4, Some("B36/S23")).unwrap();
+        assert_eq!(simulation.place_rle("3o!", 2, 0).unwrap(), 2);
+        assert!(simulation.place_rle("3o$#!", 0, 0).is_err());
+        assert!(new_simulation(0, 4, None).is_err());
+        assert!(new_simulation(4, 4, Some("not a rule")).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Server;
+use tonic::{Code, Request};
+
+use gol_bevy::grpc::gateway::BACKEND_METADATA_KEY;
+use gol_bevy::grpc::proto::game_of_life_service_client::GameOfLifeServiceClient;
+use gol_bevy::grpc::proto::game_of_life_service_server::{GameOfLifeService, GameOfLifeServiceServer};
+use gol_bevy::grpc::proto::*;
+use gol_bevy::grpc::{GameOfLifeServiceImpl, GatewayBackend, GatewayService};
+
+/// Start a backend server on a free local port, returning its address.
+async fn spawn_backend() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    tokio::spawn(
+        Server::builder()
+            .add_service(GameOfLifeServiceServer::new(GameOfLifeServiceImpl::new()))
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+    address
+}
+
+/// A gateway in front of two fresh backends labelled `a` and `b`.
+async fn create_test_gateway() -> GatewayService {
+    let a = GatewayBackend::new("a", &spawn_backend().await).unwrap();
+    let b = GatewayBackend::new("b", &spawn_backend().await).unwrap();
+    GatewayService::new(vec![a, b])
+}
+
+fn create_request(backend: Option<&str>) -> Request<CreateSimulationRequest> {
+    let mut request = Request::new(CreateSimulationRequest {
+        width: 50,
+        height: 50,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    });
+    if let Some(backend) = backend {
+        request.metadata_mut().insert(BACKEND_METADATA_KEY, backend.parse().unwrap());
+    }
+    request
+}
+
+async fn create_on(gateway: &GatewayService, backend: Option<&str>) -> String {
+    gateway.create_simulation(create_request(backend)).await.unwrap().into_inner().id
+}
+
+fn get_request(id: &str) -> Request<GetSimulationRequest> {
+    Request::new(GetSimulationRequest { id: id.to_string(), read_mask: None, generation: None })
+}
+
+#[tokio::test]
+async fn test_gateway_routes_by_label() {
+    let gateway = create_test_gateway().await;
+    let id = create_on(&gateway, Some("b")).await;
+    
+    let only = |backend: usize| GatewayService::new(vec![gateway.backends()[backend].clone()]);
+    assert_eq!(only(0).get_simulation(get_request(&id)).await.unwrap_err().code(), Code::NotFound);
+    assert!(only(1).get_simulation(get_request(&id)).await.is_ok());
+    
+    let response = gateway.get_simulation(get_request(&id)).await.unwrap().into_inner();
+    assert_eq!(response.id, id);
+}
+
+#[tokio::test]
+async fn test_gateway_rejects_unknown_label() {
+    let gateway = create_test_gateway().await;
+    let error = gateway.create_simulation(create_request(Some("c"))).await.unwrap_err();
+    assert_eq!(error.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_gateway_spreads_unlabelled_simulations() {
+    let gateway = create_test_gateway().await;
+    let first = create_on(&gateway, None).await;
+    let second = create_on(&gateway, None).await;
+    
+    for backend in gateway.backends() {
+        let single = GatewayService::new(vec![backend.clone()]);
+        let listed = single.list_simulations(Request::new(ListSimulationsRequest {})).await.unwrap().into_inner();
+        assert_eq!(listed.simulations.len(), 1, "backend {}", backend.label);
+    }
+    
+    let listed = gateway.list_simulations(Request::new(ListSimulationsRequest {})).await.unwrap().into_inner();
+    let mut ids: Vec<String> = listed.simulations.into_iter().map(|simulation| simulation.id).collect();
+    ids.sort();
+    let mut expected = vec![first, second];
+    expected.sort();
+    assert_eq!(ids, expected);
+}
+
+#[tokio::test]
+async fn test_gateway_finds_simulations_it_did_not_create() {
+    let gateway = create_test_gateway().await;
+    let id = create_on(&GatewayService::new(vec![gateway.backends()[1].clone()]), None).await;
+    
+    let response = gateway.get_simulation(get_request(&id)).await.unwrap().into_inner();
+    assert_eq!(response.id, id);
+    let missing = gateway.get_simulation(get_request("no-such-simulation")).await.unwrap_err();
+    assert_eq!(missing.code(), Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_gateway_refuses_cross_backend_diff() {
+    let gateway = create_test_gateway().await;
+    let a = create_on(&gateway, Some("a")).await;
+    let b = create_on(&gateway, Some("b")).await;
+    let a_again = create_on(&gateway, Some("a")).await;
+    let diff = |a: &str, b: &str| Request::new(DiffRequest {
+        a: Some(DiffTarget { id: a.to_string(), generation: None }),
+        b: Some(DiffTarget { id: b.to_string(), generation: None }),
+    });
+    
+    let error = gateway.get_diff(diff(&a, &b)).await.unwrap_err();
+    assert_eq!(error.code(), Code::FailedPrecondition);
+    assert!(gateway.get_diff(diff(&a, &a_again)).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_gateway_status_sums_backends() {
+    let gateway = create_test_gateway().await;
+    create_on(&gateway, Some("a")).await;
+    create_on(&gateway, Some("b")).await;
+    
+    let status = gateway.get_status(Request::new(StatusRequest {})).await.unwrap().into_inner();
+    assert_eq!(status.implementation, "gateway");
+    assert_eq!(status.status, "healthy");
+    assert_eq!(status.simulation_count, 2);
+    assert_eq!(status.engine_versions.len(), 2);
+    assert!(status.engine_versions.contains_key("a/bevy"));
+    assert!(status.limits.is_some() && status.build.is_some());
+}
+
+#[tokio::test]
+async fn test_gateway_merges_backend_events() {
+    use tokio_stream::StreamExt;
+    
+    let gateway = create_test_gateway().await;
+    let mut events = gateway.watch_events(Request::new(WatchEventsRequest { ids: vec![] })).await.unwrap().into_inner();
+    let a = create_on(&gateway, Some("a")).await;
+    let b = create_on(&gateway, Some("b")).await;
+    
+    let mut created = Vec::new();
+    for _ in 0..2 {
+        let event = events.next().await.unwrap().unwrap();
+        assert_eq!(event.r#type(), SimulationEventType::Created);
+        created.push(event.id);
+    }
+    created.sort();
+    let mut expected = vec![a, b];
+    expected.sort();
+    assert_eq!(created, expected);
+}
+
+#[tokio::test]
+async fn test_gateway_finds_batches_on_their_backend() {
+    let gateway = create_test_gateway().await;
+    let mut request = Request::new(BatchSpec {
+        pattern: "diehard".to_string(),
+        generations: 200,
+        ..Default::default()
+    });
+    request.metadata_mut().insert(BACKEND_METADATA_KEY, "b".parse().unwrap());
+    let submitted = gateway.submit_batch(request).await.unwrap().into_inner();
+    
+    let only_b = GatewayService::new(vec![gateway.backends()[1].clone()]);
+    let listed = only_b.list_batches(Request::new(ListBatchesRequest {})).await.unwrap().into_inner();
+    assert_eq!(listed.batches.len(), 1);
+    
+    let fresh = GatewayService::new(gateway.backends().to_vec());
+    let results = |batch_id: &str| Request::new(BatchResultsRequest { batch_id: batch_id.to_string() });
+    let found = fresh.get_batch_results(results(&submitted.batch_id)).await.unwrap().into_inner();
+    assert_eq!(found.status.unwrap().batch_id, submitted.batch_id);
+    let missing = fresh.get_batch_results(results("no-such-batch")).await.unwrap_err();
+    assert_eq!(missing.code(), Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_gateway_does_not_complete_failed_uploads() {
+    let gateway = GatewayService::new(vec![GatewayBackend::new("a", &spawn_backend().await).unwrap()]);
+    let id = create_on(&gateway, None).await;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(
+        Server::builder()
+            .add_service(GameOfLifeServiceServer::new(GatewayService::new(gateway.backends().to_vec())))
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+    
+    // A whole macrocell is only applied once the upload ends; the next
+    // chunk is over the gateway's message size limit, failing its stream
+    let position = Some(Position { x: 0, y: 0 });
+    let chunk = |macrocell: String| PatternChunk { id: id.clone(), position, macrocell, ..Default::default() };
+    let chunks = vec![
+        chunk("[M2] (golly 4.2)\n#R B3/S23\n$$.*$..*$***$\n4 0 1 0 0\n".to_string()),
+        chunk("#C".repeat(3 << 20)),
+    ];
+    let mut client = GameOfLifeServiceClient::connect(address).await.unwrap();
+    assert!(client.upload_pattern(tokio_stream::iter(chunks)).await.is_err());
+    
+    let simulation = gateway.get_simulation(get_request(&id)).await.unwrap().into_inner();
+    assert_eq!(simulation.live_cells, 0);
+}
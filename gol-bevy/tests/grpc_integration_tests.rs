@@ -1,8 +1,11 @@
-use tonic::{Request, Response, Status};
+use tonic::Request;
 
 use gol_bevy::grpc::proto::game_of_life_service_server::GameOfLifeService;
 use gol_bevy::grpc::proto::*;
-use gol_bevy::grpc::GameOfLifeServiceImpl;
+use gol_bevy::grpc::{errors, GameOfLifeServiceImpl};
+use tonic_types::StatusExt;
+use gol_bevy::resources::{register_automaton, Automaton, ClientQuota, RateLimit, ServerConfig, SimulationManager};
+use std::sync::Arc;
 
 /// Helper to create a test service
 fn create_test_service() -> GameOfLifeServiceImpl {
@@ -21,6 +24,33 @@ async fn test_get_status() {
     assert_eq!(status.version, "1.0.0");
     assert_eq!(status.implementation, "bevy");
     assert!(status.uptime_seconds >= 0);
+    assert_eq!((status.active_simulation_count, status.total_live_cells), (0, 0));
+    assert!(status.engine_versions["bevy"].starts_with("0."));
+    let build = status.build.unwrap();
+    assert_eq!(build.version, env!("CARGO_PKG_VERSION"));
+    assert!(!build.profile.is_empty());
+    let limits = status.limits.unwrap();
+    assert_eq!(limits.max_response_cells as usize, ServerConfig::default().max_response_cells);
+    assert_eq!(limits.max_simulations_per_client, 0);
+}
+
+#[tokio::test]
+async fn test_status_counts_live_cells_and_active_simulations() {
+    let service = GameOfLifeServiceImpl::with_config(&ServerConfig {
+        client_quota: ClientQuota { max_simulations: Some(3), ..Default::default() },
+        ..Default::default()
+    });
+    let id = service.create_simulation(Request::new(CreateSimulationRequest { initial_pattern: "random".to_string(), seed: Some(1), ..create_sized(20) }))
+        .await.unwrap().into_inner().id;
+    service.create_simulation(Request::new(create_sized(20))).await.unwrap();
+    let before = service.get_status(Request::new(StatusRequest {})).await.unwrap().into_inner();
+    service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 3, expected_version: None })).await.unwrap();
+    let after = service.get_status(Request::new(StatusRequest {})).await.unwrap().into_inner();
+    let live = service.get_summary(Request::new(GetSummaryRequest { id })).await.unwrap().into_inner().live_cells;
+    
+    assert_eq!((before.simulation_count, before.active_simulation_count), (2, 0));
+    assert_eq!((after.active_simulation_count, after.total_live_cells), (1, live));
+    assert_eq!(after.limits.unwrap().max_simulations_per_client, 3);
 }
 
 #[tokio::test]
@@ -30,6 +60,13 @@ async fn test_create_simulation() {
         width: 100,
         height: 100,
         initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
     });
     
     let response = service.create_simulation(request).await.unwrap();
@@ -44,6 +81,34 @@ async fn test_create_simulation() {
     assert!(simulation.cells.is_empty());
 }
 
+#[tokio::test]
+async fn test_create_simulation_ids_and_default_simulation() {
+    let service = GameOfLifeServiceImpl::with_config(&ServerConfig { default_simulation: true, ..Default::default() });
+    let default = service.get_simulation(Request::new(GetSimulationRequest { id: "default".to_string(), ..Default::default() })).await.unwrap().into_inner();
+    let grid = default.grid.unwrap();
+    assert_eq!((grid.width, grid.height), (100, 50));
+    
+    // Drawn ids read as adjective-object-number
+    let drawn = service.create_simulation(Request::new(create_sized(10))).await.unwrap().into_inner().id;
+    assert_eq!(drawn.split('-').count(), 3, "{}", drawn);
+    assert!(drawn.bytes().all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'-'), "{}", drawn);
+    
+    let chosen = CreateSimulationRequest { id: "my-sim-1".to_string(), ..create_sized(10) };
+    assert_eq!(service.create_simulation(Request::new(chosen.clone())).await.unwrap().into_inner().id, "my-sim-1");
+    let taken = service.create_simulation(Request::new(chosen)).await.unwrap_err();
+    assert_eq!(taken.code(), tonic::Code::AlreadyExists);
+    assert_eq!(taken.get_error_details().resource_info().unwrap().resource_name, "my-sim-1");
+    let taken = service.create_simulation(Request::new(CreateSimulationRequest { id: "default".to_string(), ..create_sized(10) })).await.unwrap_err();
+    assert_eq!(taken.code(), tonic::Code::AlreadyExists);
+    
+    let invalid = service.create_simulation(Request::new(CreateSimulationRequest { id: "My Sim".to_string(), ..create_sized(10) })).await.unwrap_err();
+    assert_eq!(invalid.code(), tonic::Code::InvalidArgument);
+    
+    // Without the flag no simulation exists at startup
+    let status = create_test_service().get_status(Request::new(StatusRequest {})).await.unwrap().into_inner();
+    assert_eq!(status.simulation_count, 0);
+}
+
 #[tokio::test]
 async fn test_create_simulation_invalid_dimensions() {
     let service = create_test_service();
@@ -53,6 +118,13 @@ async fn test_create_simulation_invalid_dimensions() {
         width: 0,
         height: 100,
         initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
     });
     
     let result = service.create_simulation(request).await;
@@ -63,6 +135,13 @@ async fn test_create_simulation_invalid_dimensions() {
         width: 100,
         height: -1,
         initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
     });
     
     let result = service.create_simulation(request).await;
@@ -73,6 +152,13 @@ async fn test_create_simulation_invalid_dimensions() {
         width: 2000,
         height: 2000,
         initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
     });
     
     let result = service.create_simulation(request).await;
@@ -88,6 +174,13 @@ async fn test_get_simulation() {
         width: 50,
         height: 50,
         initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
     });
     
     let create_response = service.create_simulation(create_request).await.unwrap();
@@ -96,6 +189,8 @@ async fn test_get_simulation() {
     // Then get it
     let get_request = Request::new(GetSimulationRequest {
         id: created_simulation.id.clone(),
+        read_mask: None,
+        generation: None,
     });
     
     let get_response = service.get_simulation(get_request).await.unwrap();
@@ -114,12 +209,46 @@ async fn test_get_simulation_not_found() {
     let service = create_test_service();
     let request = Request::new(GetSimulationRequest {
         id: "non-existent-id".to_string(),
+        read_mask: None,
+        generation: None,
     });
     
     let result = service.get_simulation(request).await;
     assert!(result.is_err());
 }
 
+#[tokio::test]
+async fn test_errors_carry_structured_details() {
+    let service = create_test_service();
+    
+    let error = service.get_simulation(Request::new(GetSimulationRequest {
+        id: "non-existent-id".to_string(),
+        read_mask: None,
+        generation: None,
+    })).await.unwrap_err();
+    let details = error.get_error_details();
+    let info = details.resource_info().expect("missing simulation reports ResourceInfo");
+    assert_eq!(info.resource_type, errors::SIMULATION_RESOURCE);
+    assert_eq!(info.resource_name, "non-existent-id");
+    
+    let error = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 2000,
+        height: 50,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap_err();
+    let details = error.get_error_details();
+    let violations = &details.bad_request().expect("invalid size reports BadRequest").field_violations;
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].field, "width");
+}
+
 #[tokio::test]
 async fn test_update_simulation() {
     let service = create_test_service();
@@ -129,6 +258,13 @@ async fn test_update_simulation() {
         width: 50,
         height: 50,
         initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
     });
     
     let create_response = service.create_simulation(create_request).await.unwrap();
@@ -137,12 +273,18 @@ async fn test_update_simulation() {
     // Update it with some cells (blinker pattern)
     let update_request = Request::new(UpdateSimulationRequest {
         id: created_simulation.id.clone(),
+        update_mask: None,
         generation: 1,
         cells: vec![
-            Cell { x: 25, y: 24, alive: true, neighbors: 0 },
-            Cell { x: 25, y: 25, alive: true, neighbors: 0 },
-            Cell { x: 25, y: 26, alive: true, neighbors: 0 },
+            Cell { x: 25, y: 24, alive: true, neighbors: 0, state: 1, color: 0 },
+            Cell { x: 25, y: 25, alive: true, neighbors: 0, state: 1, color: 0 },
+            Cell { x: 25, y: 26, alive: true, neighbors: 0, state: 1, color: 0 },
         ],
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
     });
     
     let update_response = service.update_simulation(update_request).await.unwrap();
@@ -163,6 +305,13 @@ async fn test_delete_simulation() {
         width: 50,
         height: 50,
         initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
     });
     
     let create_response = service.create_simulation(create_request).await.unwrap();
@@ -171,6 +320,7 @@ async fn test_delete_simulation() {
     // Delete it
     let delete_request = Request::new(DeleteSimulationRequest {
         id: created_simulation.id.clone(),
+        expected_version: None,
     });
     
     let delete_response = service.delete_simulation(delete_request).await.unwrap();
@@ -182,6 +332,8 @@ async fn test_delete_simulation() {
     // Try to get it again - should fail
     let get_request = Request::new(GetSimulationRequest {
         id: created_simulation.id,
+        read_mask: None,
+        generation: None,
     });
     
     let get_result = service.get_simulation(get_request).await;
@@ -197,6 +349,13 @@ async fn test_step_simulation() {
         width: 50,
         height: 50,
         initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
     });
     
     let create_response = service.create_simulation(create_request).await.unwrap();
@@ -205,12 +364,18 @@ async fn test_step_simulation() {
     // Add blinker pattern
     let update_request = Request::new(UpdateSimulationRequest {
         id: created_simulation.id.clone(),
+        update_mask: None,
         generation: 0,
         cells: vec![
-            Cell { x: 25, y: 24, alive: true, neighbors: 0 },
-            Cell { x: 25, y: 25, alive: true, neighbors: 0 },
-            Cell { x: 25, y: 26, alive: true, neighbors: 0 },
+            Cell { x: 25, y: 24, alive: true, neighbors: 0, state: 1, color: 0 },
+            Cell { x: 25, y: 25, alive: true, neighbors: 0, state: 1, color: 0 },
+            Cell { x: 25, y: 26, alive: true, neighbors: 0, state: 1, color: 0 },
         ],
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
     });
     
     service.update_simulation(update_request).await.unwrap();
@@ -219,6 +384,7 @@ async fn test_step_simulation() {
     let step_request = Request::new(StepSimulationRequest {
         id: created_simulation.id.clone(),
         steps: 1,
+        expected_version: None,
     });
     
     let step_response = service.step_simulation(step_request).await.unwrap();
@@ -238,6 +404,13 @@ async fn test_load_pattern() {
         width: 50,
         height: 50,
         initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
     });
     
     let create_response = service.create_simulation(create_request).await.unwrap();
@@ -259,6 +432,9 @@ async fn test_load_pattern() {
             ],
         }),
         position: Some(Position { x: 10, y: 10 }),
+        apgcode: String::new(),
+        overwrite: OverwritePolicy::Skip as i32,
+        expected_version: None,
     });
     
     let load_response = service.load_pattern(load_request).await.unwrap();
@@ -278,6 +454,13 @@ async fn test_blinker_pattern_behavior() {
         width: 50,
         height: 50,
         initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
     });
     
     let create_response = service.create_simulation(create_request).await.unwrap();
@@ -286,12 +469,18 @@ async fn test_blinker_pattern_behavior() {
     // Add vertical blinker pattern
     let update_request = Request::new(UpdateSimulationRequest {
         id: created_simulation.id.clone(),
+        update_mask: None,
         generation: 0,
         cells: vec![
-            Cell { x: 25, y: 24, alive: true, neighbors: 0 },
-            Cell { x: 25, y: 25, alive: true, neighbors: 0 },
-            Cell { x: 25, y: 26, alive: true, neighbors: 0 },
+            Cell { x: 25, y: 24, alive: true, neighbors: 0, state: 1, color: 0 },
+            Cell { x: 25, y: 25, alive: true, neighbors: 0, state: 1, color: 0 },
+            Cell { x: 25, y: 26, alive: true, neighbors: 0, state: 1, color: 0 },
         ],
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
     });
     
     service.update_simulation(update_request).await.unwrap();
@@ -300,6 +489,7 @@ async fn test_blinker_pattern_behavior() {
     let step_request = Request::new(StepSimulationRequest {
         id: created_simulation.id.clone(),
         steps: 1,
+        expected_version: None,
     });
     
     let step_response = service.step_simulation(step_request).await.unwrap();
@@ -311,6 +501,8 @@ async fn test_blinker_pattern_behavior() {
     // Get the simulation to check the pattern
     let get_request = Request::new(GetSimulationRequest {
         id: created_simulation.id.clone(),
+        read_mask: None,
+        generation: None,
     });
     
     let get_response = service.get_simulation(get_request).await.unwrap();
@@ -318,7 +510,7 @@ async fn test_blinker_pattern_behavior() {
     
     // Should have horizontal blinker pattern
     assert_eq!(simulation.cells.len(), 3);
-    let mut positions: Vec<(i32, i32)> = simulation.cells.iter().map(|c| (c.x, c.y)).collect();
+    let mut positions: Vec<(i64, i64)> = simulation.cells.iter().map(|c| (c.x, c.y)).collect();
     positions.sort();
     assert_eq!(positions, vec![(24, 25), (25, 25), (26, 25)]);
 }
@@ -332,6 +524,13 @@ async fn test_block_pattern_stability() {
         width: 50,
         height: 50,
         initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
     });
     
     let create_response = service.create_simulation(create_request).await.unwrap();
@@ -340,13 +539,19 @@ async fn test_block_pattern_stability() {
     // Add block pattern (still life)
     let update_request = Request::new(UpdateSimulationRequest {
         id: created_simulation.id.clone(),
+        update_mask: None,
         generation: 0,
         cells: vec![
-            Cell { x: 25, y: 25, alive: true, neighbors: 0 },
-            Cell { x: 25, y: 26, alive: true, neighbors: 0 },
-            Cell { x: 26, y: 25, alive: true, neighbors: 0 },
-            Cell { x: 26, y: 26, alive: true, neighbors: 0 },
+            Cell { x: 25, y: 25, alive: true, neighbors: 0, state: 1, color: 0 },
+            Cell { x: 25, y: 26, alive: true, neighbors: 0, state: 1, color: 0 },
+            Cell { x: 26, y: 25, alive: true, neighbors: 0, state: 1, color: 0 },
+            Cell { x: 26, y: 26, alive: true, neighbors: 0, state: 1, color: 0 },
         ],
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
     });
     
     service.update_simulation(update_request).await.unwrap();
@@ -356,6 +561,7 @@ async fn test_block_pattern_stability() {
         let step_request = Request::new(StepSimulationRequest {
             id: created_simulation.id.clone(),
             steps: 1,
+            expected_version: None,
         });
         
         let step_response = service.step_simulation(step_request).await.unwrap();
@@ -374,6 +580,13 @@ async fn test_empty_simulation_behavior() {
         width: 50,
         height: 50,
         initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
     });
     
     let create_response = service.create_simulation(create_request).await.unwrap();
@@ -383,6 +596,7 @@ async fn test_empty_simulation_behavior() {
     let step_request = Request::new(StepSimulationRequest {
         id: created_simulation.id.clone(),
         steps: 1,
+        expected_version: None,
     });
     
     let step_response = service.step_simulation(step_request).await.unwrap();
@@ -405,6 +619,13 @@ async fn test_multiple_simulations() {
             width: 50 + i * 10,
             height: 50 + i * 10,
             initial_pattern: String::new(),
+            rule: String::new(),
+            turmite: String::new(),
+            turmites: vec![],
+            boundary_mode: 0,
+            config: None,
+            seed: None,
+            id: String::new(),
         });
         
         let create_response = service.create_simulation(create_request).await.unwrap();
@@ -416,6 +637,8 @@ async fn test_multiple_simulations() {
     for id in &simulation_ids {
         let get_request = Request::new(GetSimulationRequest {
             id: id.clone(),
+            read_mask: None,
+            generation: None,
         });
         
         let get_response = service.get_simulation(get_request).await.unwrap();
@@ -426,6 +649,7 @@ async fn test_multiple_simulations() {
     // Delete one simulation
     let delete_request = Request::new(DeleteSimulationRequest {
         id: simulation_ids[1].clone(),
+        expected_version: None,
     });
     
     let delete_response = service.delete_simulation(delete_request).await.unwrap();
@@ -434,6 +658,8 @@ async fn test_multiple_simulations() {
     // Verify it's gone but others remain
     let get_request = Request::new(GetSimulationRequest {
         id: simulation_ids[1].clone(),
+        read_mask: None,
+        generation: None,
     });
     
     let get_result = service.get_simulation(get_request).await;
@@ -444,10 +670,3224 @@ async fn test_multiple_simulations() {
         if i != 1 {
             let get_request = Request::new(GetSimulationRequest {
                 id: id.clone(),
+                read_mask: None,
+                generation: None,
             });
             
             let get_response = service.get_simulation(get_request).await;
             assert!(get_response.is_ok());
         }
     }
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_get_stats_reports_chunks() {
+    let service = create_test_service();
+    
+    let create_request = Request::new(CreateSimulationRequest {
+        width: 200,
+        height: 200,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    });
+    
+    let created_simulation = service.create_simulation(create_request).await.unwrap().into_inner();
+    
+    // Two cells far enough apart to land in separate chunks
+    let update_request = Request::new(UpdateSimulationRequest {
+        id: created_simulation.id.clone(),
+        update_mask: None,
+        generation: 0,
+        cells: vec![
+            Cell { x: 1, y: 1, alive: true, neighbors: 0, state: 1, color: 0 },
+            Cell { x: 150, y: 150, alive: true, neighbors: 0, state: 1, color: 0 },
+        ],
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    });
+    
+    service.update_simulation(update_request).await.unwrap();
+    
+    let stats_request = Request::new(StatsRequest {
+        id: created_simulation.id.clone(),
+        region: None,
+    });
+    
+    let stats = service.get_stats(stats_request).await.unwrap().into_inner();
+    assert_eq!(stats.live_cells, 2);
+    assert_eq!(stats.chunk_count, 2);
+    assert_eq!(stats.chunk_size, 64);
+    assert!(stats.memory_bytes > 0);
+    
+    let status = service.get_status(Request::new(StatusRequest {})).await.unwrap().into_inner();
+    assert_eq!(status.simulation_count, 1);
+    assert_eq!(status.total_chunks, 2);
+}
+
+#[tokio::test]
+async fn test_get_stats_reports_spaceship_velocity() {
+    let service = create_test_service();
+    
+    let create_request = Request::new(CreateSimulationRequest {
+        width: 50,
+        height: 50,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    });
+    
+    let created_simulation = service.create_simulation(create_request).await.unwrap().into_inner();
+    
+    // A glider heading south-east
+    let update_request = Request::new(UpdateSimulationRequest {
+        id: created_simulation.id.clone(),
+        update_mask: None,
+        generation: 0,
+        cells: [(11, 10), (12, 11), (10, 12), (11, 12), (12, 12)].iter()
+            .map(|&(x, y)| Cell { x, y, alive: true, neighbors: 0, state: 1, color: 0 })
+            .collect(),
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    });
+    
+    service.update_simulation(update_request).await.unwrap();
+    
+    let stats = service.get_stats(Request::new(StatsRequest {
+        id: created_simulation.id.clone(),
+        region: None,
+    })).await.unwrap().into_inner();
+    
+    assert_eq!(stats.period, 4);
+    assert_eq!((stats.displacement_x, stats.displacement_y), (1, 1));
+    assert_eq!(stats.speed, "c/4 diagonal");
+    assert_eq!(stats.direction, "south-east");
+}
+
+#[tokio::test]
+async fn test_get_stats_region_measures_one_object() {
+    let service = create_test_service();
+    
+    let create_request = Request::new(CreateSimulationRequest {
+        width: 50,
+        height: 50,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    });
+    
+    let created_simulation = service.create_simulation(create_request).await.unwrap().into_inner();
+    
+    // A glider heading south-east and a blinker off to the side
+    let update_request = Request::new(UpdateSimulationRequest {
+        id: created_simulation.id.clone(),
+        update_mask: None,
+        generation: 0,
+        cells: [(11, 10), (12, 11), (10, 12), (11, 12), (12, 12), (30, 30), (31, 30), (32, 30)].iter()
+            .map(|&(x, y)| Cell { x, y, alive: true, neighbors: 0, state: 1, color: 0 })
+            .collect(),
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    });
+    
+    service.update_simulation(update_request).await.unwrap();
+    
+    let stats = |region| service.get_stats(Request::new(StatsRequest {
+        id: created_simulation.id.clone(),
+        region,
+    }));
+    let whole = stats(None).await.unwrap().into_inner();
+    assert_eq!(whole.period, 0);
+    
+    let glider = stats(Some(Region { x: 8, y: 8, width: 8, height: 8 })).await.unwrap().into_inner();
+    assert_eq!(glider.period, 4);
+    assert_eq!(glider.speed, "c/4 diagonal");
+    assert_eq!(glider.live_cells, 8);
+    
+    let blinker = stats(Some(Region { x: 29, y: 29, width: 5, height: 3 })).await.unwrap().into_inner();
+    assert_eq!((blinker.period, blinker.speed.as_str()), (2, ""));
+    
+    let status = stats(Some(Region { x: 0, y: 0, width: 0, height: 5 })).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_get_census_counts_objects() {
+    let service = create_test_service();
+    
+    let create_request = Request::new(CreateSimulationRequest {
+        width: 50,
+        height: 50,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    });
+    
+    let created_simulation = service.create_simulation(create_request).await.unwrap().into_inner();
+    
+    // Two blocks and a blinker
+    let cells = [(1, 1), (2, 1), (1, 2), (2, 2), (20, 1), (21, 1), (20, 2), (21, 2), (10, 20), (11, 20), (12, 20)];
+    let update_request = Request::new(UpdateSimulationRequest {
+        id: created_simulation.id.clone(),
+        update_mask: None,
+        generation: 0,
+        cells: cells.iter().map(|&(x, y)| Cell { x, y, alive: true, neighbors: 0, state: 1, color: 0 }).collect(),
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    });
+    
+    service.update_simulation(update_request).await.unwrap();
+    
+    let census = service.get_census(Request::new(CensusRequest {
+        id: created_simulation.id.clone(),
+    })).await.unwrap().into_inner();
+    
+    assert_eq!(census.objects.len(), 2);
+    assert_eq!((census.objects[0].apgcode.as_str(), census.objects[0].name.as_str()), ("xs4_33", "block"));
+    assert_eq!((census.objects[0].count, census.objects[0].cells), (2, 8));
+    assert_eq!((census.objects[1].apgcode.as_str(), census.objects[1].count), ("xp2_7", 1));
+    assert_eq!(census.unidentified_objects, 0);
+    
+    let missing = service.get_census(Request::new(CensusRequest {
+        id: "missing".to_string(),
+    })).await.unwrap_err();
+    assert_eq!(missing.code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_reads_do_not_wait_for_simulation_lock() {
+    let service = create_test_service();
+    
+    let create_request = Request::new(CreateSimulationRequest {
+        width: 20,
+        height: 20,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    });
+    
+    let created_simulation = service.create_simulation(create_request).await.unwrap().into_inner();
+    
+    // Hold the exclusive lock the stepper uses; reads must still complete
+    let _guard = service.simulations.lock().await;
+    
+    let get_request = Request::new(GetSimulationRequest {
+        id: created_simulation.id.clone(),
+        read_mask: None,
+        generation: None,
+    });
+    
+    let read = tokio::time::timeout(
+        std::time::Duration::from_secs(1),
+        service.get_simulation(get_request),
+    ).await;
+    
+    let simulation = read.expect("read blocked on the simulation lock").unwrap().into_inner();
+    assert_eq!(simulation.id, created_simulation.id);
+}
+
+#[tokio::test]
+async fn test_concurrent_steps_are_coalesced_in_order() {
+    let service = std::sync::Arc::new(create_test_service());
+    
+    let create_request = Request::new(CreateSimulationRequest {
+        width: 50,
+        height: 50,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    });
+    
+    let created_simulation = service.create_simulation(create_request).await.unwrap().into_inner();
+    
+    let mut handles = Vec::new();
+    for _ in 0..10 {
+        let service = service.clone();
+        let id = created_simulation.id.clone();
+        handles.push(tokio::spawn(async move {
+            let step_request = Request::new(StepSimulationRequest { id, steps: 2, expected_version: None });
+            service.step_simulation(step_request).await.unwrap().into_inner().generation
+        }));
+    }
+    
+    let mut generations = Vec::new();
+    for handle in handles {
+        generations.push(handle.await.unwrap());
+    }
+    generations.sort();
+    
+    // Every request saw its own two steps applied, none interleaved
+    assert_eq!(generations, (1..=10).map(|i| i * 2).collect::<Vec<i64>>());
+}
+
+#[tokio::test]
+async fn test_long_turbo_step_releases_the_lock_between_batches() {
+    let service = Arc::new(GameOfLifeServiceImpl::with_config(&ServerConfig { max_batch_steps: 5, ..ServerConfig::default() }));
+    let id = service.create_simulation(Request::new(create_sized(20))).await.unwrap().into_inner().id;
+    add_glider(&service, &id).await;
+    
+    let stepping = {
+        let service = service.clone();
+        let step_request = Request::new(StepSimulationRequest { id: id.clone(), steps: 40, expected_version: None });
+        tokio::spawn(async move { service.step_simulation(step_request).await.unwrap().into_inner().generation })
+    };
+    let get = || Request::new(GetSimulationRequest { id: id.clone(), read_mask: None, generation: None });
+    let generation = || async { service.get_simulation(get()).await.unwrap().into_inner().generation };
+    while generation().await == 0 {
+        tokio::task::yield_now().await;
+    }
+    
+    // Other tasks ran after the first batch, before the rest
+    assert_eq!(generation().await, 5);
+    let version = service.get_simulation(get()).await.unwrap().into_inner().version;
+    assert_eq!(stepping.await.unwrap(), 40);
+    
+    // Each batch is published once, not every generation in it
+    assert_eq!(service.get_simulation(get()).await.unwrap().into_inner().version, version + 7);
+}
+
+#[tokio::test]
+async fn test_step_missing_simulation_not_found() {
+    let service = create_test_service();
+    let step_request = Request::new(StepSimulationRequest {
+        id: "non-existent-id".to_string(),
+        steps: 1,
+        expected_version: None,
+    });
+    
+    let result = service.step_simulation(step_request).await;
+    assert_eq!(result.unwrap_err().code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_set_pacing_limits_step_rate() {
+    let service = create_test_service();
+    
+    let create_request = Request::new(CreateSimulationRequest {
+        width: 20,
+        height: 20,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    });
+    
+    let created_simulation = service.create_simulation(create_request).await.unwrap().into_inner();
+    
+    let pacing_request = Request::new(SetPacingRequest {
+        id: created_simulation.id.clone(),
+        target_generations_per_second: 50.0,
+        turbo: false,
+        expected_version: None,
+    });
+    
+    let pacing = service.set_pacing(pacing_request).await.unwrap().into_inner();
+    assert!(!pacing.turbo);
+    assert_eq!(pacing.target_generations_per_second, 50.0);
+    assert!(pacing.version > created_simulation.version);
+    let stale = service.set_pacing(Request::new(SetPacingRequest {
+        id: created_simulation.id.clone(),
+        target_generations_per_second: 0.0,
+        turbo: true,
+        expected_version: Some(created_simulation.version),
+    })).await.unwrap_err();
+    assert_eq!(stale.code(), tonic::Code::FailedPrecondition);
+    
+    // Five generations at 50 gen/s need at least four 20ms gaps
+    let started = std::time::Instant::now();
+    let step_request = Request::new(StepSimulationRequest {
+        id: created_simulation.id.clone(),
+        steps: 5,
+        expected_version: None,
+    });
+    
+    let result = service.step_simulation(step_request).await.unwrap().into_inner();
+    assert_eq!(result.generation, 5);
+    assert!(started.elapsed() >= std::time::Duration::from_millis(80));
+    
+    let status = service.get_status(Request::new(StatusRequest {})).await.unwrap().into_inner();
+    assert!(status.generations_per_second > 0.0);
+}
+
+#[tokio::test]
+async fn test_set_pacing_rejects_non_positive_target() {
+    let service = create_test_service();
+    
+    let create_request = Request::new(CreateSimulationRequest {
+        width: 20,
+        height: 20,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    });
+    
+    let created_simulation = service.create_simulation(create_request).await.unwrap().into_inner();
+    
+    let pacing_request = Request::new(SetPacingRequest {
+        id: created_simulation.id.clone(),
+        target_generations_per_second: 0.0,
+        turbo: false,
+        expected_version: None,
+    });
+    
+    let result = service.set_pacing(pacing_request).await;
+    assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_large_step_runs_as_job() {
+    let service = GameOfLifeServiceImpl::with_config(&ServerConfig {
+        max_inline_steps: 10,
+        max_batch_steps: 5,
+        ..ServerConfig::default()
+    });
+    
+    let create_request = Request::new(CreateSimulationRequest {
+        width: 20,
+        height: 20,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    });
+    
+    let created_simulation = service.create_simulation(create_request).await.unwrap().into_inner();
+    // Jobs stop early on an empty grid
+    add_glider(&service, &created_simulation.id).await;
+    
+    let step_request = Request::new(StepSimulationRequest {
+        id: created_simulation.id.clone(),
+        steps: 50,
+        expected_version: None,
+    });
+    
+    let accepted = service.step_simulation(step_request).await.unwrap().into_inner();
+    assert!(!accepted.job_id.is_empty());
+    assert_eq!(accepted.generation, 0);
+    
+    // Poll until the job finishes
+    let mut job = None;
+    for _ in 0..100 {
+        let status_request = Request::new(JobStatusRequest { job_id: accepted.job_id.clone() });
+        let status = service.get_job_status(status_request).await.unwrap().into_inner();
+        if status.state == JobState::JobCompleted as i32 {
+            job = Some(status);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    
+    let job = job.expect("job did not complete");
+    assert_eq!(job.steps_completed, 50);
+    assert_eq!(job.generation, 50);
+}
+
+#[tokio::test]
+async fn test_cancel_job() {
+    let service = GameOfLifeServiceImpl::with_config(&ServerConfig {
+        max_inline_steps: 1,
+        max_batch_steps: 1,
+        ..ServerConfig::default()
+    });
+    
+    let create_request = Request::new(CreateSimulationRequest {
+        width: 20,
+        height: 20,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    });
+    
+    let created_simulation = service.create_simulation(create_request).await.unwrap().into_inner();
+    add_glider(&service, &created_simulation.id).await;
+    
+    // Pace the simulation so the job cannot finish before we cancel it
+    let pacing_request = Request::new(SetPacingRequest {
+        id: created_simulation.id.clone(),
+        target_generations_per_second: 100.0,
+        turbo: false,
+        expected_version: None,
+    });
+    service.set_pacing(pacing_request).await.unwrap();
+    
+    let step_request = Request::new(StepSimulationRequest {
+        id: created_simulation.id.clone(),
+        steps: 1000,
+        expected_version: None,
+    });
+    let accepted = service.step_simulation(step_request).await.unwrap().into_inner();
+    
+    tokio::time::sleep(std::time::Duration::from_millis(30)).await;
+    service.cancel_job(Request::new(CancelJobRequest { job_id: accepted.job_id.clone() })).await.unwrap();
+    
+    let mut state = JobState::JobRunning as i32;
+    for _ in 0..100 {
+        let status_request = Request::new(JobStatusRequest { job_id: accepted.job_id.clone() });
+        let status = service.get_job_status(status_request).await.unwrap().into_inner();
+        state = status.state;
+        if state == JobState::JobCancelled as i32 {
+            assert!(status.steps_completed < 1000);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(state, JobState::JobCancelled as i32);
+    
+    let missing = service.get_job_status(Request::new(JobStatusRequest { job_id: "missing".to_string() })).await;
+    assert_eq!(missing.unwrap_err().code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_simulation_rate_limit_rejects_excess_requests() {
+    let config = ServerConfig {
+        simulation_rate_limit: Some(RateLimit { per_second: 0.1, burst: 3 }),
+        ..ServerConfig::default()
+    };
+    let service = GameOfLifeServiceImpl::with_config(&config);
+    
+    let busy = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 10,
+        height: 10,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    let quiet = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 10,
+        height: 10,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    
+    for _ in 0..3 {
+        service.get_simulation(Request::new(GetSimulationRequest { id: busy.id.clone(), read_mask: None, generation: None })).await.unwrap();
+    }
+    let error = service.step_simulation(Request::new(StepSimulationRequest { id: busy.id.clone(), steps: 1, expected_version: None }))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), tonic::Code::ResourceExhausted);
+    let details = error.get_error_details();
+    let violation = &details.quota_failure().expect("rate limit reports QuotaFailure").violations[0];
+    assert_eq!(violation.subject, format!("simulation:{}", busy.id));
+    
+    // Other simulations keep their own budget
+    assert!(service.get_simulation(Request::new(GetSimulationRequest { id: quiet.id, read_mask: None, generation: None })).await.is_ok());
+}
+
+/// Create a 10x10 simulation with a blinker and advance it to generation 2.
+async fn create_blinker_at_generation_two(service: &GameOfLifeServiceImpl) -> String {
+    let simulation = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 10,
+        height: 10,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    
+    service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: simulation.id.clone(),
+        generation: 2,
+        cells: vec![
+            Cell { x: 1, y: 2, alive: true, neighbors: 0, state: 1, color: 0 },
+            Cell { x: 2, y: 2, alive: true, neighbors: 0, state: 1, color: 0 },
+            Cell { x: 3, y: 2, alive: true, neighbors: 0, state: 1, color: 0 },
+        ],
+        update_mask: None,
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap();
+    
+    simulation.id
+}
+
+fn mask(paths: &[&str]) -> Option<prost_types::FieldMask> {
+    Some(prost_types::FieldMask { paths: paths.iter().map(|path| path.to_string()).collect() })
+}
+
+#[tokio::test]
+async fn test_get_simulation_read_mask_limits_fields() {
+    let service = create_test_service();
+    let id = create_blinker_at_generation_two(&service).await;
+    
+    let summary = service.get_simulation(Request::new(GetSimulationRequest {
+        id: id.clone(),
+        read_mask: mask(&["generation", "live_cells"]),
+        generation: None,
+    })).await.unwrap().into_inner();
+    
+    assert_eq!(summary.generation, 2);
+    assert_eq!(summary.live_cells, 3);
+    assert!(summary.cells.is_empty());
+    assert!(summary.grid.is_none());
+    assert!(summary.id.is_empty());
+    
+    // "*" and an empty mask both return everything
+    for full in [mask(&["*"]), mask(&[])] {
+        let simulation = service.get_simulation(Request::new(GetSimulationRequest {
+            id: id.clone(),
+            read_mask: full,
+            generation: None,
+        })).await.unwrap().into_inner();
+        assert_eq!(simulation.cells.len(), 3);
+        assert!(simulation.grid.is_some());
+    }
+    
+    let error = service.get_simulation(Request::new(GetSimulationRequest {
+        id,
+        read_mask: mask(&["population"]),
+        generation: None,
+    })).await.unwrap_err();
+    assert_eq!(error.code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_update_mask_applies_only_listed_fields() {
+    let service = create_test_service();
+    let id = create_blinker_at_generation_two(&service).await;
+    
+    // Resetting the generation to zero leaves the cells alone
+    let updated = service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: id.clone(),
+        generation: 0,
+        cells: vec![],
+        update_mask: mask(&["generation"]),
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap().into_inner();
+    assert_eq!(updated.generation, 0);
+    assert_eq!(updated.live_cells, 3);
+    
+    // "cells.alive" patches individual cells instead of replacing the grid
+    let updated = service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: id.clone(),
+        generation: 0,
+        cells: vec![
+            Cell { x: 1, y: 2, alive: false, neighbors: 0, state: 0, color: 0 },
+            Cell { x: 5, y: 5, alive: true, neighbors: 0, state: 1, color: 0 },
+        ],
+        update_mask: mask(&["cells.alive"]),
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap().into_inner();
+    assert_eq!(updated.live_cells, 3);
+    assert!(updated.cells.iter().any(|cell| cell.x == 5 && cell.y == 5));
+    assert!(!updated.cells.iter().any(|cell| cell.x == 1 && cell.y == 2));
+    
+    // An empty cell list under "cells" clears the grid
+    let updated = service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: id.clone(),
+        generation: 0,
+        cells: vec![],
+        update_mask: mask(&["cells"]),
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap().into_inner();
+    assert_eq!(updated.live_cells, 0);
+    
+    let error = service.update_simulation(Request::new(UpdateSimulationRequest {
+        id,
+        generation: 0,
+        cells: vec![],
+        update_mask: mask(&["cells", "cells.alive"]),
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap_err();
+    assert_eq!(error.code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_stale_version_refuses_mutations() {
+    let service = create_test_service();
+    let created = service.create_simulation(Request::new(create_sized(10))).await.unwrap().into_inner();
+    let id = created.id.clone();
+    
+    let renamed = service.rename_simulation(Request::new(RenameSimulationRequest {
+        id: id.clone(),
+        name: "shared".to_string(),
+        expected_version: Some(created.version),
+    })).await.unwrap().into_inner();
+    assert!(renamed.version > created.version);
+    
+    // Another client still holding the creation version is turned away
+    let stale = Some(created.version);
+    let refused = service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: id.clone(),
+        cells: vec![Cell { x: 1, y: 1, alive: true, state: 1, ..Default::default() }],
+        expected_version: stale,
+        ..Default::default()
+    })).await.unwrap_err();
+    assert_eq!(refused.code(), tonic::Code::FailedPrecondition);
+    let details = refused.get_error_details();
+    assert_eq!(details.precondition_failure().unwrap().violations[0].r#type, errors::STALE_VERSION);
+    let step = |expected_version| Request::new(StepSimulationRequest { id: id.clone(), steps: 1, expected_version });
+    assert_eq!(service.step_simulation(step(stale)).await.unwrap_err().code(), tonic::Code::FailedPrecondition);
+    let deleted = service.delete_simulation(Request::new(DeleteSimulationRequest { id: id.clone(), expected_version: stale })).await;
+    assert_eq!(deleted.unwrap_err().code(), tonic::Code::FailedPrecondition);
+    
+    let stepped = service.step_simulation(step(Some(renamed.version))).await.unwrap().into_inner();
+    assert_eq!(stepped.generation, 1);
+    let summary = service.get_summary(Request::new(GetSummaryRequest { id })).await.unwrap().into_inner();
+    assert_eq!(summary.version, stepped.version);
+    assert!(stepped.version > renamed.version);
+}
+
+#[tokio::test]
+async fn test_update_cell_operations_and_expected_generation() {
+    let service = create_test_service();
+    let id = create_blinker_at_generation_two(&service).await;
+    let operation = |r#type: CellOperationType, cells: &[(i64, i64)]| CellOperation {
+        r#type: r#type as i32,
+        cells: cells.iter().map(|&(x, y)| Position { x, y }).collect(),
+    };
+    let update = |operations: Vec<CellOperation>, expected_generation: Option<i64>| Request::new(UpdateSimulationRequest {
+        id: id.clone(),
+        operations,
+        expected_generation,
+        ..Default::default()
+    });
+    
+    // Without a mask, listed cells no longer wipe the rest of the grid
+    let updated = service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: id.clone(),
+        cells: vec![Cell { x: 8, y: 8, alive: true, state: 1, ..Default::default() }],
+        ..Default::default()
+    })).await.unwrap().into_inner();
+    assert_eq!(updated.live_cells, 4);
+    
+    // Operations apply in order, and a clear drops the edits before it
+    let updated = service.update_simulation(update(vec![
+        operation(CellOperationType::SetAlive, &[(0, 0)]),
+        operation(CellOperationType::ClearAll, &[]),
+        operation(CellOperationType::SetAlive, &[(3, 3), (4, 4)]),
+        operation(CellOperationType::SetDead, &[(4, 4)]),
+    ], Some(2))).await.unwrap().into_inner();
+    assert_eq!(updated.live_cells, 1);
+    assert_eq!((updated.cells[0].x, updated.cells[0].y), (3, 3));
+    
+    // A client behind the current generation changes nothing
+    service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 1, expected_version: None })).await.unwrap();
+    let stale = service.update_simulation(update(vec![operation(CellOperationType::ClearAll, &[])], Some(2))).await.unwrap_err();
+    assert_eq!(stale.code(), tonic::Code::Aborted);
+    
+    let invalid = service.update_simulation(update(vec![operation(CellOperationType::Unspecified, &[(1, 1)])], None)).await.unwrap_err();
+    assert_eq!(invalid.get_error_details().bad_request().unwrap().field_violations[0].field, "operations");
+    let mut combined = update(vec![operation(CellOperationType::SetAlive, &[(1, 1)])], None).into_inner();
+    combined.cells = vec![Cell { x: 2, y: 2, alive: true, state: 1, ..Default::default() }];
+    let error = service.update_simulation(Request::new(combined)).await.unwrap_err();
+    assert_eq!(error.code(), tonic::Code::InvalidArgument);
+}
+
+fn upload_chunk(id: &str, cells: Vec<Position>, rle: &str) -> PatternChunk {
+    PatternChunk {
+        id: id.to_string(),
+        position: Some(Position { x: 10, y: 10 }),
+        name: "upload".to_string(),
+        cells,
+        rle: rle.to_string(),
+        macrocell: String::new(),
+    }
+}
+
+fn macrocell_chunk(id: &str, macrocell: &str) -> PatternChunk {
+    PatternChunk {
+        macrocell: macrocell.to_string(),
+        ..upload_chunk(id, vec![], "")
+    }
+}
+
+fn upload_stream(chunks: Vec<PatternChunk>) -> impl tokio_stream::Stream<Item = Result<PatternChunk, tonic::Status>> + Unpin {
+    tokio_stream::iter(chunks.into_iter().map(Ok))
+}
+
+#[tokio::test]
+async fn test_upload_pattern_decodes_rle_across_chunks() {
+    let service = create_test_service();
+    let simulation = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 100,
+        height: 100,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    
+    // A glider split mid-run and mid-line, followed by an explicit cell
+    let chunks = vec![
+        upload_chunk(&simulation.id, vec![], "#N Glider\nx = 3, y = 3\nbo"),
+        upload_chunk(&simulation.id, vec![], "b$2bo$3"),
+        upload_chunk(&simulation.id, vec![Position { x: 20, y: 20 }], "o!"),
+    ];
+    let response = service.upload_pattern_chunks(upload_stream(chunks)).await.unwrap();
+    assert!(response.success);
+    assert_eq!(response.cells_added, 6);
+    
+    let uploaded = service.get_simulation(Request::new(GetSimulationRequest {
+        id: simulation.id,
+        read_mask: None,
+        generation: None,
+    })).await.unwrap().into_inner();
+    let mut cells: Vec<(i64, i64)> = uploaded.cells.iter().map(|cell| (cell.x, cell.y)).collect();
+    cells.sort();
+    assert_eq!(cells, vec![(10, 12), (11, 10), (11, 12), (12, 11), (12, 12), (30, 30)]);
+}
+
+#[tokio::test]
+async fn test_upload_pattern_rejects_bad_uploads() {
+    let service = create_test_service();
+    let simulation = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 100,
+        height: 100,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    
+    let empty = service.upload_pattern_chunks(upload_stream(Vec::new())).await.unwrap_err();
+    assert_eq!(empty.code(), tonic::Code::InvalidArgument);
+    
+    let missing = service.upload_pattern_chunks(upload_stream(vec![upload_chunk("missing", vec![], "o!")]))
+        .await
+        .unwrap_err();
+    assert_eq!(missing.code(), tonic::Code::NotFound);
+    
+    let invalid = service.upload_pattern_chunks(upload_stream(vec![upload_chunk(&simulation.id, vec![], "3*!")]))
+        .await
+        .unwrap_err();
+    assert_eq!(invalid.code(), tonic::Code::InvalidArgument);
+    assert_eq!(invalid.get_error_details().bad_request().unwrap().field_violations[0].field, "rle");
+}
+
+#[tokio::test]
+async fn test_replay_reconstructs_simulation() {
+    let service = create_test_service();
+    let id = create_blinker_at_generation_two(&service).await;
+    
+    service.load_pattern(Request::new(LoadPatternRequest {
+        id: id.clone(),
+        pattern: Some(Pattern {
+            name: "block".to_string(),
+            cells: vec![Position { x: 0, y: 0 }, Position { x: 1, y: 0 }, Position { x: 0, y: 1 }, Position { x: 1, y: 1 }],
+            description: String::new(),
+            author: String::new(),
+        }),
+        position: Some(Position { x: 7, y: 7 }),
+        apgcode: String::new(),
+        overwrite: OverwritePolicy::Skip as i32,
+        expected_version: None,
+    })).await.unwrap();
+    for _ in 0..3 {
+        service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 1, expected_version: None })).await.unwrap();
+    }
+    
+    let replay = service.replay_simulation(Request::new(ReplayRequest {
+        id: id.clone(),
+        up_to: 0,
+        include_entries: true,
+    })).await.unwrap().into_inner();
+    let current = service.get_simulation(Request::new(GetSimulationRequest { id: id.clone(), read_mask: None, generation: None }))
+        .await.unwrap().into_inner();
+    
+    assert!(replay.matches_current);
+    assert_eq!(replay.simulation.unwrap().generation, current.generation);
+    let operations: Vec<&str> = replay.entries.iter().map(|entry| entry.operation.as_str()).collect();
+    assert_eq!(operations, vec!["create", "update", "add_pattern", "step"]);
+    assert_eq!(replay.entries[3].detail, "3 generations");
+    
+    // Replaying a prefix shows the state before the block was loaded
+    let partial = service.replay_simulation(Request::new(ReplayRequest {
+        id: id.clone(),
+        up_to: 2,
+        include_entries: false,
+    })).await.unwrap().into_inner();
+    assert_eq!((partial.entries_replayed, partial.total_entries), (2, 4));
+    assert!(!partial.matches_current);
+    assert!(partial.entries.is_empty());
+    let simulation = partial.simulation.unwrap();
+    assert_eq!((simulation.generation, simulation.live_cells), (2, 3));
+    
+    service.delete_simulation(Request::new(DeleteSimulationRequest { id: id.clone(), expected_version: None })).await.unwrap();
+    let error = service.replay_simulation(Request::new(ReplayRequest { id, up_to: 0, include_entries: false }))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_export_and_upload_macrocell_round_trip() {
+    let service = create_test_service();
+    let source = create_blinker_at_generation_two(&service).await;
+    service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: source.clone(),
+        generation: 0,
+        cells: vec![Cell { x: 8, y: 9, alive: true, neighbors: 0, state: 1, color: 0 }],
+        update_mask: mask(&["cells.alive"]),
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap();
+    
+    let rle = service.export_pattern(Request::new(ExportPatternRequest {
+        id: source.clone(),
+        format: PatternFormat::PatternRle as i32,
+    })).await.unwrap().into_inner();
+    assert_eq!(rle.content, "x = 8, y = 8, rule = B3/S23\n3o7$7bo!\n");
+    assert_eq!((rle.generation, rle.live_cells), (2, 4));
+    
+    let exported = service.export_pattern(Request::new(ExportPatternRequest {
+        id: source,
+        format: PatternFormat::PatternMacrocell as i32,
+    })).await.unwrap().into_inner();
+    assert!(exported.content.starts_with("[M2]"));
+    
+    let target = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 100,
+        height: 100,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    
+    // Split the document mid-line; the block boundary must not matter
+    let (head, tail) = exported.content.split_at(exported.content.len() / 2);
+    let uploaded = service.upload_pattern_chunks(upload_stream(vec![
+        macrocell_chunk(&target.id, head),
+        macrocell_chunk(&target.id, tail),
+    ])).await.unwrap();
+    assert_eq!(uploaded.cells_added, 4);
+    
+    let simulation = service.get_simulation(Request::new(GetSimulationRequest { id: target.id, read_mask: None, generation: None }))
+        .await.unwrap().into_inner();
+    let mut cells: Vec<(i64, i64)> = simulation.cells.iter().map(|cell| (cell.x, cell.y)).collect();
+    cells.sort();
+    assert_eq!(cells, vec![(10, 10), (11, 10), (12, 10), (17, 17)]);
+}
+
+#[tokio::test]
+async fn test_upload_macrocell_clips_to_grid() {
+    let service = create_test_service();
+    let simulation = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 20,
+        height: 20,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    
+    // Two cells a billion cells apart; only the first lands on the grid
+    let far = gol_bevy::resources::encode_macrocell(&[(0, 0), (1_000_000_000, 1_000_000_000)], &gol_bevy::resources::Rule::conway());
+    let uploaded = service.upload_pattern_chunks(upload_stream(vec![macrocell_chunk(&simulation.id, &far)]))
+        .await
+        .unwrap();
+    assert_eq!(uploaded.cells_added, 1);
+    
+    let mixed = service.upload_pattern_chunks(upload_stream(vec![
+        upload_chunk(&simulation.id, vec![], "o"),
+        macrocell_chunk(&simulation.id, "*$\n"),
+    ])).await.unwrap_err();
+    assert_eq!(mixed.code(), tonic::Code::InvalidArgument);
+    
+    let format = service.export_pattern(Request::new(ExportPatternRequest { id: simulation.id, format: 7 }))
+        .await
+        .unwrap_err();
+    assert_eq!(format.code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_load_pattern_accepts_apgcode() {
+    let service = create_test_service();
+    let simulation = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 20,
+        height: 20,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    
+    let loaded = service.load_pattern(Request::new(LoadPatternRequest {
+        id: simulation.id.clone(),
+        pattern: None,
+        position: Some(Position { x: 5, y: 5 }),
+        apgcode: "xq4_153".to_string(),
+        overwrite: OverwritePolicy::Skip as i32,
+        expected_version: None,
+    })).await.unwrap().into_inner();
+    assert_eq!(loaded.cells_added, 5);
+    assert!(loaded.message.contains("xq4_153"));
+    
+    let invalid = service.load_pattern(Request::new(LoadPatternRequest {
+        id: simulation.id,
+        pattern: None,
+        position: Some(Position { x: 5, y: 5 }),
+        apgcode: "xs4_3!".to_string(),
+        overwrite: OverwritePolicy::Skip as i32,
+        expected_version: None,
+    })).await.unwrap_err();
+    assert_eq!(invalid.code(), tonic::Code::InvalidArgument);
+    assert_eq!(invalid.get_error_details().bad_request().unwrap().field_violations[0].field, "apgcode");
+}
+
+#[tokio::test]
+async fn test_create_simulation_with_larger_than_life_rule() {
+    let service = create_test_service();
+    
+    let created = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 30,
+        height: 30,
+        initial_pattern: String::new(),
+        rule: "r2,c0,m0,s2..3,b3,nn".to_string(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    assert_eq!(created.rule, "R2,C0,M0,S2..3,B3,NN");
+    
+    // In the radius-2 diamond every cell of a vertical bar of three sees the
+    // other two and survives, and the cells either side of its middle see all
+    // three and are born.
+    service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: created.id.clone(),
+        update_mask: None,
+        generation: 0,
+        cells: [(10, 9), (10, 10), (10, 11)].iter()
+            .map(|&(x, y)| Cell { x, y, alive: true, neighbors: 0, state: 1, color: 0 })
+            .collect(),
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap();
+    service.step_simulation(Request::new(StepSimulationRequest {
+        id: created.id.clone(),
+        steps: 1,
+        expected_version: None,
+    })).await.unwrap();
+    
+    let simulation = service.get_simulation(Request::new(GetSimulationRequest {
+        id: created.id.clone(),
+        read_mask: None,
+        generation: None,
+    })).await.unwrap().into_inner();
+    let mut cells: Vec<_> = simulation.cells.iter().map(|cell| (cell.x, cell.y)).collect();
+    cells.sort_unstable();
+    assert_eq!(cells, vec![(9, 10), (10, 9), (10, 10), (10, 11), (11, 10)]);
+    assert_eq!(simulation.rule, "R2,C0,M0,S2..3,B3,NN");
+    
+    let invalid = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 30,
+        height: 30,
+        initial_pattern: String::new(),
+        rule: "R2,C0,M0,S2..3,B3,NX".to_string(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap_err();
+    assert_eq!(invalid.code(), tonic::Code::InvalidArgument);
+    assert_eq!(invalid.get_error_details().bad_request().unwrap().field_violations[0].field, "rule");
+}
+
+#[tokio::test]
+async fn test_generations_rule_reports_decaying_cells() {
+    let service = create_test_service();
+    
+    let created = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 20,
+        height: 20,
+        initial_pattern: String::new(),
+        rule: "B2/S/C3".to_string(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    assert_eq!(created.rule, "B2/S/C3");
+    
+    service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: created.id.clone(),
+        update_mask: None,
+        generation: 0,
+        cells: vec![
+            Cell { x: 5, y: 5, alive: true, neighbors: 0, state: 1, color: 0 },
+            Cell { x: 6, y: 5, alive: true, neighbors: 0, state: 1, color: 0 },
+        ],
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap();
+    let stepped = service.step_simulation(Request::new(StepSimulationRequest {
+        id: created.id.clone(),
+        steps: 1,
+        expected_version: None,
+    })).await.unwrap().into_inner();
+    assert_eq!(stepped.live_cells, 4);
+    
+    let simulation = service.get_simulation(Request::new(GetSimulationRequest {
+        id: created.id.clone(),
+        read_mask: None,
+        generation: None,
+    })).await.unwrap().into_inner();
+    let mut decaying: Vec<_> = simulation.cells.iter()
+        .filter(|cell| !cell.alive)
+        .map(|cell| (cell.x, cell.y, cell.state))
+        .collect();
+    decaying.sort_unstable();
+    assert_eq!(decaying, vec![(5, 5, 2), (6, 5, 2)]);
+    assert!(simulation.cells.iter().filter(|cell| cell.alive).all(|cell| cell.state == 1));
+    assert_eq!(simulation.live_cells, 4);
+}
+
+/// Seeds: a dead cell with exactly two live neighbors is born, every live cell dies.
+struct SeedsAutomaton;
+
+impl Automaton for SeedsAutomaton {
+    fn name(&self) -> &str {
+        "Seeds Plugin"
+    }
+    
+    fn next_state(&self, cells: &[u8]) -> u8 {
+        (cells[0] == 0 && cells[1..].iter().filter(|&&state| state == 1).count() == 2) as u8
+    }
+}
+
+#[tokio::test]
+async fn test_registered_automaton_is_selectable_by_name() {
+    register_automaton(Arc::new(SeedsAutomaton)).unwrap();
+    let service = create_test_service();
+    let status = service.get_status(Request::new(StatusRequest {})).await.unwrap().into_inner();
+    assert!(status.automata.contains(&"Seeds Plugin".to_string()));
+    
+    let created = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 20,
+        height: 20,
+        initial_pattern: String::new(),
+        rule: "seeds plugin".to_string(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    assert_eq!(created.rule, "Seeds Plugin");
+    
+    service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: created.id.clone(),
+        update_mask: None,
+        generation: 0,
+        cells: vec![
+            Cell { x: 5, y: 5, alive: true, neighbors: 0, state: 1, color: 0 },
+            Cell { x: 6, y: 5, alive: true, neighbors: 0, state: 1, color: 0 },
+        ],
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap();
+    let stepped = service.step_simulation(Request::new(StepSimulationRequest {
+        id: created.id.clone(),
+        steps: 1,
+        expected_version: None,
+    })).await.unwrap().into_inner();
+    assert_eq!(stepped.live_cells, 4);
+}
+
+#[tokio::test]
+async fn test_hexagonal_rule_uses_six_neighbors() {
+    let service = create_test_service();
+    
+    let created = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 20,
+        height: 20,
+        initial_pattern: String::new(),
+        rule: "B2/S34H".to_string(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    assert_eq!(created.rule, "B2/S34H");
+    
+    service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: created.id.clone(),
+        update_mask: None,
+        generation: 0,
+        cells: vec![
+            Cell { x: 5, y: 5, alive: true, neighbors: 0, state: 1, color: 0 },
+            Cell { x: 6, y: 5, alive: true, neighbors: 0, state: 1, color: 0 },
+        ],
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap();
+    service.step_simulation(Request::new(StepSimulationRequest {
+        id: created.id.clone(),
+        steps: 1,
+        expected_version: None,
+    })).await.unwrap();
+    
+    // Only the two hexes touching both cells are born; on a square grid the
+    // cells above and below each would be too
+    let simulation = service.get_simulation(Request::new(GetSimulationRequest {
+        id: created.id.clone(),
+        read_mask: None,
+        generation: None,
+    })).await.unwrap().into_inner();
+    let mut cells: Vec<_> = simulation.cells.iter().map(|cell| (cell.x, cell.y, cell.neighbors)).collect();
+    cells.sort_unstable();
+    assert_eq!(cells, vec![(5, 4, 0), (6, 6, 0)]);
+}
+
+#[tokio::test]
+async fn test_langtons_ant_walks_the_grid() {
+    let service = create_test_service();
+    
+    let created = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 20,
+        height: 20,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: "rl".to_string(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    assert_eq!(created.turmite, "RL");
+    assert_eq!(created.turmites, vec![Turmite { x: 10, y: 10, heading: Heading::North as i32, state: 0 }]);
+    
+    // Four right turns on empty cells bring the ant back to its start, where
+    // it clears the cell it set and turns left
+    service.step_simulation(Request::new(StepSimulationRequest {
+        id: created.id.clone(),
+        steps: 5,
+        expected_version: None,
+    })).await.unwrap();
+    let simulation = service.get_simulation(Request::new(GetSimulationRequest {
+        id: created.id.clone(),
+        read_mask: None,
+        generation: None,
+    })).await.unwrap().into_inner();
+    assert_eq!(simulation.live_cells, 3);
+    assert_eq!(simulation.turmites, vec![Turmite { x: 9, y: 10, heading: Heading::West as i32, state: 0 }]);
+}
+
+#[tokio::test]
+async fn test_create_turmite_simulation_rejects_invalid_requests() {
+    let service = create_test_service();
+    let create = |rule: &str, turmite: &str, turmites: Vec<Turmite>| CreateSimulationRequest {
+        width: 10,
+        height: 10,
+        initial_pattern: String::new(),
+        rule: rule.to_string(),
+        turmite: turmite.to_string(),
+        turmites,
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    };
+    let ant = Turmite { x: 5, y: 5, heading: Heading::East as i32, state: 0 };
+    
+    for (request, field) in [
+        (create("B3/S23", "RL", vec![]), "turmite"),
+        (create("", "RLX", vec![]), "turmite"),
+        (create("", "", vec![ant]), "turmites"),
+        (create("", "RL", vec![Turmite { x: 10, ..ant }]), "turmites"),
+        (create("", "RL", vec![Turmite { state: 1, ..ant }]), "turmites"),
+        (create("", "RL", vec![Turmite { heading: 7, ..ant }]), "turmites"),
+    ] {
+        let error = service.create_simulation(Request::new(request)).await.unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+        assert_eq!(error.get_error_details().bad_request().unwrap().field_violations[0].field, field);
+    }
+}
+
+#[tokio::test]
+async fn test_immigration_births_take_majority_color() {
+    let service = create_test_service();
+    
+    let created = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 20,
+        height: 20,
+        initial_pattern: String::new(),
+        rule: "immigration".to_string(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    assert_eq!(created.rule, "Immigration");
+    
+    // A blinker with two cells of color 1 and one of color 0
+    service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: created.id.clone(),
+        update_mask: None,
+        generation: 0,
+        cells: vec![
+            Cell { x: 4, y: 5, alive: true, neighbors: 0, state: 1, color: 1 },
+            Cell { x: 5, y: 5, alive: true, neighbors: 0, state: 1, color: 0 },
+            Cell { x: 6, y: 5, alive: true, neighbors: 0, state: 1, color: 1 },
+        ],
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap();
+    service.step_simulation(Request::new(StepSimulationRequest {
+        id: created.id.clone(),
+        steps: 1,
+        expected_version: None,
+    })).await.unwrap();
+    
+    let simulation = service.get_simulation(Request::new(GetSimulationRequest {
+        id: created.id.clone(),
+        read_mask: None,
+        generation: None,
+    })).await.unwrap().into_inner();
+    let mut cells: Vec<_> = simulation.cells.iter().map(|cell| (cell.x, cell.y, cell.color)).collect();
+    cells.sort_unstable();
+    // The centre survives with its own color; the new ends side with the majority
+    assert_eq!(cells, vec![(5, 4, 1), (5, 5, 0), (5, 6, 1)]);
+}
+
+#[tokio::test]
+async fn test_rule_table_runs_multi_state_cells() {
+    let service = create_test_service();
+    
+    let table = "@RULE Fade
+@TABLE
+n_states:3
+neighborhood:vonNeumann
+symmetries:permute
+# isolated live cells start fading, fading cells die
+1,0,0,0,0,2
+2,0,0,0,0,0";
+    let created = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 20,
+        height: 20,
+        initial_pattern: String::new(),
+        rule: table.to_string(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    assert_eq!(created.rule, "Fade");
+    
+    service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: created.id.clone(),
+        update_mask: None,
+        generation: 0,
+        cells: vec![
+            Cell { x: 3, y: 3, alive: false, neighbors: 0, state: 2, color: 0 },
+            Cell { x: 8, y: 8, alive: true, neighbors: 0, state: 1, color: 0 },
+        ],
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap();
+    service.step_simulation(Request::new(StepSimulationRequest {
+        id: created.id.clone(),
+        steps: 1,
+        expected_version: None,
+    })).await.unwrap();
+    
+    let simulation = service.get_simulation(Request::new(GetSimulationRequest {
+        id: created.id.clone(),
+        read_mask: None,
+        generation: None,
+    })).await.unwrap().into_inner();
+    let cells: Vec<_> = simulation.cells.iter().map(|cell| (cell.x, cell.y, cell.alive, cell.state)).collect();
+    assert_eq!(cells, vec![(8, 8, false, 2)]);
+    
+    let result = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 20,
+        height: 20,
+        initial_pattern: String::new(),
+        rule: "@RULE Bad\n@TABLE\nn_states:2\nneighborhood:vonNeumann\nsymmetries:none\n0,0,0,0,0,1".to_string(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await;
+    assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_wrapped_boundary_joins_edges() {
+    let service = create_test_service();
+    
+    let created = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 10,
+        height: 10,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: BoundaryMode::Wrap as i32,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    assert_eq!(created.grid.unwrap().boundary_mode, BoundaryMode::Wrap as i32);
+    
+    // A blinker lying across the left and right edges
+    service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: created.id.clone(),
+        update_mask: None,
+        generation: 0,
+        cells: [(9, 5), (0, 5), (1, 5)].into_iter()
+            .map(|(x, y)| Cell { x, y, alive: true, neighbors: 0, state: 1, color: 0 })
+            .collect(),
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap();
+    service.step_simulation(Request::new(StepSimulationRequest {
+        id: created.id.clone(),
+        steps: 1,
+        expected_version: None,
+    })).await.unwrap();
+    
+    let simulation = service.get_simulation(Request::new(GetSimulationRequest {
+        id: created.id.clone(),
+        read_mask: None,
+        generation: None,
+    })).await.unwrap().into_inner();
+    let mut cells: Vec<_> = simulation.cells.iter().map(|cell| (cell.x, cell.y)).collect();
+    cells.sort_unstable();
+    assert_eq!(cells, vec![(0, 4), (0, 5), (0, 6)]);
+}
+
+#[tokio::test]
+async fn test_update_changes_rule_and_boundary() {
+    let service = create_test_service();
+    
+    let created = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 20,
+        height: 20,
+        initial_pattern: String::new(),
+        rule: "B2/S/C3".to_string(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: BoundaryMode::Wrap as i32,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    
+    service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: created.id.clone(),
+        update_mask: None,
+        generation: 0,
+        cells: vec![
+            Cell { x: 5, y: 5, alive: true, neighbors: 0, state: 1, color: 0 },
+            Cell { x: 6, y: 5, alive: false, neighbors: 0, state: 2, color: 0 },
+        ],
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap();
+    
+    // Switching to a two-state rule drops the decaying cell; the mask is
+    // needed to switch back to a clipped boundary
+    let updated = service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: created.id.clone(),
+        update_mask: mask(&["rule", "boundary_mode"]),
+        generation: 0,
+        cells: vec![],
+        rule: "B36/S23".to_string(),
+        boundary_mode: BoundaryMode::Clip as i32,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap().into_inner();
+    assert_eq!(updated.rule, "B36/S23");
+    assert_eq!(updated.grid.unwrap().boundary_mode, BoundaryMode::Clip as i32);
+    assert_eq!(updated.cells.iter().map(|cell| (cell.x, cell.y, cell.state)).collect::<Vec<_>>(), vec![(5, 5, 1)]);
+    
+    let replayed = service.replay_simulation(Request::new(ReplayRequest {
+        id: created.id.clone(),
+        up_to: 0,
+        include_entries: true,
+    })).await.unwrap().into_inner();
+    assert!(replayed.matches_current);
+    assert_eq!(replayed.entries[2].detail, "rule B36/S23, clip boundary, 0 cells");
+    
+    let result = service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: created.id.clone(),
+        update_mask: None,
+        generation: 0,
+        cells: vec![],
+        rule: "B3/Sx".to_string(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await;
+    assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_boundary_modes_are_validated_and_echoed() {
+    let service = create_test_service();
+    
+    for mode in [BoundaryMode::Mirror, BoundaryMode::KleinBottle] {
+        let created = service.create_simulation(Request::new(CreateSimulationRequest {
+            width: 10,
+            height: 10,
+            initial_pattern: String::new(),
+            rule: String::new(),
+            turmite: String::new(),
+            turmites: vec![],
+            boundary_mode: mode as i32,
+            config: None,
+            seed: None,
+            id: String::new(),
+        })).await.unwrap().into_inner();
+        assert_eq!(created.grid.unwrap().boundary_mode, mode as i32);
+    }
+    
+    let result = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 10,
+        height: 10,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 9,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await;
+    assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_max_generations_stops_stepping() {
+    use tokio_stream::StreamExt;
+    
+    let service = create_test_service();
+    
+    let created = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 10,
+        height: 10,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: Some(SimulationConfig { max_generations: 5, ..Default::default() }),
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    assert_eq!(created.config.unwrap().max_generations, 5);
+    assert!(!created.completed);
+    
+    service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: created.id.clone(),
+        update_mask: None,
+        generation: 0,
+        cells: [(4, 3), (4, 4), (4, 5)].iter()
+            .map(|&(x, y)| Cell { x, y, alive: true, neighbors: 0, state: 1, color: 0 })
+            .collect(),
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap();
+    
+    let stepped = service.step_simulation(Request::new(StepSimulationRequest {
+        id: created.id.clone(),
+        steps: 3,
+        expected_version: None,
+    })).await.unwrap().into_inner();
+    assert_eq!(stepped.generation, 3);
+    assert!(!stepped.completed);
+    
+    // Only the two generations left before the limit run
+    let stepped = service.step_simulation(Request::new(StepSimulationRequest {
+        id: created.id.clone(),
+        steps: 10,
+        expected_version: None,
+    })).await.unwrap().into_inner();
+    assert_eq!(stepped.generation, 5);
+    assert!(stepped.completed);
+    
+    let simulation = service.get_simulation(Request::new(GetSimulationRequest {
+        id: created.id.clone(),
+        read_mask: mask(&["generation", "completed"]),
+        generation: None,
+    })).await.unwrap().into_inner();
+    assert_eq!(simulation.generation, 5);
+    assert!(simulation.completed);
+    
+    // A stream over a finished simulation ends at once, saying why
+    let mut stream = service.stream_simulation(Request::new(StreamRequest {
+        id: created.id.clone(),
+        auto_step: true,
+        step_interval_ms: 1,
+    })).await.unwrap().into_inner();
+    let update = stream.next().await.unwrap().unwrap();
+    assert_eq!(update.generation, 5);
+    assert!(update.simulation_ended);
+    assert_eq!(update.reason, EndReason::MaxGenerations as i32);
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_simulation_config_is_validated() {
+    let service = create_test_service();
+    
+    for (config, field) in [
+        (SimulationConfig { max_generations: -1, ..Default::default() }, "config.max_generations"),
+        (SimulationConfig { max_generations: 10, completion_webhook: "https://example.com/done".to_string(), ..Default::default() }, "config.completion_webhook"),
+        (SimulationConfig { completion_webhook: "http://example.com/done".to_string(), ..Default::default() }, "config.completion_webhook"),
+    ] {
+        let status = service.create_simulation(Request::new(CreateSimulationRequest {
+            width: 10,
+            height: 10,
+            initial_pattern: String::new(),
+            rule: String::new(),
+            turmite: String::new(),
+            turmites: vec![],
+            boundary_mode: 0,
+            config: Some(config),
+            seed: None,
+            id: String::new(),
+        })).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        let details = status.get_error_details();
+        assert_eq!(details.bad_request().unwrap().field_violations[0].field, field);
+    }
+}
+
+/// Give a simulation a glider, which keeps changing until it reaches an edge.
+async fn add_glider(service: &GameOfLifeServiceImpl, id: &str) {
+    service.load_pattern(Request::new(LoadPatternRequest {
+        id: id.to_string(),
+        pattern: Some(Pattern {
+            name: "glider".to_string(),
+            description: String::new(),
+            author: String::new(),
+            cells: [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)].iter().map(|&(x, y)| Position { x, y }).collect(),
+        }),
+        position: Some(Position { x: 1, y: 1 }),
+        apgcode: String::new(),
+        overwrite: OverwritePolicy::Skip as i32,
+        expected_version: None,
+    })).await.unwrap();
+}
+
+async fn wait_for_job(service: &GameOfLifeServiceImpl, job_id: &str) -> JobStatusResponse {
+    for _ in 0..100 {
+        let status = service.get_job_status(Request::new(JobStatusRequest { job_id: job_id.to_string() })).await.unwrap().into_inner();
+        if status.state == JobState::JobCompleted as i32 {
+            return status;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    panic!("job did not complete");
+}
+
+#[tokio::test]
+async fn test_jobs_pause_repeating_simulations() {
+    let service = GameOfLifeServiceImpl::with_config(&ServerConfig {
+        max_inline_steps: 10,
+        ..ServerConfig::default()
+    });
+    let id = create_blinker_at_generation_two(&service).await;
+    
+    let accepted = service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 1000, expected_version: None })).await.unwrap().into_inner();
+    let job = wait_for_job(&service, &accepted.job_id).await;
+    assert_eq!(job.steps_completed, 2);
+    assert_eq!(job.generation, 4);
+    assert!(job.message.contains("repeats every 2 generations"), "{}", job.message);
+    
+    let simulation = service.get_simulation(Request::new(GetSimulationRequest {
+        id: id.clone(),
+        read_mask: mask(&["auto_pause"]),
+        generation: None,
+    })).await.unwrap().into_inner();
+    let pause = simulation.auto_pause.unwrap();
+    assert_eq!((pause.reason, pause.generation, pause.period), (EndReason::Cycle as i32, 4, 2));
+    
+    // A later job leaves the paused simulation alone
+    let accepted = service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 1000, expected_version: None })).await.unwrap().into_inner();
+    let job = wait_for_job(&service, &accepted.job_id).await;
+    assert_eq!((job.steps_completed, job.generation), (0, 4));
+    
+    // Stepping by hand is still allowed and lifts the pause
+    let stepped = service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 1, expected_version: None })).await.unwrap().into_inner();
+    assert_eq!(stepped.generation, 5);
+    let simulation = service.get_simulation(Request::new(GetSimulationRequest { id, read_mask: None, generation: None })).await.unwrap().into_inner();
+    assert!(simulation.auto_pause.is_none());
+}
+
+#[tokio::test]
+async fn test_auto_step_stream_ends_on_still_life() {
+    use tokio_stream::StreamExt;
+    
+    let service = create_test_service();
+    let created = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 10,
+        height: 10,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: None,
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: created.id.clone(),
+        update_mask: None,
+        generation: 0,
+        cells: [(4, 4), (5, 4), (4, 5), (5, 5)].iter()
+            .map(|&(x, y)| Cell { x, y, alive: true, neighbors: 0, state: 1, color: 0 })
+            .collect(),
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap();
+    
+    let stream = service.stream_simulation(Request::new(StreamRequest {
+        id: created.id,
+        auto_step: true,
+        step_interval_ms: 1,
+    })).await.unwrap().into_inner();
+    let updates: Vec<SimulationUpdate> = stream.map(Result::unwrap).collect().await;
+    assert_eq!(updates.len(), 1);
+    assert!(updates[0].simulation_ended);
+    assert_eq!(updates[0].reason, EndReason::Cycle as i32);
+    assert_eq!(updates[0].live_cells, 4);
+}
+
+#[tokio::test]
+async fn test_get_diff_compares_generations_and_simulations() {
+    let service = create_test_service();
+    let id = create_blinker_at_generation_two(&service).await;
+    service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 1, expected_version: None })).await.unwrap();
+    
+    let target = |id: &str, generation: Option<i64>| Some(DiffTarget { id: id.to_string(), generation });
+    let positions = |cells: &[(i64, i64)]| cells.iter().map(|&(x, y)| Position { x, y }).collect::<Vec<_>>();
+    
+    // The blinker turned from horizontal to vertical between generations 2 and 3
+    let diff = service.get_diff(Request::new(DiffRequest {
+        a: target(&id, Some(2)),
+        b: target(&id, None),
+    })).await.unwrap().into_inner();
+    assert_eq!((diff.generation_a, diff.generation_b), (2, 3));
+    assert_eq!(diff.only_in_a, positions(&[(1, 2), (3, 2)]));
+    assert_eq!(diff.only_in_b, positions(&[(2, 1), (2, 3)]));
+    assert_eq!(diff.common_cells, 1);
+    
+    let other = create_blinker_at_generation_two(&service).await;
+    let diff = service.get_diff(Request::new(DiffRequest {
+        a: target(&other, None),
+        b: target(&id, Some(2)),
+    })).await.unwrap().into_inner();
+    assert!(diff.only_in_a.is_empty() && diff.only_in_b.is_empty());
+    assert_eq!(diff.common_cells, 3);
+    
+    let missing = service.get_diff(Request::new(DiffRequest {
+        a: target(&id, Some(7)),
+        b: target(&id, None),
+    })).await.unwrap_err();
+    assert_eq!(missing.code(), tonic::Code::InvalidArgument);
+    let unknown = service.get_diff(Request::new(DiffRequest {
+        a: target("missing", None),
+        b: target(&id, None),
+    })).await.unwrap_err();
+    assert_eq!(unknown.code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_list_clone_and_rename_simulations() {
+    let service = create_test_service();
+    let id = create_blinker_at_generation_two(&service).await;
+    
+    let copy = service.clone_simulation(Request::new(CloneSimulationRequest {
+        id: id.clone(),
+        name: "copy".to_string(),
+    })).await.unwrap().into_inner();
+    assert_ne!(copy.id, id);
+    assert_eq!((copy.name.as_str(), copy.generation, copy.live_cells), ("copy", 2, 3));
+    
+    // The copy steps on its own
+    service.step_simulation(Request::new(StepSimulationRequest { id: copy.id.clone(), steps: 1, expected_version: None })).await.unwrap();
+    let renamed = service.rename_simulation(Request::new(RenameSimulationRequest {
+        id: id.clone(),
+        name: "  original ".to_string(),
+        expected_version: None,
+    })).await.unwrap().into_inner();
+    assert_eq!(renamed.name, "original");
+    
+    let list = service.list_simulations(Request::new(ListSimulationsRequest {})).await.unwrap().into_inner();
+    let summaries: Vec<_> = list.simulations.iter()
+        .map(|summary| (summary.id.as_str(), summary.name.as_str(), summary.generation, summary.live_cells))
+        .collect();
+    assert_eq!(summaries, vec![(id.as_str(), "original", 2, 3), (copy.id.as_str(), "copy", 3, 3)]);
+    assert_eq!(list.simulations[0].rule, "B3/S23");
+    
+    let too_long = service.rename_simulation(Request::new(RenameSimulationRequest {
+        id: id.clone(),
+        name: "x".repeat(65),
+        expected_version: None,
+    })).await.unwrap_err();
+    assert_eq!(too_long.code(), tonic::Code::InvalidArgument);
+    let missing = service.clone_simulation(Request::new(CloneSimulationRequest {
+        id: "missing".to_string(),
+        name: String::new(),
+    })).await.unwrap_err();
+    assert_eq!(missing.code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_history_range_and_past_generations() {
+    let service = create_test_service();
+    let id = create_blinker_at_generation_two(&service).await;
+    service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 3, expected_version: None })).await.unwrap();
+    
+    // Generations before the update that moved the blinker to 2 cannot be rebuilt
+    let history = service.get_history(Request::new(HistoryRequest { id: id.clone() })).await.unwrap().into_inner();
+    assert_eq!((history.first_generation, history.head_generation), (2, 5));
+    
+    let past = service.get_simulation(Request::new(GetSimulationRequest {
+        id: id.clone(),
+        read_mask: None,
+        generation: Some(3),
+    })).await.unwrap().into_inner();
+    assert_eq!((past.generation, past.live_cells), (3, 3));
+    assert!(past.cells.iter().any(|cell| (cell.x, cell.y) == (2, 1)));
+    
+    let current = service.get_simulation(Request::new(GetSimulationRequest {
+        id: id.clone(),
+        read_mask: None,
+        generation: Some(5),
+    })).await.unwrap().into_inner();
+    assert_eq!(current.generation, 5);
+    
+    let future = service.get_simulation(Request::new(GetSimulationRequest {
+        id: id.clone(),
+        read_mask: None,
+        generation: Some(9),
+    })).await.unwrap_err();
+    assert_eq!(future.code(), tonic::Code::InvalidArgument);
+    let missing = service.get_history(Request::new(HistoryRequest { id: "missing".to_string() })).await.unwrap_err();
+    assert_eq!(missing.code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_set_rule_switches_at_scheduled_generation() {
+    let service = create_test_service();
+    let id = create_blinker_at_generation_two(&service).await;
+    let set_rule = |rule: &str, at_generation, expected_version| service.set_rule(Request::new(SetRuleRequest {
+        id: id.clone(),
+        rule: rule.to_string(),
+        at_generation,
+        expected_version,
+    }));
+    
+    let scheduled = set_rule("B36/S23", Some(4), None).await.unwrap().into_inner();
+    assert_eq!(scheduled.rule, "B3/S23");
+    assert_eq!(scheduled.rule_changes, [RuleChange { generation: 4, rule: "B36/S23".to_string() }]);
+    set_rule("B3/S012345678", Some(10), None).await.unwrap();
+    let cancelled = set_rule("", Some(10), None).await.unwrap().into_inner();
+    assert_eq!(cancelled.rule_changes.len(), 1);
+    
+    service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 3, expected_version: None })).await.unwrap();
+    let current = service.get_simulation(Request::new(GetSimulationRequest { id: id.clone(), read_mask: None, generation: None }))
+        .await.unwrap().into_inner();
+    assert_eq!((current.generation, current.rule.as_str()), (5, "B36/S23"));
+    assert!(current.rule_changes.is_empty());
+    
+    // History and replays switch where the run did
+    let past = service.get_simulation(Request::new(GetSimulationRequest { id: id.clone(), read_mask: None, generation: Some(3) }))
+        .await.unwrap().into_inner();
+    assert_eq!(past.rule, "B3/S23");
+    let replay = service.replay_simulation(Request::new(ReplayRequest {
+        id: id.clone(),
+        up_to: 0,
+        include_entries: true,
+    })).await.unwrap().into_inner();
+    assert!(replay.matches_current);
+    assert!(replay.entries.iter().any(|entry| entry.operation == "set_rule" && entry.detail == "rule B36/S23 at generation 4"));
+    
+    let now = set_rule("B3/S23", None, Some(current.version)).await.unwrap().into_inner();
+    assert_eq!((now.generation, now.rule.as_str()), (5, "B3/S23"));
+    let stale = set_rule("B36/S23", None, Some(current.version)).await.unwrap_err();
+    assert_eq!(stale.code(), tonic::Code::FailedPrecondition);
+    let invalid = set_rule("B9/S23", Some(20), None).await.unwrap_err();
+    assert_eq!(invalid.code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_restore_checkpoint_rolls_back_edits_and_steps() {
+    let service = create_test_service();
+    let id = create_blinker_at_generation_two(&service).await;
+    let create = |name: &str| service.create_checkpoint(Request::new(CreateCheckpointRequest {
+        id: id.clone(),
+        name: name.to_string(),
+        expected_version: None,
+    }));
+    
+    let created = create(" known good ").await.unwrap().into_inner();
+    assert_eq!(created.checkpoints.len(), 1);
+    assert_eq!(created.checkpoints[0].name, "known good");
+    assert_eq!((created.checkpoints[0].generation, created.checkpoints[0].live_cells), (2, 3));
+    assert_eq!(create("  ").await.unwrap_err().code(), tonic::Code::InvalidArgument);
+    
+    service.set_rule(Request::new(SetRuleRequest { id: id.clone(), rule: "B3/S012345678".to_string(), at_generation: None, expected_version: None }))
+        .await.unwrap();
+    service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 5, expected_version: None })).await.unwrap();
+    
+    let restored = service.restore_checkpoint(Request::new(RestoreCheckpointRequest {
+        id: id.clone(),
+        name: "known good".to_string(),
+        expected_version: None,
+    })).await.unwrap().into_inner();
+    assert_eq!((restored.generation, restored.live_cells, restored.rule.as_str()), (2, 3, "B3/S23"));
+    
+    // Replays carry on past the rollback
+    service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 1, expected_version: None })).await.unwrap();
+    let replay = service.replay_simulation(Request::new(ReplayRequest { id: id.clone(), up_to: 0, include_entries: true }))
+        .await.unwrap().into_inner();
+    assert!(replay.matches_current);
+    assert!(replay.entries.iter().any(|entry| entry.operation == "restore"));
+    
+    let listed = service.list_checkpoints(Request::new(ListCheckpointsRequest { id: id.clone() })).await.unwrap().into_inner();
+    assert_eq!(listed.checkpoints.len(), 1, "restoring keeps the checkpoint");
+    let stale = service.restore_checkpoint(Request::new(RestoreCheckpointRequest {
+        id: id.clone(),
+        name: "known good".to_string(),
+        expected_version: Some(restored.version),
+    })).await.unwrap_err();
+    assert_eq!(stale.code(), tonic::Code::FailedPrecondition);
+    
+    let deleted = service.delete_checkpoint(Request::new(DeleteCheckpointRequest {
+        id: id.clone(),
+        name: "known good".to_string(),
+        expected_version: None,
+    })).await.unwrap().into_inner();
+    assert!(deleted.checkpoints.is_empty());
+    let missing = service.restore_checkpoint(Request::new(RestoreCheckpointRequest {
+        id,
+        name: "known good".to_string(),
+        expected_version: None,
+    })).await.unwrap_err();
+    assert_eq!(missing.code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_statistics_over_replayed_generations() {
+    let service = create_test_service();
+    let id = create_blinker_at_generation_two(&service).await;
+    service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 3, expected_version: None })).await.unwrap();
+    
+    let statistics = service.get_statistics(Request::new(StatisticsRequest {
+        id: id.clone(),
+        from_generation: None,
+        to_generation: None,
+    })).await.unwrap().into_inner();
+    let samples: Vec<(i64, i64, i64, i64)> = statistics.generations.iter()
+        .map(|sample| (sample.generation, sample.population, sample.births, sample.deaths))
+        .collect();
+    assert_eq!(samples, vec![(3, 3, 2, 2), (4, 3, 2, 2), (5, 3, 2, 2)]);
+    
+    let partial = service.get_statistics(Request::new(StatisticsRequest {
+        id: id.clone(),
+        from_generation: Some(3),
+        to_generation: Some(4),
+    })).await.unwrap().into_inner();
+    assert_eq!(partial.generations.iter().map(|sample| sample.generation).collect::<Vec<_>>(), vec![4]);
+    
+    let reversed = service.get_statistics(Request::new(StatisticsRequest {
+        id: id.clone(),
+        from_generation: Some(4),
+        to_generation: Some(3),
+    })).await.unwrap_err();
+    assert_eq!(reversed.code(), tonic::Code::InvalidArgument);
+    let before_log = service.get_statistics(Request::new(StatisticsRequest {
+        id,
+        from_generation: Some(0),
+        to_generation: None,
+    })).await.unwrap_err();
+    assert_eq!(before_log.code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_verify_simulation_checks_invariants() {
+    let service = create_test_service();
+    let id = create_blinker_at_generation_two(&service).await;
+    service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 3, expected_version: None })).await.unwrap();
+    
+    let verified = service.verify_simulation(Request::new(VerifyRequest {
+        id: id.clone(),
+        generations: 0,
+        checks: vec![],
+    })).await.unwrap().into_inner();
+    assert!(verified.passed);
+    assert_eq!(verified.generation, 5);
+    let results: Vec<(&str, i32)> = verified.results.iter().map(|result| (result.name.as_str(), result.status)).collect();
+    assert_eq!(results, vec![
+        ("conservation", InvariantStatus::Passed as i32),
+        ("still_life", InvariantStatus::Skipped as i32),
+        ("determinism", InvariantStatus::Passed as i32),
+        ("history", InvariantStatus::Passed as i32),
+    ]);
+    
+    let history = service.verify_simulation(Request::new(VerifyRequest {
+        id: id.clone(),
+        generations: 4,
+        checks: vec!["history".to_string()],
+    })).await.unwrap().into_inner();
+    assert_eq!(history.results.len(), 1);
+    
+    let unknown = service.verify_simulation(Request::new(VerifyRequest {
+        id: id.clone(),
+        generations: 0,
+        checks: vec!["bogus".to_string()],
+    })).await.unwrap_err();
+    assert_eq!(unknown.code(), tonic::Code::InvalidArgument);
+    let too_long = service.verify_simulation(Request::new(VerifyRequest {
+        id,
+        generations: 1_000_000,
+        checks: vec![],
+    })).await.unwrap_err();
+    assert_eq!(too_long.code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_reference_check_follows_stepping() {
+    let service = GameOfLifeServiceImpl::with_config(&ServerConfig {
+        reference_check: true,
+        ..ServerConfig::default()
+    });
+    let created = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 40,
+        height: 30,
+        initial_pattern: "random".to_string(),
+        rule: "B36/S23".to_string(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: BoundaryMode::Wrap as i32,
+        config: None,
+        seed: Some(11),
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    assert!(created.live_cells > 0);
+    
+    let stepped = service.step_simulation(Request::new(StepSimulationRequest { id: created.id.clone(), steps: 40, expected_version: None })).await.unwrap().into_inner();
+    assert_eq!(stepped.generation, 40);
+    assert_eq!(service.step_queue.divergence(&created.id), None);
+}
+
+#[cfg(feature = "scripting")]
+#[tokio::test]
+async fn test_script_errors_reach_event_watchers() {
+    use gol_bevy::resources::{ScriptHooks, Simulations};
+    use tokio_stream::StreamExt;
+    
+    let script = ScriptHooks::compile(r#"fn on_generation(grid) { if grid.generation == 2 { throw "no generation 2"; } }"#).unwrap();
+    let service = GameOfLifeServiceImpl::with_script(&ServerConfig::default(), Simulations::new(), Some(Arc::new(script)));
+    let id = service.create_simulation(Request::new(create_sized(20))).await.unwrap().into_inner().id;
+    add_glider(&service, &id).await;
+    let watched = service.watch_events(Request::new(WatchEventsRequest { ids: vec![id.clone()] })).await.unwrap().into_inner();
+    
+    // The simulation keeps stepping past the failed hook
+    let stepped = service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 4, expected_version: None })).await.unwrap().into_inner();
+    assert_eq!(stepped.generation, 4);
+    service.delete_simulation(Request::new(DeleteSimulationRequest { id, expected_version: None })).await.unwrap();
+    
+    let events: Vec<SimulationEvent> = watched.map(Result::unwrap).collect().await;
+    let errors: Vec<&SimulationEvent> = events.iter().filter(|event| event.r#type() == SimulationEventType::Error).collect();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].generation, 2);
+    assert!(errors[0].message.starts_with("Script error: on_generation failed"), "{}", errors[0].message);
+    assert!(errors[0].message.contains("no generation 2"), "{}", errors[0].message);
+}
+
+#[tokio::test]
+async fn test_run_benchmark_in_process() {
+    let service = create_test_service();
+    
+    let response = service.run_benchmark(Request::new(BenchmarkRequest {
+        pattern: "diehard".to_string(),
+        generations: 130,
+        width: 100,
+        height: 100,
+    })).await.unwrap().into_inner();
+    assert_eq!((response.pattern.as_str(), response.generations), ("diehard", 130));
+    assert_eq!(response.final_population, 0);
+    assert!(response.peak_population > 7);
+    assert!(response.generations_per_second > 0.0);
+    
+    let defaults = service.run_benchmark(Request::new(BenchmarkRequest {
+        pattern: String::new(),
+        generations: 10,
+        width: 0,
+        height: 0,
+    })).await.unwrap().into_inner();
+    assert_eq!(defaults.pattern, "glider-gun");
+    
+    let invalid = service.run_benchmark(Request::new(BenchmarkRequest {
+        pattern: "gun".to_string(),
+        generations: 1_000_000,
+        width: 0,
+        height: 2000,
+    })).await.unwrap_err();
+    assert_eq!(invalid.code(), tonic::Code::InvalidArgument);
+    let fields: Vec<String> = invalid.get_details_bad_request().unwrap().field_violations.into_iter().map(|violation| violation.field).collect();
+    assert_eq!(fields, vec!["pattern", "generations", "height"]);
+}
+
+#[tokio::test]
+async fn test_memory_usage_per_simulation() {
+    let service = create_test_service();
+    let id = create_blinker_at_generation_two(&service).await;
+    service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 3, expected_version: None })).await.unwrap();
+    
+    let list = service.list_simulations(Request::new(ListSimulationsRequest {})).await.unwrap().into_inner();
+    let summary = &list.simulations[0];
+    assert_eq!(summary.chunk_count, 1);
+    assert!(summary.cell_memory_bytes > 0);
+    assert!(summary.history_entries >= 3);
+    assert!(summary.history_memory_bytes > 0);
+    assert_eq!(summary.memory_bytes, summary.cell_memory_bytes + summary.history_memory_bytes);
+    
+    let status = service.get_status(Request::new(StatusRequest {})).await.unwrap().into_inner();
+    assert_eq!(status.memory_bytes, summary.memory_bytes);
+}
+
+#[tokio::test]
+async fn test_watch_events_follows_simulation_lifecycle() {
+    use tokio_stream::StreamExt;
+    
+    let service = GameOfLifeServiceImpl::with_config(&ServerConfig {
+        max_inline_steps: 10,
+        ..ServerConfig::default()
+    });
+    let id = create_blinker_at_generation_two(&service).await;
+    let other = create_blinker_at_generation_two(&service).await;
+    let mut everything = service.watch_events(Request::new(WatchEventsRequest { ids: vec![] })).await.unwrap().into_inner();
+    let watched = service.watch_events(Request::new(WatchEventsRequest { ids: vec![id.clone()] })).await.unwrap().into_inner();
+    
+    service.step_simulation(Request::new(StepSimulationRequest { id: other.clone(), steps: 1, expected_version: None })).await.unwrap();
+    service.load_pattern(Request::new(LoadPatternRequest {
+        id: id.clone(),
+        pattern: Some(Pattern { name: "dot".to_string(), cells: vec![Position { x: 8, y: 8 }], ..Default::default() }),
+        position: Some(Position { x: 0, y: 0 }),
+        apgcode: String::new(),
+        overwrite: OverwritePolicy::Skip as i32,
+        expected_version: None,
+    })).await.unwrap();
+    service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 1, expected_version: None })).await.unwrap();
+    // Run as a job, which stops once the blinker repeats
+    let accepted = service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 1000, expected_version: None })).await.unwrap().into_inner();
+    wait_for_job(&service, &accepted.job_id).await;
+    service.delete_simulation(Request::new(DeleteSimulationRequest { id: id.clone(), expected_version: None })).await.unwrap();
+    
+    // The stream ends once its only simulation is deleted
+    let events: Vec<SimulationEvent> = watched.map(Result::unwrap).collect().await;
+    let types: Vec<SimulationEventType> = events.iter().map(|event| event.r#type()).collect();
+    assert_eq!(types, [
+        SimulationEventType::PatternLoaded,
+        SimulationEventType::Stepped,
+        SimulationEventType::Stepped,
+        SimulationEventType::Stabilized,
+        SimulationEventType::Deleted,
+    ]);
+    assert!(events.iter().all(|event| event.id == id));
+    assert_eq!(events[0].cells_added, 1);
+    assert_eq!((events[1].steps, events[1].generation), (1, 3));
+    assert_eq!(events[3].reason(), EndReason::Cycle);
+    // Each event carries the version the change took the simulation to;
+    // the job's last step and its stabilizing share one
+    assert!(events[0].version > 0);
+    assert!(events[0].version < events[1].version && events[1].version < events[2].version);
+    assert!(events[2..].iter().all(|event| event.version >= events[2].version));
+    assert_eq!(events[3].period, 2);
+    
+    let first = everything.next().await.unwrap().unwrap();
+    assert_eq!((first.r#type(), first.id), (SimulationEventType::Stepped, other));
+    
+    let missing = service.watch_events(Request::new(WatchEventsRequest { ids: vec!["missing".to_string()] })).await;
+    assert_eq!(missing.err().unwrap().code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_watch_events_reports_generation_limit() {
+    use tokio_stream::StreamExt;
+    
+    let service = create_test_service();
+    let created = service.create_simulation(Request::new(CreateSimulationRequest {
+        width: 10,
+        height: 10,
+        initial_pattern: String::new(),
+        rule: String::new(),
+        turmite: String::new(),
+        turmites: vec![],
+        boundary_mode: 0,
+        config: Some(SimulationConfig { max_generations: 3, ..Default::default() }),
+        seed: None,
+        id: String::new(),
+    })).await.unwrap().into_inner();
+    let mut events = service.watch_events(Request::new(WatchEventsRequest { ids: vec![created.id.clone()] })).await.unwrap().into_inner();
+    
+    service.step_simulation(Request::new(StepSimulationRequest { id: created.id.clone(), steps: 5, expected_version: None })).await.unwrap();
+    let stepped = events.next().await.unwrap().unwrap();
+    assert_eq!((stepped.r#type(), stepped.steps), (SimulationEventType::Stepped, 3));
+    let paused = events.next().await.unwrap().unwrap();
+    assert_eq!(paused.r#type(), SimulationEventType::Paused);
+    assert_eq!((paused.generation, paused.reason()), (3, EndReason::MaxGenerations));
+}
+
+/// Accept webhook POSTs on a local port, passing on each JSON body.
+async fn spawn_webhook_receiver() -> (String, tokio::sync::mpsc::UnboundedReceiver<serde_json::Value>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/milestones", listener.local_addr().unwrap());
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            let mut buffer = [0; 1024];
+            while !String::from_utf8_lossy(&received).ends_with('}') {
+                let read = socket.read(&mut buffer).await.unwrap();
+                received.extend_from_slice(&buffer[..read]);
+            }
+            socket.write_all(b"HTTP/1.1 204 No Content\r\ncontent-length: 0\r\n\r\n").await.unwrap();
+            let request = String::from_utf8(received).unwrap();
+            let (_, body) = request.split_once("\r\n\r\n").unwrap();
+            let _ = sender.send(serde_json::from_str(body).unwrap());
+        }
+    });
+    (url, receiver)
+}
+
+#[tokio::test]
+async fn test_webhooks_notify_chosen_milestones() {
+    let service = GameOfLifeServiceImpl::with_config(&ServerConfig {
+        max_inline_steps: 10,
+        ..ServerConfig::default()
+    });
+    let id = create_blinker_at_generation_two(&service).await;
+    let (url, mut received) = spawn_webhook_receiver().await;
+    
+    let invalid = service.set_webhooks(Request::new(SetWebhooksRequest {
+        id: id.clone(),
+        webhooks: vec![
+            Webhook { url: "https://example.com/hook".to_string(), on_completion: true, ..Default::default() },
+            Webhook { url: url.clone(), ..Default::default() },
+        ],
+        expected_version: None,
+    })).await.unwrap_err();
+    let violations = invalid.get_details_bad_request().unwrap().field_violations;
+    let fields: Vec<&str> = violations.iter().map(|violation| violation.field.as_str()).collect();
+    assert_eq!(fields, ["webhooks[0].url", "webhooks[1]"]);
+    
+    let set = service.set_webhooks(Request::new(SetWebhooksRequest {
+        id: id.clone(),
+        webhooks: vec![Webhook { url: url.clone(), on_stabilization: true, every_generations: 2, ..Default::default() }],
+        expected_version: None,
+    })).await.unwrap().into_inner();
+    assert_eq!(set.webhooks.len(), 1);
+    let stale = service.set_webhooks(Request::new(SetWebhooksRequest {
+        id: id.clone(),
+        webhooks: Vec::new(),
+        expected_version: Some(set.version - 1),
+    })).await.unwrap_err();
+    assert_eq!(stale.code(), tonic::Code::FailedPrecondition);
+    
+    // Generation 3 passes no multiple of 2; generation 4 does
+    service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 1, expected_version: None })).await.unwrap();
+    service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 1, expected_version: None })).await.unwrap();
+    let body = received.recv().await.unwrap();
+    assert_eq!((body["milestone"].as_str(), body["generation"].as_u64()), (Some("generation"), Some(4)));
+    assert_eq!(body["id"].as_str(), Some(id.as_str()));
+    
+    // A job runs until the blinker is seen repeating
+    let accepted = service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 1000, expected_version: None })).await.unwrap().into_inner();
+    let job = wait_for_job(&service, &accepted.job_id).await;
+    let mut milestones = Vec::new();
+    while milestones.len() < 2 {
+        let body = received.recv().await.unwrap();
+        assert_eq!(body["generation"].as_i64(), Some(job.generation));
+        milestones.push((body["milestone"].as_str().unwrap().to_string(), body["period"].as_u64()));
+    }
+    milestones.sort();
+    assert_eq!(milestones, [("generation".to_string(), None), ("stabilized".to_string(), Some(2))]);
+}
+
+#[tokio::test]
+async fn test_server_webhook_applies_to_new_simulations() {
+    let (url, mut received) = spawn_webhook_receiver().await;
+    let service = GameOfLifeServiceImpl::with_config(&ServerConfig {
+        webhook_url: Some(url),
+        ..ServerConfig::default()
+    });
+    let id = create_blinker_at_generation_two(&service).await;
+    service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: id.clone(),
+        update_mask: mask(&["cells"]),
+        generation: 0,
+        cells: vec![Cell { x: 5, y: 5, alive: true, neighbors: 0, state: 1, color: 0 }],
+        rule: String::new(),
+        boundary_mode: 0,
+        operations: vec![],
+        expected_generation: None,
+        expected_version: None,
+    })).await.unwrap();
+    
+    service.step_simulation(Request::new(StepSimulationRequest { id, steps: 1, expected_version: None })).await.unwrap();
+    let body = received.recv().await.unwrap();
+    assert_eq!((body["milestone"].as_str(), body["live_cells"].as_u64()), (Some("extinct"), Some(0)));
+}
+
+fn batch_spec(pattern: &str, generations: i64, repetitions: i32, soup_density: f64) -> BatchSpec {
+    BatchSpec {
+        name: "test".to_string(),
+        pattern: pattern.to_string(),
+        rule: String::new(),
+        width: 64,
+        height: 64,
+        generations,
+        repetitions,
+        seed: 42,
+        soup_density,
+        soup_size: 0,
+    }
+}
+
+async fn wait_for_batch(service: &GameOfLifeServiceImpl, batch_id: &str) -> BatchResultsResponse {
+    for _ in 0..500 {
+        let request = Request::new(BatchResultsRequest { batch_id: batch_id.to_string() });
+        let response = service.get_batch_results(request).await.unwrap().into_inner();
+        if !matches!(response.status.as_ref().unwrap().state(), JobState::JobPending | JobState::JobRunning) {
+            return response;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    panic!("batch did not finish");
+}
+
+#[tokio::test]
+async fn test_batch_runs_each_seed() {
+    let service = create_test_service();
+    let submitted = service.submit_batch(Request::new(batch_spec("diehard", 500, 3, 0.0))).await.unwrap().into_inner();
+    assert_eq!(submitted.runs_total, 3);
+    
+    let finished = wait_for_batch(&service, &submitted.batch_id).await;
+    assert_eq!(finished.status.unwrap().state(), JobState::JobCompleted);
+    let seeds: Vec<u64> = finished.results.iter().map(|result| result.seed).collect();
+    assert_eq!(seeds, [42, 43, 44]);
+    for result in &finished.results {
+        assert_eq!(result.reason(), EndReason::Extinct);
+        assert_eq!((result.generations, result.final_population), (130, 0));
+    }
+}
+
+#[tokio::test]
+async fn test_batch_soups_repeat_by_seed() {
+    let service = create_test_service();
+    let mut outcomes = Vec::new();
+    for _ in 0..2 {
+        let submitted = service.submit_batch(Request::new(batch_spec("", 300, 2, 0.4))).await.unwrap().into_inner();
+        let finished = wait_for_batch(&service, &submitted.batch_id).await;
+        let outcome: Vec<_> = finished.results.iter()
+            .map(|result| (result.seed, result.generations, result.final_population, result.peak_population))
+            .collect();
+        outcomes.push(outcome);
+    }
+    assert_eq!(outcomes[0].len(), 2);
+    assert_eq!(outcomes[0], outcomes[1]);
+    assert_ne!(outcomes[0][0].3, 0);
+    
+    let listed = service.list_batches(Request::new(ListBatchesRequest {})).await.unwrap().into_inner();
+    assert_eq!(listed.batches.len(), 2);
+    assert!(listed.batches[0].submitted_at_ms <= listed.batches[1].submitted_at_ms);
+}
+
+#[tokio::test]
+async fn test_batch_rejects_invalid_spec() {
+    let service = create_test_service();
+    let error = service.submit_batch(Request::new(BatchSpec {
+        rule: "B9".to_string(),
+        ..batch_spec("3o@", 0, 2000, 0.0)
+    })).await.unwrap_err();
+    
+    let details = error.get_error_details();
+    let mut fields: Vec<_> = details.bad_request().unwrap().field_violations.iter()
+        .map(|violation| violation.field.as_str())
+        .collect();
+    fields.sort();
+    assert_eq!(fields, ["generations", "pattern", "repetitions", "rule"]);
+    
+    let missing = service.get_batch_results(Request::new(BatchResultsRequest { batch_id: "missing".to_string() })).await;
+    assert_eq!(missing.unwrap_err().code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_cancel_batch() {
+    let service = GameOfLifeServiceImpl::with_config(&ServerConfig {
+        batch_concurrency: 1,
+        ..ServerConfig::default()
+    });
+    let submitted = service.submit_batch(Request::new(BatchSpec {
+        width: 500,
+        height: 500,
+        ..batch_spec("glider-gun", 100_000, 5, 0.0)
+    })).await.unwrap().into_inner();
+    service.cancel_batch(Request::new(CancelBatchRequest { batch_id: submitted.batch_id.clone() })).await.unwrap();
+    
+    let finished = wait_for_batch(&service, &submitted.batch_id).await;
+    let status = finished.status.unwrap();
+    assert_eq!(status.state(), JobState::JobCancelled);
+    assert!(status.runs_completed < 5);
+    assert_eq!(finished.results.len(), status.runs_completed as usize);
+}
+
+fn with_token<T>(message: T, token: &str) -> Request<T> {
+    let mut request = Request::new(message);
+    request.metadata_mut().insert("authorization", format!("Bearer {}", token).parse().unwrap());
+    request
+}
+
+fn quota_service(quota: ClientQuota) -> GameOfLifeServiceImpl {
+    let client_tokens = Some(vec!["alice".to_string(), "bob".to_string()]);
+    GameOfLifeServiceImpl::with_config(&ServerConfig { client_quota: quota, client_tokens, ..ServerConfig::default() })
+}
+
+fn create_sized(size: i32) -> CreateSimulationRequest {
+    CreateSimulationRequest { width: size, height: size, ..Default::default() }
+}
+
+#[tokio::test]
+async fn test_experiment_compares_variants_with_control() {
+    let service = create_test_service();
+    let id = create_blinker_at_generation_two(&service).await;
+    let variant = |name: &str| ExperimentVariant { name: name.to_string(), ..ExperimentVariant::default() };
+    
+    let response = service.run_experiment(Request::new(ExperimentRequest {
+        id: id.clone(),
+        variants: vec![
+            ExperimentVariant { rule: "B3/S4".to_string(), ..variant("dies") },
+            ExperimentVariant { pattern: "2o$2o!".to_string(), x: 6, y: 6, ..variant("block") },
+        ],
+        generations: 20,
+    })).await.unwrap().into_inner();
+    
+    assert_eq!(response.generation, 2);
+    let control = response.control.unwrap();
+    assert_eq!((control.variant, control.rule.as_str()), (-1, "B3/S23"));
+    assert_eq!((control.reason(), control.period), (EndReason::Cycle, 2));
+    let [dies, block] = &response.results[..] else { panic!("expected two results") };
+    assert_eq!((dies.name.as_str(), dies.rule.as_str(), dies.reason()), ("dies", "B3/S4", EndReason::Extinct));
+    assert_eq!((dies.final_population, dies.population_difference), (0, -3));
+    assert_eq!((block.initial_population, block.final_population, block.population_difference), (7, 7, 4));
+    
+    // The simulation forked from is left as it was
+    let simulation = service.get_simulation(Request::new(GetSimulationRequest { id: id.clone(), read_mask: None, generation: None }))
+        .await.unwrap().into_inner();
+    assert_eq!((simulation.generation, simulation.live_cells, simulation.rule.as_str()), (2, 3, "B3/S23"));
+}
+
+#[tokio::test]
+async fn test_experiment_rejects_invalid_variants() {
+    let service = create_test_service();
+    let id = create_blinker_at_generation_two(&service).await;
+    let error = service.run_experiment(Request::new(ExperimentRequest {
+        id: id.clone(),
+        variants: vec![ExperimentVariant { rule: "B9".to_string(), soup_density: 2.0, ..ExperimentVariant::default() }],
+        generations: 0,
+    })).await.unwrap_err();
+    
+    let details = error.get_error_details();
+    let mut fields: Vec<_> = details.bad_request().unwrap().field_violations.iter()
+        .map(|violation| violation.field.as_str())
+        .collect();
+    fields.sort();
+    assert_eq!(fields, ["generations", "variants[0].rule", "variants[0].soup_density"]);
+    
+    let empty = service.run_experiment(Request::new(ExperimentRequest { id, variants: vec![], generations: 10 })).await;
+    assert_eq!(empty.unwrap_err().code(), tonic::Code::InvalidArgument);
+    let missing = service.run_experiment(Request::new(ExperimentRequest { id: "missing".to_string(), variants: vec![], generations: 10 })).await;
+    assert_eq!(missing.unwrap_err().code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_simulation_quota_per_token() {
+    let service = quota_service(ClientQuota { max_simulations: Some(1), ..Default::default() });
+    let first = service.create_simulation(with_token(create_sized(10), "alice")).await.unwrap().into_inner();
+    
+    let refused = service.create_simulation(with_token(create_sized(10), "alice")).await.unwrap_err();
+    assert_eq!(refused.code(), tonic::Code::ResourceExhausted);
+    let details = refused.get_error_details();
+    assert!(details.quota_failure().unwrap().violations[0].subject.starts_with("token:alice"));
+    let clone = CloneSimulationRequest { id: first.id.clone(), name: String::new() };
+    assert!(service.clone_simulation(with_token(clone, "alice")).await.is_err());
+    
+    // Other tokens have their own quota, and deleting frees a slot
+    assert!(service.create_simulation(with_token(create_sized(10), "bob")).await.is_ok());
+    service.delete_simulation(Request::new(DeleteSimulationRequest { id: first.id, expected_version: None })).await.unwrap();
+    assert!(service.create_simulation(with_token(create_sized(10), "alice")).await.is_ok());
+    
+    // Made-up tokens do not get a quota of their own
+    let unknown = service.create_simulation(with_token(create_sized(10), "mallory")).await.unwrap_err();
+    assert_eq!(unknown.code(), tonic::Code::Unauthenticated);
+}
+
+#[tokio::test]
+async fn test_step_quota_per_minute() {
+    let service = quota_service(ClientQuota { max_steps_per_minute: Some(10), ..Default::default() });
+    let id = service.create_simulation(with_token(create_sized(10), "alice")).await.unwrap().into_inner().id;
+    let step = |steps: i32| StepSimulationRequest { id: id.clone(), steps, expected_version: None };
+    
+    service.step_simulation(with_token(step(8), "alice")).await.unwrap();
+    let refused = service.step_simulation(with_token(step(3), "alice")).await.unwrap_err();
+    assert_eq!(refused.code(), tonic::Code::ResourceExhausted);
+    // Steps are charged to whoever asks for them
+    service.step_simulation(with_token(step(3), "bob")).await.unwrap();
+    service.step_simulation(with_token(step(2), "alice")).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_experiment_charges_every_run_to_the_step_quota() {
+    let service = quota_service(ClientQuota { max_steps_per_minute: Some(100), ..Default::default() });
+    let id = service.create_simulation(with_token(create_sized(10), "alice")).await.unwrap().into_inner().id;
+    let experiment = |generations: i64| ExperimentRequest {
+        id: id.clone(),
+        variants: vec![
+            ExperimentVariant { name: "highlife".to_string(), rule: "B36/S23".to_string(), ..Default::default() },
+            ExperimentVariant { name: "seeds".to_string(), rule: "B2/S".to_string(), ..Default::default() },
+        ],
+        generations,
+    };
+    
+    // Two variants and the control at 40 generations would be 120 steps
+    let refused = service.run_experiment(with_token(experiment(40), "alice")).await.unwrap_err();
+    assert_eq!(refused.code(), tonic::Code::ResourceExhausted);
+    service.run_experiment(with_token(experiment(30), "alice")).await.unwrap();
+    let step = StepSimulationRequest { id: id.clone(), steps: 20, expected_version: None };
+    assert!(service.step_simulation(with_token(step, "alice")).await.is_err());
+}
+
+#[tokio::test]
+async fn test_batches_and_benchmarks_are_charged_to_the_step_quota() {
+    let service = quota_service(ClientQuota { max_steps_per_minute: Some(100), ..Default::default() });
+    
+    // Three repetitions of 40 generations would be 120 steps
+    let refused = service.submit_batch(with_token(batch_spec("diehard", 40, 3, 0.0), "alice")).await.unwrap_err();
+    assert_eq!(refused.code(), tonic::Code::ResourceExhausted);
+    service.submit_batch(with_token(batch_spec("diehard", 30, 3, 0.0), "alice")).await.unwrap();
+    
+    let benchmark = |generations: i32| BenchmarkRequest { pattern: "acorn".to_string(), generations, width: 0, height: 0 };
+    let refused = service.run_benchmark(with_token(benchmark(20), "alice")).await.unwrap_err();
+    assert_eq!(refused.code(), tonic::Code::ResourceExhausted);
+    service.run_benchmark(with_token(benchmark(20), "bob")).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_live_cell_quota_counts_owned_simulations() {
+    let service = quota_service(ClientQuota { max_live_cells: Some(5), ..Default::default() });
+    let id = service.create_simulation(with_token(create_sized(20), "alice")).await.unwrap().into_inner().id;
+    let load = |cells: &[(i64, i64)], x: i64| LoadPatternRequest {
+        id: id.clone(),
+        pattern: Some(Pattern {
+            name: "cells".to_string(),
+            cells: cells.iter().map(|&(x, y)| Position { x, y }).collect(),
+            ..Default::default()
+        }),
+        position: Some(Position { x, y: 1 }),
+        apgcode: String::new(),
+        overwrite: OverwritePolicy::Skip as i32,
+        expected_version: None,
+    };
+    
+    service.load_pattern(Request::new(load(&[(0, 0), (1, 0), (2, 0)], 1))).await.unwrap();
+    // Cells added by anyone count against the simulation's owner
+    let refused = service.load_pattern(with_token(load(&[(0, 0), (1, 0), (2, 0)], 10), "bob")).await.unwrap_err();
+    assert_eq!(refused.code(), tonic::Code::ResourceExhausted);
+    service.load_pattern(Request::new(load(&[(0, 0), (1, 0)], 10))).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_dense_simulations_are_truncated_and_paged() {
+    let service = GameOfLifeServiceImpl::with_config(&ServerConfig { max_response_cells: 10, ..ServerConfig::default() });
+    let created = service.create_simulation(Request::new(create_sized(20))).await.unwrap().into_inner();
+    assert!(!created.truncated);
+    // 25 lone cells, all gone after one step
+    let cells: Vec<Cell> = (0..25)
+        .map(|i| Cell { x: (i % 5) * 2, y: (i / 5) * 2, alive: true, state: 1, ..Default::default() })
+        .collect();
+    service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: created.id.clone(),
+        cells: cells.clone(),
+        ..Default::default()
+    })).await.unwrap();
+    
+    let response = service.get_simulation(Request::new(GetSimulationRequest { id: created.id.clone(), ..Default::default() })).await.unwrap().into_inner();
+    assert!(response.truncated);
+    assert_eq!(response.live_cells, 25);
+    let positions = |cells: &[Cell]| cells.iter().map(|cell| (cell.x, cell.y)).collect::<Vec<_>>();
+    assert_eq!(positions(&response.cells), positions(&cells[..10]));
+    
+    let page = |page_token: String| Request::new(GetCellsRequest { id: created.id.clone(), page_size: 0, page_token });
+    let first = service.get_cells(page(String::new())).await.unwrap().into_inner();
+    assert_eq!(first.total_cells, 25);
+    assert_eq!(positions(&first.cells), positions(&response.cells));
+    
+    // Later pages stay at the first page's generation
+    service.step_simulation(Request::new(StepSimulationRequest { id: created.id.clone(), steps: 1, expected_version: None })).await.unwrap();
+    let mut paged = first.cells;
+    let mut token = first.next_page_token;
+    while !token.is_empty() {
+        let next = service.get_cells(page(token)).await.unwrap().into_inner();
+        assert_eq!(next.generation, 0);
+        assert!(next.cells.len() <= 10);
+        paged.extend(next.cells);
+        token = next.next_page_token;
+    }
+    assert_eq!(positions(&paged), positions(&cells));
+    
+    let bad = service.get_cells(page("not-a-token".to_string())).await.unwrap_err();
+    assert_eq!(bad.code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_coordinates_beyond_grid_range_are_off_grid() {
+    let service = create_test_service();
+    let id = service.create_simulation(Request::new(create_sized(20))).await.unwrap().into_inner().id;
+    let load = |x: i64, cells: Vec<Position>| Request::new(LoadPatternRequest {
+        id: id.clone(),
+        pattern: Some(Pattern { name: "far".to_string(), cells, ..Default::default() }),
+        position: Some(Position { x, y: 0 }),
+        apgcode: String::new(),
+        overwrite: OverwritePolicy::Skip as i32,
+        expected_version: None,
+    });
+    
+    let error = service.load_pattern(load(1 << 40, vec![Position { x: 0, y: 0 }])).await.unwrap_err();
+    assert_eq!(error.code(), tonic::Code::InvalidArgument);
+    // Placing at the edge of the range must not overflow
+    let response = service.load_pattern(load(i32::MAX as i64, vec![Position { x: 1, y: 0 }])).await.unwrap().into_inner();
+    assert_eq!(response.cells_added, 0);
+    let response = service.load_pattern(load(0, vec![Position { x: 1 << 40, y: 0 }, Position { x: 1, y: 1 }])).await.unwrap().into_inner();
+    assert_eq!(response.cells_added, 1);
+    
+    let updated = service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: id.clone(),
+        cells: vec![Cell { x: -(1 << 40), y: 2, alive: true, state: 1, ..Default::default() }],
+        update_mask: mask(&["cells.alive"]),
+        ..Default::default()
+    })).await.unwrap().into_inner();
+    assert_eq!(updated.live_cells, 1);
+}
+
+#[tokio::test]
+async fn test_load_pattern_overwrite_policies() {
+    let service = create_test_service();
+    let id = service.create_simulation(Request::new(create_sized(10))).await.unwrap().into_inner().id;
+    service.update_simulation(Request::new(UpdateSimulationRequest {
+        id: id.clone(),
+        cells: vec![Cell { x: 9, y: 9, alive: true, state: 1, ..Default::default() }, Cell { x: 8, y: 8, alive: true, state: 1, ..Default::default() }],
+        ..Default::default()
+    })).await.unwrap();
+    // A 2x2 block at (8, 9) hangs a column and a row off the grid
+    let load = |overwrite: OverwritePolicy| Request::new(LoadPatternRequest {
+        id: id.clone(),
+        pattern: Some(Pattern {
+            name: "block".to_string(),
+            cells: vec![Position { x: 0, y: 0 }, Position { x: 1, y: 0 }, Position { x: 0, y: 1 }, Position { x: 1, y: 1 }],
+            ..Default::default()
+        }),
+        position: Some(Position { x: 8, y: 9 }),
+        apgcode: String::new(),
+        overwrite: overwrite as i32,
+        expected_version: None,
+    });
+    
+    let refused = service.load_pattern(load(OverwritePolicy::Error)).await.unwrap_err();
+    assert_eq!(refused.code(), tonic::Code::FailedPrecondition);
+    
+    let skipped = service.load_pattern(load(OverwritePolicy::Skip)).await.unwrap().into_inner();
+    assert!(skipped.success);
+    assert_eq!((skipped.cells_added, skipped.cells_overlapping, skipped.cells_out_of_bounds), (1, 1, 2));
+    assert_eq!(skipped.bounds, Some(Region { x: 8, y: 9, width: 2, height: 1 }));
+    
+    // Replacing clears the bounding box, which the cell at (8, 8) is outside of
+    let replaced = service.load_pattern(load(OverwritePolicy::Replace)).await.unwrap().into_inner();
+    assert_eq!((replaced.cells_added, replaced.cells_replaced), (2, 2));
+    let summary = service.get_summary(Request::new(GetSummaryRequest { id: id.clone() })).await.unwrap().into_inner();
+    assert_eq!(summary.live_cells, 3);
+    
+    let mut outside = load(OverwritePolicy::Skip).into_inner();
+    outside.position = Some(Position { x: 20, y: 20 });
+    let missed = service.load_pattern(Request::new(outside)).await.unwrap().into_inner();
+    assert!(!missed.success);
+    assert_eq!((missed.cells_out_of_bounds, missed.bounds), (4, None));
+}
+
+#[tokio::test]
+async fn test_random_simulations_repeat_by_seed() {
+    let service = create_test_service();
+    let random = |seed: Option<u64>| Request::new(CreateSimulationRequest {
+        initial_pattern: "random".to_string(),
+        seed,
+        ..create_sized(32)
+    });
+    let positions = |cells: &[Cell]| cells.iter().map(|cell| (cell.x, cell.y)).collect::<Vec<_>>();
+    
+    let first = service.create_simulation(random(Some(42))).await.unwrap().into_inner();
+    let second = service.create_simulation(random(Some(42))).await.unwrap().into_inner();
+    assert_eq!(first.seed, Some(42));
+    assert!(first.live_cells > 0);
+    assert_eq!(positions(&first.cells), positions(&second.cells));
+    
+    let unseeded = service.create_simulation(random(None)).await.unwrap().into_inner();
+    let replayed = service.create_simulation(random(unseeded.seed)).await.unwrap().into_inner();
+    assert!(unseeded.seed.is_some());
+    assert_eq!(positions(&unseeded.cells), positions(&replayed.cells));
+    
+    let plain = service.create_simulation(Request::new(create_sized(32))).await.unwrap().into_inner();
+    assert_eq!(plain.seed, None);
+    assert_eq!(plain.live_cells, 0);
+}
+
+#[tokio::test]
+async fn test_clear_and_fill_region() {
+    let service = create_test_service();
+    let id = service.create_simulation(Request::new(create_sized(100))).await.unwrap().into_inner().id;
+    let region = |x: i64, y: i64, width: i64, height: i64| Some(Region { x, y, width, height });
+    let fill = |seed: Option<u64>| Request::new(FillRegionRequest {
+        id: id.clone(),
+        region: region(10, 10, 40, 40),
+        solid: false,
+        density: 0.4,
+        seed,
+        expected_version: None,
+    });
+    
+    let solid = service.fill_region(Request::new(FillRegionRequest {
+        id: id.clone(),
+        region: region(90, 90, 50, 50),
+        solid: true,
+        ..Default::default()
+    })).await.unwrap().into_inner();
+    assert_eq!((solid.cells_before, solid.cells_after, solid.live_cells), (0, 100, 100));
+    assert_eq!(solid.seed, None);
+    
+    let cleared = service.clear_region(Request::new(ClearRegionRequest { id: id.clone(), region: region(95, 0, 10, 100), expected_version: None }))
+        .await.unwrap().into_inner();
+    assert_eq!((cleared.cells_before, cleared.cells_after, cleared.live_cells), (50, 0, 50));
+    
+    // The same seed fills the same cells; an unset one is reported back
+    let first = service.fill_region(fill(Some(7))).await.unwrap().into_inner();
+    let cells = service.get_simulation(Request::new(GetSimulationRequest { id: id.clone(), read_mask: None, generation: None }))
+        .await.unwrap().into_inner().cells;
+    let again = service.fill_region(fill(Some(7))).await.unwrap().into_inner();
+    assert_eq!(first.seed, Some(7));
+    assert_eq!(again.cells_before, first.cells_after);
+    assert_eq!(again.live_cells, first.live_cells);
+    let unseeded = service.fill_region(fill(None)).await.unwrap().into_inner();
+    assert!(unseeded.seed.is_some());
+    let repeated = service.fill_region(fill(unseeded.seed)).await.unwrap().into_inner();
+    assert_eq!(repeated.cells_after, unseeded.cells_after);
+    
+    service.fill_region(fill(Some(7))).await.unwrap();
+    let refilled = service.get_simulation(Request::new(GetSimulationRequest { id: id.clone(), read_mask: None, generation: None }))
+        .await.unwrap().into_inner().cells;
+    let positions = |cells: &[Cell]| cells.iter().map(|cell| (cell.x, cell.y)).collect::<Vec<_>>();
+    assert_eq!(positions(&refilled), positions(&cells));
+    
+    let replay = service.replay_simulation(Request::new(ReplayRequest { id: id.clone(), up_to: 0, include_entries: true }))
+        .await.unwrap().into_inner();
+    assert!(replay.matches_current);
+    assert_eq!(replay.entries[1].operation, "fill_region");
+    assert_eq!(replay.entries[2].operation, "clear_region");
+    
+    let empty = service.clear_region(Request::new(ClearRegionRequest { id: id.clone(), region: region(0, 0, 0, 5), expected_version: None })).await.unwrap_err();
+    assert_eq!(empty.code(), tonic::Code::InvalidArgument);
+    let dense = service.fill_region(Request::new(FillRegionRequest { density: 1.5, ..fill(None).into_inner() })).await.unwrap_err();
+    assert_eq!(dense.code(), tonic::Code::InvalidArgument);
+    let missing = service.clear_region(Request::new(ClearRegionRequest { id: "missing".to_string(), region: region(0, 0, 1, 1), expected_version: None }))
+        .await.unwrap_err();
+    assert_eq!(missing.code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_get_summary_reports_bounds() {
+    let service = create_test_service();
+    let id = service.create_simulation(Request::new(create_sized(200))).await.unwrap().into_inner().id;
+    let summary = |id: &str| service.get_summary(Request::new(GetSummaryRequest { id: id.to_string() }));
+    
+    let empty = summary(&id).await.unwrap().into_inner();
+    assert_eq!((empty.generation, empty.live_cells, empty.bounds), (0, 0, None));
+    
+    service.load_pattern(Request::new(LoadPatternRequest {
+        id: id.clone(),
+        pattern: None,
+        position: Some(Position { x: 60, y: 120 }),
+        apgcode: "xq4_153".to_string(),
+        overwrite: OverwritePolicy::Skip as i32,
+        expected_version: None,
+    })).await.unwrap();
+    service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 4, expected_version: None })).await.unwrap();
+    let stepped = summary(&id).await.unwrap().into_inner();
+    let cells = service.get_simulation(Request::new(GetSimulationRequest { id: id.clone(), read_mask: None, generation: None }))
+        .await.unwrap().into_inner().cells;
+    let (xs, ys): (Vec<i64>, Vec<i64>) = cells.iter().map(|cell| (cell.x, cell.y)).unzip();
+    let bounds = stepped.bounds.unwrap();
+    assert_eq!((stepped.generation, stepped.live_cells), (4, 5));
+    assert_eq!((bounds.x, bounds.y), (*xs.iter().min().unwrap(), *ys.iter().min().unwrap()));
+    assert_eq!((bounds.width, bounds.height), (3, 3));
+    
+    let missing = summary("missing").await.unwrap_err();
+    assert_eq!(missing.code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_get_heatmap_counts_generations_alive() {
+    let service = create_test_service();
+    let id = service.create_simulation(Request::new(create_sized(20))).await.unwrap().into_inner().id;
+    service.load_pattern(Request::new(LoadPatternRequest {
+        id: id.clone(),
+        pattern: Some(Pattern {
+            name: "blinker and block".to_string(),
+            cells: [(4, 3), (4, 4), (4, 5), (10, 10), (11, 10), (10, 11), (11, 11)]
+                .into_iter()
+                .map(|(x, y)| Position { x, y })
+                .collect(),
+            ..Default::default()
+        }),
+        position: Some(Position { x: 0, y: 0 }),
+        apgcode: String::new(),
+        overwrite: OverwritePolicy::Skip as i32,
+        expected_version: None,
+    })).await.unwrap();
+    service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 10, expected_version: None })).await.unwrap();
+    let heatmap = |generations: i32| service.get_heatmap(Request::new(HeatmapRequest { id: id.clone(), generations }));
+    
+    let window = heatmap(4).await.unwrap().into_inner();
+    assert_eq!((window.from_generation, window.to_generation, window.max_count), (6, 10, 4));
+    let count = |x: i64, y: i64| window.cells.iter().find(|cell| (cell.x, cell.y) == (x, y)).map(|cell| cell.count);
+    assert_eq!((count(4, 4), count(10, 10), count(3, 4), count(4, 3)), (Some(4), Some(4), Some(2), Some(2)));
+    assert_eq!(count(0, 0), None);
+    assert!(!window.truncated);
+    
+    // Longer than the simulation has run covers all of it
+    let whole = heatmap(0).await.unwrap().into_inner();
+    assert_eq!((whole.from_generation, whole.max_count), (0, 10));
+    
+    let error = heatmap(5000).await.unwrap_err();
+    assert_eq!(error.code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_breakpoints_stop_stepping() {
+    let service = create_test_service();
+    let id = service.create_simulation(Request::new(create_sized(30))).await.unwrap().into_inner().id;
+    add_glider(&service, &id).await;
+    
+    let generation = |generation| Breakpoint { condition: Some(breakpoint::Condition::Generation(generation)) };
+    let set = service.set_breakpoints(Request::new(SetBreakpointsRequest {
+        id: id.clone(),
+        breakpoints: vec![
+            generation(3),
+            Breakpoint { condition: Some(breakpoint::Condition::Pattern("bo$2bo$3o!".to_string())) },
+        ],
+        expected_version: None,
+    })).await.unwrap().into_inner();
+    assert_eq!(set.breakpoints[1].condition, Some(breakpoint::Condition::Pattern("xq4_153".to_string())));
+    
+    let stepped = service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 10, expected_version: None })).await.unwrap().into_inner();
+    assert_eq!(stepped.generation, 3);
+    assert_eq!(stepped.breakpoint, Some(generation(3)));
+    
+    // Stepping on carries past it
+    let stepped = service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 10, expected_version: None })).await.unwrap().into_inner();
+    assert_eq!(stepped.generation, 13);
+    assert_eq!(stepped.breakpoint, None);
+    
+    let simulation = service.get_simulation(Request::new(GetSimulationRequest {
+        id: id.clone(),
+        read_mask: mask(&["breakpoints"]),
+        generation: None,
+    })).await.unwrap().into_inner();
+    assert_eq!(simulation.breakpoints, set.breakpoints);
+    
+    // The steps since moved the simulation past the version they were set at
+    let stale = service.set_breakpoints(Request::new(SetBreakpointsRequest {
+        id: id.clone(),
+        breakpoints: Vec::new(),
+        expected_version: Some(set.version),
+    })).await.unwrap_err();
+    assert_eq!(stale.code(), tonic::Code::FailedPrecondition);
+    
+    let status = service.set_breakpoints(Request::new(SetBreakpointsRequest {
+        id,
+        breakpoints: vec![Breakpoint { condition: None }, Breakpoint { condition: Some(breakpoint::Condition::PopulationBelow(-1)) }],
+        expected_version: None,
+    })).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    let fields: Vec<String> = status.get_error_details().bad_request().unwrap().field_violations.iter().map(|violation| violation.field.clone()).collect();
+    assert_eq!(fields, ["breakpoints[0]", "breakpoints[1]"]);
+}
+
+#[tokio::test]
+async fn test_watchpoints_report_changed_cells() {
+    use tokio_stream::StreamExt;
+    
+    let service = create_test_service();
+    let id = service.create_simulation(Request::new(create_sized(30))).await.unwrap().into_inner().id;
+    add_glider(&service, &id).await;
+    let watched = service.watch_events(Request::new(WatchEventsRequest { ids: vec![id.clone()] })).await.unwrap().into_inner();
+    
+    let watchpoint = |x, y, size, pause| Watchpoint { region: Some(Region { x, y, width: size, height: size }), pause };
+    let set = service.set_watchpoints(Request::new(SetWatchpointsRequest {
+        id: id.clone(),
+        // Where the glider starts, then a spot on its path
+        watchpoints: vec![watchpoint(0, 0, 5, false), watchpoint(10, 10, 3, true)],
+        expected_version: None,
+    })).await.unwrap().into_inner();
+    let stale = service.set_watchpoints(Request::new(SetWatchpointsRequest {
+        id: id.clone(),
+        watchpoints: Vec::new(),
+        expected_version: Some(set.version - 1),
+    })).await.unwrap_err();
+    assert_eq!(stale.code(), tonic::Code::FailedPrecondition);
+    
+    let stepped = service.step_simulation(Request::new(StepSimulationRequest { id: id.clone(), steps: 100, expected_version: None })).await.unwrap().into_inner();
+    assert!(stepped.generation < 100);
+    assert_eq!(stepped.watchpoint, Some(set.watchpoints[1]));
+    service.delete_simulation(Request::new(DeleteSimulationRequest { id: id.clone(), expected_version: None })).await.unwrap();
+    
+    let events: Vec<SimulationEvent> = watched.map(Result::unwrap).collect().await;
+    let changes: Vec<&SimulationEvent> = events.iter().filter(|event| event.r#type() == SimulationEventType::CellsChanged).collect();
+    assert_eq!((changes[0].watchpoint, changes[0].generation), (0, 1));
+    let last = changes.last().unwrap();
+    assert_eq!((last.watchpoint, last.generation), (1, stepped.generation));
+    assert!(last.cells.iter().all(|cell| (10..13).contains(&cell.x) && (10..13).contains(&cell.y)));
+    let paused = events.iter().find(|event| event.r#type() == SimulationEventType::Paused).unwrap();
+    assert_eq!(paused.reason(), EndReason::Watchpoint);
+    
+    let id = service.create_simulation(Request::new(create_sized(30))).await.unwrap().into_inner().id;
+    let status = service.set_watchpoints(Request::new(SetWatchpointsRequest {
+        id,
+        watchpoints: vec![watchpoint(40, 40, 5, false), Watchpoint { region: None, pause: false }],
+        expected_version: None,
+    })).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    let fields: Vec<String> = status.get_error_details().bad_request().unwrap().field_violations.iter().map(|violation| violation.field.clone()).collect();
+    assert_eq!(fields, ["watchpoints[0].region", "watchpoints[1].region"]);
+}
+
+#[tokio::test]
+async fn test_annotations_are_set_filtered_and_cloned() {
+    let service = create_test_service();
+    let id = service.create_simulation(Request::new(create_sized(30))).await.unwrap().into_inner().id;
+    
+    let annotation = |x, y, text: &str| Annotation { x, y, text: text.to_string() };
+    let set = service.set_annotations(Request::new(SetAnnotationsRequest {
+        id: id.clone(),
+        annotations: vec![annotation(5, 5, " glider gun "), annotation(-40, 100, "off the grid")],
+        expected_version: None,
+    })).await.unwrap().into_inner();
+    assert_eq!(set.annotations, [annotation(5, 5, "glider gun"), annotation(-40, 100, "off the grid")]);
+    
+    let inside = service.get_annotations(Request::new(GetAnnotationsRequest {
+        id: id.clone(),
+        region: Some(Region { x: 0, y: 0, width: 10, height: 10 }),
+    })).await.unwrap().into_inner();
+    assert_eq!(inside.annotations, [annotation(5, 5, "glider gun")]);
+    assert_eq!(inside.version, set.version);
+    
+    let copy = service.clone_simulation(Request::new(CloneSimulationRequest { id: id.clone(), name: String::new() })).await.unwrap().into_inner();
+    let copied = service.get_annotations(Request::new(GetAnnotationsRequest { id: copy.id, region: None })).await.unwrap().into_inner();
+    assert_eq!(copied.annotations, set.annotations);
+    
+    let stale = service.set_annotations(Request::new(SetAnnotationsRequest {
+        id: id.clone(),
+        annotations: Vec::new(),
+        expected_version: Some(set.version - 1),
+    })).await.unwrap_err();
+    assert_eq!(stale.code(), tonic::Code::FailedPrecondition);
+    
+    let status = service.set_annotations(Request::new(SetAnnotationsRequest {
+        id,
+        annotations: vec![annotation(0, 0, ""), annotation(1, 1, "fine"), annotation(i64::MAX, 0, "far")],
+        expected_version: None,
+    })).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    let fields: Vec<String> = status.get_error_details().bad_request().unwrap().field_violations.iter().map(|violation| violation.field.clone()).collect();
+    assert_eq!(fields, ["annotations[0]", "annotations[2]"]);
+}
+
+/// Apply the commands an ECS backend service sends on a thread of its own,
+/// as the app does once a frame, until `running` is cleared.
+fn run_frames(mut manager: SimulationManager, running: Arc<std::sync::atomic::AtomicBool>) -> std::thread::JoinHandle<SimulationManager> {
+    std::thread::spawn(move || {
+        while running.load(std::sync::atomic::Ordering::Relaxed) {
+            manager.process_commands();
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        manager
+    })
+}
+
+#[tokio::test]
+async fn test_ecs_backend_steps_simulations_in_their_worlds() {
+    let manager = SimulationManager::new();
+    let ecs = GameOfLifeServiceImpl::new().with_ecs_backend(manager.sender());
+    let chunked = create_test_service();
+    let running = Arc::new(std::sync::atomic::AtomicBool::new(true));
+    let frames = run_frames(manager, running.clone());
+    
+    let pattern = |cells: &[(i64, i64)], x, y| LoadPatternRequest {
+        id: "glider".to_string(),
+        pattern: Some(Pattern {
+            name: String::new(),
+            cells: cells.iter().map(|&(x, y)| Position { x, y }).collect(),
+            description: String::new(),
+            author: String::new(),
+        }),
+        position: Some(Position { x, y }),
+        apgcode: String::new(),
+        overwrite: OverwritePolicy::Skip as i32,
+        expected_version: None,
+    };
+    let step = |steps| StepSimulationRequest { id: "glider".to_string(), steps, expected_version: None };
+    let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+    let mut results = Vec::new();
+    for service in [&ecs, &chunked] {
+        service.create_simulation(Request::new(CreateSimulationRequest { id: "glider".to_string(), ..create_sized(12) })).await.unwrap();
+        service.load_pattern(Request::new(pattern(&glider, 0, 0))).await.unwrap();
+        service.step_simulation(Request::new(step(8))).await.unwrap();
+        // Edited outside the worlds, so the next step starts from a rebuilt one
+        service.load_pattern(Request::new(pattern(&[(0, 0), (1, 0), (2, 0)], 0, 9))).await.unwrap();
+        let stepped = service.step_simulation(Request::new(step(30))).await.unwrap().into_inner();
+        let current = service.get_simulation(Request::new(GetSimulationRequest { id: "glider".to_string(), read_mask: None, generation: None }))
+            .await.unwrap().into_inner();
+        let mut cells: Vec<(i64, i64)> = current.cells.iter().map(|cell| (cell.x, cell.y)).collect();
+        cells.sort_unstable();
+        results.push((stepped.generation, stepped.live_cells, cells));
+    }
+    assert_eq!(results[0], results[1]);
+    assert_eq!(results[0].0, 38);
+    
+    // The steps ran in the world, which holds what GetSimulation answered
+    running.store(false, std::sync::atomic::Ordering::Relaxed);
+    let mut manager = frames.join().unwrap();
+    let mut world_cells: Vec<(i64, i64)> = manager.get_mut("glider").unwrap().live_cells().into_iter()
+        .map(|(x, y)| (x as i64, y as i64))
+        .collect();
+    world_cells.sort_unstable();
+    assert_eq!(world_cells, results[0].2);
+    
+    running.store(true, std::sync::atomic::Ordering::Relaxed);
+    let frames = run_frames(manager, running.clone());
+    assert!(ecs.delete_simulation(Request::new(DeleteSimulationRequest { id: "glider".to_string(), expected_version: None })).await.unwrap().into_inner().success);
+    running.store(false, std::sync::atomic::Ordering::Relaxed);
+    assert!(frames.join().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_ecs_steps_release_the_lock_and_notify_webhooks() {
+    let manager = SimulationManager::new();
+    let service = Arc::new(GameOfLifeServiceImpl::new().with_ecs_backend(manager.sender()));
+    let running = Arc::new(std::sync::atomic::AtomicBool::new(true));
+    let frames = run_frames(manager, running.clone());
+    let (url, mut received) = spawn_webhook_receiver().await;
+    
+    let id = service.create_simulation(Request::new(create_sized(20))).await.unwrap().into_inner().id;
+    let other = service.create_simulation(Request::new(create_sized(20))).await.unwrap().into_inner().id;
+    add_glider(&service, &id).await;
+    service.set_webhooks(Request::new(SetWebhooksRequest {
+        id: id.clone(),
+        webhooks: vec![Webhook { url, every_generations: 4, ..Default::default() }],
+        expected_version: None,
+    })).await.unwrap();
+    
+    // With no frames running, the step waits on its world
+    running.store(false, std::sync::atomic::Ordering::Relaxed);
+    let manager = frames.join().unwrap();
+    let stepping = {
+        let service = service.clone();
+        let step_request = Request::new(StepSimulationRequest { id: id.clone(), steps: 4, expected_version: None });
+        tokio::spawn(async move { service.step_simulation(step_request).await.unwrap().into_inner() })
+    };
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let pacing = service.set_pacing(Request::new(SetPacingRequest {
+        id: other,
+        target_generations_per_second: 10.0,
+        turbo: false,
+        expected_version: None,
+    }));
+    tokio::time::timeout(std::time::Duration::from_secs(5), pacing).await
+        .expect("the simulations lock is held while a world steps")
+        .unwrap();
+    assert!(!stepping.is_finished());
+    
+    running.store(true, std::sync::atomic::Ordering::Relaxed);
+    let frames = run_frames(manager, running.clone());
+    let stepped = stepping.await.unwrap();
+    assert_eq!((stepped.generation, stepped.live_cells), (4, 5));
+    let body = received.recv().await.unwrap();
+    assert_eq!((body["milestone"].as_str(), body["generation"].as_u64()), (Some("generation"), Some(4)));
+    running.store(false, std::sync::atomic::Ordering::Relaxed);
+    frames.join().unwrap();
+}
@@ -0,0 +1,127 @@
+//! Responses cached by simulation version.
+//!
+//! Servers bump a simulation's version on every change they publish and
+//! send the new version with each WatchEvents event. While a client follows
+//! those events, a response fetched at one version holds until the next
+//! arrives, so redraws asking again are answered without a round trip.
+//! Without the event stream nothing is cached. Some changes, such as rule
+//! or annotation edits, send no event, so entries also expire after
+//! [`CACHE_TTL`], and a client's own changes drop what it cached for that
+//! simulation.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::client::game_of_life::{AnnotationsResponse, CensusResponse, SimulationEvent, SimulationEventType, SimulationResponse, StatsResponse};
+
+/// Longest a response is served from the cache, however current its
+/// version looks.
+pub const CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// Responses kept before the oldest is dropped.
+const MAX_CACHED_RESPONSES: usize = 64;
+
+/// Which request of a simulation a cached response answers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CacheKey {
+    /// GetSimulation with every field, or with only those listed, sorted.
+    Simulation(Option<Vec<String>>),
+    /// GetStats over the whole grid, or a region's x, y, width and height.
+    Stats(Option<[i64; 4]>),
+    Census,
+    Annotations,
+}
+
+#[derive(Debug, Clone)]
+pub enum CachedResponse {
+    Simulation(Box<SimulationResponse>),
+    Stats(StatsResponse),
+    Census(CensusResponse),
+    Annotations(AnnotationsResponse),
+}
+
+#[derive(Debug)]
+struct Entry {
+    version: u64,
+    fetched: Instant,
+    response: CachedResponse,
+}
+
+/// Cached responses and the newest version seen of each simulation.
+#[derive(Debug, Default)]
+pub struct ResponseCache {
+    /// Set while an event stream reports new versions.
+    tracking: bool,
+    versions: HashMap<String, u64>,
+    entries: HashMap<(String, CacheKey), Entry>,
+}
+
+impl ResponseCache {
+    pub fn start_tracking(&mut self) {
+        self.tracking = true;
+    }
+    
+    /// The event stream ended, so versions seen may already be old; forget
+    /// everything and cache nothing until it is followed again.
+    pub fn stop_tracking(&mut self) {
+        self.tracking = false;
+        self.versions.clear();
+        self.entries.clear();
+    }
+    
+    /// Note a simulation's version from a response or event; versions only
+    /// move forward.
+    pub fn observe_version(&mut self, id: &str, version: u64) {
+        if !self.tracking || version == 0 {
+            return;
+        }
+        let known = self.versions.entry(id.to_string()).or_insert(version);
+        *known = (*known).max(version);
+    }
+    
+    /// Follow an event: its version makes older responses stale, and a
+    /// deleted simulation's are dropped.
+    pub fn observe_event(&mut self, event: &SimulationEvent) {
+        match event.r#type() {
+            SimulationEventType::Deleted => self.forget(&event.id),
+            // Servers that send no versions give no way to tell what is stale
+            _ if event.version == 0 => self.forget(&event.id),
+            _ => self.observe_version(&event.id, event.version),
+        }
+    }
+    
+    /// Drop everything cached for a simulation, as after changing it.
+    pub fn forget(&mut self, id: &str) {
+        self.versions.remove(id);
+        self.entries.retain(|(entry_id, _), _| entry_id != id);
+    }
+    
+    /// The version a response fetched now may be cached under, if known.
+    pub fn version(&self, id: &str) -> Option<u64> {
+        self.versions.get(id).copied().filter(|_| self.tracking)
+    }
+    
+    /// The response cached for `key`, if it is from the simulation's current
+    /// version and not expired.
+    pub fn get(&self, id: &str, key: &CacheKey) -> Option<CachedResponse> {
+        let version = self.version(id)?;
+        let entry = self.entries.get(&(id.to_string(), key.clone()))?;
+        (entry.version == version && entry.fetched.elapsed() < CACHE_TTL).then(|| entry.response.clone())
+    }
+    
+    /// Cache a response fetched at `version`, dropping the oldest entry when
+    /// full.
+    pub fn insert(&mut self, id: &str, key: CacheKey, version: u64, response: CachedResponse) {
+        if !self.tracking {
+            return;
+        }
+        let key = (id.to_string(), key);
+        if self.entries.len() >= MAX_CACHED_RESPONSES && !self.entries.contains_key(&key) {
+            let oldest = self.entries.iter().min_by_key(|(_, entry)| entry.fetched).map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, Entry { version, fetched: Instant::now(), response });
+    }
+}
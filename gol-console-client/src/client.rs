@@ -1,7 +1,17 @@
 use anyhow::{Result, Context};
-use tonic::transport::Channel;
-use tonic::{Request, Response, Status};
+use tonic::codec::CompressionEncoding;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::interceptor::{InterceptedService, Interceptor};
+use tonic::transport::{Channel, Endpoint};
+#[cfg(feature = "tls")]
+use tonic::transport::ClientTlsConfig;
+use tonic::{Code, Request, Status};
+use tonic_types::StatusExt;
+use std::fmt;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use crate::cache::{CacheKey, CachedResponse, ResponseCache};
+use crate::profiles::{ClientConfig, ConnectionProfile};
 
 pub mod game_of_life {
     tonic::include_proto!("game_of_life");
@@ -9,22 +19,181 @@ pub mod game_of_life {
 
 use game_of_life::{
     game_of_life_service_client::GameOfLifeServiceClient,
-    StatusRequest, StatusResponse,
+    StatusRequest, StatusResponse, BenchmarkRequest, BenchmarkResponse, StatsRequest, StatsResponse, CensusRequest, CensusResponse,
     CreateSimulationRequest, SimulationResponse,
-    GetSimulationRequest, UpdateSimulationRequest, DeleteSimulationRequest, DeleteResponse,
-    StepSimulationRequest, StepResponse,
-    LoadPatternRequest, LoadPatternResponse,
-    StreamRequest, SimulationUpdate,
-    Cell, Position, Pattern, GridInfo,
+    GetSimulationRequest, GetCellsRequest, GetCellsResponse, GetSummaryRequest, SummaryResponse, UpdateSimulationRequest, DeleteSimulationRequest, DeleteResponse,
+    ListSimulationsRequest, ListSimulationsResponse, CloneSimulationRequest, RenameSimulationRequest,
+    ReplayRequest, ReplayResponse, DiffRequest, DiffTarget, DiffResponse, HistoryRequest, HistoryResponse,
+    StatisticsRequest, StatisticsResponse, HeatmapRequest, HeatmapResponse, VerifyRequest, VerifyResponse,
+    StepSimulationRequest, StepResponse, SetPacingRequest, PacingResponse, SetWebhooksRequest, Webhook, WebhooksResponse, SetBreakpointsRequest, Breakpoint, BreakpointsResponse, SetWatchpointsRequest, Watchpoint, WatchpointsResponse,
+    SetAnnotationsRequest, GetAnnotationsRequest, Annotation, AnnotationsResponse, Region, SetRuleRequest, SetRuleResponse, ExperimentRequest, ExperimentVariant, ExperimentResponse,
+    CreateCheckpointRequest, ListCheckpointsRequest, DeleteCheckpointRequest, RestoreCheckpointRequest, CheckpointsResponse,
+    LoadPatternRequest, LoadPatternResponse, OverwritePolicy, PatternChunk,
+    ExportPatternRequest, ExportPatternResponse, PatternFormat,
+    JobStatusRequest, JobStatusResponse,
+    BatchSpec, BatchStatus, ListBatchesRequest, ListBatchesResponse, BatchResultsRequest, BatchResultsResponse, CancelBatchRequest,
+    StreamRequest, SimulationUpdate, WatchEventsRequest, SimulationEvent,
+    Cell, Position, Pattern, BoundaryMode,
 };
 
+/// Structured cause of a server error, decoded from its google.rpc details.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorCause {
+    /// The request had invalid fields, as `(field, description)` pairs.
+    InvalidFields(Vec<(String, String)>),
+    /// The named simulation or job does not exist.
+    NotFound { resource_type: String, name: String },
+    /// A rate limit was hit; `subject` names the exhausted budget.
+    QuotaExceeded { subject: String },
+    /// The server attached no details we understand.
+    Unknown,
+}
+
+/// An RPC failure with its decoded cause. Returned errors can be downcast to
+/// this to branch on the cause instead of the message text.
+#[derive(Debug, Clone)]
+pub struct ServerError {
+    pub code: Code,
+    pub message: String,
+    pub cause: ErrorCause,
+}
+
+impl From<Status> for ServerError {
+    fn from(status: Status) -> Self {
+        let details = status.get_error_details();
+        let cause = if let Some(bad_request) = details.bad_request() {
+            ErrorCause::InvalidFields(bad_request.field_violations.iter()
+                .map(|violation| (violation.field.clone(), violation.description.clone()))
+                .collect())
+        } else if let Some(info) = details.resource_info() {
+            ErrorCause::NotFound {
+                resource_type: info.resource_type.clone(),
+                name: info.resource_name.clone(),
+            }
+        } else if let Some(violation) = details.quota_failure().and_then(|quota| quota.violations.first()) {
+            ErrorCause::QuotaExceeded { subject: violation.subject.clone() }
+        } else {
+            ErrorCause::Unknown
+        };
+        
+        Self {
+            code: status.code(),
+            message: status.message().to_string(),
+            cause,
+        }
+    }
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        match &self.cause {
+            ErrorCause::InvalidFields(violations) => {
+                let fields: Vec<String> = violations.iter()
+                    .map(|(field, description)| format!("{}: {}", field, description))
+                    .collect();
+                write!(f, " ({})", fields.join(", "))
+            }
+            ErrorCause::NotFound { name, .. } => write!(f, " ({})", name),
+            ErrorCause::QuotaExceeded { .. } => write!(f, "; retry shortly"),
+            ErrorCause::Unknown => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for ServerError {}
+
+/// Cells sent per UploadPattern chunk, well under the default 4 MiB message limit.
+pub const UPLOAD_CHUNK_CELLS: usize = 16 * 1024;
+
+/// Bytes of RLE or macrocell text sent per UploadPattern chunk.
+pub const UPLOAD_CHUNK_RLE_BYTES: usize = 64 * 1024;
+
+/// Split pattern text into blocks of at most `max_bytes`, respecting UTF-8 boundaries.
+fn rle_blocks(rle: &str, max_bytes: usize) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut rest = rle;
+    while !rest.is_empty() {
+        let mut end = max_bytes.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (block, remainder) = rest.split_at(end);
+        blocks.push(block.to_string());
+        rest = remainder;
+    }
+    blocks
+}
+
+fn field_mask<'a>(paths: impl IntoIterator<Item = &'a str>) -> prost_types::FieldMask {
+    prost_types::FieldMask {
+        paths: paths.into_iter().map(str::to_string).collect(),
+    }
+}
+
+/// Environment variable holding the token sent to servers that enforce
+/// per-client quotas.
+pub const TOKEN_ENV_VAR: &str = "GOL_TOKEN";
+
+/// Adds `authorization: Bearer <token>` to every request when a token is set.
+#[derive(Clone, Default)]
+pub struct TokenInterceptor {
+    authorization: Option<MetadataValue<Ascii>>,
+}
+
+impl Interceptor for TokenInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(authorization) = &self.authorization {
+            request.metadata_mut().insert("authorization", authorization.clone());
+        }
+        Ok(request)
+    }
+}
+
+/// Parse `--compression`: `gzip`, `zstd`, or `none` for uncompressed calls.
+pub fn parse_compression(name: &str) -> Result<Option<CompressionEncoding>, String> {
+    match name.to_ascii_lowercase().as_str() {
+        "none" | "off" => Ok(None),
+        "gzip" => Ok(Some(CompressionEncoding::Gzip)),
+        "zstd" => Ok(Some(CompressionEncoding::Zstd)),
+        other => Err(format!("Unknown compression '{}', expected gzip, zstd or none", other)),
+    }
+}
+
+#[cfg(feature = "tls")]
+fn with_tls(endpoint: Endpoint, tls: bool) -> Result<Endpoint> {
+    if !tls {
+        return Ok(endpoint);
+    }
+    Ok(endpoint.tls_config(ClientTlsConfig::new().with_webpki_roots())?)
+}
+
+#[cfg(not(feature = "tls"))]
+fn with_tls(endpoint: Endpoint, tls: bool) -> Result<Endpoint> {
+    if tls {
+        anyhow::bail!("Cannot connect over TLS: built without the tls feature (cargo run --features tls)");
+    }
+    Ok(endpoint)
+}
+
+type ServiceClient = GameOfLifeServiceClient<InterceptedService<Channel, TokenInterceptor>>;
+
 #[derive(Clone)]
 pub struct GameOfLifeClient {
     pub backend: String,
     pub host: String,
     pub port: u16,
     pub timeout: Duration,
-    client: Option<GameOfLifeServiceClient<Channel>>,
+    /// Identifies this client to the server's quotas; defaults to `$GOL_TOKEN`.
+    pub token: Option<String>,
+    /// Compress requests with this and ask for compressed responses; the
+    /// server answers uncompressed if it does not support it.
+    pub compression: Option<CompressionEncoding>,
+    /// Connect over TLS (requires the `tls` feature).
+    pub tls: bool,
+    client: Option<ServiceClient>,
+    /// Responses cached by simulation version, shared by every clone.
+    cache: Arc<Mutex<ResponseCache>>,
 }
 
 impl GameOfLifeClient {
@@ -34,19 +203,27 @@ impl GameOfLifeClient {
             host,
             port,
             timeout: Duration::from_secs(5),
+            token: std::env::var(TOKEN_ENV_VAR).ok().filter(|token| !token.is_empty()),
+            compression: None,
+            tls: false,
             client: None,
+            cache: Arc::default(),
         }
     }
     
+    pub fn from_profile(profile: &ConnectionProfile) -> Self {
+        let mut client = Self::new(profile.backend.clone(), profile.host.clone(), profile.port)
+            .with_token(profile.token.clone());
+        client.tls = profile.tls;
+        client
+    }
+    
+    /// A client for the connection profile named `backend`, from the client
+    /// config or built in; unknown names get a default local profile.
     pub fn for_backend(backend: &str) -> Self {
-        let (host, port) = match backend {
-            "bevy" => ("localhost".to_string(), 50051),
-            "entt" => ("localhost".to_string(), 50052),
-            "flecs" => ("localhost".to_string(), 50053),
-            _ => ("localhost".to_string(), 50051),
-        };
-        
-        Self::new(backend.to_string(), host, port)
+        let profile = ClientConfig::load_or_default().profiles().remove(backend)
+            .unwrap_or_else(|| ConnectionProfile { backend: backend.to_string(), ..Default::default() });
+        Self::from_profile(&profile)
     }
     
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
@@ -54,89 +231,690 @@ impl GameOfLifeClient {
         self
     }
     
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        if token.is_some() {
+            self.token = token;
+        }
+        self
+    }
+    
+    pub fn with_compression(mut self, compression: Option<CompressionEncoding>) -> Self {
+        self.compression = compression;
+        self
+    }
+    
     pub async fn connect(&mut self) -> Result<()> {
-        let endpoint = format!("http://{}:{}", self.host, self.port);
-        let channel = Channel::from_shared(endpoint)?
-            .timeout(self.timeout)
+        let scheme = if self.tls { "https" } else { "http" };
+        let endpoint = Channel::from_shared(format!("{}://{}:{}", scheme, self.host, self.port))?
+            .timeout(self.timeout);
+        let channel = with_tls(endpoint, self.tls)?
             .connect()
             .await
             .context("Failed to connect to gRPC server")?;
             
-        self.client = Some(GameOfLifeServiceClient::new(channel));
+        let authorization = match &self.token {
+            Some(token) => Some(format!("Bearer {}", token).parse().context("Token must be printable ASCII")?),
+            None => None,
+        };
+        let mut client = GameOfLifeServiceClient::with_interceptor(channel, TokenInterceptor { authorization });
+        if let Some(encoding) = self.compression {
+            client = client.send_compressed(encoding).accept_compressed(encoding);
+        }
+        self.client = Some(client);
         Ok(())
     }
     
-    fn get_client(&mut self) -> Result<&mut GameOfLifeServiceClient<Channel>> {
+    fn get_client(&mut self) -> Result<&mut ServiceClient> {
         self.client.as_mut().ok_or_else(|| {
             anyhow::anyhow!("Client not connected. Call connect() first.")
         })
     }
     
+    /// Follow the server's events in the background, so responses are
+    /// cached until their simulation's version moves on. Servers without
+    /// WatchEvents end the stream at once and nothing is cached.
+    pub fn track_versions(&self) {
+        let mut client = self.clone();
+        tokio::spawn(async move {
+            if client.connect().await.is_err() {
+                return;
+            }
+            let Ok(mut events) = client.watch_events(Vec::new()).await else {
+                return;
+            };
+            client.cache.lock().unwrap().start_tracking();
+            while let Ok(Some(event)) = events.message().await {
+                client.cache.lock().unwrap().observe_event(&event);
+            }
+            client.cache.lock().unwrap().stop_tracking();
+        });
+    }
+    
+    fn cached(&self, id: &str, key: &CacheKey) -> Option<CachedResponse> {
+        self.cache.lock().unwrap().get(id, key)
+    }
+    
+    /// The version a response fetched now may be cached under.
+    fn cache_version(&self, id: &str) -> Option<u64> {
+        self.cache.lock().unwrap().version(id)
+    }
+    
+    /// Cache a response fetched at `version`; responses carrying their own
+    /// version also teach it to the cache.
+    fn store_cached(&self, id: &str, key: CacheKey, version: Option<u64>, response: CachedResponse) {
+        let mut cache = self.cache.lock().unwrap();
+        let own_version = match &response {
+            CachedResponse::Simulation(simulation) => Some(simulation.version),
+            CachedResponse::Annotations(annotations) => Some(annotations.version),
+            CachedResponse::Stats(_) | CachedResponse::Census(_) => None,
+        };
+        if let Some(own_version) = own_version.filter(|&version| version > 0) {
+            cache.observe_version(id, own_version);
+        }
+        if let Some(version) = own_version.or(version).filter(|&version| version > 0) {
+            cache.insert(id, key, version, response);
+        }
+    }
+    
+    /// Drop what is cached for a simulation about to be changed.
+    fn forget(&self, id: &str) {
+        self.cache.lock().unwrap().forget(id);
+    }
+    
     pub async fn get_status(&mut self) -> Result<StatusResponse> {
         let client = self.get_client()?;
         let request = Request::new(StatusRequest {});
         
-        let response = client.get_status(request).await?;
+        let response = client.get_status(request).await.map_err(ServerError::from)?;
         Ok(response.into_inner())
     }
     
-    pub async fn create_simulation(&mut self, width: i32, height: i32, initial_pattern: Option<String>) -> Result<SimulationResponse> {
+    /// Have the server step one of its standard patterns in-process; 0 or
+    /// an empty pattern picks the server's default.
+    pub async fn run_benchmark(&mut self, pattern: String, generations: i32, size: i32) -> Result<BenchmarkResponse> {
+        let client = self.get_client()?;
+        let request = Request::new(BenchmarkRequest { pattern, generations, width: size, height: size });
+        
+        let response = client.run_benchmark(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Storage statistics and the period of the simulation's live cells, or
+    /// of those in `region` alone.
+    pub async fn get_stats(&mut self, id: String, region: Option<Region>) -> Result<StatsResponse> {
+        let key = CacheKey::Stats(region.as_ref().map(|region| [region.x, region.y, region.width, region.height]));
+        if let Some(CachedResponse::Stats(stats)) = self.cached(&id, &key) {
+            return Ok(stats);
+        }
+        let version = self.cache_version(&id);
+        let client = self.get_client()?;
+        let request = Request::new(StatsRequest { id: id.clone(), region });
+        
+        let response = client.get_stats(request).await.map_err(ServerError::from)?.into_inner();
+        self.store_cached(&id, key, version, CachedResponse::Stats(response.clone()));
+        Ok(response)
+    }
+    
+    pub async fn get_census(&mut self, id: String) -> Result<CensusResponse> {
+        if let Some(CachedResponse::Census(census)) = self.cached(&id, &CacheKey::Census) {
+            return Ok(census);
+        }
+        let version = self.cache_version(&id);
+        let client = self.get_client()?;
+        let request = Request::new(CensusRequest { id: id.clone() });
+        
+        let response = client.get_census(request).await.map_err(ServerError::from)?.into_inner();
+        self.store_cached(&id, CacheKey::Census, version, CachedResponse::Census(response.clone()));
+        Ok(response)
+    }
+    
+    /// `seed` repeats the cells of a `random` initial pattern; without one the
+    /// server picks a seed and reports it in the response.
+    pub async fn create_simulation(&mut self, width: i32, height: i32, initial_pattern: Option<String>, seed: Option<u64>) -> Result<SimulationResponse> {
         let client = self.get_client()?;
         let request = Request::new(CreateSimulationRequest {
             width,
             height,
             initial_pattern: initial_pattern.unwrap_or_default(),
+            rule: String::new(),
+            turmite: String::new(),
+            turmites: vec![],
+            boundary_mode: BoundaryMode::Clip as i32,
+            config: None,
+            seed,
+            id: String::new(),
+        });
+        
+        let response = client.create_simulation(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Create an empty simulation under `id`, a slug such as `default`;
+    /// refused when another simulation has it.
+    pub async fn create_simulation_with_id(&mut self, id: String, width: i32, height: i32) -> Result<SimulationResponse> {
+        self.forget(&id);
+        let client = self.get_client()?;
+        let request = Request::new(CreateSimulationRequest {
+            width,
+            height,
+            boundary_mode: BoundaryMode::Clip as i32,
+            id,
+            ..Default::default()
         });
         
-        let response = client.create_simulation(request).await?;
+        let response = client.create_simulation(request).await.map_err(ServerError::from)?;
         Ok(response.into_inner())
     }
     
+    /// Fetch a simulation with all of its cells, paging through them with
+    /// GetCells when the grid is too dense for one response.
     pub async fn get_simulation(&mut self, id: String) -> Result<SimulationResponse> {
+        let key = CacheKey::Simulation(None);
+        if let Some(CachedResponse::Simulation(simulation)) = self.cached(&id, &key) {
+            return Ok(*simulation);
+        }
+        let client = self.get_client()?;
+        let request = Request::new(GetSimulationRequest { id: id.clone(), read_mask: None, generation: None });
+        
+        let mut simulation = client.get_simulation(request).await.map_err(ServerError::from)?.into_inner();
+        if simulation.truncated {
+            let (generation, cells) = self.get_all_cells(id).await?;
+            simulation.generation = generation;
+            simulation.cells = cells;
+            simulation.truncated = false;
+        } else {
+            // Paged cells may be from later than the version, so only whole responses are cached
+            self.store_cached(&id, key, None, CachedResponse::Simulation(Box::new(simulation.clone())));
+        }
+        Ok(simulation)
+    }
+    
+    /// One page of a simulation's cells in row-major order; pass the
+    /// previous page's `next_page_token` for the next, or an empty one to start.
+    pub async fn get_cells(&mut self, id: String, page_token: String) -> Result<GetCellsResponse> {
+        let client = self.get_client()?;
+        let request = Request::new(GetCellsRequest { id, page_size: 0, page_token });
+        
+        let response = client.get_cells(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Generation, population and bounding box, without any cells.
+    pub async fn get_summary(&mut self, id: String) -> Result<SummaryResponse> {
+        let client = self.get_client()?;
+        let request = Request::new(GetSummaryRequest { id });
+        
+        let response = client.get_summary(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Every cell of a simulation, page by page, and the generation they
+    /// were all read at.
+    pub async fn get_all_cells(&mut self, id: String) -> Result<(i64, Vec<Cell>)> {
+        let mut page = self.get_cells(id.clone(), String::new()).await?;
+        let generation = page.generation;
+        let mut cells = std::mem::take(&mut page.cells);
+        while !page.next_page_token.is_empty() {
+            page = self.get_cells(id.clone(), page.next_page_token).await?;
+            cells.append(&mut page.cells);
+        }
+        Ok((generation, cells))
+    }
+    
+    /// Fetch a simulation as it was at an earlier `generation`, rebuilt by the
+    /// server from its replay log.
+    pub async fn get_simulation_at(&mut self, id: String, generation: i64) -> Result<SimulationResponse> {
         let client = self.get_client()?;
-        let request = Request::new(GetSimulationRequest { id });
+        let request = Request::new(GetSimulationRequest { id, read_mask: None, generation: Some(generation) });
         
-        let response = client.get_simulation(request).await?;
+        let response = client.get_simulation(request).await.map_err(ServerError::from)?;
         Ok(response.into_inner())
     }
     
+    /// The range of generations `get_simulation_at` can fetch.
+    pub async fn get_history(&mut self, id: String) -> Result<HistoryResponse> {
+        let client = self.get_client()?;
+        let request = Request::new(HistoryRequest { id });
+        
+        let response = client.get_history(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Population, births and deaths for each step after `from` up to `to`;
+    /// unset bounds default to the range `get_history` reports.
+    pub async fn get_statistics(&mut self, id: String, from: Option<i64>, to: Option<i64>) -> Result<StatisticsResponse> {
+        let client = self.get_client()?;
+        let request = Request::new(StatisticsRequest { id, from_generation: from, to_generation: to });
+        
+        let response = client.get_statistics(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// How often each cell was alive over the last `generations` steps.
+    pub async fn get_heatmap(&mut self, id: String, generations: i32) -> Result<HeatmapResponse> {
+        let client = self.get_client()?;
+        let request = Request::new(HeatmapRequest { id, generations });
+        
+        let response = client.get_heatmap(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Run the server's invariant checks over `generations` generations;
+    /// every check when `checks` is empty.
+    pub async fn verify_simulation(&mut self, id: String, generations: i32, checks: Vec<String>) -> Result<VerifyResponse> {
+        let client = self.get_client()?;
+        let request = Request::new(VerifyRequest { id, generations, checks });
+        
+        let response = client.verify_simulation(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Fetch only the named SimulationResponse fields, e.g. `["generation", "live_cells"]`
+    /// to poll progress without transferring the cell list.
+    pub async fn get_simulation_fields(&mut self, id: String, fields: &[&str]) -> Result<SimulationResponse> {
+        let mut names: Vec<String> = fields.iter().map(|field| field.to_string()).collect();
+        names.sort();
+        let key = CacheKey::Simulation(Some(names));
+        if let Some(CachedResponse::Simulation(simulation)) = self.cached(&id, &key) {
+            return Ok(*simulation);
+        }
+        let client = self.get_client()?;
+        // The version says which state the fields are from, for the cache
+        let request = Request::new(GetSimulationRequest {
+            id: id.clone(),
+            read_mask: Some(field_mask(fields.iter().copied().chain(["version"]))),
+            generation: None,
+        });
+        
+        let response = client.get_simulation(request).await.map_err(ServerError::from)?.into_inner();
+        self.store_cached(&id, key, None, CachedResponse::Simulation(Box::new(response.clone())));
+        Ok(response)
+    }
+    
     pub async fn update_simulation(&mut self, id: String, generation: Option<i64>, cells: Option<Vec<Cell>>) -> Result<SimulationResponse> {
+        self.forget(&id);
         let client = self.get_client()?;
+        // Only the fields passed in are applied; the rest are left as they are
+        let mut fields = Vec::new();
+        if generation.is_some() {
+            fields.push("generation");
+        }
+        if cells.is_some() {
+            fields.push("cells");
+        }
         let request = Request::new(UpdateSimulationRequest {
             id,
             generation: generation.unwrap_or(0),
             cells: cells.unwrap_or_default(),
+            update_mask: Some(field_mask(fields)),
+            rule: String::new(),
+            boundary_mode: BoundaryMode::Clip as i32,
+            operations: Vec::new(),
+            expected_generation: None,
+            expected_version: None,
+        });
+        
+        let response = client.update_simulation(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Switch a simulation to another rule; empty means B3/S23.
+    pub async fn set_rule(&mut self, id: String, rule: String) -> Result<SimulationResponse> {
+        self.forget(&id);
+        let client = self.get_client()?;
+        let request = Request::new(UpdateSimulationRequest {
+            id,
+            generation: 0,
+            cells: Vec::new(),
+            update_mask: Some(field_mask(["rule"])),
+            rule,
+            boundary_mode: BoundaryMode::Clip as i32,
+            operations: Vec::new(),
+            expected_generation: None,
+            expected_version: None,
+        });
+        
+        let response = client.update_simulation(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    pub async fn set_boundary(&mut self, id: String, boundary: BoundaryMode) -> Result<SimulationResponse> {
+        self.forget(&id);
+        let client = self.get_client()?;
+        let request = Request::new(UpdateSimulationRequest {
+            id,
+            generation: 0,
+            cells: Vec::new(),
+            update_mask: Some(field_mask(["boundary_mode"])),
+            rule: String::new(),
+            boundary_mode: boundary as i32,
+            operations: Vec::new(),
+            expected_generation: None,
+            expected_version: None,
         });
         
-        let response = client.update_simulation(request).await?;
+        let response = client.update_simulation(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Cap a simulation at `generations_per_second`, or run it in turbo mode with `None`.
+    pub async fn set_pacing(&mut self, id: String, generations_per_second: Option<f64>) -> Result<PacingResponse> {
+        self.forget(&id);
+        let client = self.get_client()?;
+        let request = Request::new(SetPacingRequest {
+            id,
+            target_generations_per_second: generations_per_second.unwrap_or(0.0),
+            turbo: generations_per_second.is_none(),
+            expected_version: None,
+        });
+        
+        let response = client.set_pacing(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Replace a simulation's webhooks; an empty list removes them.
+    pub async fn set_webhooks(&mut self, id: String, webhooks: Vec<Webhook>) -> Result<WebhooksResponse> {
+        self.forget(&id);
+        let client = self.get_client()?;
+        let request = Request::new(SetWebhooksRequest { id, webhooks, expected_version: None });
+        
+        let response = client.set_webhooks(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Replace a simulation's breakpoints; an empty list removes them.
+    pub async fn set_breakpoints(&mut self, id: String, breakpoints: Vec<Breakpoint>) -> Result<BreakpointsResponse> {
+        self.forget(&id);
+        let client = self.get_client()?;
+        let request = Request::new(SetBreakpointsRequest { id, breakpoints, expected_version: None });
+        
+        let response = client.set_breakpoints(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Replace a simulation's watchpoints; an empty list removes them.
+    pub async fn set_watchpoints(&mut self, id: String, watchpoints: Vec<Watchpoint>) -> Result<WatchpointsResponse> {
+        self.forget(&id);
+        let client = self.get_client()?;
+        let request = Request::new(SetWatchpointsRequest { id, watchpoints, expected_version: None });
+        
+        let response = client.set_watchpoints(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Switch a simulation to `rule` on reaching `at_generation`, or now
+    /// without one; an empty rule cancels the change at `at_generation`.
+    pub async fn schedule_rule(&mut self, id: String, rule: String, at_generation: Option<u64>) -> Result<SetRuleResponse> {
+        self.forget(&id);
+        let client = self.get_client()?;
+        let request = Request::new(SetRuleRequest { id, rule, at_generation, expected_version: None });
+        
+        let response = client.set_rule(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Replace a simulation's annotations, refused unless it is still at
+    /// `expected_version` when given.
+    pub async fn set_annotations(&mut self, id: String, annotations: Vec<Annotation>, expected_version: Option<u64>) -> Result<AnnotationsResponse> {
+        self.forget(&id);
+        let client = self.get_client()?;
+        let request = Request::new(SetAnnotationsRequest { id, annotations, expected_version });
+        
+        let response = client.set_annotations(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Every annotation of a simulation.
+    pub async fn get_annotations(&mut self, id: String) -> Result<AnnotationsResponse> {
+        if let Some(CachedResponse::Annotations(annotations)) = self.cached(&id, &CacheKey::Annotations) {
+            return Ok(annotations);
+        }
+        let client = self.get_client()?;
+        let request = Request::new(GetAnnotationsRequest { id: id.clone(), region: None });
+        
+        let response = client.get_annotations(request).await.map_err(ServerError::from)?.into_inner();
+        self.store_cached(&id, CacheKey::Annotations, None, CachedResponse::Annotations(response.clone()));
+        Ok(response)
+    }
+    
+    /// Save the simulation's current state as `name`, replacing any
+    /// checkpoint already called that.
+    pub async fn create_checkpoint(&mut self, id: String, name: String) -> Result<CheckpointsResponse> {
+        self.forget(&id);
+        let client = self.get_client()?;
+        let request = Request::new(CreateCheckpointRequest { id, name, expected_version: None });
+        
+        let response = client.create_checkpoint(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    pub async fn list_checkpoints(&mut self, id: String) -> Result<CheckpointsResponse> {
+        let client = self.get_client()?;
+        let request = Request::new(ListCheckpointsRequest { id });
+        
+        let response = client.list_checkpoints(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    pub async fn delete_checkpoint(&mut self, id: String, name: String) -> Result<CheckpointsResponse> {
+        self.forget(&id);
+        let client = self.get_client()?;
+        let request = Request::new(DeleteCheckpointRequest { id, name, expected_version: None });
+        
+        let response = client.delete_checkpoint(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Roll the simulation back to its checkpoint called `name`.
+    pub async fn restore_checkpoint(&mut self, id: String, name: String) -> Result<SimulationResponse> {
+        self.forget(&id);
+        let client = self.get_client()?;
+        let request = Request::new(RestoreCheckpointRequest { id, name, expected_version: None });
+        
+        let response = client.restore_checkpoint(request).await.map_err(ServerError::from)?;
         Ok(response.into_inner())
     }
     
     pub async fn delete_simulation(&mut self, id: String) -> Result<DeleteResponse> {
+        self.forget(&id);
+        let client = self.get_client()?;
+        let request = Request::new(DeleteSimulationRequest { id, expected_version: None });
+        
+        let response = client.delete_simulation(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    pub async fn list_simulations(&mut self) -> Result<ListSimulationsResponse> {
+        let client = self.get_client()?;
+        let request = Request::new(ListSimulationsRequest {});
+        
+        let response = client.list_simulations(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Copy a simulation's current state into a new one, optionally named.
+    pub async fn clone_simulation(&mut self, id: String, name: String) -> Result<SimulationResponse> {
+        let client = self.get_client()?;
+        let request = Request::new(CloneSimulationRequest { id, name });
+        
+        let response = client.clone_simulation(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Name a simulation; an empty name clears it.
+    pub async fn rename_simulation(&mut self, id: String, name: String) -> Result<SimulationResponse> {
+        self.forget(&id);
+        let client = self.get_client()?;
+        let request = Request::new(RenameSimulationRequest { id, name, expected_version: None });
+        
+        let response = client.rename_simulation(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Rebuild a simulation from its operation log. `up_to` limits the replay
+    /// to the first N entries; `0` replays the whole log.
+    pub async fn replay_simulation(&mut self, id: String, up_to: i32, include_entries: bool) -> Result<ReplayResponse> {
+        let client = self.get_client()?;
+        let request = Request::new(ReplayRequest { id, up_to, include_entries });
+        
+        let response = client.replay_simulation(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Compare the live cells of two simulation states. A side with a
+    /// generation is rebuilt from that simulation's replay log; without one
+    /// it is the current state.
+    pub async fn get_diff(&mut self, a: (String, Option<i64>), b: (String, Option<i64>)) -> Result<DiffResponse> {
+        let client = self.get_client()?;
+        let target = |(id, generation): (String, Option<i64>)| Some(DiffTarget { id, generation });
+        let request = Request::new(DiffRequest { a: target(a), b: target(b) });
+        
+        let response = client.get_diff(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Queue runs of a pattern on the server's scratch grids.
+    pub async fn submit_batch(&mut self, spec: BatchSpec) -> Result<BatchStatus> {
         let client = self.get_client()?;
-        let request = Request::new(DeleteSimulationRequest { id });
         
-        let response = client.delete_simulation(request).await?;
+        let response = client.submit_batch(Request::new(spec)).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    pub async fn list_batches(&mut self) -> Result<ListBatchesResponse> {
+        let client = self.get_client()?;
+        let request = Request::new(ListBatchesRequest {});
+        
+        let response = client.list_batches(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    pub async fn get_batch_results(&mut self, batch_id: String) -> Result<BatchResultsResponse> {
+        let client = self.get_client()?;
+        let request = Request::new(BatchResultsRequest { batch_id });
+        
+        let response = client.get_batch_results(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    pub async fn cancel_batch(&mut self, batch_id: String) -> Result<BatchStatus> {
+        let client = self.get_client()?;
+        let request = Request::new(CancelBatchRequest { batch_id });
+        
+        let response = client.cancel_batch(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Fork a simulation into a control and `variants`, stepped side by
+    /// side for `generations` on the server's scratch grids.
+    pub async fn run_experiment(&mut self, id: String, variants: Vec<ExperimentVariant>, generations: i64) -> Result<ExperimentResponse> {
+        let client = self.get_client()?;
+        let request = Request::new(ExperimentRequest { id, variants, generations });
+        
+        let response = client.run_experiment(request).await.map_err(ServerError::from)?;
         Ok(response.into_inner())
     }
     
     pub async fn step_simulation(&mut self, id: String, steps: i32) -> Result<StepResponse> {
+        self.forget(&id);
+        let client = self.get_client()?;
+        let request = Request::new(StepSimulationRequest { id, steps, expected_version: None });
+        
+        let response = client.step_simulation(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Progress of the background job a large StepSimulation started.
+    pub async fn get_job_status(&mut self, job_id: String) -> Result<JobStatusResponse> {
         let client = self.get_client()?;
-        let request = Request::new(StepSimulationRequest { id, steps });
+        let request = Request::new(JobStatusRequest { job_id });
         
-        let response = client.step_simulation(request).await?;
+        let response = client.get_job_status(request).await.map_err(ServerError::from)?;
         Ok(response.into_inner())
     }
     
     pub async fn load_pattern(&mut self, id: String, pattern: Pattern, position: Position) -> Result<LoadPatternResponse> {
+        self.forget(&id);
         let client = self.get_client()?;
         let request = Request::new(LoadPatternRequest {
             id,
             pattern: Some(pattern),
             position: Some(position),
+            apgcode: String::new(),
+            overwrite: OverwritePolicy::Skip as i32,
+            expected_version: None,
         });
         
-        let response = client.load_pattern(request).await?;
+        let response = client.load_pattern(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Load an object by its apgcode (e.g. `xq4_153`); the server decodes it.
+    pub async fn load_apgcode(&mut self, id: String, apgcode: String, position: Position) -> Result<LoadPatternResponse> {
+        self.forget(&id);
+        let client = self.get_client()?;
+        let request = Request::new(LoadPatternRequest {
+            id,
+            pattern: None,
+            position: Some(position),
+            apgcode,
+            overwrite: OverwritePolicy::Skip as i32,
+            expected_version: None,
+        });
+        
+        let response = client.load_pattern(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Stream a pattern too large for a single LoadPattern message, in chunks of
+    /// [`UPLOAD_CHUNK_CELLS`] cells.
+    pub async fn upload_pattern(&mut self, id: String, name: String, position: Position, cells: Vec<Position>) -> Result<LoadPatternResponse> {
+        self.forget(&id);
+        let chunks: Vec<PatternChunk> = cells.chunks(UPLOAD_CHUNK_CELLS)
+            .map(|cells| PatternChunk { cells: cells.to_vec(), ..Default::default() })
+            .collect();
+        self.send_upload(id, name, position, chunks).await
+    }
+    
+    /// Stream an RLE document; the server decodes it as the blocks arrive.
+    pub async fn upload_rle(&mut self, id: String, name: String, position: Position, rle: &str) -> Result<LoadPatternResponse> {
+        self.forget(&id);
+        let chunks: Vec<PatternChunk> = rle_blocks(rle, UPLOAD_CHUNK_RLE_BYTES).into_iter()
+            .map(|rle| PatternChunk { rle, ..Default::default() })
+            .collect();
+        self.send_upload(id, name, position, chunks).await
+    }
+    
+    /// Stream a Golly macrocell document; the server expands it once the last
+    /// block arrives.
+    pub async fn upload_macrocell(&mut self, id: String, name: String, position: Position, macrocell: &str) -> Result<LoadPatternResponse> {
+        self.forget(&id);
+        let chunks: Vec<PatternChunk> = rle_blocks(macrocell, UPLOAD_CHUNK_RLE_BYTES).into_iter()
+            .map(|macrocell| PatternChunk { macrocell, ..Default::default() })
+            .collect();
+        self.send_upload(id, name, position, chunks).await
+    }
+    
+    /// Fetch a simulation's live cells encoded as RLE or macrocell text.
+    pub async fn export_pattern(&mut self, id: String, format: PatternFormat) -> Result<ExportPatternResponse> {
+        let client = self.get_client()?;
+        let request = Request::new(ExportPatternRequest { id, format: format as i32 });
+        
+        let response = client.export_pattern(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    async fn send_upload(&mut self, id: String, name: String, position: Position, mut chunks: Vec<PatternChunk>) -> Result<LoadPatternResponse> {
+        let client = self.get_client()?;
+        if chunks.is_empty() {
+            chunks.push(PatternChunk::default());
+        }
+        // Only the first chunk carries the target and placement
+        chunks[0].id = id;
+        chunks[0].name = name;
+        chunks[0].position = Some(position);
+        
+        let response = client.upload_pattern(tokio_stream::iter(chunks)).await.map_err(ServerError::from)?;
         Ok(response.into_inner())
     }
     
@@ -148,7 +926,17 @@ impl GameOfLifeClient {
             step_interval_ms,
         });
         
-        let response = client.stream_simulation(request).await?;
+        let response = client.stream_simulation(request).await.map_err(ServerError::from)?;
+        Ok(response.into_inner())
+    }
+    
+    /// Lifecycle events of the simulations in `ids`, or of every simulation
+    /// when it is empty.
+    pub async fn watch_events(&mut self, ids: Vec<String>) -> Result<tonic::Streaming<SimulationEvent>> {
+        let client = self.get_client()?;
+        let request = Request::new(WatchEventsRequest { ids });
+        
+        let response = client.watch_events(request).await.map_err(ServerError::from)?;
         Ok(response.into_inner())
     }
 }
\ No newline at end of file
@@ -0,0 +1,85 @@
+use anyhow::Result;
+use std::path::Path;
+use crate::client::GameOfLifeClient;
+use crate::client::game_of_life::{BatchSpec, BatchStatus, EndReason, JobState};
+use super::convert::{decode, encode, FileFormat};
+
+/// The `pattern` a batch sends: the text of an `.rle` file, a `.json`,
+/// `.cells` or `.mc` file (such as a `pattern random` soup) as RLE, else the
+/// name or apgcode as given for the server to resolve.
+pub fn batch_pattern(pattern: &str) -> Result<String> {
+    if pattern.ends_with(".rle") && Path::new(pattern).is_file() {
+        return Ok(std::fs::read_to_string(pattern)?);
+    }
+    if let Ok(format) = FileFormat::from_path(pattern) {
+        if Path::new(pattern).is_file() {
+            let document = decode(format, &std::fs::read_to_string(pattern)?)?;
+            return encode(FileFormat::Rle, &document);
+        }
+    }
+    Ok(pattern.to_string())
+}
+
+pub async fn submit(client: &mut GameOfLifeClient, spec: BatchSpec) -> Result<()> {
+    client.connect().await?;
+    let status = client.submit_batch(spec).await?;
+    println!("Submitted batch {} ({} runs)", status.batch_id, status.runs_total);
+    Ok(())
+}
+
+pub async fn list(client: &mut GameOfLifeClient) -> Result<()> {
+    client.connect().await?;
+    let response = client.list_batches().await?;
+    if response.batches.is_empty() {
+        println!("No batches");
+    }
+    for batch in &response.batches {
+        println!("{}", format_status(batch));
+    }
+    Ok(())
+}
+
+pub async fn results(client: &mut GameOfLifeClient, batch_id: &str) -> Result<()> {
+    client.connect().await?;
+    let response = client.get_batch_results(batch_id.to_string()).await?;
+    if let Some(status) = &response.status {
+        println!("{}", format_status(status));
+    }
+    println!("{:>5} {:>20} {:>8} {:>10} {:>10}  {:>10}  end", "run", "seed", "gens", "final", "peak", "ms");
+    for result in &response.results {
+        let end = match result.reason() {
+            EndReason::Extinct => "died out".to_string(),
+            EndReason::Cycle => format!("period {}", result.period),
+            _ => "-".to_string(),
+        };
+        println!(
+            "{:>5} {:>20} {:>8} {:>10} {:>10}  {:>10.1}  {}",
+            result.run, result.seed, result.generations, result.final_population, result.peak_population, result.elapsed_ms, end,
+        );
+    }
+    Ok(())
+}
+
+pub async fn cancel(client: &mut GameOfLifeClient, batch_id: &str) -> Result<()> {
+    client.connect().await?;
+    let status = client.cancel_batch(batch_id.to_string()).await?;
+    println!("Cancelling batch {} ({} of {} runs finished)", status.batch_id, status.runs_completed, status.runs_total);
+    Ok(())
+}
+
+/// One line describing a batch's progress.
+fn format_status(status: &BatchStatus) -> String {
+    let state = match status.state() {
+        JobState::JobPending => "pending",
+        JobState::JobRunning => "running",
+        JobState::JobCompleted => "completed",
+        JobState::JobCancelled => "cancelled",
+        JobState::JobFailed => "failed",
+    };
+    let name = if status.name.is_empty() { "-" } else { status.name.as_str() };
+    let mut line = format!("{}  {:<16} {:<9} {}/{} runs", status.batch_id, name, state, status.runs_completed, status.runs_total);
+    if !status.message.is_empty() {
+        line.push_str(&format!("  ({})", status.message));
+    }
+    line
+}
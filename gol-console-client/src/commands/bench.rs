@@ -0,0 +1,181 @@
+use anyhow::Result;
+use prost::Message;
+use std::fmt;
+use std::io::Write;
+use std::time::{Duration, Instant};
+use crate::client::GameOfLifeClient;
+use crate::client::game_of_life::BenchmarkResponse;
+use super::pattern::{is_apgcode, PatternCommands};
+
+/// What to run: a pattern stepped through a number of generations on a
+/// fresh simulation, in StepSimulation calls of `batch` generations.
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    pub pattern: String,
+    pub generations: u32,
+    pub batch: u32,
+    pub size: i32,
+    /// Time the stepping inside the server with RunBenchmark, leaving out
+    /// the network.
+    pub in_process: bool,
+}
+
+/// Measurements of one benchmark run against one backend.
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub backend: String,
+    pub address: String,
+    pub implementation: String,
+    pub pattern: String,
+    pub generations: u64,
+    /// Time spent inside StepSimulation calls.
+    pub step_time: Duration,
+    /// Round trip of each StepSimulation call, sorted.
+    pub latencies: Vec<Duration>,
+    pub final_population: i64,
+    /// Encoded size of the largest StepResponse.
+    pub step_bytes: usize,
+    /// Encoded size of a full GetSimulation at the first and last generation.
+    pub first_state_bytes: usize,
+    pub last_state_bytes: usize,
+    /// The last GetSimulation compressed with gzip and with zstd at their
+    /// default levels, as the server would send it to a client accepting them.
+    pub last_state_gzip_bytes: usize,
+    pub last_state_zstd_bytes: usize,
+    /// Compression the benchmark's own calls used, e.g. `zstd`, or `none`.
+    pub compression: String,
+}
+
+impl BenchReport {
+    pub fn generations_per_second(&self) -> f64 {
+        self.generations as f64 / self.step_time.as_secs_f64().max(f64::EPSILON)
+    }
+    
+    /// Round trip at `percentile` (0 to 100), nearest rank.
+    pub fn latency_percentile(&self, percentile: f64) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank = (percentile / 100.0 * self.latencies.len() as f64).ceil() as usize;
+        self.latencies[rank.clamp(1, self.latencies.len()) - 1]
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        let mean = self.latencies.iter().sum::<Duration>() / self.latencies.len().max(1) as u32;
+        writeln!(f, "Backend:      {} ({}) at {}, {} compression", self.backend, self.implementation, self.address, self.compression)?;
+        writeln!(f, "Pattern:      {}, {} generations, {} cells at the end", self.pattern, self.generations, self.final_population)?;
+        writeln!(f, "Throughput:   {:.1} gen/s ({:.3} s stepping)", self.generations_per_second(), self.step_time.as_secs_f64())?;
+        writeln!(
+            f,
+            "Step latency: min {:.2} ms, p50 {:.2} ms, p90 {:.2} ms, p99 {:.2} ms, max {:.2} ms, mean {:.2} ms over {} calls",
+            millis(self.latency_percentile(0.0)),
+            millis(self.latency_percentile(50.0)),
+            millis(self.latency_percentile(90.0)),
+            millis(self.latency_percentile(99.0)),
+            millis(self.latency_percentile(100.0)),
+            millis(mean),
+            self.latencies.len(),
+        )?;
+        writeln!(
+            f,
+            "Payloads:     StepResponse up to {} B, GetSimulation {} B at the start, {} B at the end",
+            self.step_bytes, self.first_state_bytes, self.last_state_bytes,
+        )?;
+        let ratio = |compressed: usize| self.last_state_bytes as f64 / compressed.max(1) as f64;
+        write!(
+            f,
+            "Compression:  last GetSimulation {} B gzip ({:.1}x), {} B zstd ({:.1}x)",
+            self.last_state_gzip_bytes, ratio(self.last_state_gzip_bytes),
+            self.last_state_zstd_bytes, ratio(self.last_state_zstd_bytes),
+        )
+    }
+}
+
+/// Report of a RunBenchmark call, laid out like [`BenchReport`].
+pub fn format_server_benchmark(client: &GameOfLifeClient, response: &BenchmarkResponse) -> String {
+    format!(
+        "Backend:      {} ({}) at {}:{}, in-process\n\
+         Pattern:      {}, {} generations, {} cells at the end, {} at most\n\
+         Throughput:   {:.1} gen/s, {:.0} cell updates/s ({:.3} s stepping)\n\
+         Memory:       up to {} B of cell storage",
+        client.backend, response.implementation, client.host, client.port,
+        response.pattern, response.generations, response.final_population, response.peak_population,
+        response.generations_per_second, response.cell_updates_per_second, response.elapsed_ms / 1000.0,
+        response.peak_memory_bytes,
+    )
+}
+
+/// Load the pattern into a new simulation, step it and measure, then
+/// delete the simulation again.
+pub async fn run_benchmark(client: &mut GameOfLifeClient, pattern_file: &str, options: &BenchOptions) -> Result<BenchReport> {
+    client.connect().await?;
+    let status = client.get_status().await?;
+    let simulation = client.create_simulation(options.size, options.size, None, None).await?;
+    let id = simulation.id;
+    
+    let result = measure(client, &id, pattern_file, options).await;
+    client.delete_simulation(id).await?;
+    let mut report = result?;
+    report.implementation = status.implementation;
+    Ok(report)
+}
+
+async fn measure(client: &mut GameOfLifeClient, id: &str, pattern_file: &str, options: &BenchOptions) -> Result<BenchReport> {
+    let mut pattern_commands = PatternCommands::new(client.clone());
+    if is_apgcode(pattern_file) {
+        pattern_commands.load_apgcode(id.to_string(), pattern_file, 0, 0).await?;
+    } else {
+        pattern_commands.load_from_file(id.to_string(), pattern_file, 0, 0).await?;
+    }
+    let first_state_bytes = client.get_simulation(id.to_string()).await?.encoded_len();
+    
+    let batch = options.batch.max(1);
+    let mut remaining = options.generations;
+    let mut latencies = Vec::new();
+    let mut step_bytes = 0;
+    let mut final_population = 0;
+    while remaining > 0 {
+        let steps = remaining.min(batch);
+        let started = Instant::now();
+        let response = client.step_simulation(id.to_string(), steps as i32).await?;
+        latencies.push(started.elapsed());
+        if !response.job_id.is_empty() {
+            anyhow::bail!("The server ran {} steps as a background job; use a smaller --batch", steps);
+        }
+        step_bytes = step_bytes.max(response.encoded_len());
+        final_population = response.live_cells;
+        remaining -= steps;
+        if response.completed {
+            break;
+        }
+    }
+    let last_state = client.get_simulation(id.to_string()).await?.encode_to_vec();
+    
+    let step_time = latencies.iter().sum();
+    latencies.sort();
+    Ok(BenchReport {
+        backend: client.backend.clone(),
+        address: format!("{}:{}", client.host, client.port),
+        implementation: String::new(),
+        pattern: options.pattern.clone(),
+        generations: (options.generations - remaining) as u64,
+        step_time,
+        latencies,
+        final_population,
+        step_bytes,
+        first_state_bytes,
+        last_state_bytes: last_state.len(),
+        last_state_gzip_bytes: gzip_len(&last_state)?,
+        last_state_zstd_bytes: zstd::bulk::compress(&last_state, zstd::DEFAULT_COMPRESSION_LEVEL)?.len(),
+        compression: client.compression.map_or_else(|| "no".to_string(), |encoding| encoding.to_string()),
+    })
+}
+
+fn gzip_len(bytes: &[u8]) -> Result<usize> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(bytes)?;
+    Ok(encoder.finish()?.len())
+}
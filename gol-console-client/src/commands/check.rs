@@ -0,0 +1,104 @@
+use anyhow::Result;
+use std::collections::BTreeSet;
+use tonic::Code;
+use crate::client::{GameOfLifeClient, ServerError};
+use crate::client::game_of_life::{InvariantStatus, VerifyResponse};
+use super::pattern::{is_apgcode, PatternCommands};
+
+/// What one backend made of the pattern: its live cells after stepping and
+/// the invariant checks it ran from there.
+#[derive(Debug, Clone)]
+pub struct CheckReport {
+    pub backend: String,
+    pub address: String,
+    pub generation: i64,
+    pub live_cells: BTreeSet<(i64, i64)>,
+    /// `None` when the backend has no VerifySimulation.
+    pub verification: Option<VerifyResponse>,
+}
+
+/// Load the pattern into a new simulation, step it `generations` times,
+/// read its cells and verify it, then delete the simulation again.
+pub async fn check_backend(client: &mut GameOfLifeClient, pattern_file: &str, generations: u32, size: i32) -> Result<CheckReport> {
+    client.connect().await?;
+    let simulation = client.create_simulation(size, size, None, None).await?;
+    let id = simulation.id;
+    
+    let result = step_and_verify(client, &id, pattern_file, generations).await;
+    client.delete_simulation(id).await?;
+    result
+}
+
+async fn step_and_verify(client: &mut GameOfLifeClient, id: &str, pattern_file: &str, generations: u32) -> Result<CheckReport> {
+    let mut pattern_commands = PatternCommands::new(client.clone());
+    if is_apgcode(pattern_file) {
+        pattern_commands.load_apgcode(id.to_string(), pattern_file, 0, 0).await?;
+    } else {
+        pattern_commands.load_from_file(id.to_string(), pattern_file, 0, 0).await?;
+    }
+    if generations > 0 {
+        let response = client.step_simulation(id.to_string(), generations as i32).await?;
+        if !response.job_id.is_empty() {
+            anyhow::bail!("The server ran {} steps as a background job; check fewer generations", generations);
+        }
+    }
+    let (generation, cells) = client.get_all_cells(id.to_string()).await?;
+    let live_cells = cells.iter().filter(|cell| cell.alive).map(|cell| (cell.x, cell.y)).collect();
+    
+    let verification = match client.verify_simulation(id.to_string(), 0, Vec::new()).await {
+        Ok(response) => Some(response),
+        Err(e) if e.downcast_ref::<ServerError>().is_some_and(|error| error.code == Code::Unimplemented) => None,
+        Err(e) => return Err(e),
+    };
+    Ok(CheckReport {
+        backend: client.backend.clone(),
+        address: format!("{}:{}", client.host, client.port),
+        generation,
+        live_cells,
+        verification,
+    })
+}
+
+/// Lines describing every report against the first, and whether they all
+/// agree and passed their checks.
+pub fn compare_reports(reports: &[CheckReport]) -> (Vec<String>, bool) {
+    let mut lines = Vec::new();
+    let mut ok = true;
+    let Some(reference) = reports.first() else {
+        return (lines, ok);
+    };
+    for report in reports {
+        let agreement = if std::ptr::eq(report, reference) {
+            "reference".to_string()
+        } else if report.generation != reference.generation {
+            ok = false;
+            format!("MISMATCH: generation {} against {}", report.generation, reference.generation)
+        } else if report.live_cells != reference.live_cells {
+            ok = false;
+            let differing = report.live_cells.symmetric_difference(&reference.live_cells).count();
+            format!("MISMATCH: {} cells differ from {}", differing, reference.backend)
+        } else {
+            format!("matches {}", reference.backend)
+        };
+        lines.push(format!(
+            "{:<8} {:<16} generation {}, {} cells, {}",
+            report.backend, report.address, report.generation, report.live_cells.len(), agreement,
+        ));
+        
+        let Some(verification) = &report.verification else {
+            lines.push("         invariants not checked: server has no VerifySimulation".to_string());
+            continue;
+        };
+        ok &= verification.passed;
+        for result in &verification.results {
+            let status = match result.status() {
+                InvariantStatus::Passed => "pass",
+                InvariantStatus::Failed => "FAIL",
+                InvariantStatus::Skipped => "skip",
+                InvariantStatus::Unspecified => "?",
+            };
+            lines.push(format!("         {:<4} {:<12} {}", status, result.name, result.detail));
+        }
+    }
+    (lines, ok)
+}
@@ -1,8 +1,7 @@
 use anyhow::Result;
 use std::time::Duration;
 use tokio::time;
-use crate::client::GameOfLifeClient;
-use crate::client::game_of_life::SimulationUpdate;
+use crate::client::{ErrorCause, GameOfLifeClient, ServerError};
 
 pub struct ControlCommands {
     client: GameOfLifeClient,
@@ -41,12 +40,10 @@ impl ControlCommands {
         
         self.client.connect().await?;
         
-        let mut generation = 0;
         loop {
             match self.client.step_simulation(id.clone(), 1).await {
                 Ok(response) => {
-                    generation = response.generation;
-                    println!("Generation: {}, Live cells: {}", generation, response.live_cells);
+                    println!("Generation: {}, Live cells: {}", response.generation, response.live_cells);
                     
                     if response.live_cells == 0 {
                         println!("Simulation ended - no live cells remaining");
@@ -54,6 +51,12 @@ impl ControlCommands {
                     }
                 }
                 Err(e) => {
+                    // Rate limited: back off and keep playing rather than giving up
+                    if let Some(ServerError { cause: ErrorCause::QuotaExceeded { .. }, .. }) = e.downcast_ref() {
+                        println!("{}", e);
+                        time::sleep(self.auto_step_interval * 2).await;
+                        continue;
+                    }
                     println!("Error stepping simulation: {}", e);
                     break;
                 }
@@ -83,6 +86,10 @@ impl ControlCommands {
         while let Some(update) = stream.message().await? {
             println!("Generation: {}, Live cells: {}, Changed cells: {}", 
                      update.generation, update.live_cells, update.changed_cells.len());
+            for turmite in &update.turmites {
+                println!("  Turmite at ({}, {}) facing {}, state {}",
+                         turmite.x, turmite.y, format!("{:?}", turmite.heading()).to_lowercase(), turmite.state);
+            }
             
             if update.simulation_ended {
                 println!("Simulation ended - reached stable state");
@@ -117,7 +124,7 @@ impl ControlCommands {
         
         let _delete_response = self.client.delete_simulation(simulation_id).await?;
         
-        let new_sim = self.client.create_simulation(grid.width, grid.height, None).await?;
+        let new_sim = self.client.create_simulation(grid.width, grid.height, None, None).await?;
         
         self.current_simulation = Some(new_sim.id.clone());
         println!("Simulation reset. New ID: {}", new_sim.id);
@@ -0,0 +1,665 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use super::pattern::{PatternCell, PatternFile};
+
+/// Most cells a macrocell or RLE pattern may expand to; shared squares and
+/// run counts can describe far more than fit in memory.
+const MAX_EXPANDED_CELLS: usize = 10_000_000;
+
+/// Longest RLE line written, as Golly does.
+const RLE_LINE_WIDTH: usize = 70;
+
+/// Pattern file formats, told apart by extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    /// The patterns directory's `.json`.
+    Json,
+    Rle,
+    /// Plaintext `.cells`: `.` dead, `O` live.
+    Plaintext,
+    /// Golly `.mc`.
+    Macrocell,
+}
+
+impl FileFormat {
+    pub fn from_path(path: &str) -> Result<Self> {
+        let extension = Path::new(path).extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+        match extension.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "rle" => Ok(Self::Rle),
+            "cells" => Ok(Self::Plaintext),
+            "mc" => Ok(Self::Macrocell),
+            _ => anyhow::bail!("Unknown pattern format for {}; expected .json, .rle, .cells or .mc", path),
+        }
+    }
+}
+
+/// A pattern as every format can carry it. Formats without a field leave it
+/// empty; RLE, plaintext and macrocell keep metadata in comment lines.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PatternDocument {
+    pub name: String,
+    pub description: String,
+    pub author: String,
+    /// Rule the pattern was written for, e.g. `B3/S23`; JSON has none.
+    pub rule: Option<String>,
+    pub cells: Vec<(i64, i64)>,
+}
+
+impl PatternDocument {
+    /// Move the pattern so its bounding box starts at the origin.
+    pub fn normalize(&mut self) {
+        let min_x = self.cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let min_y = self.cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        for cell in &mut self.cells {
+            *cell = (cell.0 - min_x, cell.1 - min_y);
+        }
+    }
+}
+
+/// Read `input`, write it to `output` in the format its extension names,
+/// and report what was written. Patterns with no name take the input's.
+pub fn convert_file(input: &str, output: &str, normalize: bool) -> Result<()> {
+    let (from, to) = (FileFormat::from_path(input)?, FileFormat::from_path(output)?);
+    let text = fs::read_to_string(input).with_context(|| format!("Failed to read {}", input))?;
+    let mut pattern = decode(from, &text).with_context(|| format!("Failed to decode {}", input))?;
+    if pattern.name.is_empty() {
+        pattern.name = Path::new(input).file_stem().unwrap_or_default().to_string_lossy().to_string();
+    }
+    if normalize {
+        pattern.normalize();
+    }
+    fs::write(output, encode(to, &pattern)?).with_context(|| format!("Failed to write {}", output))?;
+    println!("Wrote {} ({} cells) to {}", pattern.name, pattern.cells.len(), output);
+    Ok(())
+}
+
+pub fn decode(format: FileFormat, text: &str) -> Result<PatternDocument> {
+    let mut pattern = match format {
+        FileFormat::Json => decode_json(text)?,
+        FileFormat::Rle => decode_rle(text)?,
+        FileFormat::Plaintext => decode_plaintext(text)?,
+        FileFormat::Macrocell => decode_macrocell(text)?,
+    };
+    pattern.cells.sort_unstable_by_key(|&(x, y)| (y, x));
+    pattern.cells.dedup();
+    Ok(pattern)
+}
+
+pub fn encode(format: FileFormat, pattern: &PatternDocument) -> Result<String> {
+    match format {
+        FileFormat::Json => encode_json(pattern),
+        FileFormat::Rle => Ok(encode_rle(pattern)),
+        FileFormat::Plaintext => Ok(encode_plaintext(pattern)),
+        FileFormat::Macrocell => Ok(encode_macrocell(pattern)),
+    }
+}
+
+fn decode_json(text: &str) -> Result<PatternDocument> {
+    let file: PatternFile = serde_json::from_str(text).context("Invalid JSON pattern; `pattern validate` shows what is wrong")?;
+    Ok(PatternDocument {
+        name: file.name,
+        description: file.description,
+        author: file.author,
+        rule: None,
+        cells: file.cells.iter().map(|cell| (cell.x, cell.y)).collect(),
+    })
+}
+
+fn encode_json(pattern: &PatternDocument) -> Result<String> {
+    let file = PatternFile {
+        name: pattern.name.clone(),
+        description: pattern.description.clone(),
+        author: pattern.author.clone(),
+        cells: pattern.cells.iter().map(|&(x, y)| PatternCell { x, y }).collect(),
+    };
+    let mut text = serde_json::to_string_pretty(&file)?;
+    text.push('\n');
+    Ok(text)
+}
+
+/// Append a comment line's text to the description, a space apart.
+fn describe(pattern: &mut PatternDocument, text: &str) {
+    let text = text.trim();
+    if text.is_empty() {
+        return;
+    }
+    if !pattern.description.is_empty() {
+        pattern.description.push(' ');
+    }
+    pattern.description.push_str(text);
+}
+
+/// Live cells of an RLE body, and whether it reached its closing `!`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RleBody {
+    pub cells: Vec<(i64, i64)>,
+    pub ended: bool,
+}
+
+/// Why an RLE body could not be decoded, at a 1-based line and column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RleError {
+    pub line: usize,
+    /// Unset when the body ended part way through an item.
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for RleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.column {
+            Some(column) => write!(f, "line {}, column {}: {}", self.line, column, self.message),
+            None => write!(f, "line {}: {}", self.line, self.message),
+        }
+    }
+}
+
+impl std::error::Error for RleError {}
+
+/// Decode an RLE pattern: its `#` lines, its `x = .., y = ..` header and
+/// the body after it.
+pub fn decode_rle(text: &str) -> Result<PatternDocument> {
+    let mut pattern = PatternDocument::default();
+    let mut lines = text.lines().enumerate().map(|(index, line)| (index + 1, line));
+    for (_, line) in lines.by_ref() {
+        let line = line.trim();
+        if let Some(comment) = line.strip_prefix('#') {
+            let (tag, rest) = comment.split_at(comment.len().min(1));
+            match tag {
+                "N" => pattern.name = rest.trim().to_string(),
+                "O" => pattern.author = rest.trim().to_string(),
+                "C" | "c" => describe(&mut pattern, rest),
+                _ => {}
+            }
+        } else if line.starts_with('x') {
+            pattern.rule = line.split(',')
+                .filter_map(|part| part.split_once('='))
+                .find(|(key, _)| key.trim() == "rule")
+                .map(|(_, rule)| rule.trim().to_string());
+            break;
+        } else if !line.is_empty() {
+            anyhow::bail!("Missing the `x = <width>, y = <height>` header line");
+        }
+    }
+    
+    pattern.cells = decode_rle_body(lines).context("Invalid RLE")?.cells;
+    Ok(pattern)
+}
+
+/// Decode numbered lines of an RLE body: `b` or `.` dead, `o` or a state
+/// letter `A`-`X` (after an optional `p`-`y` prefix) alive, `$` ending a
+/// row, each optionally preceded by a run count, up to `!`. Refuses bodies
+/// that expand to more than [`MAX_EXPANDED_CELLS`] live cells or past the
+/// largest coordinate.
+pub fn decode_rle_body<'a>(lines: impl IntoIterator<Item = (usize, &'a str)>) -> Result<RleBody, RleError> {
+    let mut body = RleBody::default();
+    let (mut x, mut y) = (0i64, 0i64);
+    let mut run = None::<i64>;
+    // Run count of a multi-state prefix waiting for its state letter
+    let mut prefixed = None::<i64>;
+    let mut last_line = 0;
+    for (number, line) in lines {
+        last_line = number;
+        for (index, c) in line.chars().enumerate() {
+            let error = |message: String| RleError { line: number, column: Some(index + 1), message };
+            if c.is_whitespace() {
+                continue;
+            }
+            if c.is_ascii_digit() && prefixed.is_none() {
+                let digit = c as i64 - '0' as i64;
+                run = Some(run.unwrap_or(0).checked_mul(10).and_then(|run| run.checked_add(digit))
+                    .ok_or_else(|| error("run count too large".to_string()))?);
+                continue;
+            }
+            let count = match (prefixed.take(), run.take()) {
+                (Some(count), _) if c.is_ascii_uppercase() => count,
+                (Some(_), _) => return Err(error(format!("a state prefix must be followed by a letter A-X, not {:?}", c))),
+                (None, Some(0)) => return Err(error("run count 0; leave the tag out instead".to_string())),
+                (None, run) => run.unwrap_or(1),
+            };
+            let past_edge = || error("the pattern reaches past the largest coordinate".to_string());
+            match c {
+                'b' | '.' => x = x.checked_add(count).ok_or_else(past_edge)?,
+                'o' | 'A'..='X' => {
+                    let end = x.checked_add(count).ok_or_else(past_edge)?;
+                    if body.cells.len() as i64 > MAX_EXPANDED_CELLS as i64 - count {
+                        return Err(error(format!("the pattern has more than {} live cells", MAX_EXPANDED_CELLS)));
+                    }
+                    body.cells.extend((x..end).map(|x| (x, y)));
+                    x = end;
+                }
+                'p'..='y' => prefixed = Some(count),
+                '$' => {
+                    y = y.checked_add(count).ok_or_else(past_edge)?;
+                    x = 0;
+                }
+                '!' => {
+                    body.ended = true;
+                    return Ok(body);
+                }
+                other => return Err(error(format!(
+                    "{:?} is not an RLE tag; cells are b (dead) and o (live), rows end with $ and the pattern with !",
+                    other,
+                ))),
+            }
+        }
+    }
+    if prefixed.is_some() {
+        return Err(RleError { line: last_line, column: None, message: "a state prefix (p-y) is missing its letter A-X".to_string() });
+    }
+    Ok(body)
+}
+
+fn encode_rle(pattern: &PatternDocument) -> String {
+    let mut text = comment_lines(pattern, '#', "N ", "O ", "C ");
+    let Some((min_x, min_y, max_x, max_y)) = bounds(&pattern.cells) else {
+        text.push_str(&format!("x = 0, y = 0, rule = {}\n!\n", rule_name(pattern)));
+        return text;
+    };
+    text.push_str(&format!("x = {}, y = {}, rule = {}\n", max_x - min_x + 1, max_y - min_y + 1, rule_name(pattern)));
+    
+    let mut items = Vec::new();
+    let (mut x, mut y) = (min_x, min_y);
+    let mut live_run = 0;
+    for &(cell_x, cell_y) in &sorted(&pattern.cells) {
+        if cell_y != y || cell_x != x {
+            push_run(&mut items, live_run, 'o');
+            live_run = 0;
+        }
+        if cell_y != y {
+            push_run(&mut items, cell_y - y, '$');
+            (x, y) = (min_x, cell_y);
+        }
+        push_run(&mut items, cell_x - x, 'b');
+        live_run += 1;
+        x = cell_x + 1;
+    }
+    push_run(&mut items, live_run, 'o');
+    items.push("!".to_string());
+    
+    let mut line_len = 0;
+    for item in items {
+        if line_len + item.len() > RLE_LINE_WIDTH {
+            text.push('\n');
+            line_len = 0;
+        }
+        line_len += item.len();
+        text.push_str(&item);
+    }
+    text.push('\n');
+    text
+}
+
+fn push_run(items: &mut Vec<String>, run: i64, tag: char) {
+    if run > 0 {
+        items.push(if run == 1 { tag.to_string() } else { format!("{}{}", run, tag) });
+    }
+}
+
+fn decode_plaintext(text: &str) -> Result<PatternDocument> {
+    let mut pattern = PatternDocument::default();
+    let mut y = 0;
+    for line in text.lines() {
+        if let Some(comment) = line.strip_prefix('!') {
+            let comment = comment.trim();
+            if let Some(name) = comment.strip_prefix("Name:") {
+                pattern.name = name.trim().to_string();
+            } else if let Some(author) = comment.strip_prefix("Author:") {
+                pattern.author = author.trim().to_string();
+            } else {
+                describe(&mut pattern, comment);
+            }
+            continue;
+        }
+        for (x, c) in line.trim_end().chars().enumerate() {
+            match c {
+                '.' => {}
+                'O' | '*' => pattern.cells.push((x as i64, y)),
+                other => anyhow::bail!("Unexpected {:?} in row {} of the plaintext pattern", other, y + 1),
+            }
+        }
+        y += 1;
+    }
+    Ok(pattern)
+}
+
+fn encode_plaintext(pattern: &PatternDocument) -> String {
+    let mut text = comment_lines(pattern, '!', "Name: ", "Author: ", "");
+    let Some((min_x, min_y, _, max_y)) = bounds(&pattern.cells) else {
+        return text;
+    };
+    let mut rows = vec![String::new(); (max_y - min_y + 1) as usize];
+    for &(x, y) in &sorted(&pattern.cells) {
+        let row = &mut rows[(y - min_y) as usize];
+        let column = (x - min_x) as usize;
+        row.extend(std::iter::repeat_n('.', column - row.len()));
+        row.push('O');
+    }
+    for row in rows {
+        text.push_str(&row);
+        text.push('\n');
+    }
+    text
+}
+
+/// Nodes of a macrocell file: 8x8 leaves as row bitmasks, and squares of
+/// level 4 and up made of four numbered children, 0 for empty.
+#[derive(Debug, Clone)]
+enum MacrocellNode {
+    Leaf([u8; 8]),
+    /// Level 1, from multi-state files: the states of a 2x2 block.
+    Quad([bool; 4]),
+    Inner { level: u32, children: [usize; 4] },
+}
+
+fn decode_macrocell(text: &str) -> Result<PatternDocument> {
+    let mut pattern = PatternDocument::default();
+    let mut nodes = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('[') {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            let (tag, rest) = comment.split_at(comment.len().min(1));
+            match tag {
+                "R" => pattern.rule = Some(rest.trim().to_string()),
+                "N" => pattern.name = rest.trim().to_string(),
+                "O" => pattern.author = rest.trim().to_string(),
+                "C" => describe(&mut pattern, rest),
+                _ => {}
+            }
+            continue;
+        }
+        if !line.starts_with(|c: char| c.is_ascii_digit()) {
+            let mut rows = [0u8; 8];
+            let (mut x, mut y) = (0, 0);
+            for c in line.chars() {
+                match c {
+                    '.' => x += 1,
+                    '*' if x < 8 && y < 8 => {
+                        rows[y] |= 1 << x;
+                        x += 1;
+                    }
+                    '$' => (x, y) = (0, y + 1),
+                    _ => anyhow::bail!("Invalid macrocell leaf {:?}", line),
+                }
+            }
+            nodes.push(MacrocellNode::Leaf(rows));
+            continue;
+        }
+        let numbers: Vec<usize> = line.split_whitespace().map(str::parse).collect::<Result<_, _>>()
+            .with_context(|| format!("Invalid macrocell node {:?}", line))?;
+        let [level, nw, ne, sw, se] = numbers[..] else {
+            anyhow::bail!("Invalid macrocell node {:?}", line);
+        };
+        let children = [nw, ne, sw, se];
+        if children.iter().any(|&child| child > nodes.len()) || !(1..=62).contains(&level) || level == 3 {
+            anyhow::bail!("Invalid macrocell node {:?}", line);
+        }
+        nodes.push(if level == 1 {
+            MacrocellNode::Quad(children.map(|state| state != 0))
+        } else {
+            MacrocellNode::Inner { level: level as u32, children }
+        });
+    }
+    if !nodes.is_empty() {
+        expand(&nodes, nodes.len(), 0, 0, &mut pattern.cells)?;
+    }
+    pattern.normalize();
+    Ok(pattern)
+}
+
+/// Append the live cells of node `number` with its top-left corner at `(ox, oy)`.
+fn expand(nodes: &[MacrocellNode], number: usize, ox: i64, oy: i64, cells: &mut Vec<(i64, i64)>) -> Result<()> {
+    let Some(node) = number.checked_sub(1).and_then(|index| nodes.get(index)) else {
+        return Ok(());
+    };
+    match node {
+        MacrocellNode::Leaf(rows) => {
+            for (y, row) in rows.iter().enumerate() {
+                cells.extend((0..8).filter(|x| row & (1 << x) != 0).map(|x| (ox + x, oy + y as i64)));
+            }
+        }
+        MacrocellNode::Quad(states) => {
+            for (quadrant, _) in states.iter().enumerate().filter(|(_, alive)| **alive) {
+                cells.push((ox + (quadrant & 1) as i64, oy + (quadrant >> 1) as i64));
+            }
+        }
+        MacrocellNode::Inner { level, children } => {
+            let half = 1i64 << (level - 1);
+            for (quadrant, &child) in children.iter().enumerate() {
+                let cx = ox + if quadrant & 1 == 1 { half } else { 0 };
+                let cy = oy + if quadrant >= 2 { half } else { 0 };
+                expand(nodes, child, cx, cy, cells)?;
+            }
+        }
+    }
+    if cells.len() > MAX_EXPANDED_CELLS {
+        anyhow::bail!("The macrocell pattern has more than {} live cells", MAX_EXPANDED_CELLS);
+    }
+    Ok(())
+}
+
+fn encode_macrocell(pattern: &PatternDocument) -> String {
+    let mut text = format!("[M2] (gol-console-client)\n#R {}\n", rule_name(pattern));
+    text.push_str(&comment_lines(pattern, '#', "N ", "O ", "C "));
+    let Some((min_x, min_y, _, _)) = bounds(&pattern.cells) else {
+        return text;
+    };
+    
+    let mut blocks: HashMap<(i64, i64), [u8; 8]> = HashMap::new();
+    for &(x, y) in &pattern.cells {
+        let (x, y) = (x - min_x, y - min_y);
+        blocks.entry((x / 8, y / 8)).or_default()[(y % 8) as usize] |= 1 << (x % 8);
+    }
+    let extent = blocks.keys().map(|&(bx, by)| bx.max(by) + 1).max().unwrap_or(1);
+    let mut level = 3;
+    while (1i64 << (level - 3)) < extent {
+        level += 1;
+    }
+    
+    let mut encoder = MacrocellEncoder::default();
+    encoder.node(level, 0, 0, blocks.keys().copied().collect(), &blocks);
+    for line in encoder.lines {
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text
+}
+
+/// Writes each distinct square once, numbering nodes in the order written.
+#[derive(Default)]
+struct MacrocellEncoder {
+    lines: Vec<String>,
+    leaves: HashMap<[u8; 8], usize>,
+    inner: HashMap<(u32, [usize; 4]), usize>,
+}
+
+impl MacrocellEncoder {
+    /// Number of the node covering the square of `level` whose top-left
+    /// block is `(bx, by)`; `keys` are the non-empty blocks inside it.
+    fn node(&mut self, level: u32, bx: i64, by: i64, keys: Vec<(i64, i64)>, blocks: &HashMap<(i64, i64), [u8; 8]>) -> usize {
+        if keys.is_empty() {
+            return 0;
+        }
+        if level == 3 {
+            let rows = blocks[&(bx, by)];
+            if let Some(&number) = self.leaves.get(&rows) {
+                return number;
+            }
+            self.lines.push(leaf_line(&rows));
+            self.leaves.insert(rows, self.lines.len());
+            return self.lines.len();
+        }
+        
+        let half = 1i64 << (level - 4);
+        let mut quadrants: [Vec<(i64, i64)>; 4] = Default::default();
+        for (x, y) in keys {
+            quadrants[usize::from(x >= bx + half) + 2 * usize::from(y >= by + half)].push((x, y));
+        }
+        let mut children = [0; 4];
+        for (quadrant, keys) in quadrants.into_iter().enumerate() {
+            let cx = bx + if quadrant & 1 == 1 { half } else { 0 };
+            let cy = by + if quadrant >= 2 { half } else { 0 };
+            children[quadrant] = self.node(level - 1, cx, cy, keys, blocks);
+        }
+        if let Some(&number) = self.inner.get(&(level, children)) {
+            return number;
+        }
+        let [nw, ne, sw, se] = children;
+        self.lines.push(format!("{} {} {} {} {}", level, nw, ne, sw, se));
+        self.inner.insert((level, children), self.lines.len());
+        self.lines.len()
+    }
+}
+
+/// An 8x8 leaf as `.`/`*` rows ended by `$`, leaving out trailing dead cells
+/// and rows.
+fn leaf_line(rows: &[u8; 8]) -> String {
+    let used = rows.iter().rposition(|&row| row != 0).map_or(0, |last| last + 1);
+    rows[..used].iter()
+        .map(|&row| {
+            let width = 8 - row.leading_zeros() as usize;
+            let mut line: String = (0..width).map(|x| if row & (1 << x) != 0 { '*' } else { '.' }).collect();
+            line.push('$');
+            line
+        })
+        .collect()
+}
+
+/// Name, author and description as comment lines, skipping empty ones.
+fn comment_lines(pattern: &PatternDocument, marker: char, name: &str, author: &str, description: &str) -> String {
+    let mut text = String::new();
+    for (tag, value) in [(name, &pattern.name), (author, &pattern.author), (description, &pattern.description)] {
+        if !value.is_empty() {
+            text.push_str(&format!("{}{}{}\n", marker, tag, value));
+        }
+    }
+    text
+}
+
+fn rule_name(pattern: &PatternDocument) -> &str {
+    pattern.rule.as_deref().unwrap_or("B3/S23")
+}
+
+/// Live-cell bounds `(min_x, min_y, max_x, max_y)`, inclusive.
+fn bounds(cells: &[(i64, i64)]) -> Option<(i64, i64, i64, i64)> {
+    cells.iter().fold(None, |bounds, &(x, y)| Some(match bounds {
+        Some((min_x, min_y, max_x, max_y)) => (x.min(min_x), y.min(min_y), x.max(max_x), y.max(max_y)),
+        None => (x, y, x, y),
+    }))
+}
+
+/// Cells in row-major order.
+fn sorted(cells: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    let mut cells = cells.to_vec();
+    cells.sort_unstable_by_key(|&(x, y)| (y, x));
+    cells.dedup();
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn glider() -> PatternDocument {
+        PatternDocument {
+            name: "Glider".to_string(),
+            description: "The smallest spaceship".to_string(),
+            author: "Richard K. Guy".to_string(),
+            rule: Some("B3/S23".to_string()),
+            cells: vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)],
+        }
+    }
+    
+    #[test]
+    fn test_formats_round_trip() {
+        for format in [FileFormat::Json, FileFormat::Rle, FileFormat::Plaintext, FileFormat::Macrocell] {
+            let text = encode(format, &glider()).unwrap();
+            let decoded = decode(format, &text).unwrap();
+            assert_eq!(sorted(&decoded.cells), sorted(&glider().cells), "{:?}:\n{}", format, text);
+            assert_eq!(decoded.name, "Glider", "{:?}", format);
+            assert_eq!(decoded.author, "Richard K. Guy", "{:?}", format);
+        }
+        assert_eq!(decode(FileFormat::Rle, &encode(FileFormat::Rle, &glider()).unwrap()).unwrap(), glider());
+    }
+    
+    #[test]
+    fn test_macrocell_round_trips_spread_out_patterns() {
+        let pattern = PatternDocument { cells: vec![(0, 0), (7, 7), (8, 0), (100, 3), (3, 250)], ..Default::default() };
+        let decoded = decode(FileFormat::Macrocell, &encode(FileFormat::Macrocell, &pattern).unwrap()).unwrap();
+        assert_eq!(sorted(&decoded.cells), sorted(&pattern.cells));
+    }
+    
+    #[test]
+    fn test_rle_reads_runs_rows_and_states() {
+        let pattern = decode_rle("#N Blinker\nx = 3, y = 2, rule = B3/S23\n3o$\n2$bA!\n").unwrap();
+        assert_eq!(pattern.name, "Blinker");
+        assert_eq!(pattern.rule.as_deref(), Some("B3/S23"));
+        assert_eq!(pattern.cells, vec![(0, 0), (1, 0), (2, 0), (1, 3)]);
+        
+        let body = decode_rle_body([(1, "o pA !ignored")]).unwrap();
+        assert!(body.ended);
+        assert_eq!(body.cells, vec![(0, 0), (1, 0)]);
+        assert!(!decode_rle_body([(1, "2o$o")]).unwrap().ended);
+        
+        assert!(decode_rle("3o!").is_err());
+    }
+    
+    #[test]
+    fn test_rle_errors_say_where() {
+        let error = decode_rle_body([(1, "o$"), (2, "bz!")]).unwrap_err();
+        assert_eq!((error.line, error.column), (2, Some(2)));
+        assert!(error.message.contains("is not an RLE tag"), "{}", error);
+        
+        assert!(decode_rle_body([(1, "0o!")]).unwrap_err().message.contains("run count 0"));
+        assert!(decode_rle_body([(1, "pb!")]).unwrap_err().message.contains("state prefix"));
+        assert_eq!(decode_rle_body([(4, "2p")]).unwrap_err(), RleError {
+            line: 4,
+            column: None,
+            message: "a state prefix (p-y) is missing its letter A-X".to_string(),
+        });
+    }
+    
+    #[test]
+    fn test_rle_refuses_huge_or_overflowing_runs() {
+        let error = decode_rle_body([(1, "99999999999999999999o!")]).unwrap_err();
+        assert_eq!(error.message, "run count too large");
+        
+        let error = decode_rle_body([(1, "20000000o!")]).unwrap_err();
+        assert!(error.message.contains("more than 10000000 live cells"), "{}", error);
+        let error = decode_rle_body([(1, "6000000o$6000000o!")]).unwrap_err();
+        assert_eq!(error.column, Some(17));
+        
+        let error = decode_rle_body([(1, "9223372036854775807b2b!")]).unwrap_err();
+        assert!(error.message.contains("past the largest coordinate"), "{}", error);
+        assert!(decode_rle_body([(1, "9223372036854775807$$o!")]).is_err());
+    }
+    
+    #[test]
+    fn test_plaintext_rejects_unknown_characters() {
+        let pattern = decode_plaintext("!Name: Block\n!A still life\nOO\n**\n").unwrap();
+        assert_eq!(pattern.name, "Block");
+        assert_eq!(pattern.description, "A still life");
+        assert_eq!(pattern.cells, vec![(0, 0), (1, 0), (0, 1), (1, 1)]);
+        assert!(decode_plaintext("O#\n").is_err());
+    }
+    
+    #[test]
+    fn test_macrocell_refuses_huge_expansions() {
+        // A full leaf, then each level its four copies, up to 2^12 squared cells
+        let mut text = "[M2]\n".to_string();
+        text.push_str(&"********$".repeat(8));
+        text.push('\n');
+        for level in 4..=12 {
+            let child = level - 3;
+            text.push_str(&format!("{} {} {} {} {}\n", level, child, child, child, child));
+        }
+        let error = decode(FileFormat::Macrocell, &text).unwrap_err();
+        assert!(error.to_string().contains("more than"), "{}", error);
+    }
+}
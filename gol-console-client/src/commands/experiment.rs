@@ -0,0 +1,58 @@
+use anyhow::{Context, Result};
+use crate::client::GameOfLifeClient;
+use crate::client::game_of_life::{EndReason, ExperimentResult, ExperimentVariant};
+use super::batch::batch_pattern;
+
+/// Parse a variant given as `+`-joined parts: `rule=<rule>`,
+/// `soup=<density>[@seed]` and `pattern=<pattern>[@x,y]`, e.g.
+/// `rule=B36/S23+soup=0.3@7`. The variant is named after the text.
+pub fn parse_variant(text: &str) -> Result<ExperimentVariant> {
+    let mut variant = ExperimentVariant { name: text.to_string(), ..ExperimentVariant::default() };
+    for part in text.split('+') {
+        let (key, value) = part.split_once('=')
+            .with_context(|| format!("Expected rule=, soup= or pattern= in '{}'", part))?;
+        match key {
+            "rule" => variant.rule = value.to_string(),
+            "soup" => {
+                let (density, seed) = value.split_once('@').unwrap_or((value, "0"));
+                variant.soup_density = density.parse().with_context(|| format!("Invalid soup density '{}'", density))?;
+                variant.seed = seed.parse().with_context(|| format!("Invalid soup seed '{}'", seed))?;
+            }
+            "pattern" => {
+                let (pattern, position) = value.rsplit_once('@').unwrap_or((value, "0,0"));
+                let (x, y) = position.split_once(',')
+                    .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)))
+                    .with_context(|| format!("Invalid position '{}', expected x,y", position))?;
+                variant.pattern = batch_pattern(pattern)?;
+                (variant.x, variant.y) = (x, y);
+            }
+            other => anyhow::bail!("Unknown variant part '{}', expected rule, soup or pattern", other),
+        }
+    }
+    Ok(variant)
+}
+
+/// Run the experiment and print a row per copy, control first.
+pub async fn run(client: &mut GameOfLifeClient, simulation_id: &str, variants: Vec<ExperimentVariant>, generations: i64) -> Result<()> {
+    client.connect().await?;
+    let response = client.run_experiment(simulation_id.to_string(), variants, generations).await?;
+    println!("Forked {} at generation {}", response.id, response.generation);
+    println!("{:<24} {:<16} {:>8} {:>10} {:>10} {:>10} {:>10} {:>10}  end", "variant", "rule", "gens", "initial", "final", "peak", "mean", "vs control");
+    for result in response.control.iter().chain(&response.results) {
+        println!("{}", format_result(result));
+    }
+    Ok(())
+}
+
+fn format_result(result: &ExperimentResult) -> String {
+    let end = match result.reason() {
+        EndReason::Extinct => "died out".to_string(),
+        EndReason::Cycle => format!("period {}", result.period),
+        _ => "-".to_string(),
+    };
+    format!(
+        "{:<24} {:<16} {:>8} {:>10} {:>10} {:>10} {:>10.1} {:>+10}  {}",
+        result.name, result.rule, result.generations, result.initial_population, result.final_population,
+        result.peak_population, result.mean_population, result.population_difference, end,
+    )
+}
@@ -0,0 +1,92 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use super::convert::{decode, FileFormat};
+use super::pattern::is_apgcode;
+
+/// Where fetched patterns are cached, under the patterns directory so
+/// `load fetched/<name>.rle` finds them.
+pub const FETCH_DIR: &str = "../patterns/fetched";
+
+const LIFEWIKI_URL: &str = "https://conwaylife.com/patterns";
+const CATAGOLUE_URL: &str = "https://catagolue.hatsya.com/rle";
+
+/// Where a fetched pattern came from, kept beside it as `<name>.source.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Name or apgcode as asked for.
+    pub pattern: String,
+    /// `lifewiki` or `catagolue`.
+    pub source: String,
+    pub url: String,
+    /// Unix time in seconds.
+    pub fetched_at: u64,
+    pub cells: usize,
+}
+
+/// The cache file name and download URL for a pattern: apgcodes come from
+/// Catagolue under `rule`, names from the LifeWiki pattern collection, which
+/// spells "Gosper glider gun" as `gosperglidergun`.
+pub fn pattern_url(pattern: &str, rule: &str) -> Result<(String, &'static str, String)> {
+    if is_apgcode(pattern) {
+        let rule: String = rule.chars().filter(|c| *c != '/').collect::<String>().to_ascii_lowercase();
+        return Ok((pattern.to_string(), "catagolue", format!("{}/{}/{}", CATAGOLUE_URL, rule, pattern)));
+    }
+    let slug: String = pattern.chars().filter(char::is_ascii_alphanumeric).collect::<String>().to_ascii_lowercase();
+    if slug.is_empty() {
+        anyhow::bail!("'{}' is neither a pattern name nor an apgcode", pattern);
+    }
+    let url = format!("{}/{}.rle", LIFEWIKI_URL, slug);
+    Ok((slug, "lifewiki", url))
+}
+
+/// Download a pattern's RLE into the cache unless it is already there (or
+/// `refresh` is set), and return the cached file.
+pub async fn fetch_pattern(pattern: &str, rule: &str, refresh: bool) -> Result<PathBuf> {
+    let (slug, source, url) = pattern_url(pattern, rule)?;
+    let path = Path::new(FETCH_DIR).join(format!("{}.rle", slug));
+    if path.is_file() && !refresh {
+        println!("Using cached {}", path.display());
+        return Ok(path);
+    }
+    
+    println!("Fetching {}", url);
+    let text = download(&url).await?;
+    // Error pages come back as HTML, which is not a pattern
+    let decoded = decode(FileFormat::Rle, &text).with_context(|| format!("{} did not return an RLE pattern", url))?;
+    if decoded.cells.is_empty() {
+        anyhow::bail!("{} returned an empty pattern", url);
+    }
+    
+    fs::create_dir_all(FETCH_DIR).with_context(|| format!("Failed to create {}", FETCH_DIR))?;
+    fs::write(&path, &text).with_context(|| format!("Failed to write {}", path.display()))?;
+    let provenance = Provenance {
+        pattern: pattern.to_string(),
+        source: source.to_string(),
+        url,
+        fetched_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs()),
+        cells: decoded.cells.len(),
+    };
+    let provenance_path = path.with_extension("source.json");
+    fs::write(&provenance_path, serde_json::to_string_pretty(&provenance)? + "\n")
+        .with_context(|| format!("Failed to write {}", provenance_path.display()))?;
+    println!("Saved {} ({} cells) to {}; load it with `load fetched/{}.rle`", pattern, decoded.cells.len(), path.display(), slug);
+    Ok(path)
+}
+
+#[cfg(feature = "fetch")]
+async fn download(url: &str) -> Result<String> {
+    let response = reqwest::get(url).await.with_context(|| format!("Failed to fetch {}", url))?;
+    let status = response.status();
+    if !status.is_success() {
+        anyhow::bail!("{} answered {}", url, status);
+    }
+    Ok(response.text().await?)
+}
+
+#[cfg(not(feature = "fetch"))]
+async fn download(url: &str) -> Result<String> {
+    anyhow::bail!("Cannot fetch {}: built without the fetch feature (cargo run --features fetch)", url)
+}
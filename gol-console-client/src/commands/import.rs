@@ -0,0 +1,377 @@
+use anyhow::{Context, Result};
+use flate2::read::DeflateDecoder;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use super::convert::{decode, encode, FileFormat};
+use super::thumbnail::{pattern_entries, ThumbnailStyle, PATTERNS_DIR};
+
+/// Where imported collections go, one subdirectory each, under the patterns
+/// directory so `load imported/<collection>/<file>` finds them.
+pub const IMPORT_DIR: &str = "../patterns/imported";
+
+/// Largest pattern file read from an archive, once inflated.
+const MAX_ENTRY_BYTES: u64 = 64 * 1024 * 1024;
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const END_OF_DIRECTORY_SIGNATURE: u32 = 0x0605_4b50;
+
+/// Zip compression methods that can be read.
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+/// What an import brought in, kept beside the collection as
+/// `<collection>.source.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRecord {
+    pub archive: String,
+    /// Unix time in seconds.
+    pub imported_at: u64,
+    pub patterns: usize,
+    /// Archive entries left out, with why.
+    pub skipped: Vec<(String, String)>,
+}
+
+/// A file in a zip archive, as its central directory lists it.
+struct ZipEntry {
+    name: String,
+    method: u16,
+    encrypted: bool,
+    compressed_size: u64,
+    size: u64,
+    header_offset: usize,
+}
+
+/// Extract every RLE, plaintext and macrocell pattern in the zip archive
+/// `archive` into `IMPORT_DIR/<collection>`, keeping the archive's folders.
+/// RLE and plaintext patterns are written as RLE, named after their file
+/// when they carry no name; macrocell files are kept as they are. Patterns
+/// that fail to decode are skipped, and the imported ones are added to the
+/// catalog `pattern list` shows.
+pub fn import_archive(archive: &str, collection: Option<&str>) -> Result<()> {
+    let data = fs::read(archive).with_context(|| format!("Failed to read {}", archive))?;
+    let entries = read_directory(&data).with_context(|| format!("{} is not a zip archive that can be read", archive))?;
+    let collection = collection_name(collection.unwrap_or_else(|| {
+        Path::new(archive).file_stem().and_then(|stem| stem.to_str()).unwrap_or_default()
+    }))?;
+    let root = Path::new(IMPORT_DIR).join(&collection);
+    
+    let mut imported = 0;
+    let mut skipped = Vec::new();
+    for entry in entries.iter().filter(|entry| !entry.name.ends_with('/')) {
+        match import_entry(&data, entry, &root) {
+            Ok(Some(path)) => {
+                imported += 1;
+                println!("Imported {}", path.display());
+            }
+            Ok(None) => {}
+            Err(error) => skipped.push((entry.name.clone(), format!("{:#}", error))),
+        }
+    }
+    for (name, reason) in &skipped {
+        println!("Skipped {}: {}", name, reason);
+    }
+    if imported == 0 {
+        anyhow::bail!("{} holds no RLE, plaintext or macrocell patterns", archive);
+    }
+    
+    let record = ImportRecord {
+        archive: archive.to_string(),
+        imported_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs()),
+        patterns: imported,
+        skipped,
+    };
+    let record_path = Path::new(IMPORT_DIR).join(format!("{}.source.json", collection));
+    fs::write(&record_path, serde_json::to_string_pretty(&record)? + "\n")
+        .with_context(|| format!("Failed to write {}", record_path.display()))?;
+    
+    // Draws and caches the new patterns' thumbnails
+    let catalog = pattern_entries(PATTERNS_DIR, ThumbnailStyle::Braille)?;
+    let prefix = format!("imported/{}/", collection);
+    let listed = catalog.iter().filter(|entry| entry.file.starts_with(&prefix)).count();
+    println!(
+        "Imported {} patterns into {} ({} skipped, {} in the catalog); load one with `load imported/{}/<file>`",
+        imported, root.display(), record.skipped.len(), listed, collection,
+    );
+    Ok(())
+}
+
+/// A collection name that is safe as one directory: letters, digits,
+/// hyphens and underscores, lowercased, with anything else made a hyphen.
+fn collection_name(name: &str) -> Result<String> {
+    let name: String = name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let name = name.trim_matches('-').to_string();
+    if name.is_empty() {
+        anyhow::bail!("Give the collection a name with --name");
+    }
+    Ok(name)
+}
+
+/// Convert one archive entry into the collection, returning where it was
+/// written, or `None` for files that are not patterns, such as Golly's
+/// rule and HTML files.
+fn import_entry(data: &[u8], entry: &ZipEntry, root: &Path) -> Result<Option<PathBuf>> {
+    let Ok(format) = FileFormat::from_path(&entry.name) else {
+        let extension = Path::new(&entry.name).extension().unwrap_or_default().to_ascii_lowercase();
+        if extension == "lif" || extension == "life" {
+            anyhow::bail!("Life 1.05 and 1.06 files cannot be converted yet");
+        }
+        return Ok(None);
+    };
+    if format == FileFormat::Json {
+        return Ok(None);
+    }
+    let relative = safe_path(&entry.name).context("Path leaves the collection")?;
+    let text = String::from_utf8(read_entry(data, entry)?).context("Not UTF-8 text")?;
+    let mut pattern = decode(format, &text)?;
+    if pattern.cells.is_empty() {
+        anyhow::bail!("No live cells");
+    }
+    
+    let (path, contents) = if format == FileFormat::Macrocell {
+        (root.join(&relative), text)
+    } else {
+        if pattern.name.is_empty() {
+            pattern.name = relative.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        }
+        (root.join(&relative).with_extension("rle"), encode(FileFormat::Rle, &pattern)?)
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(Some(path))
+}
+
+/// `name` as a relative path, or `None` if it is absolute or climbs out
+/// with `..`.
+fn safe_path(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    path.components().all(|component| matches!(component, Component::Normal(_))).then(|| path.to_path_buf())
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+/// The files listed in an archive's central directory. Zip64 archives,
+/// which only collections over 4 GB need, are not supported.
+fn read_directory(data: &[u8]) -> Result<Vec<ZipEntry>> {
+    // The end record is last, followed only by a comment of up to 64 KB
+    let end = (0..data.len().saturating_sub(21)).rev()
+        .take(65_536)
+        .find(|&offset| u32_at(data, offset) == Some(END_OF_DIRECTORY_SIGNATURE))
+        .context("No end of central directory record")?;
+    let count = u16_at(data, end + 10).context("Truncated end record")?;
+    let mut offset = u32_at(data, end + 16).context("Truncated end record")?;
+    if count == u16::MAX || offset == u32::MAX {
+        anyhow::bail!("Zip64 archives are not supported");
+    }
+    
+    let mut entries = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let header = offset as usize;
+        if u32_at(data, header) != Some(CENTRAL_HEADER_SIGNATURE) {
+            anyhow::bail!("Corrupt central directory at byte {}", header);
+        }
+        let field = |at: usize| u16_at(data, header + at).context("Truncated central directory");
+        let wide_field = |at: usize| u32_at(data, header + at).context("Truncated central directory");
+        let (name_length, extra_length, comment_length) = (field(28)? as usize, field(30)? as usize, field(32)? as usize);
+        let name = data.get(header + 46..header + 46 + name_length).context("Truncated central directory")?;
+        entries.push(ZipEntry {
+            name: String::from_utf8_lossy(name).replace('\\', "/"),
+            method: field(10)?,
+            encrypted: field(8)? & 1 != 0,
+            compressed_size: wide_field(20)? as u64,
+            size: wide_field(24)? as u64,
+            header_offset: wide_field(42)? as usize,
+        });
+        offset += (46 + name_length + extra_length + comment_length) as u32;
+    }
+    Ok(entries)
+}
+
+/// An entry's contents, inflated.
+fn read_entry(data: &[u8], entry: &ZipEntry) -> Result<Vec<u8>> {
+    if entry.encrypted {
+        anyhow::bail!("Encrypted");
+    }
+    if entry.size > MAX_ENTRY_BYTES {
+        anyhow::bail!("Larger than {} MB", MAX_ENTRY_BYTES / (1024 * 1024));
+    }
+    let header = entry.header_offset;
+    if u32_at(data, header) != Some(LOCAL_HEADER_SIGNATURE) {
+        anyhow::bail!("Corrupt local header");
+    }
+    let name_length = u16_at(data, header + 26).context("Truncated local header")? as usize;
+    let extra_length = u16_at(data, header + 28).context("Truncated local header")? as usize;
+    let start = header + 30 + name_length + extra_length;
+    let compressed = data.get(start..start + entry.compressed_size as usize).context("Truncated entry")?;
+    
+    let contents = match entry.method {
+        STORED => compressed.to_vec(),
+        DEFLATED => {
+            let mut contents = Vec::with_capacity(entry.size as usize);
+            // Reads one byte past the size given, to catch entries that lie about it
+            DeflateDecoder::new(compressed).take(entry.size + 1).read_to_end(&mut contents).context("Corrupt compressed data")?;
+            contents
+        }
+        method => anyhow::bail!("Unsupported compression method {}", method),
+    };
+    if contents.len() as u64 != entry.size {
+        anyhow::bail!("Inflated to {} bytes, not the {} listed", contents.len(), entry.size);
+    }
+    Ok(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::DeflateEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    
+    /// A zip archive of `files`, deflating those marked so, ended by
+    /// `comment`. Checksums are left zero, as the reader ignores them.
+    fn zip(files: &[(&str, &[u8], bool)], comment: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut directory = Vec::new();
+        for &(name, contents, deflate) in files {
+            let (method, stored) = if deflate {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(contents).unwrap();
+                (DEFLATED, encoder.finish().unwrap())
+            } else {
+                (STORED, contents.to_vec())
+            };
+            let offset = data.len() as u32;
+            data.extend(LOCAL_HEADER_SIGNATURE.to_le_bytes());
+            data.extend([20, 0, 0, 0]);
+            data.extend(method.to_le_bytes());
+            data.extend([0; 8]);
+            data.extend((stored.len() as u32).to_le_bytes());
+            data.extend((contents.len() as u32).to_le_bytes());
+            data.extend((name.len() as u16).to_le_bytes());
+            data.extend([0, 0]);
+            data.extend(name.as_bytes());
+            data.extend(&stored);
+            
+            directory.extend(CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+            directory.extend([20, 0, 20, 0, 0, 0]);
+            directory.extend(method.to_le_bytes());
+            directory.extend([0; 8]);
+            directory.extend((stored.len() as u32).to_le_bytes());
+            directory.extend((contents.len() as u32).to_le_bytes());
+            directory.extend((name.len() as u16).to_le_bytes());
+            directory.extend([0; 12]);
+            directory.extend(offset.to_le_bytes());
+            directory.extend(name.as_bytes());
+        }
+        let directory_offset = data.len() as u32;
+        data.extend(&directory);
+        data.extend(END_OF_DIRECTORY_SIGNATURE.to_le_bytes());
+        data.extend([0; 4]);
+        data.extend((files.len() as u16).to_le_bytes());
+        data.extend((files.len() as u16).to_le_bytes());
+        data.extend((directory.len() as u32).to_le_bytes());
+        data.extend(directory_offset.to_le_bytes());
+        data.extend((comment.len() as u16).to_le_bytes());
+        data.extend(comment);
+        data
+    }
+    
+    #[test]
+    fn test_entries_are_read_stored_and_deflated() {
+        let data = zip(&[("glider.rle", b"x = 3, y = 3\nbo$2bo$3o!\n", false), ("lib/block.cells", b"OO\nOO\n", true)], b"");
+        let entries = read_directory(&data).unwrap();
+        let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, vec!["glider.rle", "lib/block.cells"]);
+        assert_eq!(read_entry(&data, &entries[0]).unwrap(), b"x = 3, y = 3\nbo$2bo$3o!\n");
+        assert_eq!(entries[1].method, DEFLATED);
+        assert_eq!(read_entry(&data, &entries[1]).unwrap(), b"OO\nOO\n");
+    }
+    
+    #[test]
+    fn test_end_record_is_found_behind_a_comment() {
+        let data = zip(&[("a.rle", b"x = 1, y = 1\no!\n", false)], &[b'c'; 1000]);
+        assert_eq!(read_directory(&data).unwrap().len(), 1);
+        
+        // The longest comment allowed still leaves the record in reach
+        let data = zip(&[("a.rle", b"x = 1, y = 1\no!\n", false)], &[b'c'; 65_535]);
+        assert_eq!(read_directory(&data).unwrap().len(), 1);
+        let mut data = data;
+        data.push(b'c');
+        assert!(read_directory(&data).is_err());
+        
+        // An empty archive is the end record alone
+        assert!(read_directory(&zip(&[], b"")).unwrap().is_empty());
+        assert!(read_directory(&zip(&[], b"")[1..]).is_err());
+        assert!(read_directory(b"").is_err());
+        assert!(read_directory(b"not a zip archive at all, just text").is_err());
+    }
+    
+    #[test]
+    fn test_corrupt_archives_are_refused() {
+        let data = zip(&[("a.rle", b"x = 1, y = 1\no!\n", false)], b"");
+        let end = data.len() - 22;
+        
+        // The directory offset points past the data
+        let mut moved = data.clone();
+        moved[end + 16..end + 20].copy_from_slice(&(data.len() as u32).to_le_bytes());
+        assert!(read_directory(&moved).is_err());
+        
+        let mut entries = read_directory(&data).unwrap();
+        entries[0].size += 1;
+        assert!(read_entry(&data, &entries[0]).is_err());
+        entries[0].size -= 1;
+        entries[0].encrypted = true;
+        assert!(read_entry(&data, &entries[0]).is_err());
+    }
+    
+    #[test]
+    fn test_entries_are_converted_into_the_collection() {
+        let root = std::env::temp_dir().join(format!("gol-import-test-{}", std::process::id()));
+        let data = zip(&[
+            ("lib/block.cells", b"!Name: Block\nOO\nOO\n", true),
+            ("glider.rle", b"x = 3, y = 3\nbo$2bo$3o!\n", false),
+            ("../escape.rle", b"x = 1, y = 1\no!\n", false),
+            ("readme.txt", b"Patterns", false),
+            ("old.lif", b"#Life 1.06\n0 0\n", false),
+            ("empty.rle", b"x = 0, y = 0\n!\n", false),
+        ], b"");
+        let entries = read_directory(&data).unwrap();
+        let results: Vec<_> = entries.iter().map(|entry| import_entry(&data, entry, &root)).collect();
+        
+        assert_eq!(results[0].as_ref().unwrap().as_deref(), Some(root.join("lib/block.rle").as_path()));
+        let block = fs::read_to_string(root.join("lib/block.rle")).unwrap();
+        assert!(block.starts_with("#N Block\n"), "{}", block);
+        // Patterns without a name take their file's
+        let glider = fs::read_to_string(root.join("glider.rle")).unwrap();
+        assert!(glider.starts_with("#N glider\n"), "{}", glider);
+        assert!(results[2].is_err());
+        assert!(!root.join("../escape.rle").exists());
+        assert!(matches!(results[3], Ok(None)));
+        assert!(results[4].as_ref().unwrap_err().to_string().contains("Life 1.05 and 1.06"));
+        assert!(results[5].is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+    
+    #[test]
+    fn test_collection_names_are_one_safe_directory() {
+        assert_eq!(collection_name("Jason's Patterns 2024").unwrap(), "jason-s-patterns-2024");
+        assert_eq!(collection_name("../all_of_it").unwrap(), "all_of_it");
+        assert!(collection_name("...").is_err());
+        assert_eq!(safe_path("a/b.rle"), Some(PathBuf::from("a/b.rle")));
+        assert_eq!(safe_path("/etc/passwd"), None);
+        assert_eq!(safe_path("a/../../b.rle"), None);
+    }
+}
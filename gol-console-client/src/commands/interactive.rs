@@ -4,6 +4,12 @@ pub struct InteractiveCommand {
     // TODO: Add fields for interactive command
 }
 
+impl Default for InteractiveCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl InteractiveCommand {
     pub fn new() -> Self {
         Self {
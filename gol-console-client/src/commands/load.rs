@@ -4,6 +4,12 @@ pub struct LoadCommand {
     // TODO: Add fields for load command
 }
 
+impl Default for LoadCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LoadCommand {
     pub fn new() -> Self {
         Self {
@@ -1,5 +1,10 @@
 use anyhow::Result;
 use crate::client::GameOfLifeClient;
+use crate::client::game_of_life::Webhook;
+use crate::profiles::{config_path, ClientConfig, CONFIG_ENV_VAR};
+use crate::ui::graphics::GraphicsProtocol;
+use crate::ui::keymap::Keymap;
+use crate::ui::theme::Theme;
 
 pub mod load;
 pub mod run;
@@ -9,26 +14,45 @@ pub mod interactive;
 pub mod simulation;
 pub mod pattern;
 pub mod control;
+pub mod bench;
+pub mod batch;
+pub mod experiment;
+pub mod check;
+pub mod validate;
+pub mod convert;
+pub mod soup;
+pub mod fetch;
+pub mod import;
+pub mod thumbnail;
 
-pub async fn handle_load_command(client: &mut GameOfLifeClient, pattern: &str) -> Result<()> {
-    let mut pattern_commands = pattern::PatternCommands::new(client.clone());
-    let mut simulation_commands = simulation::SimulationCommands::new(client.clone());
-    
-    let pattern_file = if pattern.starts_with('/') || pattern.contains(':') {
+/// Where `load` and `bench` read `pattern` from: apgcodes as given, full
+/// paths as given, and anything else from the patterns directory.
+fn pattern_source(pattern: &str) -> String {
+    if pattern::is_apgcode(pattern) {
+        // Decoded by the server
+        pattern.to_string()
+    } else if pattern.starts_with('/') || pattern.contains(':') {
         // Full path provided
         pattern.to_string()
-    } else if pattern.ends_with(".json") {
+    } else if pattern.ends_with(".json") || pattern.ends_with(".rle") || pattern.ends_with(".mc") {
         // Already has extension, use patterns directory
         format!("../patterns/{}", pattern)
     } else {
         // Add .json extension and use patterns directory
         format!("../patterns/{}.json", pattern)
-    };
+    }
+}
+
+pub async fn handle_load_command(client: &mut GameOfLifeClient, pattern: &str) -> Result<()> {
+    let mut pattern_commands = pattern::PatternCommands::new(client.clone());
+    let mut simulation_commands = simulation::SimulationCommands::new(client.clone());
+    
+    let pattern_file = pattern_source(pattern);
     
     println!("Loading pattern from: {}", pattern_file);
     
     // Try to create a simulation first (in case it doesn't exist)
-    let simulation_id = match simulation_commands.create(50, 50, None).await {
+    let simulation_id = match simulation_commands.create(50, 50, None, None).await {
         Ok(response) => {
             println!("Created new simulation");
             response.id
@@ -39,7 +63,12 @@ pub async fn handle_load_command(client: &mut GameOfLifeClient, pattern: &str) -
         }
     };
     
-    match pattern_commands.load_from_file(simulation_id, &pattern_file, 0, 0).await {
+    let loaded = if pattern::is_apgcode(&pattern_file) {
+        pattern_commands.load_apgcode(simulation_id, &pattern_file, 0, 0).await
+    } else {
+        pattern_commands.load_from_file(simulation_id, &pattern_file, 0, 0).await
+    };
+    match loaded {
         Ok(_) => println!("Pattern loaded successfully"),
         Err(e) => println!("Error loading pattern: {}", e),
     }
@@ -47,6 +76,59 @@ pub async fn handle_load_command(client: &mut GameOfLifeClient, pattern: &str) -
     Ok(())
 }
 
+pub async fn handle_export_command(client: &mut GameOfLifeClient, simulation_id: &str, path: &str) -> Result<()> {
+    let mut pattern_commands = pattern::PatternCommands::new(client.clone());
+    pattern_commands.export_to_file(simulation_id.to_string(), path).await
+}
+
+pub async fn handle_export_stats_command(client: &mut GameOfLifeClient, simulation_id: &str, path: &str, from: Option<i64>, to: Option<i64>) -> Result<()> {
+    let mut simulation_commands = simulation::SimulationCommands::new(client.clone());
+    simulation_commands.export_statistics(simulation_id.to_string(), path, from, to).await
+}
+
+pub async fn handle_bench_command(client: &mut GameOfLifeClient, options: bench::BenchOptions) -> Result<()> {
+    println!("Benchmarking {} for {} generations on {}:{}", options.pattern, options.generations, client.host, client.port);
+    if options.in_process {
+        client.connect().await?;
+        let response = client.run_benchmark(options.pattern.clone(), options.generations as i32, options.size).await?;
+        println!("{}", bench::format_server_benchmark(client, &response));
+        return Ok(());
+    }
+    let report = bench::run_benchmark(client, &pattern_source(&options.pattern), &options).await?;
+    println!("{}", report);
+    Ok(())
+}
+
+/// Run the same pattern on every discovered backend, or only the
+/// configured one, and report where their grids or invariants disagree.
+/// Fails if any backend disagrees with the first or fails a check.
+pub async fn handle_check_command(client: &mut GameOfLifeClient, pattern: &str, generations: u32, size: i32, all: bool) -> Result<()> {
+    let mut clients = if all {
+        crate::discovery::discover_backends().await.iter().map(|backend| backend.client()).collect()
+    } else {
+        vec![client.clone()]
+    };
+    if clients.is_empty() {
+        anyhow::bail!("No running servers found to check");
+    }
+    println!("Checking {} for {} generations on {} backend(s)", pattern, generations, clients.len());
+    
+    let pattern_file = pattern_source(pattern);
+    let mut reports = Vec::new();
+    for backend in &mut clients {
+        reports.push(check::check_backend(backend, &pattern_file, generations, size).await?);
+    }
+    let (lines, ok) = check::compare_reports(&reports);
+    for line in lines {
+        println!("{}", line);
+    }
+    if !ok {
+        anyhow::bail!("Backends disagree or broke an invariant");
+    }
+    println!("All checks passed");
+    Ok(())
+}
+
 pub async fn handle_run_command(client: &mut GameOfLifeClient, generations: Option<u32>, delay: Option<u64>) -> Result<()> {
     let mut control_commands = control::ControlCommands::new(client.clone());
     
@@ -65,13 +147,110 @@ pub async fn handle_status_command(client: &mut GameOfLifeClient) -> Result<Stri
     simulation_commands.status().await
 }
 
+pub async fn handle_discover_command() -> Result<()> {
+    let backends = crate::discovery::discover_backends().await;
+    if backends.is_empty() {
+        println!("No running servers found on ports {:?} or in {}", crate::discovery::discovery_ports(), crate::discovery::registry_dir().display());
+    }
+    for backend in backends {
+        println!("{:<8} {}", backend.implementation, backend.address());
+    }
+    Ok(())
+}
+
+/// Point a simulation's webhook at `url` for the milestones named in `on`
+/// (`completion`, `extinction`, `stabilization`) and every `every`
+/// generations, or remove its webhooks when `url` is `None`.
+pub async fn handle_webhook_command(client: &mut GameOfLifeClient, simulation_id: &str, url: Option<&str>, on: &[String], every: u64) -> Result<()> {
+    let webhooks = match url {
+        Some(url) => {
+            let mut webhook = Webhook { url: url.to_string(), every_generations: every as i64, ..Default::default() };
+            for milestone in on {
+                match milestone.as_str() {
+                    "completion" => webhook.on_completion = true,
+                    "extinction" => webhook.on_extinction = true,
+                    "stabilization" => webhook.on_stabilization = true,
+                    other => anyhow::bail!("Unknown milestone '{}', expected completion, extinction or stabilization", other),
+                }
+            }
+            vec![webhook]
+        }
+        None => Vec::new(),
+    };
+    client.connect().await?;
+    let response = client.set_webhooks(simulation_id.to_string(), webhooks).await?;
+    if response.webhooks.is_empty() {
+        println!("Removed the webhooks of {}", response.id);
+    }
+    for webhook in response.webhooks {
+        println!("{} notifies {}", response.id, webhook.url);
+    }
+    Ok(())
+}
+
+pub async fn handle_watch_command(client: &mut GameOfLifeClient, simulation_ids: &[String]) -> Result<()> {
+    client.connect().await?;
+    let mut events = client.watch_events(simulation_ids.to_vec()).await?;
+    while let Some(event) = events.message().await? {
+        println!("{}", simulation::format_event(&event));
+    }
+    Ok(())
+}
+
 pub async fn handle_stop_command(_client: &mut GameOfLifeClient) -> Result<()> {
     println!("Stopping simulation");
     Ok(())
 }
 
-pub async fn handle_interactive_command(_client: &mut GameOfLifeClient) -> Result<()> {
+/// List the connection profiles, marking `selected`, the one in use.
+pub fn handle_profiles_command(config: &ClientConfig, selected: &str) {
+    match config_path() {
+        Some(path) => println!("Client config: {}", path.display()),
+        None => println!("Client config: none (set {})", CONFIG_ENV_VAR),
+    }
+    for (name, profile) in config.profiles() {
+        let marker = if name == selected { '*' } else { ' ' };
+        let mut line = format!("{} {} ({}): {}", marker, name, profile.backend, profile.address());
+        if profile.token.is_some() {
+            line.push_str(", token set");
+        }
+        if let Some(simulation) = &profile.simulation {
+            line.push_str(&format!(", opens {}", simulation));
+        }
+        println!("{}", line);
+    }
+}
+
+/// Run the interactive UI, opening `simulation` in its first tab.
+pub async fn handle_interactive_command(client: &mut GameOfLifeClient, theme: Theme, keymap: Keymap, graphics: Option<GraphicsProtocol>, simulation: &str) -> Result<()> {
+    use crate::ui::TerminalUI;
+    let mut ui = TerminalUI::new()?.with_client(client.clone());
+    ui.open_simulation(simulation.to_string());
+    ui.set_theme(theme);
+    ui.set_keymap(keymap);
+    ui.set_graphics(graphics)?;
+    ui.run_interactive().await
+}
+
+/// Open the interactive UI on the existing simulation `simulation`,
+/// read-only unless `write` is set.
+pub async fn handle_attach_command(client: &mut GameOfLifeClient, theme: Theme, keymap: Keymap, graphics: Option<GraphicsProtocol>, simulation: &str, write: bool) -> Result<()> {
     use crate::ui::TerminalUI;
-    let mut ui = TerminalUI::new()?;
+    let mut ui = TerminalUI::new()?.with_client(client.clone());
+    ui.set_theme(theme);
+    ui.set_keymap(keymap);
+    ui.set_graphics(graphics)?;
+    ui.attach(simulation.to_string(), write).await?;
     ui.run_interactive().await
+}
+
+/// Run the screensaver on a new `width` by `height` simulation, stepping
+/// every `interval_ms` milliseconds.
+pub async fn handle_demo_command(client: &mut GameOfLifeClient, theme: Theme, graphics: Option<GraphicsProtocol>, width: i32, height: i32, interval_ms: u64) -> Result<()> {
+    use crate::ui::TerminalUI;
+    let mut ui = TerminalUI::new()?.with_client(client.clone());
+    ui.set_theme(theme);
+    ui.set_graphics(graphics)?;
+    ui.set_auto_step_interval(std::time::Duration::from_millis(interval_ms));
+    ui.run_demo(width, height).await
 }
\ No newline at end of file
@@ -2,8 +2,19 @@ use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
-use crate::client::GameOfLifeClient;
-use crate::client::game_of_life::{Pattern, Position, LoadPatternResponse};
+use crate::client::{GameOfLifeClient, UPLOAD_CHUNK_CELLS};
+use crate::client::game_of_life::{Pattern, PatternFormat, Position, LoadPatternResponse};
+
+/// Whether `pattern` looks like an apgcode such as `xs4_33` or `xq4_153`.
+pub fn is_apgcode(pattern: &str) -> bool {
+    let Some((prefix, body)) = pattern.split_once('_') else {
+        return false;
+    };
+    let number = prefix.strip_prefix("xs").or_else(|| prefix.strip_prefix("xp")).or_else(|| prefix.strip_prefix("xq"));
+    number.is_some_and(|number| !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()))
+        && !body.is_empty()
+        && body.bytes().all(|b| b.is_ascii_alphanumeric())
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PatternFile {
@@ -15,8 +26,8 @@ pub struct PatternFile {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PatternCell {
-    pub x: i32,
-    pub y: i32,
+    pub x: i64,
+    pub y: i64,
 }
 
 pub struct PatternCommands {
@@ -28,17 +39,46 @@ impl PatternCommands {
         Self { client }
     }
     
-    pub async fn load_from_file(&mut self, simulation_id: String, file_path: &str, x: i32, y: i32) -> Result<LoadPatternResponse> {
-        let pattern_file = self.read_pattern_file(file_path)?;
-        let pattern = self.convert_to_grpc_pattern(pattern_file)?;
+    pub async fn load_from_file(&mut self, simulation_id: String, file_path: &str, x: i64, y: i64) -> Result<LoadPatternResponse> {
         let position = Position { x, y };
-        
         self.client.connect().await?;
-        let response = self.client.load_pattern(simulation_id, pattern, position).await?;
+        
+        // RLE, macrocell and very large JSON patterns are streamed instead of sent in one message
+        let name = Path::new(file_path).file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let response = if file_path.ends_with(".rle") {
+            let rle = fs::read_to_string(file_path).context("Failed to read pattern file")?;
+            self.client.upload_rle(simulation_id, name, position, &rle).await?
+        } else if file_path.ends_with(".mc") {
+            let macrocell = fs::read_to_string(file_path).context("Failed to read pattern file")?;
+            self.client.upload_macrocell(simulation_id, name, position, &macrocell).await?
+        } else {
+            let pattern = self.convert_to_grpc_pattern(self.read_pattern_file(file_path)?)?;
+            if pattern.cells.len() > UPLOAD_CHUNK_CELLS {
+                self.client.upload_pattern(simulation_id, pattern.name, position, pattern.cells).await?
+            } else {
+                self.client.load_pattern(simulation_id, pattern, position).await?
+            }
+        };
         
         if response.success {
             println!("Pattern loaded successfully");
             println!("Added {} cells", response.cells_added);
+            if response.cells_out_of_bounds > 0 {
+                println!("Dropped {} cells outside the grid", response.cells_out_of_bounds);
+            }
+        } else {
+            println!("Failed to load pattern: {}", response.message);
+        }
+        
+        Ok(response)
+    }
+    
+    pub async fn load_apgcode(&mut self, simulation_id: String, apgcode: &str, x: i64, y: i64) -> Result<LoadPatternResponse> {
+        self.client.connect().await?;
+        let response = self.client.load_apgcode(simulation_id, apgcode.to_string(), Position { x, y }).await?;
+        
+        if response.success {
+            println!("Loaded {} ({} cells)", apgcode, response.cells_added);
         } else {
             println!("Failed to load pattern: {}", response.message);
         }
@@ -46,6 +86,18 @@ impl PatternCommands {
         Ok(response)
     }
     
+    /// Save a simulation's live cells to `file_path`: Golly macrocell for `.mc`
+    /// files, RLE otherwise.
+    pub async fn export_to_file(&mut self, simulation_id: String, file_path: &str) -> Result<()> {
+        let format = if file_path.ends_with(".mc") { PatternFormat::PatternMacrocell } else { PatternFormat::PatternRle };
+        self.client.connect().await?;
+        
+        let exported = self.client.export_pattern(simulation_id, format).await?;
+        fs::write(file_path, &exported.content).context("Failed to write pattern file")?;
+        println!("Exported {} cells at generation {} to {}", exported.live_cells, exported.generation, file_path);
+        Ok(())
+    }
+    
     pub fn read_pattern_file(&self, file_path: &str) -> Result<PatternFile> {
         let path = Path::new(file_path);
         if !path.exists() {
@@ -56,7 +108,7 @@ impl PatternCommands {
             .context("Failed to read pattern file")?;
             
         let pattern: PatternFile = serde_json::from_str(&content)
-            .context("Failed to parse pattern file as JSON")?;
+            .with_context(|| format!("Failed to parse pattern file as JSON; `pattern validate {}` shows what is wrong", file_path))?;
             
         Ok(pattern)
     }
@@ -103,10 +155,10 @@ impl PatternCommands {
         println!("Author: {}", pattern.author);
         println!("Cells: {}", pattern.cells.len());
         
-        let mut min_x = i32::MAX;
-        let mut max_x = i32::MIN;
-        let mut min_y = i32::MAX;
-        let mut max_y = i32::MIN;
+        let mut min_x = i64::MAX;
+        let mut max_x = i64::MIN;
+        let mut min_y = i64::MAX;
+        let mut max_y = i64::MIN;
         
         for cell in &pattern.cells {
             min_x = min_x.min(cell.x);
@@ -4,6 +4,12 @@ pub struct RunCommand {
     // TODO: Add fields for run command
 }
 
+impl Default for RunCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RunCommand {
     pub fn new() -> Self {
         Self {
@@ -1,6 +1,7 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use std::fs;
 use crate::client::GameOfLifeClient;
-use crate::client::game_of_life::{SimulationResponse, StepResponse, DeleteResponse};
+use crate::client::game_of_life::{SimulationResponse, StepResponse, DeleteResponse, SimulationEvent, SimulationEventType, EndReason};
 
 pub struct SimulationCommands {
     client: GameOfLifeClient,
@@ -11,10 +12,13 @@ impl SimulationCommands {
         Self { client }
     }
     
-    pub async fn create(&mut self, width: i32, height: i32, pattern: Option<String>) -> Result<SimulationResponse> {
+    pub async fn create(&mut self, width: i32, height: i32, pattern: Option<String>, seed: Option<u64>) -> Result<SimulationResponse> {
         self.client.connect().await?;
-        let response = self.client.create_simulation(width, height, pattern).await?;
+        let response = self.client.create_simulation(width, height, pattern, seed).await?;
         println!("Created simulation with ID: {}", response.id);
+        if let Some(seed) = response.seed {
+            println!("Seed: {}", seed);
+        }
         Ok(response)
     }
     
@@ -54,11 +58,87 @@ impl SimulationCommands {
     pub async fn status(&mut self) -> Result<String> {
         self.client.connect().await?;
         let status = self.client.get_status().await?;
-        let status_text = format!(
-            "Server Status: {}\nVersion: {}\nImplementation: {}\nUptime: {} seconds",
-            status.status, status.version, status.implementation, status.uptime_seconds
+        let mut status_text = format!(
+            "Server Status: {}\nVersion: {}\nImplementation: {}\nUptime: {} seconds\nMemory: {} bytes across {} simulations",
+            status.status, status.version, status.implementation, status.uptime_seconds, status.memory_bytes, status.simulation_count
         );
+        status_text.push_str(&format!(
+            "\nActive: {} simulations at {:.1} gen/s, {} live cells",
+            status.active_simulation_count, status.generations_per_second, status.total_live_cells
+        ));
+        if !status.engine_versions.is_empty() {
+            let mut engines: Vec<_> = status.engine_versions.iter().map(|(name, version)| format!("{} {}", name, version)).collect();
+            engines.sort();
+            status_text.push_str(&format!("\nEngines: {}", engines.join(", ")));
+        }
+        if let Some(build) = &status.build {
+            let commit = if build.git_commit.is_empty() { "unknown commit" } else { build.git_commit.as_str() };
+            status_text.push_str(&format!("\nBuild: {} ({}, {})", build.version, commit, build.profile));
+            if !build.features.is_empty() {
+                status_text.push_str(&format!(" with {}", build.features.join(", ")));
+            }
+        }
+        if let Some(limits) = &status.limits {
+            let limit = |value: i64| if value == 0 { "unlimited".to_string() } else { value.to_string() };
+            let rate = |value: f64| if value == 0.0 { "unlimited".to_string() } else { format!("{}/s", value) };
+            status_text.push_str(&format!(
+                "\nLimits: {} cells per response, {} inline steps, {} cells per fill\nPer client: {} simulations, {} live cells, {} steps/min; requests {} per client, {} per simulation",
+                limits.max_response_cells,
+                limits.max_inline_steps,
+                limits.max_fill_cells,
+                limit(limits.max_simulations_per_client as i64),
+                limit(limits.max_live_cells_per_client),
+                limit(limits.max_steps_per_minute_per_client),
+                rate(limits.peer_requests_per_second),
+                rate(limits.simulation_requests_per_second),
+            ));
+        }
         println!("{}", status_text);
         Ok(status_text)
     }
-}
\ No newline at end of file
+    
+    /// Write the population, births and deaths series to `path`, as JSON if
+    /// it ends in `.json` and otherwise as CSV, for plotting elsewhere.
+    pub async fn export_statistics(&mut self, id: String, path: &str, from: Option<i64>, to: Option<i64>) -> Result<()> {
+        self.client.connect().await?;
+        let statistics = self.client.get_statistics(id, from, to).await?;
+        
+        let content = if path.ends_with(".json") {
+            let rows: Vec<serde_json::Value> = statistics.generations.iter().map(|sample| serde_json::json!({
+                "generation": sample.generation,
+                "population": sample.population,
+                "births": sample.births,
+                "deaths": sample.deaths,
+            })).collect();
+            serde_json::to_string_pretty(&rows)?
+        } else {
+            let mut csv = String::from("generation,population,births,deaths\n");
+            for sample in &statistics.generations {
+                csv.push_str(&format!("{},{},{},{}\n", sample.generation, sample.population, sample.births, sample.deaths));
+            }
+            csv
+        };
+        fs::write(path, content).context("Failed to write statistics file")?;
+        println!("Exported {} generations of {} to {}", statistics.generations.len(), statistics.id, path);
+        Ok(())
+    }
+}
+
+/// One line describing a WatchEvents event.
+pub fn format_event(event: &SimulationEvent) -> String {
+    let detail = match event.r#type() {
+        SimulationEventType::Created => "created".to_string(),
+        SimulationEventType::PatternLoaded => format!("pattern loaded, {} cells added", event.cells_added),
+        SimulationEventType::Stepped => format!("stepped {} generation(s)", event.steps),
+        SimulationEventType::Paused if event.reason() == EndReason::Breakpoint => "paused at a breakpoint".to_string(),
+        SimulationEventType::Paused if event.reason() == EndReason::Watchpoint => "paused at a watchpoint".to_string(),
+        SimulationEventType::Paused => "paused at its generation limit".to_string(),
+        SimulationEventType::Stabilized if event.reason() == EndReason::Cycle => format!("stabilized, repeating every {} generation(s)", event.period),
+        SimulationEventType::Stabilized => "stabilized, died out".to_string(),
+        SimulationEventType::Deleted => "deleted".to_string(),
+        SimulationEventType::CellsChanged => format!("{} cell(s) changed under watchpoint {}", event.cells.len(), event.watchpoint),
+        SimulationEventType::Error => format!("error: {}", event.message),
+        SimulationEventType::Unspecified => "unknown event".to_string(),
+    };
+    format!("{}  gen {:>8}  cells {:>8}  {}", event.id, event.generation, event.live_cells, detail)
+}
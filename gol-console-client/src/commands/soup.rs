@@ -0,0 +1,79 @@
+use anyhow::{Context, Result};
+use std::fs;
+use super::convert::{encode, FileFormat, PatternDocument};
+
+/// Widest or tallest soup written; larger ones belong on the server.
+const MAX_SOUP_SIDE: i32 = 4096;
+
+/// SplitMix64, the server's generator, so a seed gives the same soup here
+/// as in a batch run started from it.
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+    
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+        value ^ (value >> 31)
+    }
+    
+    /// True with chance `probability`, clamped to `0.0..=1.0`.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        if probability <= 0.0 {
+            return false;
+        }
+        let threshold = (probability.min(1.0) * u64::MAX as f64) as u64;
+        self.next_u64() <= threshold
+    }
+}
+
+/// Cells of a `width` by `height` rectangle alive with chance `density`,
+/// row by row, matching the soup a batch run with `seed` starts from when
+/// its soup size is the same square.
+pub fn random_soup(seed: u64, width: i32, height: i32, density: f64) -> Vec<(i64, i64)> {
+    let mut rng = SeededRng::new(seed);
+    let mut cells = Vec::new();
+    if density <= 0.0 {
+        return cells;
+    }
+    for y in 0..height {
+        for x in 0..width {
+            if rng.chance(density) {
+                cells.push((x as i64, y as i64));
+            }
+        }
+    }
+    cells
+}
+
+/// Write a soup to `output` in the format its extension names.
+pub fn write_random_soup(width: i32, height: i32, density: f64, seed: u64, rule: Option<&str>, output: &str) -> Result<()> {
+    for (name, side) in [("width", width), ("height", height)] {
+        if !(1..=MAX_SOUP_SIDE).contains(&side) {
+            anyhow::bail!("The soup {} must be between 1 and {}", name, MAX_SOUP_SIDE);
+        }
+    }
+    if !(0.0..=1.0).contains(&density) {
+        anyhow::bail!("The density must be between 0 and 1");
+    }
+    let format = FileFormat::from_path(output)?;
+    
+    let pattern = PatternDocument {
+        name: format!("Soup {}", seed),
+        description: format!("{}x{} random soup at density {} from seed {}", width, height, density, seed),
+        author: "gol-console-client pattern random".to_string(),
+        rule: rule.map(str::to_string),
+        cells: random_soup(seed, width, height, density),
+    };
+    fs::write(output, encode(format, &pattern)?).with_context(|| format!("Failed to write {}", output))?;
+    println!("Wrote {} ({} cells) to {}", pattern.description, pattern.cells.len(), output);
+    Ok(())
+}
@@ -4,6 +4,12 @@ pub struct StatusCommand {
     // TODO: Add fields for status command
 }
 
+impl Default for StatusCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl StatusCommand {
     pub fn new() -> Self {
         Self {
@@ -4,6 +4,12 @@ pub struct StopCommand {
     // TODO: Add fields for stop command
 }
 
+impl Default for StopCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl StopCommand {
     pub fn new() -> Self {
         Self {
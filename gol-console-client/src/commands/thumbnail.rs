@@ -0,0 +1,249 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use super::convert::{decode, FileFormat};
+
+/// Directory `pattern list` and the Patterns menu describe.
+pub const PATTERNS_DIR: &str = "../patterns";
+
+/// Thumbnails drawn so far, kept in the patterns directory and redrawn
+/// when their pattern file changes.
+const CACHE_FILE: &str = "thumbnails.json";
+
+/// Widest and tallest thumbnail, in characters.
+const MAX_COLUMNS: i64 = 16;
+const MAX_ROWS: i64 = 4;
+
+/// How thumbnails draw cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThumbnailStyle {
+    /// Braille characters of 2x4 dots each.
+    Braille,
+    /// Plain ASCII, one column by two rows per character, for terminals
+    /// whose fonts lack braille.
+    Ascii,
+}
+
+impl ThumbnailStyle {
+    /// Cells across and down one character shows.
+    fn dots(self) -> (i64, i64) {
+        match self {
+            Self::Braille => (2, 4),
+            Self::Ascii => (1, 2),
+        }
+    }
+    
+    fn character(self, dots: &[bool]) -> char {
+        match self {
+            Self::Braille => {
+                // Dot bits in row-major order; Unicode numbers the dots down
+                // the left column, then the right, with the bottom row last
+                const BITS: [u32; 8] = [0x01, 0x08, 0x02, 0x10, 0x04, 0x20, 0x40, 0x80];
+                let bits = dots.iter().zip(BITS).filter(|(dot, _)| **dot).map(|(_, bit)| bit).sum::<u32>();
+                char::from_u32(0x2800 + bits).unwrap_or(' ')
+            }
+            Self::Ascii => match (dots[0], dots[1]) {
+                (true, true) => ':',
+                (true, false) => '\'',
+                (false, true) => '.',
+                (false, false) => ' ',
+            },
+        }
+    }
+}
+
+/// Draw live cells at most `MAX_COLUMNS` by `MAX_ROWS` characters, scaling
+/// large patterns down so a dot is lit when any cell it covers is alive.
+pub fn render_thumbnail(cells: &[(i64, i64)], style: ThumbnailStyle) -> Vec<String> {
+    let (Some(min_x), Some(min_y)) = (cells.iter().map(|c| c.0).min(), cells.iter().map(|c| c.1).min()) else {
+        return Vec::new();
+    };
+    let width = cells.iter().map(|c| c.0).max().unwrap_or(min_x) - min_x + 1;
+    let height = cells.iter().map(|c| c.1).max().unwrap_or(min_y) - min_y + 1;
+    let (dots_x, dots_y) = style.dots();
+    let scale = 1.max((width + MAX_COLUMNS * dots_x - 1) / (MAX_COLUMNS * dots_x))
+        .max((height + MAX_ROWS * dots_y - 1) / (MAX_ROWS * dots_y));
+    
+    let lit: HashSet<(i64, i64)> = cells.iter().map(|&(x, y)| ((x - min_x) / scale, (y - min_y) / scale)).collect();
+    let columns = ((width + scale - 1) / scale + dots_x - 1) / dots_x;
+    let rows = ((height + scale - 1) / scale + dots_y - 1) / dots_y;
+    (0..rows)
+        .map(|row| {
+            (0..columns)
+                .map(|column| {
+                    let dots: Vec<bool> = (0..dots_y)
+                        .flat_map(|dy| (0..dots_x).map(move |dx| (dx, dy)))
+                        .map(|(dx, dy)| lit.contains(&(column * dots_x + dx, row * dots_y + dy)))
+                        .collect();
+                    style.character(&dots)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// A pattern file and what the catalog shows for it.
+#[derive(Debug, Clone)]
+pub struct PatternEntry {
+    /// File name relative to the patterns directory, as `load` takes it.
+    pub file: String,
+    pub name: String,
+    pub description: String,
+    pub cells: usize,
+    pub width: i64,
+    pub height: i64,
+    pub thumbnail: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CachedThumbnail {
+    /// Modification time of the pattern file, in Unix seconds.
+    modified: u64,
+    name: String,
+    description: String,
+    cells: usize,
+    width: i64,
+    height: i64,
+    braille: Vec<String>,
+    ascii: Vec<String>,
+}
+
+/// Describe every pattern file in `dir`, its `fetched` subdirectory and
+/// the collections under `imported`, drawing thumbnails only for files changed since they were cached.
+/// Files that fail to decode are left out.
+pub fn pattern_entries(dir: &str, style: ThumbnailStyle) -> Result<Vec<PatternEntry>> {
+    let cache_path = Path::new(dir).join(CACHE_FILE);
+    let mut cache: BTreeMap<String, CachedThumbnail> = fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default();
+    let mut changed = false;
+    
+    let mut entries = Vec::new();
+    for (file, path) in pattern_files(dir)? {
+        let modified = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .map_or(0, |time| time.duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs()));
+        if cache.get(&file).is_none_or(|cached| cached.modified != modified) {
+            let Some(cached) = draw(&path, modified) else {
+                continue;
+            };
+            cache.insert(file.clone(), cached);
+            changed = true;
+        }
+        let cached = &cache[&file];
+        entries.push(PatternEntry {
+            file,
+            name: cached.name.clone(),
+            description: cached.description.clone(),
+            cells: cached.cells,
+            width: cached.width,
+            height: cached.height,
+            thumbnail: match style {
+                ThumbnailStyle::Braille => cached.braille.clone(),
+                ThumbnailStyle::Ascii => cached.ascii.clone(),
+            },
+        });
+    }
+    
+    // Forget files that are gone
+    let before = cache.len();
+    cache.retain(|file, _| entries.iter().any(|entry| &entry.file == file));
+    if changed || cache.len() != before {
+        // A read-only patterns directory only costs redrawing next time
+        let _ = fs::write(&cache_path, serde_json::to_string_pretty(&cache)? + "\n");
+    }
+    Ok(entries)
+}
+
+/// Braille thumbnails of the patterns in `dir`, by file stem, as the
+/// Patterns menu names them.
+pub fn pattern_previews(dir: &str) -> HashMap<String, Vec<String>> {
+    pattern_entries(dir, ThumbnailStyle::Braille)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|entry| {
+            let stem = Path::new(&entry.file).file_stem()?.to_string_lossy().to_string();
+            Some((stem, entry.thumbnail))
+        })
+        .collect()
+}
+
+fn pattern_files(dir: &str) -> Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    for subdirectory in ["", "fetched"] {
+        list_pattern_files(Path::new(dir), subdirectory, false, &mut files)?;
+    }
+    // Imported collections keep their archives' folders
+    list_pattern_files(Path::new(dir), "imported", true, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+/// Add the pattern files in `subdirectory` of `dir`, and with `recursive`
+/// those in its subdirectories, named relative to `dir`.
+fn list_pattern_files(dir: &Path, subdirectory: &str, recursive: bool, files: &mut Vec<(String, PathBuf)>) -> Result<()> {
+    let Ok(read_dir) = fs::read_dir(dir.join(subdirectory)) else {
+        return Ok(());
+    };
+    for entry in read_dir {
+        let path = entry.with_context(|| format!("Failed to list {}", dir.display()))?.path();
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let file = if subdirectory.is_empty() { file_name.clone() } else { format!("{}/{}", subdirectory, file_name) };
+        if recursive && path.is_dir() {
+            list_pattern_files(dir, &file, true, files)?;
+            continue;
+        }
+        let listed = path.is_file()
+            && file_name != CACHE_FILE
+            && !file_name.ends_with(".source.json")
+            && FileFormat::from_path(&file_name).is_ok();
+        if listed {
+            files.push((file, path));
+        }
+    }
+    Ok(())
+}
+
+fn draw(path: &Path, modified: u64) -> Option<CachedThumbnail> {
+    let format = FileFormat::from_path(&path.to_string_lossy()).ok()?;
+    let pattern = decode(format, &fs::read_to_string(path).ok()?).ok()?;
+    let extent = |axis: fn(&(i64, i64)) -> i64| {
+        let (min, max) = (pattern.cells.iter().map(axis).min(), pattern.cells.iter().map(axis).max());
+        min.zip(max).map_or(0, |(min, max)| max - min + 1)
+    };
+    Some(CachedThumbnail {
+        modified,
+        name: pattern.name.clone(),
+        description: pattern.description.clone(),
+        cells: pattern.cells.len(),
+        width: extent(|cell| cell.0),
+        height: extent(|cell| cell.1),
+        braille: render_thumbnail(&pattern.cells, ThumbnailStyle::Braille),
+        ascii: render_thumbnail(&pattern.cells, ThumbnailStyle::Ascii),
+    })
+}
+
+/// Print every pattern in the patterns directory with its thumbnail.
+pub fn list_patterns(ascii: bool) -> Result<()> {
+    let style = if ascii { ThumbnailStyle::Ascii } else { ThumbnailStyle::Braille };
+    let entries = pattern_entries(PATTERNS_DIR, style)?;
+    if entries.is_empty() {
+        println!("No patterns in {}", PATTERNS_DIR);
+    }
+    for entry in entries {
+        let name = if entry.name.is_empty() { entry.file.clone() } else { entry.name.clone() };
+        println!("{} ({}): {} cells, {}x{}", name, entry.file, entry.cells, entry.width, entry.height);
+        if !entry.description.is_empty() {
+            println!("  {}", entry.description);
+        }
+        for line in &entry.thumbnail {
+            println!("    {}", line);
+        }
+        println!();
+    }
+    Ok(())
+}
@@ -0,0 +1,396 @@
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use super::convert::decode_rle_body;
+
+/// Pattern types the patterns directory documents.
+const PATTERN_TYPES: [&str; 5] = ["still_life", "oscillator", "spaceship", "methuselah", "gun"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The file will not load, or loads as something other than intended.
+    Error,
+    /// The file loads, but lacks metadata the other patterns have.
+    Warning,
+}
+
+/// One problem found in a pattern file, with the line it is on when known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Finding {
+    fn error(line: Option<usize>, message: impl Into<String>) -> Self {
+        Self { severity: Severity::Error, line, message: message.into() }
+    }
+    
+    fn warning(line: Option<usize>, message: impl Into<String>) -> Self {
+        Self { severity: Severity::Warning, line, message: message.into() }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "{}:", line)?;
+        }
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, " {}: {}", severity, self.message)
+    }
+}
+
+/// Check each file, printing what is wrong with it, or `ok`. Fails if any
+/// file has errors; warnings alone pass.
+pub fn validate_files(files: &[String]) -> Result<()> {
+    let (mut errors, mut warnings) = (0, 0);
+    for file in files {
+        let findings = validate_file(file)?;
+        if findings.is_empty() {
+            println!("{}: ok", file);
+        }
+        for finding in &findings {
+            println!("{}:{}", file, finding);
+            match finding.severity {
+                Severity::Error => errors += 1,
+                Severity::Warning => warnings += 1,
+            }
+        }
+    }
+    if files.len() > 1 || errors + warnings > 0 {
+        println!("{} file(s) checked: {} error(s), {} warning(s)", files.len(), errors, warnings);
+    }
+    if errors > 0 {
+        anyhow::bail!("{} error(s) in pattern files", errors);
+    }
+    Ok(())
+}
+
+/// Problems in a `.json`, `.rle` or `.cells` pattern file, by its extension.
+pub fn validate_file(file_path: &str) -> Result<Vec<Finding>> {
+    let extension = Path::new(file_path).extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+    let validate: fn(&str) -> Vec<Finding> = match extension.to_ascii_lowercase().as_str() {
+        "json" => validate_json,
+        "rle" => validate_rle,
+        "cells" => validate_plaintext,
+        _ => return Ok(vec![Finding::error(None, "unknown pattern format; expected a .json, .rle or .cells file")]),
+    };
+    let content = fs::read_to_string(file_path).with_context(|| format!("Failed to read {}", file_path))?;
+    Ok(validate(&content))
+}
+
+/// Check a pattern in the patterns directory's JSON format.
+pub fn validate_json(content: &str) -> Vec<Finding> {
+    let value: Value = match serde_json::from_str(content) {
+        Ok(value) => value,
+        Err(e) => {
+            // The error's own text ends with the position, which the finding already has
+            let text = e.to_string();
+            let message = text.split(" at line ").next().unwrap_or(&text);
+            return vec![Finding::error(Some(e.line()), format!("invalid JSON at column {}: {}", e.column(), message))];
+        }
+    };
+    let Some(object) = value.as_object() else {
+        return vec![Finding::error(None, "expected an object with name, description, author and cells")];
+    };
+    
+    let mut findings = Vec::new();
+    for field in ["name", "description", "author"] {
+        match object.get(field) {
+            None => findings.push(Finding::error(None, format!("missing \"{}\"; loading needs it, so add \"{}\": \"\" if it is unknown", field, field))),
+            Some(Value::String(text)) if text.trim().is_empty() && field != "author" => {
+                findings.push(Finding::warning(None, format!("\"{}\" is empty", field)));
+            }
+            Some(Value::String(_)) => {}
+            Some(_) => findings.push(Finding::error(None, format!("\"{}\" must be a string", field))),
+        }
+    }
+    findings.extend(json_cells(object));
+    findings.extend(json_metadata(object));
+    findings
+}
+
+fn json_cells(object: &Map<String, Value>) -> Vec<Finding> {
+    let cells = match object.get("cells") {
+        None => return vec![Finding::error(None, "missing \"cells\", the array of {\"x\": .., \"y\": ..} live cells")],
+        Some(Value::Array(cells)) => cells,
+        Some(_) => return vec![Finding::error(None, "\"cells\" must be an array of {\"x\": .., \"y\": ..} objects")],
+    };
+    if cells.is_empty() {
+        return vec![Finding::warning(None, "\"cells\" is empty, so the pattern adds nothing")];
+    }
+    
+    let mut findings = Vec::new();
+    let mut seen: HashMap<(i64, i64), usize> = HashMap::new();
+    for (index, cell) in cells.iter().enumerate() {
+        let coordinate = |axis: &str| cell.get(axis).and_then(Value::as_i64);
+        let (Some(x), Some(y)) = (coordinate("x"), coordinate("y")) else {
+            findings.push(Finding::error(None, format!("cells[{}] must be an object with integer \"x\" and \"y\", found {}", index, cell)));
+            continue;
+        };
+        if let Some(first) = seen.insert((x, y), index) {
+            seen.insert((x, y), first);
+            findings.push(Finding::error(None, format!("cells[{}] repeats ({}, {}) from cells[{}]; remove one of them", index, x, y, first)));
+        }
+    }
+    findings
+}
+
+fn json_metadata(object: &Map<String, Value>) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let pattern_type = object.get("pattern_type").and_then(Value::as_str);
+    match object.get("pattern_type") {
+        None => findings.push(Finding::warning(None, format!("missing \"pattern_type\" (one of {})", PATTERN_TYPES.join(", ")))),
+        Some(_) if pattern_type.is_some_and(|kind| PATTERN_TYPES.contains(&kind)) => {}
+        Some(other) => findings.push(Finding::warning(None, format!("unknown \"pattern_type\" {}; expected one of {}", other, PATTERN_TYPES.join(", ")))),
+    }
+    
+    match object.get("period").map(Value::as_u64) {
+        None => findings.push(Finding::warning(None, "missing \"period\", the generations it takes to repeat")),
+        Some(Some(period)) if period > 0 => {
+            if pattern_type == Some("still_life") && period != 1 {
+                findings.push(Finding::warning(None, format!("a still life has period 1, not {}", period)));
+            }
+        }
+        Some(_) => findings.push(Finding::warning(None, "\"period\" must be a positive integer")),
+    }
+    
+    match object.get("validation") {
+        None => findings.push(Finding::warning(None, "missing \"validation\" with the expected_behavior and test_steps implementations are checked against")),
+        Some(Value::Object(validation)) => {
+            if !validation.get("expected_behavior").is_some_and(Value::is_string) {
+                findings.push(Finding::warning(None, "\"validation\" has no \"expected_behavior\" text"));
+            }
+            if validation.get("test_steps").and_then(Value::as_u64).is_none_or(|steps| steps == 0) {
+                findings.push(Finding::warning(None, "\"validation\" needs a positive \"test_steps\""));
+            }
+        }
+        Some(_) => findings.push(Finding::warning(None, "\"validation\" must be an object")),
+    }
+    findings
+}
+
+/// Check a run-length encoded pattern: its `#` lines, its `x = .., y = ..`
+/// header and a body that fits the size the header declares.
+pub fn validate_rle(content: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut has_name = false;
+    let mut has_author = false;
+    let mut header: Option<(usize, i64, i64)> = None;
+    let mut body = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let number = index + 1;
+        let trimmed = line.trim();
+        if header.is_some() {
+            body.push((number, line));
+            continue;
+        }
+        if trimmed.is_empty() {
+            continue;
+        }
+        if let Some(comment) = trimmed.strip_prefix('#') {
+            match comment.chars().next() {
+                Some('N') => has_name = !comment[1..].trim().is_empty(),
+                Some('O') => has_author = !comment[1..].trim().is_empty(),
+                Some('C' | 'c' | 'P' | 'R' | 'r') => {}
+                _ => findings.push(Finding::warning(Some(number), format!("unknown comment line {:?}; use #N name, #O author or #C comment", trimmed))),
+            }
+            continue;
+        }
+        if !trimmed.starts_with('x') {
+            findings.push(Finding::error(Some(number), "missing the header line `x = <width>, y = <height>` before the cells"));
+            return findings;
+        }
+        match parse_rle_header(trimmed) {
+            Ok((width, height)) => {
+                if width < 0 || height < 0 {
+                    findings.push(Finding::error(Some(number), format!("header declares a negative size {} x {}", width, height)));
+                }
+                header = Some((number, width, height));
+            }
+            Err(message) => {
+                findings.push(Finding::error(Some(number), message));
+                return findings;
+            }
+        }
+    }
+    let Some((header_line, width, height)) = header else {
+        findings.push(Finding::error(None, "missing the header line `x = <width>, y = <height>` before the cells"));
+        return findings;
+    };
+    
+    findings.extend(rle_body(&body, header_line, width, height));
+    if !has_name {
+        findings.push(Finding::warning(None, "no `#N <name>` line; the pattern is named after the file"));
+    }
+    if !has_author {
+        findings.push(Finding::warning(None, "no `#O <author>` line"));
+    }
+    findings
+}
+
+/// Width and height from `x = 3, y = 3, rule = B3/S23`.
+fn parse_rle_header(line: &str) -> Result<(i64, i64), String> {
+    let mut width = None;
+    let mut height = None;
+    for part in line.split(',') {
+        let Some((key, value)) = part.split_once('=') else {
+            return Err(format!("header part {:?} is not `key = value`; expected `x = <width>, y = <height>`", part.trim()));
+        };
+        let number = || value.trim().parse::<i64>().map_err(|_| format!("header {} = {:?} is not a whole number", key.trim(), value.trim()));
+        match key.trim() {
+            "x" => width = Some(number()?),
+            "y" => height = Some(number()?),
+            "rule" => {}
+            other => return Err(format!("unknown header key {:?}; expected x, y and optionally rule", other)),
+        }
+    }
+    match (width, height) {
+        (Some(width), Some(height)) => Ok((width, height)),
+        _ => Err("header must set both x and y, e.g. `x = 3, y = 3`".to_string()),
+    }
+}
+
+fn rle_body(body: &[(usize, &str)], header_line: usize, width: i64, height: i64) -> Vec<Finding> {
+    let last_line = body.last().map(|&(number, _)| number);
+    let body = match decode_rle_body(body.iter().copied()) {
+        Ok(body) => body,
+        Err(error) => {
+            let message = match error.column {
+                Some(column) => format!("column {}: {}", column, error.message),
+                None => error.message,
+            };
+            return vec![Finding::error(Some(error.line), message)];
+        }
+    };
+    
+    let mut findings = Vec::new();
+    if !body.ended {
+        findings.push(Finding::error(last_line, "the pattern never ends; add ! after the last row"));
+    }
+    // Extent of the live cells, which must fit the header's size
+    let widest = body.cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+    let tallest = body.cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
+    if widest > width {
+        findings.push(Finding::error(Some(header_line), format!("live cells span {} columns but the header says x = {}; raise x", widest, width)));
+    }
+    if tallest > height {
+        findings.push(Finding::error(Some(header_line), format!("live cells span {} rows but the header says y = {}; raise y", tallest, height)));
+    }
+    if body.cells.is_empty() {
+        findings.push(Finding::warning(None, "no live cells, so the pattern adds nothing"));
+    }
+    findings
+}
+
+/// Check a plaintext `.cells` pattern: `!` comment lines, then rows of `.`
+/// for dead and `O` for live cells.
+pub fn validate_plaintext(content: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut has_name = false;
+    let mut live = 0;
+    for (index, line) in content.lines().enumerate() {
+        let number = index + 1;
+        if let Some(comment) = line.strip_prefix('!') {
+            has_name |= comment.trim_start().strip_prefix("Name:").is_some_and(|name| !name.trim().is_empty());
+            continue;
+        }
+        for (column, c) in line.trim_end().chars().enumerate() {
+            match c {
+                '.' => {}
+                'O' | '*' => live += 1,
+                other => findings.push(Finding::error(
+                    Some(number),
+                    format!("column {}: {:?} is not a cell; use . for dead and O for live cells, and start comments with !", column + 1, other),
+                )),
+            }
+        }
+    }
+    if live == 0 && findings.is_empty() {
+        findings.push(Finding::warning(None, "no live cells, so the pattern adds nothing"));
+    }
+    if !has_name {
+        findings.push(Finding::warning(None, "no `!Name: <name>` line; the pattern is named after the file"));
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn messages(findings: &[Finding]) -> Vec<&str> {
+        findings.iter().map(|finding| finding.message.as_str()).collect()
+    }
+    
+    #[test]
+    fn test_complete_patterns_have_no_findings() {
+        let json = r#"{
+            "name": "Block", "description": "A still life", "author": "",
+            "cells": [{"x": 0, "y": 0}, {"x": 1, "y": 0}, {"x": 0, "y": 1}, {"x": 1, "y": 1}],
+            "pattern_type": "still_life", "period": 1,
+            "validation": {"expected_behavior": "Stays put", "test_steps": 10}
+        }"#;
+        assert_eq!(validate_json(json), Vec::new());
+        assert_eq!(validate_rle("#N Block\n#O John Conway\nx = 2, y = 2, rule = B3/S23\n2o$2o!\n"), Vec::new());
+        assert_eq!(validate_plaintext("!Name: Block\nOO\nOO\n"), Vec::new());
+    }
+    
+    #[test]
+    fn test_json_findings() {
+        let findings = validate_json("{\"name\": 3");
+        assert_eq!(findings.len(), 1);
+        assert_eq!((findings[0].severity, findings[0].line), (Severity::Error, Some(1)));
+        assert!(findings[0].message.starts_with("invalid JSON at column"), "{}", findings[0]);
+        
+        let findings = validate_json(r#"{"name": 3, "description": " ", "cells": [{"x": 0, "y": 0}, {"x": 0, "y": 0}, {"x": "a"}], "period": 0}"#);
+        let messages = messages(&findings);
+        assert!(messages.contains(&"\"name\" must be a string"), "{:?}", messages);
+        assert!(messages.contains(&"\"description\" is empty"));
+        assert!(messages.iter().any(|message| message.starts_with("missing \"author\"")));
+        assert!(messages.contains(&"cells[1] repeats (0, 0) from cells[0]; remove one of them"));
+        assert!(messages.iter().any(|message| message.starts_with("cells[2] must be an object")));
+        assert!(messages.contains(&"\"period\" must be a positive integer"));
+        
+        assert_eq!(validate_json("[]")[0].severity, Severity::Error);
+    }
+    
+    #[test]
+    fn test_rle_findings() {
+        let findings = validate_rle("#N Glider\n#O Guy\nx = 2, y = 2\nbo$2bo$3o\n");
+        assert_eq!(findings, vec![
+            Finding::error(Some(4), "the pattern never ends; add ! after the last row"),
+            Finding::error(Some(3), "live cells span 3 columns but the header says x = 2; raise x"),
+            Finding::error(Some(3), "live cells span 3 rows but the header says y = 2; raise y"),
+        ]);
+        
+        let findings = validate_rle("#N Blinker\n#O Conway\nx = 3, y = 1\n\n3o0b!\n");
+        assert_eq!(findings, vec![Finding::error(Some(5), "column 4: run count 0; leave the tag out instead")]);
+        
+        let findings = validate_rle("#Q what\nx = 1, y = 1\nb!\n");
+        assert_eq!(findings.iter().map(|finding| finding.severity).collect::<Vec<_>>(), vec![Severity::Warning; 4]);
+        assert!(messages(&findings).contains(&"no live cells, so the pattern adds nothing"));
+        
+        assert_eq!(validate_rle("3o!\n"), vec![Finding::error(Some(1), "missing the header line `x = <width>, y = <height>` before the cells")]);
+        assert_eq!(validate_rle("x = 3\n3o!\n"), vec![Finding::error(Some(1), "header must set both x and y, e.g. `x = 3, y = 3`")]);
+    }
+    
+    #[test]
+    fn test_plaintext_findings() {
+        assert_eq!(validate_plaintext("!Name: Dot\n.O\nO#\n"), vec![
+            Finding::error(Some(3), "column 2: '#' is not a cell; use . for dead and O for live cells, and start comments with !"),
+        ]);
+        assert_eq!(validate_plaintext("..\n"), vec![
+            Finding::warning(None, "no live cells, so the pattern adds nothing"),
+            Finding::warning(None, "no `!Name: <name>` line; the pattern is named after the file"),
+        ]);
+    }
+}
@@ -0,0 +1,120 @@
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+use crate::client::GameOfLifeClient;
+
+/// Environment variable naming the directory servers register in; the
+/// servers read the same one.
+pub const REGISTRY_DIR_ENV_VAR: &str = "GOL_REGISTRY_DIR";
+
+/// Environment variable overriding the ports probed, as `first-last`.
+pub const DISCOVERY_PORTS_ENV_VAR: &str = "GOL_DISCOVERY_PORTS";
+
+/// Ports probed when `GOL_DISCOVERY_PORTS` is unset: the usual bevy, entt
+/// and flecs ports and a few more for extra instances.
+pub const DEFAULT_DISCOVERY_PORTS: RangeInclusive<u16> = 50051..=50060;
+
+/// Hosts each port is probed on. Servers may listen on either loopback
+/// address, and `localhost` only tries one of them.
+const PROBE_HOSTS: [&str; 2] = ["127.0.0.1", "[::1]"];
+
+/// Longest a probe may take, including connecting.
+const PROBE_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// A server that answered GetStatus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredBackend {
+    /// Implementation the server reported, e.g. `bevy`.
+    pub implementation: String,
+    pub host: String,
+    pub port: u16,
+}
+
+impl DiscoveredBackend {
+    pub fn client(&self) -> GameOfLifeClient {
+        GameOfLifeClient::new(self.implementation.clone(), self.host.clone(), self.port)
+    }
+
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+/// Contents of a server's registry file.
+#[derive(Debug, Deserialize)]
+struct RegistryEntry {
+    address: String,
+}
+
+/// Running servers, found by probing the registry directory's entries and
+/// the discovery port range on this machine. Sorted by port, then host.
+pub async fn discover_backends() -> Vec<DiscoveredBackend> {
+    let mut candidates: BTreeSet<(String, u16)> = registered_addresses().into_iter().collect();
+    for port in discovery_ports() {
+        candidates.extend(PROBE_HOSTS.iter().map(|host| (host.to_string(), port)));
+    }
+
+    let mut probes = JoinSet::new();
+    for (host, port) in candidates {
+        probes.spawn(probe(host, port));
+    }
+    let mut found = Vec::new();
+    while let Some(result) = probes.join_next().await {
+        if let Ok(Some(backend)) = result {
+            found.push(backend);
+        }
+    }
+    found.sort_by(|a, b| (a.port, &a.host).cmp(&(b.port, &b.host)));
+    // A server listening on all interfaces answers on both loopback addresses
+    found.dedup_by(|a, b| a.port == b.port && a.implementation == b.implementation);
+    found
+}
+
+/// The server at `host:port`, if it answers GetStatus in time.
+async fn probe(host: String, port: u16) -> Option<DiscoveredBackend> {
+    let mut client = GameOfLifeClient::new(String::new(), host.clone(), port).with_timeout(PROBE_TIMEOUT);
+    let status = tokio::time::timeout(PROBE_TIMEOUT, async {
+        client.connect().await?;
+        client.get_status().await
+    }).await.ok()?.ok()?;
+    Some(DiscoveredBackend { implementation: status.implementation, host, port })
+}
+
+/// Host and port of every readable registry file, skipping malformed ones.
+fn registered_addresses() -> Vec<(String, u16)> {
+    let Ok(files) = std::fs::read_dir(registry_dir()) else {
+        return Vec::new();
+    };
+    files.filter_map(|file| {
+        let content = std::fs::read_to_string(file.ok()?.path()).ok()?;
+        let entry: RegistryEntry = serde_json::from_str(&content).ok()?;
+        let (host, port) = entry.address.rsplit_once(':')?;
+        Some((host.to_string(), port.parse().ok()?))
+    }).collect()
+}
+
+/// `GOL_REGISTRY_DIR`, else `gol-servers` in the system temporary directory,
+/// as the servers use.
+pub fn registry_dir() -> PathBuf {
+    std::env::var_os(REGISTRY_DIR_ENV_VAR)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::temp_dir().join("gol-servers"))
+}
+
+/// `GOL_DISCOVERY_PORTS` as `first-last` or a single port, else the default
+/// range.
+pub fn discovery_ports() -> RangeInclusive<u16> {
+    std::env::var(DISCOVERY_PORTS_ENV_VAR).ok()
+        .and_then(|value| parse_port_range(&value))
+        .unwrap_or(DEFAULT_DISCOVERY_PORTS)
+}
+
+fn parse_port_range(value: &str) -> Option<RangeInclusive<u16>> {
+    let (first, last) = value.trim().split_once('-').unwrap_or((value.trim(), value.trim()));
+    let (first, last): (u16, u16) = (first.trim().parse().ok()?, last.trim().parse().ok()?);
+    (first <= last).then_some(first..=last)
+}
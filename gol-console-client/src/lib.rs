@@ -0,0 +1,6 @@
+pub mod cache;
+pub mod client;
+pub mod commands;
+pub mod discovery;
+pub mod profiles;
+pub mod ui;
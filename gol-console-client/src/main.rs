@@ -1,23 +1,39 @@
 use clap::{Parser, Subcommand};
 use anyhow::Result;
 
-mod client;
-mod ui;
-mod commands;
+use gol_console_client::{client, commands, profiles, ui};
 
 #[derive(Parser)]
 #[command(name = "gol-console-client")]
 #[command(about = "A Game of Life console client supporting multiple gRPC backends")]
 #[command(version = "0.1.0")]
 struct Cli {
-    #[arg(long, default_value = "bevy")]
-    backend: String,
+    #[arg(long, global = true, help = "Connection profile from the client config, or bevy, entt or flecs [default: the config's default_profile, else bevy]")]
+    profile: Option<String>,
     
-    #[arg(long, default_value = "50051")]
-    port: u16,
+    #[arg(long, global = true, help = "Same as --profile, for the built-in bevy, entt and flecs profiles")]
+    backend: Option<String>,
     
-    #[arg(long, default_value = "localhost")]
-    host: String,
+    #[arg(long, global = true, help = "Server port [default: the profile's; 50051 for bevy, 50052 for entt, 50053 for flecs]")]
+    port: Option<u16>,
+    
+    #[arg(long, global = true, help = "Server host [default: the profile's, else localhost]")]
+    host: Option<String>,
+    
+    #[arg(long, global = true, help = "Token identifying this client to server quotas [default: $GOL_TOKEN]")]
+    token: Option<String>,
+    
+    #[arg(long, global = true, default_value = "none", help = "Compress calls with gzip or zstd, or none")]
+    compression: String,
+    
+    #[arg(long, help = "Color theme: classic, solarized, high-contrast or monochrome [default: $GOL_THEME, else classic]")]
+    theme: Option<String>,
+    
+    #[arg(long, help = "Keys: arrows, vim, wasd or emacs [default: $GOL_KEYMAP, else the config's keymap, else arrows]")]
+    keymap: Option<String>,
+    
+    #[arg(long, default_value = "auto", help = "Draw the grid as pixels: auto, kitty, sixel or off")]
+    graphics: String,
     
     #[command(subcommand)]
     command: Option<Commands>,
@@ -29,43 +45,306 @@ enum Commands {
         #[arg(help = "Path to pattern file")]
         pattern: String,
     },
+    Export {
+        #[arg(help = "Simulation ID")]
+        simulation: String,
+        #[arg(help = "Output file (.mc for Golly macrocell, otherwise RLE)")]
+        path: String,
+    },
+    ExportStats {
+        #[arg(help = "Simulation ID")]
+        simulation: String,
+        #[arg(help = "Output file (.json for JSON, otherwise CSV)")]
+        path: String,
+        #[arg(long, help = "Generation to start after [default: first in the replay log]")]
+        from: Option<i64>,
+        #[arg(long, help = "Last generation [default: current]")]
+        to: Option<i64>,
+    },
     Run {
         #[arg(short, long, help = "Number of generations to run")]
         generations: Option<u32>,
         #[arg(short, long, help = "Delay between generations in ms")]
         delay: Option<u64>,
     },
+    Bench {
+        #[arg(short, long, default_value = "glider-gun", help = "Pattern name, file or apgcode")]
+        pattern: String,
+        #[arg(short, long, default_value = "10000", help = "Generations to step")]
+        generations: u32,
+        #[arg(long, default_value = "100", help = "Generations per StepSimulation call")]
+        batch: u32,
+        #[arg(long, default_value = "200", help = "Grid width and height")]
+        size: i32,
+        #[arg(long, help = "Step inside the server with RunBenchmark, timing no network (patterns: glider-gun, acorn, r-pentomino, diehard)")]
+        in_process: bool,
+    },
+    Check {
+        #[arg(short, long, default_value = "acorn", help = "Pattern name, file or apgcode")]
+        pattern: String,
+        #[arg(short, long, default_value = "100", help = "Generations to step before comparing")]
+        generations: u32,
+        #[arg(long, default_value = "200", help = "Grid width and height")]
+        size: i32,
+        #[arg(long, help = "Check every discovered backend against the first instead of only --backend")]
+        all: bool,
+    },
     Status,
+    Discover,
+    Webhook {
+        #[arg(help = "Simulation ID")]
+        simulation: String,
+        #[arg(help = "http:// URL to POST milestones to; omit to remove the simulation's webhooks")]
+        url: Option<String>,
+        #[arg(long, value_delimiter = ',', default_value = "completion,extinction,stabilization", help = "Milestones to notify")]
+        on: Vec<String>,
+        #[arg(long, default_value = "0", help = "Also notify every this many generations")]
+        every: u64,
+    },
+    Watch {
+        #[arg(help = "Simulation IDs to watch [default: all]")]
+        simulations: Vec<String>,
+    },
+    Batch {
+        #[command(subcommand)]
+        action: BatchAction,
+    },
+    Experiment {
+        #[arg(help = "Simulation ID")]
+        simulation: String,
+        #[arg(required = true, help = "Variants of +-joined rule=<rule>, soup=<density>[@seed] and pattern=<pattern>[@x,y]")]
+        variants: Vec<String>,
+        #[arg(short, long, default_value = "1000", help = "Generations each copy steps")]
+        generations: i64,
+    },
+    Pattern {
+        #[command(subcommand)]
+        action: PatternAction,
+    },
     Stop,
+    Profiles,
     Interactive,
+    Attach {
+        #[arg(help = "Simulation ID")]
+        simulation: String,
+        #[arg(long, help = "Allow stepping, running, placing patterns and changing settings")]
+        write: bool,
+    },
+    Demo {
+        #[arg(long, default_value = "160", help = "Grid width")]
+        width: i32,
+        #[arg(long, default_value = "100", help = "Grid height")]
+        height: i32,
+        #[arg(short, long, default_value = "100", help = "Milliseconds between generations")]
+        interval: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum BatchAction {
+    /// Queue runs of a pattern, each seeded differently, on the server
+    Submit {
+        #[arg(help = "Standard pattern name, apgcode or pattern file (.rle, .json, .cells, .mc); empty for soup only")]
+        pattern: String,
+        #[arg(short, long, default_value = "1000", help = "Generations per run")]
+        generations: i64,
+        #[arg(short, long, default_value = "1", help = "Number of runs")]
+        repetitions: i32,
+        #[arg(long, default_value = "0", help = "Seed of the first run; run i uses seed + i")]
+        seed: u64,
+        #[arg(long, default_value = "0", help = "Chance of each soup cell being alive (0 for no soup)")]
+        density: f64,
+        #[arg(long, default_value = "16", help = "Side of the random soup square")]
+        soup_size: i32,
+        #[arg(long, default_value = "200", help = "Grid width and height")]
+        size: i32,
+        #[arg(long, default_value = "", help = "Cell rule [default: B3/S23]")]
+        rule: String,
+        #[arg(long, default_value = "", help = "Label shown in listings")]
+        name: String,
+    },
+    /// List submitted batches
+    List,
+    /// Show the finished runs of a batch
+    Results {
+        #[arg(help = "Batch ID")]
+        batch: String,
+    },
+    /// Stop a batch's remaining runs
+    Cancel {
+        #[arg(help = "Batch ID")]
+        batch: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum PatternAction {
+    /// List the patterns directory with a thumbnail of each pattern
+    List {
+        #[arg(long, help = "Draw thumbnails in ASCII rather than braille")]
+        ascii: bool,
+    },
+    /// Check pattern files for syntax errors, duplicate cells, sizes that do not fit and missing metadata
+    Validate {
+        #[arg(required = true, help = "Pattern files (.json, .rle or .cells)")]
+        files: Vec<String>,
+    },
+    /// Convert a pattern between JSON, RLE, plaintext and macrocell, picking formats by extension
+    Convert {
+        #[arg(help = "Pattern file to read (.json, .rle, .cells or .mc)")]
+        input: String,
+        #[arg(help = "Pattern file to write (.json, .rle, .cells or .mc)")]
+        output: String,
+        #[arg(long, help = "Move the pattern so its bounding box starts at (0, 0)")]
+        normalize: bool,
+    },
+    /// Write a reproducible random soup; the same seed gives the soup a batch run with that seed starts from
+    Random {
+        #[arg(long, default_value = "16", help = "Soup width")]
+        width: i32,
+        #[arg(long, default_value = "16", help = "Soup height")]
+        height: i32,
+        #[arg(long, default_value = "0.5", help = "Chance of each cell being alive")]
+        density: f64,
+        #[arg(long, default_value = "0", help = "Seed of the generator")]
+        seed: u64,
+        #[arg(long, help = "Rule recorded in RLE and macrocell files")]
+        rule: Option<String>,
+        #[arg(long, help = "Pattern file to write (.json, .rle, .cells or .mc)")]
+        out: String,
+    },
+    /// Download a pattern's RLE from LifeWiki, or from Catagolue for an apgcode, into ../patterns/fetched (needs the fetch feature)
+    Fetch {
+        #[arg(help = "LifeWiki pattern name or apgcode")]
+        pattern: String,
+        #[arg(long, default_value = "b3s23", help = "Catagolue rule an apgcode is looked up under")]
+        rule: String,
+        #[arg(long, help = "Download again even if the pattern is cached")]
+        refresh: bool,
+    },
+    /// Import every RLE, plaintext and macrocell pattern in a zip collection, such as Golly's, into ../patterns/imported
+    Import {
+        #[arg(help = "Zip archive of patterns")]
+        archive: String,
+        #[arg(long, help = "Collection to import into [default: the archive's name]")]
+        name: Option<String>,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
     
-    let mut client = client::GameOfLifeClient::new(
-        cli.backend.clone(),
-        cli.host.clone(),
-        cli.port,
-    );
+    let config = profiles::ClientConfig::load()?;
+    let (profile_name, profile) = config.profile(cli.profile.as_deref().or(cli.backend.as_deref()))?;
+    let mut client = client::GameOfLifeClient::from_profile(&profile)
+        .with_token(cli.token.clone())
+        .with_compression(client::parse_compression(&cli.compression).map_err(anyhow::Error::msg)?);
+    if let Some(host) = &cli.host {
+        client.host = host.clone();
+    }
+    if let Some(port) = cli.port {
+        client.port = port;
+    }
     
     match &cli.command {
         Some(Commands::Load { pattern }) => {
             commands::handle_load_command(&mut client, pattern).await?;
         }
+        Some(Commands::Export { simulation, path }) => {
+            commands::handle_export_command(&mut client, simulation, path).await?;
+        }
+        Some(Commands::ExportStats { simulation, path, from, to }) => {
+            commands::handle_export_stats_command(&mut client, simulation, path, *from, *to).await?;
+        }
         Some(Commands::Run { generations, delay }) => {
             commands::handle_run_command(&mut client, *generations, *delay).await?;
         }
+        Some(Commands::Bench { pattern, generations, batch, size, in_process }) => {
+            let options = commands::bench::BenchOptions {
+                pattern: pattern.clone(),
+                generations: *generations,
+                batch: *batch,
+                size: *size,
+                in_process: *in_process,
+            };
+            commands::handle_bench_command(&mut client, options).await?;
+        }
+        Some(Commands::Check { pattern, generations, size, all }) => {
+            commands::handle_check_command(&mut client, pattern, *generations, *size, *all).await?;
+        }
         Some(Commands::Status) => {
             let status = commands::handle_status_command(&mut client).await?;
             println!("{}", status);
         }
+        Some(Commands::Discover) => {
+            commands::handle_discover_command().await?;
+        }
+        Some(Commands::Webhook { simulation, url, on, every }) => {
+            commands::handle_webhook_command(&mut client, simulation, url.as_deref(), on, *every).await?;
+        }
+        Some(Commands::Watch { simulations }) => {
+            commands::handle_watch_command(&mut client, simulations).await?;
+        }
+        Some(Commands::Batch { action }) => match action {
+            BatchAction::Submit { pattern, generations, repetitions, seed, density, soup_size, size, rule, name } => {
+                let spec = client::game_of_life::BatchSpec {
+                    name: name.clone(),
+                    pattern: commands::batch::batch_pattern(pattern)?,
+                    rule: rule.clone(),
+                    width: *size,
+                    height: *size,
+                    generations: *generations,
+                    repetitions: *repetitions,
+                    seed: *seed,
+                    soup_density: *density,
+                    soup_size: *soup_size,
+                };
+                commands::batch::submit(&mut client, spec).await?;
+            }
+            BatchAction::List => commands::batch::list(&mut client).await?,
+            BatchAction::Results { batch } => commands::batch::results(&mut client, batch).await?,
+            BatchAction::Cancel { batch } => commands::batch::cancel(&mut client, batch).await?,
+        },
+        Some(Commands::Experiment { simulation, variants, generations }) => {
+            let variants = variants.iter().map(|variant| commands::experiment::parse_variant(variant)).collect::<Result<Vec<_>>>()?;
+            commands::experiment::run(&mut client, simulation, variants, *generations).await?;
+        }
+        Some(Commands::Pattern { action }) => match action {
+            PatternAction::List { ascii } => commands::thumbnail::list_patterns(*ascii)?,
+            PatternAction::Validate { files } => commands::validate::validate_files(files)?,
+            PatternAction::Convert { input, output, normalize } => commands::convert::convert_file(input, output, *normalize)?,
+            PatternAction::Random { width, height, density, seed, rule, out } => {
+                commands::soup::write_random_soup(*width, *height, *density, *seed, rule.as_deref(), out)?;
+            }
+            PatternAction::Fetch { pattern, rule, refresh } => {
+                commands::fetch::fetch_pattern(pattern, rule, *refresh).await?;
+            }
+            PatternAction::Import { archive, name } => commands::import::import_archive(archive, name.as_deref())?,
+        },
         Some(Commands::Stop) => {
             commands::handle_stop_command(&mut client).await?;
         }
+        Some(Commands::Profiles) => {
+            commands::handle_profiles_command(&config, &profile_name);
+        }
         Some(Commands::Interactive) => {
-            commands::handle_interactive_command(&mut client).await?;
+            let theme = ui::theme::configured_theme(cli.theme.as_deref()).map_err(anyhow::Error::msg)?;
+            let graphics = ui::graphics::requested_protocol(&cli.graphics).map_err(anyhow::Error::msg)?;
+            let keymap = ui::keymap::configured_keymap(cli.keymap.as_deref(), config.keymap.as_deref(), &config.keys).map_err(anyhow::Error::msg)?;
+            let simulation = profile.simulation.as_deref().unwrap_or("default");
+            commands::handle_interactive_command(&mut client, theme, keymap, graphics, simulation).await?;
+        }
+        Some(Commands::Attach { simulation, write }) => {
+            let theme = ui::theme::configured_theme(cli.theme.as_deref()).map_err(anyhow::Error::msg)?;
+            let graphics = ui::graphics::requested_protocol(&cli.graphics).map_err(anyhow::Error::msg)?;
+            let keymap = ui::keymap::configured_keymap(cli.keymap.as_deref(), config.keymap.as_deref(), &config.keys).map_err(anyhow::Error::msg)?;
+            commands::handle_attach_command(&mut client, theme, keymap, graphics, simulation, *write).await?;
+        }
+        Some(Commands::Demo { width, height, interval }) => {
+            let theme = ui::theme::configured_theme(cli.theme.as_deref()).map_err(anyhow::Error::msg)?;
+            let graphics = ui::graphics::requested_protocol(&cli.graphics).map_err(anyhow::Error::msg)?;
+            commands::handle_demo_command(&mut client, theme, graphics, *width, *height, *interval).await?;
         }
         None => {
             println!("No command specified. Use --help for available commands.");
@@ -0,0 +1,125 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Environment variable naming the client config file.
+pub const CONFIG_ENV_VAR: &str = "GOL_CLIENT_CONFIG";
+
+/// Profile used when neither `--profile`, `--backend` nor the config's
+/// `default_profile` names one.
+pub const DEFAULT_PROFILE: &str = "bevy";
+
+/// Profiles that exist without a config file: each implementation on its
+/// usual local port. The config may override them.
+const BUILT_IN_PROFILES: [(&str, u16); 3] = [("bevy", 50051), ("entt", 50052), ("flecs", 50053)];
+
+/// Where to reach a server and how to talk to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConnectionProfile {
+    /// Implementation the server runs, as the status bar shows it.
+    pub backend: String,
+    pub host: String,
+    pub port: u16,
+    /// Connect over TLS, checking the server's certificate against the
+    /// web's root certificates (requires the `tls` feature).
+    pub tls: bool,
+    /// Identifies this client to server quotas; `$GOL_TOKEN` when unset.
+    pub token: Option<String>,
+    /// Simulation the interactive UI opens first; `default` when unset.
+    pub simulation: Option<String>,
+}
+
+impl Default for ConnectionProfile {
+    fn default() -> Self {
+        Self {
+            backend: DEFAULT_PROFILE.to_string(),
+            host: "localhost".to_string(),
+            port: 50051,
+            tls: false,
+            token: None,
+            simulation: None,
+        }
+    }
+}
+
+impl ConnectionProfile {
+    pub fn address(&self) -> String {
+        let scheme = if self.tls { "https" } else { "http" };
+        format!("{}://{}:{}", scheme, self.host, self.port)
+    }
+}
+
+/// The client config file, a JSON object such as
+/// `{"default_profile": "lab", "profiles": {"lab": {"host": "lab.example.com", "port": 443, "tls": true}}, "keymap": "vim", "keys": {"f5": "step"}}`.
+/// Fields left out take their defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClientConfig {
+    /// Profile used when none is asked for.
+    pub default_profile: Option<String>,
+    pub profiles: BTreeMap<String, ConnectionProfile>,
+    /// Key preset for the interactive UI: arrows, vim, wasd or emacs.
+    pub keymap: Option<String>,
+    /// Keys bound on top of the preset, from key names such as `x`,
+    /// `ctrl+r` or `f5` to action names such as `step`, or `none`.
+    pub keys: BTreeMap<String, String>,
+}
+
+impl ClientConfig {
+    /// Read the config file; an empty config when there is none.
+    pub fn load() -> Result<Self> {
+        let Some(path) = config_path() else {
+            return Ok(Self::default());
+        };
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        serde_json::from_str(&text).with_context(|| format!("Invalid client config {}", path.display()))
+    }
+
+    /// The config, or an empty one when it cannot be read, for lookups with
+    /// nowhere to report the error.
+    pub fn load_or_default() -> Self {
+        Self::load().unwrap_or_default()
+    }
+
+    /// Every profile by name: the built-in ones and the config's.
+    pub fn profiles(&self) -> BTreeMap<String, ConnectionProfile> {
+        let mut profiles: BTreeMap<String, ConnectionProfile> = BUILT_IN_PROFILES.iter()
+            .map(|&(backend, port)| (backend.to_string(), ConnectionProfile { backend: backend.to_string(), port, ..Default::default() }))
+            .collect();
+        profiles.extend(self.profiles.clone());
+        profiles
+    }
+
+    /// The profile `name`, else the config's default profile, else
+    /// [`DEFAULT_PROFILE`], with its name.
+    pub fn profile(&self, name: Option<&str>) -> Result<(String, ConnectionProfile)> {
+        let name = name.or(self.default_profile.as_deref()).unwrap_or(DEFAULT_PROFILE);
+        let profiles = self.profiles();
+        match profiles.get(name) {
+            Some(profile) => Ok((name.to_string(), profile.clone())),
+            None => {
+                let known: Vec<&str> = profiles.keys().map(String::as_str).collect();
+                anyhow::bail!("Unknown profile '{}'; profiles are {}", name, known.join(", "))
+            }
+        }
+    }
+}
+
+/// The config file: `$GOL_CLIENT_CONFIG`, else `gol/client.json` under
+/// `$XDG_CONFIG_HOME` or `~/.config`.
+pub fn config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(CONFIG_ENV_VAR).filter(|path| !path.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("gol").join("client.json"))
+}
@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Clear, Paragraph},
+    Frame,
+};
+
+use crate::client::GameOfLifeClient;
+use super::theme::Theme;
+
+/// How often the server is probed with GetStatus.
+const PROBE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Longest a probe may take, including connecting, before the server is
+/// considered unreachable. Short, as the UI waits on it.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Round trips the shown latency is averaged over.
+const LATENCY_SAMPLES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionState {
+    /// Not probed yet.
+    Connecting,
+    Connected,
+    /// The last connection attempt or RPC failed, with why.
+    Disconnected(String),
+}
+
+/// Tracks whether the backend is reachable, how long RPCs take and how fast
+/// it steps, from a periodic GetStatus probe and the RPCs the UI times
+/// itself. The probe keeps its own connection and reconnects after a
+/// failure.
+pub struct ConnectionMonitor {
+    backend: String,
+    address: String,
+    state: ConnectionState,
+    latencies: VecDeque<Duration>,
+    probe_client: Option<GameOfLifeClient>,
+    last_probe: Option<Instant>,
+    /// Generations per second the server reported at the last probe,
+    /// summed over its simulations.
+    server_rate: Option<f64>,
+}
+
+impl ConnectionMonitor {
+    pub fn new(client: &GameOfLifeClient) -> Self {
+        Self {
+            backend: client.backend.clone(),
+            address: format!("{}:{}", client.host, client.port),
+            state: ConnectionState::Connecting,
+            latencies: VecDeque::with_capacity(LATENCY_SAMPLES),
+            probe_client: None,
+            last_probe: None,
+            server_rate: None,
+        }
+    }
+    
+    pub fn state(&self) -> &ConnectionState {
+        &self.state
+    }
+    
+    /// Mean of the recent round trips, if any succeeded since the last
+    /// failure.
+    pub fn latency(&self) -> Option<Duration> {
+        let total: Duration = self.latencies.iter().sum();
+        (!self.latencies.is_empty()).then(|| total / self.latencies.len() as u32)
+    }
+    
+    pub fn server_rate(&self) -> Option<f64> {
+        self.server_rate
+    }
+    
+    pub fn probe_due(&self) -> bool {
+        self.last_probe.is_none_or(|last| last.elapsed() >= PROBE_INTERVAL)
+    }
+    
+    /// Time a GetStatus round trip against `client`'s backend, connecting
+    /// first if the last probe failed.
+    pub async fn probe(&mut self, client: &GameOfLifeClient) {
+        self.last_probe = Some(Instant::now());
+        let result = tokio::time::timeout(PROBE_TIMEOUT, async {
+            let mut probe_client = match self.probe_client.take() {
+                Some(probe_client) => probe_client,
+                None => {
+                    let mut probe_client = client.clone();
+                    probe_client.connect().await?;
+                    probe_client
+                }
+            };
+            let started = Instant::now();
+            let status = probe_client.get_status().await?;
+            let latency = started.elapsed();
+            self.probe_client = Some(probe_client);
+            anyhow::Ok((latency, status.generations_per_second))
+        }).await;
+        
+        match result {
+            Ok(Ok((latency, server_rate))) => {
+                self.server_rate = Some(server_rate);
+                self.record_success(latency);
+            }
+            Ok(Err(error)) => self.record_failure(&error),
+            Err(_) => self.record_failure(&anyhow::anyhow!("no answer within {:?}", PROBE_TIMEOUT)),
+        }
+    }
+    
+    pub fn record_success(&mut self, latency: Duration) {
+        if self.latencies.len() == LATENCY_SAMPLES {
+            self.latencies.pop_front();
+        }
+        self.latencies.push_back(latency);
+        self.state = ConnectionState::Connected;
+    }
+    
+    /// Note a failed connection or RPC. The probe reconnects on its next
+    /// run.
+    pub fn record_failure(&mut self, error: &anyhow::Error) {
+        self.latencies.clear();
+        self.probe_client = None;
+        self.server_rate = None;
+        self.state = ConnectionState::Disconnected(error.root_cause().to_string());
+    }
+    
+    /// Backend, state and latency, e.g. `bevy localhost:50051 ● 1.2 ms`.
+    pub fn label(&self) -> String {
+        let state = match (&self.state, self.latency()) {
+            (ConnectionState::Connecting, _) => "connecting…".to_string(),
+            (ConnectionState::Connected, Some(latency)) => format!("{:.1} ms", latency.as_secs_f64() * 1000.0),
+            (ConnectionState::Connected, None) => "connected".to_string(),
+            (ConnectionState::Disconnected(reason), _) => format!("disconnected: {}", reason),
+        };
+        format!("{} {} ● {}", self.backend, self.address, state)
+    }
+    
+    /// Draw the label right-aligned in `area`, a single row.
+    pub fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let label = format!(" {} ", self.label());
+        let width = (label.chars().count() as u16).min(area.width);
+        let area = Rect { x: area.right() - width, width, height: 1, ..area };
+        let color = match self.state {
+            ConnectionState::Connected => theme.connected,
+            ConnectionState::Connecting => theme.muted,
+            ConnectionState::Disconnected(_) => theme.disconnected,
+        };
+        
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(Line::from(Span::styled(label, Style::default().fg(color)))), area);
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::{HashMap, VecDeque};
+
+/// Patterns the demo shows in turn, as inline RLE.
+const SHOWCASE: [(&str, &str); 7] = [
+    ("R-pentomino", "b2o$2o$bo!"),
+    ("Acorn", "bo$3bo$2o2b3o!"),
+    ("Gosper glider gun", "24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!"),
+    ("Diehard", "6bo$2o$bo3b3o!"),
+    ("Pi-heptomino", "3o$obo$obo!"),
+    ("B-heptomino", "ob2o$3o$bo!"),
+    ("Lightweight spaceship", "bo2bo$o$o3bo$4o!"),
+];
+
+/// Longest period the stabilization check recognizes.
+const MAX_PERIOD: usize = 30;
+
+/// Populations kept for the stabilization check: the last period must
+/// repeat across all of them.
+const POPULATION_WINDOW: usize = 4 * MAX_PERIOD;
+
+/// Generations a pattern runs before the next one is shown, whether or not
+/// it has settled.
+const MAX_GENERATIONS: i64 = 2000;
+
+/// Fraction of the way the camera moves toward its target each frame.
+const EASING: f64 = 0.06;
+
+/// Frames spent on the whole population before closing in on its busiest
+/// region, and back.
+const SHOT_FRAMES: u32 = 240;
+
+/// Side of the squares live cells are counted in to find the busiest region.
+const TILE: i64 = 16;
+
+/// Drives the `demo` screensaver: which showcase pattern is up, when it has
+/// settled, and where the camera drifts.
+#[derive(Debug, Clone)]
+pub struct DemoDirector {
+    next: usize,
+    /// Name of the pattern being shown.
+    current: &'static str,
+    /// Population of each generation seen since the pattern was seeded,
+    /// most recent last.
+    populations: VecDeque<i64>,
+    /// Camera center and zoom, eased toward the target every frame.
+    camera: (f64, f64, f64),
+    /// Frames into the current shot, and whether it is a close-up.
+    shot: (u32, bool),
+}
+
+impl Default for DemoDirector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DemoDirector {
+    pub fn new() -> Self {
+        Self {
+            next: 0,
+            current: SHOWCASE[0].0,
+            populations: VecDeque::new(),
+            camera: (0.0, 0.0, 1.0),
+            shot: (0, false),
+        }
+    }
+    
+    /// Name of the pattern being shown.
+    pub fn current(&self) -> &'static str {
+        self.current
+    }
+    
+    /// The next showcase pattern's name and RLE, starting over after the
+    /// last. Observations of the previous one are forgotten.
+    pub fn next_pattern(&mut self) -> (&'static str, &'static str) {
+        let (name, rle) = SHOWCASE[self.next];
+        self.next = (self.next + 1) % SHOWCASE.len();
+        self.current = name;
+        self.populations.clear();
+        self.shot = (0, false);
+        (name, rle)
+    }
+    
+    /// Record the population after a step.
+    pub fn observe(&mut self, population: i64) {
+        self.populations.push_back(population);
+        if self.populations.len() > POPULATION_WINDOW {
+            self.populations.pop_front();
+        }
+    }
+    
+    /// Whether the pattern has died out, run too long, or settled into a
+    /// population that repeats with a period of at most `MAX_PERIOD`.
+    pub fn is_finished(&self, generation: i64) -> bool {
+        if generation >= MAX_GENERATIONS || self.populations.back() == Some(&0) {
+            return true;
+        }
+        if self.populations.len() < POPULATION_WINDOW {
+            return false;
+        }
+        (1..=MAX_PERIOD).any(|period| {
+            self.populations.iter().zip(self.populations.iter().skip(period)).all(|(a, b)| a == b)
+        })
+    }
+    
+    /// Advance the camera one frame toward the shot's target, returning the
+    /// world cell to center on and the zoom. `view` is the grid's size in
+    /// screen columns and rows.
+    pub fn frame(&mut self, live_cells: &HashMap<(i64, i64), i32>, view: (u16, u16)) -> ((i64, i64), f32) {
+        self.shot.0 += 1;
+        if self.shot.0 >= SHOT_FRAMES {
+            self.shot = (0, !self.shot.1);
+        }
+        
+        if let Some((x, y, zoom)) = self.target(live_cells, view) {
+            let (cx, cy, cz) = &mut self.camera;
+            *cx += (x - *cx) * EASING;
+            *cy += (y - *cy) * EASING;
+            *cz += (zoom - *cz) * EASING;
+        }
+        let (x, y, zoom) = self.camera;
+        ((x.round() as i64, y.round() as i64), zoom as f32)
+    }
+    
+    /// Center and zoom the current shot aims for: the whole population,
+    /// zoomed out when it does not fit, or the densest tile of it, close up.
+    fn target(&self, live_cells: &HashMap<(i64, i64), i32>, (columns, rows): (u16, u16)) -> Option<(f64, f64, f64)> {
+        if self.shot.1 {
+            let mut tiles: HashMap<(i64, i64), usize> = HashMap::new();
+            for &(x, y) in live_cells.keys() {
+                *tiles.entry((x.div_euclid(TILE), y.div_euclid(TILE))).or_default() += 1;
+            }
+            let (&(tx, ty), _) = tiles.iter().max_by_key(|&(tile, count)| (count, std::cmp::Reverse(*tile)))?;
+            let center = |tile: i64| (tile * TILE + TILE / 2) as f64;
+            return Some((center(tx), center(ty), 1.0));
+        }
+        
+        let (min_x, max_x) = (live_cells.keys().map(|c| c.0).min()?, live_cells.keys().map(|c| c.0).max()?);
+        let (min_y, max_y) = (live_cells.keys().map(|c| c.1).min()?, live_cells.keys().map(|c| c.1).max()?);
+        // Zoomed out, each column shows two cells
+        let fits = (max_x - min_x) < columns as i64 && (max_y - min_y) < rows as i64;
+        let zoom = if fits { 1.0 } else { 0.5 };
+        Some(((min_x + max_x) as f64 / 2.0, (min_y + max_y) as f64 / 2.0, zoom))
+    }
+}
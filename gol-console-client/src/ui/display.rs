@@ -1,24 +1,134 @@
-use anyhow::Result;
 use ratatui::{
-    backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Clear},
+    widgets::{Block, Borders, Paragraph},
     Frame,
 };
-use crate::client::game_of_life::{Cell, SimulationResponse};
-use std::collections::HashMap;
+use crate::client::game_of_life::{Cell as GridCell, CensusResponse, DiffResponse, Heading, HeatmapResponse, Region, SimulationResponse, SimulationUpdate, StatsResponse, SummaryResponse, Turmite, Watchpoint, Annotation};
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use super::search::{PatternMatch, PatternSearch};
+use super::measure::Measurement;
+use super::stamp::PatternStamp;
+use super::theme::Theme;
+
+/// Columns the left ruler takes: a right-aligned label and its tick.
+const RULER_WIDTH: u16 = 7;
+
+/// Fewest screen columns or rows between two ruler labels.
+const MIN_LABEL_SPACING: i32 = 8;
+
+/// Distance in cells between ruler labels: the smallest 1, 2 or 5 times a
+/// power of ten that keeps labels `MIN_LABEL_SPACING` apart when each screen
+/// column or row shows `cells_per_column` cells.
+fn ruler_step(cells_per_column: i32) -> i32 {
+    let min_step = MIN_LABEL_SPACING * cells_per_column.max(1);
+    let mut magnitude = 1;
+    loop {
+        for factor in [1, 2, 5] {
+            if factor * magnitude >= min_step {
+                return factor * magnitude;
+            }
+        }
+        magnitude *= 10;
+    }
+}
+
+/// World coordinate `offset` screen steps of `cell_size` cells past
+/// `origin`, saturating at the ends of the coordinate range so views near
+/// them never overflow.
+pub fn world_coordinate(origin: i64, offset: i64, cell_size: i64) -> i64 {
+    origin.saturating_add(offset.saturating_mul(cell_size))
+}
+
+/// Glyphs a born cell grows through while fading in, ending at the live
+/// glyph; a dying cell shrinks through them in reverse.
+const FADE_GLYPHS: [char; 3] = ['∙', '•', '●'];
+
+/// How long a cell changed under a watchpoint stays highlighted.
+const WATCH_FLASH: Duration = Duration::from_millis(1500);
+
+/// Heatmap cells from rarely alive to always alive.
+const HEAT_GLYPHS: [char; 4] = ['░', '▒', '▓', '█'];
+
+/// Glyph for a turmite, pointing the way it faces.
+fn turmite_glyph(heading: i32) -> char {
+    match Heading::try_from(heading) {
+        Ok(Heading::East) => '▶',
+        Ok(Heading::South) => '▼',
+        Ok(Heading::West) => '◀',
+        _ => '▲',
+    }
+}
 
 pub struct GridDisplay {
     width: u16,
     height: u16,
-    live_cells: HashMap<(i32, i32), bool>,
+    /// Colony color of each live cell.
+    live_cells: HashMap<(i64, i64), i32>,
+    /// Decay state of cells dying under a Generations rule.
+    decaying_cells: HashMap<(i64, i64), i32>,
+    /// Highest decay state on the grid, the far end of the decay gradient.
+    max_decay_state: i32,
+    /// Heading of each turmite, drawn over the cell beneath it.
+    turmites: HashMap<(i64, i64), i32>,
     generation: i64,
     live_count: i64,
-    viewport_x: i32,
-    viewport_y: i32,
+    /// Smallest rectangle holding every live cell, from the last summary.
+    bounds: Option<Region>,
+    viewport_x: i64,
+    viewport_y: i64,
     zoom: f32,
+    /// Whether the simulation's rule uses the hexagonal neighborhood.
+    hex: bool,
+    /// Whether coordinate rulers and the origin crosshair are drawn.
+    show_rulers: bool,
+    show_census: bool,
+    census: Option<CensusResponse>,
+    stats: Option<StatsResponse>,
+    show_diff: bool,
+    diff: Option<DiffResponse>,
+    only_in_a: HashSet<(i64, i64)>,
+    only_in_b: HashSet<(i64, i64)>,
+    show_heatmap: bool,
+    /// Generations each cell was alive in the heatmap's window, the most
+    /// any cell was, and the window's first and last generations.
+    heatmap: HashMap<(i64, i64), i32>,
+    heatmap_max: i32,
+    heatmap_window: Option<(i64, i64)>,
+    theme: Theme,
+    /// Whether the grid is left blank for a graphics protocol to draw as
+    /// pixels. Hex grids are always drawn as text.
+    pixels: bool,
+    /// Where the pixel grid goes, as laid out by the last render.
+    pixel_area: Cell<Option<Rect>>,
+    /// Colony color of each live cell before the last `advance_to`, for
+    /// fading births and deaths in while the transition runs.
+    previous_cells: HashMap<(i64, i64), i32>,
+    /// When the last transition started and how long it lasts.
+    transition: Option<(Instant, Duration)>,
+    /// The pattern last searched for with `find`, found again after each
+    /// update.
+    search: Option<PatternSearch>,
+    /// The pattern being placed, drawn as a ghost over the grid.
+    stamp: Option<PatternStamp>,
+    /// The cursor and marks of measure mode.
+    measure: Option<Measurement>,
+    /// Rectangles the simulation's watchpoints cover, tinted on the grid.
+    watched: Vec<Region>,
+    /// When each cell under a watchpoint last changed, highlighted for
+    /// `WATCH_FLASH` after.
+    watch_changes: HashMap<(i64, i64), Instant>,
+    /// Labels pinned to cells, drawn as a marker with the text beside it.
+    annotations: Vec<Annotation>,
+}
+
+impl Default for GridDisplay {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GridDisplay {
@@ -27,24 +137,314 @@ impl GridDisplay {
             width: 80,
             height: 24,
             live_cells: HashMap::new(),
+            decaying_cells: HashMap::new(),
+            max_decay_state: 0,
+            turmites: HashMap::new(),
             generation: 0,
             live_count: 0,
+            bounds: None,
             viewport_x: 0,
             viewport_y: 0,
             zoom: 1.0,
+            hex: false,
+            show_rulers: false,
+            show_census: false,
+            census: None,
+            stats: None,
+            show_diff: false,
+            diff: None,
+            only_in_a: HashSet::new(),
+            only_in_b: HashSet::new(),
+            show_heatmap: false,
+            heatmap: HashMap::new(),
+            heatmap_max: 0,
+            heatmap_window: None,
+            theme: Theme::default(),
+            pixels: false,
+            pixel_area: Cell::new(None),
+            previous_cells: HashMap::new(),
+            transition: None,
+            search: None,
+            stamp: None,
+            measure: None,
+            watched: Vec::new(),
+            watch_changes: HashMap::new(),
+            annotations: Vec::new(),
         }
     }
     
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+    
+    pub fn set_pixels(&mut self, pixels: bool) {
+        self.pixels = pixels;
+    }
+    
+    /// The area the grid should be drawn into as pixels, if it was left
+    /// blank for that by the last render.
+    pub fn pixel_area(&self) -> Option<Rect> {
+        self.pixel_area.get()
+    }
+    
+    /// Side of the square of pixels each cell is drawn as.
+    pub fn pixels_per_cell(&self) -> u32 {
+        (self.zoom * 2.0).round().max(1.0) as u32
+    }
+    
+    /// Color of the cell at (x, y) in the pixel view, or `None` when it is
+    /// empty.
+    pub fn pixel_color(&self, x: i64, y: i64) -> Option<Color> {
+        match self.cell_glyph(x, y) {
+            ('·', _) => None,
+            (_, style) => style.fg,
+        }
+    }
+    
+    /// Show the simulation's next state, fading births and deaths in over
+    /// `transition` rather than switching at once, so slow playback moves
+    /// smoothly between generations.
+    pub fn advance_to(&mut self, simulation: &SimulationResponse, transition: Duration) {
+        self.transition = None;
+        if !transition.is_zero() && simulation.generation > self.generation {
+            self.previous_cells = std::mem::take(&mut self.live_cells);
+            self.transition = Some((Instant::now(), transition));
+        }
+        self.update_from_simulation(simulation);
+    }
+    
+    /// How far the current transition has run, from 0 to 1, or `None` when
+    /// there is none running.
+    fn transition_progress(&self) -> Option<f32> {
+        let (started, duration) = self.transition?;
+        let progress = started.elapsed().as_secs_f32() / duration.as_secs_f32();
+        (progress < 1.0).then_some(progress)
+    }
+    
+    /// Whether births and deaths are still fading, so the grid should be
+    /// redrawn often.
+    pub fn is_animating(&self) -> bool {
+        self.transition_progress().is_some()
+    }
+    
     pub fn update_from_simulation(&mut self, simulation: &SimulationResponse) {
+        self.hex = simulation.rule.ends_with('H');
+        self.watched = simulation.watchpoints.iter().filter_map(|watchpoint| watchpoint.region).collect();
+        self.show_cells(simulation.generation, simulation.live_cells, &simulation.cells, &simulation.turmites);
+    }
+    
+    /// Show a StreamSimulation update, which carries every live cell but
+    /// not the rule, so the layout stays as it was.
+    pub fn update_from_stream(&mut self, update: &SimulationUpdate) {
+        self.show_cells(update.generation, update.live_cells, &update.changed_cells, &update.turmites);
+    }
+    
+    fn show_cells(&mut self, generation: i64, live_count: i64, cells: &[GridCell], turmites: &[Turmite]) {
         self.live_cells.clear();
-        self.generation = simulation.generation;
-        self.live_count = simulation.live_cells;
+        self.decaying_cells.clear();
+        self.generation = generation;
+        self.live_count = live_count;
+        self.turmites = turmites.iter()
+            .map(|turmite| ((turmite.x.into(), turmite.y.into()), turmite.heading))
+            .collect();
         
-        for cell in &simulation.cells {
+        for cell in cells {
             if cell.alive {
-                self.live_cells.insert((cell.x, cell.y), true);
+                self.live_cells.insert((cell.x, cell.y), cell.color);
+            } else if cell.state >= 2 {
+                self.decaying_cells.insert((cell.x, cell.y), cell.state);
             }
         }
+        self.max_decay_state = self.decaying_cells.values().copied().max().unwrap_or(0);
+        if let Some(search) = &mut self.search {
+            search.rescan(&self.live_cells);
+        }
+    }
+    
+    /// Search the grid for `cells` in any rotation or reflection and move
+    /// the viewport to the first occurrence. Returns how many were found.
+    pub fn find_pattern(&mut self, name: String, cells: &[(i64, i64)]) -> usize {
+        let mut search = PatternSearch::new(name, cells);
+        search.rescan(&self.live_cells);
+        let found = search.matches.len();
+        if let Some(first) = search.current_match() {
+            self.center_on(first.center());
+        }
+        self.search = Some(search);
+        found
+    }
+    
+    /// Move the viewport `step` occurrences of the searched pattern forward
+    /// or back.
+    pub fn cycle_match(&mut self, step: i32) {
+        if let Some(found) = self.search.as_mut().and_then(|search| search.cycle(step)) {
+            self.center_on(found.center());
+        }
+    }
+    
+    fn current_match(&self) -> Option<PatternMatch> {
+        self.search.as_ref().and_then(PatternSearch::current_match)
+    }
+    
+    /// Start placing a pattern, its ghost centered in the view.
+    pub fn start_stamp(&mut self, name: String, cells: &[(i64, i64)]) {
+        let cell_size = (1.0 / self.zoom) as i64;
+        let mut stamp = PatternStamp::new(name, cells, 0, 0);
+        let (width, height) = stamp.size();
+        stamp.move_by(
+            world_coordinate(self.viewport_x, self.width as i64 / 2, cell_size) - width / 2,
+            world_coordinate(self.viewport_y, self.height as i64 / 2, cell_size) - height / 2,
+        );
+        self.stamp = Some(stamp);
+    }
+    
+    pub fn stamp_mut(&mut self) -> Option<&mut PatternStamp> {
+        self.stamp.as_mut()
+    }
+    
+    pub fn is_stamping(&self) -> bool {
+        self.stamp.is_some()
+    }
+    
+    /// Stop placing, returning the stamp where it was left.
+    pub fn take_stamp(&mut self) -> Option<PatternStamp> {
+        self.stamp.take()
+    }
+    
+    /// Start measuring with the cursor in the middle of the view, observing
+    /// speeds over `generations`.
+    pub fn start_measure(&mut self, generations: Option<i64>) {
+        self.measure = Some(Measurement::new(self.view_center(), generations));
+    }
+    
+    pub fn measurement(&self) -> Option<&Measurement> {
+        self.measure.as_ref()
+    }
+    
+    pub fn measurement_mut(&mut self) -> Option<&mut Measurement> {
+        self.measure.as_mut()
+    }
+    
+    pub fn is_measuring(&self) -> bool {
+        self.measure.is_some()
+    }
+    
+    pub fn stop_measure(&mut self) {
+        self.measure = None;
+    }
+    
+    /// Move the measure cursor, scrolling the view to keep it in sight.
+    pub fn move_measure_cursor(&mut self, dx: i64, dy: i64) {
+        let Some(measure) = &mut self.measure else {
+            return;
+        };
+        measure.move_by(dx, dy);
+        let (x, y) = measure.cursor;
+        let cell_size = ((1.0 / self.zoom) as i64).max(1);
+        let right = world_coordinate(self.viewport_x, self.width as i64, cell_size);
+        let bottom = world_coordinate(self.viewport_y, self.height as i64, cell_size);
+        if !(self.viewport_x..right).contains(&x) || !(self.viewport_y..bottom).contains(&y) {
+            self.look_at((x, y), self.zoom);
+        }
+    }
+    
+    /// Take the bounding box from a summary, returning whether the cells
+    /// shown are out of date.
+    pub fn update_summary(&mut self, summary: SummaryResponse) -> bool {
+        self.bounds = summary.bounds;
+        (summary.generation, summary.live_cells) != (self.generation, self.live_count)
+    }
+    
+    pub fn update_census(&mut self, census: CensusResponse) {
+        self.census = Some(census);
+    }
+    
+    pub fn update_stats(&mut self, stats: StatsResponse) {
+        self.stats = Some(stats);
+    }
+    
+    pub fn toggle_census(&mut self) {
+        self.show_census = !self.show_census;
+    }
+    
+    pub fn is_census_shown(&self) -> bool {
+        self.show_census
+    }
+    
+    pub fn toggle_rulers(&mut self) {
+        self.show_rulers = !self.show_rulers;
+    }
+    
+    /// Tint the rectangles of `watchpoints`, as just set.
+    pub fn set_watchpoints(&mut self, watchpoints: &[Watchpoint]) {
+        self.watched = watchpoints.iter().filter_map(|watchpoint| watchpoint.region).collect();
+    }
+    
+    /// Highlight `cells`, which changed under a watchpoint, for a moment.
+    pub fn flash_changes(&mut self, cells: &[GridCell]) {
+        let now = Instant::now();
+        self.watch_changes.retain(|_, changed| now.duration_since(*changed) < WATCH_FLASH);
+        self.watch_changes.extend(cells.iter().map(|cell| ((cell.x, cell.y), now)));
+    }
+    
+    pub fn set_annotations(&mut self, annotations: Vec<Annotation>) {
+        self.annotations = annotations;
+    }
+    
+    pub fn annotations(&self) -> &[Annotation] {
+        &self.annotations
+    }
+    
+    fn is_watched(&self, x: i64, y: i64) -> bool {
+        self.watched.iter().any(|region| {
+            (region.x..region.x.saturating_add(region.width)).contains(&x)
+                && (region.y..region.y.saturating_add(region.height)).contains(&y)
+        })
+    }
+    
+    /// Whether `(x, y)` is in the region between the measure marks.
+    fn is_measured(&self, x: i64, y: i64) -> bool {
+        self.measure.as_ref().and_then(Measurement::region).is_some_and(|region| {
+            (region.x..region.x.saturating_add(region.width)).contains(&x)
+                && (region.y..region.y.saturating_add(region.height)).contains(&y)
+        })
+    }
+    
+    pub fn update_diff(&mut self, diff: DiffResponse) {
+        self.only_in_a = diff.only_in_a.iter().map(|cell| (cell.x, cell.y)).collect();
+        self.only_in_b = diff.only_in_b.iter().map(|cell| (cell.x, cell.y)).collect();
+        self.diff = Some(diff);
+    }
+    
+    pub fn set_diff_shown(&mut self, shown: bool) {
+        self.show_diff = shown;
+        if !shown {
+            self.diff = None;
+            self.only_in_a.clear();
+            self.only_in_b.clear();
+        }
+    }
+    
+    pub fn is_diff_shown(&self) -> bool {
+        self.show_diff
+    }
+    
+    pub fn update_heatmap(&mut self, heatmap: HeatmapResponse) {
+        self.heatmap = heatmap.cells.iter().map(|cell| ((cell.x, cell.y), cell.count)).collect();
+        self.heatmap_max = heatmap.max_count;
+        self.heatmap_window = Some((heatmap.from_generation, heatmap.to_generation));
+    }
+    
+    pub fn toggle_heatmap(&mut self) {
+        self.show_heatmap = !self.show_heatmap;
+        if !self.show_heatmap {
+            self.heatmap.clear();
+            self.heatmap_window = None;
+        }
+    }
+    
+    pub fn is_heatmap_shown(&self) -> bool {
+        self.show_heatmap
     }
     
     pub fn update_terminal_size(&mut self, width: u16, height: u16) {
@@ -52,18 +452,18 @@ impl GridDisplay {
         self.height = height;
     }
     
-    pub fn set_viewport(&mut self, x: i32, y: i32) {
+    pub fn set_viewport(&mut self, x: i64, y: i64) {
         self.viewport_x = x;
         self.viewport_y = y;
     }
     
-    pub fn move_viewport(&mut self, dx: i32, dy: i32) {
-        self.viewport_x += dx;
-        self.viewport_y += dy;
+    pub fn move_viewport(&mut self, dx: i64, dy: i64) {
+        self.viewport_x = self.viewport_x.saturating_add(dx);
+        self.viewport_y = self.viewport_y.saturating_add(dy);
     }
     
     pub fn set_zoom(&mut self, zoom: f32) {
-        self.zoom = zoom.max(0.5).min(4.0);
+        self.zoom = zoom.clamp(0.5, 4.0);
     }
     
     pub fn render(&self, frame: &mut Frame, area: Rect) {
@@ -77,18 +477,65 @@ impl GridDisplay {
             .split(area);
         
         self.render_status_bar(frame, chunks[0]);
-        self.render_grid(frame, chunks[1]);
+        if self.show_census {
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Min(0),     // Grid area
+                    Constraint::Length(34), // Census panel
+                ])
+                .split(chunks[1]);
+            self.render_grid(frame, columns[0]);
+            self.render_census(frame, columns[1]);
+        } else {
+            self.render_grid(frame, chunks[1]);
+        }
         self.render_command_hint(frame, chunks[2]);
     }
     
     fn render_status_bar(&self, frame: &mut Frame, area: Rect) {
-        let status_text = format!(
+        let mut status_text = format!(
             "Generation: {} | Live Cells: {} | Viewport: ({}, {}) | Zoom: {:.1}x",
             self.generation, self.live_count, self.viewport_x, self.viewport_y, self.zoom
         );
+        if let Some(bounds) = &self.bounds {
+            status_text.push_str(&format!(" | Bounds: {}x{}", bounds.width, bounds.height));
+        }
+        if self.hex {
+            status_text.push_str(" | Hex");
+        } else if self.pixels {
+            status_text.push_str(&format!(" | Pixels: {}px/cell", self.pixels_per_cell()));
+        }
+        if let Some(stats) = self.stats.as_ref().filter(|stats| stats.period > 0) {
+            status_text.push_str(&format!(" | Period: {}", stats.period));
+            if !stats.speed.is_empty() {
+                status_text.push_str(&format!(" ({} {})", stats.speed, stats.direction));
+            }
+        }
+        if let Some((from, to)) = self.heatmap_window.filter(|_| self.show_heatmap) {
+            status_text.push_str(&format!(" | Heatmap: gen {}-{}", from, to));
+        }
+        if let Some(search) = &self.search {
+            match search.matches.len() {
+                0 => status_text.push_str(&format!(" | Find {}: none", search.name)),
+                count => status_text.push_str(&format!(" | Find {}: {}/{}", search.name, search.current + 1, count)),
+            }
+        }
+        if let Some(stamp) = &self.stamp {
+            status_text.push_str(&format!(" | Placing {} at ({}, {})", stamp.name, stamp.x, stamp.y));
+        }
+        if let Some(measure) = &self.measure {
+            status_text.push_str(&format!(" | {}", measure.label()));
+        }
+        if let Some(diff) = self.diff.as_ref().filter(|_| self.show_diff) {
+            status_text.push_str(&format!(
+                " | Diff gen {} vs {}: {} only in A, {} only in B, {} shared",
+                diff.generation_a, diff.generation_b, diff.only_in_a.len(), diff.only_in_b.len(), diff.common_cells
+            ));
+        }
         
         let status = Paragraph::new(status_text)
-            .style(Style::default().fg(Color::Yellow))
+            .style(Style::default().fg(self.theme.accent))
             .block(Block::default().borders(Borders::NONE));
         
         frame.render_widget(status, area);
@@ -98,47 +545,193 @@ impl GridDisplay {
         let block = Block::default()
             .title("Game of Life")
             .borders(Borders::ALL)
-            .style(Style::default().fg(Color::White));
+            .style(Style::default().fg(self.theme.text));
         
         frame.render_widget(block, area);
         
-        let inner_area = Rect {
+        let mut inner_area = Rect {
             x: area.x + 1,
             y: area.y + 1,
             width: area.width.saturating_sub(2),
             height: area.height.saturating_sub(2),
         };
+        self.pixel_area.set(None);
+        if self.pixels && !self.hex {
+            // Ruler labels are spaced in character cells, which no longer
+            // line up with the grid; the origin axes are still drawn
+            self.pixel_area.set(Some(inner_area));
+            return;
+        }
+        // Hex rows are staggered, so columns have no single x to label
+        if self.show_rulers && !self.hex {
+            inner_area = self.render_rulers(frame, inner_area);
+        }
         
         let grid_lines = self.generate_grid_lines(inner_area);
         let grid_paragraph = Paragraph::new(grid_lines)
-            .style(Style::default().fg(Color::White));
+            .style(Style::default().fg(self.theme.text));
         
         frame.render_widget(grid_paragraph, inner_area);
+        if !self.hex {
+            self.render_annotations(frame, inner_area);
+        }
     }
     
-    fn generate_grid_lines(&self, area: Rect) -> Vec<Line> {
-        let mut lines = Vec::new();
+    /// Write each annotation's text to the right of its marker, cut off at
+    /// the edge of the grid.
+    fn render_annotations(&self, frame: &mut Frame, area: Rect) {
+        let cell_size = ((1.0 / self.zoom) as i64).max(1);
+        for annotation in &self.annotations {
+            let col = annotation.x.saturating_sub(self.viewport_x).div_euclid(cell_size) + 1;
+            let row = annotation.y.saturating_sub(self.viewport_y).div_euclid(cell_size);
+            if annotation.x < self.viewport_x || annotation.y < self.viewport_y || col >= area.width as i64 || row >= area.height as i64 {
+                continue;
+            }
+            let label = Rect {
+                x: area.x + col as u16,
+                y: area.y + row as u16,
+                width: (area.width - col as u16).min(annotation.text.chars().count() as u16 + 1),
+                height: 1,
+            };
+            let text = Paragraph::new(format!(" {}", annotation.text))
+                .style(Style::default().fg(self.theme.accent).bg(self.theme.selection));
+            frame.render_widget(text, label);
+        }
+    }
+    
+    /// Draw the x ruler along the top of `area` and the y ruler down its
+    /// left side, returning the area left for the grid.
+    fn render_rulers(&self, frame: &mut Frame, area: Rect) -> Rect {
+        if area.width <= RULER_WIDTH || area.height <= 1 {
+            return area;
+        }
+        let grid_area = Rect {
+            x: area.x + RULER_WIDTH,
+            y: area.y + 1,
+            width: area.width - RULER_WIDTH,
+            height: area.height - 1,
+        };
         let cell_size = (1.0 / self.zoom) as i32;
+        let step = ruler_step(cell_size) as i64;
+        let style = Style::default().fg(self.theme.axis);
+        
+        // A label starts at each column showing a multiple of `step`, unless
+        // the previous label is still being written
+        let mut top: Vec<char> = vec![' '; grid_area.width as usize];
+        let mut free_from = 0;
+        for col in 0..grid_area.width as usize {
+            let world_x = world_coordinate(self.viewport_x, col as i64, cell_size as i64);
+            if col < free_from || world_x.rem_euclid(step) != 0 {
+                continue;
+            }
+            let label: Vec<char> = format!("┊{}", world_x).chars().collect();
+            for (offset, c) in label.iter().enumerate() {
+                if let Some(slot) = top.get_mut(col + offset) {
+                    *slot = *c;
+                }
+            }
+            free_from = col + label.len() + 1;
+        }
+        let top_line = format!("{:width$}{}", "", top.into_iter().collect::<String>(), width = RULER_WIDTH as usize);
+        frame.render_widget(Paragraph::new(top_line).style(style), Rect { height: 1, ..area });
+        
+        let left: Vec<Line> = (0..grid_area.height).map(|row| {
+            let world_y = world_coordinate(self.viewport_y, row as i64, cell_size as i64);
+            if world_y.rem_euclid(step) == 0 {
+                Line::from(format!("{:>width$}┤", world_y, width = RULER_WIDTH as usize - 1))
+            } else {
+                Line::from(format!("{:>width$}│", "", width = RULER_WIDTH as usize - 1))
+            }
+        }).collect();
+        frame.render_widget(
+            Paragraph::new(left).style(style),
+            Rect { y: grid_area.y, width: RULER_WIDTH, height: grid_area.height, ..area },
+        );
+        
+        grid_area
+    }
+    
+    fn render_census(&self, frame: &mut Frame, area: Rect) {
+        let lines = match &self.census {
+            Some(census) => {
+                let mut lines: Vec<Line> = census.objects.iter().map(|object| {
+                    let label = if object.name.is_empty() { &object.apgcode } else { &object.name };
+                    Line::from(vec![
+                        Span::styled(format!("{:>5} ", object.count), Style::default().fg(self.theme.accent)),
+                        Span::raw(label.clone()),
+                    ])
+                }).collect();
+                if census.unidentified_objects > 0 {
+                    lines.push(Line::from(Span::styled(
+                        format!("{:>5} unidentified ({} cells)", census.unidentified_objects, census.unidentified_cells),
+                        Style::default().fg(self.theme.muted),
+                    )));
+                }
+                if lines.is_empty() {
+                    lines.push(Line::from("No objects"));
+                }
+                lines
+            }
+            None => vec![Line::from("Waiting for census...")],
+        };
+        
+        let title = match &self.census {
+            Some(census) => format!("Census (gen {})", census.generation),
+            None => "Census".to_string(),
+        };
+        let census = Paragraph::new(lines)
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .style(Style::default().fg(self.theme.text));
+        
+        frame.render_widget(census, area);
+    }
+    
+    fn generate_grid_lines(&self, area: Rect) -> Vec<Line<'_>> {
+        if self.hex {
+            return self.generate_hex_lines(area);
+        }
+        
+        let mut lines = Vec::new();
+        let cell_size = (1.0 / self.zoom) as i64;
         
         for row in 0..area.height {
             let mut line_spans = Vec::new();
-            let world_y = self.viewport_y + (row as i32 * cell_size);
+            let world_y = world_coordinate(self.viewport_y, row as i64, cell_size);
             
             for col in 0..area.width {
-                let world_x = self.viewport_x + (col as i32 * cell_size);
+                let world_x = world_coordinate(self.viewport_x, col as i64, cell_size);
                 
-                let cell_char = if self.live_cells.contains_key(&(world_x, world_y)) {
-                    '●'
-                } else {
-                    '·'
-                };
+                let (cell_char, cell_style) = self.cell_glyph(world_x, world_y);
                 
-                let cell_style = if self.live_cells.contains_key(&(world_x, world_y)) {
-                    Style::default().fg(Color::Green)
-                } else {
-                    Style::default().fg(Color::DarkGray)
-                };
+                line_spans.push(Span::styled(cell_char.to_string(), cell_style));
+            }
+            
+            lines.push(Line::from(line_spans));
+        }
+        
+        lines
+    }
+    
+    /// Lay hexes out in offset rows, each shifted half a hex from the one
+    /// above, so that a cell's six axial neighbors (q±1, r), (q, r±1),
+    /// (q-1, r-1) and (q+1, r+1) surround it on screen. Each hex takes two
+    /// columns and zoom is ignored.
+    fn generate_hex_lines(&self, area: Rect) -> Vec<Line<'_>> {
+        let mut lines = Vec::new();
+        
+        for row in 0..area.height {
+            let mut line_spans = Vec::new();
+            let r = world_coordinate(self.viewport_y, row as i64, 1);
+            let indent = r.rem_euclid(2);
+            
+            for col in 0..area.width as i64 {
+                if (col - indent) % 2 != 0 {
+                    line_spans.push(Span::raw(" "));
+                    continue;
+                }
+                let q = world_coordinate(self.viewport_x, (col + indent) / 2, 1).saturating_add(r.div_euclid(2));
                 
+                let (cell_char, cell_style) = self.cell_glyph(q, r);
                 line_spans.push(Span::styled(cell_char.to_string(), cell_style));
             }
             
@@ -148,51 +741,91 @@ impl GridDisplay {
         lines
     }
     
-    fn render_command_hint(&self, frame: &mut Frame, area: Rect) {
-        let hint_text = "Commands: q=quit, h=help, r=run, s=step, p=pause, arrows=move, +/-=zoom";
+    fn cell_glyph(&self, x: i64, y: i64) -> (char, Style) {
+        if self.show_diff {
+            if self.only_in_a.contains(&(x, y)) {
+                return ('●', Style::default().fg(self.theme.only_in_a));
+            } else if self.only_in_b.contains(&(x, y)) {
+                return ('●', Style::default().fg(self.theme.only_in_b));
+            }
+        }
         
-        let hint = Paragraph::new(hint_text)
-            .style(Style::default().fg(Color::Cyan))
-            .block(Block::default().borders(Borders::NONE));
+        if let Some(&count) = self.heatmap.get(&(x, y)).filter(|_| self.show_heatmap) {
+            let heat = count as f32 / self.heatmap_max.max(1) as f32;
+            let glyph = HEAT_GLYPHS[((heat * HEAT_GLYPHS.len() as f32) as usize).min(HEAT_GLYPHS.len() - 1)];
+            return (glyph, Style::default().fg(self.theme.heat_color(count, self.heatmap_max)));
+        }
         
-        frame.render_widget(hint, area);
+        if self.stamp.as_ref().is_some_and(|stamp| stamp.covers(x, y)) {
+            // Halfway to the background so the grid shows through the ghost
+            let ghost = self.theme.fade(self.theme.muted, self.theme.accent, 0.5);
+            let glyph = if self.live_cells.contains_key(&(x, y)) { '◉' } else { '◌' };
+            return (glyph, Style::default().fg(ghost));
+        }
+        
+        if let Some(measure) = &self.measure {
+            let live = self.live_cells.contains_key(&(x, y));
+            if measure.cursor == (x, y) {
+                return (if live { '⊕' } else { '+' }, Style::default().fg(self.theme.accent));
+            }
+            if measure.is_marked(x, y) {
+                return (if live { '⊗' } else { '×' }, Style::default().fg(self.theme.accent));
+            }
+        }
+        
+        if self.watch_changes.get(&(x, y)).is_some_and(|changed| changed.elapsed() < WATCH_FLASH) {
+            let glyph = if self.live_cells.contains_key(&(x, y)) { '●' } else { '×' };
+            return (glyph, Style::default().fg(self.theme.accent));
+        }
+        
+        if self.annotations.iter().any(|annotation| (annotation.x, annotation.y) == (x, y)) {
+            return ('◆', Style::default().fg(self.theme.accent));
+        }
+        
+        let colony_color = |color: i32| self.theme.colonies.get(color as usize).copied().unwrap_or(self.theme.colonies[0]);
+        let progress = self.transition_progress();
+        if let Some(&heading) = self.turmites.get(&(x, y)) {
+            (turmite_glyph(heading), Style::default().fg(self.theme.turmite))
+        } else if let Some(&color) = self.live_cells.get(&(x, y)) {
+            let color = match self.current_match() {
+                Some(found) if found.contains(x, y) => self.theme.accent,
+                _ => colony_color(color),
+            };
+            match progress.filter(|_| !self.previous_cells.contains_key(&(x, y))) {
+                Some(progress) => {
+                    let glyph = FADE_GLYPHS[(progress * FADE_GLYPHS.len() as f32) as usize];
+                    (glyph, Style::default().fg(self.theme.fade(self.theme.muted, color, progress)))
+                }
+                None => ('●', Style::default().fg(color)),
+            }
+        } else if let Some(&state) = self.decaying_cells.get(&(x, y)) {
+            let color = self.theme.decay_color(state, self.max_decay_state);
+            ('○', Style::default().fg(color))
+        } else if let Some((progress, &color)) = progress.zip(self.previous_cells.get(&(x, y))) {
+            let glyph = FADE_GLYPHS[FADE_GLYPHS.len() - 1 - (progress * FADE_GLYPHS.len() as f32) as usize];
+            (glyph, Style::default().fg(self.theme.fade(colony_color(color), self.theme.muted, progress)))
+        } else if self.show_rulers && !self.hex && (x == 0 || y == 0) {
+            let axis = match (x, y) {
+                (0, 0) => '┼',
+                (0, _) => '│',
+                _ => '─',
+            };
+            (axis, Style::default().fg(self.theme.axis))
+        } else if self.is_watched(x, y) || self.is_measured(x, y) {
+            ('·', Style::default().fg(self.theme.hint))
+        } else {
+            ('·', Style::default().fg(self.theme.muted))
+        }
     }
     
-    pub fn render_help(&self, frame: &mut Frame, area: Rect) {
-        let help_text = vec![
-            Line::from("Game of Life Console Client - Help"),
-            Line::from(""),
-            Line::from("Navigation:"),
-            Line::from("  Arrow Keys    - Move viewport"),
-            Line::from("  +/-           - Zoom in/out"),
-            Line::from("  Home          - Reset viewport to origin"),
-            Line::from(""),
-            Line::from("Simulation:"),
-            Line::from("  r             - Run simulation"),
-            Line::from("  s             - Step one generation"),
-            Line::from("  p             - Pause simulation"),
-            Line::from("  c             - Clear grid"),
-            Line::from("  l             - Load pattern"),
-            Line::from(""),
-            Line::from("Interface:"),
-            Line::from("  h             - Show/hide this help"),
-            Line::from("  q             - Quit application"),
-            Line::from("  Enter         - Command mode"),
-            Line::from(""),
-            Line::from("Press any key to close help"),
-        ];
-        
-        let help_block = Block::default()
-            .title("Help")
-            .borders(Borders::ALL)
-            .style(Style::default().fg(Color::Yellow));
+    fn render_command_hint(&self, frame: &mut Frame, area: Rect) {
+        let hint_text = "Commands: q=quit, h=help, r=run, s=step, p=pause, k=census, d=diff, a=heatmap, i=perf, b=sims, u=rulers, x=pixels, v=history, arrows=move, +/-=zoom";
         
-        let help_paragraph = Paragraph::new(help_text)
-            .block(help_block)
-            .style(Style::default().fg(Color::White));
+        let hint = Paragraph::new(hint_text)
+            .style(Style::default().fg(self.theme.hint))
+            .block(Block::default().borders(Borders::NONE));
         
-        frame.render_widget(Clear, area);
-        frame.render_widget(help_paragraph, area);
+        frame.render_widget(hint, area);
     }
     
     pub fn center_on_live_cells(&mut self) {
@@ -200,10 +833,10 @@ impl GridDisplay {
             return;
         }
         
-        let mut min_x = i32::MAX;
-        let mut max_x = i32::MIN;
-        let mut min_y = i32::MAX;
-        let mut max_y = i32::MIN;
+        let mut min_x = i64::MAX;
+        let mut max_x = i64::MIN;
+        let mut min_y = i64::MAX;
+        let mut max_y = i64::MIN;
         
         for &(x, y) in self.live_cells.keys() {
             min_x = min_x.min(x);
@@ -212,21 +845,48 @@ impl GridDisplay {
             max_y = max_y.max(y);
         }
         
-        let center_x = (min_x + max_x) / 2;
-        let center_y = (min_y + max_y) / 2;
+        let center_x = min_x.midpoint(max_x);
+        let center_y = min_y.midpoint(max_y);
         
-        self.viewport_x = center_x - (self.width as i32 / 2);
-        self.viewport_y = center_y - (self.height as i32 / 2);
+        self.center_on((center_x, center_y));
     }
     
-    pub fn get_cell_at_screen_pos(&self, screen_x: u16, screen_y: u16) -> (i32, i32) {
-        let cell_size = (1.0 / self.zoom) as i32;
-        let world_x = self.viewport_x + (screen_x as i32 * cell_size);
-        let world_y = self.viewport_y + (screen_y as i32 * cell_size);
+    fn center_on(&mut self, (x, y): (i64, i64)) {
+        self.viewport_x = x.saturating_sub(self.width as i64 / 2);
+        self.viewport_y = y.saturating_sub(self.height as i64 / 2);
+    }
+    
+    /// Zoom to `zoom` and move the viewport so world cell `center` is in
+    /// the middle of the grid.
+    pub fn look_at(&mut self, center: (i64, i64), zoom: f32) {
+        self.set_zoom(zoom);
+        let cell_size = (1.0 / self.zoom) as i64;
+        self.viewport_x = center.0.saturating_sub(self.width as i64 * cell_size / 2);
+        self.viewport_y = center.1.saturating_sub(self.height as i64 * cell_size / 2);
+    }
+    
+    /// World cell in the middle of the grid.
+    pub fn view_center(&self) -> (i64, i64) {
+        let cell_size = (1.0 / self.zoom) as i64;
+        (
+            self.viewport_x.saturating_add(self.width as i64 * cell_size / 2),
+            self.viewport_y.saturating_add(self.height as i64 * cell_size / 2),
+        )
+    }
+    
+    /// Colony color of each live cell shown.
+    pub fn live_cells(&self) -> &HashMap<(i64, i64), i32> {
+        &self.live_cells
+    }
+    
+    pub fn get_cell_at_screen_pos(&self, screen_x: u16, screen_y: u16) -> (i64, i64) {
+        let cell_size = (1.0 / self.zoom) as i64;
+        let world_x = world_coordinate(self.viewport_x, screen_x as i64, cell_size);
+        let world_y = world_coordinate(self.viewport_y, screen_y as i64, cell_size);
         (world_x, world_y)
     }
     
-    pub fn get_viewport_info(&self) -> (i32, i32, f32) {
+    pub fn get_viewport_info(&self) -> (i64, i64, f32) {
         (self.viewport_x, self.viewport_y, self.zoom)
     }
     
@@ -0,0 +1,221 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+
+use base64::Engine;
+use crossterm::{cursor::MoveTo, QueueableCommand};
+use ratatui::layout::Rect;
+
+use super::display::{world_coordinate, GridDisplay};
+use super::theme::to_rgb;
+
+/// Image id the grid is transmitted under with the kitty protocol, so each
+/// frame replaces the last.
+const KITTY_IMAGE_ID: u32 = 1;
+
+/// Largest base64 payload in one kitty graphics escape.
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// Character cell size in pixels assumed when the terminal does not report
+/// its own.
+const FALLBACK_CELL_PIXELS: (u16, u16) = (8, 16);
+
+/// A terminal image protocol the grid can be drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphicsProtocol {
+    Kitty,
+    Sixel,
+}
+
+/// Parse `--graphics`: `auto` detects the terminal, `off` keeps text.
+pub fn requested_protocol(mode: &str) -> Result<Option<GraphicsProtocol>, String> {
+    match mode.to_ascii_lowercase().as_str() {
+        "auto" => Ok(detect_protocol()),
+        "kitty" => Ok(Some(GraphicsProtocol::Kitty)),
+        "sixel" => Ok(Some(GraphicsProtocol::Sixel)),
+        "off" | "text" => Ok(None),
+        _ => Err(format!("Unknown graphics mode '{}' (expected auto, kitty, sixel or off)", mode)),
+    }
+}
+
+/// Guess the protocol from the environment the terminal sets, since
+/// querying it would race with key input.
+pub fn detect_protocol() -> Option<GraphicsProtocol> {
+    let var = |name| std::env::var(name).unwrap_or_default();
+    let (term, program) = (var("TERM"), var("TERM_PROGRAM"));
+    if std::env::var_os("KITTY_WINDOW_ID").is_some()
+        || term == "xterm-kitty"
+        || term == "xterm-ghostty"
+        || matches!(program.as_str(), "WezTerm" | "ghostty")
+    {
+        Some(GraphicsProtocol::Kitty)
+    } else if term.contains("sixel") || term.starts_with("foot") || term.starts_with("mlterm") || program == "iTerm.app" {
+        Some(GraphicsProtocol::Sixel)
+    } else {
+        None
+    }
+}
+
+/// Draws the grid as pixels over the blank area GridDisplay leaves for it.
+/// Images are only sent when their content or placement changes.
+pub struct PixelRenderer {
+    protocol: GraphicsProtocol,
+    /// Hash of the last image sent and where it went.
+    last_frame: Option<u64>,
+    /// Whether an image is on screen.
+    shown: bool,
+}
+
+impl PixelRenderer {
+    pub fn new(protocol: GraphicsProtocol) -> Self {
+        Self { protocol, last_frame: None, shown: false }
+    }
+    
+    pub fn protocol(&self) -> GraphicsProtocol {
+        self.protocol
+    }
+    
+    /// Paint `display`'s viewport into `area`, given in character cells.
+    pub fn draw(&mut self, out: &mut impl Write, display: &GridDisplay, area: Rect) -> io::Result<()> {
+        if area.width == 0 || area.height == 0 {
+            return Ok(());
+        }
+        let (cell_width, cell_height) = cell_pixels();
+        let (width, height) = (area.width as usize * cell_width as usize, area.height as usize * cell_height as usize);
+        let pixels = render_pixels(display, width, height);
+        
+        let mut hasher = DefaultHasher::new();
+        (area.x, area.y, area.width, area.height, &pixels).hash(&mut hasher);
+        let frame = hasher.finish();
+        if self.last_frame == Some(frame) {
+            return Ok(());
+        }
+        self.last_frame = Some(frame);
+        self.shown = true;
+        
+        out.queue(MoveTo(area.x, area.y))?;
+        match self.protocol {
+            GraphicsProtocol::Kitty => write_kitty(out, &pixels, width, height, area)?,
+            GraphicsProtocol::Sixel => write_sixel(out, &pixels, width, height)?,
+        }
+        out.flush()
+    }
+    
+    /// Send the next frame even if it is unchanged, as the text layer may
+    /// have been redrawn over it.
+    pub fn invalidate(&mut self) {
+        self.last_frame = None;
+    }
+    
+    /// Remove the image, e.g. when switching back to text. Sixel pixels are
+    /// painted into the text layer and go once the text is redrawn.
+    pub fn clear(&mut self, out: &mut impl Write) -> io::Result<()> {
+        self.last_frame = None;
+        if std::mem::take(&mut self.shown) && self.protocol == GraphicsProtocol::Kitty {
+            write!(out, "\x1b_Ga=d,d=I,i={},q=2\x1b\\", KITTY_IMAGE_ID)?;
+            out.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Size of a character cell in pixels, as reported by the terminal.
+fn cell_pixels() -> (u16, u16) {
+    match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => {
+            ((size.width / size.columns).max(1), (size.height / size.rows).max(1))
+        }
+        _ => FALLBACK_CELL_PIXELS,
+    }
+}
+
+/// RGB pixels of the viewport, row by row. Each cell is a square of
+/// `GridDisplay::pixels_per_cell` pixels; empty cells are black.
+fn render_pixels(display: &GridDisplay, width: usize, height: usize) -> Vec<(u8, u8, u8)> {
+    let (viewport_x, viewport_y, _) = display.get_viewport_info();
+    let scale = display.pixels_per_cell() as usize;
+    let (columns, rows) = (width.div_ceil(scale), height.div_ceil(scale));
+    
+    let colors: Vec<(u8, u8, u8)> = (0..rows).flat_map(|row| {
+        (0..columns).map(move |col| {
+            display.pixel_color(world_coordinate(viewport_x, col as i64, 1), world_coordinate(viewport_y, row as i64, 1))
+                .map_or((0, 0, 0), to_rgb)
+        })
+    }).collect();
+    (0..height).flat_map(|y| {
+        let row = &colors[(y / scale) * columns..][..columns];
+        (0..width).map(move |x| row[x / scale])
+    }).collect()
+}
+
+/// Transmit and place the image in one go, in base64 chunks. It goes
+/// beneath the text so popups drawn over the grid stay readable.
+fn write_kitty(out: &mut impl Write, pixels: &[(u8, u8, u8)], width: usize, height: usize, area: Rect) -> io::Result<()> {
+    let raw: Vec<u8> = pixels.iter().flat_map(|&(r, g, b)| [r, g, b]).collect();
+    let encoded = base64::engine::general_purpose::STANDARD.encode(raw);
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+    
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = (index + 1 < chunks.len()) as u8;
+        if index == 0 {
+            write!(
+                out,
+                "\x1b_Ga=T,f=24,s={},v={},c={},r={},i={},z=-1,C=1,q=2,m={};",
+                width, height, area.width, area.height, KITTY_IMAGE_ID, more,
+            )?;
+        } else {
+            write!(out, "\x1b_Gm={};", more)?;
+        }
+        out.write_all(chunk)?;
+        out.write_all(b"\x1b\\")?;
+    }
+    Ok(())
+}
+
+/// Encode the image as Sixel: a palette of its distinct colors, then bands
+/// six pixels tall, each color's pixels of a band run-length encoded.
+fn write_sixel(out: &mut impl Write, pixels: &[(u8, u8, u8)], width: usize, height: usize) -> io::Result<()> {
+    let mut palette: HashMap<(u8, u8, u8), usize> = HashMap::new();
+    let indices: Vec<usize> = pixels.iter().map(|&color| {
+        let next = palette.len();
+        *palette.entry(color).or_insert(next)
+    }).collect();
+    
+    write!(out, "\x1bPq\"1;1;{};{}", width, height)?;
+    let mut by_index: Vec<_> = palette.iter().collect();
+    by_index.sort_by_key(|&(_, &index)| index);
+    for (&(r, g, b), index) in by_index {
+        let percent = |value: u8| value as u32 * 100 / 255;
+        write!(out, "#{};2;{};{};{}", index, percent(r), percent(g), percent(b))?;
+    }
+    
+    for band in (0..height).step_by(6) {
+        let band_rows = (height - band).min(6);
+        for color in 0..palette.len() {
+            let sixels: Vec<u8> = (0..width).map(|x| {
+                (0..band_rows)
+                    .filter(|&dy| indices[(band + dy) * width + x] == color)
+                    .fold(0u8, |bits, dy| bits | 1 << dy)
+            }).collect();
+            if sixels.iter().all(|&bits| bits == 0) {
+                continue;
+            }
+            write!(out, "#{}", color)?;
+            let mut x = 0;
+            while x < width {
+                let run = sixels[x..].iter().take_while(|&&bits| bits == sixels[x]).count();
+                let symbol = (63 + sixels[x]) as char;
+                if run > 3 {
+                    write!(out, "!{}{}", run, symbol)?;
+                } else {
+                    write!(out, "{}", symbol.to_string().repeat(run))?;
+                }
+                x += run;
+            }
+            out.write_all(b"$")?;
+        }
+        out.write_all(b"-")?;
+    }
+    out.write_all(b"\x1b\\")
+}
@@ -0,0 +1,170 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::input::command_help_lines;
+use super::keymap::{KeyAction, Keymap, Mode, MODES};
+use super::theme::Theme;
+
+/// Help screen showing the keys of one mode at a time, starting with the
+/// mode it was opened from, or searching every mode's. Its text comes from
+/// the keymap and the mode and command tables, so it follows the bindings.
+pub struct HelpScreen {
+    visible: bool,
+    mode: Mode,
+    /// Text being searched for across all modes, while searching.
+    search: Option<String>,
+    /// Lines scrolled past at the top.
+    scroll: u16,
+    theme: Theme,
+}
+
+impl Default for HelpScreen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HelpScreen {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            mode: Mode::Normal,
+            search: None,
+            scroll: 0,
+            theme: Theme::default(),
+        }
+    }
+    
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+    
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+    
+    /// Show the keys of `mode`, the one the user is in.
+    pub fn open(&mut self, mode: Mode) {
+        self.visible = true;
+        self.mode = mode;
+        self.search = None;
+        self.scroll = 0;
+    }
+    
+    /// Handle a key while help is shown: Tab and ←/→ page through modes,
+    /// ↑/↓ scroll, `/` searches every mode, and Esc or the help key close.
+    pub fn handle_key(&mut self, key: &KeyEvent, keymap: &Keymap) {
+        if let Some(query) = &mut self.search {
+            match key.code {
+                KeyCode::Esc => self.search = None,
+                KeyCode::Backspace if query.is_empty() => self.search = None,
+                KeyCode::Backspace => {
+                    query.pop();
+                }
+                KeyCode::Char(c) => query.push(c),
+                KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+                KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+                _ => {}
+            }
+            if matches!(key.code, KeyCode::Char(_) | KeyCode::Backspace) {
+                self.scroll = 0;
+            }
+            return;
+        }
+        
+        let index = MODES.iter().position(|&mode| mode == self.mode).unwrap_or(0);
+        match key.code {
+            KeyCode::Esc => self.visible = false,
+            KeyCode::Char('/') => self.search = Some(String::new()),
+            KeyCode::Tab | KeyCode::Right => self.show_mode(MODES[(index + 1) % MODES.len()]),
+            KeyCode::BackTab | KeyCode::Left => self.show_mode(MODES[(index + MODES.len() - 1) % MODES.len()]),
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+            _ if keymap.action(key) == Some(KeyAction::Help) => self.visible = false,
+            _ => {}
+        }
+    }
+    
+    fn show_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+        self.scroll = 0;
+    }
+    
+    /// Help lines for `mode`, with the commands after command mode's keys.
+    fn mode_lines(mode: Mode, keymap: &Keymap) -> Vec<String> {
+        let mut lines = keymap.cheatsheet(mode);
+        if mode == Mode::Command {
+            lines.extend(command_help_lines());
+        }
+        lines
+    }
+    
+    /// Key and command lines of every mode containing the search text,
+    /// ignoring case, each labelled with its mode.
+    fn search_lines(query: &str, keymap: &Keymap) -> Vec<String> {
+        let query = query.to_lowercase();
+        MODES.iter()
+            .flat_map(|&mode| {
+                Self::mode_lines(mode, keymap).into_iter()
+                    // Headings only name the mode
+                    .filter(|line| line.starts_with("  "))
+                    .filter(|line| line.to_lowercase().contains(&query))
+                    .map(move |line| format!("{:<18}{}", mode.label(), line))
+            })
+            .collect()
+    }
+    
+    pub fn render(&self, frame: &mut Frame, area: Rect, keymap: &Keymap) {
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title("Help")
+            .borders(Borders::ALL)
+            .style(Style::default().fg(self.theme.accent));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(2), // Modes, or the search text
+                Constraint::Min(0),    // Keys
+                Constraint::Length(1), // Key hints
+            ])
+            .split(inner);
+        
+        let header = match &self.search {
+            Some(query) => Line::from(format!("Search all modes: {}_", query)),
+            None => Line::from(MODES.iter().enumerate().flat_map(|(index, &mode)| {
+                let style = if mode == self.mode {
+                    Style::default().fg(self.theme.active_tab.0).bg(self.theme.active_tab.1)
+                } else {
+                    Style::default().fg(self.theme.muted)
+                };
+                let separator = if index == 0 { "" } else { " | " };
+                [Span::raw(separator), Span::styled(mode.label(), style)]
+            }).collect::<Vec<_>>()),
+        };
+        frame.render_widget(Paragraph::new(header), rows[0]);
+        
+        let lines = match &self.search {
+            Some(query) => Self::search_lines(query, keymap),
+            None => Self::mode_lines(self.mode, keymap),
+        };
+        let body = Paragraph::new(lines.into_iter().map(Line::from).collect::<Vec<_>>())
+            .style(Style::default().fg(self.theme.text))
+            .scroll((self.scroll, 0));
+        frame.render_widget(body, rows[1]);
+        
+        let hint = if self.search.is_some() {
+            "Type to search, Backspace on empty or Esc: back to modes"
+        } else {
+            "Tab/←/→ other modes, / search, ↑/↓ scroll, Esc close; `keymap <name>` switches keys"
+        };
+        frame.render_widget(Paragraph::new(hint).style(Style::default().fg(self.theme.hint)), rows[2]);
+    }
+}
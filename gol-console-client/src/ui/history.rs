@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use crate::client::game_of_life::SimulationResponse;
+use super::theme::Theme;
+
+/// Past generations kept after fetching, so scrubbing back and forth over
+/// the same stretch does not rebuild them on the server again.
+const CACHED_FRAMES: usize = 64;
+
+/// Generations PageUp and PageDown move by.
+pub const PAGE_GENERATIONS: i64 = 10;
+
+/// Browses a simulation's past generations, which the server rebuilds from
+/// its replay log, without changing the simulation.
+pub struct HistoryScrubber {
+    /// Earliest generation the server can rebuild.
+    first: i64,
+    /// The simulation's live generation.
+    head: i64,
+    /// Generation shown.
+    position: i64,
+    frames: BTreeMap<i64, SimulationResponse>,
+    /// Why the shown generation could not be fetched, if it could not.
+    error: Option<String>,
+}
+
+impl HistoryScrubber {
+    /// Start at the live head.
+    pub fn new(first: i64, head: i64) -> Self {
+        Self {
+            first,
+            head,
+            position: head,
+            frames: BTreeMap::new(),
+            error: None,
+        }
+    }
+    
+    pub fn position(&self) -> i64 {
+        self.position
+    }
+    
+    /// Take the range reported by GetHistory; the head moves on when the
+    /// simulation is stepped elsewhere.
+    pub fn update_range(&mut self, first: i64, head: i64) {
+        self.first = first;
+        self.head = head;
+        self.frames.retain(|&generation, _| generation >= first);
+        self.position = self.position.clamp(first, head);
+    }
+    
+    /// Move to `generation`, within the stored range.
+    pub fn seek(&mut self, generation: i64) {
+        self.position = generation.clamp(self.first, self.head);
+    }
+    
+    pub fn step(&mut self, generations: i64) {
+        self.seek(self.position + generations);
+    }
+    
+    pub fn seek_head(&mut self) {
+        self.position = self.head;
+    }
+    
+    pub fn seek_first(&mut self) {
+        self.position = self.first;
+    }
+    
+    pub fn cached_frame(&self) -> Option<&SimulationResponse> {
+        self.frames.get(&self.position)
+    }
+    
+    /// Keep a fetched generation, dropping the one farthest from the shown
+    /// generation when the cache is full.
+    pub fn store_frame(&mut self, frame: SimulationResponse) {
+        self.error = None;
+        self.frames.insert(frame.generation, frame);
+        if self.frames.len() > CACHED_FRAMES {
+            let position = self.position;
+            let farthest = self.frames.keys().copied().max_by_key(|&generation| (generation - position).abs());
+            if let Some(farthest) = farthest {
+                self.frames.remove(&farthest);
+            }
+        }
+    }
+    
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+    
+    /// One row: the shown generation, a track from the first stored
+    /// generation to the live head with a marker at the shown one, and the
+    /// head, marked live.
+    pub fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let left = format!(" gen {} ", self.position);
+        let first = self.first.to_string();
+        let right = format!(" {} LIVE ", self.head);
+        // The head marker takes one column
+        let labels = left.chars().count() + first.len() + 1 + right.chars().count();
+        let track_width = (area.width as usize).saturating_sub(labels);
+        
+        let span = (self.head - self.first).max(1) as f64;
+        let marker = ((self.position - self.first) as f64 / span * track_width.saturating_sub(1) as f64).round() as usize;
+        let track: String = match &self.error {
+            // In place of the track, which has no marker to show
+            Some(error) => format!(" {:width$}", error, width = track_width.saturating_sub(1)).chars().take(track_width).collect(),
+            None => (0..track_width).map(|index| if index == marker { '●' } else { '━' }).collect(),
+        };
+        let track_style = if self.error.is_some() { theme.disconnected } else { theme.axis };
+        
+        let at_head = self.position == self.head;
+        let head_style = if at_head {
+            Style::default().fg(theme.active_tab.0).bg(theme.active_tab.1).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.hint)
+        };
+        let spans = vec![
+            Span::styled(left, Style::default().fg(theme.accent)),
+            Span::styled(first, Style::default().fg(theme.muted)),
+            Span::styled(track, Style::default().fg(track_style)),
+            Span::styled("┃", head_style),
+            Span::styled(right, head_style),
+        ];
+        
+        frame.render_widget(Paragraph::new(Line::from(spans)), area);
+    }
+}
@@ -1,27 +1,391 @@
 use anyhow::Result;
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent};
 use std::collections::VecDeque;
+use crate::client::game_of_life::{breakpoint, Breakpoint, Region, Watchpoint};
 use crate::client::GameOfLifeClient;
 use crate::commands::{simulation, pattern, control};
+use super::keymap::{mode_action, KeyAction, Keymap, Mode, ModeAction};
 
 #[derive(Debug, Clone)]
 pub enum InputAction {
     Quit,
     Help,
-    ShowHelp(bool),
     MoveViewport(i32, i32),
     Zoom(f32),
     ResetViewport,
     StepSimulation,
+    /// Step this many generations in one call.
+    StepMany(i32),
+    /// Move this many speed presets faster (positive) or slower.
+    ChangeSpeed(i32),
     RunSimulation,
     PauseSimulation,
     ClearGrid,
     LoadPattern(String),
+    /// Start measuring, observing speeds over this many generations.
+    Measure(Option<i64>),
     CommandMode,
     ExecuteCommand(String),
     CenterOnCells,
     SavePattern(String),
     SwitchBackend(String),
+    ToggleCensus,
+    ToggleDiff,
+    ToggleHeatmap,
+    TogglePerformance,
+    TogglePicker,
+    ToggleRulers,
+    TogglePixels,
+    ToggleHistory,
+    /// Move to the next (1) or previous (-1) match of the last `find`.
+    CycleMatch(i32),
+    NextTab,
+    PreviousTab,
+    ShowMenu,
+    ShowPalette,
+}
+
+/// Commands by group, with their arguments and what they do, for help.
+const COMMANDS: [(&str, &[(&str, &str)]); 4] = [
+    ("Simulation", &[
+        ("create <w> <h> [pattern]", "Create new simulation ('random [seed]' fills it)"),
+        ("step [count] [sim_id]", "Step simulation"),
+        ("run [sim_id]", "Run simulation"),
+        ("status", "Get server status"),
+        ("clear", "Clear grid"),
+        ("diff <a>[@gen] [b[@gen]]", "Overlay cells only in a (red) or only in b (cyan)"),
+        ("break [gen <n>]", "List breakpoints, or stop running at generation n"),
+        ("break pop >|< <n>", "Stop when the population crosses n"),
+        ("break pattern <name|rle>", "Stop when more of a pattern appear (B3/S23)"),
+        ("break clear", "Remove every breakpoint"),
+        ("rule [<rule> [at <gen>]]", "List rule changes, or switch rule now or at generation gen"),
+        ("rule cancel <gen>", "Cancel the rule change at generation gen"),
+        ("checkpoint [name]", "List checkpoints, or save the current state as name"),
+        ("checkpoint rm <name>", "Remove a checkpoint"),
+        ("rollback <name>", "Roll back to a checkpoint"),
+        ("watch x y [w h] [pause]", "List watchpoints, or flag changes to a cell or rectangle; pause stops running"),
+        ("watch clear", "Remove every watchpoint"),
+    ]),
+    ("Tabs", &[
+        ("tab new [sim_id]", "Open a tab on a simulation, or a new one"),
+        ("tab close", "Close the current tab"),
+        ("tab next|prev|<n>", "Switch tabs"),
+    ]),
+    ("Patterns", &[
+        ("load <name> [x] [y]", "Load pattern at position"),
+        ("gen <n>", "Show generation n from history"),
+        ("find <name|rle>", "Find a pattern in any orientation; next/previous match keys cycle"),
+        ("stamp <name|rle>", "Place a pattern"),
+        ("measure [generations]", "Measure distances, and speeds over n generations (default: the period)"),
+        ("note [x y] <text>", "Pin a label to a cell, or to the middle of the view"),
+        ("notes", "List labels"),
+        ("note go|rm <n>", "Jump to or remove label n"),
+        ("note clear", "Remove every label"),
+    ]),
+    ("Control", &[
+        ("backend <name>", "Switch backend (bevy|entt|flecs)"),
+        ("theme [name]", "Switch or cycle color theme"),
+        ("keymap [name]", "Switch or cycle keys (arrows|vim|wasd|emacs)"),
+        ("help", "Show command mode help"),
+        ("quit", "Exit application"),
+    ]),
+];
+
+/// Every command, grouped, as help lines.
+pub fn command_help_lines() -> Vec<String> {
+    let mut lines = Vec::new();
+    for (group, commands) in COMMANDS {
+        lines.push(format!("{} commands:", group));
+        lines.extend(commands.iter().map(|(usage, description)| format!("  {:<24} - {}", usage, description)));
+    }
+    lines
+}
+
+/// A `tab` command from command mode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TabCommand {
+    /// Open a tab on the given simulation, or on a new one.
+    New(Option<String>),
+    Close,
+    Next,
+    Previous,
+    /// Switch to a tab by its number, counting from one.
+    Go(usize),
+}
+
+/// A `break` command from command mode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BreakCommand {
+    List,
+    Add(Breakpoint),
+    Clear,
+}
+
+/// A `rule` command from command mode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleCommand {
+    List,
+    /// Switch to a rule, at a generation or now.
+    Set(String, Option<u64>),
+    Cancel(u64),
+}
+
+/// Parse `rule`, `rule <rule>`, `rule <rule> at <generation>` or
+/// `rule cancel <generation>`.
+pub fn parse_rule_command(command: &str) -> Option<RuleCommand> {
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    match parts.as_slice() {
+        ["rule"] => Some(RuleCommand::List),
+        ["rule", "cancel", generation] => generation.parse().ok().map(RuleCommand::Cancel),
+        ["rule", rule] => Some(RuleCommand::Set(rule.to_string(), None)),
+        ["rule", rule, "at", generation] => generation.parse().ok().map(|generation| RuleCommand::Set(rule.to_string(), Some(generation))),
+        _ => None,
+    }
+}
+
+/// A `checkpoint` or `rollback` command from command mode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckpointCommand {
+    List,
+    Create(String),
+    Remove(String),
+    Restore(String),
+}
+
+/// Parse `checkpoint`, `checkpoint <name>`, `checkpoint rm <name>` or
+/// `rollback <name>`; names may have spaces.
+pub fn parse_checkpoint_command(command: &str) -> Option<CheckpointCommand> {
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    match parts.as_slice() {
+        ["checkpoint" | "checkpoints"] => Some(CheckpointCommand::List),
+        ["checkpoint", "rm", name @ ..] if !name.is_empty() => Some(CheckpointCommand::Remove(name.join(" "))),
+        ["checkpoint", name @ ..] => Some(CheckpointCommand::Create(name.join(" "))),
+        ["rollback", name @ ..] if !name.is_empty() => Some(CheckpointCommand::Restore(name.join(" "))),
+        _ => None,
+    }
+}
+
+/// Parse `break`, `break gen <n>`, `break pop > <n>`, `break pop < <n>`,
+/// `break pattern <pattern>` or `break clear`.
+pub fn parse_break_command(command: &str) -> Option<BreakCommand> {
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    let condition = match parts.as_slice() {
+        ["break" | "b"] => return Some(BreakCommand::List),
+        ["break" | "b", "clear"] => return Some(BreakCommand::Clear),
+        ["break" | "b", "gen" | "generation", generation] => breakpoint::Condition::Generation(generation.parse().ok()?),
+        ["break" | "b", "pattern", pattern] => breakpoint::Condition::Pattern(pattern.to_string()),
+        // `pop > 100`, `pop >100` and `pop>100` alike
+        ["break" | "b", rest @ ..] => {
+            let rest = rest.concat();
+            let threshold = rest.strip_prefix("population").or_else(|| rest.strip_prefix("pop"))?;
+            if let Some(count) = threshold.strip_prefix('>') {
+                breakpoint::Condition::PopulationAbove(count.parse().ok()?)
+            } else {
+                breakpoint::Condition::PopulationBelow(threshold.strip_prefix('<')?.parse().ok()?)
+            }
+        }
+        _ => return None,
+    };
+    Some(BreakCommand::Add(Breakpoint { condition: Some(condition) }))
+}
+
+/// A breakpoint as the `break` command that sets it would give it, e.g.
+/// `pop > 100`.
+pub fn breakpoint_label(breakpoint: &Breakpoint) -> String {
+    match &breakpoint.condition {
+        Some(breakpoint::Condition::PopulationAbove(count)) => format!("pop > {}", count),
+        Some(breakpoint::Condition::PopulationBelow(count)) => format!("pop < {}", count),
+        Some(breakpoint::Condition::Pattern(apgcode)) => format!("pattern {}", apgcode),
+        Some(breakpoint::Condition::Generation(generation)) => format!("gen {}", generation),
+        None => "unknown".to_string(),
+    }
+}
+
+/// A `watch` command from command mode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchCommand {
+    List,
+    Add(Watchpoint),
+    Clear,
+}
+
+/// Parse `watch`, `watch <x> <y> [<width> <height>] [pause]` or
+/// `watch clear`.
+pub fn parse_watch_command(command: &str) -> Option<WatchCommand> {
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    let (coordinates, pause) = match parts.as_slice() {
+        ["watch"] => return Some(WatchCommand::List),
+        ["watch", "clear"] => return Some(WatchCommand::Clear),
+        ["watch", rest @ .., "pause"] => (rest, true),
+        ["watch", rest @ ..] => (rest, false),
+        _ => return None,
+    };
+    let numbers: Vec<i64> = coordinates.iter().map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    let (x, y, width, height) = match *numbers.as_slice() {
+        [x, y] => (x, y, 1, 1),
+        [x, y, width, height] => (x, y, width, height),
+        _ => return None,
+    };
+    Some(WatchCommand::Add(Watchpoint { region: Some(Region { x, y, width, height }), pause }))
+}
+
+/// A watchpoint as `(10, 10)` for one cell or `3x3 at (10, 10)`, marked
+/// when it pauses.
+pub fn watchpoint_label(watchpoint: &Watchpoint) -> String {
+    let Some(region) = &watchpoint.region else {
+        return "unknown".to_string();
+    };
+    let mut label = format!("({}, {})", region.x, region.y);
+    if (region.width, region.height) != (1, 1) {
+        label = format!("{}x{} at {}", region.width, region.height, label);
+    }
+    if watchpoint.pause {
+        label.push_str(" pause");
+    }
+    label
+}
+
+/// A `note` command from command mode. Labels are numbered from one, in
+/// the order `notes` lists them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NoteCommand {
+    List,
+    /// Pin text to a cell, or to the middle of the view when `None`.
+    Add(Option<(i64, i64)>, String),
+    Go(usize),
+    Remove(usize),
+    Clear,
+}
+
+/// Parse `notes`, `note [<x> <y>] <text>`, `note go <n>`, `note rm <n>` or
+/// `note clear`.
+pub fn parse_note_command(command: &str) -> Option<NoteCommand> {
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    let number = |n: &str| n.parse().ok().filter(|&n| n > 0);
+    match parts.as_slice() {
+        ["notes"] | ["note"] => Some(NoteCommand::List),
+        ["note", "clear"] => Some(NoteCommand::Clear),
+        ["note", "go", n] => number(n).map(NoteCommand::Go),
+        ["note", "rm", n] => number(n).map(NoteCommand::Remove),
+        ["note", x, y, text @ ..] if !text.is_empty() => match (x.parse(), y.parse()) {
+            (Ok(x), Ok(y)) => Some(NoteCommand::Add(Some((x, y)), text.join(" "))),
+            _ => Some(NoteCommand::Add(None, parts[1..].join(" "))),
+        },
+        ["note", text @ ..] => Some(NoteCommand::Add(None, text.join(" "))),
+        _ => None,
+    }
+}
+
+/// Parse `theme [name]`: the named theme, or `None` for the next one.
+pub fn parse_theme_command(command: &str) -> Option<Option<String>> {
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    match parts.as_slice() {
+        ["theme"] => Some(None),
+        ["theme", name] => Some(Some(name.to_string())),
+        _ => None,
+    }
+}
+
+/// Parse `keymap [name]`: the named keymap, or `None` for the next one.
+pub fn parse_keymap_command(command: &str) -> Option<Option<String>> {
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    match parts.as_slice() {
+        ["keymap"] => Some(None),
+        ["keymap", name] => Some(Some(name.to_string())),
+        _ => None,
+    }
+}
+
+/// Parse `find <pattern>`, returning the pattern: a name, a JSON file or
+/// inline RLE.
+pub fn parse_find_command(command: &str) -> Option<String> {
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    match parts.as_slice() {
+        ["find" | "f", pattern] => Some(pattern.to_string()),
+        _ => None,
+    }
+}
+
+/// Parse `stamp <pattern>`, returning the pattern to place: a name, a JSON
+/// file or inline RLE.
+pub fn parse_stamp_command(command: &str) -> Option<String> {
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    match parts.as_slice() {
+        ["stamp", pattern] => Some(pattern.to_string()),
+        _ => None,
+    }
+}
+
+/// Parse `measure [generations]`, returning the generations speeds are
+/// observed over, if given.
+pub fn parse_measure_command(command: &str) -> Option<Option<i64>> {
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    match parts.as_slice() {
+        ["measure"] => Some(None),
+        ["measure", generations] => generations.parse().ok().filter(|&generations| generations > 0).map(Some),
+        _ => None,
+    }
+}
+
+/// Whether `action` changes the shown simulation: stepping or running it,
+/// placing a pattern, or a command that does.
+pub fn changes_simulation(action: &InputAction) -> bool {
+    match action {
+        InputAction::StepSimulation | InputAction::StepMany(_) | InputAction::RunSimulation | InputAction::LoadPattern(_) => true,
+        InputAction::ExecuteCommand(command) => matches!(
+            command.split_whitespace().next().map(str::to_lowercase).as_deref(),
+            Some("step" | "s" | "run" | "r" | "load" | "l" | "stamp")
+        ),
+        _ => false,
+    }
+}
+
+/// Parse `gen <n>` or `goto <n>`, a generation to show from history.
+pub fn parse_goto_command(command: &str) -> Option<i64> {
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    match parts.as_slice() {
+        ["gen" | "goto", generation] => generation.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Parse `tab new [id]`, `tab close`, `tab next`, `tab prev` or `tab <n>`.
+pub fn parse_tab_command(command: &str) -> Option<TabCommand> {
+    let parts: Vec<&str> = command.split_whitespace().collect();
+    match parts.as_slice() {
+        ["tab", "new"] | ["tabnew"] => Some(TabCommand::New(None)),
+        ["tab", "new", id] | ["tabnew", id] => Some(TabCommand::New(Some(id.to_string()))),
+        ["tab", "close"] | ["tabclose"] => Some(TabCommand::Close),
+        ["tab", "next"] | ["tabnext"] => Some(TabCommand::Next),
+        ["tab", "prev" | "previous"] | ["tabprevious"] => Some(TabCommand::Previous),
+        ["tab", number] => number.parse().ok().filter(|&n| n > 0).map(TabCommand::Go),
+        _ => None,
+    }
+}
+
+/// One side of a diff: a simulation id and, for an earlier state, the
+/// generation to rebuild.
+pub type DiffSide = (String, Option<i64>);
+
+/// Parse `diff <a>[@gen] [<b>[@gen]]`. Without `b` the second side is the
+/// current state of `a`.
+pub fn parse_diff_command(command: &str) -> Option<(DiffSide, DiffSide)> {
+    let mut parts = command.split_whitespace();
+    if !parts.next()?.eq_ignore_ascii_case("diff") {
+        return None;
+    }
+    let side = |arg: &str| -> Option<DiffSide> {
+        match arg.split_once('@') {
+            Some((id, generation)) => Some((id.to_string(), Some(generation.parse().ok()?))),
+            None => Some((arg.to_string(), None)),
+        }
+    };
+    let a = side(parts.next()?)?;
+    let b = match parts.next() {
+        Some(arg) => side(arg)?,
+        None => (a.0.clone(), None),
+    };
+    parts.next().is_none().then_some((a, b))
 }
 
 pub struct InputHandler {
@@ -29,7 +393,17 @@ pub struct InputHandler {
     command_buffer: String,
     command_history: VecDeque<String>,
     history_index: usize,
-    show_help: bool,
+    /// Whether the tab prefix (`g`) was pressed and the next key completes
+    /// `gt` or `gT`.
+    pending_g: bool,
+    /// Which keys do what outside command mode.
+    keymap: Keymap,
+}
+
+impl Default for InputHandler {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl InputHandler {
@@ -39,10 +413,19 @@ impl InputHandler {
             command_buffer: String::new(),
             command_history: VecDeque::new(),
             history_index: 0,
-            show_help: false,
+            pending_g: false,
+            keymap: Keymap::default(),
         }
     }
     
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+    
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+    
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Result<Option<InputAction>> {
         if self.command_mode {
             self.handle_command_mode_key(key)
@@ -52,48 +435,74 @@ impl InputHandler {
     }
     
     fn handle_normal_mode_key(&mut self, key: KeyEvent) -> Result<Option<InputAction>> {
-        match key.code {
-            KeyCode::Char('q') | KeyCode::Char('Q') => Ok(Some(InputAction::Quit)),
-            KeyCode::Char('h') | KeyCode::Char('H') => {
-                self.show_help = !self.show_help;
-                Ok(Some(InputAction::ShowHelp(self.show_help)))
+        if std::mem::take(&mut self.pending_g) {
+            match key.code {
+                KeyCode::Char('t') => return Ok(Some(InputAction::NextTab)),
+                KeyCode::Char('T') => return Ok(Some(InputAction::PreviousTab)),
+                _ => {}
             }
-            KeyCode::Char('r') | KeyCode::Char('R') => Ok(Some(InputAction::RunSimulation)),
-            KeyCode::Char('s') | KeyCode::Char('S') => Ok(Some(InputAction::StepSimulation)),
-            KeyCode::Char('p') | KeyCode::Char('P') => Ok(Some(InputAction::PauseSimulation)),
-            KeyCode::Char('c') | KeyCode::Char('C') => Ok(Some(InputAction::ClearGrid)),
-            KeyCode::Char('l') | KeyCode::Char('L') => Ok(Some(InputAction::LoadPattern("glider".to_string()))),
-            KeyCode::Char('o') | KeyCode::Char('O') => Ok(Some(InputAction::CenterOnCells)),
-            KeyCode::Enter => {
+        }
+        
+        Ok(self.keymap.action(&key).and_then(|action| self.perform(action)))
+    }
+    
+    /// What `action` does, whether its key was pressed or it was picked in
+    /// the command palette.
+    pub fn perform(&mut self, action: KeyAction) -> Option<InputAction> {
+        Some(match action {
+            KeyAction::Quit => InputAction::Quit,
+            KeyAction::Help => InputAction::Help,
+            KeyAction::Command => {
                 self.command_mode = true;
                 self.command_buffer.clear();
-                Ok(Some(InputAction::CommandMode))
+                InputAction::CommandMode
             }
-            KeyCode::Home => Ok(Some(InputAction::ResetViewport)),
-            KeyCode::Up => Ok(Some(InputAction::MoveViewport(0, -1))),
-            KeyCode::Down => Ok(Some(InputAction::MoveViewport(0, 1))),
-            KeyCode::Left => Ok(Some(InputAction::MoveViewport(-1, 0))),
-            KeyCode::Right => Ok(Some(InputAction::MoveViewport(1, 0))),
-            KeyCode::Char('+') | KeyCode::Char('=') => Ok(Some(InputAction::Zoom(1.2))),
-            KeyCode::Char('-') | KeyCode::Char('_') => Ok(Some(InputAction::Zoom(0.8))),
-            KeyCode::Char('1') => Ok(Some(InputAction::SwitchBackend("bevy".to_string()))),
-            KeyCode::Char('2') => Ok(Some(InputAction::SwitchBackend("entt".to_string()))),
-            KeyCode::Char('3') => Ok(Some(InputAction::SwitchBackend("flecs".to_string()))),
-            KeyCode::Esc => {
-                if self.show_help {
-                    self.show_help = false;
-                    Ok(Some(InputAction::ShowHelp(false)))
-                } else {
-                    Ok(None)
-                }
+            KeyAction::TabPrefix => {
+                self.pending_g = true;
+                return None;
             }
-            _ => Ok(None),
-        }
+            KeyAction::MoveUp | KeyAction::MoveDown | KeyAction::MoveLeft | KeyAction::MoveRight => {
+                let (dx, dy) = action.direction().unwrap_or_default();
+                InputAction::MoveViewport(dx, dy)
+            }
+            KeyAction::ZoomIn => InputAction::Zoom(1.2),
+            KeyAction::ZoomOut => InputAction::Zoom(0.8),
+            KeyAction::ResetView => InputAction::ResetViewport,
+            KeyAction::Center => InputAction::CenterOnCells,
+            KeyAction::Run => InputAction::RunSimulation,
+            KeyAction::Step => InputAction::StepSimulation,
+            KeyAction::Step10 => InputAction::StepMany(10),
+            KeyAction::Step100 => InputAction::StepMany(100),
+            KeyAction::Step1000 => InputAction::StepMany(1000),
+            KeyAction::Faster => InputAction::ChangeSpeed(1),
+            KeyAction::Slower => InputAction::ChangeSpeed(-1),
+            KeyAction::Pause => InputAction::PauseSimulation,
+            KeyAction::Clear => InputAction::ClearGrid,
+            KeyAction::PlaceGlider => InputAction::LoadPattern("glider".to_string()),
+            KeyAction::Measure => InputAction::Measure(None),
+            KeyAction::History => InputAction::ToggleHistory,
+            KeyAction::NextMatch => InputAction::CycleMatch(1),
+            KeyAction::PreviousMatch => InputAction::CycleMatch(-1),
+            KeyAction::Census => InputAction::ToggleCensus,
+            KeyAction::Diff => InputAction::ToggleDiff,
+            KeyAction::Heatmap => InputAction::ToggleHeatmap,
+            KeyAction::Performance => InputAction::TogglePerformance,
+            KeyAction::Picker => InputAction::TogglePicker,
+            KeyAction::Rulers => InputAction::ToggleRulers,
+            KeyAction::Pixels => InputAction::TogglePixels,
+            KeyAction::Menu => InputAction::ShowMenu,
+            KeyAction::Palette => InputAction::ShowPalette,
+            KeyAction::NextTab => InputAction::NextTab,
+            KeyAction::PreviousTab => InputAction::PreviousTab,
+            KeyAction::Bevy => InputAction::SwitchBackend("bevy".to_string()),
+            KeyAction::Entt => InputAction::SwitchBackend("entt".to_string()),
+            KeyAction::Flecs => InputAction::SwitchBackend("flecs".to_string()),
+        })
     }
     
     fn handle_command_mode_key(&mut self, key: KeyEvent) -> Result<Option<InputAction>> {
-        match key.code {
-            KeyCode::Enter => {
+        match mode_action(Mode::Command, &key) {
+            Some(ModeAction::RunCommand) => {
                 let command = self.command_buffer.trim().to_string();
                 if !command.is_empty() {
                     self.add_to_history(command.clone());
@@ -105,23 +514,23 @@ impl InputHandler {
                     Ok(None)
                 }
             }
-            KeyCode::Esc => {
+            Some(ModeAction::CancelCommand) => {
                 self.command_mode = false;
                 self.command_buffer.clear();
                 Ok(None)
             }
-            KeyCode::Backspace => {
+            Some(ModeAction::EraseCharacter) => {
                 self.command_buffer.pop();
                 Ok(None)
             }
-            KeyCode::Up => {
+            Some(ModeAction::OlderCommand) => {
                 if !self.command_history.is_empty() && self.history_index > 0 {
                     self.history_index -= 1;
                     self.command_buffer = self.command_history[self.history_index].clone();
                 }
                 Ok(None)
             }
-            KeyCode::Down => {
+            Some(ModeAction::NewerCommand) => {
                 if !self.command_history.is_empty() && self.history_index < self.command_history.len() - 1 {
                     self.history_index += 1;
                     self.command_buffer = self.command_history[self.history_index].clone();
@@ -130,14 +539,21 @@ impl InputHandler {
                 }
                 Ok(None)
             }
-            KeyCode::Char(c) => {
-                self.command_buffer.push(c);
+            _ => {
+                if let KeyCode::Char(c) = key.code {
+                    self.command_buffer.push(c);
+                }
                 Ok(None)
             }
-            _ => Ok(None),
         }
     }
     
+    /// Enter command mode with `text` already typed, e.g. `gen `.
+    pub fn open_command(&mut self, text: &str) {
+        self.command_mode = true;
+        self.command_buffer = text.to_string();
+    }
+    
     fn add_to_history(&mut self, command: String) {
         if self.command_history.len() >= 50 {
             self.command_history.pop_front();
@@ -154,11 +570,9 @@ impl InputHandler {
         &self.command_buffer
     }
     
-    pub fn is_help_shown(&self) -> bool {
-        self.show_help
-    }
-    
-    pub async fn execute_command(&mut self, command: &str, client: &mut GameOfLifeClient) -> Result<String> {
+    /// Run a command against `simulation_id`, the simulation of the current
+    /// tab, unless the command names another.
+    pub async fn execute_command(&mut self, command: &str, client: &mut GameOfLifeClient, simulation_id: &str) -> Result<String> {
         let parts: Vec<&str> = command.split_whitespace().collect();
         if parts.is_empty() {
             return Ok("No command entered".to_string());
@@ -168,7 +582,7 @@ impl InputHandler {
         let args = &parts[1..];
         
         match cmd.as_str() {
-            "help" | "h" => Ok(self.get_help_text()),
+            "help" | "h" => Ok(command_help_lines().join("\n")),
             "quit" | "q" | "exit" => Ok("Quitting...".to_string()),
             
             "create" | "new" => {
@@ -176,24 +590,32 @@ impl InputHandler {
                     let width = args[0].parse::<i32>().unwrap_or(50);
                     let height = args[1].parse::<i32>().unwrap_or(30);
                     let pattern = args.get(2).map(|s| s.to_string());
+                    let seed = args.get(3).and_then(|s| s.parse::<u64>().ok());
                     
                     let mut sim_cmd = simulation::SimulationCommands::new(client.clone());
-                    match sim_cmd.create(width, height, pattern).await {
-                        Ok(response) => Ok(format!("Created simulation: {}", response.id)),
+                    match sim_cmd.create(width, height, pattern, seed).await {
+                        Ok(response) => match response.seed {
+                            Some(seed) => Ok(format!("Created simulation: {} (seed {})", response.id, seed)),
+                            None => Ok(format!("Created simulation: {}", response.id)),
+                        },
                         Err(e) => Ok(format!("Error creating simulation: {}", e)),
                     }
                 } else {
-                    Ok("Usage: create <width> <height> [pattern]".to_string())
+                    Ok("Usage: create <width> <height> [pattern] [seed]".to_string())
                 }
             }
             
             "step" | "s" => {
-                let steps = args.get(0).and_then(|s| s.parse::<i32>().ok()).unwrap_or(1);
-                let sim_id = args.get(1).unwrap_or(&"default").to_string();
+                let steps = args.first().and_then(|s| s.parse::<i32>().ok()).unwrap_or(1);
+                let sim_id = args.get(1).unwrap_or(&simulation_id).to_string();
                 
                 let mut sim_cmd = simulation::SimulationCommands::new(client.clone());
                 match sim_cmd.step(sim_id, steps).await {
-                    Ok(response) => Ok(format!("Stepped to generation {}", response.generation)),
+                    Ok(response) => match (&response.breakpoint, &response.watchpoint) {
+                        (Some(breakpoint), _) => Ok(format!("Stopped at generation {}: breakpoint {}", response.generation, breakpoint_label(breakpoint))),
+                        (None, Some(watchpoint)) => Ok(format!("Stopped at generation {}: watchpoint {}", response.generation, watchpoint_label(watchpoint))),
+                        (None, None) => Ok(format!("Stepped to generation {}", response.generation)),
+                    },
                     Err(e) => Ok(format!("Error stepping simulation: {}", e)),
                 }
             }
@@ -201,13 +623,13 @@ impl InputHandler {
             "load" | "l" => {
                 if !args.is_empty() {
                     let pattern_name = args[0];
-                    let x = args.get(1).and_then(|s| s.parse::<i32>().ok()).unwrap_or(0);
-                    let y = args.get(2).and_then(|s| s.parse::<i32>().ok()).unwrap_or(0);
+                    let x = args.get(1).and_then(|s| s.parse::<i64>().ok()).unwrap_or(0);
+                    let y = args.get(2).and_then(|s| s.parse::<i64>().ok()).unwrap_or(0);
                     
                     let mut pattern_cmd = pattern::PatternCommands::new(client.clone());
                     let pattern_file = format!("../patterns/{}.json", pattern_name);
                     
-                    match pattern_cmd.load_from_file("default".to_string(), &pattern_file, x, y).await {
+                    match pattern_cmd.load_from_file(simulation_id.to_string(), &pattern_file, x, y).await {
                         Ok(_) => Ok(format!("Loaded pattern: {}", pattern_name)),
                         Err(e) => Ok(format!("Error loading pattern: {}", e)),
                     }
@@ -218,7 +640,7 @@ impl InputHandler {
             
             "run" | "r" => {
                 let mut control_cmd = control::ControlCommands::new(client.clone());
-                match control_cmd.play(Some("default".to_string())).await {
+                match control_cmd.play(Some(args.first().unwrap_or(&simulation_id).to_string())).await {
                     Ok(_) => Ok("Started simulation".to_string()),
                     Err(e) => Ok(format!("Error running simulation: {}", e)),
                 }
@@ -253,34 +675,6 @@ impl InputHandler {
         }
     }
     
-    fn get_help_text(&self) -> String {
-        let help = vec![
-            "Available Commands:",
-            "",
-            "Simulation:",
-            "  create <w> <h> [pattern] - Create new simulation",
-            "  step [count] [sim_id]    - Step simulation",
-            "  run [sim_id]             - Run simulation",
-            "  status                   - Get server status",
-            "  clear                    - Clear grid",
-            "",
-            "Patterns:",
-            "  load <name> [x] [y]      - Load pattern at position",
-            "",
-            "Control:",
-            "  backend <name>           - Switch backend (bevy|entt|flecs)",
-            "  help                     - Show this help",
-            "  quit                     - Exit application",
-            "",
-            "Keyboard Shortcuts:",
-            "  q - quit, h - help, r - run, s - step, p - pause",
-            "  arrows - move view, +/- - zoom, Enter - command mode",
-            "  1/2/3 - switch backend, o - center on cells",
-        ];
-        
-        help.join("\n")
-    }
-    
     pub fn get_command_prompt(&self) -> String {
         if self.command_mode {
             format!("> {}", self.command_buffer)
@@ -4,6 +4,12 @@ pub struct InteractiveMode {
     // TODO: Add fields for interactive mode state
 }
 
+impl Default for InteractiveMode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl InteractiveMode {
     pub fn new() -> Self {
         Self {
@@ -0,0 +1,750 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::BTreeMap;
+
+/// Names accepted by `--keymap`, `GOL_KEYMAP`, the client config and the
+/// `keymap` command, in the order `keymap` cycles through them.
+pub const KEYMAP_NAMES: [&str; 4] = ["arrows", "vim", "wasd", "emacs"];
+
+/// A key with the modifiers held; Shift is folded into the character.
+type Key = (KeyCode, KeyModifiers);
+
+/// What a key does outside command mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    Quit,
+    Help,
+    Command,
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    ZoomIn,
+    ZoomOut,
+    ResetView,
+    Center,
+    Run,
+    Step,
+    Step10,
+    Step100,
+    Step1000,
+    Faster,
+    Slower,
+    Pause,
+    Clear,
+    PlaceGlider,
+    Measure,
+    History,
+    NextMatch,
+    PreviousMatch,
+    Census,
+    Diff,
+    Heatmap,
+    Performance,
+    Picker,
+    Rulers,
+    Pixels,
+    Menu,
+    Palette,
+    /// Wait for `t` or `T` to switch to the next or previous tab.
+    TabPrefix,
+    NextTab,
+    PreviousTab,
+    Bevy,
+    Entt,
+    Flecs,
+}
+
+/// Every action: its name in the config's key bindings and what it does,
+/// as the command palette lists it.
+const ACTIONS: [(KeyAction, &str, &str); 40] = [
+    (KeyAction::Quit, "quit", "Quit application"),
+    (KeyAction::Help, "help", "Show/hide help"),
+    (KeyAction::Command, "command", "Command mode"),
+    (KeyAction::MoveUp, "move-up", "Move viewport up"),
+    (KeyAction::MoveDown, "move-down", "Move viewport down"),
+    (KeyAction::MoveLeft, "move-left", "Move viewport left"),
+    (KeyAction::MoveRight, "move-right", "Move viewport right"),
+    (KeyAction::ZoomIn, "zoom-in", "Zoom in"),
+    (KeyAction::ZoomOut, "zoom-out", "Zoom out"),
+    (KeyAction::ResetView, "reset-view", "Reset viewport to origin"),
+    (KeyAction::Center, "center", "Center on live cells"),
+    (KeyAction::Run, "run", "Run or stop simulation"),
+    (KeyAction::Step, "step", "Step one generation"),
+    (KeyAction::Step10, "step-10", "Step 10 generations"),
+    (KeyAction::Step100, "step-100", "Step 100 generations"),
+    (KeyAction::Step1000, "step-1000", "Step 1000 generations"),
+    (KeyAction::Faster, "faster", "Run faster (next speed preset)"),
+    (KeyAction::Slower, "slower", "Run slower (previous speed preset)"),
+    (KeyAction::Pause, "pause", "Pause simulation"),
+    (KeyAction::Clear, "clear", "Clear grid"),
+    (KeyAction::PlaceGlider, "place-glider", "Place a glider"),
+    (KeyAction::Measure, "measure", "Measure distances and speeds"),
+    (KeyAction::History, "history", "Browse past generations"),
+    (KeyAction::NextMatch, "next-match", "Next match of the last find"),
+    (KeyAction::PreviousMatch, "previous-match", "Previous match of the last find"),
+    (KeyAction::Census, "census", "Show/hide object census"),
+    (KeyAction::Diff, "diff", "Show/hide diff overlay"),
+    (KeyAction::Heatmap, "heatmap", "Show/hide activity heatmap"),
+    (KeyAction::Performance, "performance", "Show/hide performance overlay"),
+    (KeyAction::Picker, "picker", "Show/hide simulation picker"),
+    (KeyAction::Rulers, "rulers", "Show/hide coordinate rulers"),
+    (KeyAction::Pixels, "pixels", "Switch between pixel and text grid"),
+    (KeyAction::Menu, "menu", "Open menu"),
+    (KeyAction::Palette, "palette", "Open command palette"),
+    (KeyAction::TabPrefix, "tab-prefix", "Tab prefix (then t/T)"),
+    (KeyAction::NextTab, "next-tab", "Next tab"),
+    (KeyAction::PreviousTab, "previous-tab", "Previous tab"),
+    (KeyAction::Bevy, "backend-bevy", "Switch to bevy backend"),
+    (KeyAction::Entt, "backend-entt", "Switch to entt backend"),
+    (KeyAction::Flecs, "backend-flecs", "Switch to flecs backend"),
+];
+
+/// Help lines: actions shown together and what they do.
+const HELP: [(&[KeyAction], &str); 29] = [
+    (&[KeyAction::MoveUp, KeyAction::MoveDown, KeyAction::MoveLeft, KeyAction::MoveRight], "Move viewport"),
+    (&[KeyAction::ZoomIn, KeyAction::ZoomOut], "Zoom in/out"),
+    (&[KeyAction::ResetView], "Reset viewport to origin"),
+    (&[KeyAction::Center], "Center on live cells"),
+    (&[KeyAction::Run], "Run simulation"),
+    (&[KeyAction::Step], "Step one generation"),
+    (&[KeyAction::Step10, KeyAction::Step100, KeyAction::Step1000], "Step 10/100/1000 generations on the server"),
+    (&[KeyAction::Slower, KeyAction::Faster], "Slower/faster running (0.25x-64x)"),
+    (&[KeyAction::Pause], "Pause simulation"),
+    (&[KeyAction::Clear], "Clear grid"),
+    (&[KeyAction::PlaceGlider], "Place a glider: move keys, r rotate, f flip, Enter place, Esc cancel"),
+    (&[KeyAction::Measure], "Measure: move keys, Enter marks two cells, v speed of the object between them, Esc stop"),
+    (&[KeyAction::History], "Browse past generations: left/right step, PgUp/PgDn by 10, g jump"),
+    (&[KeyAction::NextMatch, KeyAction::PreviousMatch], "Next/previous match of the last find command"),
+    (&[KeyAction::Census], "Show/hide object census"),
+    (&[KeyAction::Diff], "Show/hide diff overlay (red: only in A, cyan: only in B)"),
+    (&[KeyAction::Heatmap], "Show/hide activity heatmap of the last 64 generations"),
+    (&[KeyAction::Performance], "Show/hide performance overlay (server gen/s, updates, FPS)"),
+    (&[KeyAction::Picker], "Show/hide simulation picker"),
+    (&[KeyAction::Rulers], "Show/hide coordinate rulers and origin axes"),
+    (&[KeyAction::Pixels], "Switch between pixel and text grid (kitty/Sixel terminals)"),
+    (&[KeyAction::Menu], "Menu (settings: rule, boundary, speed)"),
+    (&[KeyAction::Palette], "Command palette: type to filter, Enter runs"),
+    (&[KeyAction::TabPrefix], "Then t/T: next/previous tab"),
+    (&[KeyAction::NextTab, KeyAction::PreviousTab], "Next/previous tab"),
+    (&[KeyAction::Bevy, KeyAction::Entt, KeyAction::Flecs], "Switch backend (bevy/entt/flecs)"),
+    (&[KeyAction::Help], "Show/hide this help"),
+    (&[KeyAction::Command], "Command mode"),
+    (&[KeyAction::Quit], "Quit application"),
+];
+
+/// Keys every preset shares.
+const COMMON: [(&str, KeyAction); 21] = [
+    ("up", KeyAction::MoveUp),
+    ("down", KeyAction::MoveDown),
+    ("left", KeyAction::MoveLeft),
+    ("right", KeyAction::MoveRight),
+    ("+", KeyAction::ZoomIn),
+    ("=", KeyAction::ZoomIn),
+    ("-", KeyAction::ZoomOut),
+    ("_", KeyAction::ZoomOut),
+    ("home", KeyAction::ResetView),
+    ("enter", KeyAction::Command),
+    (":", KeyAction::Command),
+    ("1", KeyAction::Bevy),
+    ("2", KeyAction::Entt),
+    ("3", KeyAction::Flecs),
+    ("alt+1", KeyAction::Step10),
+    ("alt+2", KeyAction::Step100),
+    ("alt+3", KeyAction::Step1000),
+    ("[", KeyAction::Slower),
+    ("]", KeyAction::Faster),
+    ("N", KeyAction::PreviousMatch),
+    ("ctrl+p", KeyAction::Palette),
+];
+
+/// The original bindings: arrows move and letters name their action.
+const ARROWS: [(&str, KeyAction); 22] = [
+    ("q", KeyAction::Quit),
+    ("h", KeyAction::Help),
+    ("r", KeyAction::Run),
+    ("s", KeyAction::Step),
+    ("p", KeyAction::Pause),
+    ("c", KeyAction::Clear),
+    ("l", KeyAction::PlaceGlider),
+    ("e", KeyAction::Measure),
+    ("o", KeyAction::Center),
+    ("k", KeyAction::Census),
+    ("d", KeyAction::Diff),
+    ("a", KeyAction::Heatmap),
+    ("i", KeyAction::Performance),
+    ("b", KeyAction::Picker),
+    ("u", KeyAction::Rulers),
+    ("x", KeyAction::Pixels),
+    ("v", KeyAction::History),
+    ("n", KeyAction::NextMatch),
+    ("m", KeyAction::Menu),
+    ("g", KeyAction::TabPrefix),
+    ("tab", KeyAction::NextTab),
+    ("backtab", KeyAction::PreviousTab),
+];
+
+/// hjkl move, so help, census and the glider move to `?`, `K` and `i`;
+/// case matters.
+const VIM: [(&str, KeyAction); 26] = [
+    ("h", KeyAction::MoveLeft),
+    ("j", KeyAction::MoveDown),
+    ("k", KeyAction::MoveUp),
+    ("l", KeyAction::MoveRight),
+    ("0", KeyAction::ResetView),
+    ("q", KeyAction::Quit),
+    ("?", KeyAction::Help),
+    ("r", KeyAction::Run),
+    ("s", KeyAction::Step),
+    ("space", KeyAction::Step),
+    ("p", KeyAction::Pause),
+    ("c", KeyAction::Clear),
+    ("i", KeyAction::PlaceGlider),
+    ("e", KeyAction::Measure),
+    ("o", KeyAction::Center),
+    ("K", KeyAction::Census),
+    ("d", KeyAction::Diff),
+    ("a", KeyAction::Heatmap),
+    ("I", KeyAction::Performance),
+    ("b", KeyAction::Picker),
+    ("u", KeyAction::Rulers),
+    ("x", KeyAction::Pixels),
+    ("v", KeyAction::History),
+    ("n", KeyAction::NextMatch),
+    ("m", KeyAction::Menu),
+    ("g", KeyAction::TabPrefix),
+];
+
+/// WASD move, so step goes to Space, diff and the heatmap to `f` and `e`,
+/// and measuring to `t`.
+const WASD: [(&str, KeyAction); 27] = [
+    ("w", KeyAction::MoveUp),
+    ("a", KeyAction::MoveLeft),
+    ("s", KeyAction::MoveDown),
+    ("d", KeyAction::MoveRight),
+    ("space", KeyAction::Step),
+    ("q", KeyAction::Quit),
+    ("h", KeyAction::Help),
+    ("?", KeyAction::Help),
+    ("r", KeyAction::Run),
+    ("p", KeyAction::Pause),
+    ("c", KeyAction::Clear),
+    ("l", KeyAction::PlaceGlider),
+    ("t", KeyAction::Measure),
+    ("o", KeyAction::Center),
+    ("k", KeyAction::Census),
+    ("f", KeyAction::Diff),
+    ("e", KeyAction::Heatmap),
+    ("i", KeyAction::Performance),
+    ("b", KeyAction::Picker),
+    ("u", KeyAction::Rulers),
+    ("x", KeyAction::Pixels),
+    ("v", KeyAction::History),
+    ("n", KeyAction::NextMatch),
+    ("m", KeyAction::Menu),
+    ("g", KeyAction::TabPrefix),
+    ("tab", KeyAction::NextTab),
+    ("backtab", KeyAction::PreviousTab),
+];
+
+/// The arrows preset plus Emacs motion: C-f/b/n/p move, C-l recenters,
+/// M-x opens command mode and M-p the palette.
+const EMACS: [(&str, KeyAction); 9] = [
+    ("ctrl+f", KeyAction::MoveRight),
+    ("ctrl+b", KeyAction::MoveLeft),
+    ("ctrl+n", KeyAction::MoveDown),
+    ("ctrl+p", KeyAction::MoveUp),
+    ("ctrl+l", KeyAction::Center),
+    ("ctrl+h", KeyAction::Help),
+    ("alt+x", KeyAction::Command),
+    ("alt+p", KeyAction::Palette),
+    ("ctrl+x", KeyAction::Quit),
+];
+
+impl KeyAction {
+    /// Every action, in the order the palette lists them.
+    pub fn all() -> impl Iterator<Item = Self> {
+        ACTIONS.iter().map(|&(action, _, _)| action)
+    }
+    
+    pub fn by_name(name: &str) -> Option<Self> {
+        ACTIONS.iter().find(|(_, known, _)| *known == name).map(|&(action, _, _)| action)
+    }
+    
+    pub fn name(self) -> &'static str {
+        ACTIONS.iter().find(|(action, _, _)| *action == self).map_or("", |&(_, name, _)| name)
+    }
+    
+    pub fn description(self) -> &'static str {
+        ACTIONS.iter().find(|(action, _, _)| *action == self).map_or("", |&(_, _, description)| description)
+    }
+    
+    /// Viewport direction of a move action.
+    pub fn direction(self) -> Option<(i32, i32)> {
+        match self {
+            KeyAction::MoveUp => Some((0, -1)),
+            KeyAction::MoveDown => Some((0, 1)),
+            KeyAction::MoveLeft => Some((-1, 0)),
+            KeyAction::MoveRight => Some((1, 0)),
+            _ => None,
+        }
+    }
+}
+
+/// Which key does what: a preset with the config's overrides on top.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    pub name: &'static str,
+    /// Keys and their actions, in the order help lists them; a key bound
+    /// to `None` by an override does nothing.
+    bindings: Vec<(Key, Option<KeyAction>)>,
+    /// Bindings from the config, kept to reapply when the preset changes.
+    overrides: Vec<(Key, Option<KeyAction>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::by_name(KEYMAP_NAMES[0]).expect("built-in keymap")
+    }
+}
+
+impl Keymap {
+    /// The preset named `name`.
+    pub fn by_name(name: &str) -> Option<Self> {
+        let (name, layers, ignore_case): (&'static str, [&[(&str, KeyAction)]; 3], bool) = match name {
+            "arrows" => ("arrows", [&ARROWS, &[], &COMMON], true),
+            "vim" => ("vim", [&VIM, &[], &COMMON], false),
+            "wasd" => ("wasd", [&WASD, &[], &COMMON], true),
+            "emacs" => ("emacs", [&EMACS, &ARROWS, &COMMON], true),
+            _ => return None,
+        };
+        let mut keymap = Self { name, bindings: Vec::new(), overrides: Vec::new() };
+        for &(spec, action) in layers.into_iter().flatten() {
+            keymap.bind_default(parse_key(spec).expect("built-in key"), action);
+        }
+        // Letters work with Shift too unless the preset tells them apart
+        if ignore_case {
+            for ((code, modifiers), action) in keymap.bindings.clone() {
+                if let (KeyCode::Char(c), Some(action)) = (code, action) {
+                    keymap.bind_default((KeyCode::Char(c.to_ascii_uppercase()), modifiers), action);
+                }
+            }
+        }
+        Some(keymap)
+    }
+    
+    /// Bind `key` unless an earlier layer did.
+    fn bind_default(&mut self, key: Key, action: KeyAction) {
+        if !self.bindings.iter().any(|(bound, _)| *bound == key) {
+            self.bindings.push((key, Some(action)));
+        }
+    }
+    
+    /// Rebind keys as `overrides` says, mapping key names such as `x`,
+    /// `ctrl+r` or `f5` to action names, or to `none` to unbind them.
+    pub fn with_overrides(mut self, overrides: &BTreeMap<String, String>) -> Result<Self, String> {
+        for (spec, action_name) in overrides {
+            let key = parse_key(spec).ok_or_else(|| format!("Unknown key '{}' in key bindings", spec))?;
+            let action = match action_name.as_str() {
+                "none" => None,
+                name => Some(KeyAction::by_name(name).ok_or_else(|| format!(
+                    "Unknown action '{}' for key '{}' (expected none or one of {})",
+                    name,
+                    spec,
+                    ACTIONS.map(|(_, name, _)| name).join(", "),
+                ))?),
+            };
+            self.overrides.push((key, action));
+        }
+        self.apply_overrides();
+        Ok(self)
+    }
+    
+    fn apply_overrides(&mut self) {
+        for &(key, action) in &self.overrides {
+            self.bindings.retain(|(bound, _)| *bound != key);
+            self.bindings.push((key, action));
+        }
+    }
+    
+    /// The preset named `name` with this keymap's overrides.
+    pub fn switch(&self, name: &str) -> Option<Self> {
+        let mut keymap = Self::by_name(name)?;
+        keymap.overrides = self.overrides.clone();
+        keymap.apply_overrides();
+        Some(keymap)
+    }
+    
+    /// The action bound to the key pressed, if any.
+    pub fn action(&self, key: &KeyEvent) -> Option<KeyAction> {
+        let key = (key.code, key.modifiers - KeyModifiers::SHIFT);
+        self.bindings.iter().find(|(bound, _)| *bound == key).and_then(|&(_, action)| action)
+    }
+    
+    /// Viewport direction of the key pressed, if it is bound to a move.
+    pub fn direction(&self, key: &KeyEvent) -> Option<(i32, i32)> {
+        self.action(key).and_then(KeyAction::direction)
+    }
+    
+    /// Names of the keys bound to `action`, leaving out the Shift variant of
+    /// a letter bound the same way.
+    pub fn keys_for(&self, action: KeyAction) -> Vec<String> {
+        let bound = |key: Key| self.bindings.contains(&(key, Some(action)));
+        self.bindings.iter()
+            .filter(|&&(key, bound_action)| {
+                bound_action == Some(action)
+                    && !matches!(key.0, KeyCode::Char(c) if c.is_ascii_uppercase() && bound((KeyCode::Char(c.to_ascii_lowercase()), key.1)))
+            })
+            .map(|&(key, _)| format_key(key))
+            .collect()
+    }
+    
+    /// One line per group of actions with the keys bound to them, such as
+    /// `  h/j/k/l, left/down/... - Move viewport`, for the help screens.
+    pub fn help_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("Keys ({} keymap):", self.name)];
+        lines.extend(HELP.iter().filter_map(|&(actions, description)| self.help_line(actions, description)));
+        lines
+    }
+    
+    /// `  keys - description` for a group of actions, with the keys of each
+    /// joined by `/`; `None` when one of them has no key.
+    fn help_line(&self, actions: &[KeyAction], description: &str) -> Option<String> {
+        let keys: Vec<Vec<String>> = actions.iter().map(|&action| self.keys_for(action)).collect();
+        let shared = keys.iter().map(Vec::len).min().filter(|&shared| shared > 0)?;
+        let alternatives: Vec<String> = (0..shared)
+            .map(|i| keys.iter().map(|names| names[i].as_str()).collect::<Vec<_>>().join("/"))
+            .collect();
+        Some(help_entry(&alternatives.join(", "), description))
+    }
+    
+    /// Help lines for `mode`: the keymap's for normal mode, else the mode's
+    /// own keys and the keymap keys it shares.
+    pub fn cheatsheet(&self, mode: Mode) -> Vec<String> {
+        if mode == Mode::Normal {
+            return self.help_lines();
+        }
+        let shared: &[(&[KeyAction], &str)] = match mode {
+            Mode::Stamp => &[(&[KeyAction::MoveUp, KeyAction::MoveDown, KeyAction::MoveLeft, KeyAction::MoveRight], "Move the pattern")],
+            Mode::Measure => &[(&[KeyAction::MoveUp, KeyAction::MoveDown, KeyAction::MoveLeft, KeyAction::MoveRight], "Move the cursor")],
+            Mode::History => &[(&[KeyAction::MoveLeft, KeyAction::MoveRight], "Back/forward one generation")],
+            Mode::Picker => &[(&[KeyAction::Picker], "Close the picker")],
+            _ => &[],
+        };
+        let mut lines = vec![format!("{} keys:", mode.label())];
+        lines.extend(shared.iter().filter_map(|&(actions, description)| self.help_line(actions, description)));
+        for &(key_mode, specs, _, description) in &MODE_KEYS {
+            if key_mode == mode {
+                // Shifted letters work alike without being listed twice
+                let keys: Vec<String> = specs.iter()
+                    .filter(|spec| !(spec.len() == 1 && spec.chars().all(|c| c.is_ascii_uppercase()) && specs.contains(&spec.to_lowercase().as_str())))
+                    .filter_map(|spec| parse_key(spec))
+                    .map(format_key)
+                    .collect();
+                lines.push(help_entry(&keys.join(", "), description));
+            }
+        }
+        lines
+    }
+}
+
+/// One help line, keys aligned in a column.
+fn help_entry(keys: &str, description: &str) -> String {
+    format!("  {:<22} - {}", keys, description)
+}
+
+/// A state of the UI whose keys differ: normal mode uses the keymap, the
+/// others have keys of their own, tried before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Normal,
+    /// Placing a pattern with `stamp` or the glider key.
+    Stamp,
+    /// Marking cells to measure between with `measure` or its key.
+    Measure,
+    /// Scrubbing through past generations.
+    History,
+    /// Choosing from the simulation picker.
+    Picker,
+    /// Typing a command.
+    Command,
+}
+
+/// Modes in the order help pages through them.
+pub const MODES: [Mode; 6] = [Mode::Normal, Mode::Stamp, Mode::Measure, Mode::History, Mode::Picker, Mode::Command];
+
+impl Mode {
+    pub fn label(self) -> &'static str {
+        match self {
+            Mode::Normal => "Normal",
+            Mode::Stamp => "Placing a pattern",
+            Mode::Measure => "Measuring",
+            Mode::History => "History",
+            Mode::Picker => "Simulation picker",
+            Mode::Command => "Command mode",
+        }
+    }
+}
+
+/// What a key does in a mode other than normal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeAction {
+    RunCommand,
+    CancelCommand,
+    EraseCharacter,
+    OlderCommand,
+    NewerCommand,
+    Rotate,
+    Flip,
+    Place,
+    CancelPlacement,
+    MarkCell,
+    MeasureSpeed,
+    ClearMarks,
+    StopMeasuring,
+    PageBack,
+    PageForward,
+    FirstGeneration,
+    LiveGeneration,
+    JumpToGeneration,
+    LeaveHistory,
+    SelectPrevious,
+    SelectNext,
+    Attach,
+    OpenInTab,
+    CloneSimulation,
+    RenameSimulation,
+    DeleteSimulation,
+    ClosePicker,
+}
+
+/// Keys of the modes other than normal, which are not remapped; help
+/// lists them from here.
+const MODE_KEYS: [(Mode, &[&str], ModeAction, &str); 27] = [
+    (Mode::Stamp, &["r", "R"], ModeAction::Rotate, "Rotate a quarter turn"),
+    (Mode::Stamp, &["f", "F"], ModeAction::Flip, "Flip"),
+    (Mode::Stamp, &["enter"], ModeAction::Place, "Place the pattern"),
+    (Mode::Stamp, &["esc"], ModeAction::CancelPlacement, "Drop the pattern"),
+    (Mode::Measure, &["enter", "space"], ModeAction::MarkCell, "Mark the cell; two marks give the distance"),
+    (Mode::Measure, &["v", "V"], ModeAction::MeasureSpeed, "Speed of the object between the marks"),
+    (Mode::Measure, &["c", "C"], ModeAction::ClearMarks, "Clear the marks"),
+    (Mode::Measure, &["esc"], ModeAction::StopMeasuring, "Stop measuring"),
+    (Mode::History, &["pageup"], ModeAction::PageBack, "Back 10 generations"),
+    (Mode::History, &["pagedown"], ModeAction::PageForward, "Forward 10 generations"),
+    (Mode::History, &["home"], ModeAction::FirstGeneration, "First recorded generation"),
+    (Mode::History, &["end"], ModeAction::LiveGeneration, "Live generation"),
+    (Mode::History, &["g"], ModeAction::JumpToGeneration, "Jump to a generation (gen <n>)"),
+    (Mode::History, &["esc"], ModeAction::LeaveHistory, "Back to the live simulation"),
+    (Mode::Picker, &["up"], ModeAction::SelectPrevious, "Select the previous simulation"),
+    (Mode::Picker, &["down"], ModeAction::SelectNext, "Select the next simulation"),
+    (Mode::Picker, &["enter"], ModeAction::Attach, "Show it in this tab"),
+    (Mode::Picker, &["t"], ModeAction::OpenInTab, "Open it in a new tab"),
+    (Mode::Picker, &["c"], ModeAction::CloneSimulation, "Clone it"),
+    (Mode::Picker, &["n"], ModeAction::RenameSimulation, "Rename it"),
+    (Mode::Picker, &["d", "delete"], ModeAction::DeleteSimulation, "Delete it (press twice)"),
+    (Mode::Picker, &["esc"], ModeAction::ClosePicker, "Close the picker"),
+    (Mode::Command, &["enter"], ModeAction::RunCommand, "Run the command"),
+    (Mode::Command, &["esc"], ModeAction::CancelCommand, "Leave command mode"),
+    (Mode::Command, &["backspace"], ModeAction::EraseCharacter, "Erase the last character"),
+    (Mode::Command, &["up"], ModeAction::OlderCommand, "Previous command from history"),
+    (Mode::Command, &["down"], ModeAction::NewerCommand, "Next command from history"),
+];
+
+/// The action of the key pressed in `mode`, if it has one there.
+pub fn mode_action(mode: Mode, key: &KeyEvent) -> Option<ModeAction> {
+    let pressed = (key.code, key.modifiers - KeyModifiers::SHIFT);
+    MODE_KEYS.iter()
+        .find(|(key_mode, specs, _, _)| *key_mode == mode && specs.iter().any(|spec| parse_key(spec) == Some(pressed)))
+        .map(|&(_, _, action, _)| action)
+}
+
+/// The keymap from `flag`, else `$GOL_KEYMAP`, else the config's `keymap`,
+/// else `arrows`, with the config's key bindings applied.
+pub fn configured_keymap(flag: Option<&str>, config_name: Option<&str>, overrides: &BTreeMap<String, String>) -> Result<Keymap, String> {
+    let name = flag.map(str::to_string)
+        .or_else(|| std::env::var("GOL_KEYMAP").ok())
+        .or_else(|| config_name.map(str::to_string))
+        .unwrap_or_else(|| KEYMAP_NAMES[0].to_string());
+    Keymap::by_name(&name)
+        .ok_or_else(|| format!("Unknown keymap '{}' (expected one of {})", name, KEYMAP_NAMES.join(", ")))?
+        .with_overrides(overrides)
+}
+
+/// Parse a key name: a character, `space`, `enter`, `esc`, `tab`, `backtab`,
+/// `up`, `down`, `left`, `right`, `home`, `end`, `pageup`, `pagedown`,
+/// `insert`, `delete`, `backspace` or `f1` to `f12`, optionally prefixed by
+/// `ctrl+` or `alt+`.
+fn parse_key(spec: &str) -> Option<Key> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut name = spec;
+    loop {
+        if let Some(rest) = name.strip_prefix("ctrl+").filter(|rest| !rest.is_empty()) {
+            modifiers |= KeyModifiers::CONTROL;
+            name = rest;
+        } else if let Some(rest) = name.strip_prefix("alt+").filter(|rest| !rest.is_empty()) {
+            modifiers |= KeyModifiers::ALT;
+            name = rest;
+        } else {
+            break;
+        }
+    }
+    let code = match name {
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "insert" => KeyCode::Insert,
+        "delete" => KeyCode::Delete,
+        "backspace" => KeyCode::Backspace,
+        _ => {
+            let mut chars = name.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                (Some('f'), Some(_)) => KeyCode::F(name[1..].parse().ok().filter(|n| (1..=12).contains(n))?),
+                _ => return None,
+            }
+        }
+    };
+    Some((code, modifiers))
+}
+
+/// The name `parse_key` reads back as `key`.
+fn format_key((code, modifiers): Key) -> String {
+    let name = match code {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => "enter".to_string(),
+        KeyCode::Esc => "esc".to_string(),
+        KeyCode::Tab => "tab".to_string(),
+        KeyCode::BackTab => "backtab".to_string(),
+        KeyCode::Up => "up".to_string(),
+        KeyCode::Down => "down".to_string(),
+        KeyCode::Left => "left".to_string(),
+        KeyCode::Right => "right".to_string(),
+        KeyCode::Home => "home".to_string(),
+        KeyCode::End => "end".to_string(),
+        KeyCode::PageUp => "pageup".to_string(),
+        KeyCode::PageDown => "pagedown".to_string(),
+        KeyCode::Insert => "insert".to_string(),
+        KeyCode::Delete => "delete".to_string(),
+        KeyCode::Backspace => "backspace".to_string(),
+        KeyCode::F(n) => format!("f{}", n),
+        other => format!("{:?}", other).to_lowercase(),
+    };
+    let mut prefix = String::new();
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        prefix.push_str("ctrl+");
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        prefix.push_str("alt+");
+    }
+    prefix + &name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::ClientConfig;
+    
+    fn press(spec: &str) -> KeyEvent {
+        let (code, modifiers) = parse_key(spec).unwrap();
+        KeyEvent::new(code, modifiers)
+    }
+    
+    #[test]
+    fn test_every_preset_builds_and_binds_every_key_once() {
+        for name in KEYMAP_NAMES {
+            let keymap = Keymap::by_name(name).unwrap();
+            assert_eq!(keymap.name, name);
+            for (index, (key, _)) in keymap.bindings.iter().enumerate() {
+                assert!(!keymap.bindings[..index].iter().any(|(bound, _)| bound == key), "{} binds {} twice", name, format_key(*key));
+            }
+            assert_eq!(keymap.action(&press("up")), Some(KeyAction::MoveUp), "{}", name);
+            assert_eq!(keymap.action(&press(":")), Some(KeyAction::Command), "{}", name);
+        }
+        assert!(Keymap::by_name("dvorak").is_none());
+        assert_eq!(Keymap::default().name, "arrows");
+    }
+    
+    #[test]
+    fn test_presets_move_with_their_own_keys() {
+        let vim = Keymap::by_name("vim").unwrap();
+        assert_eq!(vim.direction(&press("h")), Some((-1, 0)));
+        assert_eq!(vim.direction(&press("j")), Some((0, 1)));
+        assert_eq!(vim.action(&press("K")), Some(KeyAction::Census));
+        assert_eq!(vim.action(&press("?")), Some(KeyAction::Help));
+        
+        let wasd = Keymap::by_name("wasd").unwrap();
+        assert_eq!(wasd.direction(&press("w")), Some((0, -1)));
+        // Shift works alike on presets that ignore case
+        assert_eq!(wasd.direction(&press("D")), Some((1, 0)));
+        assert_eq!(wasd.action(&press("space")), Some(KeyAction::Step));
+        
+        // Emacs keeps the arrows preset's letters underneath its own keys
+        let emacs = Keymap::by_name("emacs").unwrap();
+        assert_eq!(emacs.direction(&press("ctrl+p")), Some((0, -1)));
+        assert_eq!(emacs.action(&press("alt+x")), Some(KeyAction::Command));
+        assert_eq!(emacs.action(&press("r")), Some(KeyAction::Run));
+        assert_eq!(emacs.keys_for(KeyAction::Quit), vec!["ctrl+x", "q"]);
+    }
+    
+    #[test]
+    fn test_config_overrides_rebind_and_survive_switching() {
+        let config: ClientConfig = serde_json::from_str(r#"{"keymap": "vim", "keys": {"f5": "step", "q": "none", "ctrl+r": "run"}}"#).unwrap();
+        let keymap = configured_keymap(Some("vim"), config.keymap.as_deref(), &config.keys).unwrap();
+        assert_eq!(keymap.name, "vim");
+        assert_eq!(keymap.action(&press("f5")), Some(KeyAction::Step));
+        assert_eq!(keymap.action(&press("ctrl+r")), Some(KeyAction::Run));
+        assert_eq!(keymap.action(&press("q")), None);
+        assert!(!keymap.keys_for(KeyAction::Quit).contains(&"q".to_string()));
+        
+        let wasd = keymap.switch("wasd").unwrap();
+        assert_eq!(wasd.action(&press("f5")), Some(KeyAction::Step));
+        assert_eq!(wasd.action(&press("q")), None);
+        assert_eq!(wasd.direction(&press("w")), Some((0, -1)));
+    }
+    
+    #[test]
+    fn test_bad_config_names_are_reported() {
+        let keys = |key: &str, action: &str| BTreeMap::from([(key.to_string(), action.to_string())]);
+        let error = configured_keymap(Some("dvorak"), None, &BTreeMap::new()).unwrap_err();
+        assert_eq!(error, "Unknown keymap 'dvorak' (expected one of arrows, vim, wasd, emacs)");
+        let error = Keymap::default().with_overrides(&keys("hyper+x", "run")).unwrap_err();
+        assert_eq!(error, "Unknown key 'hyper+x' in key bindings");
+        let error = Keymap::default().with_overrides(&keys("x", "explode")).unwrap_err();
+        assert!(error.starts_with("Unknown action 'explode' for key 'x' (expected none or one of quit, "), "{}", error);
+        
+        assert!(serde_json::from_str::<ClientConfig>(r#"{"keys": ["x"]}"#).is_err());
+    }
+    
+    #[test]
+    fn test_key_names_read_back_as_written() {
+        for spec in ["x", "X", "space", "enter", "backtab", "pagedown", "f1", "f12", "ctrl+r", "alt+1", "ctrl+alt+delete", "+"] {
+            assert_eq!(format_key(parse_key(spec).unwrap()), spec);
+        }
+        for spec in ["", "f13", "f0", "ctrl+", "xy", "shift+x"] {
+            assert_eq!(parse_key(spec), None, "{}", spec);
+        }
+    }
+    
+    #[test]
+    fn test_mode_keys_and_help() {
+        assert_eq!(mode_action(Mode::Stamp, &press("R")), Some(ModeAction::Rotate));
+        assert_eq!(mode_action(Mode::Picker, &press("delete")), Some(ModeAction::DeleteSimulation));
+        assert_eq!(mode_action(Mode::Normal, &press("esc")), None);
+        
+        let help = Keymap::by_name("vim").unwrap().help_lines();
+        assert_eq!(help[0], "Keys (vim keymap):");
+        assert!(help.iter().any(|line| line.starts_with("  k/j/h/l, up/down/left/right") && line.ends_with("- Move viewport")), "{:#?}", help);
+        let stamp = Keymap::default().cheatsheet(Mode::Stamp);
+        assert!(stamp.contains(&help_entry("r", "Rotate a quarter turn")), "{:#?}", stamp);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::client::game_of_life::Region;
+
+/// Measuring on the grid: a cursor moved over the cells and up to two
+/// marked cells. The marks give the distance between them and, as opposite
+/// corners, a region whose object's speed is measured.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    /// World cell under the cursor.
+    pub cursor: (i64, i64),
+    marks: Vec<(i64, i64)>,
+    /// Generations the object's movement is observed over; by default its
+    /// period.
+    pub generations: Option<i64>,
+    /// The last speed measured in the region, for the status bar.
+    pub speed: Option<String>,
+}
+
+/// How far apart two cells are.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Distance {
+    pub dx: i64,
+    pub dy: i64,
+}
+
+impl Distance {
+    pub fn euclidean(&self) -> f64 {
+        (self.dx as f64).hypot(self.dy as f64)
+    }
+    
+    pub fn manhattan(&self) -> i64 {
+        self.dx.saturating_abs().saturating_add(self.dy.saturating_abs())
+    }
+}
+
+impl Measurement {
+    pub fn new(cursor: (i64, i64), generations: Option<i64>) -> Self {
+        Self { cursor, marks: Vec::new(), generations, speed: None }
+    }
+    
+    pub fn move_by(&mut self, dx: i64, dy: i64) {
+        self.cursor = (self.cursor.0.saturating_add(dx), self.cursor.1.saturating_add(dy));
+    }
+    
+    /// Mark the cell under the cursor; a third mark starts over.
+    pub fn mark(&mut self) {
+        if self.marks.len() == 2 {
+            self.marks.clear();
+        }
+        self.marks.push(self.cursor);
+        self.speed = None;
+    }
+    
+    pub fn clear_marks(&mut self) {
+        self.marks.clear();
+        self.speed = None;
+    }
+    
+    pub fn is_marked(&self, x: i64, y: i64) -> bool {
+        self.marks.contains(&(x, y))
+    }
+    
+    /// From the first mark to the second, or to the cursor while only one
+    /// is marked.
+    pub fn distance(&self) -> Option<Distance> {
+        let &from = self.marks.first()?;
+        let to = self.marks.get(1).copied().unwrap_or(self.cursor);
+        Some(Distance { dx: to.0.saturating_sub(from.0), dy: to.1.saturating_sub(from.1) })
+    }
+    
+    /// The rectangle with the two marks as opposite corners.
+    pub fn region(&self) -> Option<Region> {
+        let [(x0, y0), (x1, y1)] = self.marks[..] else {
+            return None;
+        };
+        Some(Region {
+            x: x0.min(x1),
+            y: y0.min(y1),
+            width: x0.abs_diff(x1).saturating_add(1) as i64,
+            height: y0.abs_diff(y1).saturating_add(1) as i64,
+        })
+    }
+    
+    /// For the status bar, e.g. `Measure (3, 4) | dx 5 dy -2 | 5.39 euclidean, 7 manhattan`.
+    pub fn label(&self) -> String {
+        let mut label = format!("Measure ({}, {})", self.cursor.0, self.cursor.1);
+        if let Some(distance) = self.distance() {
+            label.push_str(&format!(
+                " | dx {} dy {} | {:.2} euclidean, {} manhattan",
+                distance.dx, distance.dy, distance.euclidean(), distance.manhattan()
+            ));
+        }
+        if let Some(speed) = &self.speed {
+            label.push_str(&format!(" | {}", speed));
+        }
+        label
+    }
+}
+
+/// Mean position of the live cells in `region` grown by `margin` on every
+/// side, if there are any.
+pub fn centroid(cells: impl Iterator<Item = (i64, i64)>, region: &Region, margin: i64) -> Option<(f64, f64)> {
+    let (x0, y0) = (region.x.saturating_sub(margin), region.y.saturating_sub(margin));
+    let x1 = region.x.saturating_add(region.width).saturating_add(margin);
+    let y1 = region.y.saturating_add(region.height).saturating_add(margin);
+    let (mut count, mut sum_x, mut sum_y) = (0.0, 0.0, 0.0);
+    for (x, y) in cells.filter(|&(x, y)| (x0..x1).contains(&x) && (y0..y1).contains(&y)) {
+        count += 1.0;
+        sum_x += x as f64;
+        sum_y += y as f64;
+    }
+    (count > 0.0).then(|| (sum_x / count, sum_y / count))
+}
+
+/// How far an object moved from `before` to `after` in `generations`, e.g.
+/// `moved (+2.0, +2.0) in 8 gens = 0.250c`; speeds are in cells per
+/// generation along the longer axis, as `c/4` is.
+pub fn observed_speed(before: (f64, f64), after: (f64, f64), generations: i64) -> String {
+    let (dx, dy) = (after.0 - before.0, after.1 - before.1);
+    let speed = dx.abs().max(dy.abs()) / generations as f64;
+    format!("moved ({:+.1}, {:+.1}) in {} gens = {:.3}c", dx, dy, generations, speed)
+}
@@ -1,13 +1,83 @@
-use anyhow::Result;
+use std::collections::HashMap;
+
+use crossterm::event::KeyCode;
 use ratatui::{
-    backend::Backend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
-    text::{Line, Span},
+    style::Style,
+    text::Line,
     widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
     Frame,
 };
 
+use crate::client::game_of_life::BoundaryMode;
+use crate::discovery::DiscoveredBackend;
+use super::theme::{Theme, THEME_NAMES};
+
+/// Auto-step speeds the Settings menu and the speed keys step through, in
+/// generations per second: multiples of the default one per second.
+pub const SPEED_PRESETS: [f64; 9] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0];
+
+/// Boundary modes the Settings menu cycles through.
+const BOUNDARIES: [BoundaryMode; 4] = [BoundaryMode::Clip, BoundaryMode::Wrap, BoundaryMode::Mirror, BoundaryMode::KleinBottle];
+
+/// The current simulation's editable properties, as shown in the Settings menu.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationSettings {
+    pub rule: String,
+    pub boundary: BoundaryMode,
+    /// Auto-step speed in generations per second.
+    pub speed: f64,
+    /// Name of the color theme, one of `THEME_NAMES`.
+    pub theme: &'static str,
+}
+
+impl Default for SimulationSettings {
+    fn default() -> Self {
+        Self {
+            rule: "B3/S23".to_string(),
+            boundary: BoundaryMode::Clip,
+            speed: 1.0,
+            theme: THEME_NAMES[0],
+        }
+    }
+}
+
+/// A change picked in the Settings menu, for the caller to apply.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingChange {
+    Rule(String),
+    Boundary(BoundaryMode),
+    Speed(f64),
+    Theme(&'static str),
+}
+
+pub fn boundary_label(boundary: BoundaryMode) -> &'static str {
+    match boundary {
+        BoundaryMode::Clip => "clip",
+        BoundaryMode::Wrap => "wrap (torus)",
+        BoundaryMode::Mirror => "mirror",
+        BoundaryMode::KleinBottle => "Klein bottle",
+    }
+}
+
+/// The preset `step` places away from the first at least as fast as `speed`,
+/// stopping at the slowest and fastest.
+pub fn step_speed(speed: f64, step: i32) -> f64 {
+    let index = SPEED_PRESETS.iter().position(|&preset| preset >= speed).unwrap_or(SPEED_PRESETS.len() - 1) as i32;
+    SPEED_PRESETS[(index + step).clamp(0, SPEED_PRESETS.len() as i32 - 1) as usize]
+}
+
+/// A speed as a multiple of one generation per second, e.g. `0.25x`.
+pub fn speed_label(speed: f64) -> String {
+    format!("{}x", (speed * 100.0).round() / 100.0)
+}
+
+/// The entry `step` places away from `current` in `options`, wrapping around.
+fn cycle<T: Copy + PartialEq>(options: &[T], current: T, step: i32) -> T {
+    let index = options.iter().position(|&option| option == current).unwrap_or(0) as i32;
+    options[(index + step).rem_euclid(options.len() as i32) as usize]
+}
+
 #[derive(Debug, Clone)]
 pub enum MenuType {
     Main,
@@ -21,7 +91,22 @@ pub struct MenuSystem {
     current_menu: Option<MenuType>,
     selected_index: usize,
     available_patterns: Vec<String>,
-    available_backends: Vec<String>,
+    /// Thumbnail of each pattern, by name, shown beside the Patterns menu.
+    pattern_previews: HashMap<String, Vec<String>>,
+    /// Servers found by the last discovery, listed in the Backends menu.
+    available_backends: Vec<DiscoveredBackend>,
+    settings: SimulationSettings,
+    /// Rule text being typed, while the rule setting is being edited.
+    rule_input: Option<String>,
+    /// Outcome of the last settings change, shown under the list.
+    settings_status: Option<String>,
+    theme: Theme,
+}
+
+impl Default for MenuSystem {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MenuSystem {
@@ -37,22 +122,40 @@ impl MenuSystem {
                 "block".to_string(),
                 "glider-gun".to_string(),
             ],
-            available_backends: vec![
-                "bevy".to_string(),
-                "entt".to_string(),
-                "flecs".to_string(),
-            ],
+            pattern_previews: HashMap::new(),
+            available_backends: Vec::new(),
+            settings: SimulationSettings::default(),
+            rule_input: None,
+            settings_status: None,
+            theme: Theme::default(),
         }
     }
     
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.settings.theme = theme.name;
+    }
+    
+    pub fn set_backends(&mut self, backends: Vec<DiscoveredBackend>) {
+        self.available_backends = backends;
+    }
+    
+    /// The server highlighted in the Backends menu.
+    pub fn selected_backend(&self) -> Option<&DiscoveredBackend> {
+        self.available_backends.get(self.selected_index)
+    }
+    
     pub fn show_menu(&mut self, menu_type: MenuType) {
         self.current_menu = Some(menu_type);
         self.selected_index = 0;
+        self.rule_input = None;
+        self.settings_status = None;
     }
     
     pub fn hide_menu(&mut self) {
         self.current_menu = None;
         self.selected_index = 0;
+        self.rule_input = None;
     }
     
     pub fn is_menu_active(&self) -> bool {
@@ -73,7 +176,7 @@ impl MenuSystem {
                 MenuType::About => 1,
             };
             
-            if direction > 0 && self.selected_index < max_items - 1 {
+            if direction > 0 && self.selected_index + 1 < max_items {
                 self.selected_index += 1;
             } else if direction < 0 && self.selected_index > 0 {
                 self.selected_index -= 1;
@@ -85,17 +188,17 @@ impl MenuSystem {
         if let Some(menu_type) = &self.current_menu {
             match menu_type {
                 MenuType::Main => {
-                    let items = vec!["New Simulation", "Load Pattern", "Switch Backend", "Settings", "About"];
+                    let items = ["New Simulation", "Load Pattern", "Switch Backend", "Settings", "About"];
                     items.get(self.selected_index).map(|s| s.to_string())
                 }
                 MenuType::Patterns => {
                     self.available_patterns.get(self.selected_index).cloned()
                 }
                 MenuType::Backends => {
-                    self.available_backends.get(self.selected_index).cloned()
+                    self.selected_backend().map(|backend| backend.implementation.clone())
                 }
                 MenuType::Settings => {
-                    let items = vec!["Auto-step Speed", "Grid Colors", "Viewport", "Keybindings"];
+                    let items = ["Rule", "Boundary", "Speed"];
                     items.get(self.selected_index).map(|s| s.to_string())
                 }
                 MenuType::About => Some("Close".to_string()),
@@ -123,22 +226,20 @@ impl MenuSystem {
     }
     
     fn render_main_menu(&self, frame: &mut Frame, area: Rect) {
-        let items = vec![
-            "New Simulation",
+        let items = ["New Simulation",
             "Load Pattern",
             "Switch Backend",
             "Settings",
-            "About",
-        ];
+            "About"];
         
         let list_items: Vec<ListItem> = items
             .iter()
             .enumerate()
             .map(|(i, &item)| {
                 let style = if i == self.selected_index {
-                    Style::default().fg(Color::Yellow).bg(Color::DarkGray)
+                    Style::default().fg(self.theme.accent).bg(self.theme.selection)
                 } else {
-                    Style::default().fg(Color::White)
+                    Style::default().fg(self.theme.text)
                 };
                 ListItem::new(item).style(style)
             })
@@ -149,9 +250,9 @@ impl MenuSystem {
                 Block::default()
                     .title("Main Menu")
                     .borders(Borders::ALL)
-                    .style(Style::default().fg(Color::White))
+                    .style(Style::default().fg(self.theme.text))
             )
-            .highlight_style(Style::default().fg(Color::Yellow))
+            .highlight_style(Style::default().fg(self.theme.accent))
             .highlight_symbol(">> ");
         
         frame.render_widget(list, area);
@@ -163,9 +264,9 @@ impl MenuSystem {
             .enumerate()
             .map(|(i, pattern)| {
                 let style = if i == self.selected_index {
-                    Style::default().fg(Color::Yellow).bg(Color::DarkGray)
+                    Style::default().fg(self.theme.accent).bg(self.theme.selection)
                 } else {
-                    Style::default().fg(Color::White)
+                    Style::default().fg(self.theme.text)
                 };
                 ListItem::new(pattern.as_str()).style(style)
             })
@@ -176,76 +277,105 @@ impl MenuSystem {
                 Block::default()
                     .title("Select Pattern")
                     .borders(Borders::ALL)
-                    .style(Style::default().fg(Color::White))
+                    .style(Style::default().fg(self.theme.text))
             )
-            .highlight_style(Style::default().fg(Color::Yellow))
+            .highlight_style(Style::default().fg(self.theme.accent))
             .highlight_symbol(">> ");
         
-        frame.render_widget(list, area);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Min(0),     // Pattern list
+                Constraint::Length(20), // Preview
+            ])
+            .split(area);
+        frame.render_widget(list, columns[0]);
+        
+        let preview = self.available_patterns.get(self.selected_index)
+            .and_then(|pattern| self.pattern_previews.get(pattern))
+            .map_or_else(|| vec![Line::from("no preview")], |thumbnail| thumbnail.iter().map(|line| Line::from(line.as_str())).collect());
+        let paragraph = Paragraph::new(preview)
+            .block(
+                Block::default()
+                    .title("Preview")
+                    .borders(Borders::ALL)
+                    .style(Style::default().fg(self.theme.text))
+            )
+            .style(Style::default().fg(self.theme.accent));
+        frame.render_widget(paragraph, columns[1]);
     }
     
     fn render_backends_menu(&self, frame: &mut Frame, area: Rect) {
-        let list_items: Vec<ListItem> = self.available_backends
+        let mut list_items: Vec<ListItem> = self.available_backends
             .iter()
             .enumerate()
             .map(|(i, backend)| {
                 let style = if i == self.selected_index {
-                    Style::default().fg(Color::Yellow).bg(Color::DarkGray)
+                    Style::default().fg(self.theme.accent).bg(self.theme.selection)
                 } else {
-                    Style::default().fg(Color::White)
+                    Style::default().fg(self.theme.text)
                 };
-                let description = match backend.as_str() {
+                let description = match backend.implementation.as_str() {
                     "bevy" => "Bevy ECS Implementation",
                     "entt" => "EnTT ECS Implementation",
                     "flecs" => "Flecs ECS Implementation",
-                    _ => backend,
+                    other => other,
                 };
-                ListItem::new(description).style(style)
+                ListItem::new(format!("{} ({})", description, backend.address())).style(style)
             })
             .collect();
+        if list_items.is_empty() {
+            list_items.push(ListItem::new("No running servers found").style(Style::default().fg(self.theme.muted)));
+        }
         
         let list = List::new(list_items)
             .block(
                 Block::default()
                     .title("Select Backend")
                     .borders(Borders::ALL)
-                    .style(Style::default().fg(Color::White))
+                    .style(Style::default().fg(self.theme.text))
             )
-            .highlight_style(Style::default().fg(Color::Yellow))
+            .highlight_style(Style::default().fg(self.theme.accent))
             .highlight_symbol(">> ");
         
         frame.render_widget(list, area);
     }
     
     fn render_settings_menu(&self, frame: &mut Frame, area: Rect) {
-        let items = vec![
-            "Auto-step Speed",
-            "Grid Colors",
-            "Viewport Settings",
-            "Keybinding Configuration",
-        ];
+        let rule = match &self.rule_input {
+            Some(input) => format!("{}_", input),
+            None => self.settings.rule.clone(),
+        };
+        let items = [format!("Rule:     {}", rule),
+            format!("Boundary: {}", boundary_label(self.settings.boundary)),
+            format!("Speed:    {} ({} gen/s)", speed_label(self.settings.speed), self.settings.speed),
+            format!("Theme:    {}", self.settings.theme)];
         
-        let list_items: Vec<ListItem> = items
-            .iter()
+        let mut list_items: Vec<ListItem> = items
+            .into_iter()
             .enumerate()
-            .map(|(i, &item)| {
+            .map(|(i, item)| {
                 let style = if i == self.selected_index {
-                    Style::default().fg(Color::Yellow).bg(Color::DarkGray)
+                    Style::default().fg(self.theme.accent).bg(self.theme.selection)
                 } else {
-                    Style::default().fg(Color::White)
+                    Style::default().fg(self.theme.text)
                 };
                 ListItem::new(item).style(style)
             })
             .collect();
+        if let Some(status) = &self.settings_status {
+            list_items.push(ListItem::new(""));
+            list_items.push(ListItem::new(status.as_str()).style(Style::default().fg(self.theme.hint)));
+        }
         
         let list = List::new(list_items)
             .block(
                 Block::default()
                     .title("Settings")
                     .borders(Borders::ALL)
-                    .style(Style::default().fg(Color::White))
+                    .style(Style::default().fg(self.theme.text))
             )
-            .highlight_style(Style::default().fg(Color::Yellow))
+            .highlight_style(Style::default().fg(self.theme.accent))
             .highlight_symbol(">> ");
         
         frame.render_widget(list, area);
@@ -279,9 +409,9 @@ impl MenuSystem {
                 Block::default()
                     .title("About")
                     .borders(Borders::ALL)
-                    .style(Style::default().fg(Color::White))
+                    .style(Style::default().fg(self.theme.text))
             )
-            .style(Style::default().fg(Color::White))
+            .style(Style::default().fg(self.theme.text))
             .alignment(Alignment::Left)
             .wrap(Wrap { trim: true });
         
@@ -318,7 +448,7 @@ impl MenuSystem {
             };
             
             let prompt_paragraph = Paragraph::new(prompt)
-                .style(Style::default().fg(Color::Cyan))
+                .style(Style::default().fg(self.theme.hint))
                 .block(Block::default().borders(Borders::NONE));
             
             frame.render_widget(prompt_paragraph, prompt_area);
@@ -329,13 +459,81 @@ impl MenuSystem {
         self.available_patterns = patterns;
     }
     
+    pub fn set_pattern_previews(&mut self, previews: HashMap<String, Vec<String>>) {
+        self.pattern_previews = previews;
+    }
+    
+    pub fn settings(&self) -> &SimulationSettings {
+        &self.settings
+    }
+    
+    pub fn set_settings(&mut self, settings: SimulationSettings) {
+        self.settings = settings;
+    }
+    
+    /// Show the outcome of a settings change under the Settings list.
+    pub fn set_settings_status(&mut self, status: String) {
+        self.settings_status = Some(status);
+    }
+    
+    pub fn is_editing_rule(&self) -> bool {
+        self.rule_input.is_some()
+    }
+    
+    /// Handle a key in the Settings menu. Enter on the rule starts editing it
+    /// and applies the typed rule; Enter or ←/→ on the boundary, speed and
+    /// theme step through their options. Returns the change to apply, if any.
+    pub fn handle_settings_key(&mut self, key: KeyCode) -> Option<SettingChange> {
+        if let Some(input) = &mut self.rule_input {
+            match key {
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    let rule = input.trim().to_string();
+                    self.rule_input = None;
+                    return Some(SettingChange::Rule(rule));
+                }
+                KeyCode::Esc => self.rule_input = None,
+                _ => {}
+            }
+            return None;
+        }
+        
+        let step = match key {
+            KeyCode::Up => {
+                self.move_selection(-1);
+                return None;
+            }
+            KeyCode::Down => {
+                self.move_selection(1);
+                return None;
+            }
+            KeyCode::Enter | KeyCode::Right => 1,
+            KeyCode::Left => -1,
+            _ => return None,
+        };
+        match self.selected_index {
+            0 if key == KeyCode::Enter => {
+                self.rule_input = Some(self.settings.rule.clone());
+                None
+            }
+            1 => Some(SettingChange::Boundary(cycle(&BOUNDARIES, self.settings.boundary, step))),
+            2 => Some(SettingChange::Speed(step_speed(self.settings.speed, step))),
+            3 => Some(SettingChange::Theme(cycle(&THEME_NAMES, self.settings.theme, step))),
+            _ => None,
+        }
+    }
+    
     pub fn get_menu_help(&self) -> String {
         if let Some(menu_type) = &self.current_menu {
             match menu_type {
                 MenuType::Main => "Navigate: ↑/↓, Select: Enter, Back: Esc",
                 MenuType::Patterns => "Navigate: ↑/↓, Load: Enter, Back: Esc",
-                MenuType::Backends => "Navigate: ↑/↓, Switch: Enter, Back: Esc",
-                MenuType::Settings => "Navigate: ↑/↓, Configure: Enter, Back: Esc",
+                MenuType::Backends => "Navigate: ↑/↓, Switch: Enter, Rescan: r, Back: Esc",
+                MenuType::Settings if self.rule_input.is_some() => "Type a rule, Apply: Enter, Cancel: Esc",
+                MenuType::Settings => "Navigate: ↑/↓, Edit: Enter, Change: ←/→, Back: Esc",
                 MenuType::About => "Press Esc to close",
             }.to_string()
         } else {
@@ -1,36 +1,150 @@
 use anyhow::Result;
 use ratatui::{
     backend::CrosstermBackend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::Style,
+    widgets::Paragraph,
     Terminal,
 };
 use crossterm::{
-    event::{self, Event, KeyCode},
+    event::{self, Event, KeyCode, KeyEvent},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
 use std::io::{self, stdout};
 use std::time::{Duration, Instant};
-use tokio::time;
+use tokio::sync::mpsc::{self, error::TryRecvError};
 
 pub mod interactive;
+pub mod connection;
+pub mod demo;
 pub mod display;
+pub mod graphics;
+pub mod help;
+pub mod history;
 pub mod input;
+pub mod keymap;
+pub mod measure;
 pub mod menu;
+pub mod palette;
+pub mod performance;
+pub mod picker;
+pub mod search;
+pub mod stamp;
+pub mod stepping;
+pub mod tabs;
+pub mod theme;
 
+use connection::ConnectionMonitor;
+use demo::DemoDirector;
 use display::GridDisplay;
-use input::{InputHandler, InputAction};
-use menu::{MenuSystem, MenuType};
+use graphics::{GraphicsProtocol, PixelRenderer};
+use help::HelpScreen;
+use history::{HistoryScrubber, PAGE_GENERATIONS};
+use input::{breakpoint_label, changes_simulation, parse_break_command, parse_checkpoint_command, parse_diff_command, parse_find_command, parse_goto_command, parse_keymap_command, parse_measure_command, parse_note_command, parse_rule_command, parse_stamp_command, parse_tab_command, parse_theme_command, parse_watch_command, watchpoint_label, BreakCommand, CheckpointCommand, InputHandler, InputAction, NoteCommand, RuleCommand, TabCommand, WatchCommand};
+use keymap::{mode_action, KeyAction, Keymap, Mode, ModeAction, KEYMAP_NAMES};
+use menu::{boundary_label, MenuSystem, MenuType, SettingChange, SimulationSettings};
+use palette::CommandPalette;
+use performance::PerformanceMonitor;
+use picker::{PickerAction, SimulationPicker};
+use stamp::PatternStamp;
+use stepping::StepRun;
+use tabs::TabSet;
+use theme::{truecolor_supported, Theme, THEME_NAMES};
+use crate::client::game_of_life::{Annotation, BoundaryMode, Cell, Position, SimulationEvent, SimulationEventType, SimulationUpdate};
 use crate::client::GameOfLifeClient;
+use crate::discovery::discover_backends;
+use crate::commands::thumbnail;
+
+/// Time between redraws while a step is being animated.
+const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Auto-step intervals from which steps are animated; faster playback is
+/// smooth enough without.
+const MIN_ANIMATED_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Time a speed change takes to ramp the auto-step interval to the new
+/// speed, so playback eases into it rather than jumping.
+const SPEED_RAMP: Duration = Duration::from_millis(600);
+
+/// Time between picker refreshes prompted by simulation events.
+const PICKER_EVENT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Time between picker refreshes when the server sends no events.
+const PICKER_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Time between summary polls, which keep the status bar's bounding box
+/// current and notice others changing the shown simulation.
+const SUMMARY_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Steps the activity heatmap covers, ending at the shown generation.
+const HEATMAP_GENERATIONS: i32 = 64;
+
+/// How often an attached simulation's stream sends its cells, in
+/// milliseconds.
+const STREAM_INTERVAL_MS: i32 = 250;
+
+/// Shown when a read-only attach is asked to change a simulation.
+const READ_ONLY_STATUS: &str = "Read-only: attach with --write to change simulations";
 
 pub struct TerminalUI {
     terminal: Terminal<CrosstermBackend<io::Stdout>>,
-    display: GridDisplay,
+    /// Open simulations; the active tab is shown and stepped, and its
+    /// simulation is replaced by the one created when it does not exist.
+    tabs: TabSet,
     input_handler: InputHandler,
     menu_system: MenuSystem,
+    picker: SimulationPicker,
+    /// Ctrl-P popup running any action by name.
+    palette: CommandPalette,
+    /// Keys of the mode help was opened from, or of any mode.
+    help: HelpScreen,
+    /// When the picker's list was last fetched.
+    last_picker_refresh: Instant,
+    /// Signalled for each WatchEvents event while the picker is open;
+    /// `None` when closed or the server sends no events.
+    picker_events: Option<mpsc::UnboundedReceiver<()>>,
+    /// An event arrived since the picker's list was fetched.
+    picker_stale: bool,
+    /// Draws the grid as pixels when the terminal supports a graphics
+    /// protocol; the grid is text when this is `None` or pixels are off.
+    graphics: Option<PixelRenderer>,
     client: GameOfLifeClient,
+    /// Reachability and latency of `client`'s backend, for the status bar.
+    connection: ConnectionMonitor,
+    /// Render and update timing for the performance overlay.
+    performance: PerformanceMonitor,
+    /// When the active simulation's summary was last fetched.
+    last_summary_poll: Instant,
     last_update: Instant,
     auto_step_interval: Duration,
+    /// Auto-step speed in generations per second that `auto_step_interval`
+    /// ramps towards.
+    target_speed: f64,
+    /// When the auto-step interval last moved towards `target_speed`.
+    last_ramp: Instant,
+    /// Generations being stepped in one call, shown in the tab bar until
+    /// they are done; auto-stepping waits for it.
+    stepping: Option<StepRun>,
     running: bool,
+    /// Why stepping last stopped short on its own, such as the breakpoint
+    /// that fired; shown in the tab bar until the simulation is stepped or
+    /// run again.
+    stopped: Option<String>,
+    /// Whether the UI was attached to an existing simulation, which is never
+    /// replaced by a new one when it cannot be stepped.
+    attached: bool,
+    /// Whether simulations are only watched: stepping, running, placing
+    /// patterns and changing settings, names or the server's simulations
+    /// are refused.
+    read_only: bool,
+    /// Updates from the StreamSimulation stream of the simulation named,
+    /// forwarded by a background task, shown instead of polling summaries
+    /// while that simulation is in the active tab.
+    stream: Option<(String, mpsc::UnboundedReceiver<SimulationUpdate>)>,
+    /// CELLS_CHANGED events from WatchEvents, followed once a watchpoint is
+    /// set, which highlight the changed cells in their simulation's tab.
+    cell_changes: Option<mpsc::UnboundedReceiver<SimulationEvent>>,
 }
 
 impl TerminalUI {
@@ -41,16 +155,36 @@ impl TerminalUI {
         let terminal = Terminal::new(backend)?;
         
         let client = GameOfLifeClient::for_backend("bevy");
+        let mut menu_system = MenuSystem::new();
+        menu_system.set_pattern_previews(thumbnail::pattern_previews(thumbnail::PATTERNS_DIR));
         
         Ok(Self {
             terminal,
-            display: GridDisplay::new(),
+            tabs: TabSet::new("default".to_string()),
             input_handler: InputHandler::new(),
-            menu_system: MenuSystem::new(),
+            menu_system,
+            picker: SimulationPicker::new(),
+            palette: CommandPalette::new(),
+            help: HelpScreen::new(),
+            last_picker_refresh: Instant::now(),
+            picker_events: None,
+            picker_stale: false,
+            graphics: None,
+            connection: ConnectionMonitor::new(&client),
+            performance: PerformanceMonitor::new(),
             client,
+            last_summary_poll: Instant::now(),
             last_update: Instant::now(),
             auto_step_interval: Duration::from_millis(1000),
+            target_speed: 1.0,
+            last_ramp: Instant::now(),
             running: false,
+            stopped: None,
+            attached: false,
+            read_only: false,
+            stream: None,
+            stepping: None,
+            cell_changes: None,
         })
     }
     
@@ -58,29 +192,130 @@ impl TerminalUI {
         loop {
             let size = self.terminal.size()?;
             let (term_width, term_height) = (size.width, size.height);
-            self.display.update_terminal_size(term_width, term_height);
+            // The tab bar takes the top row
+            self.tabs.update_terminal_size(term_width, term_height.saturating_sub(1));
             
+            let speed = self.speed_badge();
             self.terminal.draw(|f| {
                 let size = f.area();
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([
+                        Constraint::Length(1), // Tab bar
+                        Constraint::Min(0),    // Simulation view
+                    ])
+                    .split(size);
+                let display = &self.tabs.active().display;
+                let mut view = if self.picker.is_visible() {
+                    let columns = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([
+                            Constraint::Min(0),     // Simulation view
+                            Constraint::Length(32), // Simulation picker
+                        ])
+                        .split(rows[1]);
+                    self.picker.render(f, columns[1]);
+                    columns[0]
+                } else {
+                    rows[1]
+                };
+                if let Some(history) = &self.tabs.active().history {
+                    let parts = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([
+                            Constraint::Min(0),    // Simulation view
+                            Constraint::Length(1), // History scrub bar
+                        ])
+                        .split(view);
+                    history.render(f, parts[1], &self.tabs.theme());
+                    view = parts[0];
+                }
                 
+                self.tabs.render_tab_bar(f, rows[0]);
+                let mut badge = speed;
+                if let Some(run) = &self.stepping {
+                    badge.push_str(&run.label());
+                    badge.push(' ');
+                }
+                if let Some(reason) = &self.stopped {
+                    badge.push_str(&format!("stopped: {} ", reason));
+                }
+                if self.read_only {
+                    badge.push_str("read-only ");
+                }
+                if !badge.is_empty() {
+                    let badge = Paragraph::new(badge).alignment(Alignment::Right);
+                    f.render_widget(badge.style(Style::default().fg(self.tabs.theme().hint)), rows[0]);
+                }
                 if self.menu_system.is_menu_active() {
-                    self.display.render(f, size);
+                    display.render(f, view);
                     self.menu_system.render(f, size);
-                } else if self.input_handler.is_help_shown() {
-                    self.display.render_help(f, size);
+                } else if self.help.is_visible() {
+                    self.help.render(f, size, self.input_handler.keymap());
                 } else {
-                    self.display.render(f, size);
+                    display.render(f, view);
+                }
+                if !self.help.is_visible() {
+                    // Over the right end of the view's status bar
+                    self.connection.render(f, Rect { height: 1, ..view }, &self.tabs.theme());
+                    if self.performance.is_shown() {
+                        self.performance.render(f, view, &self.tabs.theme(), self.connection.server_rate());
+                    }
+                }
+                
+                if self.palette.is_visible() {
+                    self.palette.render(f, size);
                 }
                 
                 if self.input_handler.is_command_mode() {
                     let prompt = self.input_handler.get_command_prompt();
                     self.menu_system.render_command_prompt(f, size, &prompt);
+                } else if self.menu_system.is_menu_active() {
+                    self.menu_system.render_command_prompt(f, size, &self.menu_system.get_menu_help());
                 }
             })?;
+            self.draw_pixels()?;
+            self.performance.record_frame(self.display().is_animating(), FRAME_INTERVAL);
             
-            if event::poll(Duration::from_millis(100))? {
-                if let Event::Key(key) = event::read()? {
-                    if let Some(action) = self.input_handler.handle_key_event(key)? {
+            // Redraw at a steady frame rate while births and deaths fade
+            let poll_timeout = if self.display().is_animating() { FRAME_INTERVAL } else { Duration::from_millis(100) };
+            if event::poll(poll_timeout)? {
+                let event = event::read()?;
+                // Keys may open or close popups over the grid, and resizing
+                // clears it
+                if let Some(renderer) = &mut self.graphics {
+                    renderer.invalidate();
+                }
+                if let Event::Key(key) = event {
+                    if self.help.is_visible() {
+                        self.help.handle_key(&key, self.input_handler.keymap());
+                    } else if self.menu_system.is_menu_active() {
+                        self.handle_menu_key(key.code).await;
+                    } else if self.palette.is_visible() {
+                        let picked = self.palette.handle_key(key.code).and_then(|action| self.input_handler.perform(action));
+                        if let Some(action) = picked {
+                            if self.handle_action(action).await? {
+                                break;
+                            }
+                        }
+                    } else if self.picker.is_visible() && !self.input_handler.is_command_mode() {
+                        self.handle_picker_key(key).await;
+                    } else if self.display().is_stamping()
+                        && !self.input_handler.is_command_mode()
+                        && self.handle_stamp_key(key).await
+                    {
+                        // Taken by the pattern being placed
+                    } else if self.display().is_measuring()
+                        && !self.input_handler.is_command_mode()
+                        && self.handle_measure_key(key).await
+                    {
+                        // Taken by the measure cursor
+                    } else if self.tabs.active().history.is_some()
+                        && !self.input_handler.is_command_mode()
+                        && self.handle_history_key(key).await
+                    {
+                        // Taken by the scrub bar
+                    } else if let Some(action) = self.input_handler.handle_key_event(key)? {
                         if self.handle_action(action).await? {
                             break;
                         }
@@ -88,7 +323,24 @@ impl TerminalUI {
                 }
             }
             
-            if self.running && self.last_update.elapsed() >= self.auto_step_interval {
+            if self.connection.probe_due() {
+                self.connection.probe(&self.client).await;
+            }
+            
+            if self.picker.is_visible() && self.picker_refresh_due() {
+                self.refresh_picker().await;
+            }
+            
+            self.apply_cell_changes();
+            let streamed = self.apply_stream_updates();
+            if !streamed && self.tabs.active().history.is_none() && self.last_summary_poll.elapsed() >= SUMMARY_POLL_INTERVAL {
+                self.poll_summary().await;
+            }
+            
+            self.poll_stepping().await;
+            self.ramp_speed();
+            
+            if self.running && self.stepping.is_none() && self.last_update.elapsed() >= self.auto_step_interval {
                 self.step_simulation().await?;
                 self.last_update = Instant::now();
             }
@@ -97,37 +349,171 @@ impl TerminalUI {
         Ok(())
     }
     
+    /// Show simulation `id` in the first tab instead of `default`; it is
+    /// created under that id when first stepped if it does not exist.
+    pub fn open_simulation(&mut self, id: String) {
+        self.tabs.active_mut().simulation_id = id;
+    }
+    
+    /// Open the UI on the existing simulation `id` and follow its stream
+    /// rather than creating one. Read-only unless `write` is set.
+    pub async fn attach(&mut self, id: String, write: bool) -> Result<()> {
+        let mut client = self.client.clone();
+        client.connect().await?;
+        let simulation = client.get_simulation(id.clone()).await?;
+        self.tabs.active_mut().simulation_id = id.clone();
+        self.display_mut().update_from_simulation(&simulation);
+        self.display_mut().center_on_live_cells();
+        if let Ok(annotations) = client.get_annotations(id.clone()).await {
+            self.display_mut().set_annotations(annotations.annotations);
+        }
+        self.attached = true;
+        self.read_only = !write;
+        self.follow_stream(id);
+        Ok(())
+    }
+    
+    /// Run the `demo` screensaver on a new `width` by `height` simulation:
+    /// seed each showcase pattern in turn, step it until it settles, and
+    /// drift the camera over it. Any key quits, deleting the simulation.
+    pub async fn run_demo(&mut self, width: i32, height: i32) -> Result<()> {
+        let mut client = self.client.clone();
+        client.connect().await?;
+        let simulation = client.create_simulation(width, height, None, None).await?;
+        self.tabs.active_mut().simulation_id = simulation.id.clone();
+        let mut demo = DemoDirector::new();
+        self.show_next_pattern(&mut demo, (width, height)).await?;
+        self.running = true;
+        
+        loop {
+            let size = self.terminal.size()?;
+            // The caption takes the top row
+            self.tabs.update_terminal_size(size.width, size.height.saturating_sub(1));
+            let (center, zoom) = demo.frame(self.display().live_cells(), (size.width, size.height));
+            self.display_mut().look_at(center, zoom);
+            
+            let caption = format!(" Demo: {} (press any key to exit)", demo.current());
+            let style = Style::default().fg(self.tabs.theme().accent);
+            self.terminal.draw(|f| {
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([
+                        Constraint::Length(1), // Caption
+                        Constraint::Min(0),    // Simulation view
+                    ])
+                    .split(f.area());
+                f.render_widget(Paragraph::new(caption).style(style), rows[0]);
+                self.tabs.active().display.render(f, rows[1]);
+            })?;
+            self.draw_pixels()?;
+            self.performance.record_frame(self.display().is_animating(), FRAME_INTERVAL);
+            
+            if event::poll(FRAME_INTERVAL)? {
+                if let Event::Key(_) = event::read()? {
+                    break;
+                }
+                // Resizing clears the pixels
+                if let Some(renderer) = &mut self.graphics {
+                    renderer.invalidate();
+                }
+            }
+            
+            if self.last_update.elapsed() >= self.auto_step_interval {
+                self.step_simulation().await?;
+                self.last_update = Instant::now();
+                let (generation, live_count) = self.display().get_stats();
+                demo.observe(live_count);
+                if demo.is_finished(generation) {
+                    self.show_next_pattern(&mut demo, (width, height)).await?;
+                }
+            }
+        }
+        
+        let _ = client.delete_simulation(self.simulation_id()).await;
+        Ok(())
+    }
+    
+    /// Clear the demo's simulation and seed the next showcase pattern in the
+    /// middle of its `width` by `height` grid, back at generation 0.
+    async fn show_next_pattern(&mut self, demo: &mut DemoDirector, (width, height): (i32, i32)) -> Result<()> {
+        let (name, rle) = demo.next_pattern();
+        let cells = search::load_search_pattern(rle).map_err(anyhow::Error::msg)?;
+        let pattern_width = cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+        let pattern_height = cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
+        let position = Position { x: (width as i64 - pattern_width) / 2, y: (height as i64 - pattern_height) / 2 };
+        
+        let mut client = self.client.clone();
+        client.connect().await?;
+        client.update_simulation(self.simulation_id(), Some(0), Some(Vec::new())).await?;
+        let cells = cells.into_iter().map(|(x, y)| Position { x, y }).collect();
+        client.upload_pattern(self.simulation_id(), name.to_string(), position, cells).await?;
+        self.refresh_simulation().await;
+        Ok(())
+    }
+    
     async fn handle_action(&mut self, action: InputAction) -> Result<bool> {
+        if self.read_only && changes_simulation(&action) {
+            println!("{}", READ_ONLY_STATUS);
+            return Ok(false);
+        }
         match action {
             InputAction::Quit => return Ok(true),
             
-            InputAction::ShowHelp(show) => {
-                // Help display is handled by the input handler
+            InputAction::Help => {
+                let mode = if self.display().is_stamping() {
+                    Mode::Stamp
+                } else if self.display().is_measuring() {
+                    Mode::Measure
+                } else if self.tabs.active().history.is_some() {
+                    Mode::History
+                } else {
+                    Mode::Normal
+                };
+                self.help.open(mode);
             }
             
             InputAction::MoveViewport(dx, dy) => {
-                self.display.move_viewport(dx, dy);
+                self.display_mut().move_viewport(dx.into(), dy.into());
             }
             
             InputAction::Zoom(factor) => {
-                let (_, _, current_zoom) = self.display.get_viewport_info();
-                self.display.set_zoom(current_zoom * factor);
+                let (_, _, current_zoom) = self.display().get_viewport_info();
+                self.display_mut().set_zoom(current_zoom * factor);
             }
             
             InputAction::ResetViewport => {
-                self.display.set_viewport(0, 0);
-                self.display.set_zoom(1.0);
+                self.display_mut().set_viewport(0, 0);
+                self.display_mut().set_zoom(1.0);
             }
             
             InputAction::CenterOnCells => {
-                self.display.center_on_live_cells();
+                self.display_mut().center_on_live_cells();
             }
             
             InputAction::StepSimulation => {
+                self.leave_history().await;
+                self.stopped = None;
                 self.step_simulation().await?;
             }
             
+            InputAction::StepMany(steps) => {
+                self.leave_history().await;
+                self.stopped = None;
+                match &mut self.stepping {
+                    Some(run) => run.queue(steps.into()),
+                    None => self.stepping = Some(StepRun::start(&self.client, self.simulation_id(), steps.into())),
+                }
+            }
+            
+            InputAction::ChangeSpeed(step) => {
+                if let Err(error) = self.set_speed(menu::step_speed(self.target_speed, step)).await {
+                    println!("Server pacing unchanged: {}", error);
+                }
+            }
+            
             InputAction::RunSimulation => {
+                self.leave_history().await;
+                self.stopped = None;
                 self.running = !self.running;
                 if self.running {
                     self.last_update = Instant::now();
@@ -142,8 +528,12 @@ impl TerminalUI {
                 self.load_pattern(&pattern).await?;
             }
             
+            InputAction::Measure(generations) => {
+                self.display_mut().start_measure(generations);
+            }
+            
             InputAction::SwitchBackend(backend) => {
-                self.client = GameOfLifeClient::for_backend(&backend);
+                self.switch_client(GameOfLifeClient::for_backend(&backend));
             }
             
             InputAction::CommandMode => {
@@ -151,12 +541,161 @@ impl TerminalUI {
             }
             
             InputAction::ExecuteCommand(command) => {
-                let result = self.input_handler.execute_command(&command, &mut self.client).await?;
+                if matches!(command.as_str(), "help" | "h") {
+                    self.help.open(Mode::Command);
+                    return Ok(false);
+                }
+                if let Some(targets) = parse_diff_command(&command) {
+                    self.tabs.active_mut().diff_targets = Some(targets);
+                    self.display_mut().set_diff_shown(true);
+                    self.refresh_diff().await;
+                    return Ok(false);
+                }
+                if let Some(name) = parse_theme_command(&command) {
+                    self.switch_theme(name.as_deref());
+                    return Ok(false);
+                }
+                if let Some(name) = parse_keymap_command(&command) {
+                    self.switch_keymap(name.as_deref());
+                    return Ok(false);
+                }
+                if let Some(generation) = parse_goto_command(&command) {
+                    if self.tabs.active().history.is_none() {
+                        self.enter_history().await;
+                    }
+                    if let Some(history) = &mut self.tabs.active_mut().history {
+                        history.seek(generation);
+                    }
+                    self.show_history_position().await;
+                    return Ok(false);
+                }
+                if let Some(pattern) = parse_find_command(&command) {
+                    match search::load_search_pattern(&pattern) {
+                        Ok(cells) => {
+                            self.display_mut().find_pattern(pattern, &cells);
+                        }
+                        Err(error) => println!("{}", error),
+                    }
+                    return Ok(false);
+                }
+                if let Some(generations) = parse_measure_command(&command) {
+                    self.display_mut().start_measure(generations);
+                    return Ok(false);
+                }
+                if let Some(pattern) = parse_stamp_command(&command) {
+                    self.load_pattern(&pattern).await?;
+                    return Ok(false);
+                }
+                if let Some(tab_command) = parse_tab_command(&command) {
+                    self.handle_tab_command(tab_command).await;
+                    return Ok(false);
+                }
+                if let Some(break_command) = parse_break_command(&command) {
+                    self.handle_break_command(break_command).await;
+                    return Ok(false);
+                }
+                if let Some(watch_command) = parse_watch_command(&command) {
+                    self.handle_watch_command(watch_command).await;
+                    return Ok(false);
+                }
+                if let Some(rule_command) = parse_rule_command(&command) {
+                    self.handle_rule_command(rule_command).await;
+                    return Ok(false);
+                }
+                if let Some(checkpoint_command) = parse_checkpoint_command(&command) {
+                    self.handle_checkpoint_command(checkpoint_command).await;
+                    return Ok(false);
+                }
+                if let Some(note_command) = parse_note_command(&command) {
+                    self.handle_note_command(note_command).await;
+                    return Ok(false);
+                }
+                let simulation_id = self.simulation_id();
+                let result = self.input_handler.execute_command(&command, &mut self.client, &simulation_id).await?;
                 println!("{}", result); // In a real UI, this would show in a status area
             }
             
+            InputAction::TogglePerformance => {
+                self.performance.toggle();
+            }
+            
+            InputAction::ToggleHeatmap => {
+                self.display_mut().toggle_heatmap();
+                if self.display().is_heatmap_shown() {
+                    self.refresh_heatmap().await;
+                }
+            }
+            
+            InputAction::ToggleCensus => {
+                self.display_mut().toggle_census();
+                if self.display().is_census_shown() {
+                    self.refresh_census().await;
+                }
+            }
+            
+            InputAction::ToggleDiff => {
+                let shown = !self.display().is_diff_shown();
+                self.display_mut().set_diff_shown(shown);
+                if shown {
+                    self.refresh_diff().await;
+                }
+            }
+            
             InputAction::ClearGrid => {
-                self.display = GridDisplay::new();
+                let mut display = GridDisplay::new();
+                display.set_theme(self.tabs.theme());
+                display.set_pixels(self.tabs.pixels());
+                self.tabs.active_mut().display = display;
+            }
+            
+            InputAction::ToggleRulers => {
+                self.display_mut().toggle_rulers();
+            }
+            
+            // Without a graphics protocol the grid can only be text
+            InputAction::TogglePixels if self.graphics.is_some() => {
+                let pixels = !self.tabs.pixels();
+                self.set_pixels(pixels)?;
+            }
+            
+            InputAction::ToggleHistory => {
+                if self.tabs.active().history.is_some() {
+                    self.leave_history().await;
+                } else {
+                    self.enter_history().await;
+                }
+            }
+            
+            InputAction::CycleMatch(step) => {
+                self.display_mut().cycle_match(step);
+            }
+            
+            InputAction::TogglePicker => {
+                self.picker.toggle();
+                if self.picker.is_visible() {
+                    self.watch_picker_events();
+                    self.refresh_picker().await;
+                } else {
+                    self.picker_events = None;
+                }
+            }
+            
+            InputAction::NextTab => {
+                self.tabs.next();
+                self.refresh_simulation().await;
+            }
+            
+            InputAction::PreviousTab => {
+                self.tabs.previous();
+                self.refresh_simulation().await;
+            }
+            
+            InputAction::ShowMenu => {
+                self.menu_system.show_menu(MenuType::Main);
+            }
+            
+            InputAction::ShowPalette => {
+                self.palette.open(self.input_handler.keymap());
             }
             
             _ => {}
@@ -165,51 +704,1121 @@ impl TerminalUI {
         Ok(false)
     }
     
+    /// Handle a key while a menu is open: navigate it, open submenus and
+    /// apply what was picked.
+    async fn handle_menu_key(&mut self, key: KeyCode) {
+        let Some(menu) = self.menu_system.get_current_menu() else {
+            return;
+        };
+        
+        if let MenuType::Settings = menu {
+            if key == KeyCode::Esc && !self.menu_system.is_editing_rule() {
+                self.menu_system.show_menu(MenuType::Main);
+            } else if let Some(change) = self.menu_system.handle_settings_key(key) {
+                self.apply_setting(change).await;
+            }
+            return;
+        }
+        
+        match key {
+            KeyCode::Esc => self.menu_system.hide_menu(),
+            KeyCode::Up => self.menu_system.move_selection(-1),
+            KeyCode::Down => self.menu_system.move_selection(1),
+            KeyCode::Char('r') if matches!(menu, MenuType::Backends) => {
+                self.menu_system.set_backends(discover_backends().await);
+                self.menu_system.show_menu(MenuType::Backends);
+            }
+            KeyCode::Enter => {
+                let selected = self.menu_system.get_selected_item().unwrap_or_default();
+                match menu {
+                    MenuType::Main => match selected.as_str() {
+                        "New Simulation" => {
+                            self.menu_system.hide_menu();
+                            self.create_simulation().await;
+                        }
+                        "Load Pattern" => self.menu_system.show_menu(MenuType::Patterns),
+                        "Switch Backend" => {
+                            self.menu_system.set_backends(discover_backends().await);
+                            self.menu_system.show_menu(MenuType::Backends);
+                        }
+                        "Settings" => {
+                            self.menu_system.show_menu(MenuType::Settings);
+                            self.refresh_settings().await;
+                        }
+                        _ => self.menu_system.show_menu(MenuType::About),
+                    },
+                    MenuType::Patterns => {
+                        self.menu_system.hide_menu();
+                        let _ = self.load_pattern(&selected).await;
+                    }
+                    MenuType::Backends => {
+                        if let Some(backend) = self.menu_system.selected_backend() {
+                            let client = backend.client();
+                            self.switch_client(client);
+                        }
+                        self.menu_system.hide_menu();
+                    }
+                    MenuType::Settings | MenuType::About => self.menu_system.hide_menu(),
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    /// Handle a key while the simulation picker is open. b or Esc closes it;
+    /// other keys are the picker's, with what they pick carried out here.
+    async fn handle_picker_key(&mut self, key: KeyEvent) {
+        let keymap_action = self.input_handler.keymap().action(&key);
+        if !self.picker.is_editing_name() && keymap_action == Some(KeyAction::Help) {
+            self.help.open(Mode::Picker);
+            return;
+        }
+        let closes = mode_action(Mode::Picker, &key) == Some(ModeAction::ClosePicker) || keymap_action == Some(KeyAction::Picker);
+        if !self.picker.is_editing_name() && closes {
+            self.picker.toggle();
+            self.picker_events = None;
+            return;
+        }
+        let Some(action) = self.picker.handle_key(&key) else {
+            return;
+        };
+        
+        let mut client = self.client.clone();
+        if let Err(error) = client.connect().await {
+            self.picker.set_status(format!("Server unavailable: {}", error));
+            return;
+        }
+        let status = match action {
+            PickerAction::Attach(id) => {
+                self.tabs.active_mut().simulation_id = id;
+                self.tabs.active_mut().diff_targets = None;
+                self.tabs.active_mut().history = None;
+                self.refresh_simulation().await;
+                None
+            }
+            PickerAction::OpenInTab(id) => {
+                self.tabs.open(id);
+                self.refresh_simulation().await;
+                None
+            }
+            PickerAction::Rename(..) | PickerAction::Delete(_) if self.read_only => Some(READ_ONLY_STATUS.to_string()),
+            PickerAction::Clone(id) => Some(match client.clone_simulation(id, String::new()).await {
+                Ok(simulation) => format!("Cloned as {}", simulation.id),
+                Err(error) => format!("Clone failed: {}", error),
+            }),
+            PickerAction::Rename(id, name) => Some(match client.rename_simulation(id, name).await {
+                Ok(_) => "Renamed".to_string(),
+                Err(error) => format!("Rename failed: {}", error),
+            }),
+            PickerAction::Delete(id) => Some(match client.delete_simulation(id).await {
+                Ok(response) => response.message,
+                Err(error) => format!("Delete failed: {}", error),
+            }),
+        };
+        if let Some(status) = status {
+            self.picker.set_status(status);
+        }
+        self.refresh_picker().await;
+    }
+    
+    /// Handle a key while the history scrub bar is shown, returning whether
+    /// it was the scrub bar's. Other keys work as usual, so the viewport can
+    /// still be moved.
+    async fn handle_history_key(&mut self, key: KeyEvent) -> bool {
+        let direction = self.input_handler.keymap().direction(&key);
+        let Some(history) = &mut self.tabs.active_mut().history else {
+            return false;
+        };
+        match (mode_action(Mode::History, &key), direction) {
+            (_, Some((dx, 0))) if dx != 0 => history.step(dx.into()),
+            (Some(ModeAction::PageBack), _) => history.step(-PAGE_GENERATIONS),
+            (Some(ModeAction::PageForward), _) => history.step(PAGE_GENERATIONS),
+            (Some(ModeAction::FirstGeneration), _) => history.seek_first(),
+            (Some(ModeAction::LiveGeneration), _) => history.seek_head(),
+            (Some(ModeAction::JumpToGeneration), _) => {
+                self.input_handler.open_command("gen ");
+                return true;
+            }
+            (Some(ModeAction::LeaveHistory), _) => {
+                self.leave_history().await;
+                return true;
+            }
+            _ => return false,
+        }
+        self.show_history_position().await;
+        true
+    }
+    
+    /// Move, turn, place or drop the pattern being placed, returning whether
+    /// the key was one of those.
+    async fn handle_stamp_key(&mut self, key: KeyEvent) -> bool {
+        let direction = self.input_handler.keymap().direction(&key);
+        let Some(stamp) = self.display_mut().stamp_mut() else {
+            return false;
+        };
+        match (mode_action(Mode::Stamp, &key), direction) {
+            (_, Some((dx, dy))) => stamp.move_by(dx.into(), dy.into()),
+            (Some(ModeAction::Rotate), _) => stamp.rotate(),
+            (Some(ModeAction::Flip), _) => stamp.flip(),
+            (Some(ModeAction::CancelPlacement), _) => {
+                self.display_mut().take_stamp();
+            }
+            (Some(ModeAction::Place), _) => {
+                if let Some(stamp) = self.display_mut().take_stamp() {
+                    self.place_stamp(stamp).await;
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+    
+    /// Move the measure cursor, mark cells, measure a speed or stop,
+    /// returning whether the key was one of those.
+    async fn handle_measure_key(&mut self, key: KeyEvent) -> bool {
+        let direction = self.input_handler.keymap().direction(&key);
+        let Some(measurement) = self.display_mut().measurement_mut() else {
+            return false;
+        };
+        match (mode_action(Mode::Measure, &key), direction) {
+            (_, Some((dx, dy))) => self.display_mut().move_measure_cursor(dx.into(), dy.into()),
+            (Some(ModeAction::MarkCell), _) => measurement.mark(),
+            (Some(ModeAction::ClearMarks), _) => measurement.clear_marks(),
+            (Some(ModeAction::StopMeasuring), _) => self.display_mut().stop_measure(),
+            (Some(ModeAction::MeasureSpeed), _) => self.measure_speed().await,
+            _ => return false,
+        }
+        true
+    }
+    
+    /// Measure the speed of the object between the measure marks: its
+    /// period and speed from the server's statistics of the region alone,
+    /// and how far its cells moved over the measuring generations, by
+    /// their mean position now and in history that many generations ago.
+    async fn measure_speed(&mut self) {
+        let Some(measurement) = self.display().measurement() else {
+            return;
+        };
+        let Some(region) = measurement.region() else {
+            println!("Mark two opposite corners of the object's region first");
+            return;
+        };
+        let generations = measurement.generations;
+        let mut client = self.client.clone();
+        if client.connect().await.is_err() {
+            return;
+        }
+        let id = self.simulation_id();
+        let stats = match client.get_stats(id.clone(), Some(region)).await {
+            Ok(stats) => stats,
+            Err(error) => {
+                println!("Error measuring speed: {}", error);
+                return;
+            }
+        };
+        let mut speed = match stats.period {
+            0 => "no period found".to_string(),
+            period if stats.speed.is_empty() => format!("period {}, not moving", period),
+            period => format!("period {}, {} {}", period, stats.speed, stats.direction),
+        };
+        // Cells move at most one cell a generation, so the object was within
+        // that many cells of the region
+        let generations = generations.unwrap_or(i64::from(stats.period)).min(stats.generation);
+        if generations > 0 {
+            let now = client.get_all_cells(id.clone()).await;
+            let past = match &now {
+                Ok((generation, _)) => client.get_simulation_at(id, generation - generations).await.map(|past| past.cells),
+                Err(_) => Ok(Vec::new()),
+            };
+            let live = |cells: &[Cell]| cells.iter().filter(|cell| cell.alive).map(|cell| (cell.x, cell.y)).collect::<Vec<_>>();
+            match (now, past) {
+                (Ok((_, now)), Ok(past)) => {
+                    let after = measure::centroid(live(&now).into_iter(), &region, 0);
+                    let before = measure::centroid(live(&past).into_iter(), &region, generations);
+                    if let Some((before, after)) = before.zip(after) {
+                        speed.push_str(&format!("; {}", measure::observed_speed(before, after, generations)));
+                    }
+                }
+                (Err(error), _) | (_, Err(error)) => println!("Error observing movement over {} generations: {}", generations, error),
+            }
+        }
+        if let Some(measurement) = self.display_mut().measurement_mut() {
+            measurement.speed = Some(speed);
+        }
+    }
+    
+    /// Add a placed pattern's cells to the simulation and show the result.
+    async fn place_stamp(&mut self, stamp: PatternStamp) {
+        let mut client = self.client.clone();
+        if client.connect().await.is_err() {
+            return;
+        }
+        let cells = stamp.cells().into_iter().map(|(x, y)| Position { x, y }).collect();
+        let position = Position { x: stamp.x, y: stamp.y };
+        match client.upload_pattern(self.simulation_id(), stamp.name.clone(), position, cells).await {
+            Ok(_) => self.refresh_simulation().await,
+            Err(error) => println!("Error placing {}: {}", stamp.name, error),
+        }
+    }
+    
+    /// Show the scrub bar at the live generation, pausing playback.
+    async fn enter_history(&mut self) {
+        self.running = false;
+        let mut client = self.client.clone();
+        let history = match client.connect().await {
+            Ok(_) => client.get_history(self.simulation_id()).await,
+            Err(error) => Err(error),
+        };
+        match history {
+            Ok(history) => {
+                self.tabs.active_mut().history = Some(HistoryScrubber::new(history.first_generation, history.head_generation));
+            }
+            Err(error) => println!("History unavailable: {}", error),
+        }
+    }
+    
+    /// Hide the scrub bar and show the live generation again.
+    async fn leave_history(&mut self) {
+        if self.tabs.active_mut().history.take().is_some() {
+            self.refresh_simulation().await;
+        }
+    }
+    
+    /// Show the generation the scrub bar is at, fetching it unless it was
+    /// fetched recently, and pick up any change in the stored range.
+    async fn show_history_position(&mut self) {
+        let id = self.simulation_id();
+        let mut client = self.client.clone();
+        let connected = client.connect().await.is_ok();
+        let tab = self.tabs.active_mut();
+        let Some(history) = &mut tab.history else {
+            return;
+        };
+        if connected {
+            if let Ok(range) = client.get_history(id.clone()).await {
+                history.update_range(range.first_generation, range.head_generation);
+            }
+        }
+        if history.cached_frame().is_none() {
+            let fetched = if connected {
+                client.get_simulation_at(id, history.position()).await
+            } else {
+                Err(anyhow::anyhow!("Server unavailable"))
+            };
+            match fetched {
+                Ok(frame) => history.store_frame(frame),
+                Err(error) => history.set_error(error.to_string()),
+            }
+        }
+        if let Some(frame) = history.cached_frame() {
+            tab.display.update_from_simulation(frame);
+        }
+    }
+    
+    /// Follow the server's simulation events so the picker refreshes when
+    /// simulations change rather than on a timer. Servers without
+    /// WatchEvents end the stream at once and keep the timer.
+    fn watch_picker_events(&mut self) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut client = self.client.clone();
+        tokio::spawn(async move {
+            if client.connect().await.is_err() {
+                return;
+            }
+            let Ok(mut events) = client.watch_events(Vec::new()).await else {
+                return;
+            };
+            // Ends at the first event after the picker closes
+            while let Ok(Some(_)) = events.message().await {
+                if sender.send(()).is_err() {
+                    break;
+                }
+            }
+        });
+        self.picker_events = Some(receiver);
+    }
+    
+    /// Whether to fetch the picker's list again: soon after an event, or
+    /// every second without an event stream.
+    fn picker_refresh_due(&mut self) -> bool {
+        if let Some(events) = &mut self.picker_events {
+            loop {
+                match events.try_recv() {
+                    Ok(()) => self.picker_stale = true,
+                    Err(TryRecvError::Empty) => {
+                        return self.picker_stale && self.last_picker_refresh.elapsed() >= PICKER_EVENT_INTERVAL;
+                    }
+                    Err(TryRecvError::Disconnected) => break,
+                }
+            }
+            self.picker_events = None;
+        }
+        self.last_picker_refresh.elapsed() >= PICKER_POLL_INTERVAL
+    }
+    
+    /// Follow `id`'s StreamSimulation updates, which carry its cells as it
+    /// is stepped by others, without stepping it.
+    fn follow_stream(&mut self, id: String) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut client = self.client.clone();
+        let stream_id = id.clone();
+        tokio::spawn(async move {
+            if client.connect().await.is_err() {
+                return;
+            }
+            let Ok(mut updates) = client.stream_simulation(stream_id, false, STREAM_INTERVAL_MS).await else {
+                return;
+            };
+            // Ends at the first update after the UI stops following
+            while let Ok(Some(update)) = updates.message().await {
+                if sender.send(update).is_err() {
+                    break;
+                }
+            }
+        });
+        self.stream = Some((id, receiver));
+    }
+    
+    /// Show the latest streamed update if the followed simulation is in the
+    /// active tab, returning whether the stream is still keeping it current.
+    /// A stream that ended is dropped, falling back to polling.
+    fn apply_stream_updates(&mut self) -> bool {
+        let active = self.tabs.active().history.is_none() && !self.display().is_stamping();
+        let Some((id, updates)) = &mut self.stream else {
+            return false;
+        };
+        let following = active && *id == self.tabs.active().simulation_id;
+        let mut latest = None;
+        loop {
+            match updates.try_recv() {
+                Ok(update) => latest = Some(update),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.stream = None;
+                    return false;
+                }
+            }
+        }
+        if let Some(update) = latest.filter(|_| following) {
+            let display = &mut self.tabs.active_mut().display;
+            if (update.generation, update.live_cells) != display.get_stats() {
+                self.performance.record_update();
+            }
+            display.update_from_stream(&update);
+        }
+        following
+    }
+    
+    /// Follow the server's CELLS_CHANGED events, for every simulation, so
+    /// any open tab with watchpoints highlights its changed cells.
+    fn watch_cell_changes(&mut self) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut client = self.client.clone();
+        tokio::spawn(async move {
+            if client.connect().await.is_err() {
+                return;
+            }
+            let Ok(mut events) = client.watch_events(Vec::new()).await else {
+                return;
+            };
+            while let Ok(Some(event)) = events.message().await {
+                if event.r#type() == SimulationEventType::CellsChanged && sender.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+        self.cell_changes = Some(receiver);
+    }
+    
+    /// Highlight the cells changed under watchpoints since the last call in
+    /// the tabs showing their simulations.
+    fn apply_cell_changes(&mut self) {
+        let Some(events) = &mut self.cell_changes else {
+            return;
+        };
+        loop {
+            match events.try_recv() {
+                Ok(event) => {
+                    for tab in self.tabs.iter_mut().filter(|tab| tab.simulation_id == event.id) {
+                        tab.display.flash_changes(&event.cells);
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.cell_changes = None;
+                    break;
+                }
+            }
+        }
+    }
+    
+    async fn refresh_picker(&mut self) {
+        self.last_picker_refresh = Instant::now();
+        self.picker_stale = false;
+        let mut client = self.client.clone();
+        if client.connect().await.is_ok() {
+            if let Ok(response) = client.list_simulations().await {
+                self.picker.update_simulations(response.simulations);
+            }
+        }
+    }
+    
+    /// Show the current simulation's rule and boundary in the Settings menu,
+    /// beside the local auto-step speed.
+    async fn refresh_settings(&mut self) {
+        let mut settings = SimulationSettings { speed: self.target_speed, ..self.menu_system.settings().clone() };
+        
+        let mut client = self.client.clone();
+        let fetched = match client.connect().await {
+            Ok(_) => client.get_simulation_fields(self.simulation_id(), &["rule", "grid"]).await,
+            Err(error) => Err(error),
+        };
+        match fetched {
+            Ok(simulation) => {
+                settings.rule = simulation.rule;
+                settings.boundary = simulation.grid
+                    .and_then(|grid| BoundaryMode::try_from(grid.boundary_mode).ok())
+                    .unwrap_or(BoundaryMode::Clip);
+            }
+            Err(error) => self.menu_system.set_settings_status(format!("Simulation unavailable: {}", error)),
+        }
+        self.menu_system.set_settings(settings);
+    }
+    
+    /// Ramp auto-stepping to `speed` generations per second and pace the
+    /// server's streams to match; read-only UIs leave the server alone.
+    async fn set_speed(&mut self, speed: f64) -> Result<()> {
+        // Auto-stepping is paced here; the server's pacing is kept in line
+        // for streams, but the local speed applies even when that fails
+        self.target_speed = speed;
+        self.last_ramp = Instant::now();
+        if self.read_only {
+            return Ok(());
+        }
+        let mut client = self.client.clone();
+        client.connect().await?;
+        client.set_pacing(self.simulation_id(), Some(speed)).await?;
+        Ok(())
+    }
+    
+    /// Move the auto-step interval part of the way to the target speed, in
+    /// proportion to the time since the last move, arriving after
+    /// [`SPEED_RAMP`].
+    fn ramp_speed(&mut self) {
+        let elapsed = self.last_ramp.elapsed();
+        self.last_ramp = Instant::now();
+        let speed = 1.0 / self.auto_step_interval.as_secs_f64();
+        // Even steps in log space, so 0.25x to 64x eases like 1x to 2x
+        let ratio = self.target_speed / speed;
+        let fraction = (elapsed.as_secs_f64() / SPEED_RAMP.as_secs_f64()).min(1.0);
+        let next = if ratio.ln().abs() <= 0.01 || fraction >= 1.0 {
+            self.target_speed
+        } else {
+            speed * ratio.powf(fraction)
+        };
+        self.auto_step_interval = Duration::from_secs_f64(1.0 / next);
+    }
+    
+    /// Speed for the tab bar, e.g. `4x `, or `1.7x→4x ` while ramping.
+    fn speed_badge(&self) -> String {
+        let speed = 1.0 / self.auto_step_interval.as_secs_f64();
+        let target = menu::speed_label(self.target_speed);
+        if menu::speed_label(speed) == target {
+            format!("{} ", target)
+        } else {
+            format!("{:.1}x→{} ", speed, target)
+        }
+    }
+    
+    /// Apply a change picked in the Settings menu, reporting the outcome there.
+    async fn apply_setting(&mut self, change: SettingChange) {
+        let mut settings = self.menu_system.settings().clone();
+        let mut client = self.client.clone();
+        let connected = client.connect().await;
+        let id = self.simulation_id();
+        
+        let status = match change {
+            SettingChange::Rule(_) | SettingChange::Boundary(_) if self.read_only => READ_ONLY_STATUS.to_string(),
+            SettingChange::Speed(speed) => {
+                settings.speed = speed;
+                match self.set_speed(speed).await {
+                    Ok(_) => format!("Speed set to {}", menu::speed_label(speed)),
+                    Err(error) => format!("Speed set to {} here; server pacing unchanged: {}", menu::speed_label(speed), error),
+                }
+            }
+            SettingChange::Rule(rule) => {
+                let result = match connected {
+                    Ok(_) => client.set_rule(id, rule).await,
+                    Err(error) => Err(error),
+                };
+                match result {
+                    Ok(simulation) => {
+                        settings.rule = simulation.rule.clone();
+                        self.display_mut().update_from_simulation(&simulation);
+                        format!("Rule set to {}", simulation.rule)
+                    }
+                    Err(error) => format!("Rule not changed: {}", error),
+                }
+            }
+            SettingChange::Theme(name) => {
+                self.switch_theme(Some(name));
+                settings.theme = name;
+                format!("Theme set to {}", name)
+            }
+            SettingChange::Boundary(boundary) => {
+                let result = match connected {
+                    Ok(_) => client.set_boundary(id, boundary).await,
+                    Err(error) => Err(error),
+                };
+                match result {
+                    Ok(_) => {
+                        settings.boundary = boundary;
+                        format!("Boundary set to {}", boundary_label(boundary))
+                    }
+                    Err(error) => format!("Boundary not changed: {}", error),
+                }
+            }
+        };
+        self.menu_system.set_settings(settings);
+        self.menu_system.set_settings_status(status);
+    }
+    
+    fn display(&self) -> &GridDisplay {
+        &self.tabs.active().display
+    }
+    
+    fn display_mut(&mut self) -> &mut GridDisplay {
+        &mut self.tabs.active_mut().display
+    }
+    
+    fn simulation_id(&self) -> String {
+        self.tabs.active().simulation_id.clone()
+    }
+    
+    /// Add or clear the active simulation's breakpoints on the server, then
+    /// list them.
+    async fn handle_break_command(&mut self, command: BreakCommand) {
+        if self.read_only && command != BreakCommand::List {
+            println!("{}", READ_ONLY_STATUS);
+            return;
+        }
+        let mut client = self.client.clone();
+        let id = self.simulation_id();
+        let breakpoints = async {
+            client.connect().await?;
+            let mut breakpoints = client.get_simulation_fields(id.clone(), &["breakpoints"]).await?.breakpoints;
+            match command {
+                BreakCommand::List => return Ok(breakpoints),
+                BreakCommand::Add(breakpoint) => breakpoints.push(breakpoint),
+                BreakCommand::Clear => breakpoints.clear(),
+            }
+            anyhow::Ok(client.set_breakpoints(id.clone(), breakpoints).await?.breakpoints)
+        };
+        match breakpoints.await {
+            Ok(breakpoints) if breakpoints.is_empty() => println!("No breakpoints on {}", id),
+            Ok(breakpoints) => {
+                let labels: Vec<String> = breakpoints.iter().map(breakpoint_label).collect();
+                println!("Breakpoints on {}: {}", id, labels.join(", "));
+            }
+            Err(error) => println!("{}", error),
+        }
+    }
+    
+    /// Switch the active simulation's rule now, or schedule or cancel a
+    /// change at a later generation, then list the changes still to come.
+    async fn handle_rule_command(&mut self, command: RuleCommand) {
+        if self.read_only && command != RuleCommand::List {
+            println!("{}", READ_ONLY_STATUS);
+            return;
+        }
+        let mut client = self.client.clone();
+        let id = self.simulation_id();
+        let changed = async {
+            client.connect().await?;
+            let (rule, at_generation) = match command {
+                RuleCommand::List => {
+                    let simulation = client.get_simulation_fields(id.clone(), &["rule", "rule_changes"]).await?;
+                    return anyhow::Ok((simulation.rule, simulation.rule_changes, None));
+                }
+                RuleCommand::Set(rule, at_generation) => (rule, at_generation),
+                RuleCommand::Cancel(generation) => (String::new(), Some(generation)),
+            };
+            let response = client.schedule_rule(id.clone(), rule, at_generation).await?;
+            // Switching now may change the layout, as to hex rows
+            let simulation = match at_generation {
+                None => Some(client.get_simulation(id.clone()).await?),
+                Some(_) => None,
+            };
+            Ok((response.rule, response.rule_changes, simulation))
+        };
+        match changed.await {
+            Ok((rule, changes, simulation)) => {
+                if let Some(simulation) = simulation {
+                    self.display_mut().update_from_simulation(&simulation);
+                }
+                let scheduled: Vec<String> = changes.iter().map(|change| format!("{} at generation {}", change.rule, change.generation)).collect();
+                if scheduled.is_empty() {
+                    println!("Rule of {}: {}", id, rule);
+                } else {
+                    println!("Rule of {}: {}, then {}", id, rule, scheduled.join(", "));
+                }
+            }
+            Err(error) => println!("{}", error),
+        }
+    }
+    
+    /// Save, remove or roll back to the active simulation's checkpoints on
+    /// the server, then list them.
+    async fn handle_checkpoint_command(&mut self, command: CheckpointCommand) {
+        if self.read_only && command != CheckpointCommand::List {
+            println!("{}", READ_ONLY_STATUS);
+            return;
+        }
+        let mut client = self.client.clone();
+        let id = self.simulation_id();
+        let changed = async {
+            client.connect().await?;
+            let checkpoints = match command {
+                CheckpointCommand::List => client.list_checkpoints(id.clone()).await?,
+                CheckpointCommand::Create(name) => client.create_checkpoint(id.clone(), name).await?,
+                CheckpointCommand::Remove(name) => client.delete_checkpoint(id.clone(), name).await?,
+                CheckpointCommand::Restore(name) => {
+                    let simulation = client.restore_checkpoint(id.clone(), name.clone()).await?;
+                    return anyhow::Ok((Vec::new(), Some((name, simulation))));
+                }
+            };
+            Ok((checkpoints.checkpoints, None))
+        };
+        match changed.await {
+            Ok((_, Some((name, simulation)))) => {
+                self.display_mut().update_from_simulation(&simulation);
+                println!("Rolled {} back to {} at generation {}", id, name, simulation.generation);
+            }
+            Ok((checkpoints, None)) if checkpoints.is_empty() => println!("No checkpoints on {}", id),
+            Ok((checkpoints, None)) => {
+                let labels: Vec<String> = checkpoints.iter()
+                    .map(|checkpoint| format!("{} (generation {}, {} cells)", checkpoint.name, checkpoint.generation, checkpoint.live_cells))
+                    .collect();
+                println!("Checkpoints of {}: {}", id, labels.join(", "));
+            }
+            Err(error) => println!("{}", error),
+        }
+    }
+    
+    /// Add or clear the active simulation's watchpoints on the server, then
+    /// list them and tint them on the grid.
+    async fn handle_watch_command(&mut self, command: WatchCommand) {
+        if self.read_only && command != WatchCommand::List {
+            println!("{}", READ_ONLY_STATUS);
+            return;
+        }
+        let mut client = self.client.clone();
+        let id = self.simulation_id();
+        let watchpoints = async {
+            client.connect().await?;
+            let mut watchpoints = client.get_simulation_fields(id.clone(), &["watchpoints"]).await?.watchpoints;
+            match command {
+                WatchCommand::List => return Ok(watchpoints),
+                WatchCommand::Add(watchpoint) => watchpoints.push(watchpoint),
+                WatchCommand::Clear => watchpoints.clear(),
+            }
+            anyhow::Ok(client.set_watchpoints(id.clone(), watchpoints).await?.watchpoints)
+        };
+        match watchpoints.await {
+            Ok(watchpoints) => {
+                self.display_mut().set_watchpoints(&watchpoints);
+                if watchpoints.is_empty() {
+                    println!("No watchpoints on {}", id);
+                    return;
+                }
+                if self.cell_changes.is_none() {
+                    self.watch_cell_changes();
+                }
+                let labels: Vec<String> = watchpoints.iter().map(watchpoint_label).collect();
+                println!("Watchpoints on {}: {}", id, labels.join(", "));
+            }
+            Err(error) => println!("{}", error),
+        }
+    }
+    
+    /// Add, remove or list the active simulation's labels, or move the view
+    /// to one. Changes are made against the labels just fetched, so one
+    /// added by someone else meanwhile is not overwritten.
+    async fn handle_note_command(&mut self, command: NoteCommand) {
+        if self.read_only && matches!(command, NoteCommand::Add(..) | NoteCommand::Remove(_) | NoteCommand::Clear) {
+            println!("{}", READ_ONLY_STATUS);
+            return;
+        }
+        let mut client = self.client.clone();
+        let id = self.simulation_id();
+        let center = self.display().view_center();
+        let annotations = async {
+            client.connect().await?;
+            let current = client.get_annotations(id.clone()).await?;
+            let mut annotations = current.annotations;
+            match &command {
+                NoteCommand::List | NoteCommand::Go(_) => return Ok(annotations),
+                NoteCommand::Add(at, text) => {
+                    let (x, y) = at.unwrap_or(center);
+                    annotations.push(Annotation { x, y, text: text.clone() });
+                }
+                NoteCommand::Remove(number) if *number <= annotations.len() => {
+                    annotations.remove(number - 1);
+                }
+                NoteCommand::Remove(number) => anyhow::bail!("No label {} on {}", number, id),
+                NoteCommand::Clear => annotations.clear(),
+            }
+            anyhow::Ok(client.set_annotations(id.clone(), annotations, Some(current.version)).await?.annotations)
+        };
+        let annotations = match annotations.await {
+            Ok(annotations) => annotations,
+            Err(error) => {
+                println!("{}", error);
+                return;
+            }
+        };
+        if let NoteCommand::Go(number) = command {
+            match annotations.get(number - 1) {
+                Some(annotation) => {
+                    let zoom = self.display().get_viewport_info().2;
+                    self.display_mut().look_at((annotation.x, annotation.y), zoom);
+                }
+                None => println!("No label {} on {}", number, id),
+            }
+        }
+        if annotations.is_empty() {
+            println!("No labels on {}", id);
+        } else {
+            let labels: Vec<String> = annotations.iter().enumerate()
+                .map(|(index, annotation)| format!("{}. ({}, {}) {}", index + 1, annotation.x, annotation.y, annotation.text))
+                .collect();
+            println!("Labels on {}: {}", id, labels.join(", "));
+        }
+        self.display_mut().set_annotations(annotations);
+    }
+    
+    async fn handle_tab_command(&mut self, command: TabCommand) {
+        match command {
+            TabCommand::New(Some(id)) => {
+                self.tabs.open(id);
+                self.refresh_simulation().await;
+            }
+            TabCommand::New(None) => {
+                let mut client = self.client.clone();
+                if client.connect().await.is_ok() {
+                    if let Ok(simulation) = client.create_simulation(100, 50, None, None).await {
+                        self.tabs.open(simulation.id.clone());
+                        self.display_mut().update_from_simulation(&simulation);
+                    }
+                }
+            }
+            TabCommand::Close => {
+                if self.tabs.close_active() {
+                    self.refresh_simulation().await;
+                }
+            }
+            TabCommand::Next => {
+                self.tabs.next();
+                self.refresh_simulation().await;
+            }
+            TabCommand::Previous => {
+                self.tabs.previous();
+                self.refresh_simulation().await;
+            }
+            TabCommand::Go(number) => {
+                if self.tabs.select(number - 1) {
+                    self.refresh_simulation().await;
+                }
+            }
+        }
+    }
+    
+    /// Fetch the active tab's simulation, which may have been stepped by
+    /// others since the tab was last shown, or the past generation it is
+    /// browsing.
+    async fn refresh_simulation(&mut self) {
+        if self.tabs.active().history.is_some() {
+            self.show_history_position().await;
+            return;
+        }
+        let mut client = self.client.clone();
+        if client.connect().await.is_ok() {
+            if let Ok(simulation) = client.get_simulation(self.simulation_id()).await {
+                self.display_mut().update_from_simulation(&simulation);
+                self.performance.record_update();
+            }
+            if let Ok(annotations) = client.get_annotations(self.simulation_id()).await {
+                self.display_mut().set_annotations(annotations.annotations);
+            }
+        }
+    }
+    
+    /// Show the outcome of a finished multi-generation step and start the
+    /// steps queued behind it.
+    async fn poll_stepping(&mut self) {
+        let Some(run) = &mut self.stepping else {
+            return;
+        };
+        let Some(result) = run.poll() else {
+            return;
+        };
+        let (simulation_id, queued) = (run.simulation_id.clone(), run.queued());
+        self.stepping = None;
+        let stopped = match result {
+            Ok(stopped) => stopped,
+            Err(error) => {
+                println!("{}", error);
+                return;
+            }
+        };
+        if simulation_id == self.simulation_id() {
+            self.refresh_simulation().await;
+        }
+        // Steps queued behind ones that stopped short would only go past
+        // where they stopped
+        if let Some(reason) = stopped {
+            self.running = false;
+            self.stopped = Some(reason);
+        } else if queued > 0 {
+            self.stepping = Some(StepRun::start(&self.client, simulation_id, queued));
+        }
+    }
+    
+    /// Fetch the active simulation's summary, and its cells only when it
+    /// has changed since they were shown.
+    async fn poll_summary(&mut self) {
+        self.last_summary_poll = Instant::now();
+        let mut client = self.client.clone();
+        if client.connect().await.is_ok() {
+            if let Ok(summary) = client.get_summary(self.simulation_id()).await {
+                if self.display_mut().update_summary(summary) {
+                    self.refresh_simulation().await;
+                }
+            }
+        }
+    }
+    
+    /// Create a simulation and switch to it.
+    async fn create_simulation(&mut self) {
+        let mut client = self.client.clone();
+        if client.connect().await.is_ok() {
+            if let Ok(simulation) = client.create_simulation(100, 50, None, None).await {
+                self.tabs.active_mut().simulation_id = simulation.id.clone();
+                self.display_mut().update_from_simulation(&simulation);
+            }
+        }
+    }
+    
+    /// Create the active tab's simulation under the id the tab has, so
+    /// `default` exists once the first step is asked for, or under a new id
+    /// when the server refuses it.
+    async fn recreate_simulation(&mut self) {
+        let mut client = self.client.clone();
+        if client.connect().await.is_ok() {
+            match client.create_simulation_with_id(self.simulation_id(), 100, 50).await {
+                Ok(simulation) => self.display_mut().update_from_simulation(&simulation),
+                Err(_) => self.create_simulation().await,
+            }
+        }
+    }
+    
     async fn step_simulation(&mut self) -> Result<()> {
         let mut client = self.client.clone();
         
         match client.connect().await {
             Ok(_) => {
-                match client.step_simulation("default".to_string(), 1).await {
-                    Ok(_) => {
-                        match client.get_simulation("default".to_string()).await {
+                let started = Instant::now();
+                match client.step_simulation(self.simulation_id(), 1).await {
+                    Ok(response) => {
+                        self.connection.record_success(started.elapsed());
+                        if let Some(breakpoint) = &response.breakpoint {
+                            self.running = false;
+                            self.stopped = Some(format!("breakpoint {}", breakpoint_label(breakpoint)));
+                        } else if let Some(watchpoint) = &response.watchpoint {
+                            self.running = false;
+                            self.stopped = Some(format!("watchpoint {}", watchpoint_label(watchpoint)));
+                        }
+                        match client.get_simulation(self.simulation_id()).await {
                             Ok(sim) => {
-                                self.display.update_from_simulation(&sim);
-                            }
-                            Err(_) => {
-                                // Create simulation if it doesn't exist
-                                let _ = client.create_simulation(100, 50, None).await;
+                                // Fade across the whole interval during slow playback
+                                let transition = if self.running && self.auto_step_interval >= MIN_ANIMATED_INTERVAL {
+                                    self.auto_step_interval
+                                } else {
+                                    Duration::ZERO
+                                };
+                                self.display_mut().advance_to(&sim, transition);
+                                self.performance.record_update();
+                                if let Ok(stats) = client.get_stats(self.simulation_id(), None).await {
+                                    self.display_mut().update_stats(stats);
+                                }
+                                if self.display().is_census_shown() {
+                                    self.refresh_census().await;
+                                }
+                                if self.display().is_diff_shown() {
+                                    self.refresh_diff().await;
+                                }
+                                if self.display().is_heatmap_shown() {
+                                    self.refresh_heatmap().await;
+                                }
                             }
+                            // Create simulation if it doesn't exist
+                            Err(_) if !self.attached => self.recreate_simulation().await,
+                            Err(_) => {}
                         }
                     }
-                    Err(_) => {
-                        // Create simulation if step fails
-                        let _ = client.create_simulation(100, 50, None).await;
-                    }
+                    // Create simulation if step fails
+                    Err(_) if !self.attached => self.recreate_simulation().await,
+                    Err(_) => {}
                 }
             }
-            Err(_) => {
+            Err(error) => {
                 // Server not available
+                self.connection.record_failure(&error);
             }
         }
         
         Ok(())
     }
     
+    async fn refresh_census(&mut self) {
+        let mut client = self.client.clone();
+        if client.connect().await.is_ok() {
+            if let Ok(census) = client.get_census(self.simulation_id()).await {
+                self.display_mut().update_census(census);
+            }
+        }
+    }
+    
+    async fn refresh_heatmap(&mut self) {
+        let mut client = self.client.clone();
+        if client.connect().await.is_ok() {
+            if let Ok(heatmap) = client.get_heatmap(self.simulation_id(), HEATMAP_GENERATIONS).await {
+                self.display_mut().update_heatmap(heatmap);
+            }
+        }
+    }
+    
+    /// Fetch the diff for the overlay, by default between the shown
+    /// simulation's last two generations.
+    async fn refresh_diff(&mut self) {
+        let (a, b) = match &self.tabs.active().diff_targets {
+            Some(targets) => targets.clone(),
+            None => {
+                let (generation, _) = self.tabs.active().display.get_stats();
+                let id = self.simulation_id();
+                ((id.clone(), Some((generation - 1).max(0))), (id, None))
+            }
+        };
+        let mut client = self.client.clone();
+        if client.connect().await.is_ok() {
+            if let Ok(diff) = client.get_diff(a, b).await {
+                self.display_mut().update_diff(diff);
+            }
+        }
+    }
+    
+    /// Start placing a pattern, by name or inline RLE, as a ghost over the grid.
     async fn load_pattern(&mut self, pattern_name: &str) -> Result<()> {
-        // This would load a pattern from the patterns directory
-        // For now, we'll just create a simple pattern
-        println!("Loading pattern: {}", pattern_name);
+        if self.read_only {
+            println!("{}", READ_ONLY_STATUS);
+            return Ok(());
+        }
+        match search::load_search_pattern(pattern_name) {
+            Ok(cells) => self.display_mut().start_stamp(pattern_name.to_string(), &cells),
+            Err(error) => println!("{}", error),
+        }
+        Ok(())
+    }
+    
+    /// Switch to the named theme, or cycle to the next one with `None`.
+    /// Unknown names are ignored.
+    fn switch_theme(&mut self, name: Option<&str>) {
+        let name = name.unwrap_or_else(|| {
+            let current = THEME_NAMES.iter().position(|&name| name == self.tabs.theme().name).unwrap_or(0);
+            THEME_NAMES[(current + 1) % THEME_NAMES.len()]
+        });
+        if let Some(theme) = Theme::by_name(name) {
+            self.set_theme(theme.with_truecolor(truecolor_supported()));
+        }
+    }
+    
+    /// Switch to the named keymap, or cycle to the next one with `None`,
+    /// keeping the configured key bindings. Unknown names are ignored.
+    fn switch_keymap(&mut self, name: Option<&str>) {
+        let name = name.unwrap_or_else(|| {
+            let current = KEYMAP_NAMES.iter().position(|&name| name == self.input_handler.keymap().name).unwrap_or(0);
+            KEYMAP_NAMES[(current + 1) % KEYMAP_NAMES.len()]
+        });
+        if let Some(keymap) = self.input_handler.keymap().switch(name) {
+            self.input_handler.set_keymap(keymap);
+        }
+    }
+    
+    /// Use `keymap` for keys outside command mode.
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.input_handler.set_keymap(keymap);
+    }
+    
+    /// Apply a color theme to the grid, tab bar, menus and panels.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.tabs.set_theme(theme);
+        self.menu_system.set_theme(theme);
+        self.picker.set_theme(theme);
+        self.palette.set_theme(theme);
+        self.help.set_theme(theme);
+    }
+    
+    /// Draw the grid with a terminal graphics protocol, or as text with
+    /// `None`. Pixels are on whenever a protocol is set; `x` toggles them.
+    pub fn set_graphics(&mut self, protocol: Option<GraphicsProtocol>) -> Result<()> {
+        self.set_pixels(false)?;
+        self.graphics = protocol.map(PixelRenderer::new);
+        self.set_pixels(self.graphics.is_some())
+    }
+    
+    fn set_pixels(&mut self, pixels: bool) -> Result<()> {
+        if !pixels {
+            if let Some(renderer) = &mut self.graphics {
+                renderer.clear(self.terminal.backend_mut())?;
+            }
+            // Sixel pixels stay until the text beneath them is redrawn
+            self.terminal.clear()?;
+        }
+        self.tabs.set_pixels(pixels);
+        Ok(())
+    }
+    
+    /// Draw the grid into the area the last frame left blank for it, unless
+    /// a popup is covering it.
+    fn draw_pixels(&mut self) -> Result<()> {
+        let Some(renderer) = &mut self.graphics else {
+            return Ok(());
+        };
+        let covered = self.menu_system.is_menu_active() || self.help.is_visible() || self.palette.is_visible();
+        let display = &self.tabs.active().display;
+        match display.pixel_area().filter(|_| !covered) {
+            Some(area) => renderer.draw(self.terminal.backend_mut(), display, area)?,
+            // Hex grids are drawn as text
+            None => renderer.clear(self.terminal.backend_mut())?,
+        }
         Ok(())
     }
     
     pub fn with_client(mut self, client: GameOfLifeClient) -> Self {
-        self.client = client;
+        self.switch_client(client);
         self
     }
     
+    /// Talk to another backend, probing it afresh for the status bar.
+    fn switch_client(&mut self, client: GameOfLifeClient) {
+        self.connection = ConnectionMonitor::new(&client);
+        client.track_versions();
+        self.client = client;
+    }
+    
     pub fn set_auto_step_interval(&mut self, interval: Duration) {
         self.auto_step_interval = interval;
+        self.target_speed = 1.0 / interval.as_secs_f64();
     }
 }
 
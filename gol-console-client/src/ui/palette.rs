@@ -0,0 +1,171 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use super::keymap::{KeyAction, Keymap};
+use super::theme::Theme;
+
+/// Popup listing every action with the keys bound to it, filtered by fuzzy
+/// search as the user types; Enter runs the selected action.
+pub struct CommandPalette {
+    visible: bool,
+    query: String,
+    /// Actions the palette offers, with their keys joined for display.
+    actions: Vec<(KeyAction, String)>,
+    /// Indexes into `actions` that match the query, best match first.
+    matches: Vec<usize>,
+    selected_index: usize,
+    theme: Theme,
+}
+
+impl Default for CommandPalette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandPalette {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            query: String::new(),
+            actions: Vec::new(),
+            matches: Vec::new(),
+            selected_index: 0,
+            theme: Theme::default(),
+        }
+    }
+    
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+    
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+    
+    /// Show the palette with an empty query, listing the keys `keymap`
+    /// binds to each action.
+    pub fn open(&mut self, keymap: &Keymap) {
+        self.actions = KeyAction::all()
+            // Prefixes and the palette itself do nothing on their own
+            .filter(|action| !matches!(action, KeyAction::TabPrefix | KeyAction::Palette))
+            .map(|action| (action, keymap.keys_for(action).join(", ")))
+            .collect();
+        self.query.clear();
+        self.visible = true;
+        self.filter();
+    }
+    
+    pub fn close(&mut self) {
+        self.visible = false;
+    }
+    
+    /// Handle a key while the palette is open: typing filters, ↑/↓ select,
+    /// Enter returns the selected action and closes, Esc closes.
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<KeyAction> {
+        match key {
+            KeyCode::Esc => self.close(),
+            KeyCode::Enter => {
+                self.close();
+                return self.matches.get(self.selected_index).map(|&index| self.actions[index].0);
+            }
+            KeyCode::Up => self.selected_index = self.selected_index.saturating_sub(1),
+            KeyCode::Down => self.selected_index = (self.selected_index + 1).min(self.matches.len().saturating_sub(1)),
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.filter();
+            }
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.filter();
+            }
+            _ => {}
+        }
+        None
+    }
+    
+    /// Rank the actions against the query by their description and name,
+    /// dropping those it does not match, and select the best.
+    fn filter(&mut self) {
+        let mut scored: Vec<(i32, usize)> = self.actions.iter().enumerate()
+            .filter_map(|(index, &(action, _))| {
+                let score = fuzzy_score(&self.query, action.description()).max(fuzzy_score(&self.query, action.name()))?;
+                Some((score, index))
+            })
+            .collect();
+        // Stable, so equal scores keep the registry's order
+        scored.sort_by_key(|&(score, _)| -score);
+        self.matches = scored.into_iter().map(|(_, index)| index).collect();
+        self.selected_index = 0;
+    }
+    
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let popup = centered(area, 60, 20);
+        frame.render_widget(Clear, popup);
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3), // Query
+                Constraint::Min(0),    // Matching actions
+            ])
+            .split(popup);
+        
+        let query = Paragraph::new(format!("> {}_", self.query))
+            .block(Block::default().title("Command Palette").borders(Borders::ALL))
+            .style(Style::default().fg(self.theme.accent));
+        frame.render_widget(query, rows[0]);
+        
+        let width = rows[1].width.saturating_sub(2) as usize;
+        let items: Vec<ListItem> = self.matches.iter().map(|&index| {
+            let (action, keys) = &self.actions[index];
+            let description = action.description();
+            let gap = width.saturating_sub(description.len() + keys.len()).max(1);
+            ListItem::new(format!("{}{}{}", description, " ".repeat(gap), keys))
+        }).collect();
+        let list = List::new(items)
+            .block(Block::default().title("Enter run, Esc close").borders(Borders::ALL))
+            .style(Style::default().fg(self.theme.text))
+            .highlight_style(Style::default().fg(self.theme.accent).bg(self.theme.selection));
+        let mut state = ListState::default().with_selected((!self.matches.is_empty()).then_some(self.selected_index));
+        frame.render_stateful_widget(list, rows[1], &mut state);
+    }
+}
+
+/// How well `query` matches `text` when its characters appear in order,
+/// ignoring case: consecutive characters and those starting a word score
+/// higher. `None` when it does not match; an empty query matches anything.
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+    let mut chars = text.char_indices();
+    for wanted in query.chars().flat_map(char::to_lowercase) {
+        let (index, _) = chars.find(|(_, c)| c.to_lowercase().eq(std::iter::once(wanted)))?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += 5;
+        }
+        if index == 0 || !text.as_bytes()[index - 1].is_ascii_alphanumeric() {
+            score += 3;
+        }
+        previous = Some(index);
+    }
+    Some(score)
+}
+
+/// A `width` columns by `height` rows rectangle in the middle of `area`,
+/// shrunk to fit.
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::theme::Theme;
+
+/// Span the render and update rates are averaged over.
+const RATE_WINDOW: Duration = Duration::from_secs(2);
+
+/// Width and height of the overlay, borders included.
+const OVERLAY_WIDTH: u16 = 26;
+const OVERLAY_HEIGHT: u16 = 6;
+
+/// Client-side timing for the performance overlay: when frames were drawn
+/// and simulation updates arrived, and how many animation frames came late.
+pub struct PerformanceMonitor {
+    shown: bool,
+    frames: VecDeque<Instant>,
+    updates: VecDeque<Instant>,
+    /// Frames an animation missed because a redraw came later than its
+    /// interval allowed.
+    dropped_frames: u64,
+    /// Whether the last frame was drawn mid-animation.
+    was_animating: bool,
+}
+
+impl Default for PerformanceMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PerformanceMonitor {
+    pub fn new() -> Self {
+        Self {
+            shown: false,
+            frames: VecDeque::new(),
+            updates: VecDeque::new(),
+            dropped_frames: 0,
+            was_animating: false,
+        }
+    }
+    
+    pub fn toggle(&mut self) {
+        self.shown = !self.shown;
+    }
+    
+    pub fn is_shown(&self) -> bool {
+        self.shown
+    }
+    
+    /// Note a redraw; while animating at one frame per `interval`, a
+    /// longer gap since the last one counts the frames it skipped.
+    pub fn record_frame(&mut self, animating: bool, interval: Duration) {
+        let now = Instant::now();
+        let animated = std::mem::replace(&mut self.was_animating, animating);
+        if let Some(&last) = self.frames.back().filter(|_| animating && animated) {
+            let skipped = (now - last).as_secs_f64() / interval.as_secs_f64();
+            self.dropped_frames += (skipped as u64).saturating_sub(1);
+        }
+        push_within_window(&mut self.frames, now);
+    }
+    
+    /// Note a new generation shown from the server.
+    pub fn record_update(&mut self) {
+        push_within_window(&mut self.updates, Instant::now());
+    }
+    
+    pub fn frame_rate(&self) -> f64 {
+        rate(&self.frames)
+    }
+    
+    pub fn update_rate(&self) -> f64 {
+        rate(&self.updates)
+    }
+    
+    /// Draw the overlay in the top-right corner of `area`, just under its
+    /// first row, with `server_rate` from the last status probe.
+    pub fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme, server_rate: Option<f64>) {
+        if area.width < OVERLAY_WIDTH || area.height < OVERLAY_HEIGHT + 1 {
+            return;
+        }
+        let area = Rect {
+            x: area.right() - OVERLAY_WIDTH,
+            y: area.y + 1,
+            width: OVERLAY_WIDTH,
+            height: OVERLAY_HEIGHT,
+        };
+        let server = match server_rate {
+            Some(rate) => format!("{:.1} gen/s", rate),
+            None => "unknown".to_string(),
+        };
+        let lines = vec![
+            Line::from(format!("Server   {}", server)),
+            Line::from(format!("Updates  {:.1} /s", self.update_rate())),
+            Line::from(format!("Render   {:.1} fps", self.frame_rate())),
+            Line::from(format!("Dropped  {} frames", self.dropped_frames)),
+        ];
+        let block = Block::default()
+            .title("Performance")
+            .borders(Borders::ALL)
+            .style(Style::default().fg(theme.accent));
+        
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
+
+/// Record `now`, forgetting times older than the rate window.
+fn push_within_window(times: &mut VecDeque<Instant>, now: Instant) {
+    while times.front().is_some_and(|&time| now - time > RATE_WINDOW) {
+        times.pop_front();
+    }
+    times.push_back(now);
+}
+
+/// Events per second over the rate window, ending now.
+fn rate(times: &VecDeque<Instant>) -> f64 {
+    let now = Instant::now();
+    times.iter().filter(|&&time| now - time <= RATE_WINDOW).count() as f64 / RATE_WINDOW.as_secs_f64()
+}
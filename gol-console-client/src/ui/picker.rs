@@ -0,0 +1,209 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::client::game_of_life::SimulationSummary;
+use super::keymap::{mode_action, Mode, ModeAction};
+use super::theme::Theme;
+
+/// An action picked in the simulation picker, for the caller to carry out
+/// against the server.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PickerAction {
+    /// Show the simulation in the current tab.
+    Attach(String),
+    /// Show the simulation in a new tab.
+    OpenInTab(String),
+    Clone(String),
+    Rename(String, String),
+    Delete(String),
+}
+
+/// Side panel listing the server's simulations, refreshed from
+/// ListSimulations while it is open.
+pub struct SimulationPicker {
+    visible: bool,
+    simulations: Vec<SimulationSummary>,
+    selected_index: usize,
+    /// Name being typed, while the selected simulation is being renamed.
+    name_input: Option<String>,
+    /// Set after the first delete key press; a second one deletes.
+    confirm_delete: bool,
+    /// Outcome of the last action, shown under the list.
+    status: Option<String>,
+    theme: Theme,
+}
+
+impl Default for SimulationPicker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulationPicker {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            simulations: Vec::new(),
+            selected_index: 0,
+            name_input: None,
+            confirm_delete: false,
+            status: None,
+            theme: Theme::default(),
+        }
+    }
+    
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+    
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        self.name_input = None;
+        self.confirm_delete = false;
+        self.status = None;
+    }
+    
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+    
+    pub fn is_editing_name(&self) -> bool {
+        self.name_input.is_some()
+    }
+    
+    /// Replace the listed simulations, keeping the same one selected if it
+    /// is still there.
+    pub fn update_simulations(&mut self, simulations: Vec<SimulationSummary>) {
+        let selected_id = self.selected().map(|simulation| simulation.id.clone());
+        self.simulations = simulations;
+        self.selected_index = selected_id
+            .and_then(|id| self.simulations.iter().position(|simulation| simulation.id == id))
+            .unwrap_or(self.selected_index)
+            .min(self.simulations.len().saturating_sub(1));
+    }
+    
+    pub fn set_status(&mut self, status: String) {
+        self.status = Some(status);
+    }
+    
+    fn selected(&self) -> Option<&SimulationSummary> {
+        self.simulations.get(self.selected_index)
+    }
+    
+    /// Handle a key while the picker is open: ↑/↓ select, Enter attaches, t
+    /// opens in a new tab, c clones, n renames and d twice deletes. Returns
+    /// the action to carry out, if any.
+    pub fn handle_key(&mut self, key: &KeyEvent) -> Option<PickerAction> {
+        if let Some(input) = &mut self.name_input {
+            match key.code {
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    let name = input.trim().to_string();
+                    self.name_input = None;
+                    return self.selected().map(|simulation| PickerAction::Rename(simulation.id.clone(), name));
+                }
+                KeyCode::Esc => self.name_input = None,
+                _ => {}
+            }
+            return None;
+        }
+        
+        let confirm_delete = std::mem::take(&mut self.confirm_delete);
+        let id = self.selected()?.id.clone();
+        match mode_action(Mode::Picker, key)? {
+            ModeAction::SelectPrevious => {
+                self.selected_index = self.selected_index.saturating_sub(1);
+                None
+            }
+            ModeAction::SelectNext => {
+                self.selected_index = (self.selected_index + 1).min(self.simulations.len() - 1);
+                None
+            }
+            ModeAction::Attach => Some(PickerAction::Attach(id)),
+            ModeAction::OpenInTab => Some(PickerAction::OpenInTab(id)),
+            ModeAction::CloneSimulation => Some(PickerAction::Clone(id)),
+            ModeAction::RenameSimulation => {
+                self.name_input = Some(self.selected()?.name.clone());
+                None
+            }
+            ModeAction::DeleteSimulation if confirm_delete => Some(PickerAction::Delete(id)),
+            ModeAction::DeleteSimulation => {
+                self.confirm_delete = true;
+                self.status = Some("Press d again to delete".to_string());
+                None
+            }
+            _ => None,
+        }
+    }
+    
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),    // Simulation list
+                Constraint::Length(4), // Key hints and status
+            ])
+            .split(area);
+        
+        let mut items: Vec<ListItem> = self.simulations.iter().enumerate().map(|(index, simulation)| {
+            let label = match &self.name_input {
+                Some(input) if index == self.selected_index => format!("{}_", input),
+                _ if simulation.name.is_empty() => simulation.id.chars().take(8).collect(),
+                _ => simulation.name.clone(),
+            };
+            let text = format!(
+                "{}\n  gen {} pop {} {} {}",
+                label, simulation.generation, simulation.live_cells, simulation.rule, format_bytes(simulation.memory_bytes),
+            );
+            let style = if index == self.selected_index {
+                Style::default().fg(self.theme.accent).bg(self.theme.selection)
+            } else {
+                Style::default().fg(self.theme.text)
+            };
+            ListItem::new(text).style(style)
+        }).collect();
+        if items.is_empty() {
+            items.push(ListItem::new("No simulations"));
+        }
+        
+        let list = List::new(items)
+            .block(Block::default().title("Simulations").borders(Borders::ALL))
+            .style(Style::default().fg(self.theme.text));
+        frame.render_widget(list, rows[0]);
+        
+        let hint = if self.name_input.is_some() {
+            "Type a name, Apply: Enter, Cancel: Esc".to_string()
+        } else {
+            let status = self.status.as_deref().unwrap_or("");
+            format!("Enter attach, t tab, c clone, n rename, d delete, b close\n{}", status)
+        };
+        let hint = Paragraph::new(hint)
+            .style(Style::default().fg(self.theme.hint))
+            .wrap(Wrap { trim: true });
+        frame.render_widget(hint, rows[1]);
+    }
+}
+
+/// Byte count in the largest unit that keeps it at or above 1, e.g. `12.3 KiB`.
+fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes.max(0) as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes.max(0))
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
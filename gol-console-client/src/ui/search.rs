@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::commands::convert::decode_rle_body;
+use crate::commands::pattern::PatternFile;
+
+/// A cell position, as (x, y).
+type Position = (i64, i64);
+
+/// An occurrence of the searched pattern, by its bounding box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternMatch {
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+}
+
+impl PatternMatch {
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        (self.x..self.x.saturating_add(self.width)).contains(&x) && (self.y..self.y.saturating_add(self.height)).contains(&y)
+    }
+    
+    pub fn center(&self) -> (i64, i64) {
+        (self.x.saturating_add(self.width / 2), self.y.saturating_add(self.height / 2))
+    }
+}
+
+/// A pattern being searched for, with where it was last found.
+#[derive(Debug, Clone)]
+pub struct PatternSearch {
+    pub name: String,
+    /// The pattern's distinct rotations and reflections, each shifted so its
+    /// bounding box starts at (0, 0).
+    orientations: Vec<Vec<(i64, i64)>>,
+    pub matches: Vec<PatternMatch>,
+    /// Index of the match the viewport was last moved to.
+    pub current: usize,
+}
+
+impl PatternSearch {
+    pub fn new(name: String, cells: &[(i64, i64)]) -> Self {
+        Self {
+            name,
+            orientations: orientations(cells),
+            matches: Vec::new(),
+            current: 0,
+        }
+    }
+    
+    /// Find the pattern among `live_cells` again, e.g. after a step.
+    pub fn rescan(&mut self, live_cells: &HashMap<(i64, i64), i32>) {
+        self.matches = find_matches(live_cells, &self.orientations);
+        self.current = self.current.min(self.matches.len().saturating_sub(1));
+    }
+    
+    /// Move `step` matches forward or back, wrapping around, and return the
+    /// new current match.
+    pub fn cycle(&mut self, step: i32) -> Option<PatternMatch> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        let count = self.matches.len() as i32;
+        self.current = (self.current as i32 + step).rem_euclid(count) as usize;
+        Some(self.matches[self.current])
+    }
+    
+    pub fn current_match(&self) -> Option<PatternMatch> {
+        self.matches.get(self.current).copied()
+    }
+}
+
+/// The eight rotations and reflections of `cells`, normalized and without
+/// duplicates, so symmetric patterns are not found several times.
+fn orientations(cells: &[(i64, i64)]) -> Vec<Vec<(i64, i64)>> {
+    let transforms: [fn(Position) -> Position; 8] = [
+        |(x, y)| (x, y),
+        |(x, y)| (-y, x),
+        |(x, y)| (-x, -y),
+        |(x, y)| (y, -x),
+        |(x, y)| (-x, y),
+        |(x, y)| (x, -y),
+        |(x, y)| (y, x),
+        |(x, y)| (-y, -x),
+    ];
+    let mut orientations: Vec<Vec<(i64, i64)>> = Vec::new();
+    for transform in transforms {
+        let moved: Vec<(i64, i64)> = cells.iter().map(|&cell| transform(cell)).collect();
+        let min_x = moved.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let min_y = moved.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        let mut normalized: Vec<(i64, i64)> = moved.iter().map(|&(x, y)| (x - min_x, y - min_y)).collect();
+        normalized.sort_by_key(|&(x, y)| (y, x));
+        normalized.dedup();
+        if !normalized.is_empty() && !orientations.contains(&normalized) {
+            orientations.push(normalized);
+        }
+    }
+    orientations
+}
+
+/// Occurrences of any orientation whose cells are all live with no other
+/// live cell in or around their bounding box, so a glider inside a larger
+/// object does not count. Sorted top to bottom, then left to right.
+fn find_matches(live_cells: &HashMap<(i64, i64), i32>, orientations: &[Vec<(i64, i64)>]) -> Vec<PatternMatch> {
+    let mut matches = Vec::new();
+    for cells in orientations {
+        let width = cells.iter().map(|&(x, _)| x).max().unwrap_or(0) + 1;
+        let height = cells.iter().map(|&(_, y)| y).max().unwrap_or(0) + 1;
+        // Cells are sorted, so the first is the topmost live cell of any
+        // occurrence and fixes where it starts
+        let (anchor_x, anchor_y) = cells[0];
+        for &(x, y) in live_cells.keys() {
+            let (origin_x, origin_y) = (x - anchor_x, y - anchor_y);
+            if !cells.iter().all(|&(dx, dy)| live_cells.contains_key(&(origin_x + dx, origin_y + dy))) {
+                continue;
+            }
+            let live_around = (-1..=height).flat_map(|dy| (-1..=width).map(move |dx| (dx, dy)))
+                .filter(|&(dx, dy)| live_cells.contains_key(&(origin_x + dx, origin_y + dy)))
+                .count();
+            if live_around == cells.len() {
+                matches.push(PatternMatch { x: origin_x, y: origin_y, width, height });
+            }
+        }
+    }
+    matches.sort_by_key(|found| (found.y, found.x));
+    matches.dedup();
+    matches
+}
+
+/// Cells of the pattern `find` was given: inline RLE such as `bo$2bo$3o!`,
+/// a pattern name from the patterns directory, or a path to a JSON pattern
+/// file.
+pub fn load_search_pattern(pattern: &str) -> Result<Vec<(i64, i64)>, String> {
+    if pattern.contains('$') || pattern.ends_with('!') {
+        let body = decode_rle_body([(1, pattern)]).map_err(|error| format!("Invalid RLE '{}': {}", pattern, error))?;
+        if body.cells.is_empty() {
+            return Err(format!("Pattern {} has no cells", pattern));
+        }
+        return Ok(body.cells);
+    }
+    let path = if pattern.ends_with(".json") {
+        pattern.to_string()
+    } else {
+        format!("../patterns/{}.json", pattern)
+    };
+    let content = std::fs::read_to_string(Path::new(&path))
+        .map_err(|_| format!("Pattern not found: {}", pattern))?;
+    let file: PatternFile = serde_json::from_str(&content)
+        .map_err(|error| format!("Invalid pattern file {}: {}", path, error))?;
+    if file.cells.is_empty() {
+        return Err(format!("Pattern {} has no cells", pattern));
+    }
+    Ok(file.cells.iter().map(|cell| (cell.x, cell.y)).collect())
+}
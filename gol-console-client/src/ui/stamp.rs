@@ -0,0 +1,64 @@
+use std::collections::HashSet;
+
+/// A pattern being placed: drawn as a ghost over the grid, moved, rotated
+/// and flipped, until it is committed to the simulation or dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternStamp {
+    pub name: String,
+    /// Cells relative to the stamp's top-left corner, with the bounding box
+    /// starting at (0, 0).
+    cells: HashSet<(i64, i64)>,
+    /// World position of the top-left corner.
+    pub x: i64,
+    pub y: i64,
+}
+
+impl PatternStamp {
+    pub fn new(name: String, cells: &[(i64, i64)], x: i64, y: i64) -> Self {
+        let mut stamp = Self { name, cells: cells.iter().copied().collect(), x, y };
+        stamp.normalize();
+        stamp
+    }
+    
+    fn normalize(&mut self) {
+        let min_x = self.cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let min_y = self.cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        self.cells = self.cells.iter().map(|&(x, y)| (x - min_x, y - min_y)).collect();
+    }
+    
+    /// Width and height of the stamp's bounding box.
+    pub fn size(&self) -> (i64, i64) {
+        let width = self.cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+        let height = self.cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
+        (width, height)
+    }
+    
+    pub fn move_by(&mut self, dx: i64, dy: i64) {
+        self.x = self.x.saturating_add(dx);
+        self.y = self.y.saturating_add(dy);
+    }
+    
+    /// Turn a quarter clockwise about the top-left corner.
+    pub fn rotate(&mut self) {
+        let (_, height) = self.size();
+        self.cells = self.cells.iter().map(|&(x, y)| (height - 1 - y, x)).collect();
+    }
+    
+    /// Mirror left to right.
+    pub fn flip(&mut self) {
+        let (width, _) = self.size();
+        self.cells = self.cells.iter().map(|&(x, y)| (width - 1 - x, y)).collect();
+    }
+    
+    /// Whether the ghost covers world cell `(x, y)`.
+    pub fn covers(&self, x: i64, y: i64) -> bool {
+        self.cells.contains(&(x.wrapping_sub(self.x), y.wrapping_sub(self.y)))
+    }
+    
+    /// Cells relative to the top-left corner, in row-major order.
+    pub fn cells(&self) -> Vec<(i64, i64)> {
+        let mut cells: Vec<_> = self.cells.iter().copied().collect();
+        cells.sort_unstable_by_key(|&(x, y)| (y, x));
+        cells
+    }
+}
@@ -0,0 +1,122 @@
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, error::TryRecvError};
+
+use crate::client::game_of_life::JobState;
+use crate::client::GameOfLifeClient;
+use super::input::{breakpoint_label, watchpoint_label};
+
+/// Time between GetJobStatus calls while the server steps in the
+/// background.
+const JOB_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Frames of the spinner shown while stepping.
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+/// Most generations one StepSimulation call asks for; further queued steps
+/// wait for the next call.
+const MAX_STEPS_PER_CALL: i64 = i32::MAX as i64;
+
+/// Reported by the task running a step.
+enum StepProgress {
+    /// The background job has run this many of the steps.
+    Advanced(i64),
+    /// The steps are done, stopped early for the reason given, or failed.
+    Finished(Result<Option<String>, String>),
+}
+
+/// Many generations stepped by one StepSimulation call, run in the
+/// background so the UI keeps drawing. Steps asked for meanwhile are
+/// queued and run when it finishes.
+pub struct StepRun {
+    pub simulation_id: String,
+    steps: i64,
+    completed: i64,
+    queued: i64,
+    started: Instant,
+    updates: mpsc::UnboundedReceiver<StepProgress>,
+}
+
+impl StepRun {
+    /// Step `simulation_id` by `steps` generations on the server. Steps
+    /// past the server's inline limit run as a job whose progress is
+    /// polled.
+    pub fn start(client: &GameOfLifeClient, simulation_id: String, steps: i64) -> Self {
+        let steps = steps.min(MAX_STEPS_PER_CALL);
+        let (sender, updates) = mpsc::unbounded_channel();
+        let mut client = client.clone();
+        let id = simulation_id.clone();
+        tokio::spawn(async move {
+            let progress = sender.clone();
+            let finished = async {
+                client.connect().await?;
+                let response = client.step_simulation(id, steps as i32).await?;
+                if response.job_id.is_empty() {
+                    return Ok(response.breakpoint.as_ref().map(|breakpoint| format!("breakpoint {}", breakpoint_label(breakpoint)))
+                        .or_else(|| response.watchpoint.as_ref().map(|watchpoint| format!("watchpoint {}", watchpoint_label(watchpoint)))));
+                }
+                loop {
+                    tokio::time::sleep(JOB_POLL_INTERVAL).await;
+                    let status = client.get_job_status(response.job_id.clone()).await?;
+                    match status.state() {
+                        JobState::JobPending | JobState::JobRunning => {
+                            let _ = progress.send(StepProgress::Advanced(status.steps_completed));
+                        }
+                        // Jobs say why they stopped short, such as at a breakpoint
+                        JobState::JobCompleted if status.steps_completed < status.steps_total => return Ok(Some(status.message)),
+                        JobState::JobCompleted => return Ok(None),
+                        JobState::JobCancelled => anyhow::bail!("Stepping cancelled: {}", status.message),
+                        JobState::JobFailed => anyhow::bail!("Stepping failed: {}", status.message),
+                    }
+                }
+            };
+            let result: anyhow::Result<Option<String>> = finished.await;
+            let _ = sender.send(StepProgress::Finished(result.map_err(|error| error.to_string())));
+        });
+        Self {
+            simulation_id,
+            steps,
+            completed: 0,
+            queued: 0,
+            started: Instant::now(),
+            updates,
+        }
+    }
+    
+    /// Add `steps` more generations, stepped after these.
+    pub fn queue(&mut self, steps: i64) {
+        self.queued = self.queued.saturating_add(steps);
+    }
+    
+    /// Generations queued behind this run.
+    pub fn queued(&self) -> i64 {
+        self.queued
+    }
+    
+    /// Take the task's progress, returning its outcome once it finishes:
+    /// why the steps stopped early, if they did, or why they failed.
+    pub fn poll(&mut self) -> Option<Result<Option<String>, String>> {
+        loop {
+            match self.updates.try_recv() {
+                Ok(StepProgress::Advanced(completed)) => self.completed = completed,
+                Ok(StepProgress::Finished(result)) => return Some(result),
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => return Some(Err("Stepping stopped".to_string())),
+            }
+        }
+    }
+    
+    /// Progress for the tab bar, e.g. `/ stepping… 340/1000 (+100 queued)`;
+    /// only background jobs report how many steps have run.
+    pub fn label(&self) -> String {
+        let frame = SPINNER[(self.started.elapsed().as_millis() / 100) as usize % SPINNER.len()];
+        let mut label = if self.completed > 0 {
+            format!("{} stepping… {}/{}", frame, self.completed, self.steps)
+        } else {
+            format!("{} stepping… {} generations", frame, self.steps)
+        };
+        if self.queued > 0 {
+            label.push_str(&format!(" (+{} queued)", self.queued));
+        }
+        label
+    }
+}
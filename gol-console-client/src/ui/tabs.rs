@@ -0,0 +1,153 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use super::display::GridDisplay;
+use super::history::HistoryScrubber;
+use super::input::DiffSide;
+use super::theme::Theme;
+
+/// An open simulation with its own grid view, viewport and diff overlay.
+pub struct SimulationTab {
+    pub simulation_id: String,
+    pub display: GridDisplay,
+    /// What the diff overlay compares; `None` compares the simulation's
+    /// previous generation with its current one.
+    pub diff_targets: Option<(DiffSide, DiffSide)>,
+    /// Set while past generations are being browsed instead of the live one.
+    pub history: Option<HistoryScrubber>,
+}
+
+impl SimulationTab {
+    pub fn new(simulation_id: String) -> Self {
+        Self {
+            simulation_id,
+            display: GridDisplay::new(),
+            diff_targets: None,
+            history: None,
+        }
+    }
+}
+
+/// The simulations open in the UI, one of which is shown. There is always
+/// at least one tab.
+pub struct TabSet {
+    tabs: Vec<SimulationTab>,
+    active: usize,
+    /// Applied to the tab bar and every tab's grid, including new ones.
+    theme: Theme,
+    /// Whether every tab's grid is drawn as pixels.
+    pixels: bool,
+}
+
+impl TabSet {
+    pub fn new(simulation_id: String) -> Self {
+        Self {
+            tabs: vec![SimulationTab::new(simulation_id)],
+            active: 0,
+            theme: Theme::default(),
+            pixels: false,
+        }
+    }
+    
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        for tab in &mut self.tabs {
+            tab.display.set_theme(theme);
+        }
+    }
+    
+    pub fn theme(&self) -> Theme {
+        self.theme
+    }
+    
+    pub fn set_pixels(&mut self, pixels: bool) {
+        self.pixels = pixels;
+        for tab in &mut self.tabs {
+            tab.display.set_pixels(pixels);
+        }
+    }
+    
+    pub fn pixels(&self) -> bool {
+        self.pixels
+    }
+    
+    pub fn active(&self) -> &SimulationTab {
+        &self.tabs[self.active]
+    }
+    
+    pub fn active_mut(&mut self) -> &mut SimulationTab {
+        &mut self.tabs[self.active]
+    }
+    
+    /// Every open tab, active or not.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut SimulationTab> {
+        self.tabs.iter_mut()
+    }
+    
+    /// Open a tab after the current one and switch to it.
+    pub fn open(&mut self, simulation_id: String) {
+        self.active += 1;
+        let mut tab = SimulationTab::new(simulation_id);
+        tab.display.set_theme(self.theme);
+        tab.display.set_pixels(self.pixels);
+        self.tabs.insert(self.active, tab);
+    }
+    
+    /// Close the current tab, unless it is the last one.
+    pub fn close_active(&mut self) -> bool {
+        if self.tabs.len() == 1 {
+            return false;
+        }
+        self.tabs.remove(self.active);
+        self.active = self.active.min(self.tabs.len() - 1);
+        true
+    }
+    
+    pub fn next(&mut self) {
+        self.active = (self.active + 1) % self.tabs.len();
+    }
+    
+    pub fn previous(&mut self) {
+        self.active = (self.active + self.tabs.len() - 1) % self.tabs.len();
+    }
+    
+    /// Switch to the tab at `index`, counting from zero.
+    pub fn select(&mut self, index: usize) -> bool {
+        if index >= self.tabs.len() {
+            return false;
+        }
+        self.active = index;
+        true
+    }
+    
+    pub fn update_terminal_size(&mut self, width: u16, height: u16) {
+        for tab in &mut self.tabs {
+            tab.display.update_terminal_size(width, height);
+        }
+    }
+    
+    /// One label per tab with its simulation's last known generation and
+    /// population, the current tab highlighted.
+    pub fn render_tab_bar(&self, frame: &mut Frame, area: Rect) {
+        let mut spans = Vec::new();
+        for (index, tab) in self.tabs.iter().enumerate() {
+            let (generation, live_count) = tab.display.get_stats();
+            let label = format!(" {}:{} gen {} pop {} ", index + 1, tab.simulation_id, generation, live_count);
+            let style = if index == self.active {
+                Style::default().fg(self.theme.active_tab.0).bg(self.theme.active_tab.1).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(self.theme.muted)
+            };
+            spans.push(Span::styled(label, style));
+            spans.push(Span::raw("│"));
+        }
+        spans.pop();
+        
+        frame.render_widget(Paragraph::new(Line::from(spans)), area);
+    }
+}
@@ -0,0 +1,268 @@
+use ratatui::style::Color;
+
+/// An RGB color, as the ends of a truecolor gradient.
+type Rgb = (u8, u8, u8);
+
+/// Names accepted by `--theme`, `GOL_THEME` and the `theme` command, in the
+/// order the Settings menu cycles through them.
+pub const THEME_NAMES: [&str; 4] = ["classic", "solarized", "high-contrast", "monochrome"];
+
+/// Colors used across the grid, panels and menus.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    /// Body text, borders and unselected list entries.
+    pub text: Color,
+    /// Status bar, counts and the selected list entry.
+    pub accent: Color,
+    /// Background of the selected list entry.
+    pub selection: Color,
+    /// Key hints and action outcomes.
+    pub hint: Color,
+    /// Dead cells and secondary text.
+    pub muted: Color,
+    /// Rulers and the origin axes.
+    pub axis: Color,
+    /// Foreground and background of the current tab.
+    pub active_tab: (Color, Color),
+    /// Live cells by colony under Immigration and QuadLife; plain rules only
+    /// use the first.
+    pub colonies: [Color; 4],
+    /// Generations decay states 2, 3, ...; later states reuse the last.
+    pub decay: [Color; 5],
+    /// Heatmap cells from rarely alive to always alive.
+    pub heat: [Color; 5],
+    pub turmite: Color,
+    pub only_in_a: Color,
+    pub only_in_b: Color,
+    /// Connection indicator while the server answers, and while it does not.
+    pub connected: Color,
+    pub disconnected: Color,
+    /// Colors decay states fade between on truecolor terminals, from the
+    /// first decay state to the last.
+    gradient: Option<(Rgb, Rgb)>,
+    /// Whether the terminal shows 24-bit color, so `gradient` can be used.
+    truecolor: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+impl Theme {
+    /// The look the client had before themes.
+    pub fn classic() -> Self {
+        Self {
+            name: "classic",
+            text: Color::White,
+            accent: Color::Yellow,
+            selection: Color::DarkGray,
+            hint: Color::Cyan,
+            muted: Color::DarkGray,
+            axis: Color::Blue,
+            active_tab: (Color::Black, Color::Cyan),
+            colonies: [Color::Green, Color::LightRed, Color::LightBlue, Color::LightYellow],
+            decay: [Color::Yellow, Color::LightRed, Color::Red, Color::Magenta, Color::Blue],
+            heat: [Color::Blue, Color::Cyan, Color::Green, Color::Yellow, Color::LightRed],
+            turmite: Color::LightRed,
+            only_in_a: Color::LightRed,
+            only_in_b: Color::LightCyan,
+            connected: Color::Green,
+            disconnected: Color::LightRed,
+            gradient: Some(((255, 220, 0), (40, 40, 160))),
+            truecolor: false,
+        }
+    }
+    
+    /// Ethan Schoonover's Solarized accents on the dark base tones.
+    pub fn solarized() -> Self {
+        let rgb = |value: u32| Color::Rgb((value >> 16) as u8, (value >> 8) as u8, value as u8);
+        Self {
+            name: "solarized",
+            text: rgb(0x93a1a1),
+            accent: rgb(0xb58900),
+            selection: rgb(0x073642),
+            hint: rgb(0x2aa198),
+            muted: rgb(0x586e75),
+            axis: rgb(0x268bd2),
+            active_tab: (rgb(0x002b36), rgb(0x2aa198)),
+            colonies: [rgb(0x859900), rgb(0xdc322f), rgb(0x268bd2), rgb(0xb58900)],
+            decay: [rgb(0xb58900), rgb(0xcb4b16), rgb(0xdc322f), rgb(0xd33682), rgb(0x6c71c4)],
+            heat: [rgb(0x268bd2), rgb(0x2aa198), rgb(0x859900), rgb(0xb58900), rgb(0xdc322f)],
+            turmite: rgb(0xcb4b16),
+            only_in_a: rgb(0xdc322f),
+            only_in_b: rgb(0x2aa198),
+            connected: rgb(0x859900),
+            disconnected: rgb(0xdc322f),
+            gradient: Some(((0xb5, 0x89, 0x00), (0x6c, 0x71, 0xc4))),
+            truecolor: false,
+        }
+    }
+    
+    /// Bright, saturated colors only, for low-vision use and washed-out
+    /// displays.
+    pub fn high_contrast() -> Self {
+        Self {
+            name: "high-contrast",
+            text: Color::White,
+            accent: Color::LightYellow,
+            selection: Color::Blue,
+            hint: Color::LightCyan,
+            muted: Color::Gray,
+            axis: Color::LightMagenta,
+            active_tab: (Color::Black, Color::LightYellow),
+            colonies: [Color::LightGreen, Color::LightRed, Color::LightCyan, Color::LightYellow],
+            decay: [Color::LightYellow, Color::LightMagenta, Color::LightRed, Color::LightBlue, Color::White],
+            heat: [Color::LightBlue, Color::LightCyan, Color::LightGreen, Color::LightYellow, Color::LightRed],
+            turmite: Color::LightMagenta,
+            only_in_a: Color::LightRed,
+            only_in_b: Color::LightCyan,
+            connected: Color::LightGreen,
+            disconnected: Color::LightRed,
+            gradient: None,
+            truecolor: false,
+        }
+    }
+    
+    /// Shades of gray only.
+    pub fn monochrome() -> Self {
+        Self {
+            name: "monochrome",
+            text: Color::White,
+            accent: Color::White,
+            selection: Color::DarkGray,
+            hint: Color::Gray,
+            muted: Color::DarkGray,
+            axis: Color::Gray,
+            active_tab: (Color::Black, Color::White),
+            colonies: [Color::White; 4],
+            decay: [Color::Gray, Color::Gray, Color::DarkGray, Color::DarkGray, Color::DarkGray],
+            heat: [Color::DarkGray, Color::DarkGray, Color::Gray, Color::Gray, Color::White],
+            turmite: Color::White,
+            only_in_a: Color::White,
+            only_in_b: Color::Gray,
+            connected: Color::White,
+            disconnected: Color::DarkGray,
+            gradient: Some(((200, 200, 200), (70, 70, 70))),
+            truecolor: false,
+        }
+    }
+    
+    /// Look up a theme by name, ignoring case.
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "classic" => Some(Self::classic()),
+            "solarized" => Some(Self::solarized()),
+            "high-contrast" | "high_contrast" | "contrast" => Some(Self::high_contrast()),
+            "monochrome" | "mono" => Some(Self::monochrome()),
+            _ => None,
+        }
+    }
+    
+    /// Fade decay states smoothly when the terminal shows 24-bit color.
+    pub fn with_truecolor(mut self, truecolor: bool) -> Self {
+        self.truecolor = truecolor;
+        self
+    }
+    
+    /// Color of a cell in decay state `state` (2 or more) when the highest
+    /// state on the grid is `max_state`.
+    pub fn decay_color(&self, state: i32, max_state: i32) -> Color {
+        match self.gradient.filter(|_| self.truecolor && max_state > 2) {
+            Some((from, to)) => {
+                let t = (state - 2) as f32 / (max_state - 2) as f32;
+                let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t.clamp(0.0, 1.0)).round() as u8;
+                Color::Rgb(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+            }
+            None => self.decay[((state - 2).max(0) as usize).min(self.decay.len() - 1)],
+        }
+    }
+    
+    /// Color of a heatmap cell alive `count` times when the most any cell
+    /// was alive is `max_count`: blended along `heat` on truecolor
+    /// terminals, otherwise its nearest entry.
+    pub fn heat_color(&self, count: i32, max_count: i32) -> Color {
+        let position = (count as f32 / max_count.max(1) as f32).clamp(0.0, 1.0) * (self.heat.len() - 1) as f32;
+        if !self.truecolor {
+            return self.heat[position.round() as usize];
+        }
+        let low = position.floor() as usize;
+        let high = (low + 1).min(self.heat.len() - 1);
+        self.fade(self.heat[low], self.heat[high], position - low as f32)
+    }
+    
+    /// Color a `progress` (0 to 1) of the way from `from` to `to`: a smooth
+    /// blend on truecolor terminals, otherwise a switch halfway.
+    pub fn fade(&self, from: Color, to: Color, progress: f32) -> Color {
+        if !self.truecolor {
+            return if progress < 0.5 { from } else { to };
+        }
+        let (from, to) = (to_rgb(from), to_rgb(to));
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * progress.clamp(0.0, 1.0)).round() as u8;
+        Color::Rgb(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+    }
+}
+
+/// The theme named by `--theme`, else by `GOL_THEME`, else classic, with
+/// gradients when the terminal supports them.
+pub fn configured_theme(flag: Option<&str>) -> Result<Theme, String> {
+    let name = flag.map(str::to_string)
+        .or_else(|| std::env::var("GOL_THEME").ok())
+        .unwrap_or_else(|| THEME_NAMES[0].to_string());
+    Theme::by_name(&name)
+        .map(|theme| theme.with_truecolor(truecolor_supported()))
+        .ok_or_else(|| format!("Unknown theme '{}' (expected one of {})", name, THEME_NAMES.join(", ")))
+}
+
+/// Whether the terminal advertises 24-bit color through `COLORTERM`.
+pub fn truecolor_supported() -> bool {
+    std::env::var("COLORTERM").is_ok_and(|value| value == "truecolor" || value == "24bit")
+}
+
+/// RGB value of a terminal color, for drawing it as pixels. Named colors
+/// use xterm's default palette.
+pub fn to_rgb(color: Color) -> Rgb {
+    match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(index) => indexed_rgb(index),
+        Color::Black => (0, 0, 0),
+        Color::Red => (205, 0, 0),
+        Color::Green => (0, 205, 0),
+        Color::Yellow => (205, 205, 0),
+        Color::Blue => (0, 0, 238),
+        Color::Magenta => (205, 0, 205),
+        Color::Cyan => (0, 205, 205),
+        Color::Gray => (229, 229, 229),
+        Color::DarkGray => (127, 127, 127),
+        Color::LightRed => (255, 0, 0),
+        Color::LightGreen => (0, 255, 0),
+        Color::LightYellow => (255, 255, 0),
+        Color::LightBlue => (92, 92, 255),
+        Color::LightMagenta => (255, 0, 255),
+        Color::LightCyan => (0, 255, 255),
+        Color::White | Color::Reset => (255, 255, 255),
+    }
+}
+
+/// RGB value of an entry in the xterm 256-color palette.
+fn indexed_rgb(index: u8) -> Rgb {
+    const NAMED: [Color; 16] = [
+        Color::Black, Color::Red, Color::Green, Color::Yellow, Color::Blue, Color::Magenta, Color::Cyan, Color::Gray,
+        Color::DarkGray, Color::LightRed, Color::LightGreen, Color::LightYellow, Color::LightBlue, Color::LightMagenta,
+        Color::LightCyan, Color::White,
+    ];
+    match index {
+        0..=15 => to_rgb(NAMED[index as usize]),
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let cube = index - 16;
+            (level(cube / 36), level(cube / 6 % 6), level(cube % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}